@@ -0,0 +1,354 @@
+// Find the document open in a running CAD application so it can be analyzed in one click
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Manager, State};
+
+use crate::jobs::{run_job, JobLogLevel};
+use crate::model_store::{load_model, ModelInfo, ModelStore};
+use crate::part_transforms::PartTransformStore;
+use crate::recent_files::RecentFilesState;
+
+/// Document names in window titles: anything ending in a CAD extension
+static DOCUMENT_NAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?i)([^\\/\[\]<>:"|?*\t]+?\.(?:sldprt|sldasm|ipt|iam|step|stp|prt|asm|catpart|catproduct|fcstd))\b"#).unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CadApplication {
+    Solidworks,
+    Inventor,
+    Creo,
+    Catia,
+    Freecad,
+    Unknown,
+}
+
+impl CadApplication {
+    fn from_window(process: &str, title: &str) -> CadApplication {
+        let text = format!("{} {}", process, title).to_lowercase();
+        if text.contains("sldworks") || text.contains("solidworks") {
+            CadApplication::Solidworks
+        } else if text.contains("inventor") {
+            CadApplication::Inventor
+        } else if text.contains("xtop") || text.contains("creo") {
+            CadApplication::Creo
+        } else if text.contains("cnext") || text.contains("catia") {
+            CadApplication::Catia
+        } else if text.contains("freecad") {
+            CadApplication::Freecad
+        } else {
+            CadApplication::Unknown
+        }
+    }
+}
+
+/// How the document was found
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentSource {
+    Automation,     // Asked the application over COM; the path is exact
+    WindowTitle,    // Read from a window title; only the file name is known
+}
+
+/// The document open in a CAD application
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveCadDocument {
+    pub application: CadApplication,
+    pub name: String,
+    pub path: Option<String>,   // None when only the window title was available
+    pub source: DocumentSource,
+}
+
+/// Result of analyzing the active document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveDocumentAnalysis {
+    pub document: ActiveCadDocument,
+    pub step_path: String,      // STEP file that was loaded (the document itself, a sibling or an export)
+    pub model: ModelInfo,
+}
+
+/// Run a helper program and return its output, or None if it is missing or failed
+fn capture(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+    let output = command.output().ok()?;
+    output.status.success().then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(windows)]
+fn powershell(script: &str) -> Option<String> {
+    capture("powershell.exe", &["-NoProfile", "-NonInteractive", "-Command", script])
+}
+
+/// Single-quoted PowerShell string literal
+#[cfg(windows)]
+fn ps_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Ask SolidWorks and Inventor for their active document over COM
+#[cfg(windows)]
+fn automation_document() -> Option<ActiveCadDocument> {
+    const QUERY: &str = r#"
+        $ErrorActionPreference = 'SilentlyContinue'
+        try {
+            $sw = [Runtime.InteropServices.Marshal]::GetActiveObject('SldWorks.Application')
+            if ($sw.ActiveDoc) { "solidworks`t" + $sw.ActiveDoc.GetPathName() + "`t" + $sw.ActiveDoc.GetTitle() }
+        } catch {}
+        try {
+            $inv = [Runtime.InteropServices.Marshal]::GetActiveObject('Inventor.Application')
+            if ($inv.ActiveDocument) { "inventor`t" + $inv.ActiveDocument.FullFileName + "`t" + $inv.ActiveDocument.DisplayName }
+        } catch {}
+    "#;
+    let output = powershell(QUERY)?;
+    output.lines().find_map(|line| {
+        let mut fields = line.trim().splitn(3, '\t');
+        let application = match fields.next()? {
+            "solidworks" => CadApplication::Solidworks,
+            "inventor" => CadApplication::Inventor,
+            _ => return None,
+        };
+        // Unsaved documents have no path yet
+        let path = fields.next().map(str::trim).filter(|p| !p.is_empty()).map(str::to_string);
+        let name = path.as_deref()
+            .and_then(|p| Path::new(p).file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .or_else(|| fields.next().map(|t| t.trim().to_string()))?;
+        Some(ActiveCadDocument { application, name, path, source: DocumentSource::Automation })
+    })
+}
+
+#[cfg(not(windows))]
+fn automation_document() -> Option<ActiveCadDocument> {
+    None
+}
+
+/// Have the application save a STEP copy of its active document
+#[cfg(windows)]
+fn export_step_copy(document: &ActiveCadDocument, target: &Path) -> Result<(), String> {
+    let target = ps_quote(&target.to_string_lossy());
+    let script = match document.application {
+        CadApplication::Solidworks => format!(
+            "$sw = [Runtime.InteropServices.Marshal]::GetActiveObject('SldWorks.Application'); \
+             if ($sw.ActiveDoc.SaveAs3({}, 0, 2) -ne 0) {{ exit 1 }}",
+            target
+        ),
+        CadApplication::Inventor => format!(
+            "$inv = [Runtime.InteropServices.Marshal]::GetActiveObject('Inventor.Application'); \
+             $inv.ActiveDocument.SaveAs({}, $true)",
+            target
+        ),
+        _ => return Err("STEP export is only automated for SolidWorks and Inventor".to_string()),
+    };
+    powershell(&script).map(|_| ()).ok_or_else(|| "The CAD application refused to export STEP".to_string())
+}
+
+#[cfg(not(windows))]
+fn export_step_copy(_document: &ActiveCadDocument, _target: &Path) -> Result<(), String> {
+    Err("STEP export from a running CAD application is only available on Windows".to_string())
+}
+
+/// (process, title) of visible top-level windows
+#[cfg(windows)]
+fn window_titles() -> Vec<(String, String)> {
+    let output = powershell(
+        "Get-Process | Where-Object { $_.MainWindowTitle } | ForEach-Object { $_.ProcessName + \"`t\" + $_.MainWindowTitle }",
+    );
+    output.unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(process, title)| (process.trim().to_string(), title.trim().to_string()))
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+fn window_titles() -> Vec<(String, String)> {
+    const SCRIPT: &str = r#"
+        tell application "System Events"
+            set out to ""
+            repeat with p in (processes whose background only is false)
+                try
+                    set out to out & name of p & tab & name of window 1 of p & linefeed
+                end try
+            end repeat
+            return out
+        end tell
+    "#;
+    capture("osascript", &["-e", SCRIPT])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(process, title)| (process.trim().to_string(), title.trim().to_string()))
+        .collect()
+}
+
+/// Needs wmctrl; Wayland sessions without it report nothing
+#[cfg(all(unix, not(target_os = "macos")))]
+fn window_titles() -> Vec<(String, String)> {
+    // "0x04a00003  0 freecad.FreeCAD  host  Bracket.FCStd - FreeCAD 0.21"
+    capture("wmctrl", &["-lx"])
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            (fields.len() > 4).then(|| (fields[2].to_string(), fields[4..].join(" ")))
+        })
+        .collect()
+}
+
+/// Document named in a CAD application's window title
+pub fn parse_window_title(process: &str, title: &str) -> Option<ActiveCadDocument> {
+    let application = CadApplication::from_window(process, title);
+    let name = DOCUMENT_NAME.captures(title)?.get(1)?.as_str().trim().to_string();
+    // Any window can show a file name; only trust CAD applications unless it is a STEP file
+    if application == CadApplication::Unknown && !is_step(&name) {
+        return None;
+    }
+    Some(ActiveCadDocument { application, name, path: None, source: DocumentSource::WindowTitle })
+}
+
+fn is_step(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.ends_with(".step") || lower.ends_with(".stp")
+}
+
+/// The document open in a running CAD application, preferring exact COM answers over window titles
+pub fn detect_active_document() -> Option<ActiveCadDocument> {
+    if let Some(document) = automation_document() {
+        return Some(document);
+    }
+    let windows = window_titles();
+    windows.iter()
+        .filter_map(|(process, title)| parse_window_title(process, title))
+        .min_by_key(|document| document.application == CadApplication::Unknown)
+}
+
+/// STEP file next to a native document, e.g. Bracket.step beside Bracket.SLDPRT
+fn sibling_step(path: &Path) -> Option<PathBuf> {
+    ["step", "stp", "STEP", "STP"].iter()
+        .map(|ext| path.with_extension(ext))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Turn a detected document into a STEP file on disk
+pub fn resolve_step_path(
+    document: &ActiveCadDocument,
+    recent: Option<&RecentFilesState>,
+) -> Result<String, String> {
+    // Window titles only give a name; the recent files list usually knows where it lives
+    let path = document.path.clone().or_else(|| {
+        let stem = Path::new(&document.name).file_stem()?.to_string_lossy().into_owned();
+        let recent = recent?;
+        std::iter::once(document.name.clone())
+            .chain(["step", "stp"].iter().map(|ext| format!("{}.{}", stem, ext)))
+            .find_map(|name| recent.find_by_filename(&name))
+            .map(|entry| entry.path)
+    });
+    let path = path.ok_or_else(|| {
+        format!("{} is open but its location is unknown; open it once in Copilot first", document.name)
+    })?;
+
+    if is_step(&path) && Path::new(&path).is_file() {
+        return Ok(path);
+    }
+
+    // A fresh export reflects unsaved edits, so it wins over a STEP file already on disk
+    if document.source == DocumentSource::Automation {
+        let stem = Path::new(&path).file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let dir = std::env::temp_dir().join("ohmframe-active-document");
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create export folder: {}", e))?;
+        let target = dir.join(format!("{}.step", stem));
+        match export_step_copy(document, &target) {
+            Ok(()) if target.is_file() => return Ok(target.to_string_lossy().into_owned()),
+            Ok(()) => tracing::warn!(document = %document.name, "STEP export produced no file"),
+            Err(e) => tracing::warn!(document = %document.name, error = %e, "STEP export failed"),
+        }
+    }
+
+    sibling_step(Path::new(&path))
+        .map(|p| p.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("No STEP copy of {} was found; export it as STEP next to the part", document.name))
+}
+
+/// Detect the active document and the STEP file to analyze for it
+pub fn active_step_file(app: &AppHandle) -> Result<(ActiveCadDocument, String), String> {
+    let document = detect_active_document().ok_or("No open CAD document was found")?;
+    let recent = app.try_state::<RecentFilesState>();
+    let step_path = resolve_step_path(&document, recent.as_deref())?;
+    Ok((document, step_path))
+}
+
+/// Report the document open in a running CAD application
+#[tauri::command]
+pub async fn detect_active_cad_document() -> Result<Option<ActiveCadDocument>, String> {
+    let _metrics = crate::metrics::track("detect_active_cad_document");
+    crate::run_blocking(detect_active_document).await
+}
+
+/// Load the part the user is looking at in their CAD application
+#[tauri::command]
+pub async fn analyze_active_cad_document(
+    app: AppHandle,
+    state: State<'_, ModelStore>,
+    transforms: State<'_, PartTransformStore>,
+    recent: State<'_, RecentFilesState>,
+    job_id: Option<String>,
+) -> Result<ActiveDocumentAnalysis, String> {
+    let handle = app.clone();
+    let (document, step_path) = run_job(app, "analyze_active_cad_document", job_id, None, move |job| {
+        let (document, step_path) = active_step_file(&handle)?;
+        job.log(JobLogLevel::Info, format!("Analyzing {} via {}", document.name, step_path));
+        Ok((document, step_path))
+    })
+    .await?;
+
+    tracing::info!(document = %document.name, step_path = %step_path, "analyzing active CAD document");
+    let model = load_model(state, transforms, recent, None, Some(step_path.clone()), Some(document.name.clone()))?;
+    Ok(ActiveDocumentAnalysis { document, step_path, model })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_titles_and_step_resolution() {
+        let sw = parse_window_title("SLDWORKS", "SOLIDWORKS Premium 2023 SP1.0 - [Bracket.SLDPRT *]").unwrap();
+        assert_eq!(sw.application, CadApplication::Solidworks);
+        assert_eq!(sw.name, "Bracket.SLDPRT");
+        let inv = parse_window_title("Inventor", "Autodesk Inventor Professional 2024 - [Housing.iam]").unwrap();
+        assert_eq!((inv.application, inv.name.as_str()), (CadApplication::Inventor, "Housing.iam"));
+        assert!(parse_window_title("notepad", "notes.prt - Notepad").is_none());
+        assert!(parse_window_title("SLDWORKS", "SOLIDWORKS Premium 2023 - [Part1 *]").is_none());
+        assert!(parse_window_title("explorer", "C:\\parts\\shaft.step").is_some());
+
+        let dir = std::env::temp_dir().join(format!("ohmframe-active-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let native = dir.join("Bracket.SLDPRT");
+        std::fs::write(&native, b"native").unwrap();
+        let document = ActiveCadDocument {
+            application: CadApplication::Solidworks,
+            name: "Bracket.SLDPRT".to_string(),
+            path: Some(native.to_string_lossy().into_owned()),
+            source: DocumentSource::WindowTitle,
+        };
+        assert!(resolve_step_path(&document, None).is_err());
+        std::fs::write(dir.join("Bracket.step"), b"ISO-10303-21;").unwrap();
+        assert!(resolve_step_path(&document, None).unwrap().ends_with("Bracket.step"));
+
+        let unlocated = ActiveCadDocument { path: None, ..document };
+        assert!(resolve_step_path(&unlocated, None).unwrap_err().contains("location is unknown"));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
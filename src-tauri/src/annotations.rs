@@ -0,0 +1,332 @@
+// Notes, tags and flagged issues attached to parts, faces and interfaces
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::assembly_parser::{ParsedFace, ParsedPart};
+use crate::model_store::{LoadedModel, ModelStore};
+use crate::recent_files::hash_content;
+use crate::storage::{load_json, save_json, unix_timestamp};
+
+/// Suffix of the file saved next to a model that was opened from disk
+const SIDECAR_SUFFIX: &str = "notes.json";
+
+/// What an annotation is attached to, by geometric key (survives re-export and renumbering)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnnotationTarget {
+    Part { key: String },
+    Face { key: String },
+    Interface { key: String },
+}
+
+/// Target as the frontend refers to it in the current session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnnotationTargetRef {
+    Part { part_id: String },
+    Face { part_id: String, face_id: i64 },
+    Interface { interface_id: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    Note,
+    Issue,
+}
+
+/// A persisted annotation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: String,
+    pub target: AnnotationTarget,
+    pub kind: AnnotationKind,
+    pub text: String,
+    pub tags: Vec<String>,
+    pub severity: Option<String>,  // Issues only: "low", "medium", "high" or a team's own scale
+    pub resolved: bool,
+    pub author: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+/// Annotation with its target resolved against the loaded model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedAnnotation {
+    #[serde(flatten)]
+    pub annotation: Annotation,
+    pub location: Option<AnnotationTargetRef>, // None when the geometry no longer exists
+}
+
+/// A note or issue to attach
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAnnotation {
+    pub target: AnnotationTargetRef,
+    pub kind: AnnotationKind,
+    pub text: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub severity: Option<String>,
+    pub author: Option<String>,
+}
+
+/// Fields to change on an existing annotation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnnotationUpdate {
+    pub text: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub severity: Option<String>,
+    pub resolved: Option<bool>,
+}
+
+/// Managed state; annotations live in a file next to the model, or in app data for unsaved content
+pub struct AnnotationStore {
+    dir: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl AnnotationStore {
+    pub fn new(dir: PathBuf) -> Self {
+        AnnotationStore { dir, lock: Mutex::new(()) }
+    }
+
+    fn file_for(&self, model: &LoadedModel) -> PathBuf {
+        match &model.path {
+            Some(path) => PathBuf::from(format!("{}.{}", path, SIDECAR_SUFFIX)),
+            None => self.dir.join(format!("{}.json", model.content_hash)),
+        }
+    }
+
+    /// Load, change and save the annotations for a model under one lock
+    fn update<R>(&self, file: &Path, f: impl FnOnce(&mut Vec<Annotation>) -> Result<R, String>) -> Result<R, String> {
+        let _guard = self.lock.lock().map_err(|_| "Annotation store poisoned".to_string())?;
+        let mut annotations: Vec<Annotation> = load_json(file);
+        let result = f(&mut annotations)?;
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        save_json(file, &annotations)?;
+        Ok(result)
+    }
+
+    fn load(&self, file: &Path) -> Result<Vec<Annotation>, String> {
+        let _guard = self.lock.lock().map_err(|_| "Annotation store poisoned".to_string())?;
+        Ok(load_json(file))
+    }
+}
+
+/// Part name plus occurrence among same-named parts; entity ids change on every export
+fn part_key(model: &LoadedModel, part: &ParsedPart) -> String {
+    let occurrence = model.assembly.parts.iter()
+        .take_while(|p| p.id != part.id)
+        .filter(|p| p.name == part.name)
+        .count();
+    format!("{}#{}", part.name, occurrence)
+}
+
+/// Part key plus the face's rounded local geometry
+fn face_key(part_key: &str, face: &ParsedFace) -> String {
+    let round = |v: &[f64; 3], step: f64| v.map(|c| ((c / step).round() * step + 0.0).to_string()).join(",");
+    let signature = format!(
+        "{}|{}|{}|{}|{}",
+        part_key,
+        face.face_type,
+        round(&face.center, 1e-2),
+        round(&face.normal, 1e-3),
+        face.radius.map(|r| format!("{:.3}", r)).unwrap_or_default(),
+    );
+    format!("{}:{}", face.face_type, &hash_content(signature.as_bytes())[..16])
+}
+
+fn find_part<'a>(model: &'a LoadedModel, part_id: &str) -> Result<&'a ParsedPart, String> {
+    model.assembly.parts.iter()
+        .find(|p| p.id == part_id)
+        .ok_or_else(|| format!("Unknown part: {}", part_id))
+}
+
+fn face_key_by_id(model: &LoadedModel, part_id: &str, face_id: i64) -> Result<String, String> {
+    let part = find_part(model, part_id)?;
+    let face = part.faces.iter()
+        .find(|f| f.id == face_id)
+        .ok_or_else(|| format!("Unknown face {} on part {}", face_id, part_id))?;
+    Ok(face_key(&part_key(model, part), face))
+}
+
+/// Stable key for a session reference
+fn resolve_target(model: &LoadedModel, target: &AnnotationTargetRef) -> Result<AnnotationTarget, String> {
+    Ok(match target {
+        AnnotationTargetRef::Part { part_id } => AnnotationTarget::Part { key: part_key(model, find_part(model, part_id)?) },
+        AnnotationTargetRef::Face { part_id, face_id } => AnnotationTarget::Face { key: face_key_by_id(model, part_id, *face_id)? },
+        AnnotationTargetRef::Interface { interface_id } => {
+            let interface = model.interfaces.as_ref()
+                .and_then(|r| r.interfaces.iter().find(|i| &i.id == interface_id))
+                .ok_or_else(|| format!("Unknown interface: {}", interface_id))?;
+            let mut keys = [
+                face_key_by_id(model, &interface.part_a_id, interface.part_a_face_id)?,
+                face_key_by_id(model, &interface.part_b_id, interface.part_b_face_id)?,
+            ];
+            keys.sort();
+            AnnotationTarget::Interface { key: keys.join("+") }
+        }
+    })
+}
+
+/// Where a stored key is in the current session, if it still exists
+fn locate_target(model: &LoadedModel, target: &AnnotationTarget) -> Option<AnnotationTargetRef> {
+    let parts = model.assembly.parts.iter().map(|p| AnnotationTargetRef::Part { part_id: p.id.clone() });
+    let faces = model.assembly.parts.iter().flat_map(|p| {
+        p.faces.iter().map(|f| AnnotationTargetRef::Face { part_id: p.id.clone(), face_id: f.id })
+    });
+    let interfaces = model.interfaces.iter().flat_map(|r| {
+        r.interfaces.iter().map(|i| AnnotationTargetRef::Interface { interface_id: i.id.clone() })
+    });
+    parts.chain(faces).chain(interfaces).find(|r| resolve_target(model, r).ok().as_ref() == Some(target))
+}
+
+fn resolve_all(model: &LoadedModel, annotations: Vec<Annotation>) -> Vec<ResolvedAnnotation> {
+    annotations.into_iter()
+        .map(|annotation| ResolvedAnnotation { location: locate_target(model, &annotation.target), annotation })
+        .collect()
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut tags: Vec<String> = tags.into_iter()
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Attach a note or flagged issue to a part, face or interface of a loaded model
+#[tauri::command]
+pub fn add_annotation(
+    state: State<'_, ModelStore>,
+    store: State<'_, AnnotationStore>,
+    handle: String,
+    annotation: NewAnnotation,
+) -> Result<ResolvedAnnotation, String> {
+    let NewAnnotation { target, kind, text, tags, severity, author } = annotation;
+    let (stable, file) = state.with_model(&handle, |model| {
+        Ok::<_, String>((resolve_target(model, &target)?, store.file_for(model)))
+    })??;
+
+    let now = unix_timestamp();
+    let annotation = store.update(&file, |annotations| {
+        let next = annotations.iter()
+            .filter_map(|a| a.id.strip_prefix("note-").and_then(|n| n.parse::<u64>().ok()))
+            .max()
+            .unwrap_or(0) + 1;
+        let annotation = Annotation {
+            id: format!("note-{}", next),
+            target: stable,
+            kind,
+            text,
+            tags: normalize_tags(tags),
+            severity: if kind == AnnotationKind::Issue { severity } else { None },
+            resolved: false,
+            author,
+            created_at: now,
+            updated_at: now,
+        };
+        annotations.push(annotation.clone());
+        Ok(annotation)
+    })?;
+
+    tracing::info!(handle = %handle, id = %annotation.id, "annotation added");
+    Ok(ResolvedAnnotation { annotation, location: Some(target) })
+}
+
+/// Edit text, tags, severity or resolution of an annotation
+#[tauri::command]
+pub fn update_annotation(
+    state: State<'_, ModelStore>,
+    store: State<'_, AnnotationStore>,
+    handle: String,
+    id: String,
+    update: AnnotationUpdate,
+) -> Result<ResolvedAnnotation, String> {
+    let file = state.with_model(&handle, |model| store.file_for(model))?;
+    let annotation = store.update(&file, |annotations| {
+        let annotation = annotations.iter_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| format!("Unknown annotation: {}", id))?;
+        if let Some(text) = update.text {
+            annotation.text = text;
+        }
+        if let Some(tags) = update.tags {
+            annotation.tags = normalize_tags(tags);
+        }
+        if update.severity.is_some() && annotation.kind == AnnotationKind::Issue {
+            annotation.severity = update.severity;
+        }
+        if let Some(resolved) = update.resolved {
+            annotation.resolved = resolved;
+        }
+        annotation.updated_at = unix_timestamp();
+        Ok(annotation.clone())
+    })?;
+
+    state.with_model(&handle, |model| ResolvedAnnotation { location: locate_target(model, &annotation.target), annotation })
+}
+
+/// Delete an annotation; returns whether it existed
+#[tauri::command]
+pub fn delete_annotation(
+    state: State<'_, ModelStore>,
+    store: State<'_, AnnotationStore>,
+    handle: String,
+    id: String,
+) -> Result<bool, String> {
+    let file = state.with_model(&handle, |model| store.file_for(model))?;
+    store.update(&file, |annotations| {
+        let before = annotations.len();
+        annotations.retain(|a| a.id != id);
+        Ok(annotations.len() != before)
+    })
+}
+
+/// Annotations for a loaded model, optionally only those with a tag or of one kind
+#[tauri::command]
+pub fn list_annotations(
+    state: State<'_, ModelStore>,
+    store: State<'_, AnnotationStore>,
+    handle: String,
+    tag: Option<String>,
+    kind: Option<AnnotationKind>,
+) -> Result<Vec<ResolvedAnnotation>, String> {
+    let file = state.with_model(&handle, |model| store.file_for(model))?;
+    let tag = tag.map(|t| t.trim().to_lowercase());
+    let annotations: Vec<Annotation> = store.load(&file)?
+        .into_iter()
+        .filter(|a| tag.as_ref().map(|t| a.tags.contains(t)).unwrap_or(true))
+        .filter(|a| kind.map(|k| a.kind == k).unwrap_or(true))
+        .collect();
+    state.with_model(&handle, |model| resolve_all(model, annotations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::face;
+
+    #[test]
+    fn test_face_keys_survive_renumbering() {
+        let planar = |id, center| ParsedFace { area: 1.0, step_entity_id: Some(id), ..face(id, "planar", center, [0.0, 0.0, 1.0], None) };
+        let original = planar(12, [10.0, 5.0, 2.0]);
+        let reexported = planar(340, [10.0000001, 5.0, 2.0]);
+        let other = planar(13, [10.0, 5.0, 3.0]);
+
+        assert_eq!(face_key("bracket#0", &original), face_key("bracket#0", &reexported));
+        assert_ne!(face_key("bracket#0", &original), face_key("bracket#0", &other));
+        assert_ne!(face_key("bracket#0", &original), face_key("bracket#1", &original));
+        assert!(face_key("bracket#0", &original).starts_with("planar:"));
+
+        assert_eq!(normalize_tags(vec![" Review ".into(), "review".into(), "".into(), "DFM".into()]), vec!["dfm", "review"]);
+    }
+}
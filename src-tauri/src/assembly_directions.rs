@@ -0,0 +1,236 @@
+// Assembly direction feasibility: the cone of translations each part can be inserted along, given its mating contacts
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_sequence::AssemblySequence;
+use crate::interface_detection::DetectedInterface;
+use crate::linalg::{add, cross, dot, norm, normalize, scale, Vec3};
+use crate::model_store::{FaceRef, LoadedModel, ModelStore, WorldFace};
+use crate::validation::Checked;
+
+/// Unit directions sampled on the sphere when searching the feasible cone
+const SPHERE_SAMPLES: usize = 2048;
+const ANGLE_TOLERANCE: f64 = 1e-6;
+
+/// Motion limit one contact puts on a part
+#[derive(Debug, Clone)]
+pub enum Contact {
+    /// The part may not move into its mate: `normal` points out of the part at the contact
+    Face { normal: Vec3 },
+    /// The part can only slide along a pin or bore axis
+    Axis { axis: Vec3 },
+}
+
+/// Insertion directions left open by a set of contacts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectionCone {
+    pub feasible: bool,
+    pub directions: Vec<Vec3>,             // Feasible among the world axes and the contacts' own free directions
+    pub cone_axis: Option<Vec3>,           // Middle of the feasible set; None when it covers the whole sphere
+    pub cone_half_angle_deg: Option<f64>,  // 0 for slides along a pin axis
+}
+
+/// Directions for one part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartDirections {
+    pub part_id: String,
+    pub interfaces: Vec<String>,  // Contacts that constrain the part
+    #[serde(flatten)]
+    pub cone: DirectionCone,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblyDirectionResult {
+    pub sequenced: bool,                  // Only mates placed earlier constrain a part; otherwise all mates do
+    pub parts: Vec<PartDirections>,
+    pub infeasible_parts: Vec<String>,
+    pub ignored_interfaces: Vec<String>,  // Types that say nothing about direction ("unknown")
+}
+
+/// Evenly spread unit vectors (Fibonacci sphere)
+fn sphere_samples(count: usize) -> impl Iterator<Item = Vec3> {
+    let golden = std::f64::consts::PI * (3.0 - 5f64.sqrt());
+    (0..count).map(move |i| {
+        let z = 1.0 - 2.0 * (i as f64 + 0.5) / count as f64;
+        let r = (1.0 - z * z).sqrt();
+        let phi = golden * i as f64;
+        [r * phi.cos(), r * phi.sin(), z]
+    })
+}
+
+fn allows(contacts: &[Contact], d: &Vec3) -> bool {
+    contacts.iter().all(|contact| match contact {
+        Contact::Face { normal } => dot(d, &normalize(normal)) <= ANGLE_TOLERANCE,
+        Contact::Axis { axis } => norm(&cross(d, &normalize(axis))) <= ANGLE_TOLERANCE,
+    })
+}
+
+/// Feasible translation directions: the intersection of the contacts' half-spaces, cut to axis lines by pins
+pub fn feasible_directions(contacts: &[Contact]) -> DirectionCone {
+    let mut candidates: Vec<Vec3> = vec![
+        [1.0, 0.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, -1.0],
+    ];
+    for contact in contacts {
+        match contact {
+            Contact::Face { normal } => candidates.push(scale(&normalize(normal), -1.0)),
+            Contact::Axis { axis } => {
+                candidates.push(normalize(axis));
+                candidates.push(scale(&normalize(axis), -1.0));
+            }
+        }
+    }
+    let mut directions: Vec<Vec3> = Vec::new();
+    for candidate in candidates.into_iter().filter(|d| allows(contacts, d)) {
+        if !directions.iter().any(|d| dot(d, &candidate) > 1.0 - 1e-9) {
+            directions.push(candidate);
+        }
+    }
+
+    let sliding = contacts.iter().any(|c| matches!(c, Contact::Axis { .. }));
+    let (cone_axis, cone_half_angle_deg) = if sliding {
+        (directions.first().copied(), directions.first().map(|_| 0.0))
+    } else {
+        let feasible: Vec<Vec3> = sphere_samples(SPHERE_SAMPLES).filter(|d| allows(contacts, d)).chain(directions.iter().copied()).collect();
+        let sum = feasible.iter().fold([0.0; 3], |s, d| add(&s, d));
+        if feasible.is_empty() {
+            (None, None)
+        } else if norm(&sum) < 1e-3 * feasible.len() as f64 {
+            (None, Some(180.0))
+        } else {
+            let axis = normalize(&sum);
+            let half_angle = feasible.iter().map(|d| dot(d, &axis).clamp(-1.0, 1.0).acos()).fold(0.0, f64::max);
+            (Some(axis), Some(half_angle.to_degrees()))
+        }
+    };
+
+    DirectionCone { feasible: !directions.is_empty() || cone_axis.is_some() || cone_half_angle_deg.is_some(), directions, cone_axis, cone_half_angle_deg }
+}
+
+/// Planar face normal pointing out of its part
+fn outward(world: &WorldFace) -> Vec3 {
+    let n = normalize(&world.normal);
+    if world.face.same_sense == Some(false) { scale(&n, -1.0) } else { n }
+}
+
+/// Contact an interface puts on `part_id`, or None when its type says nothing about direction
+fn contact_for(model: &LoadedModel, interface: &DetectedInterface, part_id: &str) -> Result<Option<Contact>, String> {
+    let a = FaceRef { part_id: interface.part_a_id.clone(), face_id: interface.part_a_face_id };
+    let b = FaceRef { part_id: interface.part_b_id.clone(), face_id: interface.part_b_face_id };
+    let (own, other) = if interface.part_a_id == part_id { (a, b) } else { (b, a) };
+    let (own, other) = (model.world_face(&own)?, model.world_face(&other)?);
+
+    Ok(match interface.interface_type.as_str() {
+        "pin_in_hole" => own.axis.or(other.axis).map(|axis| Contact::Axis { axis }),
+        "face_to_face" | "shaft_in_bore" | "weld" | "bond" => {
+            if own.face.face_type == "planar" {
+                Some(Contact::Face { normal: outward(&own) })
+            } else if other.face.face_type == "planar" {
+                Some(Contact::Face { normal: scale(&outward(&other), -1.0) })
+            } else {
+                None
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Feasible directions of every part; with a sequence, each part only against the parts placed before it
+pub fn assembly_directions(
+    model: &LoadedModel,
+    interfaces: &[DetectedInterface],
+    sequence: Option<&AssemblySequence>,
+) -> Result<AssemblyDirectionResult, String> {
+    // Parts to check, each with the parts that may constrain it
+    let checks: Vec<(String, Option<Vec<String>>)> = match sequence {
+        Some(sequence) => sequence.steps.iter().enumerate()
+            .map(|(i, step)| {
+                let earlier = std::iter::once(sequence.base_part_id.clone())
+                    .chain(sequence.steps[..i].iter().map(|s| s.part_id.clone()))
+                    .collect();
+                (step.part_id.clone(), Some(earlier))
+            })
+            .collect(),
+        None => model.assembly.parts.iter().map(|p| (p.id.clone(), None)).collect(),
+    };
+
+    let mut ignored = Vec::new();
+    let mut parts = Vec::new();
+    for (part_id, earlier) in checks {
+        let mut contacts = Vec::new();
+        let mut constraining = Vec::new();
+        for interface in interfaces {
+            let other = if interface.part_a_id == part_id {
+                &interface.part_b_id
+            } else if interface.part_b_id == part_id {
+                &interface.part_a_id
+            } else {
+                continue;
+            };
+            if earlier.as_ref().is_some_and(|e| !e.contains(other)) {
+                continue;
+            }
+            match contact_for(model, interface, &part_id)? {
+                Some(contact) => {
+                    constraining.push(interface.id.clone());
+                    contacts.push(contact);
+                }
+                None if !ignored.contains(&interface.id) => ignored.push(interface.id.clone()),
+                None => {}
+            }
+        }
+        parts.push(PartDirections { part_id, interfaces: constraining, cone: feasible_directions(&contacts) });
+    }
+
+    let infeasible_parts: Vec<String> = parts.iter().filter(|p| !p.cone.feasible).map(|p| p.part_id.clone()).collect();
+    tracing::info!(parts = parts.len(), infeasible = infeasible_parts.len(), sequenced = sequence.is_some(), "assembly directions checked");
+    Ok(AssemblyDirectionResult { sequenced: sequence.is_some(), parts, infeasible_parts, ignored_interfaces: ignored })
+}
+
+/// Insertion direction cones per part of a loaded model, flagging parts that cannot be assembled
+#[tauri::command]
+pub fn check_assembly_directions(
+    state: State<'_, ModelStore>,
+    handle: String,
+    sequence: Option<Checked<AssemblySequence>>,
+) -> Result<AssemblyDirectionResult, String> {
+    state.with_model(&handle, |model| {
+        let interfaces = model.interfaces.as_ref()
+            .map(|r| r.interfaces.as_slice())
+            .ok_or("Detect interfaces on the model first")?;
+        assembly_directions(model, interfaces, sequence.as_deref())
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn face(normal: Vec3) -> Contact {
+        Contact::Face { normal }
+    }
+
+    #[test]
+    fn test_direction_cones() {
+        // Block in a corner: free in the quarter sphere away from both walls
+        let corner = feasible_directions(&[face([0.0, 0.0, -1.0]), face([-1.0, 0.0, 0.0])]);
+        assert!(corner.feasible);
+        let axis = corner.cone_axis.unwrap();
+        assert!((axis[0] - axis[2]).abs() < 0.02 && axis[0] > 0.6 && axis[1].abs() < 0.02);
+        assert!((corner.cone_half_angle_deg.unwrap() - 90.0).abs() < 0.5);
+
+        // Peg in a hole through a base plate: straight up only
+        let peg = feasible_directions(&[face([0.0, 0.0, -1.0]), Contact::Axis { axis: [0.0, 0.0, 1.0] }]);
+        assert_eq!(peg.directions, vec![[0.0, 0.0, 1.0]]);
+        assert_eq!(peg.cone_half_angle_deg, Some(0.0));
+
+        // Pin captured between two plates cannot go anywhere
+        let captured = feasible_directions(&[
+            face([0.0, 0.0, -1.0]),
+            face([0.0, 0.0, 1.0]),
+            Contact::Axis { axis: [0.0, 0.0, 1.0] },
+        ]);
+        assert!(!captured.feasible);
+        assert!(captured.directions.is_empty() && captured.cone_axis.is_none());
+    }
+}
@@ -1,11 +1,16 @@
 // Assembly STEP parsing for tolerance stackup mode
 
-use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_2, PI};
+use tauri::AppHandle;
+
+use crate::jobs::run_job;
+use crate::step_entities::{StepEntities, StepEntity};
 
 /// Result of assembly parsing
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AssemblyParseResult {
     pub success: bool,
     pub error: Option<String>,
@@ -46,25 +51,132 @@ pub struct ParsedFace {
     pub radius: Option<f64>,
     pub axis: Option<[f64; 3]>,
     pub step_entity_id: Option<i64>,
+    #[serde(default)]
+    pub same_sense: Option<bool>,  // False when the face normal opposes the surface normal (e.g. holes)
+    #[serde(default)]
+    pub semi_angle: Option<f64>,     // Conical: half-angle in degrees; `radius` is taken in the placement plane
+    #[serde(default)]
+    pub apex: Option<[f64; 3]>,      // Conical: apex position
+    #[serde(default)]
+    pub minor_radius: Option<f64>,   // Toroidal: tube radius; `radius` holds the major radius
+}
+
+/// Part without its faces, listed first so large assemblies stay responsive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartSummary {
+    pub id: String,
+    pub name: String,
+    pub step_entity_id: i64,
+    pub transform: [f64; 16],
+    pub bounding_box: Option<PartBoundingBox>,
+    pub product_definition_id: Option<i64>,
+    pub face_count: usize,
 }
 
-/// STEP entity reference
-#[derive(Debug, Clone)]
-struct StepEntity {
-    id: i64,
-    entity_type: String,
-    data: String,
+impl From<&ParsedPart> for PartSummary {
+    fn from(part: &ParsedPart) -> Self {
+        PartSummary {
+            id: part.id.clone(),
+            name: part.name.clone(),
+            step_entity_id: part.step_entity_id,
+            transform: part.transform,
+            bounding_box: part.bounding_box.clone(),
+            product_definition_id: part.product_definition_id,
+            face_count: part.faces.len(),
+        }
+    }
+}
+
+/// One page of an assembly's parts, faces fetched separately
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblySummary {
+    pub success: bool,
+    pub error: Option<String>,
+    pub filename: Option<String>,
+    pub parts: Vec<PartSummary>,
+    pub offset: usize,
+    pub total_parts: usize,
+    pub has_sub_assemblies: bool,
+}
+
+/// One page of a part's faces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FacePage {
+    pub part_id: String,
+    pub faces: Vec<ParsedFace>,
+    pub offset: usize,
+    pub total_faces: usize,
+}
+
+/// Items `offset..offset + limit`, empty past the end
+pub fn page<T>(items: &[T], offset: usize, limit: usize) -> &[T] {
+    let start = offset.min(items.len());
+    &items[start..start.saturating_add(limit).min(items.len())]
+}
+
+impl AssemblyParseResult {
+    /// Part summaries from `offset`, at most `limit` of them
+    pub fn summary(&self, offset: usize, limit: usize) -> AssemblySummary {
+        AssemblySummary {
+            success: self.success,
+            error: self.error.clone(),
+            filename: self.filename.clone(),
+            parts: page(&self.parts, offset, limit).iter().map(PartSummary::from).collect(),
+            offset,
+            total_parts: self.parts.len(),
+            has_sub_assemblies: self.has_sub_assemblies,
+        }
+    }
+
+    /// Faces of one part from `offset`, at most `limit` of them
+    pub fn face_page(&self, part_id: &str, offset: usize, limit: usize) -> Result<FacePage, String> {
+        let part = self.parts.iter()
+            .find(|p| p.id == part_id)
+            .ok_or_else(|| format!("Unknown part: {}", part_id))?;
+        Ok(FacePage {
+            part_id: part.id.clone(),
+            faces: page(&part.faces, offset, limit).to_vec(),
+            offset,
+            total_faces: part.faces.len(),
+        })
+    }
 }
 
 /// Parse assembly STEP file and extract parts with transforms
 #[tauri::command]
-pub fn parse_assembly_step(content: String, filename: String) -> AssemblyParseResult {
+pub async fn parse_assembly_step(
+    app: AppHandle,
+    content: String,
+    filename: String,
+    job_id: Option<String>,
+) -> Result<AssemblyParseResult, String> {
+    let label = Some(filename.clone());
+    run_job(app, "parse_assembly_step", job_id, label, move |_| Ok(parse_assembly_text(&content, &filename))).await
+}
+
+/// Assembly parsing over borrowed content
+pub fn parse_assembly_text(content: &str, filename: &str) -> AssemblyParseResult {
+    tracing::info!(filename = %filename, bytes = content.len(), "parsing assembly STEP");
+
     // Validate STEP format
-    if !content.contains("ISO-10303-21") && !content.contains("STEP") {
+    if !crate::step_format::looks_like_step(content) {
+        tracing::warn!(filename = %filename, "rejected content without STEP header");
         return AssemblyParseResult {
             success: false,
             error: Some("Invalid STEP file format".to_string()),
-            filename: Some(filename),
+            filename: Some(filename.to_string()),
+            parts: vec![],
+            total_parts: 0,
+            has_sub_assemblies: false,
+        };
+    }
+
+    if let Some(partial) = crate::large_files::assess_large_file(content) {
+        tracing::warn!(filename = %filename, reason = %partial.reason, "skipping assembly parsing for large file");
+        return AssemblyParseResult {
+            success: false,
+            error: Some(format!("Assembly structure skipped: {}", partial.reason)),
+            filename: Some(filename.to_string()),
             parts: vec![],
             total_parts: 0,
             has_sub_assemblies: false,
@@ -72,26 +184,29 @@ pub fn parse_assembly_step(content: String, filename: String) -> AssemblyParseRe
     }
 
     // Parse all entities
-    let entities = parse_step_entities(&content);
+    let entities = StepEntities::parse(content);
 
     // Extract product definitions (parts)
     let product_defs = extract_product_definitions(&entities);
+    tracing::debug!(entities = entities.len(), products = product_defs.len(), "STEP entities indexed");
 
     // Extract transforms for each product
     let transforms = extract_transforms(&entities, &product_defs);
 
+    // Cone angles are in the file's plane angle unit
+    let angle_scale = if entities.of_type("CONICAL_SURFACE").next().is_some() { plane_angle_scale(&entities) } else { 1.0 };
+
     // Extract face data for each part
     let mut parts: Vec<ParsedPart> = Vec::new();
-    let mut part_id = 0;
 
-    for (product_id, product_name) in &product_defs {
+    for (part_id, (product_id, product_name)) in product_defs.iter().enumerate() {
         let transform = transforms.get(product_id).cloned().unwrap_or(identity_matrix());
 
         // Extract faces associated with this product
-        let faces = extract_faces_for_product(&content, &entities, *product_id);
+        let faces = extract_faces_for_product(&entities, *product_id, angle_scale);
 
-        // Calculate bounding box from faces
-        let bounding_box = calculate_bounding_box(&faces);
+        // Calculate bounding box from face boundaries
+        let bounding_box = calculate_bounding_box(&entities, &faces);
 
         let part = ParsedPart {
             id: format!("part-{}", part_id),
@@ -104,65 +219,38 @@ pub fn parse_assembly_step(content: String, filename: String) -> AssemblyParseRe
         };
 
         parts.push(part);
-        part_id += 1;
     }
 
     // Check for sub-assemblies
     let has_sub_assemblies = content.contains("NEXT_ASSEMBLY_USAGE_OCCURRENCE");
+    tracing::info!(parts = parts.len(), has_sub_assemblies, "assembly parsed");
 
     AssemblyParseResult {
         success: true,
         error: None,
-        filename: Some(filename),
+        filename: Some(filename.to_string()),
         total_parts: parts.len(),
         parts,
         has_sub_assemblies,
     }
 }
 
-/// Parse STEP entities into a map
-fn parse_step_entities(content: &str) -> HashMap<i64, StepEntity> {
-    let mut entities = HashMap::new();
-
-    // Match entity pattern: #123=ENTITY_TYPE(...);
-    let entity_re = Regex::new(r"#(\d+)\s*=\s*([A-Z_]+)\s*\(([^;]*)\)\s*;").unwrap();
-
-    for cap in entity_re.captures_iter(content) {
-        if let Ok(id) = cap[1].parse::<i64>() {
-            entities.insert(id, StepEntity {
-                id,
-                entity_type: cap[2].to_string(),
-                data: cap[3].to_string(),
-            });
-        }
-    }
-
-    entities
-}
-
 /// Extract product definitions (part names)
-fn extract_product_definitions(entities: &HashMap<i64, StepEntity>) -> HashMap<i64, String> {
+fn extract_product_definitions(entities: &StepEntities) -> HashMap<i64, String> {
     let mut products = HashMap::new();
 
     // Look for PRODUCT_DEFINITION entities
-    for (id, entity) in entities {
-        if entity.entity_type == "PRODUCT_DEFINITION" {
-            // Try to extract product name from linked PRODUCT entity
-            if let Some(name) = extract_product_name(entities, &entity.data) {
-                products.insert(*id, name);
-            } else {
-                products.insert(*id, format!("Part_{}", id));
-            }
-        }
+    for entity in entities.of_type("PRODUCT_DEFINITION") {
+        // Try to extract product name from linked PRODUCT entity
+        let name = extract_product_name(entities, entity).unwrap_or_else(|| format!("Part_{}", entity.id));
+        products.insert(entity.id, name);
     }
 
     // Also check MANIFOLD_SOLID_BREP for parts without PRODUCT_DEFINITION
     if products.is_empty() {
-        for (id, entity) in entities {
-            if entity.entity_type == "MANIFOLD_SOLID_BREP" {
-                let name = extract_quoted_name(&entity.data).unwrap_or(format!("Solid_{}", id));
-                products.insert(*id, name);
-            }
+        for entity in entities.of_type("MANIFOLD_SOLID_BREP") {
+            let name = entity.quoted().map(Cow::into_owned).unwrap_or_else(|| format!("Solid_{}", entity.id));
+            products.insert(entity.id, name);
         }
     }
 
@@ -170,41 +258,27 @@ fn extract_product_definitions(entities: &HashMap<i64, StepEntity>) -> HashMap<i
 }
 
 /// Extract product name from PRODUCT entity
-fn extract_product_name(entities: &HashMap<i64, StepEntity>, data: &str) -> Option<String> {
+fn extract_product_name(entities: &StepEntities, entity: &StepEntity) -> Option<String> {
     // PRODUCT_DEFINITION references PRODUCT_DEFINITION_FORMATION which references PRODUCT
-    let ref_re = Regex::new(r"#(\d+)").unwrap();
-
-    for cap in ref_re.captures_iter(data) {
-        if let Ok(ref_id) = cap[1].parse::<i64>() {
-            if let Some(entity) = entities.get(&ref_id) {
-                if entity.entity_type == "PRODUCT_DEFINITION_FORMATION" {
-                    return extract_product_name(entities, &entity.data);
-                } else if entity.entity_type == "PRODUCT" {
-                    return extract_quoted_name(&entity.data);
-                }
-            }
+    for referenced in entities.referenced(entity) {
+        match referenced.entity_type {
+            "PRODUCT_DEFINITION_FORMATION" => return extract_product_name(entities, referenced),
+            "PRODUCT" => return referenced.quoted().map(Cow::into_owned),
+            _ => {}
         }
     }
 
     None
 }
 
-/// Extract quoted name from entity data
-fn extract_quoted_name(data: &str) -> Option<String> {
-    let name_re = Regex::new(r"'([^']*)'").unwrap();
-    name_re.captures(data).map(|c| c[1].to_string())
-}
-
 /// Extract transforms for products
-fn extract_transforms(entities: &HashMap<i64, StepEntity>, _products: &HashMap<i64, String>) -> HashMap<i64, [f64; 16]> {
+fn extract_transforms(entities: &StepEntities, _products: &HashMap<i64, String>) -> HashMap<i64, [f64; 16]> {
     let mut transforms = HashMap::new();
 
     // Look for ITEM_DEFINED_TRANSFORMATION and AXIS2_PLACEMENT_3D
-    for (id, entity) in entities {
-        if entity.entity_type == "AXIS2_PLACEMENT_3D" {
-            if let Some(transform) = parse_axis_placement(entities, &entity.data) {
-                transforms.insert(*id, transform);
-            }
+    for entity in entities.of_type("AXIS2_PLACEMENT_3D") {
+        if let Some(transform) = parse_axis_placement(entities, entity) {
+            transforms.insert(entity.id, transform);
         }
     }
 
@@ -212,31 +286,15 @@ fn extract_transforms(entities: &HashMap<i64, StepEntity>, _products: &HashMap<i
 }
 
 /// Parse AXIS2_PLACEMENT_3D into transformation matrix
-fn parse_axis_placement(entities: &HashMap<i64, StepEntity>, data: &str) -> Option<[f64; 16]> {
-    let ref_re = Regex::new(r"#(\d+)").unwrap();
-    let refs: Vec<i64> = ref_re.captures_iter(data)
-        .filter_map(|c| c[1].parse().ok())
-        .collect();
-
-    if refs.is_empty() {
-        return Some(identity_matrix());
-    }
-
-    // First ref is location point, second is Z axis, third is X axis
-    let location = refs.get(0)
+pub(crate) fn parse_axis_placement(entities: &StepEntities, placement: &StepEntity) -> Option<[f64; 16]> {
+    // AXIS2_PLACEMENT_3D(name, location, axis, ref_direction); axis and ref_direction are optional
+    let location = placement.param_ref(1)
         .and_then(|id| entities.get(id))
-        .and_then(|e| parse_cartesian_point(&e.data))
+        .and_then(|e| e.triple())
         .unwrap_or([0.0, 0.0, 0.0]);
 
-    let z_axis = refs.get(1)
-        .and_then(|id| entities.get(id))
-        .and_then(|e| parse_direction(&e.data))
-        .unwrap_or([0.0, 0.0, 1.0]);
-
-    let x_axis = refs.get(2)
-        .and_then(|id| entities.get(id))
-        .and_then(|e| parse_direction(&e.data))
-        .unwrap_or([1.0, 0.0, 0.0]);
+    let z_axis = placement_axis(entities, placement);
+    let x_axis = reference_direction(&z_axis, placement.param_ref(3).and_then(|id| entities.get(id)).and_then(parse_direction));
 
     // Calculate Y axis
     let y_axis = cross(&z_axis, &x_axis);
@@ -250,44 +308,42 @@ fn parse_axis_placement(entities: &HashMap<i64, StepEntity>, data: &str) -> Opti
     ])
 }
 
-/// Parse CARTESIAN_POINT
-fn parse_cartesian_point(data: &str) -> Option<[f64; 3]> {
-    let coord_re = Regex::new(r"\(\s*([+-]?\d+\.?\d*(?:[eE][+-]?\d+)?)\s*,\s*([+-]?\d+\.?\d*(?:[eE][+-]?\d+)?)\s*,\s*([+-]?\d+\.?\d*(?:[eE][+-]?\d+)?)\s*\)").unwrap();
+/// Z axis of an AXIS2_PLACEMENT_3D, (0, 0, 1) when unset
+fn placement_axis(entities: &StepEntities, placement: &StepEntity) -> [f64; 3] {
+    placement.param_ref(2)
+        .and_then(|id| entities.get(id))
+        .and_then(parse_direction)
+        .unwrap_or([0.0, 0.0, 1.0])
+}
 
-    coord_re.captures(data).and_then(|cap| {
-        let x = cap[1].parse().ok()?;
-        let y = cap[2].parse().ok()?;
-        let z = cap[3].parse().ok()?;
-        Some([x, y, z])
-    })
+/// X axis: the reference direction made perpendicular to Z, or (1, 0, 0) projected as in ISO 10303-42
+fn reference_direction(z_axis: &[f64; 3], reference: Option<[f64; 3]>) -> [f64; 3] {
+    let project = |v: [f64; 3]| {
+        let d = v[0] * z_axis[0] + v[1] * z_axis[1] + v[2] * z_axis[2];
+        [v[0] - d * z_axis[0], v[1] - d * z_axis[1], v[2] - d * z_axis[2]]
+    };
+    [reference, Some([1.0, 0.0, 0.0]), Some([0.0, 1.0, 0.0])].into_iter()
+        .flatten()
+        .map(project)
+        .find(|v| (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt() > 1e-6)
+        .map(|v| normalize(&v))
+        .unwrap_or([1.0, 0.0, 0.0])
 }
 
 /// Parse DIRECTION
-fn parse_direction(data: &str) -> Option<[f64; 3]> {
-    parse_cartesian_point(data).map(|v| normalize(&v))
+fn parse_direction(entity: &StepEntity) -> Option<[f64; 3]> {
+    entity.triple().map(|v| normalize(&v))
 }
 
 /// Extract faces for a product
-fn extract_faces_for_product(content: &str, entities: &HashMap<i64, StepEntity>, _product_id: i64) -> Vec<ParsedFace> {
+fn extract_faces_for_product(entities: &StepEntities, _product_id: i64, angle_scale: f64) -> Vec<ParsedFace> {
     let mut faces = Vec::new();
     let mut face_id = 0;
 
     // Extract all ADVANCED_FACE entities
-    for (id, entity) in entities {
+    for entity in entities.iter() {
         if entity.entity_type == "ADVANCED_FACE" || entity.entity_type == "FACE_SURFACE" {
-            let (face_type, normal, center, radius, axis) = extract_face_geometry(entities, &entity.data, content);
-
-            faces.push(ParsedFace {
-                id: face_id,
-                face_type,
-                normal,
-                center,
-                area: 0.0,  // Would need full geometry for accurate area
-                radius,
-                axis,
-                step_entity_id: Some(*id),
-            });
-
+            faces.push(ParsedFace { id: face_id, ..extract_face_geometry(entities, entity, angle_scale) });
             face_id += 1;
         }
     }
@@ -295,145 +351,142 @@ fn extract_faces_for_product(content: &str, entities: &HashMap<i64, StepEntity>,
     faces
 }
 
-/// Extract face geometry (type, normal, center)
-fn extract_face_geometry(entities: &HashMap<i64, StepEntity>, data: &str, content: &str) -> (String, [f64; 3], [f64; 3], Option<f64>, Option<[f64; 3]>) {
-    let ref_re = Regex::new(r"#(\d+)").unwrap();
-
-    // Default values
-    let mut face_type = "freeform".to_string();
-    let mut normal = [0.0, 0.0, 1.0];
-    let mut center = [0.0, 0.0, 0.0];
-    let mut radius = None;
-    let mut axis = None;
-
-    // Find the surface reference
-    for cap in ref_re.captures_iter(data) {
-        if let Ok(ref_id) = cap[1].parse::<i64>() {
-            if let Some(entity) = entities.get(&ref_id) {
-                match entity.entity_type.as_str() {
-                    "PLANE" => {
-                        face_type = "planar".to_string();
-                        if let Some(placement) = find_axis_placement(entities, &entity.data) {
-                            if let Some(pos) = placement.0 {
-                                center = pos;
-                            }
-                            if let Some(dir) = placement.1 {
-                                normal = dir;
-                                axis = Some(dir);
-                            }
-                        }
-                    }
-                    "CYLINDRICAL_SURFACE" => {
-                        face_type = "cylindrical".to_string();
-                        if let Some((placement, r)) = parse_cylindrical_surface(entities, &entity.data) {
-                            if let Some(pos) = placement.0 {
-                                center = pos;
-                            }
-                            if let Some(dir) = placement.1 {
-                                axis = Some(dir);
-                                // For cylindrical, normal is radial (simplified)
-                                normal = [1.0, 0.0, 0.0];
-                            }
-                            radius = r;
-                        }
-                    }
-                    "CONICAL_SURFACE" => {
-                        face_type = "conical".to_string();
-                    }
-                    "SPHERICAL_SURFACE" => {
-                        face_type = "spherical".to_string();
-                    }
-                    "TOROIDAL_SURFACE" => {
-                        face_type = "toroidal".to_string();
-                    }
-                    "B_SPLINE_SURFACE_WITH_KNOTS" | "B_SPLINE_SURFACE" => {
-                        face_type = "freeform".to_string();
-                    }
-                    _ => {}
-                }
-            }
+/// Extract face geometry (type, normal, center and surface parameters); `angle_scale` converts file angles to radians
+fn extract_face_geometry(entities: &StepEntities, face: &StepEntity, angle_scale: f64) -> ParsedFace {
+    let mut parsed = ParsedFace {
+        id: 0,
+        face_type: "freeform".to_string(),
+        normal: [0.0, 0.0, 1.0],
+        center: [0.0, 0.0, 0.0],
+        area: crate::face_area::face_area(entities, face),
+        radius: None,
+        axis: None,
+        step_entity_id: Some(face.id),
+        same_sense: face.param_flag(3),
+        semi_angle: None,
+        apex: None,
+        minor_radius: None,
+    };
+
+    // ADVANCED_FACE / FACE_SURFACE(name, bounds, face_geometry, same_sense)
+    let Some(entity) = face.param_ref(2).and_then(|id| entities.get(id)) else { return parsed };
+    let placement = find_axis_placement(entities, entity);
+    if let Some((position, direction)) = placement {
+        if let Some(pos) = position {
+            parsed.center = pos;
         }
-    }
-
-    // If still freeform, check content for surface type
-    if face_type == "freeform" {
-        if content.contains("PLANE") {
-            face_type = "planar".to_string();
+        if entity.entity_type != "SPHERICAL_SURFACE" {
+            parsed.axis = direction;
         }
     }
 
-    (face_type, normal, center, radius, axis)
-}
-
-/// Find AXIS2_PLACEMENT_3D position and direction
-fn find_axis_placement(entities: &HashMap<i64, StepEntity>, data: &str) -> Option<(Option<[f64; 3]>, Option<[f64; 3]>)> {
-    let ref_re = Regex::new(r"#(\d+)").unwrap();
-
-    for cap in ref_re.captures_iter(data) {
-        if let Ok(ref_id) = cap[1].parse::<i64>() {
-            if let Some(entity) = entities.get(&ref_id) {
-                if entity.entity_type == "AXIS2_PLACEMENT_3D" {
-                    // Parse the placement
-                    let refs: Vec<i64> = ref_re.captures_iter(&entity.data)
-                        .filter_map(|c| c[1].parse().ok())
-                        .collect();
-
-                    let position = refs.get(0)
-                        .and_then(|id| entities.get(id))
-                        .and_then(|e| parse_cartesian_point(&e.data));
-
-                    let direction = refs.get(1)
-                        .and_then(|id| entities.get(id))
-                        .and_then(|e| parse_direction(&e.data));
-
-                    return Some((position, direction));
-                }
+    match entity.entity_type {
+        "PLANE" => {
+            parsed.face_type = "planar".to_string();
+            if let Some(dir) = parsed.axis {
+                parsed.normal = dir;
             }
         }
+        "CYLINDRICAL_SURFACE" => {
+            parsed.face_type = "cylindrical".to_string();
+            // For cylindrical, normal is radial (simplified)
+            if parsed.axis.is_some() {
+                parsed.normal = [1.0, 0.0, 0.0];
+            }
+            parsed.radius = positive_length(entity, 2);
+        }
+        "CONICAL_SURFACE" => {
+            // CONICAL_SURFACE(name, position, radius, semi_angle); radius is measured in the placement plane
+            parsed.face_type = "conical".to_string();
+            parsed.radius = positive_length(entity, 2).or(Some(0.0));
+            let semi_angle = entity.param_real(3).map(|a| a.abs() * angle_scale).filter(|a| *a > 0.0 && *a < FRAC_PI_2);
+            parsed.semi_angle = semi_angle.map(f64::to_degrees);
+            if let (Some(angle), Some(axis), Some(radius)) = (semi_angle, parsed.axis, parsed.radius) {
+                let offset = radius / angle.tan();
+                parsed.apex = Some([
+                    parsed.center[0] - axis[0] * offset,
+                    parsed.center[1] - axis[1] * offset,
+                    parsed.center[2] - axis[2] * offset,
+                ]);
+            }
+        }
+        "SPHERICAL_SURFACE" => {
+            // SPHERICAL_SURFACE(name, position, radius)
+            parsed.face_type = "spherical".to_string();
+            parsed.radius = positive_length(entity, 2);
+        }
+        "TOROIDAL_SURFACE" | "DEGENERATE_TOROIDAL_SURFACE" => {
+            // TOROIDAL_SURFACE(name, position, major_radius, minor_radius)
+            parsed.face_type = "toroidal".to_string();
+            parsed.radius = positive_length(entity, 2);
+            parsed.minor_radius = positive_length(entity, 3);
+        }
+        _ => {}
     }
+    parsed
+}
 
-    None
+/// Length parameter at a schema position; a negative value from a sloppy exporter keeps its magnitude
+fn positive_length(entity: &StepEntity, index: usize) -> Option<f64> {
+    entity.param_real(index)
+        .map(f64::abs)
+        .filter(|r| *r > 0.0)
 }
 
-/// Parse cylindrical surface
-fn parse_cylindrical_surface(entities: &HashMap<i64, StepEntity>, data: &str) -> Option<((Option<[f64; 3]>, Option<[f64; 3]>), Option<f64>)> {
-    let ref_re = Regex::new(r"#(\d+)").unwrap();
-    let num_re = Regex::new(r"(\d+\.?\d*(?:[eE][+-]?\d+)?)").unwrap();
+/// Radians per plane angle unit of the file: degrees when the unit context declares them, radians otherwise
+fn plane_angle_scale(entities: &StepEntities) -> f64 {
+    let degrees = entities.of_type("PLANE_ANGLE_UNIT").any(|unit| {
+        entities.get_typed(unit.id, "CONVERSION_BASED_UNIT")
+            .and_then(|conversion| conversion.quoted())
+            .is_some_and(|name| name.to_ascii_uppercase().starts_with("DEGREE"))
+    });
+    if degrees { PI / 180.0 } else { 1.0 }
+}
 
-    let placement = find_axis_placement(entities, data);
+/// Location and axis of an AXIS2_PLACEMENT_3D, each absent when its point or direction is unreadable
+type Placement = (Option<[f64; 3]>, Option<[f64; 3]>);
 
-    // Extract radius (usually last number in data)
-    let radius = num_re.captures_iter(data)
-        .last()
-        .and_then(|c| c[1].parse().ok());
+/// Find AXIS2_PLACEMENT_3D position and direction
+fn find_axis_placement(entities: &StepEntities, surface: &StepEntity) -> Option<Placement> {
+    // Elementary surfaces: (name, position, ...)
+    let placement = surface.param_ref(1).and_then(|id| entities.get_typed(id, "AXIS2_PLACEMENT_3D"))?;
 
-    placement.map(|p| (p, radius))
+    let position = placement.param_ref(1)
+        .and_then(|id| entities.get(id))
+        .and_then(|e| e.triple());
+
+    Some((position, Some(placement_axis(entities, placement))))
 }
 
-/// Calculate bounding box from faces
-fn calculate_bounding_box(faces: &[ParsedFace]) -> Option<PartBoundingBox> {
+/// Calculate bounding box from face boundary loops, falling back to center and radii
+fn calculate_bounding_box(entities: &StepEntities, faces: &[ParsedFace]) -> Option<PartBoundingBox> {
     if faces.is_empty() {
         return None;
     }
 
     let mut min = [f64::MAX, f64::MAX, f64::MAX];
     let mut max = [f64::MIN, f64::MIN, f64::MIN];
-
-    for face in faces {
+    let mut include = |p: [f64; 3]| {
         for i in 0..3 {
-            min[i] = min[i].min(face.center[i]);
-            max[i] = max[i].max(face.center[i]);
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
         }
-    }
+    };
 
-    // Expand bounding box a bit based on face radii
     for face in faces {
-        if let Some(r) = face.radius {
-            for i in 0..3 {
-                min[i] = min[i].min(face.center[i] - r);
-                max[i] = max[i].max(face.center[i] + r);
-            }
+        let boundary = face.step_entity_id
+            .and_then(|id| entities.get(id))
+            .map(|entity| crate::face_area::boundary_points(entities, entity))
+            .unwrap_or_default();
+        if !boundary.is_empty() {
+            boundary.into_iter().for_each(&mut include);
+            continue;
         }
+        // Closed surfaces (full spheres and tori) have no boundary; bound them by their radii
+        let reach = face.radius.unwrap_or(0.0) + face.minor_radius.unwrap_or(0.0);
+        include(face.center);
+        include(face.center.map(|c| c - reach));
+        include(face.center.map(|c| c + reach));
     }
 
     Some(PartBoundingBox {
@@ -478,7 +531,8 @@ mod tests {
     #[test]
     fn test_parse_cartesian_point() {
         let data = "'point',(1.5, -2.3, 4.0)";
-        let result = parse_cartesian_point(data);
+        let point = StepEntity { id: 1, entity_type: "CARTESIAN_POINT", data };
+        let result = point.triple();
         assert!(result.is_some());
         let [x, y, z] = result.unwrap();
         assert!((x - 1.5).abs() < 1e-6);
@@ -486,6 +540,58 @@ mod tests {
         assert!((z - 4.0).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_placement_and_radius_by_position() {
+        let content = "\
+#1=CARTESIAN_POINT('',(1.,2.,3.));
+#2=DIRECTION('',(0.,1.,0.));
+#3=AXIS2_PLACEMENT_3D('',#1,$,#2);
+#4=CYLINDRICAL_SURFACE('',#3,-4.E0);
+#5=ADVANCED_FACE('',(#6),#4,.F.);";
+        let entities = StepEntities::parse(content);
+
+        // Unset axis falls back to Z instead of taking the reference direction as the axis
+        let m = parse_axis_placement(&entities, entities.get(3).unwrap()).unwrap();
+        assert_eq!(&m[8..11], &[0.0, 0.0, 1.0]);
+        assert_eq!(&m[0..3], &[0.0, 1.0, 0.0]);
+        assert_eq!(&m[12..15], &[1.0, 2.0, 3.0]);
+
+        let face = extract_face_geometry(&entities, entities.get(5).unwrap(), 1.0);
+        assert_eq!(face.face_type, "cylindrical");
+        assert_eq!(face.center, [1.0, 2.0, 3.0]);
+        assert_eq!(face.radius, Some(4.0));
+        assert_eq!(face.axis, Some([0.0, 0.0, 1.0]));
+        assert_eq!(face.same_sense, Some(false));
+    }
+
+    #[test]
+    fn test_cone_and_torus_parameters() {
+        let content = "\
+ISO-10303-21;
+DATA;
+#1=CARTESIAN_POINT('',(0.,0.,10.));
+#2=DIRECTION('',(0.,0.,1.));
+#3=AXIS2_PLACEMENT_3D('',#1,#2,$);
+#4=CONICAL_SURFACE('',#3,5.,45.);
+#5=ADVANCED_FACE('',(),#4,.T.);
+#6=TOROIDAL_SURFACE('',#3,20.,2.5);
+#7=ADVANCED_FACE('',(),#6,.T.);
+#8=(CONVERSION_BASED_UNIT('DEGREE',#9)NAMED_UNIT(#10)PLANE_ANGLE_UNIT());
+ENDSEC;";
+        let entities = StepEntities::parse(content);
+        let scale = plane_angle_scale(&entities);
+        assert!((scale - PI / 180.0).abs() < 1e-12);
+
+        let cone = extract_face_geometry(&entities, entities.get(5).unwrap(), scale);
+        assert_eq!(cone.face_type, "conical");
+        assert!((cone.semi_angle.unwrap() - 45.0).abs() < 1e-9);
+        let apex = cone.apex.unwrap();
+        assert!((apex[2] - 5.0).abs() < 1e-9);
+
+        let torus = extract_face_geometry(&entities, entities.get(7).unwrap(), scale);
+        assert_eq!((torus.radius, torus.minor_radius), (Some(20.0), Some(2.5)));
+    }
+
     #[test]
     fn test_identity_matrix() {
         let m = identity_matrix();
@@ -494,4 +600,52 @@ mod tests {
         assert_eq!(m[10], 1.0);
         assert_eq!(m[15], 1.0);
     }
+
+    #[test]
+    fn test_part_and_face_pages() {
+        let face = |id| ParsedFace { id, face_type: "planar".to_string(), normal: [0.0, 0.0, 1.0], center: [0.0; 3], area: 1.0,
+            radius: None, axis: None, step_entity_id: None, same_sense: None, semi_angle: None, apex: None, minor_radius: None };
+        let part = |i: usize| ParsedPart {
+            id: format!("part-{}", i), name: format!("Part {}", i), step_entity_id: i as i64, transform: [0.0; 16],
+            bounding_box: None, faces: (0..i as i64).map(face).collect(), product_definition_id: None,
+        };
+        let result = AssemblyParseResult {
+            success: true, error: None, filename: None, parts: (0..5).map(part).collect(), total_parts: 5, has_sub_assemblies: false,
+        };
+
+        let summary = result.summary(3, 10);
+        assert_eq!(summary.parts.iter().map(|p| p.face_count).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(summary.total_parts, 5);
+        assert!(result.summary(9, 10).parts.is_empty());
+
+        let faces = result.face_page("part-4", 1, 2).unwrap();
+        assert_eq!(faces.faces.iter().map(|f| f.id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(faces.total_faces, 4);
+        assert!(result.face_page("part-9", 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_bounding_box_from_face_boundaries() {
+        let content = "\
+#1=CARTESIAN_POINT('',(0.,0.,0.));
+#2=CARTESIAN_POINT('',(200.,0.,0.));
+#3=CARTESIAN_POINT('',(200.,50.,0.));
+#4=VERTEX_POINT('',#1);
+#5=VERTEX_POINT('',#2);
+#6=VERTEX_POINT('',#3);
+#7=EDGE_CURVE('',#4,#5,$,.T.);
+#8=EDGE_CURVE('',#5,#6,$,.T.);
+#9=EDGE_CURVE('',#6,#4,$,.T.);
+#10=EDGE_LOOP('',(#11,#12,#13));
+#11=ORIENTED_EDGE('',*,*,#7,.T.);
+#12=ORIENTED_EDGE('',*,*,#8,.T.);
+#13=ORIENTED_EDGE('',*,*,#9,.T.);
+#14=FACE_OUTER_BOUND('',#10,.T.);
+#15=ADVANCED_FACE('',(#14),$,.T.);";
+        let entities = StepEntities::parse(content);
+        let faces = extract_faces_for_product(&entities, 0, 1.0);
+        let bbox = calculate_bounding_box(&entities, &faces).unwrap();
+        assert_eq!(bbox.min, [0.0, 0.0, 0.0]);
+        assert_eq!(bbox.dimensions, [200.0, 50.0, 0.0]);
+    }
 }
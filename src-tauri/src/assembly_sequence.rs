@@ -0,0 +1,234 @@
+// Assembly sequence and locating scheme: which part locates to which, used in place of the geometric interface graph
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+use crate::interface_detection::DetectedInterface;
+use crate::model_store::ModelStore;
+use crate::stackup_path::{build_stackup_path, StackupPath};
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// One assembly step: the part placed and the interfaces it locates on, datum-first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblyStep {
+    pub part_id: String,
+    pub locators: Vec<String>,  // Interface ids in datum precedence: primary first, up to three
+}
+
+/// Assembly order starting from a base part (or fixture) that is placed before the first step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblySequence {
+    pub base_part_id: String,
+    pub steps: Vec<AssemblyStep>,
+}
+
+impl Validate for AssemblySequence {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.steps.is_empty() {
+            errors.add("steps", "expected at least one assembly step");
+        }
+        let mut seen = HashSet::from([self.base_part_id.as_str()]);
+        for (i, step) in self.steps.iter().enumerate() {
+            if !seen.insert(step.part_id.as_str()) {
+                errors.add(format!("steps[{}].part_id", i), format!("part {} is placed twice", step.part_id));
+            }
+            if step.locators.is_empty() || step.locators.len() > 3 {
+                errors.add(format!("steps[{}].locators", i), format!("expected 1 to 3 locating interfaces, got {}", step.locators.len()));
+            }
+        }
+    }
+}
+
+/// How one part is located: its primary locator sets the chain, the others only orient it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocatingEdge {
+    pub step: usize,                       // 1-based
+    pub part_id: String,
+    pub located_by: String,                // Part across the primary locator
+    pub primary_interface: String,
+    pub secondary_interfaces: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocatingScheme {
+    pub base_part_id: String,
+    pub edges: Vec<LocatingEdge>,              // In assembly order
+    pub non_locating_interfaces: Vec<String>,  // Contacts no step locates on; they do not carry the chain
+}
+
+impl LocatingScheme {
+    /// Parts in the order they are placed, base first
+    pub fn placement_order(&self) -> Vec<String> {
+        std::iter::once(self.base_part_id.clone()).chain(self.edges.iter().map(|e| e.part_id.clone())).collect()
+    }
+
+    fn edge_of(&self, part_id: &str) -> Option<&LocatingEdge> {
+        self.edges.iter().find(|e| e.part_id == part_id)
+    }
+
+    /// Part followed by its locating ancestors up to the base
+    fn ancestry(&self, part_id: &str) -> Result<Vec<String>, String> {
+        let mut parts = vec![part_id.to_string()];
+        let mut current = part_id;
+        while current != self.base_part_id {
+            let edge = self.edge_of(current).ok_or_else(|| format!("Part {} is not in the assembly sequence", current))?;
+            parts.push(edge.located_by.clone());
+            current = &edge.located_by;
+        }
+        Ok(parts)
+    }
+}
+
+/// Resolve the sequence against the detected interfaces; each locator must join the step's part to one placed earlier
+pub fn resolve_sequence(sequence: &AssemblySequence, interfaces: &[DetectedInterface]) -> Result<LocatingScheme, String> {
+    let by_id: HashMap<&str, &DetectedInterface> = interfaces.iter().map(|i| (i.id.as_str(), i)).collect();
+    let mut placed = HashSet::from([sequence.base_part_id.as_str()]);
+    let mut locating = HashSet::new();
+    let mut edges = Vec::new();
+
+    for (i, step) in sequence.steps.iter().enumerate() {
+        let mut parents = Vec::new();
+        for id in &step.locators {
+            let interface = by_id.get(id.as_str()).ok_or_else(|| format!("Unknown interface: {}", id))?;
+            let other = if interface.part_a_id == step.part_id {
+                &interface.part_b_id
+            } else if interface.part_b_id == step.part_id {
+                &interface.part_a_id
+            } else {
+                return Err(format!("Interface {} does not touch part {}", id, step.part_id));
+            };
+            if !placed.contains(other.as_str()) {
+                return Err(format!(
+                    "Step {}: {} cannot locate on {} through {}; {} is not placed yet",
+                    i + 1, step.part_id, other, id, other
+                ));
+            }
+            parents.push(other.clone());
+            locating.insert(id.as_str());
+        }
+        placed.insert(step.part_id.as_str());
+        edges.push(LocatingEdge {
+            step: i + 1,
+            part_id: step.part_id.clone(),
+            located_by: parents[0].clone(),
+            primary_interface: step.locators[0].clone(),
+            secondary_interfaces: step.locators[1..].to_vec(),
+        });
+    }
+
+    Ok(LocatingScheme {
+        base_part_id: sequence.base_part_id.clone(),
+        edges,
+        non_locating_interfaces: interfaces.iter()
+            .filter(|i| !locating.contains(i.id.as_str()))
+            .map(|i| i.id.clone())
+            .collect(),
+    })
+}
+
+/// Chain between two parts through their primary locators, via the last part both locate from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocatingChain {
+    pub parts: Vec<String>,
+    pub interface_ids: Vec<String>,
+}
+
+pub fn locating_chain(scheme: &LocatingScheme, from_part_id: &str, to_part_id: &str) -> Result<LocatingChain, String> {
+    let up = scheme.ancestry(from_part_id)?;
+    let down = scheme.ancestry(to_part_id)?;
+    let common = up.iter()
+        .position(|p| down.contains(p))
+        .ok_or_else(|| format!("Parts {} and {} share no locating ancestor", from_part_id, to_part_id))?;
+    let meet = down.iter().position(|p| *p == up[common]).unwrap_or(down.len() - 1);
+
+    let primary = |part: &String| scheme.edge_of(part).map(|e| e.primary_interface.clone());
+    let parts: Vec<String> = up[..=common].iter().chain(down[..meet].iter().rev()).cloned().collect();
+    let interface_ids = up[..common].iter().filter_map(primary)
+        .chain(down[..meet].iter().rev().filter_map(primary))
+        .collect();
+    Ok(LocatingChain { parts, interface_ids })
+}
+
+/// Chain and dimension loop between two parts following the locating scheme
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceChainResult {
+    pub scheme: LocatingScheme,
+    pub chain: LocatingChain,
+    pub path: Option<StackupPath>,  // None when the chain has fewer than two interfaces
+}
+
+/// Stackup chain between two parts of a loaded model as the assembly sequence locates them
+#[tauri::command]
+pub fn get_sequence_chain(
+    state: State<'_, ModelStore>,
+    handle: String,
+    sequence: Checked<AssemblySequence>,
+    from_part_id: String,
+    to_part_id: String,
+    axis: Option<[f64; 3]>,
+) -> Result<SequenceChainResult, String> {
+    state.with_model(&handle, |model| {
+        let stored = model.interfaces.as_ref()
+            .map(|r| r.interfaces.as_slice())
+            .ok_or("Detect interfaces on the model first")?;
+        let scheme = resolve_sequence(&sequence, stored)?;
+        let chain = locating_chain(&scheme, &from_part_id, &to_part_id)?;
+        let path = if chain.interface_ids.len() < 2 {
+            None
+        } else {
+            let links = chain.interface_ids.iter()
+                .filter_map(|id| stored.iter().find(|i| i.id == *id))
+                .collect::<Vec<_>>();
+            Some(build_stackup_path(&links, axis)?)
+        };
+        tracing::info!(handle = %handle, steps = sequence.steps.len(), chain = chain.interface_ids.len(), "sequence chain built");
+        Ok(SequenceChainResult { scheme, chain, path })
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::interface;
+    use crate::validation::validate;
+
+    fn step(part_id: &str, locators: &[&str]) -> AssemblyStep {
+        AssemblyStep { part_id: part_id.to_string(), locators: locators.iter().map(|s| s.to_string()).collect() }
+    }
+
+    #[test]
+    fn test_chain_follows_locating_scheme_not_shortest_contact() {
+        // The bracket also touches the base, but it locates on the plate
+        let interfaces = [
+            interface("interface-1", "base", "plate", [0.0; 3]),
+            interface("interface-2", "plate", "bracket", [0.0; 3]),
+            interface("interface-3", "bracket", "base", [0.0; 3]),
+            interface("interface-4", "bracket", "pin", [0.0; 3]),
+        ];
+        let sequence = AssemblySequence {
+            base_part_id: "base".to_string(),
+            steps: vec![
+                step("plate", &["interface-1"]),
+                step("bracket", &["interface-2", "interface-3"]),
+                step("pin", &["interface-4"]),
+            ],
+        };
+        assert!(validate(&sequence).is_ok());
+
+        let scheme = resolve_sequence(&sequence, &interfaces).unwrap();
+        assert_eq!(scheme.placement_order(), vec!["base", "plate", "bracket", "pin"]);
+        assert_eq!(scheme.edges[1].located_by, "plate");
+        assert!(scheme.non_locating_interfaces.is_empty());
+
+        let chain = locating_chain(&scheme, "base", "pin").unwrap();
+        assert_eq!(chain.interface_ids, vec!["interface-1", "interface-2", "interface-4"]);
+        assert_eq!(chain.parts, vec!["base", "plate", "bracket", "pin"]);
+        let sideways = locating_chain(&scheme, "pin", "plate").unwrap();
+        assert_eq!(sideways.interface_ids, vec!["interface-4", "interface-2"]);
+
+        // Out of order: the pin cannot locate on a bracket that is not there yet
+        let early = AssemblySequence { steps: vec![step("pin", &["interface-4"])], ..sequence };
+        assert!(resolve_sequence(&early, &interfaces).unwrap_err().contains("not placed yet"));
+    }
+}
@@ -0,0 +1,383 @@
+// 3D variation analysis: interfaces perturbed in translation and rotation, placements propagated from a base part
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tauri::{AppHandle, State};
+
+use crate::assembly_sequence::{resolve_sequence, AssemblySequence};
+use crate::interface_detection::DetectedInterface;
+use crate::jobs::run_job;
+use crate::linalg::{
+    add, distance, dot, mat3_mul_vec, norm, normalize, quaternion_to_matrix, scale, sub, Mat3, RigidTransform, Vec3, IDENTITY3,
+};
+use crate::model_store::ModelStore;
+use crate::tolerance_calc::{sample_link, summarize_samples, LinkInput, MonteCarloResult, TargetSpec, MAX_MONTE_CARLO_SAMPLES};
+use crate::validation::{Checked, FieldErrors, Validate};
+
+const DEFAULT_SAMPLES: usize = 20_000;
+
+/// How far one interface may move: ± per world axis, rotations about the contact point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceTolerance3d {
+    pub interface_id: String,
+    #[serde(default)]
+    pub translation: [f64; 3],   // ± mm along world x, y, z
+    #[serde(default)]
+    pub rotation: [f64; 3],      // ± degrees about world x, y, z
+    pub distribution: String,    // "normal" or "uniform"
+    pub sigma: Option<f64>,      // Default 3.0 for normal distribution
+}
+
+/// Point on a part whose position is reported, optionally against a point on another part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VariationMeasure {
+    pub part_id: String,
+    pub point: [f64; 3],                   // Nominal world position
+    pub reference_part_id: Option<String>, // Fixed in the world when absent
+    pub reference_point: Option<[f64; 3]>, // Defaults to `point`, i.e. the point's own deviation
+    pub direction: Option<[f64; 3]>,       // Signed gap along this axis; distance when absent
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblyVariationInput {
+    pub base_part_id: Option<String>,     // Part held fixed; the first part when absent
+    #[serde(default)]
+    pub sequence: Option<AssemblySequence>,  // Placements follow its locating scheme instead of the interface graph
+    pub tolerances: Vec<InterfaceTolerance3d>,
+    pub measure: VariationMeasure,
+    pub target_spec: Option<TargetSpec>,
+    pub monte_carlo_samples: Option<usize>,
+}
+
+impl Validate for AssemblyVariationInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.tolerances.is_empty() {
+            errors.add("tolerances", "expected at least one interface tolerance");
+        }
+        for (i, tolerance) in self.tolerances.iter().enumerate() {
+            let field = |name: &str| format!("tolerances[{}].{}", i, name);
+            for (axis, value) in ["x", "y", "z"].iter().zip(tolerance.translation) {
+                errors.at_least(field(&format!("translation.{}", axis)), value, 0.0);
+            }
+            for (axis, value) in ["x", "y", "z"].iter().zip(tolerance.rotation) {
+                errors.within(field(&format!("rotation.{}", axis)), value, 0.0, 10.0);
+            }
+            errors.one_of(field("distribution"), &tolerance.distribution, &["normal", "uniform"]);
+            if let Some(sigma) = tolerance.sigma {
+                errors.above(field("sigma"), sigma, 0.0);
+            }
+        }
+        if let Some(sequence) = &self.sequence {
+            sequence.validate(errors);
+        }
+        let measure = &self.measure;
+        for (name, point) in [("measure.point", Some(measure.point)), ("measure.reference_point", measure.reference_point)] {
+            if point.is_some_and(|p| !p.iter().all(|c| c.is_finite())) {
+                errors.add(name, "expected finite coordinates");
+            }
+        }
+        if let Some(direction) = measure.direction {
+            if !direction.iter().all(|c| c.is_finite()) || norm(&direction) < 1e-9 {
+                errors.add("measure.direction", "expected a finite, non-zero vector");
+            }
+        }
+        if let Some(spec) = &self.target_spec {
+            errors.finite("target_spec.nominal", spec.nominal);
+            errors.at_least("target_spec.plus_tolerance", spec.plus_tolerance, 0.0);
+            errors.at_least("target_spec.minus_tolerance", spec.minus_tolerance, 0.0);
+        }
+        if let Some(samples) = self.monte_carlo_samples {
+            if samples == 0 || samples > MAX_MONTE_CARLO_SAMPLES {
+                errors.add("monte_carlo_samples", format!("expected 1 to {}, got {}", MAX_MONTE_CARLO_SAMPLES, samples));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblyVariationResult {
+    pub samples: usize,
+    pub nominal: f64,
+    pub distribution: MonteCarloResult,
+    pub yield_fraction: Option<f64>,       // Share of samples within the target spec
+    pub placement_order: Vec<String>,      // Parts in the order placements were propagated
+    pub unused_interfaces: Vec<String>,    // Close a loop of the assembly graph; not perturbed
+    pub floating_parts: Vec<String>,       // Not connected to the base part; kept nominal
+}
+
+/// Placement of `child` from `parent` through one interface
+struct TreeEdge<'a> {
+    parent: usize,
+    child: usize,
+    contact: Vec3,
+    tolerance: Option<&'a InterfaceTolerance3d>,
+}
+
+/// Tree edges, parts in placement order, and the interfaces left out of the tree
+type PlacementTree<'a> = (Vec<TreeEdge<'a>>, Vec<usize>, Vec<String>);
+
+/// Breadth-first spanning tree of the parts, interfaces as edges. Toleranced interfaces are grown
+/// first, so a nominal contact never bypasses the variation of a chain
+fn spanning_tree<'a>(
+    part_ids: &[String],
+    base: usize,
+    interfaces: &[DetectedInterface],
+    tolerances: &'a [InterfaceTolerance3d],
+) -> PlacementTree<'a> {
+    let index: HashMap<&str, usize> = part_ids.iter().enumerate().map(|(i, id)| (id.as_str(), i)).collect();
+    let mut placed = vec![false; part_ids.len()];
+    let mut used = vec![false; interfaces.len()];
+    let mut order = vec![base];
+    let mut edges = Vec::new();
+    placed[base] = true;
+
+    for toleranced_only in [true, false] {
+        let mut queue: VecDeque<usize> = order.iter().copied().collect();
+        while let Some(parent) = queue.pop_front() {
+            for (k, interface) in interfaces.iter().enumerate() {
+                let tolerance = tolerances.iter().find(|t| t.interface_id == interface.id);
+                if toleranced_only && tolerance.is_none() {
+                    continue;
+                }
+                let ends = (index.get(interface.part_a_id.as_str()), index.get(interface.part_b_id.as_str()));
+                let (Some(&a), Some(&b)) = ends else { continue };
+                let child = if a == parent { b } else if b == parent { a } else { continue };
+                if placed[child] {
+                    continue;
+                }
+                placed[child] = true;
+                used[k] = true;
+                order.push(child);
+                queue.push_back(child);
+                edges.push(TreeEdge { parent, child, contact: interface.contact_point, tolerance });
+            }
+        }
+    }
+
+    let unused = interfaces.iter().zip(&used).filter(|(_, u)| !**u).map(|(i, _)| i.id.clone()).collect();
+    (edges, order, unused)
+}
+
+/// Tree given by an assembly sequence: each part placed from the part across its primary locator
+fn sequence_tree<'a>(
+    part_ids: &[String],
+    sequence: &AssemblySequence,
+    interfaces: &[DetectedInterface],
+    tolerances: &'a [InterfaceTolerance3d],
+) -> Result<PlacementTree<'a>, String> {
+    let find = |id: &str| part_ids.iter().position(|p| p == id).ok_or_else(|| format!("Unknown part: {}", id));
+    let scheme = resolve_sequence(sequence, interfaces)?;
+    let order = scheme.placement_order().iter().map(|id| find(id)).collect::<Result<Vec<_>, _>>()?;
+    let mut edges = Vec::new();
+    for edge in &scheme.edges {
+        let child = find(&edge.part_id)?;
+        edges.push(TreeEdge {
+            parent: find(&edge.located_by)?,
+            child,
+            contact: interfaces.iter()
+                .find(|i| i.id == edge.primary_interface)
+                .map(|i| i.contact_point)
+                .unwrap_or_default(),
+            tolerance: tolerances.iter().find(|t| t.interface_id == edge.primary_interface),
+        });
+    }
+    // Secondary locators only orient the part; like loop-closing contacts they are not perturbed
+    let unused = interfaces.iter()
+        .filter(|i| !scheme.edges.iter().any(|e| e.primary_interface == i.id))
+        .map(|i| i.id.clone())
+        .collect();
+    Ok((edges, order, unused))
+}
+
+/// Zero-mean deviation within ±`tolerance`
+fn sample_deviation<R: rand::Rng + ?Sized>(tolerance: f64, spec: &InterfaceTolerance3d, rng: &mut R) -> f64 {
+    if tolerance <= 0.0 {
+        return 0.0;
+    }
+    let link = LinkInput {
+        name: None,
+        nominal: 0.0,
+        plus_tolerance: tolerance,
+        minus_tolerance: tolerance,
+        direction: "positive".to_string(),
+        distribution: spec.distribution.clone(),
+        sigma: spec.sigma,
+        inspected: false,
+    };
+    sample_link(&link, rng)
+}
+
+/// Rotation by a rotation vector (axis times angle in radians)
+fn rotation_from_vector(v: &Vec3) -> Mat3 {
+    let angle = norm(v);
+    if angle < 1e-15 {
+        return IDENTITY3;
+    }
+    let axis = scale(v, (angle / 2.0).sin() / angle);
+    quaternion_to_matrix(&[(angle / 2.0).cos(), axis[0], axis[1], axis[2]])
+}
+
+/// Random rigid motion of an interface about its nominal contact point
+fn perturbation<R: rand::Rng + ?Sized>(edge: &TreeEdge, rng: &mut R) -> RigidTransform {
+    let Some(spec) = edge.tolerance else { return RigidTransform::default() };
+    let translation = spec.translation.map(|t| sample_deviation(t, spec, rng));
+    let angles = spec.rotation.map(|r| sample_deviation(r, spec, rng).to_radians());
+    let rotation = rotation_from_vector(&angles);
+    // x' = R (x - c) + c + t
+    let c = edge.contact;
+    RigidTransform { rotation, translation: add(&sub(&c, &mat3_mul_vec(&rotation, &c)), &translation) }
+}
+
+/// Gap or position of the measured point for one set of part placements
+fn measure_value(measure: &VariationMeasure, point: &RigidTransform, reference: &RigidTransform) -> f64 {
+    let p = point.apply(&measure.point);
+    let r = reference.apply(&measure.reference_point.unwrap_or(measure.point));
+    match measure.direction {
+        Some(direction) => dot(&sub(&p, &r), &normalize(&direction)),
+        None => distance(&p, &r),
+    }
+}
+
+/// Monte Carlo over the interface tolerances, placements propagated along the spanning tree
+pub fn simulate_assembly_variation(
+    part_ids: &[String],
+    interfaces: &[DetectedInterface],
+    input: &AssemblyVariationInput,
+) -> Result<AssemblyVariationResult, String> {
+    let find = |id: &str| part_ids.iter().position(|p| p == id).ok_or_else(|| format!("Unknown part: {}", id));
+    for tolerance in &input.tolerances {
+        if !interfaces.iter().any(|i| i.id == tolerance.interface_id) {
+            return Err(format!("Unknown interface: {}", tolerance.interface_id));
+        }
+    }
+    let measured = find(input.measure.part_id.as_str())?;
+    let reference = input.measure.reference_part_id.as_deref().map(find).transpose()?;
+
+    let (edges, order, unused_interfaces) = match &input.sequence {
+        Some(sequence) => sequence_tree(part_ids, sequence, interfaces, &input.tolerances)?,
+        None => {
+            let base = match input.base_part_id.as_deref() {
+                Some(id) => find(id)?,
+                None if part_ids.is_empty() => return Err("Model has no parts".to_string()),
+                None => 0,
+            };
+            spanning_tree(part_ids, base, interfaces, &input.tolerances)
+        }
+    };
+    let samples = input.monte_carlo_samples.unwrap_or(DEFAULT_SAMPLES);
+    tracing::info!(parts = part_ids.len(), tree_edges = edges.len(), unused = unused_interfaces.len(), samples, "simulating 3D assembly variation");
+
+    let fixed = RigidTransform::default();
+    let nominal = measure_value(&input.measure, &fixed, &fixed);
+    let mut rng = rand::thread_rng();
+    let mut placements = vec![fixed; part_ids.len()];
+    let mut values: Vec<f64> = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        for edge in &edges {
+            placements[edge.child] = perturbation(edge, &mut rng).then(&placements[edge.parent]);
+        }
+        let reference = reference.map(|r| placements[r]).unwrap_or(fixed);
+        values.push(measure_value(&input.measure, &placements[measured], &reference));
+    }
+
+    let yield_fraction = input.target_spec.as_ref().map(|spec| {
+        let within = values.iter()
+            .filter(|v| **v >= spec.nominal - spec.minus_tolerance && **v <= spec.nominal + spec.plus_tolerance)
+            .count();
+        within as f64 / samples as f64
+    });
+    values.sort_by(|a, b| a.total_cmp(b));
+
+    let mut floating_parts: Vec<String> = part_ids.iter().enumerate()
+        .filter(|(i, _)| !order.contains(i))
+        .map(|(_, id)| id.clone())
+        .collect();
+    floating_parts.sort();
+
+    Ok(AssemblyVariationResult {
+        samples,
+        nominal,
+        distribution: summarize_samples(values, input.target_spec.as_ref()),
+        yield_fraction,
+        placement_order: order.iter().map(|&i| part_ids[i].clone()).collect(),
+        unused_interfaces,
+        floating_parts,
+    })
+}
+
+/// 3D variation of a point or gap on a loaded model, using its detected interfaces
+#[tauri::command]
+pub async fn simulate_model_variation(
+    app: AppHandle,
+    state: State<'_, ModelStore>,
+    handle: String,
+    input: Checked<AssemblyVariationInput>,
+    job_id: Option<String>,
+) -> Result<AssemblyVariationResult, String> {
+    let input = input.into_inner();
+    let (part_ids, interfaces) = state.with_model(&handle, |model| {
+        let part_ids: Vec<String> = model.assembly.parts.iter().map(|p| p.id.clone()).collect();
+        let interfaces = model.interfaces.as_ref().map(|r| r.interfaces.clone());
+        (part_ids, interfaces)
+    })?;
+    let interfaces = interfaces.ok_or("Detect interfaces on the model first")?;
+    run_job(app, "simulate_model_variation", job_id, None, move |_| {
+        simulate_assembly_variation(&part_ids, &interfaces, &input)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::interface;
+    use crate::validation::validate;
+
+    fn tolerance(id: &str, translation: [f64; 3], rotation: [f64; 3]) -> InterfaceTolerance3d {
+        InterfaceTolerance3d { interface_id: id.to_string(), translation, rotation, distribution: "uniform".to_string(), sigma: None }
+    }
+
+    #[test]
+    fn test_tilt_and_stacked_offsets_propagate() {
+        let parts: Vec<String> = ["base", "bracket", "arm"].iter().map(|s| s.to_string()).collect();
+        let interfaces = [
+            interface("interface-1", "base", "bracket", [0.0, 0.0, 0.0]),
+            interface("interface-2", "bracket", "arm", [0.0, 0.0, 10.0]),
+            interface("interface-3", "arm", "base", [0.0, 50.0, 0.0]),
+        ];
+        // Bracket may tilt ±0.5° about x; the arm shifts ±0.1 mm in z on the bracket
+        let input = AssemblyVariationInput {
+            base_part_id: None,
+            sequence: None,
+            tolerances: vec![
+                tolerance("interface-1", [0.0; 3], [0.5, 0.0, 0.0]),
+                tolerance("interface-2", [0.0, 0.0, 0.1], [0.0; 3]),
+            ],
+            measure: VariationMeasure {
+                part_id: "arm".to_string(),
+                point: [0.0, 100.0, 10.0],
+                reference_part_id: None,
+                reference_point: None,
+                direction: Some([0.0, 0.0, 1.0]),
+            },
+            target_spec: Some(TargetSpec { nominal: 0.0, plus_tolerance: 0.5, minus_tolerance: 0.5 }),
+            monte_carlo_samples: Some(20_000),
+        };
+        assert!(validate(&input).is_ok());
+
+        let result = simulate_assembly_variation(&parts, &interfaces, &input).unwrap();
+        assert_eq!(result.placement_order, vec!["base", "bracket", "arm"]);
+        assert_eq!(result.unused_interfaces, vec!["interface-3"]);
+        assert_eq!(result.nominal, 0.0);
+
+        // 100 mm lever: ±0.873 mm from the tilt plus ±0.1 mm of offset
+        let lever = 100.0 * 0.5f64.to_radians().sin();
+        assert!(result.distribution.max <= lever + 0.1 + 1e-9 && result.distribution.min >= -lever - 0.1 - 1e-9);
+        let expected_sigma = ((100.0 * 0.5f64.to_radians()).powi(2) / 3.0 + 0.01 / 3.0).sqrt();
+        assert!((result.distribution.std_dev - expected_sigma).abs() < 0.03, "{}", result.distribution.std_dev);
+        assert!(result.yield_fraction.unwrap() > 0.4 && result.yield_fraction.unwrap() < 0.7);
+
+        let bad = AssemblyVariationInput { base_part_id: Some("frame".to_string()), ..input };
+        assert!(simulate_assembly_variation(&parts, &interfaces, &bad).unwrap_err().contains("frame"));
+    }
+}
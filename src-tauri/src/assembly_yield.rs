@@ -0,0 +1,241 @@
+// First-pass yield across several stackups of one assembly, with shared links sampled once per build
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use crate::jobs::run_job;
+use crate::tolerance_calc::{sample_link, validate_link, LinkInput, TargetSpec, MAX_MONTE_CARLO_SAMPLES};
+use crate::validation::{Checked, FieldErrors, Validate};
+
+const DEFAULT_SAMPLES: usize = 100_000;
+
+/// One gap of the assembly and the spec it must meet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackupSpec {
+    pub name: Option<String>,
+    pub links: Vec<LinkInput>,
+    pub target_spec: TargetSpec,
+}
+
+/// Stackups on the same assembly; links with the same name are the same dimension
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblyYieldInput {
+    pub stackups: Vec<StackupSpec>,
+    pub monte_carlo_samples: Option<usize>,
+}
+
+impl Validate for AssemblyYieldInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.stackups.is_empty() {
+            errors.add("stackups", "expected at least one stackup");
+        }
+        let mut named: HashMap<&str, &LinkInput> = HashMap::new();
+        for (s, stackup) in self.stackups.iter().enumerate() {
+            if stackup.links.is_empty() {
+                errors.add(format!("stackups[{}].links", s), "expected at least one link");
+            }
+            for (i, link) in stackup.links.iter().enumerate() {
+                let prefix = format!("stackups[{}].links[{}]", s, i);
+                validate_link(link, &prefix, errors);
+                let Some(name) = link.name.as_deref() else { continue };
+                let first = *named.entry(name).or_insert(link);
+                let same = first.nominal == link.nominal
+                    && first.plus_tolerance == link.plus_tolerance
+                    && first.minus_tolerance == link.minus_tolerance
+                    && first.distribution == link.distribution
+                    && first.sigma == link.sigma;
+                if !same {
+                    errors.add(prefix, format!("shared link \"{}\" is defined differently in another stackup", name));
+                }
+            }
+            let spec = &stackup.target_spec;
+            errors.finite(format!("stackups[{}].target_spec.nominal", s), spec.nominal);
+            errors.at_least(format!("stackups[{}].target_spec.plus_tolerance", s), spec.plus_tolerance, 0.0);
+            errors.at_least(format!("stackups[{}].target_spec.minus_tolerance", s), spec.minus_tolerance, 0.0);
+        }
+        if let Some(samples) = self.monte_carlo_samples {
+            if samples == 0 || samples > MAX_MONTE_CARLO_SAMPLES {
+                errors.add("monte_carlo_samples", format!("expected 1 to {}, got {}", MAX_MONTE_CARLO_SAMPLES, samples));
+            }
+        }
+    }
+}
+
+/// Simulated yield of one stackup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackupYield {
+    pub index: usize,
+    pub name: Option<String>,
+    pub yield_fraction: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// Fraction of simulated builds meeting every stackup spec at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblyYieldResult {
+    pub samples: usize,
+    pub assembly_yield: f64,
+    pub independent_yield: f64,     // Product of stackup yields, as if no links were shared
+    pub stackups: Vec<StackupYield>,
+    pub shared_links: Vec<String>,  // Names used by more than one stackup
+}
+
+/// Joint Monte Carlo: every build draws each distinct link once and checks all stackups against it
+pub fn compute_assembly_yield(input: &AssemblyYieldInput) -> AssemblyYieldResult {
+    let samples = input.monte_carlo_samples.unwrap_or(DEFAULT_SAMPLES);
+
+    // Distinct dimensions: named links share a slot, unnamed links get their own
+    let mut slots: Vec<&LinkInput> = Vec::new();
+    let mut by_name: HashMap<&str, usize> = HashMap::new();
+    let mut users: HashMap<&str, Vec<usize>> = HashMap::new();
+    let terms: Vec<Vec<(usize, f64)>> = input.stackups.iter().enumerate()
+        .map(|(s, stackup)| {
+            stackup.links.iter()
+                .map(|link| {
+                    let sign = if link.direction == "negative" { -1.0 } else { 1.0 };
+                    let slot = match link.name.as_deref() {
+                        Some(name) => {
+                            users.entry(name).or_default().push(s);
+                            *by_name.entry(name).or_insert_with(|| {
+                                slots.push(link);
+                                slots.len() - 1
+                            })
+                        }
+                        None => {
+                            slots.push(link);
+                            slots.len() - 1
+                        }
+                    };
+                    (slot, sign)
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut shared_links: Vec<String> = users.into_iter()
+        .filter(|(_, stackups)| {
+            let mut distinct = stackups.clone();
+            distinct.dedup();
+            distinct.len() > 1
+        })
+        .map(|(name, _)| name.to_string())
+        .collect();
+    shared_links.sort();
+    tracing::info!(stackups = input.stackups.len(), links = slots.len(), shared = shared_links.len(), samples, "calculating assembly yield");
+
+    let mut rng = rand::thread_rng();
+    let mut values = vec![0.0; slots.len()];
+    let mut passes = vec![0usize; input.stackups.len()];
+    let mut sums = vec![(0.0, 0.0); input.stackups.len()];
+    let mut all_pass = 0usize;
+    for _ in 0..samples {
+        for (value, link) in values.iter_mut().zip(&slots) {
+            *value = sample_link(link, &mut rng);
+        }
+        let mut build_ok = true;
+        for (s, stackup) in input.stackups.iter().enumerate() {
+            let total: f64 = terms[s].iter().map(|(slot, sign)| sign * values[*slot]).sum();
+            let spec = &stackup.target_spec;
+            let ok = total >= spec.nominal - spec.minus_tolerance && total <= spec.nominal + spec.plus_tolerance;
+            passes[s] += ok as usize;
+            build_ok &= ok;
+            sums[s].0 += total;
+            sums[s].1 += total * total;
+        }
+        all_pass += build_ok as usize;
+    }
+
+    let n = samples as f64;
+    let stackups: Vec<StackupYield> = input.stackups.iter().enumerate()
+        .map(|(s, stackup)| {
+            let mean = sums[s].0 / n;
+            StackupYield {
+                index: s,
+                name: stackup.name.clone(),
+                yield_fraction: passes[s] as f64 / n,
+                mean,
+                std_dev: (sums[s].1 / n - mean * mean).max(0.0).sqrt(),
+            }
+        })
+        .collect();
+
+    AssemblyYieldResult {
+        samples,
+        assembly_yield: all_pass as f64 / n,
+        independent_yield: stackups.iter().map(|s| s.yield_fraction).product(),
+        stackups,
+        shared_links,
+    }
+}
+
+/// Estimate the share of builds that meet all gaps of an assembly
+#[tauri::command]
+pub async fn calculate_assembly_yield(
+    app: AppHandle,
+    input: Checked<AssemblyYieldInput>,
+    job_id: Option<String>,
+) -> Result<AssemblyYieldResult, String> {
+    let input = input.into_inner();
+    run_job(app, "calculate_assembly_yield", job_id, None, move |_| Ok(compute_assembly_yield(&input))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::validate;
+
+    fn link(name: Option<&str>, nominal: f64, tolerance: f64, direction: &str) -> LinkInput {
+        LinkInput {
+            name: name.map(str::to_string),
+            nominal,
+            plus_tolerance: tolerance,
+            minus_tolerance: tolerance,
+            direction: direction.to_string(),
+            distribution: "normal".to_string(),
+            sigma: Some(3.0),
+            inspected: false,
+        }
+    }
+
+    fn gap(links: Vec<LinkInput>, min: f64) -> StackupSpec {
+        // Any gap above `min`
+        StackupSpec { name: None, links, target_spec: TargetSpec { nominal: min, plus_tolerance: 1e9, minus_tolerance: 0.0 } }
+    }
+
+    #[test]
+    fn test_shared_link_correlates_gaps() {
+        // Both gaps fail when the shared housing is short, so the joint yield beats the product
+        let housing = link(Some("housing"), 10.0, 0.3, "positive");
+        let input = AssemblyYieldInput {
+            stackups: vec![
+                gap(vec![housing.clone(), link(None, 10.0, 0.0001, "negative")], 0.0),
+                gap(vec![housing, link(None, 10.0, 0.0001, "negative")], 0.0),
+            ],
+            monte_carlo_samples: Some(40_000),
+        };
+        assert!(validate(&input).is_ok());
+
+        let result = compute_assembly_yield(&input);
+        assert_eq!(result.shared_links, vec!["housing".to_string()]);
+        for stackup in &result.stackups {
+            assert!((stackup.yield_fraction - 0.5).abs() < 0.02);
+        }
+        assert!((result.assembly_yield - 0.5).abs() < 0.02);
+        assert!((result.independent_yield - 0.25).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_conflicting_shared_link_rejected() {
+        let input = AssemblyYieldInput {
+            stackups: vec![
+                gap(vec![link(Some("shim"), 2.0, 0.1, "positive")], 0.0),
+                gap(vec![link(Some("shim"), 2.5, 0.1, "positive")], 0.0),
+            ],
+            monte_carlo_samples: None,
+        };
+        let err = validate(&input).unwrap_err();
+        assert!(err.starts_with("stackups[1].links[0]: shared link \"shim\""), "{}", err);
+    }
+}
@@ -0,0 +1,430 @@
+// Periodic session snapshots so a crash does not lose a long analysis session
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::clipping_planes::ClippingPlane;
+use crate::coordinate_systems::CoordinateSystem;
+use crate::encryption::EncryptionState;
+use crate::interface_detection::InterfaceDetectionResult;
+use crate::journal::{Edit, EditJournal};
+use crate::model_store::{load_model, LoadedModel, ModelInfo, ModelStore};
+use crate::part_display::PartDisplayState;
+use crate::part_transforms::PartTransformStore;
+use crate::recent_files::{hash_content, RecentFilesState};
+use crate::session::SessionState;
+use crate::storage::{load_json, save_json, unix_timestamp};
+use crate::tolerance_calc::ToleranceInput;
+
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Snapshot of the running session; removed on a clean exit
+const SNAPSHOT_FILE: &str = "session.json";
+
+/// Snapshot left behind by a session that did not exit cleanly
+const RECOVERY_FILE: &str = "session.recovered.json";
+
+/// Copies of models that have no file on disk
+const CONTENT_DIR: &str = "models";
+
+/// Stackup the user is still editing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackupDraft {
+    pub id: String,
+    pub name: Option<String>,
+    pub input: ToleranceInput,
+    pub updated_at: u64,
+}
+
+/// What is needed to reload a model with its edits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelSnapshot {
+    pub handle: String,
+    pub filename: String,
+    pub path: Option<String>,
+    pub content_hash: String,
+    pub interfaces: Option<InterfaceDetectionResult>,
+    pub coordinate_systems: Vec<CoordinateSystem>,
+    #[serde(default)]
+    pub display: PartDisplayState,
+    #[serde(default)]
+    pub clipping_planes: Vec<ClippingPlane>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub saved_at: u64,
+    pub models: Vec<ModelSnapshot>,
+    pub active_handle: Option<String>,   // Model the main window was showing
+    pub stackups: Vec<StackupDraft>,
+}
+
+/// Summary shown when offering recovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryInfo {
+    pub saved_at: u64,
+    pub filenames: Vec<String>,
+    pub stackups: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveredModel {
+    pub previous_handle: String,
+    pub model: ModelInfo,
+    pub changed_on_disk: bool,   // Interfaces were dropped because the file no longer matches
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryFailure {
+    pub filename: String,
+    pub error: String,
+}
+
+/// Result of recover_session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveredSession {
+    pub saved_at: u64,
+    pub models: Vec<RecoveredModel>,
+    pub failed: Vec<RecoveryFailure>,
+    pub active_handle: Option<String>,   // New handle of the model that was active
+    pub stackups: Vec<StackupDraft>,
+}
+
+/// Managed autosave state
+pub struct AutosaveState {
+    dir: PathBuf,
+    drafts: Mutex<Vec<StackupDraft>>,
+    last_written: Mutex<Option<String>>,
+}
+
+impl AutosaveState {
+    /// Set up the recovery folder; a snapshot still present means the last session crashed
+    pub fn new(dir: PathBuf) -> Self {
+        let _ = std::fs::create_dir_all(dir.join(CONTENT_DIR));
+        let snapshot = dir.join(SNAPSHOT_FILE);
+        if snapshot.exists() {
+            tracing::warn!("previous session did not exit cleanly; keeping its snapshot for recovery");
+            if let Err(e) = std::fs::rename(&snapshot, dir.join(RECOVERY_FILE)) {
+                tracing::warn!(error = %e, "failed to keep session snapshot");
+            }
+        }
+        AutosaveState { dir, drafts: Mutex::new(Vec::new()), last_written: Mutex::new(None) }
+    }
+
+    fn content_path(&self, hash: &str) -> PathBuf {
+        self.dir.join(CONTENT_DIR).join(format!("{}.step", hash))
+    }
+
+    pub(crate) fn drafts(&self) -> Vec<StackupDraft> {
+        self.drafts.lock().map(|d| d.clone()).unwrap_or_default()
+    }
+
+    /// Store a draft, replacing one with the same id; returns the replaced draft
+    pub fn put_draft(&self, draft: StackupDraft) -> Result<Option<StackupDraft>, String> {
+        let mut drafts = self.drafts.lock().map_err(|_| "Autosave state poisoned".to_string())?;
+        match drafts.iter_mut().find(|d| d.id == draft.id) {
+            Some(existing) => Ok(Some(std::mem::replace(existing, draft))),
+            None => {
+                drafts.push(draft);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Drop a draft; returns it if there was one
+    pub fn remove_draft(&self, id: &str) -> Result<Option<StackupDraft>, String> {
+        let mut drafts = self.drafts.lock().map_err(|_| "Autosave state poisoned".to_string())?;
+        Ok(drafts.iter().position(|d| d.id == id).map(|i| drafts.remove(i)))
+    }
+
+    /// Write the snapshot unless nothing changed since the last write; returns whether it was written
+    fn write(&self, mut snapshot: SessionSnapshot) -> Result<bool, String> {
+        let body = serde_json::to_string(&snapshot).map_err(|e| format!("Failed to serialize session: {}", e))?;
+        let mut last = self.last_written.lock().map_err(|_| "Autosave state poisoned".to_string())?;
+        if last.as_deref() == Some(body.as_str()) {
+            return Ok(false);
+        }
+        snapshot.saved_at = unix_timestamp();
+        save_json(&self.dir.join(SNAPSHOT_FILE), &snapshot)?;
+        *last = Some(body);
+        Ok(true)
+    }
+
+    fn pending(&self) -> Option<SessionSnapshot> {
+        let path = self.dir.join(RECOVERY_FILE);
+        path.exists().then(|| load_json(&path))
+    }
+
+    /// Delete model copies no snapshot refers to
+    fn prune_content(&self) {
+        let mut keep: HashSet<String> = HashSet::new();
+        for file in [SNAPSHOT_FILE, RECOVERY_FILE] {
+            let path = self.dir.join(file);
+            if path.exists() {
+                let snapshot: SessionSnapshot = load_json(&path);
+                keep.extend(snapshot.models.into_iter().map(|m| m.content_hash));
+            }
+        }
+        let Ok(entries) = std::fs::read_dir(self.dir.join(CONTENT_DIR)) else { return };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hash = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+            if !keep.contains(&hash) {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    fn discard_recovery(&self) -> bool {
+        let removed = std::fs::remove_file(self.dir.join(RECOVERY_FILE)).is_ok();
+        self.prune_content();
+        removed
+    }
+}
+
+/// Edits of a loaded model worth keeping across sessions
+pub(crate) fn model_snapshot(model: &LoadedModel) -> ModelSnapshot {
+    ModelSnapshot {
+        handle: model.handle.clone(),
+        filename: model.filename.clone(),
+        path: model.path.clone(),
+        content_hash: model.content_hash.clone(),
+        interfaces: model.interfaces.clone(),
+        coordinate_systems: model.coordinate_systems.clone(),
+        display: model.display.clone(),
+        clipping_planes: model.clipping_planes.clone(),
+    }
+}
+
+/// Load saved model content and put its edits back; interfaces are dropped when the content no longer matches
+pub(crate) fn restore_model(
+    models: &State<'_, ModelStore>,
+    transforms: &State<'_, PartTransformStore>,
+    recent: &State<'_, RecentFilesState>,
+    saved: ModelSnapshot,
+    content: String,
+) -> Result<RecoveredModel, String> {
+    let changed_on_disk = hash_content(content.as_bytes()) != saved.content_hash;
+    let info = load_model(models.clone(), transforms.clone(), recent.clone(), Some(content), saved.path.clone(), Some(saved.filename.clone()))?;
+    models.with_model_mut(&info.handle, |model| {
+        if !changed_on_disk {
+            model.interfaces = saved.interfaces;
+        }
+        model.coordinate_systems = saved.coordinate_systems;
+        model.display = saved.display;
+        model.clipping_planes = saved.clipping_planes;
+    })?;
+    Ok(RecoveredModel { previous_handle: saved.handle, model: info, changed_on_disk })
+}
+
+/// Collect the current session, keeping a copy of models that only exist in memory
+fn capture_snapshot(
+    state: &AutosaveState,
+    models: &ModelStore,
+    session: Option<&SessionState>,
+    encryption: &EncryptionState,
+) -> Result<SessionSnapshot, String> {
+    let models = models.map_models(|model| {
+        let content_hash = model.content_hash.clone();
+        let on_disk = model.path.as_deref().map(|p| Path::new(p).is_file()).unwrap_or(false);
+        if !on_disk {
+            let copy = state.content_path(&content_hash);
+            if !copy.exists() {
+                if let Err(e) = encryption.write(&copy, model.content.as_bytes()) {
+                    tracing::warn!(filename = %model.filename, error = %e, "failed to keep model copy for recovery");
+                }
+            }
+        }
+        model_snapshot(model)
+    })?;
+
+    Ok(SessionSnapshot {
+        saved_at: 0,
+        models,
+        active_handle: session.and_then(|s| s.shared_handle()),
+        stackups: state.drafts(),
+    })
+}
+
+/// Snapshot the session now if anything changed
+pub fn autosave_now(app: &AppHandle) -> Result<bool, String> {
+    let state = app.try_state::<AutosaveState>().ok_or("Autosave is not initialized")?;
+    let models = app.try_state::<ModelStore>().ok_or("Model store is not initialized")?;
+    let session = app.try_state::<SessionState>();
+    let encryption = app.try_state::<EncryptionState>().ok_or("Encryption is not initialized")?;
+    let snapshot = capture_snapshot(&state, &models, session.as_deref(), &encryption)?;
+    state.write(snapshot)
+}
+
+/// Snapshot the session in the background for as long as the app runs
+pub fn start_autosave(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(AUTOSAVE_INTERVAL);
+        match autosave_now(&app) {
+            Ok(true) => tracing::debug!("session autosaved"),
+            Ok(false) => {}
+            Err(e) => tracing::warn!(error = %e, "autosave failed"),
+        }
+    });
+}
+
+/// Drop the running snapshot on a clean exit so the next launch does not offer recovery
+pub fn mark_clean_exit(app: &AppHandle) {
+    if let Some(state) = app.try_state::<AutosaveState>() {
+        let _ = std::fs::remove_file(state.dir.join(SNAPSHOT_FILE));
+        state.prune_content();
+        tracing::info!("clean exit; session snapshot removed");
+    }
+}
+
+/// Keep an in-progress stackup so it survives a crash
+#[tauri::command]
+pub fn save_stackup_draft(
+    state: State<'_, AutosaveState>,
+    journal: State<'_, EditJournal>,
+    id: String,
+    name: Option<String>,
+    input: ToleranceInput,
+) -> Result<(), String> {
+    let draft = StackupDraft { id: id.clone(), name, input, updated_at: unix_timestamp() };
+    let before = state.put_draft(draft.clone())?;
+    journal.record("Edit stackup", Edit::StackupDraft { id, before, after: Some(draft) });
+    Ok(())
+}
+
+/// Forget a stackup draft once it is finished or abandoned
+#[tauri::command]
+pub fn discard_stackup_draft(
+    state: State<'_, AutosaveState>,
+    journal: State<'_, EditJournal>,
+    id: String,
+) -> Result<bool, String> {
+    let Some(before) = state.remove_draft(&id)? else { return Ok(false) };
+    journal.record("Discard stackup", Edit::StackupDraft { id, before: Some(before), after: None });
+    Ok(true)
+}
+
+/// What a crashed session left behind, if anything
+#[tauri::command]
+pub fn check_session_recovery(state: State<'_, AutosaveState>) -> Option<RecoveryInfo> {
+    state.pending().map(|snapshot| RecoveryInfo {
+        saved_at: snapshot.saved_at,
+        filenames: snapshot.models.iter().map(|m| m.filename.clone()).collect(),
+        stackups: snapshot.stackups.len(),
+    })
+}
+
+/// Reload the models and stackup drafts of a crashed session
+#[tauri::command]
+pub fn recover_session(
+    state: State<'_, AutosaveState>,
+    models: State<'_, ModelStore>,
+    transforms: State<'_, PartTransformStore>,
+    recent: State<'_, RecentFilesState>,
+    encryption: State<'_, EncryptionState>,
+) -> Result<RecoveredSession, String> {
+    let snapshot = state.pending().ok_or("There is no session to recover")?;
+    let mut recovered = RecoveredSession {
+        saved_at: snapshot.saved_at,
+        models: Vec::new(),
+        failed: Vec::new(),
+        active_handle: None,
+        stackups: snapshot.stackups.clone(),
+    };
+
+    for saved in snapshot.models {
+        // The file on disk wins; the recovery copy covers models that never had one
+        let copy = state.content_path(&saved.content_hash);
+        let content = match saved.path.as_deref().and_then(|p| std::fs::read_to_string(p).ok()) {
+            Some(content) => Ok(content),
+            None if copy.exists() => encryption.read_to_string(&copy),
+            None => Err("File is no longer available".to_string()),
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(error) => {
+                recovered.failed.push(RecoveryFailure { filename: saved.filename, error });
+                continue;
+            }
+        };
+        let filename = saved.filename.clone();
+        match restore_model(&models, &transforms, &recent, saved, content) {
+            Ok(model) => {
+                if snapshot.active_handle.as_deref() == Some(model.previous_handle.as_str()) {
+                    recovered.active_handle = Some(model.model.handle.clone());
+                }
+                recovered.models.push(model);
+            }
+            Err(error) => recovered.failed.push(RecoveryFailure { filename, error }),
+        }
+    }
+
+    {
+        let mut drafts = state.drafts.lock().map_err(|_| "Autosave state poisoned".to_string())?;
+        for draft in &recovered.stackups {
+            if !drafts.iter().any(|d| d.id == draft.id) {
+                drafts.push(draft.clone());
+            }
+        }
+    }
+    state.discard_recovery();
+
+    tracing::info!(models = recovered.models.len(), failed = recovered.failed.len(), "session recovered");
+    Ok(recovered)
+}
+
+/// Decline recovery and delete the crashed session's snapshot
+#[tauri::command]
+pub fn discard_session_recovery(state: State<'_, AutosaveState>) -> bool {
+    state.discard_recovery()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_survives_crash_but_not_clean_exit() {
+        let dir = std::env::temp_dir().join(format!("ohmframe-autosave-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let state = AutosaveState::new(dir.clone());
+        let snapshot = SessionSnapshot {
+            models: vec![ModelSnapshot {
+                handle: "model-1".to_string(),
+                filename: "bracket.step".to_string(),
+                path: None,
+                content_hash: "abc".to_string(),
+                interfaces: None,
+                coordinate_systems: Vec::new(),
+                display: PartDisplayState::default(),
+                clipping_planes: Vec::new(),
+            }],
+            ..Default::default()
+        };
+        std::fs::write(state.content_path("abc"), "ISO-10303-21;").unwrap();
+        std::fs::write(state.content_path("stale"), "ISO-10303-21;").unwrap();
+        assert!(state.write(snapshot.clone()).unwrap());
+        assert!(!state.write(snapshot).unwrap());
+        assert!(state.pending().is_none());
+
+        // Next launch after a crash: the snapshot becomes recoverable
+        let relaunched = AutosaveState::new(dir.clone());
+        let pending = relaunched.pending().unwrap();
+        assert_eq!(pending.models[0].filename, "bracket.step");
+        assert!(pending.saved_at > 0);
+
+        relaunched.prune_content();
+        assert!(relaunched.content_path("abc").exists());
+        assert!(!relaunched.content_path("stale").exists());
+
+        assert!(relaunched.discard_recovery());
+        assert!(relaunched.pending().is_none());
+        assert!(!relaunched.content_path("abc").exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
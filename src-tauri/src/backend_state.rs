@@ -0,0 +1,127 @@
+// Snapshot of what the backend is holding, for diagnosing memory use and stuck work
+
+use serde::{Deserialize, Serialize};
+use std::mem::size_of;
+use tauri::State;
+
+use crate::assembly_parser::{ParsedFace, ParsedPart};
+use crate::interface_detection::DetectedInterface;
+use crate::jobs::{active_jobs, ActiveJob};
+use crate::large_files::count_entities;
+use crate::linalg::Vec3;
+use crate::metrics::process_memory;
+use crate::model_store::{LoadedModel, ModelStore};
+use crate::FaceGroup;
+
+/// One loaded model and what is cached for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedModelState {
+    pub handle: String,
+    pub filename: String,
+    pub path: Option<String>,
+    pub bytes: usize,                 // STEP text kept for re-analysis
+    pub entities: usize,
+    pub parts: usize,
+    pub triangles: usize,
+    pub cached: Vec<String>,          // Results held beyond the parse, e.g. "interfaces" or "scan"
+    pub approximate_memory: usize,    // Bytes, from the sizes of the held buffers
+    pub loaded_at: u64,
+}
+
+/// What the backend holds right now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendState {
+    pub process_memory: u64,          // Resident memory in bytes (0 if unavailable)
+    pub models_memory: usize,         // Sum of the model estimates
+    pub models: Vec<LoadedModelState>,
+    pub active_jobs: Vec<ActiveJob>,
+}
+
+fn faces_bytes(faces: &[ParsedFace]) -> usize {
+    faces.iter().map(|f| size_of::<ParsedFace>() + f.face_type.len()).sum()
+}
+
+fn parts_bytes(parts: &[ParsedPart]) -> usize {
+    parts.iter().map(|p| size_of::<ParsedPart>() + p.id.len() + p.name.len() + faces_bytes(&p.faces)).sum()
+}
+
+/// Approximate heap use of a model; counts the large buffers, not allocator overhead
+pub fn approximate_model_memory(model: &LoadedModel) -> usize {
+    let mesh = model.mesh.as_ref().map_or(0, |mesh| {
+        4 * (mesh.vertices.len() + mesh.indices.len() + mesh.normals.len() + mesh.triangle_face_ids.len())
+            + mesh.face_groups.iter().map(|g| size_of::<FaceGroup>() + g.face_type.len()).sum::<usize>()
+    });
+    let interfaces = model.interfaces.as_ref().map_or(0, |r| r.interfaces.len() * size_of::<DetectedInterface>());
+    let scan = model.scan.as_ref().map_or(0, |s| s.points.len() * size_of::<Vec3>());
+
+    model.content.len() + mesh + parts_bytes(&model.assembly.parts) + interfaces + scan
+}
+
+fn cached_results(model: &LoadedModel) -> Vec<String> {
+    let mut cached = Vec::new();
+    if model.mesh.is_some() {
+        cached.push("mesh".to_string());
+    }
+    if !model.assembly.parts.is_empty() {
+        cached.push("assembly".to_string());
+    }
+    if model.interfaces.is_some() {
+        cached.push("interfaces".to_string());
+    }
+    if !model.gdt.frames.is_empty() || !model.gdt.controls.is_empty() {
+        cached.push("gdt".to_string());
+    }
+    if model.scan.is_some() {
+        cached.push("scan".to_string());
+    }
+    if !model.coordinate_systems.is_empty() {
+        cached.push("coordinate_systems".to_string());
+    }
+    cached
+}
+
+/// State of one loaded model
+pub fn model_state(model: &LoadedModel) -> LoadedModelState {
+    LoadedModelState {
+        handle: model.handle.clone(),
+        filename: model.filename.clone(),
+        path: model.path.clone(),
+        bytes: model.content.len(),
+        entities: count_entities(&model.content),
+        parts: model.assembly.parts.len(),
+        triangles: model.mesh.as_ref().map_or(0, |m| m.indices.len() / 3),
+        cached: cached_results(model),
+        approximate_memory: approximate_model_memory(model),
+        loaded_at: model.loaded_at,
+    }
+}
+
+/// Loaded models, cached results, memory and running jobs
+#[tauri::command]
+pub fn get_backend_state(state: State<'_, ModelStore>) -> Result<BackendState, String> {
+    let models = state.map_models(model_state)?;
+    Ok(BackendState {
+        process_memory: process_memory(),
+        models_memory: models.iter().map(|m| m.approximate_memory).sum(),
+        models,
+        active_jobs: active_jobs(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_state() {
+        let content = "ISO-10303-21;\nHEADER;\nFILE_NAME('block.step','',(''),(''),'','CAD','');\nENDSEC;\nDATA;\n#1=CARTESIAN_POINT('',(0.,0.,0.));\n#2=CARTESIAN_POINT('',(10.,20.,5.));\nENDSEC;\nEND-ISO-10303-21;";
+        let model = LoadedModel::parse("model-1".to_string(), content.to_string(), "block.step".to_string(), None);
+        let state = model_state(&model);
+
+        assert_eq!(state.entities, 2);
+        assert_eq!(state.bytes, content.len());
+        assert!(state.approximate_memory >= content.len());
+        assert_eq!(state.cached.contains(&"mesh".to_string()), model.mesh.is_some());
+        assert!(!state.cached.contains(&"interfaces".to_string()));
+    }
+}
@@ -0,0 +1,346 @@
+// Bearing seats: shafts and bores sized for standard rolling bearings, checked against recommended fits
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::ParsedPart;
+use crate::features::{recognize_part_bosses, recognize_part_holes};
+use crate::hardware::{hardware_library, match_part, HardwareCategory, BEARINGS};
+use crate::iso_fits::{tolerance_zone, ToleranceZone};
+use crate::linalg::Vec3;
+use crate::model_store::ModelStore;
+use crate::oring_grooves::CheckStatus;
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// Largest gap between a modelled diameter and a bearing size that still counts as a seat (mm)
+const MATCH_TOLERANCE: f64 = 0.05;
+
+// ISO 492 normal tolerance class: (size range upper bound, lower deviation of the mean diameter in µm)
+const BORE_DEVIATION: [(f64, f64); 6] = [(10.0, -8.0), (18.0, -8.0), (30.0, -10.0), (50.0, -12.0), (80.0, -15.0), (120.0, -20.0)];
+const OUTSIDE_DEVIATION: [(f64, f64); 6] = [(18.0, -8.0), (30.0, -9.0), (50.0, -11.0), (80.0, -13.0), (120.0, -15.0), (150.0, -18.0)];
+
+/// Which ring the seat carries
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeatKind {
+    Shaft,    // Inner ring on a journal
+    Housing,  // Outer ring in a bore
+}
+
+/// A journal or bore whose diameter matches a standard bearing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BearingSeat {
+    pub id: String,
+    pub part_id: String,
+    pub kind: SeatKind,
+    pub face_ids: Vec<i64>,
+    pub diameter: f64,           // As modelled
+    pub nominal_diameter: f64,   // Bearing bore (shaft) or outside diameter (housing)
+    pub center: Vec3,
+    pub axis: Vec3,
+    pub bearings: Vec<String>,   // Designations with this ring size, e.g. ["6000", "6200"]
+}
+
+fn default_rotation() -> String {
+    "inner".to_string()
+}
+
+fn default_load() -> String {
+    "normal".to_string()
+}
+
+/// Tolerance specified for one seat, as a zone code or as deviations from nominal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatTolerance {
+    pub seat_id: String,
+    #[serde(default)]
+    pub zone: Option<String>,             // e.g. "k6" on a shaft or "H7" in a housing
+    #[serde(default)]
+    pub deviations: Option<[f64; 2]>,     // Upper and lower deviation (mm)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BearingSeatInput {
+    #[serde(default = "default_rotation")]
+    pub rotation: String,          // Ring that turns relative to the load: "inner" or "outer"
+    #[serde(default = "default_load")]
+    pub load: String,              // "light", "normal" or "heavy"
+    #[serde(default)]
+    pub tolerances: Vec<SeatTolerance>,
+}
+
+impl Default for BearingSeatInput {
+    fn default() -> Self {
+        BearingSeatInput { rotation: default_rotation(), load: default_load(), tolerances: Vec::new() }
+    }
+}
+
+impl Validate for BearingSeatInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.one_of("rotation", &self.rotation, &["inner", "outer"]);
+        errors.one_of("load", &self.load, &["light", "normal", "heavy"]);
+        for (i, t) in self.tolerances.iter().enumerate() {
+            match (&t.zone, t.deviations) {
+                (Some(zone), _) => {
+                    if let Err(e) = tolerance_zone(10.0, zone) {
+                        errors.add(format!("tolerances[{}].zone", i), e);
+                    }
+                }
+                (None, Some([upper, lower])) => {
+                    errors.finite(format!("tolerances[{}].deviations", i), upper);
+                    errors.finite(format!("tolerances[{}].deviations", i), lower);
+                    if upper < lower {
+                        errors.add(format!("tolerances[{}].deviations", i), "expected the upper deviation first");
+                    }
+                }
+                (None, None) => errors.add(format!("tolerances[{}]", i), "expected a zone or deviations"),
+            }
+        }
+    }
+}
+
+/// A seat against its recommended zone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatCheck {
+    pub seat: BearingSeat,
+    pub recommended: ToleranceZone,
+    pub actual: ToleranceZone,        // Zone "model" when no tolerance was given: the modelled size only
+    pub deviation: [f64; 2],          // Actual minus recommended, upper and lower deviation (mm)
+    pub interference: [f64; 2],       // Least and most with a normal-class ring; negative is clearance
+    pub status: CheckStatus,
+    pub messages: Vec<String>,
+}
+
+/// Zone bearing makers recommend for a ball bearing seat of this kind and size
+pub fn recommended_zone(kind: SeatKind, nominal: f64, rotation: &str, load: &str) -> &'static str {
+    match (kind, rotation, load) {
+        // Rotating inner ring load: tight on the shaft, loose in the housing
+        (SeatKind::Shaft, "inner", "light") if nominal <= 18.0 => "h5",
+        (SeatKind::Shaft, "inner", "light") if nominal <= 100.0 => "js6",
+        (SeatKind::Shaft, "inner", "light") => "k6",
+        (SeatKind::Shaft, "inner", "heavy") if nominal <= 100.0 => "m6",
+        (SeatKind::Shaft, "inner", "heavy") => "n6",
+        (SeatKind::Shaft, "inner", _) if nominal <= 18.0 => "js6",
+        (SeatKind::Shaft, "inner", _) if nominal <= 100.0 => "k6",
+        (SeatKind::Shaft, "inner", _) => "m6",
+        (SeatKind::Housing, "inner", "heavy") => "JS7",
+        (SeatKind::Housing, "inner", _) => "H7",
+        // Rotating outer ring load (e.g. a wheel hub): loose on the shaft, tight in the housing
+        (SeatKind::Shaft, _, "light") => "g6",
+        (SeatKind::Shaft, _, _) => "h6",
+        (SeatKind::Housing, _, "light") => "M7",
+        (SeatKind::Housing, _, "heavy") => "P7",
+        (SeatKind::Housing, _, _) => "N7",
+    }
+}
+
+/// Lower deviation of a normal-class ring (mm); the upper deviation is 0
+fn ring_deviation(kind: SeatKind, nominal: f64) -> f64 {
+    let table = match kind {
+        SeatKind::Shaft => &BORE_DEVIATION,
+        SeatKind::Housing => &OUTSIDE_DEVIATION,
+    };
+    table.iter().find(|(max, _)| nominal <= *max).unwrap_or(&table[table.len() - 1]).1 / 1000.0
+}
+
+fn matching_bearings(kind: SeatKind, diameter: f64) -> Option<(f64, Vec<String>)> {
+    let size = |b: &(&str, f64, f64, f64)| match kind {
+        SeatKind::Shaft => b.1,
+        SeatKind::Housing => b.2,
+    };
+    let nominal = BEARINGS.iter().map(size).min_by(|a, b| (a - diameter).abs().total_cmp(&(b - diameter).abs()))?;
+    if (nominal - diameter).abs() > MATCH_TOLERANCE {
+        return None;
+    }
+    let designations = BEARINGS.iter().filter(|b| size(b) == nominal).map(|b| b.0.to_string()).collect();
+    Some((nominal, designations))
+}
+
+/// Journals (bosses) sized for a bearing bore and bores sized for a bearing outside diameter
+pub fn recognize_part_seats(part: &ParsedPart) -> Vec<BearingSeat> {
+    let journals = recognize_part_bosses(part).into_iter().map(|f| (SeatKind::Shaft, f));
+    let bores = recognize_part_holes(part).into_iter().map(|f| (SeatKind::Housing, f));
+    journals.chain(bores)
+        .filter_map(|(kind, feature)| {
+            let (nominal_diameter, bearings) = matching_bearings(kind, feature.diameter)?;
+            Some(BearingSeat {
+                id: String::new(),
+                part_id: part.id.clone(),
+                kind,
+                face_ids: feature.face_ids,
+                diameter: feature.diameter,
+                nominal_diameter,
+                center: feature.center,
+                axis: feature.axis,
+                bearings,
+            })
+        })
+        .enumerate()
+        .map(|(i, seat)| BearingSeat { id: format!("{}-seat-{}", part.id, i + 1), ..seat })
+        .collect()
+}
+
+/// Compare a seat's tolerance with the recommended zone and work out the fit with the ring
+pub fn check_seat(seat: &BearingSeat, given: Option<&SeatTolerance>, input: &BearingSeatInput) -> Result<SeatCheck, String> {
+    let nominal = seat.nominal_diameter;
+    let code = recommended_zone(seat.kind, nominal, &input.rotation, &input.load);
+    let (recommended, _) = tolerance_zone(nominal, code)?;
+    let mut messages = Vec::new();
+    let mut status = CheckStatus::Pass;
+
+    let from_deviations = |code: &str, upper: f64, lower: f64| ToleranceZone {
+        code: code.to_string(),
+        upper_deviation: upper,
+        lower_deviation: lower,
+        max_size: nominal + upper,
+        min_size: nominal + lower,
+        tolerance: upper - lower,
+    };
+    let actual = match given {
+        Some(SeatTolerance { zone: Some(zone), .. }) => {
+            let (actual, is_hole) = tolerance_zone(nominal, zone)?;
+            if is_hole != (seat.kind == SeatKind::Housing) {
+                status = CheckStatus::Fail;
+                messages.push(format!("{} is a {} zone but the seat is a {}", zone, if is_hole { "hole" } else { "shaft" }, if is_hole { "journal" } else { "bore" }));
+            }
+            actual
+        }
+        Some(SeatTolerance { deviations: Some([upper, lower]), .. }) => from_deviations("given", *upper, *lower),
+        _ => {
+            messages.push(format!("No tolerance given; only the modelled size is checked against {}", code));
+            let offset = seat.diameter - nominal;
+            from_deviations("model", offset, offset)
+        }
+    };
+
+    let deviation = [actual.upper_deviation - recommended.upper_deviation, actual.lower_deviation - recommended.lower_deviation];
+    let above = deviation[0] > 1e-9;
+    let below = deviation[1] < -1e-9;
+    let overlaps = actual.lower_deviation <= recommended.upper_deviation + 1e-9
+        && actual.upper_deviation >= recommended.lower_deviation - 1e-9;
+    if !overlaps {
+        let worst = if actual.code == "model" { CheckStatus::Warn } else { CheckStatus::Fail };
+        status = status.max(worst);
+        messages.push(format!(
+            "{} lies entirely {} the recommended {} ({:+.3}/{:+.3} mm)",
+            actual.code,
+            if above { "above" } else { "below" },
+            code,
+            recommended.upper_deviation,
+            recommended.lower_deviation
+        ));
+    } else if above || below {
+        status = status.max(CheckStatus::Warn);
+        if above {
+            messages.push(format!("Upper deviation is {:.0} µm above {}", deviation[0] * 1000.0, code));
+        }
+        if below {
+            messages.push(format!("Lower deviation is {:.0} µm below {}", -deviation[1] * 1000.0, code));
+        }
+    }
+
+    // Ring diameters run from nominal down to the lower deviation
+    let ring = ring_deviation(seat.kind, nominal);
+    let interference = match seat.kind {
+        SeatKind::Shaft => [actual.lower_deviation, actual.upper_deviation - ring],
+        SeatKind::Housing => [ring - actual.upper_deviation, -actual.lower_deviation],
+    };
+    let wants_interference = matches!((seat.kind, input.rotation.as_str()), (SeatKind::Shaft, "inner") | (SeatKind::Housing, "outer"));
+    if wants_interference && interference[1] <= 0.0 {
+        status = status.max(CheckStatus::Fail);
+        messages.push("The ring turns under the load but can never be tight on this seat; it will creep".to_string());
+    }
+
+    Ok(SeatCheck { seat: seat.clone(), recommended, actual, deviation, interference, status, messages })
+}
+
+/// Find bearing seats on a loaded model and check their tolerances against recommended fits
+#[tauri::command]
+pub fn check_bearing_seats(
+    state: State<'_, ModelStore>,
+    handle: String,
+    input: Checked<BearingSeatInput>,
+) -> Result<Vec<SeatCheck>, String> {
+    state.with_model(&handle, |model| {
+        let library = hardware_library();
+        let checks = model.assembly.parts.iter()
+            // The bearings themselves have ring-sized cylinders too
+            .filter(|part| !match_part(part, &library).is_some_and(|m| m.category == HardwareCategory::Bearing))
+            .flat_map(recognize_part_seats)
+            .map(|seat| check_seat(&seat, input.tolerances.iter().find(|t| t.seat_id == seat.id), &input))
+            .collect::<Result<Vec<_>, String>>()?;
+        tracing::info!(
+            handle = %handle,
+            seats = checks.len(),
+            failed = checks.iter().filter(|c| c.status == CheckStatus::Fail).count(),
+            "bearing seats checked"
+        );
+        Ok(checks)
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seat(kind: SeatKind, nominal: f64) -> BearingSeat {
+        BearingSeat {
+            id: "s".to_string(),
+            part_id: "p".to_string(),
+            kind,
+            face_ids: vec![1],
+            diameter: nominal,
+            nominal_diameter: nominal,
+            center: [0.0; 3],
+            axis: [0.0, 0.0, 1.0],
+            bearings: matching_bearings(kind, nominal).unwrap().1,
+        }
+    }
+
+    fn zone(code: &str) -> SeatTolerance {
+        SeatTolerance { seat_id: "s".to_string(), zone: Some(code.to_string()), deviations: None }
+    }
+
+    #[test]
+    fn test_shaft_seat_against_k6() {
+        let input = BearingSeatInput::default();
+        let shaft = seat(SeatKind::Shaft, 20.0);
+        assert_eq!(shaft.bearings, vec!["6004", "6204"]);
+
+        // k6 at 20 mm is +2/+15 µm; the 6004 bore is 0/-10 µm
+        let check = check_seat(&shaft, Some(&zone("k6")), &input).unwrap();
+        assert_eq!(check.recommended.code, "k6");
+        assert_eq!(check.status, CheckStatus::Pass);
+        assert!((check.interference[0] - 0.002).abs() < 1e-9 && (check.interference[1] - 0.025).abs() < 1e-9);
+
+        // js6 (±6.5 µm) overlaps k6 but reaches below it, h6 lies wholly below it, f6 can never grip
+        // the rotating ring
+        assert_eq!(check_seat(&shaft, Some(&zone("js6")), &input).unwrap().status, CheckStatus::Warn);
+        assert_eq!(check_seat(&shaft, Some(&zone("h6")), &input).unwrap().status, CheckStatus::Fail);
+        let loose = check_seat(&shaft, Some(&zone("f6")), &input).unwrap();
+        assert_eq!(loose.status, CheckStatus::Fail);
+        assert!(loose.messages.iter().any(|m| m.contains("creep")));
+
+        // A hole zone on a journal is a mistake
+        assert_eq!(check_seat(&shaft, Some(&zone("H7")), &input).unwrap().status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_housing_seat_and_modelled_size() {
+        let input = BearingSeatInput::default();
+        let housing = seat(SeatKind::Housing, 42.0);
+        assert_eq!(housing.bearings, vec!["6004", "6302"]);
+
+        let check = check_seat(&housing, Some(&zone("H7")), &input).unwrap();
+        assert_eq!((check.recommended.code.as_str(), check.status), ("H7", CheckStatus::Pass));
+        assert!((check.interference[0] + 0.025 + 0.011).abs() < 1e-9);
+
+        // Modelled at nominal, with no tolerance: inside H7 (0/+25 µm) but outside N7 for a rotating outer ring
+        assert_eq!(check_seat(&housing, None, &input).unwrap().status, CheckStatus::Pass);
+        let hub = BearingSeatInput { rotation: "outer".to_string(), ..input };
+        let modelled = check_seat(&housing, None, &hub).unwrap();
+        assert_eq!(modelled.recommended.code, "N7");
+        assert_eq!(modelled.status, CheckStatus::Fail);
+        assert!(matching_bearings(SeatKind::Housing, 43.0).is_none());
+    }
+}
@@ -0,0 +1,189 @@
+// Timed run of the STEP pipeline on one file, for attaching performance data to issues
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+use tauri::AppHandle;
+
+use crate::assembly_parser::parse_assembly_text;
+use crate::interface_detection::{find_mating_interfaces, DetectionParams};
+use crate::jobs::{run_job, Job, JobLogLevel};
+use crate::metrics::process_memory;
+use crate::step_entities::StepEntities;
+use crate::storage::unix_timestamp;
+
+/// Stages in pipeline order
+const STAGES: [&str; 6] = ["read", "entity_scan", "analysis", "tessellation", "assembly_parse", "interface_detection"];
+
+/// Timing of one pipeline stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkStage {
+    pub name: String,
+    pub duration_ms: f64,
+    pub items: usize,                 // What the stage produced: bytes, entities, triangles, parts or interfaces
+    pub memory_after: u64,            // Resident memory in bytes once the stage finished
+    pub skipped: Option<String>,      // Why the stage did not run
+}
+
+/// Machine the benchmark ran on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkEnvironment {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub threads: usize,
+}
+
+/// Stage-by-stage breakdown of a benchmark run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkResult {
+    pub filename: String,
+    pub bytes: usize,
+    pub timestamp: u64,
+    pub total_ms: f64,
+    pub memory_before: u64,
+    pub stages: Vec<BenchmarkStage>,
+    pub environment: BenchmarkEnvironment,
+}
+
+fn environment() -> BenchmarkEnvironment {
+    BenchmarkEnvironment {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        threads: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+    }
+}
+
+/// Collects stage timings and reports progress on the job
+struct StageTimer<'a> {
+    job: &'a Job,
+    stages: Vec<BenchmarkStage>,
+}
+
+impl StageTimer<'_> {
+    fn time<T>(&mut self, name: &str, f: impl FnOnce() -> (T, usize)) -> T {
+        self.job.progress(self.stages.len(), Some(STAGES.len()), Some(format!("Timing {}", name)));
+        let start = Instant::now();
+        let (value, items) = f();
+        let duration_ms = start.elapsed().as_secs_f64() * 1000.0;
+        self.job.log(JobLogLevel::Info, format!("{}: {:.1} ms", name, duration_ms));
+        self.stages.push(BenchmarkStage {
+            name: name.to_string(),
+            duration_ms,
+            items,
+            memory_after: process_memory(),
+            skipped: None,
+        });
+        value
+    }
+
+    fn skip(&mut self, name: &str, reason: String) {
+        self.stages.push(BenchmarkStage {
+            name: name.to_string(),
+            duration_ms: 0.0,
+            items: 0,
+            memory_after: process_memory(),
+            skipped: Some(reason),
+        });
+    }
+}
+
+/// Run every pipeline stage on a file and time each one
+pub fn benchmark_file(path: &Path, job: &Job) -> Result<BenchmarkResult, String> {
+    let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string();
+    let memory_before = process_memory();
+    let started = Instant::now();
+    let mut timer = StageTimer { job, stages: Vec::with_capacity(STAGES.len()) };
+
+    let content = timer.time("read", || {
+        let content = std::fs::read_to_string(path);
+        let bytes = content.as_ref().map(|c| c.len()).unwrap_or(0);
+        (content, bytes)
+    })
+    .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    timer.time("entity_scan", || ((), StepEntities::parse(&content).len()));
+
+    let analysis = timer.time("analysis", || {
+        let analysis = crate::analyze_step_text(&content, &filename);
+        let faces = analysis.topology.as_ref().map(|t| t.num_faces).unwrap_or(0);
+        (analysis, faces)
+    });
+    if !analysis.success {
+        return Err(analysis.error.unwrap_or_else(|| "Not a STEP file".to_string()));
+    }
+
+    match &analysis.partial {
+        Some(partial) => {
+            for stage in &STAGES[3..] {
+                timer.skip(stage, partial.reason.clone());
+            }
+        }
+        None => {
+            let mesh = timer.time("tessellation", || {
+                let mesh = crate::parse_step_to_mesh(&content, &analysis, &Default::default());
+                let triangles = mesh.as_ref().map(|(m, _, _)| m.indices.len() / 3).unwrap_or(0);
+                (mesh, triangles)
+            });
+            if let Err(e) = mesh {
+                timer.stages.last_mut().expect("tessellation stage").skipped = Some(e);
+            }
+
+            let assembly = timer.time("assembly_parse", || {
+                let assembly = parse_assembly_text(&content, &filename);
+                let parts = assembly.parts.len();
+                (assembly, parts)
+            });
+
+            let params = DetectionParams::default();
+            timer.time("interface_detection", || {
+                let result = find_mating_interfaces(assembly.parts, params.proximity_threshold, params.normal_threshold);
+                ((), result.total_interfaces)
+            });
+        }
+    }
+
+    job.progress(STAGES.len(), Some(STAGES.len()), None);
+    Ok(BenchmarkResult {
+        filename,
+        bytes: content.len(),
+        timestamp: unix_timestamp(),
+        total_ms: started.elapsed().as_secs_f64() * 1000.0,
+        memory_before,
+        stages: timer.stages,
+        environment: environment(),
+    })
+}
+
+/// Time each pipeline stage on a STEP file
+#[tauri::command]
+pub async fn run_benchmark(app: AppHandle, file_path: String, job_id: Option<String>) -> Result<BenchmarkResult, String> {
+    let label = Path::new(&file_path).file_name().and_then(|n| n.to_str()).map(|s| s.to_string());
+    tracing::info!(path = %file_path, "running benchmark");
+    run_job(app, "run_benchmark", job_id, label, move |job| benchmark_file(Path::new(&file_path), job)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_benchmark_stages() {
+        let dir = std::env::temp_dir().join(format!("ohmframe-benchmark-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("block.step");
+        std::fs::write(&path, "ISO-10303-21;\nHEADER;\nFILE_NAME('block.step','',(''),(''),'','CAD','');\nENDSEC;\nDATA;\n#1=CARTESIAN_POINT('',(0.,0.,0.));\n#2=CARTESIAN_POINT('',(10.,20.,5.));\n#3=MANIFOLD_SOLID_BREP('Block',#4);\nENDSEC;\nEND-ISO-10303-21;").unwrap();
+
+        let job = Job::start(None, "run_benchmark", None, None);
+        let result = benchmark_file(&path, &job).unwrap();
+        let names: Vec<&str> = result.stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, STAGES);
+        assert_eq!(result.stages[0].items, result.bytes);
+        assert_eq!(result.stages[1].items, 3);
+        assert!(result.stages.iter().all(|s| s.duration_ms >= 0.0));
+
+        assert!(benchmark_file(&dir.join("missing.step"), &job).unwrap_err().contains("Failed to read"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
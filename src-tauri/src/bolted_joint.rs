@@ -0,0 +1,339 @@
+// Metric thread engagement and bolt preload/tightening torque estimates
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::hardware::{hardware_library, match_part, HardwareCategory};
+use crate::materials::{validate_material, Material};
+use crate::model_store::ModelStore;
+use crate::validation::{Checked, FieldErrors, Validate};
+
+// ISO 261 coarse pitches: nominal diameter, pitch
+const COARSE_PITCHES: [(f64, f64); 14] = [
+    (2.0, 0.4), (2.5, 0.45), (3.0, 0.5), (4.0, 0.7), (5.0, 0.8), (6.0, 1.0), (8.0, 1.25),
+    (10.0, 1.5), (12.0, 1.75), (14.0, 2.0), (16.0, 2.0), (20.0, 2.5), (24.0, 3.0), (30.0, 3.5),
+];
+
+/// ISO 898-1 property classes
+const PROPERTY_CLASSES: [&str; 8] = ["4.6", "5.6", "5.8", "6.8", "8.8", "9.8", "10.9", "12.9"];
+
+const SHEAR_TO_TENSILE: f64 = 0.577;  // Von Mises shear strength ratio
+
+fn default_friction() -> f64 {
+    0.12  // Lightly oiled, zinc plated
+}
+
+fn default_preload_fraction() -> f64 {
+    0.75
+}
+
+/// Basic dimensions of an ISO metric thread (mm)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricThread {
+    pub code: String,
+    pub diameter: f64,
+    pub pitch: f64,
+    pub pitch_diameter: f64,
+    pub minor_diameter: f64,  // Internal thread D1
+    pub stress_area: f64,     // mm²
+}
+
+/// Parse "M8" (coarse pitch) or "M8x1" (fine pitch)
+pub fn parse_thread(code: &str) -> Result<MetricThread, String> {
+    let body = code.trim().strip_prefix(['M', 'm'])
+        .ok_or_else(|| format!("Thread \"{}\" should look like M8 or M8x1", code))?;
+    let (diameter, pitch) = match body.split_once(['x', 'X']) {
+        Some((d, p)) => (d.parse::<f64>().ok(), p.parse::<f64>().ok()),
+        None => {
+            let d = body.parse::<f64>().ok();
+            (d, d.and_then(|d| COARSE_PITCHES.iter().find(|(nominal, _)| *nominal == d).map(|(_, p)| *p)))
+        }
+    };
+    let diameter = diameter.filter(|d| *d > 0.0).ok_or_else(|| format!("Invalid thread diameter in \"{}\"", code))?;
+    let pitch = pitch.filter(|p| *p > 0.0 && *p < diameter / 2.0)
+        .ok_or_else(|| format!("No pitch for \"{}\"; give it explicitly, e.g. M{}x1", code, diameter))?;
+
+    Ok(MetricThread {
+        code: code.trim().to_string(),
+        diameter,
+        pitch,
+        pitch_diameter: diameter - 0.6495 * pitch,
+        minor_diameter: diameter - 1.0825 * pitch,
+        stress_area: std::f64::consts::FRAC_PI_4 * (diameter - 0.9382 * pitch).powi(2),
+    })
+}
+
+/// Nominal tensile and yield strength (MPa) of a property class: "8.8" is 800 MPa and 80% of it
+fn class_strength(class: &str) -> Option<(f64, f64)> {
+    if !PROPERTY_CLASSES.contains(&class) {
+        return None;
+    }
+    let (whole, ratio) = class.split_once('.')?;
+    let tensile = whole.parse::<f64>().ok()? * 100.0;
+    Some((tensile, tensile * ratio.parse::<f64>().ok()? / 10.0))
+}
+
+fn validate_bolt(thread: &str, property_class: &str, errors: &mut FieldErrors) {
+    if let Err(e) = parse_thread(thread) {
+        errors.add("thread", e);
+    }
+    errors.one_of("property_class", property_class, &PROPERTY_CLASSES);
+}
+
+/// Bolt and the material it threads into
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadEngagementInput {
+    pub thread: String,
+    pub property_class: String,
+    pub internal_material: Material,
+}
+
+impl Validate for ThreadEngagementInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        validate_bolt(&self.thread, &self.property_class, errors);
+        validate_material(&self.internal_material, "internal_material", errors);
+    }
+}
+
+/// Engagement at which the threads carry the bolt's full tensile load
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThreadEngagement {
+    pub thread: MetricThread,
+    pub bolt_tensile_capacity: f64,  // N
+    pub external_strip_length: f64,  // Engagement to strip the bolt threads (mm)
+    pub internal_strip_length: f64,  // Engagement to strip the tapped threads (mm)
+    pub minimum_engagement: f64,
+    pub governing: String,           // "bolt" or "internal"
+}
+
+/// Thread stripping vs bolt tensile failure from basic thread dimensions
+pub fn compute_thread_engagement(thread: &MetricThread, property_class: &str, internal: &Material) -> ThreadEngagement {
+    let (bolt_tensile, _) = class_strength(property_class).unwrap_or((800.0, 640.0));
+    let capacity = thread.stress_area * bolt_tensile;
+
+    // Shear area per mm of engagement: bolt threads shear at D1, tapped threads at d
+    let pi = std::f64::consts::PI;
+    let external_area = 0.75 * pi * thread.minor_diameter;
+    let internal_area = 0.875 * pi * thread.diameter;
+    let external_strip_length = capacity / (SHEAR_TO_TENSILE * bolt_tensile * external_area);
+    let internal_strip_length = capacity / (SHEAR_TO_TENSILE * internal.tensile_strength * internal_area);
+
+    ThreadEngagement {
+        thread: thread.clone(),
+        bolt_tensile_capacity: capacity,
+        external_strip_length,
+        internal_strip_length,
+        minimum_engagement: external_strip_length.max(internal_strip_length),
+        governing: if internal_strip_length > external_strip_length { "internal" } else { "bolt" }.to_string(),
+    }
+}
+
+/// Bolt, preload target and friction for torque estimation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoltPreloadInput {
+    pub thread: String,
+    pub property_class: String,
+    #[serde(default = "default_preload_fraction")]
+    pub preload_fraction: f64,              // Share of the yield load in the stress area
+    #[serde(default = "default_friction")]
+    pub thread_friction: f64,
+    #[serde(default = "default_friction")]
+    pub head_friction: f64,
+    pub head_bearing_diameter: Option<f64>, // Outer diameter of the head contact; 1.5 d when absent
+    pub hole_diameter: Option<f64>,         // Clearance hole; 1.1 d when absent
+}
+
+impl Validate for BoltPreloadInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        validate_bolt(&self.thread, &self.property_class, errors);
+        errors.within("preload_fraction", self.preload_fraction, 0.05, 1.0);
+        errors.within("thread_friction", self.thread_friction, 0.0, 1.0);
+        errors.within("head_friction", self.head_friction, 0.0, 1.0);
+        if let Some(d) = self.head_bearing_diameter {
+            errors.above("head_bearing_diameter", d, 0.0);
+        }
+        if let Some(d) = self.hole_diameter {
+            errors.above("hole_diameter", d, 0.0);
+        }
+    }
+}
+
+/// Preload and the torque that produces it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoltPreload {
+    pub thread: MetricThread,
+    pub preload: f64,            // N
+    pub bolt_stress: f64,        // Tensile stress in the stress area (MPa)
+    pub tightening_torque: f64,  // N·m
+    pub nut_factor: f64,         // K in T = K F d
+    pub thread_torque_share: f64,
+}
+
+/// VDI 2230 tightening torque: pitch, thread friction and head friction terms
+pub fn compute_bolt_preload(thread: &MetricThread, input: &BoltPreloadInput) -> BoltPreload {
+    let (_, bolt_yield) = class_strength(&input.property_class).unwrap_or((800.0, 640.0));
+    let preload = input.preload_fraction * bolt_yield * thread.stress_area;
+
+    let bearing = input.head_bearing_diameter.unwrap_or(1.5 * thread.diameter);
+    let hole = input.hole_diameter.unwrap_or(1.1 * thread.diameter);
+    let thread_torque = preload * (0.16 * thread.pitch + 0.58 * thread.pitch_diameter * input.thread_friction);
+    let head_torque = preload * input.head_friction * (bearing + hole) / 4.0;
+    let torque = thread_torque + head_torque;
+
+    BoltPreload {
+        thread: thread.clone(),
+        preload,
+        bolt_stress: preload / thread.stress_area,
+        tightening_torque: torque / 1000.0,
+        nut_factor: torque / (preload * thread.diameter),
+        thread_torque_share: thread_torque / torque,
+    }
+}
+
+/// Minimum thread engagement length for a bolt in a tapped material
+#[tauri::command]
+pub fn calculate_thread_engagement(input: Checked<ThreadEngagementInput>) -> Result<ThreadEngagement, String> {
+    let thread = parse_thread(&input.thread)?;
+    Ok(compute_thread_engagement(&thread, &input.property_class, &input.internal_material))
+}
+
+/// Preload and tightening torque estimate for a bolt
+#[tauri::command]
+pub fn calculate_bolt_preload(input: Checked<BoltPreloadInput>) -> Result<BoltPreload, String> {
+    let thread = parse_thread(&input.thread)?;
+    Ok(compute_bolt_preload(&thread, &input))
+}
+
+/// Joint settings for checking a recognized fastener
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FastenerCheckInput {
+    pub property_class: String,
+    pub internal_material: Material,
+    pub engagement_length: Option<f64>,  // Actual thread engagement, when known
+    #[serde(default = "default_preload_fraction")]
+    pub preload_fraction: f64,
+    #[serde(default = "default_friction")]
+    pub thread_friction: f64,
+    #[serde(default = "default_friction")]
+    pub head_friction: f64,
+}
+
+impl Validate for FastenerCheckInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.one_of("property_class", &self.property_class, &PROPERTY_CLASSES);
+        validate_material(&self.internal_material, "internal_material", errors);
+        if let Some(length) = self.engagement_length {
+            errors.above("engagement_length", length, 0.0);
+        }
+        errors.within("preload_fraction", self.preload_fraction, 0.05, 1.0);
+        errors.within("thread_friction", self.thread_friction, 0.0, 1.0);
+        errors.within("head_friction", self.head_friction, 0.0, 1.0);
+    }
+}
+
+/// Engagement and preload for a fastener part of a loaded model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FastenerCheck {
+    pub part_id: String,
+    pub label: String,
+    pub engagement: ThreadEngagement,
+    pub preload: BoltPreload,
+    pub warnings: Vec<String>,
+}
+
+/// Check a part recognized as a fastener: thread and head size come from the hardware match
+#[tauri::command]
+pub fn check_fastener_joint(
+    state: State<'_, ModelStore>,
+    handle: String,
+    part_id: String,
+    input: Checked<FastenerCheckInput>,
+) -> Result<FastenerCheck, String> {
+    let library = hardware_library();
+    let found = state.with_model(&handle, |model| {
+        model.assembly.parts.iter()
+            .find(|p| p.id == part_id)
+            .map(|part| match_part(part, &library))
+            .ok_or_else(|| format!("Unknown part: {}", part_id))
+    })??;
+    let found = found
+        .filter(|m| m.category == HardwareCategory::Fastener)
+        .ok_or_else(|| format!("Part {} is not recognized as a fastener", part_id))?;
+    let signature = library.iter().find(|s| s.label == found.label)
+        .ok_or_else(|| format!("Unknown hardware: {}", found.label))?;
+
+    // Labels read "M5x16 SHCS": thread, then length
+    let size = found.label.split(['x', ' ']).next().unwrap_or_default();
+    let thread = parse_thread(size)?;
+    let engagement = compute_thread_engagement(&thread, &input.property_class, &input.internal_material);
+    let preload = compute_bolt_preload(&thread, &BoltPreloadInput {
+        thread: thread.code.clone(),
+        property_class: input.property_class.clone(),
+        preload_fraction: input.preload_fraction,
+        thread_friction: input.thread_friction,
+        head_friction: input.head_friction,
+        head_bearing_diameter: Some(signature.outer_diameter),
+        hole_diameter: None,
+    });
+
+    let mut warnings = Vec::new();
+    if let Some(length) = input.engagement_length {
+        if length < engagement.minimum_engagement {
+            warnings.push(format!(
+                "Engagement {:.1} mm is below the {:.1} mm needed; the {} threads strip before the bolt breaks",
+                length, engagement.minimum_engagement, engagement.governing
+            ));
+        }
+    }
+    if found.confidence < 0.5 {
+        warnings.push(format!("Low confidence ({:.2}) in the {} match", found.confidence, found.label));
+    }
+
+    Ok(FastenerCheck { part_id, label: found.label, engagement, preload, warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::material_library;
+
+    #[test]
+    fn test_parse_thread() {
+        let m10 = parse_thread("M10").unwrap();
+        assert_eq!(m10.pitch, 1.5);
+        assert!((m10.stress_area - 58.0).abs() < 0.1, "{}", m10.stress_area);
+        assert_eq!(parse_thread("M10x1.25").unwrap().pitch, 1.25);
+        assert!(parse_thread("M7").unwrap_err().contains("No pitch"));
+        assert!(parse_thread("10").is_err());
+        assert_eq!(class_strength("10.9"), Some((1000.0, 900.0)));
+    }
+
+    #[test]
+    fn test_soft_tapped_material_needs_longer_engagement() {
+        let thread = parse_thread("M8").unwrap();
+        let library = material_library();
+        let steel = compute_thread_engagement(&thread, "8.8", &library[0]);
+        let aluminium = compute_thread_engagement(&thread, "8.8", &library[3]);
+        assert_eq!(steel.governing, "bolt");
+        assert_eq!(aluminium.governing, "internal");
+        assert!(aluminium.minimum_engagement > 1.5 * steel.minimum_engagement);
+    }
+
+    #[test]
+    fn test_m10_torque() {
+        // Catalogue value for M10 8.8 at friction 0.12 is about 50 N·m
+        let input = BoltPreloadInput {
+            thread: "M10".to_string(),
+            property_class: "8.8".to_string(),
+            preload_fraction: 0.75,
+            thread_friction: 0.12,
+            head_friction: 0.12,
+            head_bearing_diameter: Some(16.0),
+            hole_diameter: Some(11.0),
+        };
+        let result = compute_bolt_preload(&parse_thread("M10").unwrap(), &input);
+        assert!((result.preload - 0.75 * 640.0 * 58.0).abs() < 100.0);
+        assert!((40.0..60.0).contains(&result.tightening_torque), "{}", result.tightening_torque);
+        assert!((0.15..0.22).contains(&result.nut_factor));
+    }
+}
@@ -0,0 +1,265 @@
+// B-rep tessellation: triangulate the actual face surfaces of every shell, one face group per ADVANCED_FACE
+
+use truck_meshalgo::prelude::*;
+use truck_stepio::r#in::Table;
+
+use crate::step_entities::StepEntities;
+use crate::step_format::{surface_class, SurfaceClass};
+use crate::{BoundingBox, FaceGroup};
+
+/// Chord tolerance as a fraction of the model diagonal
+const RELATIVE_TOLERANCE: f64 = 1e-3;
+/// Chord tolerance when the model size is unknown, in model units
+const DEFAULT_TOLERANCE: f64 = 0.01;
+
+/// Triangulated shells, before orientation and optimization
+#[derive(Debug, Clone, Default)]
+pub struct BrepMesh {
+    pub vertices: Vec<f32>,
+    pub indices: Vec<u32>,
+    pub normals: Vec<f32>,
+    pub face_groups: Vec<FaceGroup>,
+}
+
+/// Triangles of one face, each corner as (position, normal)
+type FaceTriangles = Vec<[([f64; 3], [f64; 3]); 3]>;
+
+/// Viewer face type from the surface an ADVANCED_FACE sits on
+fn face_type(entities: &StepEntities, face_id: i64) -> &'static str {
+    let surface = entities.get_typed(face_id, "ADVANCED_FACE")
+        .and_then(|face| face.param_ref(2))
+        .and_then(|id| surface_class(entities.instance(id).iter().map(|e| e.entity_type)));
+    match surface {
+        Some(SurfaceClass::Planar) => "planar",
+        Some(SurfaceClass::Cylindrical) => "cylindrical",
+        _ => "curved",
+    }
+}
+
+/// Chord tolerance for a model whose points span the given diagonal
+fn tolerance_for(diagonal: f64) -> f64 {
+    if diagonal.is_finite() && diagonal > 0.0 { diagonal * RELATIVE_TOLERANCE } else { DEFAULT_TOLERANCE }
+}
+
+/// Diagonal of the box around every CARTESIAN_POINT, which bounds the model before meshing
+fn point_diagonal(entities: &StepEntities) -> f64 {
+    let mut min = [f64::MAX; 3];
+    let mut max = [f64::MIN; 3];
+    for p in entities.of_type("CARTESIAN_POINT").filter_map(|e| e.triple()) {
+        for (k, c) in p.into_iter().enumerate() {
+            min[k] = min[k].min(c);
+            max[k] = max[k].max(c);
+        }
+    }
+    (0..3).map(|k| (max[k] - min[k]).powi(2)).sum::<f64>().sqrt()
+}
+
+impl BrepMesh {
+    /// Append one face's triangles (positions and per-corner normals) as a new face group
+    pub fn push_face(&mut self, face_id: u32, face_type: &str, triangles: &[[([f64; 3], [f64; 3]); 3]]) {
+        let start_index = self.indices.len() as u32;
+        let mut weighted = [0.0; 3];
+        let mut total_area = 0.0;
+
+        for triangle in triangles {
+            let [a, b, c] = triangle.map(|(p, _)| p);
+            let ab = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+            let ac = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+            let cross = [ab[1] * ac[2] - ab[2] * ac[1], ab[2] * ac[0] - ab[0] * ac[2], ab[0] * ac[1] - ab[1] * ac[0]];
+            let area = 0.5 * (cross[0] * cross[0] + cross[1] * cross[1] + cross[2] * cross[2]).sqrt();
+            for (k, w) in weighted.iter_mut().enumerate() {
+                *w += area * (a[k] + b[k] + c[k]) / 3.0;
+            }
+            total_area += area;
+
+            for (position, normal) in triangle {
+                // Corners without a surface normal take the flat triangle normal
+                let normal = if normal.iter().any(|n| *n != 0.0) { *normal } else { cross };
+                let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt().max(f64::MIN_POSITIVE);
+                self.indices.push((self.vertices.len() / 3) as u32);
+                self.vertices.extend(position.map(|v| v as f32));
+                self.normals.extend(normal.map(|n| (n / length) as f32));
+            }
+        }
+
+        let center = if total_area > 0.0 {
+            weighted.map(|w| w / total_area)
+        } else {
+            let corners = triangles.iter().flatten().map(|(p, _)| *p);
+            let count = triangles.len().max(1) as f64 * 3.0;
+            corners.fold([0.0; 3], |acc, p| [acc[0] + p[0], acc[1] + p[1], acc[2] + p[2]]).map(|s| s / count)
+        };
+
+        self.face_groups.push(FaceGroup {
+            face_id,
+            face_type: face_type.to_string(),
+            start_index,
+            triangle_count: triangles.len() as u32,
+            center,
+            cullable: false,
+        });
+    }
+
+    pub fn bounding_box(&self) -> BoundingBox {
+        let mut min = [f64::MAX; 3];
+        let mut max = [f64::MIN; 3];
+        for v in self.vertices.chunks_exact(3) {
+            for (k, &c) in v.iter().enumerate() {
+                min[k] = min[k].min(c as f64);
+                max[k] = max[k].max(c as f64);
+            }
+        }
+        BoundingBox { min, max, dimensions: [max[0] - min[0], max[1] - min[1], max[2] - min[2]] }
+    }
+}
+
+/// Triangulate one shell into (STEP face id, triangles) pairs. Face i of a converted shell is the
+/// i-th face listed by its CLOSED_SHELL / OPEN_SHELL record, which is how face groups get their ids
+fn tessellate_shell(table: &Table, entities: &StepEntities, shell_id: u64, tolerance: f64) -> Result<Vec<(i64, FaceTriangles)>, String> {
+    let shell = table.to_compressed_shell(&table.shell[&shell_id]).map_err(|e| e.to_string())?;
+    let face_ids: Vec<i64> = entities.get(shell_id as i64)
+        .map(|record| record.references().collect())
+        .unwrap_or_default();
+
+    let polygons = shell.robust_triangulation(tolerance);
+    let mut faces = Vec::new();
+    for (i, face) in polygons.faces.iter().enumerate() {
+        let Some(polygon) = &face.surface else { continue };
+        // A face the shell record does not list has no id to group it under
+        let Some(&face_id) = face_ids.as_slice().get(i) else {
+            tracing::debug!(shell = shell_id, face = i, "skipping face missing from the shell record");
+            continue;
+        };
+        let positions = polygon.positions();
+        let normals = polygon.normals();
+        let triangles: FaceTriangles = polygon.faces().triangle_iter()
+            .map(|corners| corners.map(|v| {
+                let p = positions[v.pos];
+                // Reversed faces come back already inverted by the triangulation
+                let n = v.nor.and_then(|n| normals.get(n)).map_or([0.0; 3], |n| [n.x, n.y, n.z]);
+                ([p.x, p.y, p.z], n)
+            }))
+            .collect();
+        if !triangles.is_empty() {
+            faces.push((face_id, triangles));
+        }
+    }
+    Ok(faces)
+}
+
+/// Tessellate every shell in the file, spreading shells over the available cores
+pub fn tessellate_brep(content: &str) -> Result<BrepMesh, String> {
+    let table = Table::from_step(content).ok_or("STEP data section could not be read as AP203/AP214 geometry")?;
+    let entities = StepEntities::parse(content);
+    let tolerance = tolerance_for(point_diagonal(&entities));
+
+    let mut shell_ids: Vec<u64> = table.shell.keys().copied().collect();
+    shell_ids.sort();
+    if shell_ids.is_empty() {
+        return Err("No shells to tessellate".to_string());
+    }
+
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(shell_ids.len());
+    let chunk_size = shell_ids.len().div_ceil(threads);
+    tracing::debug!(shells = shell_ids.len(), threads, "tessellating shells");
+
+    let (table, entities) = (&table, &entities);
+    let shells: Vec<Result<Vec<(i64, FaceTriangles)>, String>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = shell_ids.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                chunk.iter().map(|&shell_id| {
+                    tessellate_shell(table, entities, shell_id, tolerance).inspect_err(|e| {
+                        tracing::debug!(shell = shell_id, error = %e, "skipping shell the B-rep kernel cannot convert");
+                    })
+                }).collect::<Vec<_>>()
+            }))
+            .collect();
+        workers.into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect()
+    });
+
+    let mut mesh = BrepMesh::default();
+    let mut failed_shells = 0;
+    let mut unaddressable_faces = 0;
+    for shell in shells {
+        let Ok(faces) = shell else {
+            failed_shells += 1;
+            continue;
+        };
+        for (face_id, triangles) in faces {
+            // Face groups carry 32-bit ids; a face whose STEP id does not fit cannot be picked
+            let Ok(group_id) = u32::try_from(face_id) else {
+                unaddressable_faces += 1;
+                continue;
+            };
+            mesh.push_face(group_id, face_type(entities, face_id), &triangles);
+        }
+    }
+
+    if unaddressable_faces > 0 {
+        tracing::warn!(unaddressable_faces, "skipped faces whose STEP ids do not fit a face group id");
+    }
+    if mesh.indices.is_empty() {
+        return Err(format!("No faces could be tessellated ({} shells failed to convert)", failed_shells));
+    }
+    tracing::debug!(
+        faces = mesh.face_groups.len(),
+        triangles = mesh.indices.len() / 3,
+        failed_shells,
+        tolerance,
+        "tessellated B-rep faces"
+    );
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_face_groups_follow_pushed_faces() {
+        let up = [0.0, 0.0, 1.0];
+        let mut mesh = BrepMesh::default();
+        // A 2 x 1 rectangle at z = 0, then one triangle without normals at z = 1
+        mesh.push_face(12, "planar", &[
+            [([0.0, 0.0, 0.0], up), ([2.0, 0.0, 0.0], up), ([2.0, 1.0, 0.0], up)],
+            [([0.0, 0.0, 0.0], up), ([2.0, 1.0, 0.0], up), ([0.0, 1.0, 0.0], up)],
+        ]);
+        mesh.push_face(20, "curved", &[[([0.0, 0.0, 1.0], [0.0; 3]), ([1.0, 0.0, 1.0], [0.0; 3]), ([0.0, 1.0, 1.0], [0.0; 3])]]);
+
+        assert_eq!(mesh.indices.len(), 9);
+        let (first, second) = (&mesh.face_groups[0], &mesh.face_groups[1]);
+        assert_eq!((first.face_id, first.start_index, first.triangle_count), (12, 0, 2));
+        assert_eq!((second.face_id, second.start_index, second.triangle_count), (20, 6, 1));
+        assert!((first.center[0] - 1.0).abs() < 1e-9 && (first.center[1] - 0.5).abs() < 1e-9);
+        assert_eq!(&mesh.normals[18..21], &[0.0, 0.0, 1.0]);
+
+        let bbox = mesh.bounding_box();
+        assert_eq!(bbox.dimensions, [2.0, 1.0, 1.0]);
+        assert!((tolerance_for(6f64.sqrt()) - 6f64.sqrt() * 1e-3).abs() < 1e-12);
+        assert_eq!(tolerance_for(0.0), DEFAULT_TOLERANCE);
+
+        let entities = StepEntities::parse("#1=CARTESIAN_POINT('',(0.,0.,0.));\n#2=CARTESIAN_POINT('',(3.,4.,0.));");
+        assert!((point_diagonal(&entities) - 5.0).abs() < 1e-12);
+    }
+    #[test]
+    fn test_block_tessellates_one_group_per_face() {
+        let mesh = tessellate_brep(include_str!("../fixtures/block.step")).unwrap();
+
+        let ids: Vec<u32> = mesh.face_groups.iter().map(|g| g.face_id).collect();
+        assert_eq!(ids, vec![42, 64, 79, 94, 103, 112]);
+        assert!(mesh.face_groups.iter().all(|g| g.face_type == "planar" && g.triangle_count >= 2));
+        let triangles: u32 = mesh.face_groups.iter().map(|g| g.triangle_count).sum();
+        assert_eq!(triangles as usize, mesh.indices.len() / 3);
+        assert_eq!(mesh.vertices.len(), mesh.normals.len());
+
+        let bbox = mesh.bounding_box();
+        for (k, expected) in [20.0, 10.0, 5.0].into_iter().enumerate() {
+            assert!((bbox.dimensions[k] - expected).abs() < 1e-4 && bbox.min[k].abs() < 1e-4);
+        }
+        // The bottom face (z = 0) sits at the centre of the block's footprint
+        let bottom = mesh.face_groups[0].center;
+        assert!((bottom[0] - 10.0).abs() < 1e-6 && (bottom[1] - 5.0).abs() < 1e-6 && bottom[2].abs() < 1e-6);
+    }
+}
@@ -0,0 +1,167 @@
+// Bounding spheres and camera framing for "fit view" and "zoom to part"
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::interface_detection::transform_point;
+use crate::linalg::{add, distance, normalize, scale, sub, Vec3};
+use crate::model_store::ModelStore;
+
+/// Sphere enclosing a model or part
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingSphere {
+    pub center: Vec3,
+    pub radius: f64,
+}
+
+/// How the camera should frame a sphere
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraFitOptions {
+    pub fov_deg: f64,          // Vertical field of view
+    pub aspect: f64,           // Viewport width / height
+    pub view_direction: Vec3,  // From target towards the camera
+    pub up: Vec3,
+    pub padding: f64,          // Multiplier on the fitted distance
+}
+
+impl Default for CameraFitOptions {
+    fn default() -> Self {
+        CameraFitOptions {
+            fov_deg: 45.0,
+            aspect: 1.0,
+            view_direction: [1.0, 1.0, 1.0],
+            up: [0.0, 0.0, 1.0],
+            padding: 1.1,
+        }
+    }
+}
+
+/// Suggested perspective camera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CameraFit {
+    pub position: Vec3,
+    pub target: Vec3,
+    pub up: Vec3,
+    pub fov_deg: f64,
+    pub near: f64,
+    pub far: f64,
+}
+
+/// Sphere and camera for one part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartViewFit {
+    pub part_id: String,
+    pub sphere: BoundingSphere,
+    pub camera: CameraFit,
+}
+
+/// Framing for a whole model and each of its parts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelViewFit {
+    pub sphere: Option<BoundingSphere>,
+    pub camera: Option<CameraFit>,
+    pub parts: Vec<PartViewFit>,
+}
+
+/// Ritter's approximate bounding sphere, within a few percent of minimal
+pub fn bounding_sphere(points: &[Vec3]) -> Option<BoundingSphere> {
+    let first = *points.first()?;
+    let farthest = |from: &Vec3| {
+        *points.iter().max_by(|a, b| distance(a, from).total_cmp(&distance(b, from))).unwrap_or(from)
+    };
+    let a = farthest(&first);
+    let b = farthest(&a);
+
+    let mut center = scale(&add(&a, &b), 0.5);
+    let mut radius = distance(&a, &b) / 2.0;
+    for p in points {
+        let d = distance(p, &center);
+        if d > radius {
+            // Grow just enough to take in p, keeping the opposite side of the sphere fixed
+            let new_radius = (radius + d) / 2.0;
+            center = add(&center, &scale(&sub(p, &center), (new_radius - radius) / d));
+            radius = new_radius;
+        }
+    }
+
+    Some(BoundingSphere { center, radius })
+}
+
+/// Camera looking at the sphere center from far enough that the whole sphere is in view
+pub fn fit_camera(sphere: &BoundingSphere, options: &CameraFitOptions) -> CameraFit {
+    let half_v = (options.fov_deg.clamp(1.0, 179.0) / 2.0).to_radians();
+    let half_h = (half_v.tan() * options.aspect.max(1e-3)).atan();
+    let radius = sphere.radius.max(1e-6);
+    let distance = radius / half_v.min(half_h).sin() * options.padding.max(1.0);
+
+    let direction = normalize(&options.view_direction);
+    let direction = if direction == [0.0; 3] { [0.0, 0.0, 1.0] } else { direction };
+
+    CameraFit {
+        position: add(&sphere.center, &scale(&direction, distance)),
+        target: sphere.center,
+        up: options.up,
+        fov_deg: options.fov_deg,
+        near: (distance - radius).max(distance * 1e-3),
+        far: distance + radius,
+    }
+}
+
+/// Bounding spheres and suggested cameras for a loaded model and its parts
+#[tauri::command]
+pub fn get_view_fit(
+    state: State<'_, ModelStore>,
+    handle: String,
+    options: Option<CameraFitOptions>,
+) -> Result<ModelViewFit, String> {
+    let options = options.unwrap_or_default();
+    state.with_model(&handle, |model| {
+        let model_points: Vec<Vec3> = model.mesh.as_ref()
+            .map(|mesh| mesh.vertices.chunks_exact(3).map(|v| [v[0] as f64, v[1] as f64, v[2] as f64]).collect())
+            .unwrap_or_default();
+        let sphere = bounding_sphere(&model_points);
+
+        let parts = model.assembly.parts.iter()
+            .filter_map(|part| {
+                let bbox = part.bounding_box.as_ref()?;
+                let corners: Vec<Vec3> = (0..8)
+                    .map(|i| {
+                        let corner = [0, 1, 2].map(|axis| if i >> axis & 1 == 0 { bbox.min[axis] } else { bbox.max[axis] });
+                        transform_point(&corner, &part.transform)
+                    })
+                    .collect();
+                let sphere = bounding_sphere(&corners)?;
+                Some(PartViewFit { part_id: part.id.clone(), sphere, camera: fit_camera(&sphere, &options) })
+            })
+            .collect();
+
+        ModelViewFit {
+            camera: sphere.map(|s| fit_camera(&s, &options)),
+            sphere,
+            parts,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sphere_and_camera_frame_cube() {
+        let corners: Vec<Vec3> = (0..8)
+            .map(|i| [0, 1, 2].map(|axis| if i >> axis & 1 == 0 { 0.0 } else { 2.0 }))
+            .collect();
+        let sphere = bounding_sphere(&corners).unwrap();
+        assert!(distance(&sphere.center, &[1.0, 1.0, 1.0]) < 1e-9);
+        assert!((sphere.radius - 3f64.sqrt()).abs() < 1e-9);
+        assert!(corners.iter().all(|c| distance(c, &sphere.center) <= sphere.radius + 1e-9));
+
+        let options = CameraFitOptions { padding: 1.0, view_direction: [0.0, 0.0, 1.0], ..Default::default() };
+        let camera = fit_camera(&sphere, &options);
+        let expected = sphere.radius / 22.5f64.to_radians().sin();
+        assert!((distance(&camera.position, &camera.target) - expected).abs() < 1e-9);
+        assert!(camera.near > 0.0 && camera.far > camera.near);
+    }
+}
@@ -0,0 +1,279 @@
+// Clearance envelope sweep: move one part along an insertion or removal stroke and report what it hits
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::linalg::{add, distance, norm, normalize, scale, sub, Vec3};
+use crate::mesh_query::TriangleBvh;
+use crate::model_store::{part_world_mesh, ModelStore};
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// Upper bound on positions checked along one stroke
+const MAX_SWEEP_SAMPLES: usize = 2000;
+/// Positions per stroke when no step is given
+const DEFAULT_SWEEP_SAMPLES: f64 = 100.0;
+
+/// Stroke the part follows, as offsets from where it sits now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SweepPath {
+    /// Straight stroke, e.g. a connector plugged along its mating axis
+    Axis { direction: Vec3, distance: f64 },
+    /// Polyline through the given offsets; the start (no offset) is implied
+    Waypoints { points: Vec<Vec3> },
+}
+
+impl SweepPath {
+    /// Path vertices as offsets, starting at the origin
+    fn vertices(&self) -> Vec<Vec3> {
+        match self {
+            SweepPath::Axis { direction, distance } => vec![[0.0; 3], scale(&normalize(direction), *distance)],
+            SweepPath::Waypoints { points } => std::iter::once([0.0; 3]).chain(points.iter().copied()).collect(),
+        }
+    }
+
+    fn length(&self) -> f64 {
+        self.vertices().windows(2).map(|w| distance(&w[0], &w[1])).sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepRequest {
+    pub part_id: String,
+    pub path: SweepPath,
+    pub step: Option<f64>,           // Spacing of the checked positions; stroke / 100 by default
+    #[serde(default)]
+    pub min_clearance: f64,          // Required gap; closer than this counts as a collision
+    #[serde(default)]
+    pub ignore_parts: Vec<String>,   // E.g. the mating part the stroke is meant to engage
+}
+
+impl Validate for SweepRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        match &self.path {
+            SweepPath::Axis { direction, distance } => {
+                if !direction.iter().all(|c| c.is_finite()) || norm(direction) < 1e-12 {
+                    errors.add("path.direction", "expected a finite non-zero direction");
+                }
+                errors.above("path.distance", *distance, 0.0);
+            }
+            SweepPath::Waypoints { points } => {
+                if points.is_empty() {
+                    errors.add("path.points", "expected at least one waypoint");
+                }
+                if !points.iter().flatten().all(|c| c.is_finite()) {
+                    errors.add("path.points", "expected finite coordinates");
+                }
+            }
+        }
+        errors.at_least("min_clearance", self.min_clearance, 0.0);
+        if let Some(step) = self.step {
+            errors.above("step", step, 0.0);
+            let length = self.path.length();
+            if step > 0.0 && length.is_finite() && length / step > MAX_SWEEP_SAMPLES as f64 {
+                errors.add("step", format!("a {:.3} mm stroke needs at most {} positions; use a step of {:.4} or more", length, MAX_SWEEP_SAMPLES, length / MAX_SWEEP_SAMPLES as f64));
+            }
+        }
+    }
+}
+
+/// Nearest obstacle at one position along the stroke
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepSample {
+    pub travel: f64,                      // Distance along the path
+    pub offset: Vec3,
+    pub clearance: Option<f64>,           // None when nothing is left to hit
+    pub nearest_part_id: Option<String>,
+}
+
+/// Stretch of the stroke where the part hits (or comes too close to) one obstacle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepCollision {
+    pub part_id: String,
+    pub first_travel: f64,
+    pub last_travel: f64,
+    pub min_clearance: f64,  // 0 when the bodies touch or overlap
+    pub point: Vec3,         // On the moving part, where it first comes too close
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepResult {
+    pub part_id: String,
+    pub stroke_length: f64,
+    pub samples: Vec<SweepSample>,
+    pub collisions: Vec<SweepCollision>,
+    pub start_contacts: Vec<String>,  // Touching at the start of the stroke, so left out (seated mates)
+    pub min_clearance: Option<f64>,
+    pub clear: bool,
+}
+
+/// Offsets along the path at most `step` apart, with the travel to each
+pub fn sample_path(vertices: &[Vec3], step: f64) -> Vec<(f64, Vec3)> {
+    let mut samples = vec![(0.0, vertices[0])];
+    let mut travel = 0.0;
+    for pair in vertices.windows(2) {
+        let length = distance(&pair[0], &pair[1]);
+        if length == 0.0 {
+            continue;
+        }
+        let count = (length / step).ceil().max(1.0) as usize;
+        let run = sub(&pair[1], &pair[0]);
+        for i in 1..=count {
+            let t = i as f64 / count as f64;
+            samples.push((travel + t * length, add(&pair[0], &scale(&run, t))));
+        }
+        travel += length;
+    }
+    samples
+}
+
+/// Gap between the moved part and an obstacle, 0 when they touch or one encloses the other
+fn gap(moved: &TriangleBvh, obstacle: &TriangleBvh) -> Option<(f64, Vec3)> {
+    let closest = moved.min_distance(obstacle)?;
+    let enclosed = closest.distance > 0.0
+        && (obstacle.signed_distance(&closest.point_a).is_some_and(|d| d < 0.0)
+            || moved.signed_distance(&closest.point_b).is_some_and(|d| d < 0.0));
+    Some((if enclosed { 0.0 } else { closest.distance }, closest.point_a))
+}
+
+/// Check the moving part against every obstacle at each sampled position
+pub fn sweep_clearance(
+    part_id: &str,
+    moving: &TriangleBvh,
+    obstacles: &[(String, TriangleBvh)],
+    samples: &[(f64, Vec3)],
+    min_clearance: f64,
+) -> SweepResult {
+    let too_close = |clearance: f64| clearance <= 0.0 || clearance < min_clearance;
+    let (seated, obstacles): (Vec<_>, Vec<_>) = obstacles.iter()
+        .partition(|(_, obstacle)| gap(moving, obstacle).is_some_and(|(d, _)| d <= 0.0));
+
+    let mut swept = Vec::with_capacity(samples.len());
+    let mut collisions: Vec<SweepCollision> = Vec::new();
+    for &(travel, offset) in samples {
+        let moved = moving.translated(&offset);
+        let mut nearest: Option<(f64, &String)> = None;
+        for (obstacle_id, obstacle) in &obstacles {
+            let Some((clearance, point)) = gap(&moved, obstacle) else { continue };
+            if !nearest.is_some_and(|(best, _)| best <= clearance) {
+                nearest = Some((clearance, obstacle_id));
+            }
+            if !too_close(clearance) {
+                continue;
+            }
+            match collisions.iter_mut().find(|c| c.part_id == *obstacle_id) {
+                Some(collision) => {
+                    collision.last_travel = travel;
+                    collision.min_clearance = collision.min_clearance.min(clearance);
+                }
+                None => collisions.push(SweepCollision {
+                    part_id: obstacle_id.clone(),
+                    first_travel: travel,
+                    last_travel: travel,
+                    min_clearance: clearance,
+                    point,
+                }),
+            }
+        }
+        swept.push(SweepSample {
+            travel,
+            offset,
+            clearance: nearest.map(|(c, _)| c),
+            nearest_part_id: nearest.map(|(_, id)| id.clone()),
+        });
+    }
+
+    let min_clearance = swept.iter().filter_map(|s| s.clearance).min_by(f64::total_cmp);
+    SweepResult {
+        part_id: part_id.to_string(),
+        stroke_length: samples.last().map_or(0.0, |s| s.0),
+        samples: swept,
+        clear: collisions.is_empty(),
+        collisions,
+        start_contacts: seated.into_iter().map(|(id, _)| id.clone()).collect(),
+        min_clearance,
+    }
+}
+
+/// Sweep a part of a loaded model along a stroke and report collisions with the other parts
+#[tauri::command]
+pub fn sweep_part_clearance(
+    state: State<'_, ModelStore>,
+    handle: String,
+    request: Checked<SweepRequest>,
+) -> Result<SweepResult, String> {
+    state.with_model(&handle, |model| {
+        let parts = &model.assembly.parts;
+        let part = parts.iter()
+            .find(|p| p.id == request.part_id)
+            .ok_or_else(|| format!("Unknown part: {}", request.part_id))?;
+        let moving = part_world_mesh(part)
+            .map(|mesh| TriangleBvh::build(&mesh))
+            .ok_or_else(|| format!("Part {} has no geometry", part.id))?;
+        let obstacles: Vec<(String, TriangleBvh)> = parts.iter()
+            .filter(|p| p.id != part.id && !request.ignore_parts.contains(&p.id))
+            .filter_map(|p| part_world_mesh(p).map(|mesh| (p.id.clone(), TriangleBvh::build(&mesh))))
+            .collect();
+
+        let length = request.path.length();
+        let step = request.step.unwrap_or(length / DEFAULT_SWEEP_SAMPLES);
+        let samples = sample_path(&request.path.vertices(), step);
+        let result = sweep_clearance(&part.id, &moving, &obstacles, &samples, request.min_clearance);
+        tracing::info!(
+            handle = %handle,
+            part = %part.id,
+            positions = samples.len(),
+            collisions = result.collisions.len(),
+            "clearance sweep finished"
+        );
+        Ok(result)
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::validate;
+    use crate::MeshData;
+
+    fn cube(min: Vec3, max: Vec3) -> TriangleBvh {
+        let (vertices, indices, normals, _) = crate::create_mesh_from_points(&[min, max]);
+        TriangleBvh::build(&MeshData { vertices, indices, normals, face_groups: Vec::new(), triangle_face_ids: Vec::new() })
+    }
+
+    #[test]
+    fn test_sweep_reports_collision_stretch() {
+        let request = SweepRequest {
+            part_id: "plug".to_string(),
+            path: SweepPath::Axis { direction: [2.0, 0.0, 0.0], distance: 4.0 },
+            step: Some(0.5),
+            min_clearance: 0.0,
+            ignore_parts: Vec::new(),
+        };
+        assert!(validate(&request).is_ok());
+        let samples = sample_path(&request.path.vertices(), 0.5);
+        assert_eq!(samples.len(), 9);
+
+        // A post in the way from x = 2.5 to 3.5, a seated housing behind the plug, a cover well clear above
+        let obstacles = vec![
+            ("post".to_string(), cube([2.5, 0.0, 0.0], [3.5, 1.0, 1.0])),
+            ("housing".to_string(), cube([-1.0, 0.0, 0.0], [0.0, 1.0, 1.0])),
+            ("cover".to_string(), cube([0.0, 0.0, 3.0], [5.0, 1.0, 4.0])),
+        ];
+        let result = sweep_clearance("plug", &cube([0.0; 3], [1.0; 3]), &obstacles, &samples, 0.0);
+        assert!(!result.clear);
+        assert_eq!(result.start_contacts, vec!["housing"]);
+        assert_eq!(result.collisions.len(), 1);
+        let hit = &result.collisions[0];
+        assert_eq!(hit.part_id, "post");
+        assert!((hit.first_travel - 1.5).abs() < 1e-9 && (hit.last_travel - 3.5).abs() < 1e-9);
+        assert!((result.samples[0].clearance.unwrap() - 1.5).abs() < 1e-9);
+
+        // Requiring 2.5 mm of clearance also catches the cover
+        let strict = sweep_clearance("plug", &cube([0.0; 3], [1.0; 3]), &obstacles, &samples, 2.5);
+        assert!(strict.collisions.iter().any(|c| c.part_id == "cover"));
+
+        let bad = SweepRequest { step: Some(0.0001), ..request };
+        assert!(validate(&bad).unwrap_err().contains("step"));
+    }
+}
@@ -0,0 +1,136 @@
+// Named clipping planes kept with a loaded model and referenced by section and report commands
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::linalg::{norm, normalize};
+use crate::mesh_query::SlicePlane;
+use crate::model_store::{LoadedModel, ModelStore};
+
+/// A saved section plane; the normal points to the side that is clipped away
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClippingPlane {
+    pub name: String,
+    pub origin: [f64; 3],
+    pub normal: [f64; 3],  // Unit length
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,     // Applied in the viewer
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ClippingPlane {
+    pub fn slice_plane(&self) -> SlicePlane {
+        SlicePlane { origin: self.origin, normal: self.normal }
+    }
+}
+
+fn checked_plane(name: &str, origin: [f64; 3], normal: [f64; 3], enabled: bool) -> Result<ClippingPlane, String> {
+    if name.trim().is_empty() {
+        return Err("Clipping plane name must not be empty".to_string());
+    }
+    if !origin.iter().chain(normal.iter()).all(|c| c.is_finite()) || norm(&normal) < 1e-9 {
+        return Err(format!("Clipping plane {} needs a finite origin and a non-zero normal", name));
+    }
+    Ok(ClippingPlane { name: name.trim().to_string(), origin, normal: normalize(&normal), enabled })
+}
+
+impl LoadedModel {
+    /// Plane given inline or by the name of a saved clipping plane
+    pub fn resolve_plane(&self, plane: Option<SlicePlane>, plane_name: Option<&str>) -> Result<SlicePlane, String> {
+        match (plane, plane_name) {
+            (_, Some(name)) => self.clipping_planes.iter()
+                .find(|p| p.name == name)
+                .map(ClippingPlane::slice_plane)
+                .ok_or_else(|| format!("Unknown clipping plane: {}", name)),
+            (Some(plane), None) => Ok(plane),
+            (None, None) => Err("Give a plane or the name of a clipping plane".to_string()),
+        }
+    }
+}
+
+/// Add a named clipping plane to a model
+#[tauri::command]
+pub fn create_clipping_plane(
+    state: State<'_, ModelStore>,
+    handle: String,
+    name: String,
+    origin: [f64; 3],
+    normal: [f64; 3],
+) -> Result<ClippingPlane, String> {
+    let plane = checked_plane(&name, origin, normal, true)?;
+    state.with_model_mut(&handle, |model| {
+        if model.clipping_planes.iter().any(|p| p.name == plane.name) {
+            return Err(format!("Clipping plane {} already exists", plane.name));
+        }
+        model.clipping_planes.push(plane.clone());
+        Ok(plane)
+    })?
+}
+
+/// Move, reorient, rename or toggle a clipping plane; omitted fields keep their value
+#[tauri::command]
+pub fn update_clipping_plane(
+    state: State<'_, ModelStore>,
+    handle: String,
+    name: String,
+    new_name: Option<String>,
+    origin: Option<[f64; 3]>,
+    normal: Option<[f64; 3]>,
+    enabled: Option<bool>,
+) -> Result<ClippingPlane, String> {
+    state.with_model_mut(&handle, |model| {
+        let index = model.clipping_planes.iter()
+            .position(|p| p.name == name)
+            .ok_or_else(|| format!("Unknown clipping plane: {}", name))?;
+        let current = &model.clipping_planes[index];
+        let updated = checked_plane(
+            new_name.as_deref().unwrap_or(&current.name),
+            origin.unwrap_or(current.origin),
+            normal.unwrap_or(current.normal),
+            enabled.unwrap_or(current.enabled),
+        )?;
+        if updated.name != name && model.clipping_planes.iter().any(|p| p.name == updated.name) {
+            return Err(format!("Clipping plane {} already exists", updated.name));
+        }
+        model.clipping_planes[index] = updated.clone();
+        Ok(updated)
+    })?
+}
+
+/// Remove a clipping plane; false when there was none by that name
+#[tauri::command]
+pub fn delete_clipping_plane(state: State<'_, ModelStore>, handle: String, name: String) -> Result<bool, String> {
+    state.with_model_mut(&handle, |model| {
+        let before = model.clipping_planes.len();
+        model.clipping_planes.retain(|p| p.name != name);
+        model.clipping_planes.len() != before
+    })
+}
+
+/// Clipping planes saved on a model
+#[tauri::command]
+pub fn list_clipping_planes(state: State<'_, ModelStore>, handle: String) -> Result<Vec<ClippingPlane>, String> {
+    state.with_model(&handle, |model| model.clipping_planes.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plane_checks_and_resolution() {
+        let plane = checked_plane(" mid ", [0.0, 0.0, 5.0], [0.0, 0.0, 2.0], true).unwrap();
+        assert_eq!(plane.name, "mid");
+        assert_eq!(plane.normal, [0.0, 0.0, 1.0]);
+        assert!(checked_plane("flat", [0.0; 3], [0.0; 3], true).is_err());
+
+        let mut model = LoadedModel::parse("model-1".to_string(), "ISO-10303-21;".to_string(), "empty.step".to_string(), None);
+        model.clipping_planes.push(plane);
+        assert_eq!(model.resolve_plane(None, Some("mid")).unwrap().origin, [0.0, 0.0, 5.0]);
+        assert!(model.resolve_plane(None, Some("side")).unwrap_err().contains("Unknown clipping plane"));
+        assert!(model.resolve_plane(None, None).is_err());
+    }
+}
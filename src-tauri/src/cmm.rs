@@ -0,0 +1,361 @@
+// CMM measurement results import (QIF, CSV exports) and comparison to stackup assumptions
+
+use roxmltree::{Document, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+
+use crate::gdt::FeatureControlFrame;
+use crate::model_store::ModelStore;
+use crate::tolerance_calc::LinkInput;
+
+/// One measured characteristic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasuredCharacteristic {
+    pub name: String,
+    pub characteristic: String,        // e.g. "Diameter", "Position"
+    pub nominal: Option<f64>,
+    pub measured: f64,
+    pub upper_tolerance: Option<f64>,  // Signed deviation from nominal
+    pub lower_tolerance: Option<f64>,
+}
+
+impl MeasuredCharacteristic {
+    /// Acceptance limits; geometric tolerances have only an upper limit from zero
+    pub fn limits(&self) -> (Option<f64>, Option<f64>) {
+        let nominal = self.nominal.unwrap_or(0.0);
+        (self.lower_tolerance.map(|t| nominal + t), self.upper_tolerance.map(|t| nominal + t))
+    }
+
+    pub fn in_tolerance(&self) -> Option<bool> {
+        match self.limits() {
+            (None, None) => None,
+            (lo, hi) => Some(lo.is_none_or(|lo| self.measured >= lo) && hi.is_none_or(|hi| self.measured <= hi)),
+        }
+    }
+}
+
+/// Result of reading a CMM results file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CmmImportResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub format: Option<String>,
+    pub measurements: Vec<MeasuredCharacteristic>,
+}
+
+/// Measurement matched against a tolerance link or feature control frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasurementComparison {
+    pub name: String,
+    pub measured: f64,
+    pub in_tolerance: Option<bool>,     // Against the measurement's own tolerance
+    pub link_index: Option<usize>,
+    pub control_id: Option<String>,
+    pub assumed_min: Option<f64>,       // Range the stackup assumes
+    pub assumed_max: Option<f64>,
+    pub violates_stackup: bool,
+    pub message: Option<String>,
+}
+
+/// Comparison of a measurement set against the stackup and GD&T
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CmmComparisonResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub comparisons: Vec<MeasurementComparison>,
+    pub violation_count: usize,
+    pub unmatched: Vec<String>,
+}
+
+fn local_name<'a>(node: &Node<'a, '_>) -> &'a str {
+    node.tag_name().name()
+}
+
+/// Text of the first descendant element with the given local name
+fn descendant_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.descendants()
+        .find(|n| n.is_element() && local_name(n) == name)
+        .and_then(|n| n.text())
+        .map(str::trim)
+}
+
+fn descendant_f64(node: Node<'_, '_>, name: &str) -> Option<f64> {
+    descendant_text(node, name).and_then(|t| t.parse().ok())
+}
+
+/// Elements whose local name ends with `suffix`, keyed by their id attribute
+fn elements_by_id<'a, 'input>(doc: &'a Document<'input>, suffix: &str) -> HashMap<&'a str, Node<'a, 'input>> {
+    doc.descendants()
+        .filter(|n| n.is_element() && local_name(n).ends_with(suffix))
+        .filter_map(|n| n.attribute("id").map(|id| (id, n)))
+        .collect()
+}
+
+/// QIF results: measurement -> characteristic item -> nominal -> definition
+pub fn parse_qif(xml: &str) -> Result<Vec<MeasuredCharacteristic>, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("Invalid QIF XML: {}", e))?;
+    let definitions = elements_by_id(&doc, "CharacteristicDefinition");
+    let nominals = elements_by_id(&doc, "CharacteristicNominal");
+    let items = elements_by_id(&doc, "CharacteristicItem");
+
+    let mut measurements = Vec::new();
+    for node in doc.descendants().filter(|n| n.is_element() && local_name(n).ends_with("CharacteristicMeasurement")) {
+        let Some(measured) = descendant_f64(node, "Value") else { continue };
+        let item = descendant_text(node, "CharacteristicItemId").and_then(|id| items.get(id));
+        let nominal = item
+            .and_then(|i| descendant_text(*i, "CharacteristicNominalId"))
+            .and_then(|id| nominals.get(id));
+        let definition = nominal
+            .and_then(|n| descendant_text(*n, "CharacteristicDefinitionId"))
+            .and_then(|id| definitions.get(id));
+
+        let name = item
+            .and_then(|i| descendant_text(*i, "Name"))
+            .or_else(|| descendant_text(node, "CharacteristicItemId"))
+            .unwrap_or_default()
+            .to_string();
+        let characteristic = local_name(&node).trim_end_matches("CharacteristicMeasurement").to_string();
+
+        // Geometric tolerances carry one zone size; dimensional ones a max/min deviation
+        let (upper_tolerance, lower_tolerance) = match definition {
+            Some(def) => match descendant_f64(*def, "ToleranceValue") {
+                Some(zone) => (Some(zone), None),
+                None => (descendant_f64(*def, "MaxValue"), descendant_f64(*def, "MinValue")),
+            },
+            None => (None, None),
+        };
+        let target = nominal.and_then(|n| descendant_f64(*n, "TargetValue"))
+            .or_else(|| definition.and_then(|d| descendant_f64(*d, "TargetValue")));
+
+        measurements.push(MeasuredCharacteristic {
+            name,
+            characteristic,
+            nominal: target,
+            measured,
+            upper_tolerance,
+            lower_tolerance,
+        });
+    }
+
+    Ok(measurements)
+}
+
+/// CSV export with a header row; a "minus" column holds a magnitude, a "lower" column a signed value
+pub fn parse_cmm_csv(text: &str) -> Result<Vec<MeasuredCharacteristic>, String> {
+    let split = |line: &str| -> Vec<String> {
+        line.split([',', ';']).map(|f| f.trim().trim_matches('"').to_string()).collect()
+    };
+
+    let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+    let header: Vec<String> = split(lines.next().ok_or("CSV is empty")?)
+        .into_iter()
+        .map(|h| h.to_ascii_lowercase())
+        .collect();
+    let column = |names: &[&str]| header.iter().position(|h| names.contains(&h.as_str()));
+
+    let name_col = column(&["name", "characteristic", "feature", "label"]).ok_or("CSV has no name column")?;
+    let measured_col = column(&["measured", "actual", "value", "measurement"]).ok_or("CSV has no measured column")?;
+    let type_col = column(&["type"]);
+    let nominal_col = column(&["nominal"]);
+    let upper_col = column(&["upper", "upper_tol", "plus", "utol", "+tol"]);
+    let lower_col = column(&["lower", "lower_tol", "ltol"]);
+    let minus_col = column(&["minus", "-tol"]);
+
+    let mut measurements = Vec::new();
+    for line in lines {
+        let fields = split(line);
+        let number = |col: Option<usize>| col.and_then(|c| fields.get(c)).and_then(|f| f.parse::<f64>().ok());
+        let Some(measured) = number(Some(measured_col)) else { continue };
+
+        measurements.push(MeasuredCharacteristic {
+            name: fields.get(name_col).cloned().unwrap_or_default(),
+            characteristic: type_col.and_then(|c| fields.get(c)).cloned().unwrap_or_default(),
+            nominal: number(nominal_col),
+            measured,
+            upper_tolerance: number(upper_col),
+            lower_tolerance: number(lower_col).or_else(|| number(minus_col).map(|m| -m.abs())),
+        });
+    }
+
+    Ok(measurements)
+}
+
+/// Match measurements to named links and feature control frames
+pub fn compare_measurements(
+    measurements: &[MeasuredCharacteristic],
+    links: &[LinkInput],
+    controls: &[FeatureControlFrame],
+) -> CmmComparisonResult {
+    let mut comparisons = Vec::new();
+    let mut unmatched = Vec::new();
+
+    for m in measurements {
+        let link_index = links.iter().position(|l| {
+            l.name.as_deref().is_some_and(|n| n.eq_ignore_ascii_case(&m.name))
+        });
+        let control = controls.iter()
+            .find(|c| c.id.eq_ignore_ascii_case(&m.name))
+            .or_else(|| {
+                // Fall back to the characteristic type when only one control has it
+                let mut same_kind = controls.iter()
+                    .filter(|c| c.characteristic.name().eq_ignore_ascii_case(&m.characteristic));
+                match (same_kind.next(), same_kind.next()) {
+                    (Some(only), None) => Some(only),
+                    _ => None,
+                }
+            });
+
+        let (assumed_min, assumed_max) = match (link_index, control) {
+            (Some(i), _) => (Some(links[i].nominal - links[i].minus_tolerance), Some(links[i].nominal + links[i].plus_tolerance)),
+            (None, Some(c)) => (None, Some(c.tolerance)),
+            (None, None) => {
+                unmatched.push(m.name.clone());
+                continue;
+            }
+        };
+
+        let below = assumed_min.is_some_and(|lo| m.measured < lo - 1e-12);
+        let above = assumed_max.is_some_and(|hi| m.measured > hi + 1e-12);
+        let message = if below {
+            Some(format!("{} measured {:.4}, below the assumed minimum {:.4}", m.name, m.measured, assumed_min.unwrap_or_default()))
+        } else if above {
+            Some(format!("{} measured {:.4}, above the assumed maximum {:.4}", m.name, m.measured, assumed_max.unwrap_or_default()))
+        } else {
+            None
+        };
+
+        comparisons.push(MeasurementComparison {
+            name: m.name.clone(),
+            measured: m.measured,
+            in_tolerance: m.in_tolerance(),
+            link_index,
+            control_id: control.map(|c| c.id.clone()),
+            assumed_min,
+            assumed_max,
+            violates_stackup: below || above,
+            message,
+        });
+    }
+
+    CmmComparisonResult {
+        success: true,
+        error: None,
+        violation_count: comparisons.iter().filter(|c| c.violates_stackup).count(),
+        comparisons,
+        unmatched,
+    }
+}
+
+/// Read CMM results from a QIF or CSV file
+#[tauri::command]
+pub fn import_cmm_results(path: String) -> CmmImportResult {
+    let extension = Path::new(&path).extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    let outcome = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read file: {}", e))
+        .and_then(|text| {
+            match extension.as_str() {
+                "qif" | "xml" => parse_qif(&text).map(|m| ("qif", m)),
+                "csv" | "txt" => parse_cmm_csv(&text).map(|m| ("csv", m)),
+                _ => Err("Unsupported CMM results format (expected .qif or .csv)".to_string()),
+            }
+        });
+
+    match outcome {
+        Ok((format, measurements)) => {
+            tracing::info!(path = %path, count = measurements.len(), "CMM results imported");
+            CmmImportResult { success: true, error: None, format: Some(format.to_string()), measurements }
+        }
+        Err(e) => CmmImportResult { success: false, error: Some(e), format: None, measurements: Vec::new() },
+    }
+}
+
+/// Flag measurements that fall outside what the stackup links or the model's GD&T assume
+#[tauri::command]
+pub fn compare_cmm_results(
+    state: State<'_, ModelStore>,
+    handle: Option<String>,
+    measurements: Vec<MeasuredCharacteristic>,
+    links: Vec<LinkInput>,
+) -> CmmComparisonResult {
+    let controls = match handle {
+        Some(handle) => match state.with_model(&handle, |model| model.gdt.controls.clone()) {
+            Ok(controls) => controls,
+            Err(e) => {
+                return CmmComparisonResult {
+                    success: false,
+                    error: Some(e),
+                    comparisons: Vec::new(),
+                    violation_count: 0,
+                    unmatched: Vec::new(),
+                }
+            }
+        },
+        None => Vec::new(),
+    };
+    compare_measurements(&measurements, &links, &controls)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QIF: &str = r#"<?xml version="1.0"?>
+<QIFDocument xmlns="http://qifstandards.org/xsd/qif3">
+  <Characteristics>
+    <CharacteristicDefinitions>
+      <DiameterCharacteristicDefinition id="1"><Tolerance><MaxValue>0.05</MaxValue><MinValue>-0.05</MinValue></Tolerance></DiameterCharacteristicDefinition>
+      <PositionCharacteristicDefinition id="2"><ToleranceValue>0.1</ToleranceValue></PositionCharacteristicDefinition>
+    </CharacteristicDefinitions>
+    <CharacteristicNominals>
+      <DiameterCharacteristicNominal id="3"><CharacteristicDefinitionId>1</CharacteristicDefinitionId><TargetValue>6.0</TargetValue></DiameterCharacteristicNominal>
+      <PositionCharacteristicNominal id="4"><CharacteristicDefinitionId>2</CharacteristicDefinitionId></PositionCharacteristicNominal>
+    </CharacteristicNominals>
+    <CharacteristicItems>
+      <DiameterCharacteristicItem id="5"><Name>bore</Name><CharacteristicNominalId>3</CharacteristicNominalId></DiameterCharacteristicItem>
+      <PositionCharacteristicItem id="6"><Name>bore-pos</Name><CharacteristicNominalId>4</CharacteristicNominalId></PositionCharacteristicItem>
+    </CharacteristicItems>
+  </Characteristics>
+  <MeasurementsResults><MeasurementResultsSet><MeasurementResults><MeasuredCharacteristics><CharacteristicMeasurements>
+    <DiameterCharacteristicMeasurement><CharacteristicItemId>5</CharacteristicItemId><Value>6.08</Value></DiameterCharacteristicMeasurement>
+    <PositionCharacteristicMeasurement><CharacteristicItemId>6</CharacteristicItemId><Value>0.04</Value></PositionCharacteristicMeasurement>
+  </CharacteristicMeasurements></MeasuredCharacteristics></MeasurementResults></MeasurementResultsSet></MeasurementsResults>
+</QIFDocument>"#;
+
+    #[test]
+    fn test_qif_chain_resolves_tolerances() {
+        let measurements = parse_qif(QIF).unwrap();
+        assert_eq!(measurements.len(), 2);
+        assert_eq!(measurements[0].name, "bore");
+        assert_eq!(measurements[0].characteristic, "Diameter");
+        assert_eq!(measurements[0].nominal, Some(6.0));
+        assert_eq!(measurements[0].in_tolerance(), Some(false));
+        assert_eq!(measurements[1].in_tolerance(), Some(true));
+    }
+
+    #[test]
+    fn test_csv_measurement_flags_stackup_link() {
+        let measurements = parse_cmm_csv("Name,Nominal,Actual,Plus,Minus\nspacer,10,10.15,0.1,0.1\nshim,2,2.01,0.05,0.05\n").unwrap();
+        assert_eq!(measurements[0].lower_tolerance, Some(-0.1));
+
+        let link = |name: &str, nominal: f64| LinkInput {
+            name: Some(name.to_string()),
+            nominal,
+            plus_tolerance: 0.1,
+            minus_tolerance: 0.1,
+            direction: "positive".to_string(),
+            distribution: "normal".to_string(),
+            sigma: None,
+            inspected: false,
+        };
+        let result = compare_measurements(&measurements, &[link("spacer", 10.0), link("shim", 2.0)], &[]);
+        assert_eq!(result.violation_count, 1);
+        assert!(result.comparisons[0].violates_stackup);
+        assert_eq!(result.comparisons[1].link_index, Some(1));
+    }
+}
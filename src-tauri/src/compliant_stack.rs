@@ -0,0 +1,210 @@
+// Over-constrained stacks: parts squeezed into a fixed length, the interference shared by interface springs in series
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::jobs::run_job;
+use crate::tolerance_calc::{sample_link, validate_link, LinkInput, MAX_MONTE_CARLO_SAMPLES};
+use crate::validation::{Checked, FieldErrors, Validate};
+
+const DEFAULT_SAMPLES: usize = 20_000;
+
+/// Contact between neighbouring parts (or a part and the constraint) with its compliance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceCompliance {
+    pub name: Option<String>,
+    pub compliance: f64,  // mm/N; only ratios matter unless forces are read
+}
+
+/// Parts stacked between two faces a fixed distance apart, e.g. plates under a bolt or bearings in a housing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompliantStackInput {
+    pub parts: Vec<LinkInput>,                  // Thicknesses in stack order; direction is ignored
+    pub constraint: LinkInput,                  // Length the parts are forced into
+    pub interfaces: Vec<InterfaceCompliance>,   // parts.len() + 1: before the first part, between parts, after the last
+    pub monte_carlo_samples: Option<usize>,
+}
+
+impl Validate for CompliantStackInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.parts.is_empty() {
+            errors.add("parts", "expected at least one part");
+        }
+        for (i, part) in self.parts.iter().enumerate() {
+            validate_link(part, &format!("parts[{}]", i), errors);
+        }
+        validate_link(&self.constraint, "constraint", errors);
+        if self.interfaces.len() != self.parts.len() + 1 {
+            errors.add("interfaces", format!("expected {} interfaces for {} parts, got {}", self.parts.len() + 1, self.parts.len(), self.interfaces.len()));
+        }
+        for (i, interface) in self.interfaces.iter().enumerate() {
+            errors.above(format!("interfaces[{}].compliance", i), interface.compliance, 0.0);
+        }
+        if let Some(samples) = self.monte_carlo_samples {
+            if samples == 0 || samples > MAX_MONTE_CARLO_SAMPLES {
+                errors.add("monte_carlo_samples", format!("expected 1 to {}, got {}", MAX_MONTE_CARLO_SAMPLES, samples));
+            }
+        }
+    }
+}
+
+/// Mean, standard deviation and range of one simulated quantity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Spread {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Spread {
+    fn of(values: &[f64]) -> Spread {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        Spread {
+            mean,
+            std_dev: (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt(),
+            min: values.iter().copied().fold(f64::INFINITY, f64::min),
+            max: values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// How much one interface gives and where it ends up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfaceShare {
+    pub index: usize,
+    pub name: Option<String>,
+    pub share: f64,              // Fraction of the interference this interface absorbs
+    pub deflection: Spread,      // mm, zero when the stack is loose
+    pub position: Spread,        // Face after this interface, from the first constraint face
+    pub rigid_position: Spread,  // Same with rigid contacts: parts pushed against the first face
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompliantStackResult {
+    pub samples: usize,
+    pub interference: Spread,      // Part total minus constraint; negative is clearance
+    pub loose_fraction: f64,       // Share of builds where the parts do not fill the constraint
+    pub force: Spread,             // N, from the interference over the total compliance
+    pub interfaces: Vec<InterfaceShare>,
+}
+
+/// Springs in series: the interference splits in proportion to compliance, at one common force
+pub fn simulate_compliant_stack(input: &CompliantStackInput) -> CompliantStackResult {
+    let samples = input.monte_carlo_samples.unwrap_or(DEFAULT_SAMPLES);
+    let total_compliance: f64 = input.interfaces.iter().map(|i| i.compliance).sum();
+    let shares: Vec<f64> = input.interfaces.iter().map(|i| i.compliance / total_compliance).collect();
+    tracing::info!(parts = input.parts.len(), samples, "simulating compliant stack");
+
+    let mut rng = rand::thread_rng();
+    let count = input.interfaces.len();
+    let mut interference = Vec::with_capacity(samples);
+    let mut force = Vec::with_capacity(samples);
+    let mut deflections = vec![Vec::with_capacity(samples); count];
+    let mut positions = vec![Vec::with_capacity(samples); count];
+    let mut rigid_positions = vec![Vec::with_capacity(samples); count];
+    let mut loose = 0usize;
+    let mut thicknesses = vec![0.0; input.parts.len()];
+
+    for _ in 0..samples {
+        for (t, part) in thicknesses.iter_mut().zip(&input.parts) {
+            *t = sample_link(part, &mut rng);
+        }
+        let constraint = sample_link(&input.constraint, &mut rng);
+        let overlap = thicknesses.iter().sum::<f64>() - constraint;
+        let squeeze = overlap.max(0.0);
+        loose += (overlap < 0.0) as usize;
+        interference.push(overlap);
+        force.push(squeeze / total_compliance);
+
+        // Interface k sits after parts 0..k, moved back by everything squeezed up to and including it
+        let (mut stacked, mut squeezed) = (0.0, 0.0);
+        for k in 0..count {
+            let deflection = squeeze * shares[k];
+            squeezed += deflection;
+            deflections[k].push(deflection);
+            positions[k].push(stacked - squeezed);
+            rigid_positions[k].push(stacked.min(constraint));
+            if let Some(t) = thicknesses.get(k) {
+                stacked += t;
+            }
+        }
+    }
+
+    CompliantStackResult {
+        samples,
+        interference: Spread::of(&interference),
+        loose_fraction: loose as f64 / samples as f64,
+        force: Spread::of(&force),
+        interfaces: input.interfaces.iter().enumerate()
+            .map(|(k, interface)| InterfaceShare {
+                index: k,
+                name: interface.name.clone(),
+                share: shares[k],
+                deflection: Spread::of(&deflections[k]),
+                position: Spread::of(&positions[k]),
+                rigid_position: Spread::of(&rigid_positions[k]),
+            })
+            .collect(),
+    }
+}
+
+/// Share the interference of an over-constrained stack between compliant interfaces
+#[tauri::command]
+pub async fn calculate_compliant_stack(
+    app: AppHandle,
+    input: Checked<CompliantStackInput>,
+    job_id: Option<String>,
+) -> Result<CompliantStackResult, String> {
+    let input = input.into_inner();
+    run_job(app, "calculate_compliant_stack", job_id, None, move |_| Ok(simulate_compliant_stack(&input))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::validate;
+
+    fn length(nominal: f64, tolerance: f64) -> LinkInput {
+        LinkInput {
+            name: None,
+            nominal,
+            plus_tolerance: tolerance,
+            minus_tolerance: tolerance,
+            direction: "positive".to_string(),
+            distribution: "uniform".to_string(),
+            sigma: None,
+            inspected: false,
+        }
+    }
+
+    fn interface(compliance: f64) -> InterfaceCompliance {
+        InterfaceCompliance { name: None, compliance }
+    }
+
+    #[test]
+    fn test_soft_gasket_takes_most_of_the_squeeze() {
+        // Two 10 mm plates with a gasket interface between them, clamped to 19.9 mm
+        let input = CompliantStackInput {
+            parts: vec![length(10.0, 0.02), length(10.0, 0.02)],
+            constraint: length(19.9, 0.01),
+            interfaces: vec![interface(1e-6), interface(8e-6), interface(1e-6)],
+            monte_carlo_samples: Some(10_000),
+        };
+        assert!(validate(&input).is_ok());
+
+        let result = simulate_compliant_stack(&input);
+        assert_eq!(result.loose_fraction, 0.0);
+        assert!((result.interference.mean - 0.1).abs() < 0.002);
+        assert!((result.interfaces[1].share - 0.8).abs() < 1e-12);
+        assert!((result.interfaces[1].deflection.mean - 0.08).abs() < 0.002);
+        // The last interface always lands on the far constraint face
+        let last = &result.interfaces[2];
+        assert!((last.position.mean - 19.9).abs() < 0.002 && last.position.max <= 19.91 + 1e-9);
+        assert!((result.force.mean - 0.1 / 1e-5).abs() < 200.0);
+
+        let bad = CompliantStackInput { interfaces: vec![interface(1.0)], ..input };
+        assert!(validate(&bad).unwrap_err().contains("expected 3 interfaces"));
+    }
+}
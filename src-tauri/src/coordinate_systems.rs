@@ -0,0 +1,229 @@
+// Named user coordinate systems and reporting locations in them
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::features::outward_normal;
+use crate::interface_detection::transform_direction;
+use crate::linalg::{cross, dot, norm, normalize, reject, solve3, sub, Vec3};
+use crate::model_store::{FaceRef, LoadedModel, ModelStore};
+
+/// How a coordinate system was defined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CoordinateSystemDefinition {
+    /// 3-2-1 datum scheme: Z from the primary plane, X from the secondary, origin where all three meet
+    ThreeFaces { primary: FaceRef, secondary: FaceRef, tertiary: FaceRef },
+    /// Z from a plane, X along the edge where two other planes meet; origin on that edge
+    FaceEdge { face: FaceRef, edge: [FaceRef; 2] },
+    /// Column-major 4x4 transform from local to world
+    Matrix { matrix: [f64; 16] },
+}
+
+/// A named frame in world coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CoordinateSystem {
+    pub name: String,
+    pub origin: Vec3,
+    pub x_axis: Vec3,
+    pub y_axis: Vec3,
+    pub z_axis: Vec3,
+    pub definition: CoordinateSystemDefinition,
+}
+
+impl CoordinateSystem {
+    /// World point in this frame
+    pub fn to_local_point(&self, p: &Vec3) -> Vec3 {
+        let d = sub(p, &self.origin);
+        [dot(&d, &self.x_axis), dot(&d, &self.y_axis), dot(&d, &self.z_axis)]
+    }
+
+    /// World direction in this frame
+    pub fn to_local_direction(&self, v: &Vec3) -> Vec3 {
+        [dot(v, &self.x_axis), dot(v, &self.y_axis), dot(v, &self.z_axis)]
+    }
+}
+
+/// A face reported in a user coordinate system
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalFace {
+    pub face: FaceRef,
+    pub face_type: String,
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub axis: Option<Vec3>,
+}
+
+/// Locations reported in a coordinate system
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalLocations {
+    pub coordinate_system: String,
+    pub faces: Vec<LocalFace>,
+    pub points: Vec<Vec3>,
+}
+
+/// Planar face as (point, outward unit normal) in world coordinates
+fn world_plane(model: &LoadedModel, face: &FaceRef) -> Result<(Vec3, Vec3), String> {
+    let world = model.world_face(face)?;
+    if world.face.face_type != "planar" {
+        return Err(format!("Face {} on part {} is not planar", face.face_id, face.part_id));
+    }
+    let part = model.assembly.parts.iter().find(|p| p.id == face.part_id)
+        .ok_or_else(|| format!("Unknown part: {}", face.part_id))?;
+    Ok((world.center, transform_direction(&outward_normal(&world.face), &part.transform)))
+}
+
+/// Right-handed frame from a Z direction and an approximate X direction
+fn frame(origin: Vec3, z: &Vec3, x_hint: &Vec3) -> Result<(Vec3, Vec3, Vec3, Vec3), String> {
+    let z = normalize(z);
+    let x = reject(x_hint, &z);
+    if norm(&x) < 1e-6 {
+        return Err("Secondary reference is parallel to the primary face".to_string());
+    }
+    let x = normalize(&x);
+    Ok((origin, x, cross(&z, &x), z))
+}
+
+/// Point where three planes meet
+fn three_plane_point(planes: [(Vec3, Vec3); 3]) -> Result<Vec3, String> {
+    let m = planes.map(|(_, n)| n);
+    let b = planes.map(|(p, n)| dot(&p, &n));
+    solve3(&m, &b).ok_or_else(|| "Datum faces do not meet in a single point".to_string())
+}
+
+/// Resolve a definition against the model's geometry
+pub fn resolve_coordinate_system(
+    model: &LoadedModel,
+    name: String,
+    definition: CoordinateSystemDefinition,
+) -> Result<CoordinateSystem, String> {
+    let (origin, x_axis, y_axis, z_axis) = match &definition {
+        CoordinateSystemDefinition::ThreeFaces { primary, secondary, tertiary } => {
+            let a = world_plane(model, primary)?;
+            let b = world_plane(model, secondary)?;
+            let c = world_plane(model, tertiary)?;
+            frame(three_plane_point([a, b, c])?, &a.1, &b.1)?
+        }
+        CoordinateSystemDefinition::FaceEdge { face, edge } => {
+            let a = world_plane(model, face)?;
+            let e1 = world_plane(model, &edge[0])?;
+            let e2 = world_plane(model, &edge[1])?;
+            let direction = cross(&e1.1, &e2.1);
+            if norm(&direction) < 1e-6 {
+                return Err("Edge faces are parallel and do not meet".to_string());
+            }
+            // Origin where the edge pierces the primary plane, or on the edge nearest the face center
+            let origin = three_plane_point([a, e1, e2]).or_else(|_| {
+                three_plane_point([(a.0, normalize(&direction)), e1, e2])
+            })?;
+            frame(origin, &a.1, &direction)?
+        }
+        CoordinateSystemDefinition::Matrix { matrix } => {
+            if matrix.iter().any(|v| !v.is_finite()) {
+                return Err("Matrix contains non-finite values".to_string());
+            }
+            let column = |c: usize| [matrix[c * 4], matrix[c * 4 + 1], matrix[c * 4 + 2]];
+            frame(column(3), &column(2), &column(0))?
+        }
+    };
+
+    Ok(CoordinateSystem { name, origin, x_axis, y_axis, z_axis, definition })
+}
+
+/// Define (or redefine) a named coordinate system on a loaded model
+#[tauri::command]
+pub fn define_coordinate_system(
+    state: State<'_, ModelStore>,
+    handle: String,
+    name: String,
+    definition: CoordinateSystemDefinition,
+) -> Result<CoordinateSystem, String> {
+    if name.trim().is_empty() {
+        return Err("Coordinate system name is required".to_string());
+    }
+    state.with_model_mut(&handle, |model| {
+        let cs = resolve_coordinate_system(model, name, definition)?;
+        model.coordinate_systems.retain(|c| c.name != cs.name);
+        model.coordinate_systems.push(cs.clone());
+        tracing::info!(handle = %model.handle, name = %cs.name, "coordinate system defined");
+        Ok(cs)
+    })?
+}
+
+/// Coordinate systems defined on a loaded model
+#[tauri::command]
+pub fn list_coordinate_systems(state: State<'_, ModelStore>, handle: String) -> Result<Vec<CoordinateSystem>, String> {
+    state.with_model(&handle, |model| model.coordinate_systems.clone())
+}
+
+/// Remove a coordinate system; returns whether it existed
+#[tauri::command]
+pub fn delete_coordinate_system(state: State<'_, ModelStore>, handle: String, name: String) -> Result<bool, String> {
+    state.with_model_mut(&handle, |model| {
+        let before = model.coordinate_systems.len();
+        model.coordinate_systems.retain(|c| c.name != name);
+        model.coordinate_systems.len() != before
+    })
+}
+
+/// Report faces and world points in a named coordinate system
+#[tauri::command]
+pub fn locate_in_coordinate_system(
+    state: State<'_, ModelStore>,
+    handle: String,
+    name: String,
+    faces: Option<Vec<FaceRef>>,
+    points: Option<Vec<Vec3>>,
+) -> Result<LocalLocations, String> {
+    state.with_model(&handle, |model| {
+        let cs = model.coordinate_systems.iter().find(|c| c.name == name)
+            .ok_or_else(|| format!("Unknown coordinate system: {}", name))?;
+
+        let faces = faces.unwrap_or_default().into_iter()
+            .map(|face| {
+                let world = model.world_face(&face)?;
+                Ok(LocalFace {
+                    face_type: world.face.face_type.clone(),
+                    center: cs.to_local_point(&world.center),
+                    normal: cs.to_local_direction(&world.normal),
+                    axis: world.axis.map(|a| cs.to_local_direction(&a)),
+                    face,
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        let points = points.unwrap_or_default().iter().map(|p| cs.to_local_point(p)).collect();
+
+        Ok(LocalLocations { coordinate_system: cs.name.clone(), faces, points })
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_three_plane_origin_and_local_coordinates() {
+        // Datum planes x = 5, y = -2, z = 1 with outward normals +Z, -Y, -X
+        let origin = three_plane_point([
+            ([0.0, 0.0, 1.0], [0.0, 0.0, 1.0]),
+            ([0.0, -2.0, 0.0], [0.0, -1.0, 0.0]),
+            ([5.0, 0.0, 0.0], [-1.0, 0.0, 0.0]),
+        ]).unwrap();
+        assert_eq!(origin, [5.0, -2.0, 1.0]);
+
+        let (origin, x_axis, y_axis, z_axis) = frame(origin, &[0.0, 0.0, 1.0], &[0.0, -3.0, 0.0]).unwrap();
+        let cs = CoordinateSystem {
+            name: "A|B|C".to_string(),
+            origin,
+            x_axis,
+            y_axis,
+            z_axis,
+            definition: CoordinateSystemDefinition::Matrix { matrix: [0.0; 16] },
+        };
+        assert_eq!(cs.x_axis, [0.0, -1.0, 0.0]);
+        assert_eq!(cs.y_axis, [1.0, 0.0, 0.0]);
+        assert_eq!(cs.to_local_point(&[6.0, -4.0, 1.5]), [2.0, 1.0, 0.5]);
+
+        assert!(frame(origin, &[0.0, 0.0, 1.0], &[0.0, 0.0, 2.0]).is_err());
+    }
+}
@@ -0,0 +1,208 @@
+// Rough machining cost and time estimation per part
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+use crate::assembly_parser::ParsedPart;
+use crate::features::recognize_part_holes;
+use crate::gdt::FeatureControlFrame;
+use crate::model_store::ModelStore;
+
+/// Tolerances at or below `tolerance` multiply machining time by `multiplier`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToleranceBand {
+    pub tolerance: f64,
+    pub multiplier: f64,
+}
+
+/// Shop rates and heuristics used by the estimator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateTable {
+    pub machine_rate_per_hour: f64,
+    pub setup_minutes: f64,
+    pub material_cost_per_cm3: f64,
+    pub removal_rate_cm3_per_min: f64,
+    pub minutes_per_hole: f64,
+    pub minutes_per_face: f64,      // Finishing pass per face
+    pub stock_allowance: f64,       // Added to each side of the part envelope, mm
+    pub fill_ratio: f64,            // Part volume as a fraction of its envelope
+    pub tolerance_bands: Vec<ToleranceBand>,
+}
+
+impl Default for RateTable {
+    fn default() -> Self {
+        RateTable {
+            machine_rate_per_hour: 75.0,
+            setup_minutes: 30.0,
+            material_cost_per_cm3: 0.02,
+            removal_rate_cm3_per_min: 15.0,
+            minutes_per_hole: 0.5,
+            minutes_per_face: 0.2,
+            stock_allowance: 2.0,
+            fill_ratio: 0.5,
+            tolerance_bands: vec![
+                ToleranceBand { tolerance: 0.1, multiplier: 1.0 },
+                ToleranceBand { tolerance: 0.05, multiplier: 1.25 },
+                ToleranceBand { tolerance: 0.025, multiplier: 1.6 },
+                ToleranceBand { tolerance: 0.01, multiplier: 2.5 },
+            ],
+        }
+    }
+}
+
+impl RateTable {
+    /// Largest multiplier among bands the tolerance falls within
+    pub fn tolerance_multiplier(&self, tolerance: Option<f64>) -> f64 {
+        let Some(tolerance) = tolerance else { return 1.0 };
+        self.tolerance_bands.iter()
+            .filter(|b| tolerance <= b.tolerance + 1e-12)
+            .map(|b| b.multiplier)
+            .fold(1.0, f64::max)
+    }
+}
+
+/// Cost and time estimate for one part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartCostEstimate {
+    pub part_id: String,
+    pub name: String,
+    pub stock_dimensions: [f64; 3],
+    pub stock_volume_cm3: f64,
+    pub removed_volume_cm3: f64,
+    pub hole_count: usize,
+    pub face_count: usize,
+    pub tightest_tolerance: Option<f64>,
+    pub tolerance_multiplier: f64,
+    pub machining_minutes: f64,
+    pub material_cost: f64,
+    pub machining_cost: f64,
+    pub total_cost: f64,
+}
+
+/// Estimate for every part of a model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEstimateResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub rates: RateTable,
+    pub parts: Vec<PartCostEstimate>,
+    pub total_minutes: f64,
+    pub total_cost: f64,
+}
+
+/// Tightest tolerance among controls applied to a part's faces
+fn tightest_control_tolerance(part_id: &str, controls: &[FeatureControlFrame]) -> Option<f64> {
+    controls.iter()
+        .filter(|c| c.features.iter().any(|f| f.part_id == part_id))
+        .map(|c| c.tolerance)
+        .reduce(f64::min)
+}
+
+/// Estimate machining time and cost of one part
+pub fn estimate_part(part: &ParsedPart, tightest_tolerance: Option<f64>, rates: &RateTable) -> PartCostEstimate {
+    let envelope = part.bounding_box.as_ref().map(|b| b.dimensions).unwrap_or([0.0; 3]);
+    let stock_dimensions = envelope.map(|d| d + 2.0 * rates.stock_allowance);
+
+    let stock_volume = stock_dimensions.iter().product::<f64>() / 1000.0;
+    let part_volume = envelope.iter().product::<f64>() / 1000.0 * rates.fill_ratio;
+    let removed_volume = (stock_volume - part_volume).max(0.0);
+
+    let hole_count = recognize_part_holes(part).len();
+    let face_count = part.faces.len();
+    let tolerance_multiplier = rates.tolerance_multiplier(tightest_tolerance);
+
+    let roughing = removed_volume / rates.removal_rate_cm3_per_min.max(1e-9);
+    let features = hole_count as f64 * rates.minutes_per_hole + face_count as f64 * rates.minutes_per_face;
+    let machining_minutes = rates.setup_minutes + (roughing + features) * tolerance_multiplier;
+
+    let material_cost = stock_volume * rates.material_cost_per_cm3;
+    let machining_cost = machining_minutes / 60.0 * rates.machine_rate_per_hour;
+
+    PartCostEstimate {
+        part_id: part.id.clone(),
+        name: part.name.clone(),
+        stock_dimensions,
+        stock_volume_cm3: stock_volume,
+        removed_volume_cm3: removed_volume,
+        hole_count,
+        face_count,
+        tightest_tolerance,
+        tolerance_multiplier,
+        machining_minutes,
+        material_cost,
+        machining_cost,
+        total_cost: material_cost + machining_cost,
+    }
+}
+
+/// Estimate machining cost per part; `part_tolerances` overrides the tightest tolerance per part id
+#[tauri::command]
+pub fn estimate_machining_cost(
+    state: State<'_, ModelStore>,
+    handle: String,
+    rates: Option<RateTable>,
+    part_tolerances: Option<HashMap<String, f64>>,
+) -> CostEstimateResult {
+    let rates = rates.unwrap_or_default();
+    let overrides = part_tolerances.unwrap_or_default();
+
+    let outcome = state.with_model(&handle, |model| {
+        model.assembly.parts.iter()
+            .map(|part| {
+                let tolerance = overrides.get(&part.id).copied()
+                    .or_else(|| tightest_control_tolerance(&part.id, &model.gdt.controls));
+                estimate_part(part, tolerance, &rates)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    match outcome {
+        Ok(parts) => CostEstimateResult {
+            success: true,
+            error: None,
+            total_minutes: parts.iter().map(|p| p.machining_minutes).sum(),
+            total_cost: parts.iter().map(|p| p.total_cost).sum(),
+            rates,
+            parts,
+        },
+        Err(e) => CostEstimateResult {
+            success: false,
+            error: Some(e),
+            rates,
+            parts: Vec::new(),
+            total_minutes: 0.0,
+            total_cost: 0.0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly_parser::PartBoundingBox;
+
+    #[test]
+    fn test_tight_tolerance_raises_cost() {
+        let part = ParsedPart {
+            id: "block".to_string(),
+            name: "block".to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: Some(PartBoundingBox { min: [0.0; 3], max: [96.0, 46.0, 16.0], dimensions: [96.0, 46.0, 16.0] }),
+            faces: Vec::new(),
+            product_definition_id: None,
+        };
+        let rates = RateTable::default();
+
+        let loose = estimate_part(&part, Some(0.2), &rates);
+        let tight = estimate_part(&part, Some(0.02), &rates);
+
+        // 100 x 50 x 20 stock is 100 cm3
+        assert!((loose.stock_volume_cm3 - 100.0).abs() < 1e-9);
+        assert_eq!(loose.tolerance_multiplier, 1.0);
+        assert_eq!(tight.tolerance_multiplier, 1.6);
+        assert!(tight.total_cost > loose.total_cost);
+    }
+}
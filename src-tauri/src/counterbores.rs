@@ -0,0 +1,290 @@
+// Counterbored holes: seating plane, the fastener or washer face seated on it, and the axial links of the screwed joint
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::{ParsedFace, ParsedPart};
+use crate::features::{is_coaxial, outward_normal, recognize_part_holes};
+use crate::hardware::{hardware_library, match_part, HardwareCategory};
+use crate::interface_detection::{transform_direction, transform_point};
+use crate::linalg::{add, dot, norm, reject, scale, sub, Vec3};
+use crate::model_store::ModelStore;
+use crate::tolerance_calc::LinkInput;
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// Largest axial gap (mm) between a seat and a face still counted as seated on it
+const SEAT_GAP: f64 = 0.05;
+
+fn default_depth_tolerance() -> f64 {
+    0.1
+}
+
+fn default_height_tolerance() -> f64 {
+    0.15  // Roughly h13 on an ISO 4762 head
+}
+
+/// Tolerances of the generated links
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CounterboreLinkInput {
+    #[serde(default = "default_depth_tolerance")]
+    pub depth_tolerance: f64,   // ± on the counterbore depth
+    #[serde(default = "default_height_tolerance")]
+    pub height_tolerance: f64,  // ± on the head or washer height above its bearing face
+}
+
+impl Default for CounterboreLinkInput {
+    fn default() -> Self {
+        CounterboreLinkInput { depth_tolerance: default_depth_tolerance(), height_tolerance: default_height_tolerance() }
+    }
+}
+
+impl Validate for CounterboreLinkInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.at_least("depth_tolerance", self.depth_tolerance, 0.0);
+        errors.at_least("height_tolerance", self.height_tolerance, 0.0);
+    }
+}
+
+/// A through or tapped hole with a larger coaxial bore ending on a flat seat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Counterbore {
+    pub id: String,
+    pub part_id: String,
+    pub hole_id: String,
+    pub counterbore_hole_id: String,
+    pub hole_diameter: f64,
+    pub counterbore_diameter: f64,
+    pub seat_face_id: i64,
+    pub seat_point: Vec3,            // Where the axis meets the seat plane (world)
+    pub seat_normal: Vec3,           // Out of the material, towards the open end
+    pub entry_face_id: Option<i64>,
+    pub depth: Option<f64>,          // Entry plane to seat
+}
+
+/// A counterbore with the face seated on it and its axial links
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatPairing {
+    pub counterbore: Counterbore,
+    pub seated_part_id: Option<String>,
+    pub seated_face_id: Option<i64>,
+    pub seated_label: Option<String>,  // Hardware label when the seated part is a recognized fastener
+    pub gap: Option<f64>,              // Seat to bearing face, along the seat normal
+    pub height: Option<f64>,           // Bearing face to the top of the head or washer
+    pub recess: Option<f64>,           // Entry plane to the top of the seated part; positive when sunk below
+    pub links: Vec<LinkInput>,         // Depth (+), gap, then height (-): their sum is the recess
+    pub warnings: Vec<String>,
+}
+
+fn world_face(part: &ParsedPart, face: &ParsedFace) -> (Vec3, Vec3) {
+    (transform_point(&face.center, &part.transform), transform_direction(&outward_normal(face), &part.transform))
+}
+
+/// Planar faces of a part perpendicular to `normal` (outward the same way when `sign` is 1, opposite when -1)
+fn planar_faces<'a>(part: &'a ParsedPart, normal: &'a Vec3, sign: f64) -> impl Iterator<Item = (&'a ParsedFace, Vec3)> + 'a {
+    part.faces.iter()
+        .filter(|f| f.face_type == "planar")
+        .map(move |f| (f, world_face(part, f)))
+        .filter(move |(_, (_, n))| sign * dot(n, normal) > 1.0 - 1e-6)
+        .map(|(f, (center, _))| (f, center))
+}
+
+/// Counterbores of a part: coaxial hole pairs whose step is a planar annulus
+pub fn recognize_part_counterbores(part: &ParsedPart) -> Vec<Counterbore> {
+    let holes = recognize_part_holes(part);
+    let mut counterbores = Vec::new();
+    for bore in &holes {
+        let Some(hole) = holes.iter()
+            .filter(|h| h.diameter < bore.diameter && is_coaxial(&h.center, &h.axis, &bore.center, &bore.axis))
+            .max_by(|a, b| a.diameter.total_cmp(&b.diameter))
+        else {
+            continue;
+        };
+        let radius = bore.diameter / 2.0;
+        let annulus = std::f64::consts::PI * (radius * radius - hole.diameter * hole.diameter / 4.0);
+
+        // The seat is centered on the axis and no larger than the counterbore, unlike the part's top face
+        let seat = part.faces.iter()
+            .filter(|f| f.face_type == "planar" && (f.area <= 0.0 || f.area <= annulus * 1.01))
+            .map(|f| (f, world_face(part, f)))
+            .filter(|(_, (center, normal))| {
+                dot(normal, &bore.axis).abs() > 1.0 - 1e-6 && norm(&reject(&sub(center, &bore.center), &bore.axis)) <= radius
+            })
+            .max_by(|a, b| a.0.area.total_cmp(&b.0.area));
+        let Some((seat, (seat_center, seat_normal))) = seat else { continue };
+        let seat_point = add(&bore.center, &scale(&bore.axis, dot(&sub(&seat_center, &bore.center), &bore.axis)));
+
+        // Entry plane: the nearest face above the seat facing the same way
+        let entry = planar_faces(part, &seat_normal, 1.0)
+            .map(|(f, center)| (f.id, dot(&sub(&center, &seat_point), &seat_normal)))
+            .filter(|(_, offset)| *offset > 1e-6)
+            .min_by(|a, b| a.1.total_cmp(&b.1));
+
+        counterbores.push(Counterbore {
+            id: format!("{}-cbore-{}", part.id, counterbores.len() + 1),
+            part_id: part.id.clone(),
+            hole_id: hole.id.clone(),
+            counterbore_hole_id: bore.id.clone(),
+            hole_diameter: hole.diameter,
+            counterbore_diameter: bore.diameter,
+            seat_face_id: seat.id,
+            seat_point,
+            seat_normal,
+            entry_face_id: entry.map(|e| e.0),
+            depth: entry.map(|e| e.1),
+        });
+    }
+    counterbores
+}
+
+/// Find the face seated on a counterbore among the other parts and build the depth, gap and height links
+pub fn pair_seat(counterbore: Counterbore, parts: &[ParsedPart], input: &CounterboreLinkInput) -> SeatPairing {
+    let normal = counterbore.seat_normal;
+    let radius = counterbore.counterbore_diameter / 2.0;
+    let on_axis = |center: &Vec3| norm(&reject(&sub(center, &counterbore.seat_point), &normal)) <= radius;
+
+    // Facing the seat, on the axis and touching it
+    let seated = parts.iter()
+        .filter(|p| p.id != counterbore.part_id)
+        .flat_map(|p| planar_faces(p, &normal, -1.0).map(move |(f, center)| (p, f, center)))
+        .filter(|(_, _, center)| on_axis(center))
+        .map(|(p, f, center)| (p, f, center, dot(&sub(&center, &counterbore.seat_point), &normal)))
+        .filter(|(_, _, _, gap)| gap.abs() <= SEAT_GAP)
+        .min_by(|a, b| a.3.abs().total_cmp(&b.3.abs()));
+
+    let mut warnings = Vec::new();
+    let Some((part, face, center, gap)) = seated else {
+        warnings.push(format!("Nothing is seated on {}", counterbore.id));
+        return SeatPairing {
+            counterbore,
+            seated_part_id: None,
+            seated_face_id: None,
+            seated_label: None,
+            gap: None,
+            height: None,
+            recess: None,
+            links: Vec::new(),
+            warnings,
+        };
+    };
+
+    // Top of the head or washer: its farthest face looking out of the counterbore
+    let height = planar_faces(part, &normal, 1.0)
+        .filter(|(_, top)| on_axis(top))
+        .map(|(_, top)| dot(&sub(&top, &center), &normal))
+        .filter(|h| *h > 1e-6)
+        .reduce(f64::max);
+    if height.is_none() {
+        warnings.push(format!("No top face found on {} above its bearing face", part.id));
+    }
+    let seated_label = match_part(part, &hardware_library())
+        .filter(|m| m.category == HardwareCategory::Fastener)
+        .map(|m| m.label);
+
+    let link = |name: String, nominal: f64, tolerance: f64, direction: &str| LinkInput {
+        name: Some(name),
+        nominal,
+        plus_tolerance: tolerance,
+        minus_tolerance: tolerance,
+        direction: direction.to_string(),
+        distribution: "normal".to_string(),
+        sigma: None,
+        inspected: false,
+    };
+    let mut links = Vec::new();
+    match counterbore.depth {
+        Some(depth) => links.push(link(format!("{} depth", counterbore.id), depth, input.depth_tolerance, "positive")),
+        None => warnings.push(format!("No entry plane found above the {} seat", counterbore.id)),
+    }
+    if gap.abs() > 1e-9 {
+        links.push(link(format!("{} seat gap", counterbore.id), -gap, 0.0, "positive"));
+    }
+    if let Some(height) = height {
+        links.push(link(format!("{} height above seat", part.id), height, input.height_tolerance, "negative"));
+    }
+
+    SeatPairing {
+        recess: counterbore.depth.zip(height).map(|(depth, height)| depth - gap - height),
+        counterbore,
+        seated_part_id: Some(part.id.clone()),
+        seated_face_id: Some(face.id),
+        seated_label,
+        gap: Some(gap),
+        height,
+        links,
+        warnings,
+    }
+}
+
+/// Counterbores of a loaded model paired with the fastener or washer faces seated on them
+#[tauri::command]
+pub fn pair_counterbore_seats(
+    state: State<'_, ModelStore>,
+    handle: String,
+    input: Checked<CounterboreLinkInput>,
+) -> Result<Vec<SeatPairing>, String> {
+    state.with_model(&handle, |model| {
+        let parts = &model.assembly.parts;
+        let pairings: Vec<SeatPairing> = parts.iter()
+            .flat_map(recognize_part_counterbores)
+            .map(|counterbore| pair_seat(counterbore, parts, &input))
+            .collect();
+        tracing::info!(
+            handle = %handle,
+            counterbores = pairings.len(),
+            seated = pairings.iter().filter(|p| p.seated_part_id.is_some()).count(),
+            "counterbore seats paired"
+        );
+        pairings
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{face, hole};
+
+    fn part(id: &str, faces: Vec<ParsedFace>) -> ParsedPart {
+        ParsedPart {
+            id: id.to_string(),
+            name: id.to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: None,
+            faces,
+            product_definition_id: None,
+        }
+    }
+
+    #[test]
+    fn test_counterbore_seats_screw_head() {
+        // 10 mm plate, 6.6 mm hole with an 11 mm counterbore 6.5 deep; M6 head 6 tall sits on the seat
+        let plate = part("plate", vec![
+            hole(1, [0.0, 0.0, 1.75], 3.3),
+            hole(2, [0.0, 0.0, 6.75], 5.5),
+            ParsedFace { area: 60.8, ..face(3, "planar", [0.0, 0.0, 3.5], [0.0, 0.0, 1.0], None) },
+            ParsedFace { area: 4800.0, ..face(4, "planar", [40.0, 30.0, 10.0], [0.0, 0.0, 1.0], None) },
+            ParsedFace { area: 4800.0, ..face(5, "planar", [40.0, 30.0, 0.0], [0.0, 0.0, -1.0], None) },
+        ]);
+        let screw = part("screw", vec![
+            ParsedFace { area: 50.0, ..face(1, "planar", [0.0, 0.0, 3.5], [0.0, 0.0, -1.0], None) },
+            ParsedFace { area: 78.5, ..face(2, "planar", [0.0, 0.0, 9.5], [0.0, 0.0, 1.0], None) },
+        ]);
+
+        let counterbores = recognize_part_counterbores(&plate);
+        assert_eq!(counterbores.len(), 1);
+        let cbore = &counterbores[0];
+        assert_eq!((cbore.seat_face_id, cbore.entry_face_id), (3, Some(4)));
+        assert!((cbore.depth.unwrap() - 6.5).abs() < 1e-9);
+
+        let pairing = pair_seat(cbore.clone(), &[plate.clone(), screw], &CounterboreLinkInput::default());
+        assert_eq!((pairing.seated_part_id.as_deref(), pairing.seated_face_id), (Some("screw"), Some(1)));
+        assert!((pairing.height.unwrap() - 6.0).abs() < 1e-9);
+        assert!((pairing.recess.unwrap() - 0.5).abs() < 1e-9);
+        assert_eq!(pairing.links.len(), 2);
+        assert_eq!(pairing.links[1].direction, "negative");
+        assert!(pairing.warnings.is_empty());
+
+        let empty = pair_seat(cbore.clone(), &[plate], &CounterboreLinkInput::default());
+        assert!(empty.seated_part_id.is_none() && !empty.warnings.is_empty());
+    }
+}
@@ -0,0 +1,35 @@
+// API keys for cloud connectors, kept in the OS keychain instead of app data
+
+use keyring::Entry;
+
+/// Keychain service all connector secrets are filed under
+const KEYCHAIN_SERVICE: &str = "ohmframe-copilot";
+
+fn entry(name: &str) -> Result<Entry, String> {
+    Entry::new(KEYCHAIN_SERVICE, name).map_err(|e| format!("Keychain unavailable: {}", e))
+}
+
+/// Store or replace a secret
+pub fn store_secret(name: &str, secret: &str) -> Result<(), String> {
+    entry(name)?
+        .set_password(secret)
+        .map_err(|e| format!("Failed to store {} in the keychain: {}", name, e))
+}
+
+/// Read a secret; None when it was never stored
+pub fn load_secret(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(secret) => Ok(Some(secret)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("Failed to read {} from the keychain: {}", name, e)),
+    }
+}
+
+/// Remove a secret, returning whether one existed
+pub fn delete_secret(name: &str) -> Result<bool, String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(format!("Failed to remove {} from the keychain: {}", name, e)),
+    }
+}
@@ -0,0 +1,174 @@
+// Custom URL scheme handling (ohmframe://)
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{App, AppHandle, Emitter, Manager, State};
+use tauri_plugin_deep_link::DeepLinkExt;
+use url::Url;
+
+use crate::recent_files::RecentFilesState;
+
+/// URL scheme registered for the app
+pub const DEEP_LINK_SCHEME: &str = "ohmframe";
+
+/// Event emitted to the frontend when a deep link arrives
+pub const DEEP_LINK_EVENT: &str = "deep-link-open";
+
+/// Parsed deep link request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeepLinkRequest {
+    pub url: String,
+    pub action: String,              // "open", "analyze"
+    pub path: Option<String>,
+    pub mode: Option<String>,        // "general", "dfm", "tolerance"
+    pub params: HashMap<String, String>,
+}
+
+/// Deep link received before the frontend was ready to listen
+#[derive(Default)]
+pub struct PendingDeepLink(pub Mutex<Option<DeepLinkRequest>>);
+
+/// Parse an ohmframe:// URL into a request
+pub fn parse_deep_link(raw: &str) -> Result<DeepLinkRequest, String> {
+    let url = Url::parse(raw).map_err(|e| format!("Invalid deep link '{}': {}", raw, e))?;
+
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(format!("Unsupported URL scheme: {}", url.scheme()));
+    }
+
+    // ohmframe://open?... puts the action in the host position
+    let action = url.host_str()
+        .map(|h| h.to_string())
+        .or_else(|| url.path_segments().and_then(|mut s| s.next()).map(|s| s.to_string()))
+        .filter(|a| !a.is_empty())
+        .unwrap_or_else(|| "open".to_string());
+
+    if action != "open" && action != "analyze" {
+        return Err(format!("Unsupported deep link action: {}", action));
+    }
+
+    let mut params: HashMap<String, String> = url.query_pairs()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+    let path = params.remove("path").filter(|p| !p.is_empty());
+    let mode = match params.remove("mode") {
+        Some(mode) => Some(normalize_mode(&mode)?),
+        None => None,
+    };
+
+    if let Some(path) = &path {
+        let lower = path.to_lowercase();
+        if !lower.ends_with(".step") && !lower.ends_with(".stp") {
+            return Err(format!("Deep link path is not a STEP file: {}", path));
+        }
+    }
+
+    Ok(DeepLinkRequest {
+        url: raw.to_string(),
+        action,
+        path,
+        mode,
+        params,
+    })
+}
+
+/// Map URL mode names onto the frontend analysis modes
+fn normalize_mode(mode: &str) -> Result<String, String> {
+    match mode.to_lowercase().as_str() {
+        "general" | "chat" => Ok("general".to_string()),
+        "dfm" => Ok("dfm".to_string()),
+        "tolerance" | "stackup" => Ok("tolerance".to_string()),
+        other => Err(format!("Unsupported deep link mode: {}", other)),
+    }
+}
+
+/// Handle an incoming deep link: record the file, queue the request, and notify the frontend
+pub fn handle_deep_link(app: &AppHandle, raw: &str) {
+    let request = match parse_deep_link(raw) {
+        Ok(request) => request,
+        Err(e) => {
+            tracing::warn!(url = raw, error = %e, "rejected deep link");
+            let _ = app.emit("deep-link-error", e);
+            return;
+        }
+    };
+    tracing::info!(action = %request.action, path = ?request.path, mode = ?request.mode, "deep link received");
+
+    // Register the file in recent files so the frontend can reopen it by path
+    if let Some(path) = &request.path {
+        if let Some(recent) = app.try_state::<RecentFilesState>() {
+            if let Err(e) = recent.record(path.clone(), None, None) {
+                let _ = app.emit("deep-link-error", e);
+                return;
+            }
+        }
+    }
+
+    if let Some(pending) = app.try_state::<PendingDeepLink>() {
+        if let Ok(mut slot) = pending.0.lock() {
+            *slot = Some(request.clone());
+        }
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+
+    let _ = app.emit(DEEP_LINK_EVENT, request);
+}
+
+/// Register the scheme and route launch-time and runtime deep links
+pub fn setup_deep_links(app: &App) -> Result<(), String> {
+    app.manage(PendingDeepLink::default());
+
+    // Windows and Linux only register schemes at install time; make dev builds work too
+    #[cfg(any(windows, target_os = "linux"))]
+    app.deep_link()
+        .register_all()
+        .map_err(|e| format!("Failed to register deep link scheme: {}", e))?;
+
+    let handle = app.handle().clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle_deep_link(&handle, url.as_str());
+        }
+    });
+
+    let launch_urls = app.deep_link()
+        .get_current()
+        .map_err(|e| format!("Failed to read launch deep link: {}", e))?;
+    for url in launch_urls.unwrap_or_default() {
+        handle_deep_link(app.handle(), url.as_str());
+    }
+
+    Ok(())
+}
+
+/// Take the deep link that launched the app, if the frontend has not consumed it yet
+#[tauri::command]
+pub fn take_pending_deep_link(state: State<'_, PendingDeepLink>) -> Option<DeepLinkRequest> {
+    state.0.lock().ok().and_then(|mut slot| slot.take())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_open_with_mode() {
+        let request = parse_deep_link("ohmframe://open?path=%2Fparts%2Fbracket.step&mode=stackup").unwrap();
+        assert_eq!(request.action, "open");
+        assert_eq!(request.path.as_deref(), Some("/parts/bracket.step"));
+        assert_eq!(request.mode.as_deref(), Some("tolerance"));
+    }
+
+    #[test]
+    fn test_rejects_foreign_scheme_and_files() {
+        assert!(parse_deep_link("https://open?path=a.step").is_err());
+        assert!(parse_deep_link("ohmframe://open?path=/etc/passwd").is_err());
+        assert!(parse_deep_link("ohmframe://open?mode=unknown").is_err());
+    }
+}
@@ -0,0 +1,228 @@
+// Design-for-manufacturing rule checks against recognized features
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::{ParsedFace, ParsedPart};
+use crate::features::{outward_normal, part_extent_along, recognize_part_holes, Hole};
+use crate::linalg::{dot, normalize, sub};
+use crate::model_store::ModelStore;
+
+/// Rule limits; a rule set to None is skipped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DfmRules {
+    pub min_wall_thickness: Option<f64>,
+    pub min_hole_diameter: Option<f64>,
+    pub max_depth_to_diameter: Option<f64>,
+    pub min_internal_radius: Option<f64>,
+}
+
+impl Default for DfmRules {
+    fn default() -> Self {
+        DfmRules {
+            min_wall_thickness: Some(1.0),
+            min_hole_diameter: Some(1.0),
+            max_depth_to_diameter: Some(10.0),
+            min_internal_radius: Some(0.5),
+        }
+    }
+}
+
+/// Rule that produced a violation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DfmRule {
+    MinWallThickness,
+    MinHoleDiameter,
+    MaxDepthToDiameter,
+    MinInternalRadius,
+}
+
+/// A rule violation with the faces to highlight
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DfmViolation {
+    pub rule: DfmRule,
+    pub part_id: String,
+    pub face_ids: Vec<i64>,
+    pub value: f64,
+    pub limit: f64,
+    pub message: String,
+}
+
+/// Result of a DFM check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DfmResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub rules: DfmRules,
+    pub violations: Vec<DfmViolation>,
+    pub holes_checked: usize,
+    pub faces_checked: usize,
+}
+
+/// Thin walls: opposing parallel planes whose outward normals face away from each other
+fn check_walls(part: &ParsedPart, min: f64, violations: &mut Vec<DfmViolation>) {
+    let planes: Vec<&ParsedFace> = part.faces.iter().filter(|f| f.face_type == "planar").collect();
+
+    for (i, a) in planes.iter().enumerate() {
+        let na = outward_normal(a);
+        for b in &planes[i + 1..] {
+            if dot(&na, &outward_normal(b)) > -0.9999 {
+                continue;
+            }
+            // Material lies between the planes only when b sits behind a
+            let thickness = -dot(&sub(&b.center, &a.center), &na);
+            if thickness > 1e-9 && thickness < min {
+                violations.push(DfmViolation {
+                    rule: DfmRule::MinWallThickness,
+                    part_id: part.id.clone(),
+                    face_ids: vec![a.id, b.id],
+                    value: thickness,
+                    limit: min,
+                    message: format!("Wall between faces {} and {} is {:.3} thick (min {:.3})", a.id, b.id, thickness, min),
+                });
+            }
+        }
+    }
+}
+
+fn check_holes(part: &ParsedPart, holes: &[Hole], rules: &DfmRules, violations: &mut Vec<DfmViolation>) {
+    for hole in holes {
+        if let Some(min) = rules.min_hole_diameter {
+            if hole.diameter < min {
+                violations.push(DfmViolation {
+                    rule: DfmRule::MinHoleDiameter,
+                    part_id: part.id.clone(),
+                    face_ids: hole.face_ids.clone(),
+                    value: hole.diameter,
+                    limit: min,
+                    message: format!("{} is {:.3} in diameter (min {:.3})", hole.id, hole.diameter, min),
+                });
+            }
+        }
+
+        if let Some(max) = rules.max_depth_to_diameter {
+            // Depth is estimated as the part extent along the axis, i.e. a through hole
+            let local_axis = part.faces.iter()
+                .find(|f| hole.face_ids.first() == Some(&f.id))
+                .and_then(|f| f.axis);
+            let Some(depth) = local_axis.and_then(|axis| part_extent_along(part, &normalize(&axis))) else { continue };
+
+            let ratio = depth / hole.diameter;
+            if ratio > max {
+                violations.push(DfmViolation {
+                    rule: DfmRule::MaxDepthToDiameter,
+                    part_id: part.id.clone(),
+                    face_ids: hole.face_ids.clone(),
+                    value: ratio,
+                    limit: max,
+                    message: format!("{} is {:.1}x its diameter deep (max {:.1})", hole.id, ratio, max),
+                });
+            }
+        }
+    }
+}
+
+/// Concave cylinders tighter than a cutter can reach; faces already flagged as small holes are skipped
+fn check_internal_radii(part: &ParsedPart, min: f64, violations: &mut Vec<DfmViolation>) {
+    let flagged: Vec<i64> = violations.iter()
+        .filter(|v| v.rule == DfmRule::MinHoleDiameter && v.part_id == part.id)
+        .flat_map(|v| v.face_ids.iter().copied())
+        .collect();
+
+    for face in &part.faces {
+        let concave = face.face_type == "cylindrical" && face.same_sense == Some(false);
+        let Some(radius) = face.radius.filter(|_| concave) else { continue };
+        if radius < min && !flagged.contains(&face.id) {
+            violations.push(DfmViolation {
+                rule: DfmRule::MinInternalRadius,
+                part_id: part.id.clone(),
+                face_ids: vec![face.id],
+                value: radius,
+                limit: min,
+                message: format!("Internal radius on face {} is {:.3} (min {:.3})", face.id, radius, min),
+            });
+        }
+    }
+}
+
+/// Run every enabled rule against one part
+pub fn check_part(part: &ParsedPart, rules: &DfmRules) -> (Vec<DfmViolation>, usize) {
+    let holes = recognize_part_holes(part);
+    let mut violations = Vec::new();
+
+    if let Some(min) = rules.min_wall_thickness {
+        check_walls(part, min, &mut violations);
+    }
+    check_holes(part, &holes, rules, &mut violations);
+    if let Some(min) = rules.min_internal_radius {
+        check_internal_radii(part, min, &mut violations);
+    }
+
+    (violations, holes.len())
+}
+
+/// Check a loaded model against manufacturability rules
+#[tauri::command]
+pub fn run_dfm_check(state: State<'_, ModelStore>, handle: String, rules: Option<DfmRules>) -> DfmResult {
+    let rules = rules.unwrap_or_default();
+    let outcome = state.with_model(&handle, |model| {
+        let mut violations = Vec::new();
+        let mut holes_checked = 0;
+        for part in &model.assembly.parts {
+            let (part_violations, holes) = check_part(part, &rules);
+            violations.extend(part_violations);
+            holes_checked += holes;
+        }
+        let faces_checked = model.assembly.parts.iter().map(|p| p.faces.len()).sum();
+        (violations, holes_checked, faces_checked)
+    });
+
+    match outcome {
+        Ok((violations, holes_checked, faces_checked)) => {
+            tracing::info!(handle = %handle, violations = violations.len(), "DFM check complete");
+            DfmResult { success: true, error: None, rules, violations, holes_checked, faces_checked }
+        }
+        Err(e) => DfmResult {
+            success: false,
+            error: Some(e),
+            rules,
+            violations: Vec::new(),
+            holes_checked: 0,
+            faces_checked: 0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly_parser::PartBoundingBox;
+    use crate::test_fixtures::face;
+
+    #[test]
+    fn test_thin_wall_and_deep_small_hole() {
+        let part = ParsedPart {
+            id: "bracket".to_string(),
+            name: "bracket".to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: Some(PartBoundingBox { min: [0.0, 0.0, 0.0], max: [40.0, 40.0, 20.0], dimensions: [40.0, 40.0, 20.0] }),
+            faces: vec![
+                face(0, "planar", [0.0, 0.0, 20.0], [0.0, 0.0, 1.0], None),
+                face(1, "planar", [0.0, 0.0, 0.0], [0.0, 0.0, -1.0], None),
+                face(2, "planar", [0.0, 0.0, 0.5], [0.0, 0.0, 1.0], None),    // Pocket floor leaving 0.5 mm
+                face(3, "cylindrical", [10.0, 10.0, 0.0], [0.0, 0.0, 1.0], Some(0.8)),
+            ],
+            product_definition_id: None,
+        };
+
+        let (violations, holes) = check_part(&part, &DfmRules::default());
+        let rules: Vec<DfmRule> = violations.iter().map(|v| v.rule).collect();
+        assert_eq!(holes, 1);
+        assert_eq!(rules, vec![DfmRule::MinWallThickness, DfmRule::MaxDepthToDiameter]);
+        assert_eq!(violations[0].face_ids, vec![1, 2]);
+        assert!((violations[1].value - 12.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,300 @@
+// Fit candidate distributions to measured values of a dimension and turn the best into a stackup link
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::fit_statistics::standard_normal_cdf;
+use crate::tolerance_calc::LinkInput;
+
+const MIN_VALUES: usize = 5;
+const TAIL: f64 = 0.001349898;  // One-sided tail beyond 3 sigma
+
+/// One fitted distribution with goodness-of-fit statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributionCandidate {
+    pub distribution: String,              // "normal", "lognormal" or "weibull"
+    pub parameters: BTreeMap<String, f64>, // Maximum likelihood estimates
+    pub log_likelihood: f64,
+    pub aic: f64,
+    pub ks_statistic: f64,                 // Largest gap between empirical and fitted CDF
+    pub ks_p_value: f64,
+}
+
+/// Fitted candidates for a column of measurements plus a link built from the best one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributionFitResult {
+    pub column: String,
+    pub count: usize,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub candidates: Vec<DistributionCandidate>,  // Best (lowest AIC) first
+    pub link: LinkInput,                         // 3-sigma-equivalent limits of the best fit
+}
+
+/// Fitted model, able to evaluate its density and quantiles
+enum Fitted {
+    Normal { mean: f64, sigma: f64 },
+    LogNormal { mu: f64, sigma: f64 },
+    Weibull { shape: f64, scale: f64 },
+}
+
+impl Fitted {
+    fn name(&self) -> &'static str {
+        match self {
+            Fitted::Normal { .. } => "normal",
+            Fitted::LogNormal { .. } => "lognormal",
+            Fitted::Weibull { .. } => "weibull",
+        }
+    }
+
+    fn parameters(&self) -> BTreeMap<String, f64> {
+        let pairs = match *self {
+            Fitted::Normal { mean, sigma } => [("mean", mean), ("sigma", sigma)],
+            Fitted::LogNormal { mu, sigma } => [("mu", mu), ("sigma", sigma)],
+            Fitted::Weibull { shape, scale } => [("shape", shape), ("scale", scale)],
+        };
+        pairs.into_iter().map(|(k, v)| (k.to_string(), v)).collect()
+    }
+
+    fn ln_pdf(&self, x: f64) -> f64 {
+        let ln_sqrt_2pi = 0.5 * (2.0 * std::f64::consts::PI).ln();
+        match *self {
+            Fitted::Normal { mean, sigma } => -ln_sqrt_2pi - sigma.ln() - 0.5 * ((x - mean) / sigma).powi(2),
+            Fitted::LogNormal { mu, sigma } => -ln_sqrt_2pi - sigma.ln() - x.ln() - 0.5 * ((x.ln() - mu) / sigma).powi(2),
+            Fitted::Weibull { shape, scale } => {
+                let z = x / scale;
+                shape.ln() - scale.ln() + (shape - 1.0) * z.ln() - z.powf(shape)
+            }
+        }
+    }
+
+    fn cdf(&self, x: f64) -> f64 {
+        match *self {
+            Fitted::Normal { mean, sigma } => standard_normal_cdf((x - mean) / sigma),
+            Fitted::LogNormal { mu, sigma } => standard_normal_cdf((x.ln() - mu) / sigma),
+            Fitted::Weibull { shape, scale } => 1.0 - (-(x / scale).powf(shape)).exp(),
+        }
+    }
+
+    /// Median and the 3-sigma-equivalent lower and upper limits
+    fn limits(&self) -> (f64, f64, f64) {
+        match *self {
+            Fitted::Normal { mean, sigma } => (mean, mean - 3.0 * sigma, mean + 3.0 * sigma),
+            Fitted::LogNormal { mu, sigma } => (mu.exp(), (mu - 3.0 * sigma).exp(), (mu + 3.0 * sigma).exp()),
+            Fitted::Weibull { shape, scale } => {
+                let quantile = |p: f64| scale * (-(1.0 - p).ln()).powf(1.0 / shape);
+                (quantile(0.5), quantile(TAIL), quantile(1.0 - TAIL))
+            }
+        }
+    }
+}
+
+fn mean_and_sigma(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    (mean, (values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt())
+}
+
+/// Two-parameter Weibull MLE: bisect the shape equation in log space on values scaled to at most 1
+fn fit_weibull(values: &[f64]) -> Option<Fitted> {
+    let max = values.iter().copied().fold(f64::MIN, f64::max);
+    let logs: Vec<f64> = values.iter().map(|x| (x / max).ln()).collect();
+    let mean_log = logs.iter().sum::<f64>() / logs.len() as f64;
+    let equation = |k: f64| {
+        let (weighted, total) = logs.iter().fold((0.0, 0.0), |(w, t), l| {
+            let p = (k * l).exp();
+            (w + p * l, t + p)
+        });
+        weighted / total - 1.0 / k - mean_log
+    };
+
+    let (mut lo, mut hi) = (1e-3f64.ln(), 1e7f64.ln());
+    if equation(lo.exp()) > 0.0 || equation(hi.exp()) < 0.0 {
+        return None;
+    }
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if equation(mid.exp()) < 0.0 { lo = mid } else { hi = mid }
+    }
+    let shape = (0.5 * (lo + hi)).exp();
+    let scaled = (logs.iter().map(|l| (shape * l).exp()).sum::<f64>() / logs.len() as f64).powf(1.0 / shape);
+    Some(Fitted::Weibull { shape, scale: scaled * max })
+}
+
+/// Asymptotic Kolmogorov-Smirnov p-value for statistic d over n samples
+fn ks_p_value(d: f64, n: usize) -> f64 {
+    let sqrt_n = (n as f64).sqrt();
+    let lambda = (sqrt_n + 0.12 + 0.11 / sqrt_n) * d;
+    if lambda < 0.3 {
+        return 1.0;  // The series does not converge here and the value is 1 to five places
+    }
+    let sum: f64 = (1..=100)
+        .map(|j| {
+            let sign = if j % 2 == 1 { 1.0 } else { -1.0 };
+            sign * (-2.0 * (j * j) as f64 * lambda * lambda).exp()
+        })
+        .sum();
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+fn evaluate(fitted: &Fitted, sorted: &[f64]) -> DistributionCandidate {
+    let n = sorted.len() as f64;
+    let log_likelihood: f64 = sorted.iter().map(|x| fitted.ln_pdf(*x)).sum();
+    let ks_statistic = sorted.iter().enumerate()
+        .map(|(i, x)| {
+            let f = fitted.cdf(*x);
+            (f - i as f64 / n).abs().max(((i + 1) as f64 / n - f).abs())
+        })
+        .fold(0.0, f64::max);
+    DistributionCandidate {
+        distribution: fitted.name().to_string(),
+        parameters: fitted.parameters(),
+        log_likelihood,
+        aic: 2.0 * 2.0 - 2.0 * log_likelihood,
+        ks_statistic,
+        ks_p_value: ks_p_value(ks_statistic, sorted.len()),
+    }
+}
+
+/// Values of the named column (or the first numeric one); a header row is detected when the first
+/// row is not numeric
+pub fn read_measurements(csv: &str, column: Option<&str>) -> Result<(String, Vec<f64>), String> {
+    let split = |line: &str| -> Vec<String> {
+        line.split([',', ';', '\t']).map(|f| f.trim().trim_matches('"').to_string()).collect()
+    };
+    let rows: Vec<Vec<String>> = csv.lines().filter(|l| !l.trim().is_empty()).map(split).collect();
+    let first = rows.first().ok_or("CSV is empty")?;
+    let has_header = first.iter().any(|f| f.parse::<f64>().is_err());
+    let body = if has_header { &rows[1..] } else { &rows[..] };
+
+    let index = match column {
+        Some(name) if has_header => first.iter()
+            .position(|h| h.eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("CSV has no column named {}", name))?,
+        Some(name) => name.parse::<usize>().map_err(|_| "CSV has no header row to look up the column".to_string())?,
+        None => (0..first.len())
+            .find(|c| body.iter().any(|row| row.get(*c).is_some_and(|f| f.parse::<f64>().is_ok())))
+            .ok_or("CSV has no numeric column")?,
+    };
+    let label = if has_header { first.get(index).cloned().unwrap_or_default() } else { format!("column {}", index + 1) };
+
+    let values: Vec<f64> = body.iter()
+        .filter_map(|row| row.get(index).and_then(|f| f.parse::<f64>().ok()))
+        .filter(|v| v.is_finite())
+        .collect();
+    if values.len() < MIN_VALUES {
+        return Err(format!("Need at least {} measured values, found {}", MIN_VALUES, values.len()));
+    }
+    Ok((label, values))
+}
+
+/// Fit normal, lognormal and Weibull distributions and build a link from the best by AIC
+pub fn fit_distributions(label: String, mut values: Vec<f64>, direction: &str) -> Result<DistributionFitResult, String> {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let (mean, sigma) = mean_and_sigma(&values);
+    if sigma <= 0.0 {
+        return Err("Measured values do not vary".to_string());
+    }
+
+    let mut fits = vec![Fitted::Normal { mean, sigma }];
+    if values[0] > 0.0 {
+        let logs: Vec<f64> = values.iter().map(|x| x.ln()).collect();
+        let (mu, log_sigma) = mean_and_sigma(&logs);
+        fits.push(Fitted::LogNormal { mu, sigma: log_sigma });
+        fits.extend(fit_weibull(&values));
+    }
+
+    let mut ranked: Vec<(DistributionCandidate, Fitted)> = fits.into_iter()
+        .map(|f| (evaluate(&f, &values), f))
+        .filter(|(c, _)| c.aic.is_finite())
+        .collect();
+    ranked.sort_by(|a, b| a.0.aic.total_cmp(&b.0.aic));
+    let (best, best_fit) = ranked.first().ok_or("No distribution could be fitted")?;
+    tracing::debug!(column = %label, best = %best.distribution, values = values.len(), "fitted measured distribution");
+
+    // The stackup samples a normal over the limits, centred on their midpoint
+    let (median, lower, upper) = best_fit.limits();
+    let link = LinkInput {
+        name: Some(label.clone()),
+        nominal: median,
+        plus_tolerance: (upper - median).max(0.0),
+        minus_tolerance: (median - lower).max(0.0),
+        direction: direction.to_string(),
+        distribution: "normal".to_string(),
+        sigma: Some(3.0),
+        inspected: false,
+    };
+
+    let n = values.len() as f64;
+    Ok(DistributionFitResult {
+        column: label,
+        count: values.len(),
+        mean,
+        std_dev: sigma * (n / (n - 1.0)).sqrt(),
+        candidates: ranked.into_iter().map(|(c, _)| c).collect(),
+        link,
+    })
+}
+
+/// Fit distributions to a CSV column of measured values for one dimension
+#[tauri::command]
+pub fn fit_measured_distribution(
+    csv: String,
+    column: Option<String>,
+    direction: Option<String>,
+) -> Result<DistributionFitResult, String> {
+    let direction = direction.unwrap_or_else(|| "positive".to_string());
+    if direction != "positive" && direction != "negative" {
+        return Err(format!("direction: expected \"positive\" or \"negative\", got \"{}\"", direction));
+    }
+    let (label, values) = read_measurements(&csv, column.as_deref())?;
+    fit_distributions(label, values, &direction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, Normal, Weibull};
+
+    #[test]
+    fn test_read_measurements() {
+        let csv = "part,length\nA,10.01\nB,9.99\nC,10.02\nD,n/a\nE,10.00\nF,9.98\n";
+        let (label, values) = read_measurements(csv, None).unwrap();
+        assert_eq!(label, "length");
+        assert_eq!(values, vec![10.01, 9.99, 10.02, 10.00, 9.98]);
+        assert!(read_measurements(csv, Some("width")).is_err());
+        assert!(read_measurements("1\n2\n", None).is_err());
+    }
+
+    #[test]
+    fn test_normal_data_fits_normal() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        let normal = Normal::new(10.0, 0.02).unwrap();
+        let values: Vec<f64> = (0..500).map(|_| normal.sample(&mut rng)).collect();
+        let result = fit_distributions("length".to_string(), values, "positive").unwrap();
+
+        let normal = result.candidates.iter().find(|c| c.distribution == "normal").unwrap();
+        assert!((normal.parameters["mean"] - 10.0).abs() < 0.005);
+        assert!(normal.ks_p_value > 0.001);
+        assert!(result.candidates.iter().any(|c| c.distribution == "weibull"));
+        assert!((result.link.nominal - 10.0).abs() < 0.005);
+        assert!((result.link.plus_tolerance - 0.06).abs() < 0.01);
+        assert_eq!(result.link.name.as_deref(), Some("length"));
+    }
+
+    #[test]
+    fn test_skewed_data_prefers_weibull() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        let weibull = Weibull::new(2.0, 1.5).unwrap();
+        let values: Vec<f64> = (0..1000).map(|_| weibull.sample(&mut rng)).collect();
+        let result = fit_distributions("runout".to_string(), values, "negative").unwrap();
+
+        let best = &result.candidates[0];
+        assert_eq!(best.distribution, "weibull");
+        assert!((best.parameters["shape"] - 1.5).abs() < 0.15);
+        assert!((best.parameters["scale"] - 2.0).abs() < 0.15);
+        assert!(result.link.plus_tolerance > result.link.minus_tolerance);
+        assert_eq!(result.link.direction, "negative");
+    }
+}
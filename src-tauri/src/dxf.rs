@@ -0,0 +1,158 @@
+// DXF (R12) export of model sections for 2D CAD and cutting quotes
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use tauri::State;
+
+use crate::mesh_query::{plane_basis, slice_mesh, SlicePlane, SliceResult};
+use crate::model_store::ModelStore;
+
+/// Layer the section outlines are drawn on
+const SECTION_LAYER: &str = "SECTION";
+
+/// Result of a DXF export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DxfExportResult {
+    pub output_path: Option<String>,
+    pub content: Option<String>,     // DXF text when no output path was given
+    pub polyline_count: usize,
+    pub extent: [f64; 2],            // Drawing width and height
+}
+
+fn group(out: &mut String, code: i32, value: impl std::fmt::Display) {
+    let _ = write!(out, "{}\n{}\n", code, value);
+}
+
+/// Section polylines as an R12 DXF, drawn in the plane's (u, v) axes relative to its origin
+pub fn section_to_dxf(slice: &SliceResult) -> (String, [f64; 2]) {
+    let (_, u, v) = plane_basis(&slice.plane.normal);
+    let origin = slice.plane.origin;
+    let to_2d = |p: &[f64; 3]| {
+        let d = [p[0] - origin[0], p[1] - origin[1], p[2] - origin[2]];
+        [d[0] * u[0] + d[1] * u[1] + d[2] * u[2], d[0] * v[0] + d[1] * v[1] + d[2] * v[2]]
+    };
+
+    let polylines: Vec<(Vec<[f64; 2]>, bool)> = slice.polylines.iter()
+        .filter(|p| p.points.len() >= 2)
+        .map(|p| (p.points.iter().map(to_2d).collect(), p.closed))
+        .collect();
+
+    let mut min = [f64::MAX; 2];
+    let mut max = [f64::MIN; 2];
+    for p in polylines.iter().flat_map(|(points, _)| points) {
+        for k in 0..2 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+    if polylines.is_empty() {
+        min = [0.0; 2];
+        max = [0.0; 2];
+    }
+
+    let mut out = String::new();
+    group(&mut out, 0, "SECTION");
+    group(&mut out, 2, "HEADER");
+    group(&mut out, 9, "$ACADVER");
+    group(&mut out, 1, "AC1009");
+    group(&mut out, 9, "$EXTMIN");
+    group(&mut out, 10, min[0]);
+    group(&mut out, 20, min[1]);
+    group(&mut out, 9, "$EXTMAX");
+    group(&mut out, 10, max[0]);
+    group(&mut out, 20, max[1]);
+    group(&mut out, 0, "ENDSEC");
+
+    group(&mut out, 0, "SECTION");
+    group(&mut out, 2, "TABLES");
+    group(&mut out, 0, "TABLE");
+    group(&mut out, 2, "LAYER");
+    group(&mut out, 70, 1);
+    group(&mut out, 0, "LAYER");
+    group(&mut out, 2, SECTION_LAYER);
+    group(&mut out, 70, 0);
+    group(&mut out, 62, 7);
+    group(&mut out, 6, "CONTINUOUS");
+    group(&mut out, 0, "ENDTAB");
+    group(&mut out, 0, "ENDSEC");
+
+    group(&mut out, 0, "SECTION");
+    group(&mut out, 2, "ENTITIES");
+    for (points, closed) in &polylines {
+        group(&mut out, 0, "POLYLINE");
+        group(&mut out, 8, SECTION_LAYER);
+        group(&mut out, 66, 1);
+        group(&mut out, 70, if *closed { 1 } else { 0 });
+        for p in points {
+            group(&mut out, 0, "VERTEX");
+            group(&mut out, 8, SECTION_LAYER);
+            group(&mut out, 10, p[0]);
+            group(&mut out, 20, p[1]);
+            group(&mut out, 30, 0.0);
+        }
+        group(&mut out, 0, "SEQEND");
+        group(&mut out, 8, SECTION_LAYER);
+    }
+    group(&mut out, 0, "ENDSEC");
+    group(&mut out, 0, "EOF");
+
+    (out, [max[0] - min[0], max[1] - min[1]])
+}
+
+/// Slice a loaded model and export the section as DXF, to a file or returned as text
+#[tauri::command]
+pub fn export_section_dxf(
+    state: State<'_, ModelStore>,
+    handle: String,
+    plane: SlicePlane,
+    output_path: Option<String>,
+) -> Result<DxfExportResult, String> {
+    let slice = state.with_model(&handle, |model| {
+        model.mesh.as_ref()
+            .map(|mesh| slice_mesh(mesh, plane))
+            .ok_or_else(|| "Model has no mesh".to_string())
+    })??;
+    let (dxf, extent) = section_to_dxf(&slice);
+
+    let content = match &output_path {
+        Some(path) => {
+            std::fs::write(path, &dxf).map_err(|e| format!("Failed to write DXF: {}", e))?;
+            tracing::info!(handle = %handle, path = %path, "section exported to DXF");
+            None
+        }
+        None => Some(dxf),
+    };
+
+    Ok(DxfExportResult { output_path, content, polyline_count: slice.polylines.len(), extent })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_query::SlicePolyline;
+
+    #[test]
+    fn test_closed_section_becomes_closed_polyline() {
+        let slice = SliceResult {
+            plane: SlicePlane { origin: [0.0, 0.0, 5.0], normal: [0.0, 0.0, 1.0] },
+            polylines: vec![SlicePolyline {
+                points: vec![[0.0, 0.0, 5.0], [10.0, 0.0, 5.0], [10.0, 4.0, 5.0], [0.0, 4.0, 5.0]],
+                closed: true,
+            }],
+            segment_count: 4,
+        };
+
+        let (dxf, extent) = section_to_dxf(&slice);
+        let lines: Vec<&str> = dxf.lines().collect();
+
+        assert!(dxf.contains("AC1009"));
+        assert_eq!(lines.iter().filter(|l| **l == "VERTEX").count(), 4);
+        let flag = lines.iter().position(|l| *l == "POLYLINE").unwrap() + 6;
+        assert_eq!((lines[flag - 1], lines[flag]), ("70", "1"));
+        assert_eq!(lines[lines.len() - 1], "EOF");
+        // Width and height in some in-plane orientation
+        let mut sides = extent;
+        sides.sort_by(f64::total_cmp);
+        assert!((sides[0] - 4.0).abs() < 1e-9 && (sides[1] - 10.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,279 @@
+// Optional at-rest encryption (AES-256-GCM) for project bundles and cached model copies
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::credentials::{load_secret, store_secret};
+use crate::storage::{load_json, save_json};
+
+/// Leading bytes of every encrypted file
+const MAGIC: &[u8; 8] = b"OHMENC01";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+/// OWASP 2023 recommendation for PBKDF2-HMAC-SHA256; kept cheap in unit tests
+const PBKDF2_ROUNDS: u32 = if cfg!(test) { 1_000 } else { 600_000 };
+
+/// Keychain entry holding the generated key
+const KEYCHAIN_KEY: &str = "at-rest-encryption-key";
+
+/// Where the key comes from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    #[default]
+    Keychain,   // Random key generated once and kept in the OS keychain
+    Password,   // Derived from a password entered each session
+}
+
+impl KeySource {
+    fn tag(self) -> u8 {
+        match self {
+            KeySource::Keychain => 1,
+            KeySource::Password => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(KeySource::Keychain),
+            2 => Some(KeySource::Password),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EncryptionSettings {
+    pub enabled: bool,
+    pub key_source: KeySource,
+}
+
+/// Settings plus whether files can be written and read right now
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptionStatus {
+    #[serde(flatten)]
+    pub settings: EncryptionSettings,
+    pub unlocked: bool,   // A password was entered this session (always true for the keychain)
+}
+
+/// Managed encryption settings; the password and derived keys only live in memory
+pub struct EncryptionState {
+    path: PathBuf,
+    settings: Mutex<EncryptionSettings>,
+    password: Mutex<Option<String>>,
+    keys: Mutex<Vec<([u8; SALT_LEN], [u8; 32])>>,   // Derived password keys by salt
+}
+
+fn derive_key(password: &str, salt: &[u8; SALT_LEN]) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, PBKDF2_ROUNDS, &mut key);
+    key
+}
+
+fn keychain_key() -> Result<[u8; 32], String> {
+    if let Some(stored) = load_secret(KEYCHAIN_KEY)? {
+        let bytes = STANDARD.decode(stored.trim()).map_err(|e| format!("Keychain encryption key is corrupt: {}", e))?;
+        return bytes.try_into().map_err(|_| "Keychain encryption key has the wrong length".to_string());
+    }
+    let mut key = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut key);
+    store_secret(KEYCHAIN_KEY, &STANDARD.encode(key))?;
+    tracing::info!("generated at-rest encryption key in the keychain");
+    Ok(key)
+}
+
+/// Whether bytes were written by `seal`
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.len() >= HEADER_LEN && bytes.starts_with(MAGIC)
+}
+
+/// Encrypt with a ready key; the salt is stored so a password key can be derived again
+fn encrypt(plain: &[u8], source: KeySource, salt: &[u8; SALT_LEN], key: &[u8; 32]) -> Result<Vec<u8>, String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("Invalid encryption key: {}", e))?;
+    let sealed = cipher.encrypt(Nonce::from_slice(&nonce), plain).map_err(|_| "Encryption failed".to_string())?;
+
+    let mut out = Vec::with_capacity(HEADER_LEN + sealed.len());
+    out.extend_from_slice(MAGIC);
+    out.push(source.tag());
+    out.extend_from_slice(salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Header fields of an encrypted file
+fn parse_header(bytes: &[u8]) -> Result<(KeySource, [u8; SALT_LEN]), String> {
+    if !is_encrypted(bytes) {
+        return Err("Not an encrypted file".to_string());
+    }
+    let source = KeySource::from_tag(bytes[MAGIC.len()]).ok_or("Unknown encryption key source")?;
+    let mut salt = [0u8; SALT_LEN];
+    salt.copy_from_slice(&bytes[MAGIC.len() + 1..MAGIC.len() + 1 + SALT_LEN]);
+    Ok((source, salt))
+}
+
+fn decrypt(bytes: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, String> {
+    let nonce = &bytes[HEADER_LEN - NONCE_LEN..HEADER_LEN];
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| format!("Invalid encryption key: {}", e))?;
+    cipher.decrypt(Nonce::from_slice(nonce), &bytes[HEADER_LEN..])
+        .map_err(|_| "Decryption failed: wrong password or key, or the file was modified".to_string())
+}
+
+impl EncryptionState {
+    pub fn load(path: PathBuf) -> Self {
+        let settings = load_json(&path);
+        EncryptionState { path, settings: Mutex::new(settings), password: Mutex::new(None), keys: Mutex::new(Vec::new()) }
+    }
+
+    fn settings(&self) -> EncryptionSettings {
+        self.settings.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    fn status(&self) -> EncryptionStatus {
+        let settings = self.settings();
+        let unlocked = settings.key_source == KeySource::Keychain
+            || self.password.lock().map(|p| p.is_some()).unwrap_or(false);
+        EncryptionStatus { settings, unlocked }
+    }
+
+    /// Password key for a salt, derived once per session
+    fn password_key(&self, salt: &[u8; SALT_LEN]) -> Result<[u8; 32], String> {
+        let mut keys = self.keys.lock().map_err(|_| "Encryption state poisoned".to_string())?;
+        if let Some((_, key)) = keys.iter().find(|(s, _)| s == salt) {
+            return Ok(*key);
+        }
+        let password = self.password.lock().map_err(|_| "Encryption state poisoned".to_string())?.clone();
+        let password = password.ok_or("Enter the encryption password first")?;
+        let key = derive_key(&password, salt);
+        keys.push((*salt, key));
+        Ok(key)
+    }
+
+    /// Encrypt if encryption is enabled, otherwise return the bytes unchanged
+    pub fn seal(&self, plain: &[u8]) -> Result<Vec<u8>, String> {
+        let settings = self.settings();
+        if !settings.enabled {
+            return Ok(plain.to_vec());
+        }
+        match settings.key_source {
+            KeySource::Keychain => encrypt(plain, KeySource::Keychain, &[0u8; SALT_LEN], &keychain_key()?),
+            KeySource::Password => {
+                // Reuse the first salt of the session so the key is derived only once
+                let salt = self.keys.lock().ok().and_then(|k| k.first().map(|(s, _)| *s)).unwrap_or_else(|| {
+                    let mut salt = [0u8; SALT_LEN];
+                    rand::rngs::OsRng.fill_bytes(&mut salt);
+                    salt
+                });
+                encrypt(plain, KeySource::Password, &salt, &self.password_key(&salt)?)
+            }
+        }
+    }
+
+    /// Decrypt a sealed file; plain files pass through so enabling encryption keeps old files readable
+    pub fn open(&self, bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+        if !is_encrypted(&bytes) {
+            return Ok(bytes);
+        }
+        let (source, salt) = parse_header(&bytes)?;
+        let key = match source {
+            KeySource::Keychain => keychain_key()?,
+            KeySource::Password => self.password_key(&salt)?,
+        };
+        decrypt(&bytes, &key)
+    }
+
+    /// Write a file through `seal`
+    pub fn write(&self, path: &Path, plain: &[u8]) -> Result<(), String> {
+        let bytes = self.seal(plain)?;
+        std::fs::write(path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// Read a file through `open`
+    pub fn read_to_string(&self, path: &Path) -> Result<String, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        String::from_utf8(self.open(bytes)?).map_err(|_| format!("{} is not valid UTF-8", path.display()))
+    }
+}
+
+/// Current encryption settings
+#[tauri::command]
+pub fn get_encryption_settings(state: State<'_, EncryptionState>) -> EncryptionStatus {
+    state.status()
+}
+
+/// Turn encryption of new project bundles and model copies on or off
+#[tauri::command]
+pub fn set_encryption_settings(
+    state: State<'_, EncryptionState>,
+    enabled: bool,
+    key_source: KeySource,
+) -> Result<EncryptionStatus, String> {
+    let settings = EncryptionSettings { enabled, key_source };
+    if enabled && key_source == KeySource::Keychain {
+        keychain_key()?;
+    }
+    save_json(&state.path, &settings)?;
+    *state.settings.lock().map_err(|_| "Encryption state poisoned".to_string())? = settings;
+    tracing::info!(enabled, ?key_source, "encryption settings changed");
+    Ok(state.status())
+}
+
+/// Enter the password used for password-protected files this session
+#[tauri::command]
+pub fn unlock_encryption(state: State<'_, EncryptionState>, password: String) -> Result<EncryptionStatus, String> {
+    if password.is_empty() {
+        return Err("Password must not be empty".to_string());
+    }
+    *state.password.lock().map_err(|_| "Encryption state poisoned".to_string())? = Some(password);
+    state.keys.lock().map_err(|_| "Encryption state poisoned".to_string())?.clear();
+    Ok(state.status())
+}
+
+/// Forget the session password and derived keys
+#[tauri::command]
+pub fn lock_encryption(state: State<'_, EncryptionState>) -> Result<EncryptionStatus, String> {
+    *state.password.lock().map_err(|_| "Encryption state poisoned".to_string())? = None;
+    state.keys.lock().map_err(|_| "Encryption state poisoned".to_string())?.clear();
+    Ok(state.status())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_roundtrip_and_tamper() {
+        let dir = std::env::temp_dir().join(format!("ohmframe-encryption-{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        let state = EncryptionState::load(dir.join("encryption.json"));
+        *state.settings.lock().unwrap() = EncryptionSettings { enabled: true, key_source: KeySource::Password };
+        assert!(state.seal(b"ISO-10303-21;").unwrap_err().contains("password"));
+
+        *state.password.lock().unwrap() = Some("hunter2".to_string());
+        let sealed = state.seal(b"ISO-10303-21;").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert_eq!(state.open(sealed.clone()).unwrap(), b"ISO-10303-21;");
+        assert_eq!(state.open(b"plain".to_vec()).unwrap(), b"plain");
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(state.open(tampered).is_err());
+
+        // A fresh session with the wrong password cannot read it
+        let other = EncryptionState::load(dir.join("encryption.json"));
+        *other.password.lock().unwrap() = Some("hunter3".to_string());
+        assert!(other.open(sealed).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
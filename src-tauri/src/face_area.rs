@@ -0,0 +1,268 @@
+// Face areas from boundary loops: circular edges are tessellated, analytic surfaces use their parameter spans
+
+use std::f64::consts::{PI, TAU};
+
+use crate::assembly_parser::parse_axis_placement;
+use crate::linalg::{add, cross, distance, dot, norm, scale, sub, Vec3};
+use crate::step_entities::{StepEntities, StepEntity};
+use crate::step_tokenizer::references;
+
+/// Angular step when tessellating circular edges
+const ARC_STEP: f64 = PI / 36.0;
+
+/// Placement of a surface or curve: origin and x, y, z axes
+struct Frame {
+    origin: Vec3,
+    x: Vec3,
+    y: Vec3,
+    z: Vec3,
+}
+
+impl Frame {
+    fn of(entities: &StepEntities, placement_id: Option<i64>) -> Option<Frame> {
+        let placement = entities.get_typed(placement_id?, "AXIS2_PLACEMENT_3D")?;
+        let m = parse_axis_placement(entities, placement)?;
+        Some(Frame { origin: [m[12], m[13], m[14]], x: [m[0], m[1], m[2]], y: [m[4], m[5], m[6]], z: [m[8], m[9], m[10]] })
+    }
+
+    /// Angle around z, radial distance from the z axis and height along it
+    fn cylindrical(&self, p: &Vec3) -> (f64, f64, f64) {
+        let d = sub(p, &self.origin);
+        let (u, v) = (dot(&d, &self.x), dot(&d, &self.y));
+        (v.atan2(u), u.hypot(v), dot(&d, &self.z))
+    }
+
+    fn point_at(&self, angle: f64, radius: f64) -> Vec3 {
+        add(&self.origin, &add(&scale(&self.x, radius * angle.cos()), &scale(&self.y, radius * angle.sin())))
+    }
+}
+
+fn vertex_point(entities: &StepEntities, vertex_id: Option<i64>) -> Option<Vec3> {
+    let vertex = entities.get(vertex_id?)?;
+    entities.get(vertex.param_ref(1)?)?.triple()
+}
+
+/// Points along an edge in loop order, without its last point (the next edge starts there)
+fn edge_points(entities: &StepEntities, oriented: &StepEntity) -> Option<Vec<Vec3>> {
+    // ORIENTED_EDGE(name, *, *, edge_element, orientation)
+    let (edge, forward) = match oriented.entity_type {
+        "ORIENTED_EDGE" => (entities.get(oriented.param_ref(3)?)?, oriented.param_flag(4).unwrap_or(true)),
+        _ => (oriented, true),
+    };
+    // EDGE_CURVE(name, edge_start, edge_end, edge_geometry, same_sense)
+    let start = vertex_point(entities, edge.param_ref(1))?;
+    let end = vertex_point(entities, edge.param_ref(2))?;
+    let curve = edge.param_ref(3).and_then(|id| entities.get(id));
+
+    let mut points = match curve {
+        Some(circle) if circle.entity_type == "CIRCLE" => {
+            // CIRCLE(name, position, radius)
+            let frame = Frame::of(entities, circle.param_ref(1))?;
+            let radius = circle.param_real(2)?.abs();
+            let (from, _, _) = frame.cylindrical(&start);
+            let (to, _, _) = frame.cylindrical(&end);
+            let mut sweep = (to - from).rem_euclid(TAU);
+            if sweep < 1e-9 || distance(&start, &end) < 1e-9 {
+                sweep = TAU;
+            }
+            if edge.param_flag(4) == Some(false) {
+                sweep -= TAU;
+            }
+            let steps = (sweep.abs() / ARC_STEP).ceil().max(1.0) as usize;
+            let mut arc: Vec<Vec3> = (0..steps).map(|i| frame.point_at(from + sweep * i as f64 / steps as f64, radius)).collect();
+            arc[0] = start;
+            arc.push(end);
+            arc
+        }
+        _ => vec![start, end],
+    };
+    if !forward {
+        points.reverse();
+    }
+    points.pop();
+    Some(points)
+}
+
+/// Boundary loops of a face as point rings, the outer bound first
+fn face_loops(entities: &StepEntities, face: &StepEntity) -> Vec<Vec<Vec3>> {
+    let mut loops: Vec<(bool, Vec<Vec3>)> = Vec::new();
+    let Some(bounds) = face.param(1) else { return Vec::new() };
+    for bound in references(bounds).filter_map(|id| entities.get(id)) {
+        // FACE_BOUND / FACE_OUTER_BOUND(name, bound, orientation)
+        let Some(ring) = bound.param_ref(1).and_then(|id| entities.get(id)) else { continue };
+        let points: Vec<Vec3> = match ring.entity_type {
+            "EDGE_LOOP" => ring.param(1)
+                .map(|edges| references(edges).filter_map(|id| entities.get(id)).filter_map(|e| edge_points(entities, e)).flatten().collect())
+                .unwrap_or_default(),
+            "POLY_LOOP" => ring.param(1)
+                .map(|pts| references(pts).filter_map(|id| entities.get(id)?.triple()).collect())
+                .unwrap_or_default(),
+            "VERTEX_LOOP" => vertex_point(entities, ring.param_ref(1)).into_iter().collect(),
+            _ => Vec::new(),
+        };
+        if !points.is_empty() {
+            loops.push((bound.entity_type == "FACE_OUTER_BOUND", points));
+        }
+    }
+    loops.sort_by_key(|(outer, _)| !outer);
+    loops.into_iter().map(|(_, points)| points).collect()
+}
+
+/// Points on a face's boundary loops (edge vertices and sampled arcs), for extents
+pub fn boundary_points(entities: &StepEntities, face: &StepEntity) -> Vec<Vec3> {
+    face_loops(entities, face).into_iter().flatten().collect()
+}
+
+/// Vector area of a closed ring (Newell's method)
+fn vector_area(ring: &[Vec3]) -> Vec3 {
+    let mut total = [0.0; 3];
+    for (i, p) in ring.iter().enumerate() {
+        total = add(&total, &cross(p, &ring[(i + 1) % ring.len()]));
+    }
+    scale(&total, 0.5)
+}
+
+/// Flat area enclosed by the loops, inner loops subtracted
+fn polygon_area(loops: &[Vec<Vec3>]) -> f64 {
+    let Some((outer, inner)) = loops.split_first() else { return 0.0 };
+    let holes: f64 = inner.iter().map(|ring| norm(&vector_area(ring))).sum();
+    (norm(&vector_area(outer)) - holes).abs()
+}
+
+/// Span of an angle unwrapped along each ring (closing back to its start), the widest ring wins
+fn angular_span(loops: &[Vec<Vec3>], angle: impl Fn(&Vec3) -> f64) -> f64 {
+    let mut widest: f64 = 0.0;
+    for ring in loops {
+        let Some(first) = ring.first() else { continue };
+        let (mut previous, mut current) = (angle(first), 0.0);
+        let (mut low, mut high) = (0.0f64, 0.0f64);
+        for p in ring.iter().skip(1).chain(std::iter::once(first)) {
+            let a = angle(p);
+            current += (a - previous + PI).rem_euclid(TAU) - PI;
+            previous = a;
+            low = low.min(current);
+            high = high.max(current);
+        }
+        widest = widest.max(high - low);
+    }
+    widest.min(TAU)
+}
+
+fn range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)))
+}
+
+/// Area of an ADVANCED_FACE or FACE_SURFACE (mm²); 0 when the boundary cannot be resolved
+pub fn face_area(entities: &StepEntities, face: &StepEntity) -> f64 {
+    let loops = face_loops(entities, face);
+    let Some(surface) = face.param_ref(2).and_then(|id| entities.get(id)) else { return polygon_area(&loops) };
+    let frame = Frame::of(entities, surface.param_ref(1));
+    let points = || loops.iter().flatten();
+
+    let area = match (surface.entity_type, &frame) {
+        ("CYLINDRICAL_SURFACE", Some(frame)) if !loops.is_empty() => {
+            let radius = surface.param_real(2).unwrap_or(0.0).abs();
+            let (low, high) = range(points().map(|p| frame.cylindrical(p).2));
+            radius * angular_span(&loops, |p| frame.cylindrical(p).0) * (high - low)
+        }
+        ("CONICAL_SURFACE", Some(frame)) if !loops.is_empty() => {
+            // Frustum between the smallest and largest circle reached by the boundary
+            let (r_low, r_high) = range(points().map(|p| frame.cylindrical(p).1));
+            let (h_low, h_high) = range(points().map(|p| frame.cylindrical(p).2));
+            let slant = (r_high - r_low).hypot(h_high - h_low);
+            0.5 * angular_span(&loops, |p| frame.cylindrical(p).0) * (r_low + r_high) * slant
+        }
+        ("SPHERICAL_SURFACE", Some(frame)) => {
+            // Spherical zone: 2πr·h, scaled by the angle swept around the axis
+            let radius = surface.param_real(2).unwrap_or(0.0).abs();
+            if loops.is_empty() {
+                4.0 * PI * radius * radius
+            } else {
+                let (low, high) = range(points().map(|p| frame.cylindrical(p).2));
+                radius * angular_span(&loops, |p| frame.cylindrical(p).0) * (high - low)
+            }
+        }
+        ("TOROIDAL_SURFACE", Some(frame)) => {
+            let major = surface.param_real(2).unwrap_or(0.0).abs();
+            let minor = surface.param_real(3).unwrap_or(0.0).abs();
+            if loops.is_empty() {
+                4.0 * PI * PI * major * minor
+            } else {
+                // Patch between tube angles ψ1..ψ2: r·Δθ·(R·Δψ + r·(sin ψ2 − sin ψ1))
+                let tube_angle = |p: &Vec3| {
+                    let (_, rho, h) = frame.cylindrical(p);
+                    h.atan2(rho - major)
+                };
+                let (low, high) = range(points().map(tube_angle));
+                let sweep = angular_span(&loops, |p| frame.cylindrical(p).0);
+                minor * sweep * (major * (high - low) + minor * (high.sin() - low.sin())).abs()
+            }
+        }
+        _ => polygon_area(&loops),
+    };
+    if area.is_finite() { area } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLATE_AND_PIN: &str = "\
+#1=CARTESIAN_POINT('',(0.,0.,0.));
+#2=DIRECTION('',(0.,0.,1.));
+#3=DIRECTION('',(1.,0.,0.));
+#4=AXIS2_PLACEMENT_3D('',#1,#2,#3);
+#10=CARTESIAN_POINT('',(0.,0.,0.));
+#11=CARTESIAN_POINT('',(20.,0.,0.));
+#12=CARTESIAN_POINT('',(20.,10.,0.));
+#13=CARTESIAN_POINT('',(0.,10.,0.));
+#14=CARTESIAN_POINT('',(12.,5.,0.));
+#20=VERTEX_POINT('',#10);
+#21=VERTEX_POINT('',#11);
+#22=VERTEX_POINT('',#12);
+#23=VERTEX_POINT('',#13);
+#24=VERTEX_POINT('',#14);
+#30=EDGE_CURVE('',#20,#21,#90,.T.);
+#31=EDGE_CURVE('',#21,#22,#90,.T.);
+#32=EDGE_CURVE('',#22,#23,#90,.T.);
+#33=EDGE_CURVE('',#23,#20,#90,.T.);
+#34=EDGE_CURVE('',#24,#24,#80,.T.);
+#40=ORIENTED_EDGE('',*,*,#30,.T.);
+#41=ORIENTED_EDGE('',*,*,#31,.T.);
+#42=ORIENTED_EDGE('',*,*,#32,.T.);
+#43=ORIENTED_EDGE('',*,*,#33,.T.);
+#44=ORIENTED_EDGE('',*,*,#34,.F.);
+#50=EDGE_LOOP('',(#40,#41,#42,#43));
+#51=EDGE_LOOP('',(#44));
+#52=FACE_OUTER_BOUND('',#50,.T.);
+#53=FACE_BOUND('',#51,.T.);
+#60=PLANE('',#4);
+#61=ADVANCED_FACE('',(#52,#53),#60,.T.);
+#70=CARTESIAN_POINT('',(10.,5.,0.));
+#71=AXIS2_PLACEMENT_3D('',#70,#2,#3);
+#80=CIRCLE('',#71,2.);
+#90=LINE('',#1,#2);
+#100=CARTESIAN_POINT('',(12.,5.,8.));
+#101=VERTEX_POINT('',#100);
+#102=CARTESIAN_POINT('',(10.,5.,8.));
+#103=AXIS2_PLACEMENT_3D('',#102,#2,#3);
+#104=CIRCLE('',#103,2.);
+#105=EDGE_CURVE('',#101,#101,#104,.T.);
+#106=ORIENTED_EDGE('',*,*,#105,.T.);
+#107=EDGE_LOOP('',(#106));
+#108=FACE_BOUND('',#107,.T.);
+#109=FACE_BOUND('',#51,.T.);
+#110=CYLINDRICAL_SURFACE('',#71,2.);
+#111=ADVANCED_FACE('',(#108,#109),#110,.T.);";
+
+    #[test]
+    fn test_plate_with_hole_and_cylinder() {
+        let entities = StepEntities::parse(PLATE_AND_PIN);
+        let plate = face_area(&entities, entities.get(61).unwrap());
+        assert!((plate - (200.0 - PI * 4.0)).abs() < 0.2, "{}", plate);
+
+        // Two full circles 8 mm apart: lateral area 2πrh
+        let pin = face_area(&entities, entities.get(111).unwrap());
+        assert!((pin - 2.0 * PI * 2.0 * 8.0).abs() < 1e-6, "{}", pin);
+    }
+}
@@ -0,0 +1,292 @@
+// Feature recognition from parsed faces (holes and bosses)
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::{ParsedFace, ParsedPart};
+use crate::face_area::boundary_points;
+use crate::interface_detection::{transform_direction, transform_point};
+use crate::linalg::{add, any_perpendicular, cross, dot, norm, normalize, scale, sub, Vec3};
+use crate::model_store::{LoadedModel, ModelStore};
+use crate::step_entities::StepEntities;
+
+/// Cylindrical hole made of one or more coaxial faces of equal radius
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hole {
+    pub id: String,
+    pub part_id: String,
+    pub face_ids: Vec<i64>,
+    pub diameter: f64,
+    pub center: Vec3,            // Point on the axis (world)
+    pub axis: Vec3,              // Unit axis direction (world)
+    pub internal: Option<bool>,  // Hole vs boss when the face orientation is known
+}
+
+/// Local frame of a hole or boss: origin on the axis at the entry plane, Z along the axis into the feature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFrame {
+    pub feature_id: String,
+    pub part_id: String,
+    pub kind: String,            // "hole" or "boss"
+    pub diameter: f64,
+    pub depth: Option<f64>,      // Axial length of the cylinder faces, when their boundaries are known
+    pub origin: Vec3,
+    pub x_axis: Vec3,
+    pub y_axis: Vec3,
+    pub z_axis: Vec3,
+    pub entry_face_id: Option<i64>,  // Planar face containing the entry plane
+}
+
+/// Coaxial cylinder groups of a part, holes and bosses alike; ids are assigned by the callers
+fn recognize_part_cylinders(part: &ParsedPart) -> Vec<Hole> {
+    let mut holes: Vec<Hole> = Vec::new();
+
+    for face in &part.faces {
+        if face.face_type != "cylindrical" {
+            continue;
+        }
+        let (Some(radius), Some(axis)) = (face.radius, face.axis) else { continue };
+        if radius <= 0.0 {
+            continue;
+        }
+
+        let center = transform_point(&face.center, &part.transform);
+        let axis = transform_direction(&axis, &part.transform);
+        // A cylinder face whose orientation opposes its surface normal points into the axis: a hole
+        let internal = face.same_sense.map(|same| !same);
+
+        let existing = holes.iter_mut().find(|h| {
+            (h.diameter - 2.0 * radius).abs() < 1e-6 * radius.max(1.0) && is_coaxial(&h.center, &h.axis, &center, &axis)
+        });
+
+        match existing {
+            Some(hole) => {
+                hole.face_ids.push(face.id);
+                hole.internal = hole.internal.or(internal);
+            }
+            None => holes.push(Hole {
+                id: String::new(),
+                part_id: part.id.clone(),
+                face_ids: vec![face.id],
+                diameter: 2.0 * radius,
+                center,
+                axis: normalize(&axis),
+                internal,
+            }),
+        }
+    }
+
+    holes
+}
+
+/// Recognize holes on a part; split cylinder faces are merged into one hole
+pub fn recognize_part_holes(part: &ParsedPart) -> Vec<Hole> {
+    // Bosses are not holes
+    let mut holes: Vec<Hole> = recognize_part_cylinders(part).into_iter().filter(|h| h.internal != Some(false)).collect();
+    for (i, hole) in holes.iter_mut().enumerate() {
+        hole.id = format!("{}-hole-{}", part.id, i + 1);
+    }
+    holes
+}
+
+/// Recognize bosses (external cylinders with known orientation) on a part
+pub fn recognize_part_bosses(part: &ParsedPart) -> Vec<Hole> {
+    let mut bosses: Vec<Hole> = recognize_part_cylinders(part).into_iter().filter(|h| h.internal == Some(false)).collect();
+    for (i, boss) in bosses.iter_mut().enumerate() {
+        boss.id = format!("{}-boss-{}", part.id, i + 1);
+    }
+    bosses
+}
+
+/// Whether two axes are the same line
+pub(crate) fn is_coaxial(point_a: &Vec3, axis_a: &Vec3, point_b: &Vec3, axis_b: &Vec3) -> bool {
+    if dot(axis_a, axis_b).abs() < 1.0 - 1e-6 {
+        return false;
+    }
+    let offset = sub(point_b, point_a);
+    norm(&cross(&offset, &normalize(axis_a))) < 1e-4 * norm(&offset).max(1.0)
+}
+
+/// Planar face normal pointing out of the material
+pub(crate) fn outward_normal(face: &ParsedFace) -> Vec3 {
+    let n = normalize(&face.normal);
+    if face.same_sense == Some(false) { scale(&n, -1.0) } else { n }
+}
+
+/// Part extent along a local direction, from its bounding box
+pub(crate) fn part_extent_along(part: &ParsedPart, axis: &Vec3) -> Option<f64> {
+    let bbox = part.bounding_box.as_ref()?;
+    Some((0..3).map(|i| axis[i].abs() * (bbox.max[i] - bbox.min[i])).sum())
+}
+
+/// Axial range of a feature's cylinder faces, from their boundary loops or else their centers
+pub(crate) fn axial_span(entities: &StepEntities, part: &ParsedPart, feature: &Hole) -> (f64, f64, bool) {
+    let faces = part.faces.iter().filter(|f| feature.face_ids.contains(&f.id));
+    let mut bounded = true;
+    let points: Vec<Vec3> = faces
+        .flat_map(|face| {
+            let boundary = face.step_entity_id
+                .and_then(|id| entities.get(id))
+                .map(|entity| boundary_points(entities, entity))
+                .unwrap_or_default();
+            if boundary.is_empty() {
+                bounded = false;
+                vec![transform_point(&face.center, &part.transform)]
+            } else {
+                boundary.iter().map(|p| transform_point(p, &part.transform)).collect()
+            }
+        })
+        .collect();
+    let along = points.iter().map(|p| dot(&sub(p, &feature.center), &feature.axis));
+    let low = along.clone().fold(f64::INFINITY, f64::min);
+    let high = along.fold(f64::NEG_INFINITY, f64::max);
+    (low, high, bounded && high > low)
+}
+
+/// Frame at the open end of a hole or boss: the end lying in a planar face that faces away from the cylinder
+pub fn feature_frame(entities: &StepEntities, part: &ParsedPart, feature: &Hole, kind: &str) -> FeatureFrame {
+    let (low, high, bounded) = axial_span(entities, part, feature);
+    let tolerance = 1e-3 * (high - low).abs().max(1.0);
+
+    // Planar faces perpendicular to the axis at either end; prefer the high end when both qualify (through holes)
+    let entry = [(high, 1.0), (low, -1.0)].into_iter().find_map(|(end, outward)| {
+        part.faces.iter()
+            .filter(|f| f.face_type == "planar")
+            .find(|f| {
+                let normal = transform_direction(&outward_normal(f), &part.transform);
+                let at = dot(&sub(&transform_point(&f.center, &part.transform), &feature.center), &feature.axis);
+                dot(&normal, &feature.axis) * outward > 1.0 - 1e-6 && (at - end).abs() < tolerance
+            })
+            .map(|f| (end, outward, f.id))
+    });
+    let (end, outward, entry_face_id) = match entry {
+        Some((end, outward, id)) => (end, outward, Some(id)),
+        None => (high, 1.0, None),
+    };
+
+    let z_axis = scale(&feature.axis, -outward);
+    let x_axis = any_perpendicular(&z_axis);
+    FeatureFrame {
+        feature_id: feature.id.clone(),
+        part_id: part.id.clone(),
+        kind: kind.to_string(),
+        diameter: feature.diameter,
+        depth: bounded.then_some(high - low),
+        origin: add(&feature.center, &scale(&feature.axis, if end.is_finite() { end } else { 0.0 })),
+        x_axis,
+        y_axis: cross(&z_axis, &x_axis),
+        z_axis,
+        entry_face_id,
+    }
+}
+
+/// Frames for every hole and boss of a loaded model
+pub fn model_feature_frames(model: &LoadedModel) -> Vec<FeatureFrame> {
+    let entities = StepEntities::parse(&model.content);
+    model.assembly.parts.iter()
+        .flat_map(|part| {
+            let holes = recognize_part_holes(part).into_iter().map(|h| feature_frame(&entities, part, &h, "hole"));
+            let bosses = recognize_part_bosses(part).into_iter().map(|b| feature_frame(&entities, part, &b, "boss"));
+            holes.chain(bosses).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Holes across every part of a loaded model
+pub fn model_holes(model: &LoadedModel) -> Vec<Hole> {
+    model.assembly.parts.iter().flat_map(recognize_part_holes).collect()
+}
+
+/// Recognize holes on a loaded model
+#[tauri::command]
+pub fn detect_holes(state: State<'_, ModelStore>, handle: String) -> Result<Vec<Hole>, String> {
+    state.with_model(&handle, model_holes)
+}
+
+/// Local frames of holes and bosses, for snapping stackup chain ends to features
+#[tauri::command]
+pub fn get_feature_frames(state: State<'_, ModelStore>, handle: String) -> Result<Vec<FeatureFrame>, String> {
+    state.with_model(&handle, model_feature_frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cylinder(id: i64, center: Vec3, radius: f64, same_sense: Option<bool>) -> ParsedFace {
+        ParsedFace {
+            id,
+            face_type: "cylindrical".to_string(),
+            normal: [1.0, 0.0, 0.0],
+            center,
+            area: 0.0,
+            radius: Some(radius),
+            axis: Some([0.0, 0.0, 1.0]),
+            step_entity_id: None,
+            same_sense,
+            semi_angle: None,
+            apex: None,
+            minor_radius: None,
+        }
+    }
+
+    #[test]
+    fn test_split_faces_merge_and_bosses_drop() {
+        let part = ParsedPart {
+            id: "plate".to_string(),
+            name: "plate".to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: None,
+            faces: vec![
+                cylinder(0, [10.0, 10.0, 0.0], 3.0, Some(false)),
+                cylinder(1, [10.0, 10.0, 5.0], 3.0, Some(false)),   // Other half of the same hole
+                cylinder(2, [30.0, 10.0, 0.0], 3.0, Some(false)),
+                cylinder(3, [50.0, 10.0, 0.0], 4.0, Some(true)),    // Boss
+            ],
+            product_definition_id: None,
+        };
+
+        let holes = recognize_part_holes(&part);
+        assert_eq!(holes.len(), 2);
+        assert_eq!(holes[0].face_ids, vec![0, 1]);
+        assert!((holes[0].diameter - 6.0).abs() < 1e-9);
+        assert_eq!(holes[1].id, "plate-hole-2");
+    }
+
+    #[test]
+    fn test_hole_frame_sits_on_entry_face() {
+        let top = ParsedFace {
+            id: 9,
+            face_type: "planar".to_string(),
+            normal: [0.0, 0.0, 1.0],
+            center: [20.0, 20.0, 5.0],
+            same_sense: Some(true),
+            radius: None,
+            axis: None,
+            ..cylinder(9, [0.0; 3], 0.0, None)
+        };
+        let part = ParsedPart {
+            id: "plate".to_string(),
+            name: "plate".to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: None,
+            faces: vec![
+                cylinder(0, [10.0, 10.0, 0.0], 3.0, Some(false)),
+                cylinder(1, [10.0, 10.0, 5.0], 3.0, Some(false)),
+                top,
+            ],
+            product_definition_id: None,
+        };
+
+        let hole = &recognize_part_holes(&part)[0];
+        let frame = feature_frame(&StepEntities::parse(""), &part, hole, "hole");
+        assert_eq!(frame.entry_face_id, Some(9));
+        assert!(norm(&sub(&frame.origin, &[10.0, 10.0, 5.0])) < 1e-9);
+        assert!(norm(&sub(&frame.z_axis, &[0.0, 0.0, -1.0])) < 1e-9);
+        assert!(dot(&frame.x_axis, &frame.z_axis).abs() < 1e-9 && (norm(&frame.y_axis) - 1.0).abs() < 1e-9);
+        // Without boundary loops the depth is unknown
+        assert!(frame.depth.is_none());
+    }
+}
@@ -0,0 +1,197 @@
+// Statistical fit check: interference probability and clearance distribution of a pin in a hole
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::jobs::run_job;
+use crate::journal::{Edit, EditJournal};
+use crate::model_store::ModelStore;
+use crate::tolerance_calc::{
+    calculate_rss, calculate_worst_case, sample_stackup, summarize_samples, validate_link, LinkInput,
+    MonteCarloResult, MAX_MONTE_CARLO_SAMPLES,
+};
+use crate::validation::{Checked, FieldErrors, Validate};
+
+const DEFAULT_SAMPLES: usize = 100_000;
+
+/// Diameter distributions of a mating pin and hole; link directions are ignored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FitInput {
+    pub pin: LinkInput,
+    pub hole: LinkInput,
+    pub monte_carlo_samples: Option<usize>,
+}
+
+impl Validate for FitInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        validate_link(&self.pin, "pin", errors);
+        validate_link(&self.hole, "hole", errors);
+        if let Some(samples) = self.monte_carlo_samples {
+            if samples == 0 || samples > MAX_MONTE_CARLO_SAMPLES {
+                errors.add("monte_carlo_samples", format!("expected 1 to {}, got {}", MAX_MONTE_CARLO_SAMPLES, samples));
+            }
+        }
+    }
+}
+
+/// Clearance (hole minus pin) statistics for a fit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FitAnalysis {
+    pub fit_type: String,                        // "clearance", "transition" or "interference" at worst case
+    pub min_clearance: f64,                      // Worst case; negative is interference
+    pub max_clearance: f64,
+    pub mean_clearance: f64,
+    pub clearance_sigma: f64,
+    pub interference_probability: f64,           // Normal approximation of P(clearance < 0)
+    pub simulated_interference_probability: f64, // Share of Monte Carlo samples below zero
+    pub clearance: MonteCarloResult,
+}
+
+/// Hole as a positive link and pin as a negative one, so the stack total is the clearance
+fn clearance_links(input: &FitInput) -> [LinkInput; 2] {
+    let hole = LinkInput { direction: "positive".to_string(), ..input.hole.clone() };
+    let pin = LinkInput { direction: "negative".to_string(), ..input.pin.clone() };
+    [hole, pin]
+}
+
+/// Standard normal CDF via the Abramowitz-Stegun erf approximation (error below 1.5e-7)
+pub fn standard_normal_cdf(z: f64) -> f64 {
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+/// Worst-case fit class plus analytic and simulated interference probability
+pub fn compute_fit_analysis(input: &FitInput) -> FitAnalysis {
+    let links = clearance_links(input);
+    let worst_case = calculate_worst_case(&links);
+    let (_, variances) = calculate_rss(&links);
+    let clearance_sigma = variances.iter().sum::<f64>().sqrt();
+
+    // Both distributions are centred on the middle of their tolerance band
+    let mid = |link: &LinkInput| link.nominal + (link.plus_tolerance - link.minus_tolerance) / 2.0;
+    let mean_clearance = mid(&input.hole) - mid(&input.pin);
+    let interference_probability = if clearance_sigma > 0.0 {
+        standard_normal_cdf(-mean_clearance / clearance_sigma)
+    } else if mean_clearance < 0.0 {
+        1.0
+    } else {
+        0.0
+    };
+
+    let samples = sample_stackup(&links, input.monte_carlo_samples.unwrap_or(DEFAULT_SAMPLES));
+    let interfering = samples.partition_point(|c| *c < 0.0);
+    let simulated_interference_probability = interfering as f64 / samples.len() as f64;
+
+    let fit_type = if worst_case.min >= 0.0 {
+        "clearance"
+    } else if worst_case.max <= 0.0 {
+        "interference"
+    } else {
+        "transition"
+    };
+    tracing::debug!(fit_type, interference_probability, simulated_interference_probability, "fit analyzed");
+
+    FitAnalysis {
+        fit_type: fit_type.to_string(),
+        min_clearance: worst_case.min,
+        max_clearance: worst_case.max,
+        mean_clearance,
+        clearance_sigma,
+        interference_probability,
+        simulated_interference_probability,
+        clearance: summarize_samples(samples, None),
+    }
+}
+
+/// Interference probability and clearance distribution for a pin and hole
+#[tauri::command]
+pub async fn calculate_fit_interference(
+    app: AppHandle,
+    input: Checked<FitInput>,
+    job_id: Option<String>,
+) -> Result<FitAnalysis, String> {
+    let input = input.into_inner();
+    run_job(app, "calculate_fit_interference", job_id, None, move |_| Ok(compute_fit_analysis(&input))).await
+}
+
+/// Analyze the fit of a detected interface of a loaded model; the result is kept on the interface
+#[tauri::command]
+pub fn analyze_interface_fit(
+    state: State<'_, ModelStore>,
+    journal: State<'_, EditJournal>,
+    handle: String,
+    interface_id: String,
+    input: Checked<FitInput>,
+) -> Result<FitAnalysis, String> {
+    let analysis = compute_fit_analysis(&input);
+    let (before, after) = state.with_model_mut(&handle, |model| {
+        let before = model.interfaces.clone();
+        let interface = model.interfaces.as_mut()
+            .and_then(|r| r.interfaces.iter_mut().find(|i| i.id == interface_id))
+            .ok_or_else(|| format!("Unknown interface: {}", interface_id))?;
+        interface.fit = Some(analysis.clone());
+        Ok::<_, String>((before, model.interfaces.clone()))
+    })??;
+    journal.record(format!("Fit on {}", interface_id), Edit::Interfaces { handle, before, after });
+    Ok(analysis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diameter(nominal: f64, plus: f64, minus: f64) -> LinkInput {
+        LinkInput {
+            name: None,
+            nominal,
+            plus_tolerance: plus,
+            minus_tolerance: minus,
+            direction: "positive".to_string(),
+            distribution: "normal".to_string(),
+            sigma: Some(3.0),
+            inspected: false,
+        }
+    }
+
+    #[test]
+    fn test_standard_normal_cdf() {
+        assert!((standard_normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((standard_normal_cdf(1.0) - 0.841345).abs() < 1e-5);
+        assert!((standard_normal_cdf(-3.0) - 0.0013499).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_clearance_fit() {
+        // 10 H7/g6: hole 10.000..10.015, pin 9.986..9.995
+        let input = FitInput {
+            pin: diameter(9.995, 0.0, 0.009),
+            hole: diameter(10.0, 0.015, 0.0),
+            monte_carlo_samples: Some(20_000),
+        };
+        let fit = compute_fit_analysis(&input);
+        assert_eq!(fit.fit_type, "clearance");
+        assert!((fit.min_clearance - 0.005).abs() < 1e-9);
+        assert!((fit.max_clearance - 0.029).abs() < 1e-9);
+        assert!((fit.mean_clearance - 0.017).abs() < 1e-9);
+        assert!(fit.interference_probability < 1e-6);
+        assert_eq!(fit.simulated_interference_probability, 0.0);
+    }
+
+    #[test]
+    fn test_transition_fit_probability() {
+        // Equal bands centred on the same diameter interfere half the time
+        let input = FitInput {
+            pin: diameter(10.0, 0.01, 0.01),
+            hole: diameter(10.0, 0.01, 0.01),
+            monte_carlo_samples: Some(20_000),
+        };
+        let fit = compute_fit_analysis(&input);
+        assert_eq!(fit.fit_type, "transition");
+        assert!((fit.interference_probability - 0.5).abs() < 1e-9);
+        assert!((fit.simulated_interference_probability - 0.5).abs() < 0.03);
+        assert!(fit.clearance.mean.abs() < 0.001);
+    }
+}
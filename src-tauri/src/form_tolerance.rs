@@ -0,0 +1,235 @@
+// Form and orientation tolerance evaluation from tessellated faces
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use tauri::State;
+
+use crate::gdt::GeometricCharacteristic;
+use crate::linalg::{any_perpendicular, cross, dot, fit_plane, normalize, reject, Vec3};
+use crate::mesh_query::vertex;
+use crate::model_store::ModelStore;
+use crate::MeshData;
+
+/// How the tolerance zone is oriented
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FitMethod {
+    LeastSquares,
+    #[default]
+    MinZone,
+}
+
+/// Form tolerance check on a mesh face
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormToleranceRequest {
+    pub characteristic: GeometricCharacteristic,  // Flatness, parallelism or perpendicularity
+    pub face_id: u32,                              // Mesh face group
+    pub datum_face_id: Option<u32>,                // Required for orientation tolerances
+    pub tolerance: Option<f64>,
+    #[serde(default)]
+    pub method: FitMethod,
+}
+
+/// Result of a form tolerance check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FormToleranceResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub characteristic: GeometricCharacteristic,
+    pub value: f64,                     // Zone width containing every face point
+    pub tolerance: Option<f64>,
+    pub pass: Option<bool>,
+    pub method: FitMethod,
+    pub point_count: usize,
+    pub zone_normal: Option<Vec3>,
+}
+
+/// Unique vertices of a mesh face group
+pub fn face_points(mesh: &MeshData, face_id: u32) -> Vec<Vec3> {
+    let Some(group) = mesh.face_groups.iter().find(|g| g.face_id == face_id) else { return vec![] };
+    let start = group.start_index as usize;
+    let end = (start + group.triangle_count as usize * 3).min(mesh.indices.len());
+
+    let unique: BTreeSet<u32> = mesh.indices[start..end].iter().copied().collect();
+    unique.into_iter().map(|i| vertex(mesh, i)).collect()
+}
+
+/// Width of the slab normal to `normal` containing all points
+fn zone_width(points: &[Vec3], normal: &Vec3) -> f64 {
+    let (min, max) = points.iter()
+        .map(|p| dot(p, normal))
+        .fold((f64::MAX, f64::MIN), |(lo, hi), d| (lo.min(d), hi.max(d)));
+    max - min
+}
+
+/// Pattern search over zone orientations for the narrowest zone
+fn minimize_width<const N: usize>(points: &[Vec3], direction: impl Fn(&[f64; N]) -> Vec3) -> (f64, Vec3) {
+    let mut params = [0.0; N];
+    let mut best = zone_width(points, &direction(&params));
+    let mut step = 0.05;
+
+    while step > 1e-8 {
+        let mut improved = false;
+        for i in 0..N {
+            for sign in [1.0, -1.0] {
+                let mut trial = params;
+                trial[i] += sign * step;
+                let width = zone_width(points, &direction(&trial));
+                if width < best - 1e-15 {
+                    best = width;
+                    params = trial;
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            step *= 0.5;
+        }
+    }
+
+    (best, direction(&params))
+}
+
+/// Flatness: narrowest pair of parallel planes containing the face
+pub fn flatness(points: &[Vec3], method: FitMethod) -> Option<(f64, Vec3)> {
+    let (_, normal) = fit_plane(points)?;
+    Some(match method {
+        FitMethod::LeastSquares => (zone_width(points, &normal), normal),
+        FitMethod::MinZone => {
+            let u = any_perpendicular(&normal);
+            let v = cross(&normal, &u);
+            minimize_width(points, |p: &[f64; 2]| {
+                normalize(&[
+                    normal[0] + p[0] * u[0] + p[1] * v[0],
+                    normal[1] + p[0] * u[1] + p[1] * v[1],
+                    normal[2] + p[0] * u[2] + p[1] * v[2],
+                ])
+            })
+        }
+    })
+}
+
+/// Parallelism: zone planes parallel to the datum plane
+pub fn parallelism(points: &[Vec3], datum_normal: &Vec3) -> f64 {
+    zone_width(points, &normalize(datum_normal))
+}
+
+/// Perpendicularity: zone planes perpendicular to the datum plane
+pub fn perpendicularity(points: &[Vec3], datum_normal: &Vec3, method: FitMethod) -> Option<(f64, Vec3)> {
+    let datum = normalize(datum_normal);
+    let (_, normal) = fit_plane(points)?;
+
+    // Start from the face's own normal, constrained to lie in the datum plane
+    let start = reject(&normal, &datum);
+    let start = if dot(&start, &start) > 1e-12 { normalize(&start) } else { any_perpendicular(&datum) };
+    let side = cross(&datum, &start);
+
+    Some(match method {
+        FitMethod::LeastSquares => (zone_width(points, &start), start),
+        FitMethod::MinZone => minimize_width(points, |p: &[f64; 1]| {
+            let (sin, cos) = p[0].sin_cos();
+            normalize(&[
+                cos * start[0] + sin * side[0],
+                cos * start[1] + sin * side[1],
+                cos * start[2] + sin * side[2],
+            ])
+        }),
+    })
+}
+
+/// Evaluate a form tolerance on the points of a mesh
+pub fn evaluate_on_mesh(mesh: &MeshData, request: &FormToleranceRequest) -> Result<(f64, Vec3, usize), String> {
+    let points = face_points(mesh, request.face_id);
+    if points.len() < 3 {
+        return Err(format!("Face {} has too few tessellation points", request.face_id));
+    }
+
+    let datum_normal = || -> Result<Vec3, String> {
+        let datum_id = request.datum_face_id.ok_or("A datum face is required")?;
+        let datum_points = face_points(mesh, datum_id);
+        fit_plane(&datum_points)
+            .map(|(_, n)| n)
+            .ok_or_else(|| format!("Datum face {} is not planar enough to fit", datum_id))
+    };
+
+    let (value, normal) = match request.characteristic {
+        GeometricCharacteristic::Flatness => flatness(&points, request.method),
+        GeometricCharacteristic::Parallelism => {
+            let datum = datum_normal()?;
+            Some((parallelism(&points, &datum), datum))
+        }
+        GeometricCharacteristic::Perpendicularity => perpendicularity(&points, &datum_normal()?, request.method),
+        other => return Err(format!("{} is not supported on tessellated faces", other.name())),
+    }
+    .ok_or_else(|| format!("Face {} points are degenerate", request.face_id))?;
+
+    Ok((value, normal, points.len()))
+}
+
+/// Check flatness, parallelism or perpendicularity of a face on a loaded model
+#[tauri::command]
+pub fn evaluate_form_tolerance(
+    state: State<'_, ModelStore>,
+    handle: String,
+    request: FormToleranceRequest,
+) -> FormToleranceResult {
+    let outcome = state.with_model(&handle, |model| {
+        let mesh = model.mesh.as_ref().ok_or("Model has no mesh")?;
+        evaluate_on_mesh(mesh, &request)
+    }).and_then(|r| r);
+
+    match outcome {
+        Ok((value, normal, point_count)) => FormToleranceResult {
+            success: true,
+            error: None,
+            characteristic: request.characteristic,
+            value,
+            tolerance: request.tolerance,
+            pass: request.tolerance.map(|t| value <= t + 1e-12),
+            method: request.method,
+            point_count,
+            zone_normal: Some(normal),
+        },
+        Err(e) => FormToleranceResult {
+            success: false,
+            error: Some(e),
+            characteristic: request.characteristic,
+            value: 0.0,
+            tolerance: request.tolerance,
+            pass: None,
+            method: request.method,
+            point_count: 0,
+            zone_normal: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_zone_flatness_not_worse_than_least_squares() {
+        // Slightly warped square: one raised corner
+        let points = vec![
+            [0.0, 0.0, 0.0],
+            [10.0, 0.0, 0.0],
+            [0.0, 10.0, 0.0],
+            [10.0, 10.0, 0.02],
+            [5.0, 5.0, 0.0],
+        ];
+        let (ls, _) = flatness(&points, FitMethod::LeastSquares).unwrap();
+        let (mz, _) = flatness(&points, FitMethod::MinZone).unwrap();
+        assert!(mz <= ls + 1e-12);
+        assert!(mz > 0.0 && mz < 0.02);
+    }
+
+    #[test]
+    fn test_perpendicular_wall() {
+        // Wall in the XZ plane leaning 0.01 over its 10 mm height, datum is the XY floor
+        let points = vec![[0.0, 0.0, 0.0], [10.0, 0.0, 0.0], [0.0, 0.01, 10.0], [10.0, 0.01, 10.0]];
+        let (value, _) = perpendicularity(&points, &[0.0, 0.0, 1.0], FitMethod::MinZone).unwrap();
+        assert!((value - 0.01).abs() < 1e-6);
+        assert!((parallelism(&points, &[0.0, 1.0, 0.0]) - 0.01).abs() < 1e-12);
+    }
+}
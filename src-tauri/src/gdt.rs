@@ -0,0 +1,993 @@
+// GD&T: datum reference frames (from faces or datum targets) and feature control frames
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::features::{model_holes, Hole};
+use crate::hole_patterns::model_pattern_features;
+use crate::linalg::{add, any_perpendicular, cross, dot, norm, normalize, reject, scale, solve3, sub, Vec3};
+use crate::assembly_parser::ParsedFace;
+use crate::model_store::{FaceRef, LoadedModel, ModelStore, WorldFace};
+use crate::tolerance_calc::{form_link, LinkInput};
+
+/// Material condition modifier
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaterialCondition {
+    #[default]
+    Rfs,
+    Mmc,
+    Lmc,
+}
+
+impl MaterialCondition {
+    fn symbol(&self) -> &'static str {
+        match self {
+            MaterialCondition::Rfs => "",
+            MaterialCondition::Mmc => " (M)",
+            MaterialCondition::Lmc => " (L)",
+        }
+    }
+}
+
+/// Geometric characteristic of a feature control frame
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeometricCharacteristic {
+    Position,
+    Flatness,
+    Straightness,
+    Circularity,
+    Cylindricity,
+    Parallelism,
+    Perpendicularity,
+    Angularity,
+    Profile,
+    Runout,
+}
+
+impl GeometricCharacteristic {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeometricCharacteristic::Position => "Position",
+            GeometricCharacteristic::Flatness => "Flatness",
+            GeometricCharacteristic::Straightness => "Straightness",
+            GeometricCharacteristic::Circularity => "Circularity",
+            GeometricCharacteristic::Cylindricity => "Cylindricity",
+            GeometricCharacteristic::Parallelism => "Parallelism",
+            GeometricCharacteristic::Perpendicularity => "Perpendicularity",
+            GeometricCharacteristic::Angularity => "Angularity",
+            GeometricCharacteristic::Profile => "Profile",
+            GeometricCharacteristic::Runout => "Runout",
+        }
+    }
+
+    /// Form tolerances never reference datums
+    pub fn is_form(&self) -> bool {
+        matches!(
+            self,
+            GeometricCharacteristic::Flatness
+                | GeometricCharacteristic::Straightness
+                | GeometricCharacteristic::Circularity
+                | GeometricCharacteristic::Cylindricity
+        )
+    }
+
+    /// Orientation, location and runout tolerances need at least one datum
+    pub fn requires_datums(&self) -> bool {
+        !self.is_form() && *self != GeometricCharacteristic::Profile
+    }
+}
+
+/// Face selected as a datum feature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatumFeature {
+    pub label: String,  // "A", "B", "C"
+    pub face: FaceRef,
+}
+
+/// Datum reference in a feature control frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatumReference {
+    pub label: String,
+    #[serde(default)]
+    pub material_condition: MaterialCondition,
+}
+
+/// Feature control frame applied to one or more faces
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureControlFrame {
+    #[serde(default)]
+    pub id: String,
+    pub characteristic: GeometricCharacteristic,
+    pub tolerance: f64,
+    #[serde(default)]
+    pub diametral: bool,  // Cylindrical tolerance zone
+    #[serde(default)]
+    pub material_condition: MaterialCondition,
+    #[serde(default)]
+    pub datums: Vec<DatumReference>,
+    pub features: Vec<FaceRef>,
+}
+
+impl FeatureControlFrame {
+    /// Text form of the frame, e.g. "Position | dia 0.100 (M) | A | B | C"
+    pub fn callout(&self) -> String {
+        let mut parts = vec![
+            self.characteristic.name().to_string(),
+            format!(
+                "{}{:.3}{}",
+                if self.diametral { "dia " } else { "" },
+                self.tolerance,
+                self.material_condition.symbol()
+            ),
+        ];
+        for datum in &self.datums {
+            parts.push(format!("{}{}", datum.label, datum.material_condition.symbol()));
+        }
+        parts.join(" | ")
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.tolerance <= 0.0 {
+            return Err("Tolerance must be positive".to_string());
+        }
+        if self.features.is_empty() {
+            return Err("A feature control frame needs at least one feature".to_string());
+        }
+        if self.characteristic.is_form() && !self.datums.is_empty() {
+            return Err(format!("{} cannot reference datums", self.characteristic.name()));
+        }
+        if self.characteristic.requires_datums() && self.datums.is_empty() {
+            return Err(format!("{} requires at least one datum", self.characteristic.name()));
+        }
+        Ok(())
+    }
+}
+
+/// Datum reference frame established 3-2-1 from datum features
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatumReferenceFrame {
+    pub name: String,  // Datum labels in precedence order, e.g. "A|B|C"
+    pub datums: Vec<DatumFeature>,
+    pub origin: Vec3,
+    pub x_axis: Vec3,
+    pub y_axis: Vec3,
+    pub z_axis: Vec3,
+    pub constrained_dof: u8,  // Out of 6
+    #[serde(default)]
+    pub targets: Vec<DatumTargetSet>,  // Empty unless simulated from datum targets
+}
+
+impl DatumReferenceFrame {
+    /// World point expressed in frame coordinates
+    pub fn to_frame(&self, point: &Vec3) -> Vec3 {
+        let d = sub(point, &self.origin);
+        [dot(&d, &self.x_axis), dot(&d, &self.y_axis), dot(&d, &self.z_axis)]
+    }
+
+    /// World direction expressed in frame coordinates
+    pub fn direction_to_frame(&self, direction: &Vec3) -> Vec3 {
+        [dot(direction, &self.x_axis), dot(direction, &self.y_axis), dot(direction, &self.z_axis)]
+    }
+
+    /// Column-major 4x4 matrix mapping world coordinates into the frame
+    pub fn world_to_frame_matrix(&self) -> [f64; 16] {
+        let t = self.to_frame(&[0.0, 0.0, 0.0]);
+        [
+            self.x_axis[0], self.y_axis[0], self.z_axis[0], 0.0,
+            self.x_axis[1], self.y_axis[1], self.z_axis[1], 0.0,
+            self.x_axis[2], self.y_axis[2], self.z_axis[2], 0.0,
+            t[0], t[1], t[2], 1.0,
+        ]
+    }
+}
+
+/// Frame name for a datum sequence
+pub fn frame_name<'a>(labels: impl IntoIterator<Item = &'a str>) -> String {
+    labels.into_iter().collect::<Vec<_>>().join("|")
+}
+
+/// GD&T definitions attached to a loaded model
+#[derive(Debug, Clone, Default)]
+pub struct GdtModel {
+    pub frames: Vec<DatumReferenceFrame>,
+    pub controls: Vec<FeatureControlFrame>,
+    next_control_id: u64,
+}
+
+impl GdtModel {
+    /// Frame matching a control's datum sequence
+    pub fn frame_for(&self, datums: &[DatumReference]) -> Option<&DatumReferenceFrame> {
+        let name = frame_name(datums.iter().map(|d| d.label.as_str()));
+        self.frames.iter().find(|f| f.name == name)
+    }
+}
+
+/// Origin constraint contributed by a datum feature
+enum Constraint {
+    Plane { normal: Vec3, point: Vec3 },
+    Axis { direction: Vec3, point: Vec3 },
+}
+
+/// Frame direction of a datum feature; plane axes point into the material
+fn datum_direction(world: &WorldFace) -> Result<(bool, Vec3), String> {
+    match world.face.face_type.as_str() {
+        "planar" => Ok((true, normalize(&scale(&world.normal, -1.0)))),
+        "cylindrical" | "conical" => world.axis
+            .map(|axis| (false, normalize(&axis)))
+            .ok_or_else(|| format!("Datum face {} has no axis", world.face.id)),
+        other => Err(format!("{} faces cannot be used as datum features", other)),
+    }
+}
+
+/// Establish a datum reference frame from up to three datum features (primary first)
+pub fn establish_frame(datums: &[(DatumFeature, WorldFace)]) -> Result<DatumReferenceFrame, String> {
+    if datums.is_empty() || datums.len() > 3 {
+        return Err("A datum reference frame needs one to three datum features".to_string());
+    }
+
+    let mut z_axis: Option<Vec3> = None;
+    let mut x_axis: Option<Vec3> = None;
+    let mut constraints: Vec<Constraint> = Vec::new();
+
+    for (precedence, (datum, world)) in datums.iter().enumerate() {
+        let (planar, direction) = datum_direction(world)?;
+
+        match z_axis {
+            // Primary orients the frame
+            None => z_axis = Some(direction),
+            Some(z) => {
+                // Later datums clock the frame about the primary axis
+                if x_axis.is_none() {
+                    let candidate = if planar {
+                        reject(&direction, &z)
+                    } else {
+                        reject(&sub(&world.center, &datums[0].1.center), &z)
+                    };
+                    if norm(&candidate) > 1e-6 {
+                        x_axis = Some(normalize(&candidate));
+                    } else if planar {
+                        return Err(format!(
+                            "Datum {} is parallel to the primary datum and cannot orient the frame",
+                            datum.label
+                        ));
+                    }
+                }
+            }
+        }
+
+        constraints.push(if planar {
+            Constraint::Plane { normal: direction, point: world.center }
+        } else {
+            Constraint::Axis { direction, point: world.center }
+        });
+
+        tracing::debug!(label = %datum.label, precedence, planar, "datum feature applied");
+    }
+
+    let z = z_axis.unwrap_or([0.0, 0.0, 1.0]);
+    let rotational_dof = if x_axis.is_some() { 3 } else { 2 };
+    let x = x_axis.unwrap_or_else(|| any_perpendicular(&z));
+    let y = cross(&z, &x);
+
+    // Origin: least-squares fit to the datum constraints, anchored to the primary datum where free
+    let mut rows: Vec<(Vec3, f64)> = Vec::new();
+    for constraint in &constraints {
+        match constraint {
+            Constraint::Plane { normal, point } => rows.push((*normal, dot(normal, point))),
+            Constraint::Axis { direction, point } => {
+                let u = any_perpendicular(direction);
+                let v = cross(direction, &u);
+                rows.push((u, dot(&u, point)));
+                rows.push((v, dot(&v, point)));
+            }
+        }
+    }
+
+    let anchor = datums[0].1.center;
+    let mut ata = [[0.0; 3]; 3];
+    let mut atb = [0.0; 3];
+    for (row, rhs) in &rows {
+        for i in 0..3 {
+            for j in 0..3 {
+                ata[i][j] += row[i] * row[j];
+            }
+            atb[i] += row[i] * rhs;
+        }
+    }
+    let regularization = 1e-9;
+    for i in 0..3 {
+        ata[i][i] += regularization;
+        atb[i] += regularization * anchor[i];
+    }
+    let origin = solve3(&ata, &atb).unwrap_or(anchor);
+
+    let translational_dof = constraint_rank(&rows);
+
+    Ok(DatumReferenceFrame {
+        name: frame_name(datums.iter().map(|(d, _)| d.label.as_str())),
+        datums: datums.iter().map(|(d, _)| d.clone()).collect(),
+        origin,
+        x_axis: x,
+        y_axis: y,
+        z_axis: z,
+        constrained_dof: (rotational_dof + translational_dof) as u8,
+        targets: Vec::new(),
+    })
+}
+
+/// Number of independent directions among the constraint rows
+fn constraint_rank(rows: &[(Vec3, f64)]) -> usize {
+    let mut basis: Vec<Vec3> = Vec::new();
+    for (row, _) in rows {
+        let mut v = *row;
+        for b in &basis {
+            v = reject(&v, b);
+        }
+        if norm(&v) > 1e-6 {
+            basis.push(normalize(&v));
+        }
+    }
+    basis.len().min(3)
+}
+
+/// Face location in a datum reference frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrameLocation {
+    pub face: FaceRef,
+    pub position: Vec3,
+    pub direction: Vec3,  // Axis for cylindrical faces, normal otherwise
+}
+
+/// Define (or redefine) a datum reference frame on a loaded model
+#[tauri::command]
+pub fn define_datum_frame(
+    state: State<'_, ModelStore>,
+    handle: String,
+    datums: Vec<DatumFeature>,
+) -> Result<DatumReferenceFrame, String> {
+    state.with_model_mut(&handle, |model| {
+        let resolved = datums.into_iter()
+            .map(|datum| model.world_face(&datum.face).map(|world| (datum, world)))
+            .collect::<Result<Vec<_>, String>>()?;
+        let frame = establish_frame(&resolved)?;
+
+        model.gdt.frames.retain(|f| f.name != frame.name);
+        model.gdt.frames.push(frame.clone());
+        tracing::info!(handle = %model.handle, frame = %frame.name, dof = frame.constrained_dof, "datum frame defined");
+        Ok(frame)
+    })?
+}
+
+/// Datum reference frames defined on a model
+#[tauri::command]
+pub fn list_datum_frames(state: State<'_, ModelStore>, handle: String) -> Result<Vec<DatumReferenceFrame>, String> {
+    state.with_model(&handle, |model| model.gdt.frames.clone())
+}
+
+/// Attach a feature control frame; datum-referencing frames need a matching DRF
+#[tauri::command]
+pub fn add_feature_control_frame(
+    state: State<'_, ModelStore>,
+    handle: String,
+    control: FeatureControlFrame,
+) -> Result<FeatureControlFrame, String> {
+    control.validate()?;
+
+    state.with_model_mut(&handle, |model| {
+        if !control.datums.is_empty() && model.gdt.frame_for(&control.datums).is_none() {
+            return Err(format!(
+                "No datum reference frame defined for {}",
+                frame_name(control.datums.iter().map(|d| d.label.as_str()))
+            ));
+        }
+        for face in &control.features {
+            model.world_face(face)?;
+        }
+
+        let mut control = control;
+        if control.id.is_empty() {
+            model.gdt.next_control_id += 1;
+            control.id = format!("fcf-{}", model.gdt.next_control_id);
+        }
+        model.gdt.controls.retain(|c| c.id != control.id);
+        model.gdt.controls.push(control.clone());
+        Ok(control)
+    })?
+}
+
+/// Feature control frames on a model
+#[tauri::command]
+pub fn list_feature_control_frames(
+    state: State<'_, ModelStore>,
+    handle: String,
+) -> Result<Vec<FeatureControlFrame>, String> {
+    state.with_model(&handle, |model| model.gdt.controls.clone())
+}
+
+/// Remove a feature control frame
+#[tauri::command]
+pub fn remove_feature_control_frame(state: State<'_, ModelStore>, handle: String, id: String) -> Result<bool, String> {
+    state.with_model_mut(&handle, |model| {
+        let before = model.gdt.controls.len();
+        model.gdt.controls.retain(|c| c.id != id);
+        model.gdt.controls.len() != before
+    })
+}
+
+/// Stackup contributors implied by form controls: each flatness or roundness band, one-sided from the contact
+pub fn form_contributors(controls: &[FeatureControlFrame]) -> Vec<LinkInput> {
+    controls.iter()
+        .filter(|c| c.characteristic.is_form())
+        .map(|c| form_link(Some(format!("{} ({})", c.callout(), c.id)), c.tolerance, "positive"))
+        .collect()
+}
+
+/// Form error contributors for the form controls on a model
+#[tauri::command]
+pub fn list_form_contributors(state: State<'_, ModelStore>, handle: String) -> Result<Vec<LinkInput>, String> {
+    state.with_model(&handle, |model| form_contributors(&model.gdt.controls))
+}
+
+/// Express face locations in a datum reference frame
+#[tauri::command]
+pub fn locate_in_datum_frame(
+    state: State<'_, ModelStore>,
+    handle: String,
+    frame: String,
+    faces: Vec<FaceRef>,
+) -> Result<Vec<FrameLocation>, String> {
+    state.with_model(&handle, |model| {
+        let drf = model.gdt.frames.iter()
+            .find(|f| f.name == frame)
+            .ok_or_else(|| format!("Unknown datum reference frame: {}", frame))?;
+
+        faces.into_iter()
+            .map(|face| {
+                let world = model.world_face(&face)?;
+                let direction = world.axis.unwrap_or(world.normal);
+                Ok(FrameLocation {
+                    position: drf.to_frame(&world.center),
+                    direction: drf.direction_to_frame(&direction),
+                    face,
+                })
+            })
+            .collect()
+    })?
+}
+
+/// Shape of a datum target
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DatumTargetKind {
+    Point,
+    Line,
+    Area,
+}
+
+/// Point, line or area on a face where the part contacts its fixture (castings, weldments)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatumTarget {
+    pub label: String,  // "A1", "A2", ...
+    pub kind: DatumTargetKind,
+    pub face: FaceRef,
+    pub location: Vec3,  // Target point, or the middle of the line or area (world)
+    #[serde(default)]
+    pub direction: Option<Vec3>,  // Line targets
+    #[serde(default)]
+    pub size: Option<f64>,  // Line length or area diameter
+    #[serde(default)]
+    pub location_tolerance: f64,  // ± variation of the surface at the target, along the face normal
+}
+
+/// Datum simulated from its targets, e.g. A from A1-A3
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatumTargetSet {
+    pub label: String,
+    pub targets: Vec<DatumTarget>,
+}
+
+/// Face location whose variation from target movement is wanted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TargetMeasure {
+    pub face: FaceRef,
+    pub direction: Vec3,  // Frame coordinates, e.g. [0, 0, 1] for the height above the primary
+}
+
+/// Points where the fixture touches a target; a line touches at its ends, an area seats on its middle
+fn contact_points(target: &DatumTarget) -> Result<Vec<Vec3>, String> {
+    match target.kind {
+        DatumTargetKind::Point | DatumTargetKind::Area => Ok(vec![target.location]),
+        DatumTargetKind::Line => {
+            let (Some(direction), Some(length)) = (target.direction, target.size) else {
+                return Err(format!("Line target {} needs a direction and a length", target.label));
+            };
+            let half = scale(&normalize(&direction), length / 2.0);
+            Ok(vec![sub(&target.location, &half), add(&target.location, &half)])
+        }
+    }
+}
+
+/// Plane normal through the three contact points spanning the largest triangle
+fn primary_target_normal(points: &[Vec3]) -> Option<Vec3> {
+    let mut best: Option<Vec3> = None;
+    for i in 0..points.len() {
+        for j in i + 1..points.len() {
+            for k in j + 1..points.len() {
+                let n = cross(&sub(&points[j], &points[i]), &sub(&points[k], &points[i]));
+                if best.is_none_or(|b| norm(&n) > norm(&b)) {
+                    best = Some(n);
+                }
+            }
+        }
+    }
+    best.filter(|n| norm(n) > 1e-9).map(|n| normalize(&n))
+}
+
+/// Establish a datum reference frame from datum targets (primary first), each target paired with its world face
+///
+/// The primary plane runs through its targets, the secondary contains its targets and is perpendicular to
+/// the primary, and the tertiary touches its outermost target perpendicular to both.
+pub fn establish_target_frame(datums: &[(DatumTargetSet, Vec<WorldFace>)]) -> Result<DatumReferenceFrame, String> {
+    if datums.is_empty() || datums.len() > 3 {
+        return Err("A datum reference frame needs one to three datum features".to_string());
+    }
+
+    let (mut z_axis, mut x_axis): (Option<Vec3>, Option<Vec3>) = (None, None);
+    let mut simulated: Vec<(DatumFeature, WorldFace)> = Vec::new();
+
+    for (set, faces) in datums {
+        let first = faces.first().ok_or_else(|| format!("Datum {} has no targets", set.label))?;
+        let inward = scale(&normalize(&faces.iter().fold([0.0; 3], |sum, f| add(&sum, &normalize(&f.normal)))), -1.0);
+        let points = set.targets.iter().map(contact_points).collect::<Result<Vec<_>, String>>()?.concat();
+
+        let normal = match (z_axis, x_axis) {
+            (None, _) => primary_target_normal(&points)
+                .ok_or_else(|| format!("Primary datum {} needs three targets that are not in line", set.label))?,
+            (Some(z), None) => {
+                // Direction along the targets, across the primary
+                let span = points.iter()
+                    .flat_map(|a| points.iter().map(move |b| reject(&sub(b, a), &z)))
+                    .max_by(|a, b| norm(a).total_cmp(&norm(b)))
+                    .filter(|s| norm(s) > 1e-6);
+                let n = match span {
+                    Some(span) => cross(&span, &z),
+                    None => reject(&inward, &z),
+                };
+                if norm(&n) < 1e-6 {
+                    return Err(format!("Datum {} is parallel to the primary datum and cannot orient the frame", set.label));
+                }
+                normalize(&n)
+            }
+            (Some(z), Some(x)) => {
+                let n = reject(&reject(&inward, &z), &x);
+                if norm(&n) < 1e-6 {
+                    return Err(format!("Datum {} does not constrain the remaining direction", set.label));
+                }
+                normalize(&n)
+            }
+        };
+        let normal = if dot(&normal, &inward) < 0.0 { scale(&normal, -1.0) } else { normal };
+
+        // The simulator touches the target standing furthest out of the material
+        let contact = points.iter()
+            .copied()
+            .min_by(|a, b| dot(&normal, a).total_cmp(&dot(&normal, b)))
+            .unwrap_or(first.center);
+        if z_axis.is_none() {
+            z_axis = Some(normal);
+        } else if x_axis.is_none() {
+            x_axis = Some(normal);
+        }
+
+        simulated.push((
+            DatumFeature { label: set.label.clone(), face: set.targets[0].face.clone() },
+            WorldFace {
+                face: ParsedFace { face_type: "planar".to_string(), ..first.face.clone() },
+                center: contact,
+                normal: scale(&normal, -1.0),
+                axis: None,
+            },
+        ));
+    }
+
+    let mut frame = establish_frame(&simulated)?;
+    frame.targets = datums.iter().map(|(set, _)| set.clone()).collect();
+    Ok(frame)
+}
+
+/// Stackup contributors from datum target variation: each target moved by its tolerance shifts the frame,
+/// and with it every measured location (central difference, so the sensitivity is linearized)
+pub fn target_contributors(
+    datums: &[(DatumTargetSet, Vec<WorldFace>)],
+    measures: &[(TargetMeasure, WorldFace)],
+) -> Result<Vec<LinkInput>, String> {
+    let nominal = establish_target_frame(datums)?;
+    let mut links = Vec::new();
+
+    for (i, (set, faces)) in datums.iter().enumerate() {
+        for (j, target) in set.targets.iter().enumerate() {
+            if target.location_tolerance <= 0.0 {
+                continue;
+            }
+            let shifted = |offset: f64| {
+                let mut moved = datums.to_vec();
+                let location = &mut moved[i].0.targets[j].location;
+                *location = add(location, &scale(&normalize(&faces[j].normal), offset));
+                establish_target_frame(&moved)
+            };
+            let (plus, minus) = (shifted(target.location_tolerance)?, shifted(-target.location_tolerance)?);
+
+            for (measure, world) in measures {
+                let direction = normalize(&measure.direction);
+                let along = |frame: &DatumReferenceFrame| dot(&frame.to_frame(&world.center), &direction);
+                let swing = ((along(&plus) - along(&minus)) / 2.0).abs();
+                if swing > 1e-12 {
+                    links.push(LinkInput {
+                        name: Some(format!(
+                            "{} target {} at {}:{}",
+                            nominal.name, target.label, measure.face.part_id, measure.face.face_id
+                        )),
+                        nominal: 0.0,
+                        plus_tolerance: swing,
+                        minus_tolerance: swing,
+                        direction: "positive".to_string(),
+                        distribution: "normal".to_string(),
+                        sigma: None,
+                        inspected: false,
+                    });
+                }
+            }
+        }
+    }
+    Ok(links)
+}
+
+/// Resolve each target's face on a model
+fn resolve_targets(model: &LoadedModel, datums: &[DatumTargetSet]) -> Result<Vec<(DatumTargetSet, Vec<WorldFace>)>, String> {
+    datums.iter()
+        .map(|set| {
+            let faces = set.targets.iter().map(|t| model.world_face(&t.face)).collect::<Result<Vec<_>, String>>()?;
+            Ok((set.clone(), faces))
+        })
+        .collect()
+}
+
+/// Define (or redefine) a datum reference frame simulated from datum targets
+#[tauri::command]
+pub fn define_datum_target_frame(
+    state: State<'_, ModelStore>,
+    handle: String,
+    datums: Vec<DatumTargetSet>,
+) -> Result<DatumReferenceFrame, String> {
+    state.with_model_mut(&handle, |model| {
+        let frame = establish_target_frame(&resolve_targets(model, &datums)?)?;
+
+        model.gdt.frames.retain(|f| f.name != frame.name);
+        model.gdt.frames.push(frame.clone());
+        tracing::info!(handle = %model.handle, frame = %frame.name, targets = datums.iter().map(|d| d.targets.len()).sum::<usize>(), "datum target frame defined");
+        Ok(frame)
+    })?
+}
+
+/// Stackup contributors from the target variation of a target-based frame
+#[tauri::command]
+pub fn list_datum_target_contributors(
+    state: State<'_, ModelStore>,
+    handle: String,
+    frame: String,
+    measures: Vec<TargetMeasure>,
+) -> Result<Vec<LinkInput>, String> {
+    state.with_model(&handle, |model| {
+        let drf = model.gdt.frames.iter()
+            .find(|f| f.name == frame)
+            .ok_or_else(|| format!("Unknown datum reference frame: {}", frame))?;
+        if drf.targets.is_empty() {
+            return Err(format!("Datum reference frame {} is not defined from datum targets", frame));
+        }
+        let measured = measures.into_iter()
+            .map(|measure| model.world_face(&measure.face).map(|world| (measure, world)))
+            .collect::<Result<Vec<_>, String>>()?;
+        target_contributors(&resolve_targets(model, &drf.targets)?, &measured)
+    })?
+}
+
+/// Basic (theoretically exact) location of a hole in frame coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoleBasicLocation {
+    pub hole_id: String,
+    pub basic: Vec3,
+    #[serde(default)]
+    pub actual_diameter: Option<f64>,  // Measured size; the modeled diameter is used when None
+}
+
+/// Size limits of the toleranced holes (MMC is the smallest hole)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HoleSizeLimits {
+    pub mmc_diameter: f64,
+    pub lmc_diameter: f64,
+}
+
+/// Position result for one hole
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolePositionResult {
+    pub hole_id: String,
+    pub basic: Vec3,
+    pub actual: Vec3,          // Hole axis location in frame coordinates
+    pub deviation: Vec3,       // Perpendicular to the hole axis
+    pub position_error: f64,   // Diameter (or width) of the zone the axis needs
+    pub actual_diameter: f64,
+    pub bonus: f64,
+    pub allowed: f64,
+    pub pass: bool,
+}
+
+/// Result of a true position evaluation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TruePositionResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub control_id: String,
+    pub frame: Option<String>,
+    pub results: Vec<HolePositionResult>,
+    pub passed: usize,
+    pub failed: usize,
+}
+
+/// Bonus tolerance from the hole's departure from its material condition size
+pub fn position_bonus(condition: MaterialCondition, actual_diameter: f64, limits: Option<HoleSizeLimits>) -> f64 {
+    let Some(limits) = limits else { return 0.0 };
+    let max_bonus = (limits.lmc_diameter - limits.mmc_diameter).abs();
+    let bonus = match condition {
+        MaterialCondition::Rfs => 0.0,
+        MaterialCondition::Mmc => actual_diameter - limits.mmc_diameter,
+        MaterialCondition::Lmc => limits.lmc_diameter - actual_diameter,
+    };
+    bonus.clamp(0.0, max_bonus)
+}
+
+/// Evaluate one hole against a position control (datum shift is not applied)
+pub fn evaluate_hole_position(
+    frame: &DatumReferenceFrame,
+    control: &FeatureControlFrame,
+    hole: &Hole,
+    location: &HoleBasicLocation,
+    limits: Option<HoleSizeLimits>,
+) -> HolePositionResult {
+    let actual = frame.to_frame(&hole.center);
+    let axis = normalize(&frame.direction_to_frame(&hole.axis));
+    let deviation = reject(&sub(&actual, &location.basic), &axis);
+
+    let position_error = if control.diametral {
+        2.0 * norm(&deviation)
+    } else {
+        2.0 * deviation.iter().fold(0.0f64, |m, d| m.max(d.abs()))
+    };
+
+    let actual_diameter = location.actual_diameter.unwrap_or(hole.diameter);
+    let bonus = position_bonus(control.material_condition, actual_diameter, limits);
+    let allowed = control.tolerance + bonus;
+
+    HolePositionResult {
+        hole_id: hole.id.clone(),
+        basic: location.basic,
+        actual,
+        deviation,
+        position_error,
+        actual_diameter,
+        bonus,
+        allowed,
+        pass: position_error <= allowed + 1e-9,
+    }
+}
+
+/// Evaluate recognized holes, or whole hole patterns, against a position feature control frame
+#[tauri::command]
+pub fn evaluate_true_position(
+    state: State<'_, ModelStore>,
+    handle: String,
+    control_id: String,
+    holes: Vec<HoleBasicLocation>,
+    size_limits: Option<HoleSizeLimits>,
+) -> TruePositionResult {
+    let outcome = state.with_model(&handle, |model| {
+        let control = model.gdt.controls.iter()
+            .find(|c| c.id == control_id)
+            .ok_or_else(|| format!("Unknown feature control frame: {}", control_id))?;
+        if control.characteristic != GeometricCharacteristic::Position {
+            return Err(format!("{} is not a position control", control_id));
+        }
+        let frame = model.gdt.frame_for(&control.datums)
+            .ok_or_else(|| "The control's datum reference frame is not defined".to_string())?;
+
+        // Whole patterns locate as one feature at their center
+        let mut recognized = model_holes(model);
+        recognized.extend(model_pattern_features(model));
+        let results = holes.iter()
+            .map(|location| {
+                let hole = recognized.iter()
+                    .find(|h| h.id == location.hole_id)
+                    .ok_or_else(|| format!("Unknown hole: {}", location.hole_id))?;
+                Ok(evaluate_hole_position(frame, control, hole, location, size_limits))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        Ok((frame.name.clone(), results))
+    }).and_then(|r| r);
+
+    match outcome {
+        Ok((frame, results)) => {
+            let passed = results.iter().filter(|r| r.pass).count();
+            tracing::info!(control = %control_id, passed, failed = results.len() - passed, "true position evaluated");
+            TruePositionResult {
+                success: true,
+                error: None,
+                control_id,
+                frame: Some(frame),
+                failed: results.len() - passed,
+                passed,
+                results,
+            }
+        }
+        Err(e) => TruePositionResult {
+            success: false,
+            error: Some(e),
+            control_id,
+            frame: None,
+            results: vec![],
+            passed: 0,
+            failed: 0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datum(label: &str, face_type: &str, center: Vec3, normal: Vec3) -> (DatumFeature, WorldFace) {
+        let face = ParsedFace {
+            id: 0,
+            face_type: face_type.to_string(),
+            normal,
+            center,
+            area: 100.0,
+            radius: None,
+            axis: (face_type == "cylindrical").then_some(normal),
+            step_entity_id: None,
+            same_sense: None,
+            semi_angle: None,
+            apex: None,
+            minor_radius: None,
+        };
+        (
+            DatumFeature { label: label.to_string(), face: FaceRef { part_id: "p".to_string(), face_id: 0 } },
+            WorldFace { center, normal, axis: face.axis, face },
+        )
+    }
+
+    #[test]
+    fn test_three_plane_frame_locates_corner() {
+        // Bottom, left and back faces of a block whose corner sits at (10, 20, 5)
+        let datums = vec![
+            datum("A", "planar", [30.0, 40.0, 5.0], [0.0, 0.0, -1.0]),
+            datum("B", "planar", [10.0, 40.0, 25.0], [-1.0, 0.0, 0.0]),
+            datum("C", "planar", [30.0, 20.0, 25.0], [0.0, -1.0, 0.0]),
+        ];
+        let frame = establish_frame(&datums).unwrap();
+
+        assert_eq!(frame.name, "A|B|C");
+        assert_eq!(frame.constrained_dof, 6);
+        let corner = frame.to_frame(&[10.0, 20.0, 5.0]);
+        assert!(corner.iter().all(|c| c.abs() < 1e-6));
+    }
+
+    fn target(label: &str, location: Vec3, location_tolerance: f64) -> DatumTarget {
+        DatumTarget {
+            label: label.to_string(),
+            kind: DatumTargetKind::Point,
+            face: FaceRef { part_id: "p".to_string(), face_id: 0 },
+            location,
+            direction: None,
+            size: None,
+            location_tolerance,
+        }
+    }
+
+    #[test]
+    fn test_target_frame_and_primary_target_variation() {
+        // Casting with A1-A3 on its bottom at z = 5, B1-B2 on the left at x = 10, C1 on the back at y = 20
+        let face = |normal: Vec3| datum("", "planar", [0.0; 3], normal).1;
+        let set = |label: &str, targets: Vec<DatumTarget>, normal: Vec3| {
+            let faces = targets.iter().map(|_| face(normal)).collect();
+            (DatumTargetSet { label: label.to_string(), targets }, faces)
+        };
+        let datums = vec![
+            set("A", vec![
+                target("A1", [10.0, 20.0, 5.0], 0.3),
+                target("A2", [70.0, 20.0, 5.0], 0.0),
+                target("A3", [40.0, 80.0, 5.0], 0.0),
+            ], [0.0, 0.0, -1.0]),
+            set("B", vec![target("B1", [10.0, 30.0, 10.0], 0.0), target("B2", [10.0, 70.0, 10.0], 0.0)], [-1.0, 0.0, 0.0]),
+            set("C", vec![target("C1", [40.0, 20.0, 10.0], 0.0)], [0.0, -1.0, 0.0]),
+        ];
+
+        let frame = establish_target_frame(&datums).unwrap();
+        assert_eq!(frame.name, "A|B|C");
+        assert_eq!(frame.constrained_dof, 6);
+        assert!(frame.to_frame(&[10.0, 20.0, 5.0]).iter().all(|c| c.abs() < 1e-6));
+
+        // A top face over the centroid of A1-A3 rises and falls by a third of A1's variation
+        let top = TargetMeasure { face: FaceRef { part_id: "p".to_string(), face_id: 1 }, direction: [0.0, 0.0, 1.0] };
+        let links = target_contributors(&datums, &[(top, datum("", "planar", [40.0, 40.0, 25.0], [0.0, 0.0, 1.0]).1)]).unwrap();
+        assert_eq!(links.len(), 1);
+        assert!((links[0].plus_tolerance - 0.1).abs() < 1e-4);
+        assert!(links[0].name.as_deref().unwrap().contains("A1"));
+    }
+
+    #[test]
+    fn test_parallel_secondary_rejected() {
+        let datums = vec![
+            datum("A", "planar", [0.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+            datum("B", "planar", [0.0, 0.0, 10.0], [0.0, 0.0, -1.0]),
+        ];
+        assert!(establish_frame(&datums).is_err());
+    }
+
+    #[test]
+    fn test_form_control_rejects_datums() {
+        let control = FeatureControlFrame {
+            id: String::new(),
+            characteristic: GeometricCharacteristic::Flatness,
+            tolerance: 0.05,
+            diametral: false,
+            material_condition: MaterialCondition::Rfs,
+            datums: vec![DatumReference { label: "A".to_string(), material_condition: MaterialCondition::Rfs }],
+            features: vec![FaceRef { part_id: "p".to_string(), face_id: 1 }],
+        };
+        assert!(control.validate().is_err());
+        assert_eq!(
+            FeatureControlFrame { datums: vec![], ..control }.callout(),
+            "Flatness | 0.050"
+        );
+    }
+
+    #[test]
+    fn test_true_position_with_mmc_bonus() {
+        let frame = establish_frame(&[
+            datum("A", "planar", [0.0, 0.0, 0.0], [0.0, 0.0, -1.0]),
+            datum("B", "planar", [0.0, 0.0, 0.0], [-1.0, 0.0, 0.0]),
+            datum("C", "planar", [0.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+        ]).unwrap();
+        let control = FeatureControlFrame {
+            id: "fcf-1".to_string(),
+            characteristic: GeometricCharacteristic::Position,
+            tolerance: 0.1,
+            diametral: true,
+            material_condition: MaterialCondition::Mmc,
+            datums: vec![],
+            features: vec![],
+        };
+        let hole = Hole {
+            id: "p-hole-1".to_string(),
+            part_id: "p".to_string(),
+            face_ids: vec![0],
+            diameter: 6.05,
+            center: [20.03, 10.04, 3.0],  // 0.05 off the basic location, perpendicular to the axis
+            axis: [0.0, 0.0, 1.0],
+            internal: Some(true),
+        };
+        let location = HoleBasicLocation { hole_id: hole.id.clone(), basic: [20.0, 10.0, 0.0], actual_diameter: None };
+
+        // Error is 2 * 0.05 = 0.10 plus the bonus from a hole 0.05 over MMC
+        let limits = HoleSizeLimits { mmc_diameter: 6.0, lmc_diameter: 6.1 };
+        let result = evaluate_hole_position(&frame, &control, &hole, &location, Some(limits));
+        assert!((result.position_error - 0.1).abs() < 1e-9);
+        assert!((result.bonus - 0.05).abs() < 1e-9);
+        assert!(result.pass);
+
+        let rfs = FeatureControlFrame { material_condition: MaterialCondition::Rfs, tolerance: 0.08, ..control };
+        assert!(!evaluate_hole_position(&frame, &rfs, &hole, &location, Some(limits)).pass);
+    }
+}
@@ -0,0 +1,233 @@
+// Standard hardware recognition (fasteners, bearings, dowels) from part geometry
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::ParsedPart;
+use crate::features::part_extent_along;
+use crate::linalg::normalize;
+use crate::model_store::ModelStore;
+
+/// Kind of standard part
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HardwareCategory {
+    Fastener,
+    Bearing,
+    Dowel,
+}
+
+/// Geometric signature of a catalogue part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareSignature {
+    pub label: String,            // e.g. "M5x16 SHCS"
+    pub category: HardwareCategory,
+    pub standard: String,
+    pub diameters: Vec<f64>,      // Cylinder diameters the part must have
+    pub outer_diameter: f64,
+    pub length: f64,              // Along the main axis
+}
+
+/// A part recognized as standard hardware
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareMatch {
+    pub part_id: String,
+    pub part_name: String,
+    pub label: String,
+    pub category: HardwareCategory,
+    pub standard: String,
+    pub confidence: f64,
+}
+
+// ISO 4762 socket head cap screws: thread, head diameter, head height
+const SHCS: [(f64, f64, f64); 7] = [
+    (3.0, 5.5, 3.0),
+    (4.0, 7.0, 4.0),
+    (5.0, 8.5, 5.0),
+    (6.0, 10.0, 6.0),
+    (8.0, 13.0, 8.0),
+    (10.0, 16.0, 10.0),
+    (12.0, 18.0, 12.0),
+];
+const SCREW_LENGTHS: [f64; 18] = [
+    6.0, 8.0, 10.0, 12.0, 16.0, 20.0, 25.0, 30.0, 35.0, 40.0, 45.0, 50.0, 55.0, 60.0, 70.0, 80.0, 90.0, 100.0,
+];
+
+// Deep groove ball bearings: designation, bore, outside diameter, width
+pub(crate) const BEARINGS: [(&str, f64, f64, f64); 15] = [
+    ("608", 8.0, 22.0, 7.0),
+    ("6000", 10.0, 26.0, 8.0),
+    ("6001", 12.0, 28.0, 8.0),
+    ("6002", 15.0, 32.0, 9.0),
+    ("6003", 17.0, 35.0, 10.0),
+    ("6004", 20.0, 42.0, 12.0),
+    ("6200", 10.0, 30.0, 9.0),
+    ("6201", 12.0, 32.0, 10.0),
+    ("6202", 15.0, 35.0, 11.0),
+    ("6203", 17.0, 40.0, 12.0),
+    ("6204", 20.0, 47.0, 14.0),
+    ("6205", 25.0, 52.0, 15.0),
+    ("6300", 10.0, 35.0, 11.0),
+    ("6301", 12.0, 37.0, 12.0),
+    ("6302", 15.0, 42.0, 13.0),
+];
+
+// ISO 8734 dowel pins
+const DOWEL_DIAMETERS: [f64; 8] = [2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0, 12.0];
+const DOWEL_LENGTHS: [f64; 10] = [6.0, 8.0, 10.0, 12.0, 16.0, 20.0, 24.0, 30.0, 40.0, 50.0];
+
+/// Built-in catalogue of standard hardware
+pub fn hardware_library() -> Vec<HardwareSignature> {
+    let mut library = Vec::new();
+
+    for (d, dk, k) in SHCS {
+        for length in SCREW_LENGTHS.iter().filter(|&&l| l >= d && l <= 10.0 * d) {
+            library.push(HardwareSignature {
+                label: format!("M{}x{} SHCS", d, length),
+                category: HardwareCategory::Fastener,
+                standard: "ISO 4762".to_string(),
+                diameters: vec![d, dk],
+                outer_diameter: dk,
+                length: length + k,
+            });
+        }
+    }
+
+    for (designation, bore, outside, width) in BEARINGS {
+        library.push(HardwareSignature {
+            label: format!("{} bearing", designation),
+            category: HardwareCategory::Bearing,
+            standard: "ISO 15".to_string(),
+            diameters: vec![bore, outside],
+            outer_diameter: outside,
+            length: width,
+        });
+    }
+
+    for d in DOWEL_DIAMETERS {
+        for length in DOWEL_LENGTHS.iter().filter(|&&l| l >= 2.0 * d && l <= 10.0 * d) {
+            library.push(HardwareSignature {
+                label: format!("{}x{} dowel", d, length),
+                category: HardwareCategory::Dowel,
+                standard: "ISO 8734".to_string(),
+                diameters: vec![d],
+                outer_diameter: d,
+                length: *length,
+            });
+        }
+    }
+
+    library
+}
+
+fn within(actual: f64, expected: f64, relative: f64, absolute: f64) -> Option<f64> {
+    let error = (actual - expected).abs();
+    (error <= (expected * relative).max(absolute)).then(|| error / expected.max(1e-9))
+}
+
+/// Score a part against one signature; None when it does not fit
+fn score(diameters: &[f64], outer: f64, length: f64, signature: &HardwareSignature) -> Option<f64> {
+    let mut errors = Vec::new();
+    for required in &signature.diameters {
+        let best = diameters.iter()
+            .filter_map(|d| within(*d, *required, 0.01, 0.05))
+            .reduce(f64::min)?;
+        errors.push(best);
+    }
+    errors.push(within(outer, signature.outer_diameter, 0.01, 0.05)?);
+    errors.push(within(length, signature.length, 0.02, 0.2)?);
+
+    let mean = errors.iter().sum::<f64>() / errors.len() as f64;
+    Some((1.0 - mean * 10.0).clamp(0.0, 1.0))
+}
+
+/// Best catalogue match for a part, from its cylinder sizes and length along the main axis
+pub fn match_part(part: &ParsedPart, library: &[HardwareSignature]) -> Option<HardwareMatch> {
+    let cylinders: Vec<(f64, [f64; 3])> = part.faces.iter()
+        .filter(|f| f.face_type == "cylindrical")
+        .filter_map(|f| Some((2.0 * f.radius?, f.axis?)))
+        .collect();
+    let (outer, axis) = cylinders.iter().copied().max_by(|a, b| a.0.total_cmp(&b.0))?;
+    let length = part_extent_along(part, &normalize(&axis))?;
+    let diameters: Vec<f64> = cylinders.iter().map(|c| c.0).collect();
+
+    let (signature, confidence) = library.iter()
+        .filter_map(|s| score(&diameters, outer, length, s).map(|c| (s, c)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))?;
+
+    Some(HardwareMatch {
+        part_id: part.id.clone(),
+        part_name: part.name.clone(),
+        label: signature.label.clone(),
+        category: signature.category,
+        standard: signature.standard.clone(),
+        confidence,
+    })
+}
+
+/// Label the parts of a loaded model that match standard hardware
+#[tauri::command]
+pub fn recognize_hardware(state: State<'_, ModelStore>, handle: String) -> Result<Vec<HardwareMatch>, String> {
+    let library = hardware_library();
+    state.with_model(&handle, |model| {
+        model.assembly.parts.iter()
+            .filter_map(|part| match_part(part, &library))
+            .collect()
+    })
+}
+
+/// The built-in hardware catalogue
+#[tauri::command]
+pub fn list_hardware_library() -> Vec<HardwareSignature> {
+    hardware_library()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly_parser::{ParsedFace, PartBoundingBox};
+
+    fn round_part(id: &str, diameters: &[f64], length: f64) -> ParsedPart {
+        let outer = diameters.iter().copied().fold(0.0, f64::max);
+        ParsedPart {
+            id: id.to_string(),
+            name: id.to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: Some(PartBoundingBox {
+                min: [-outer / 2.0, -outer / 2.0, 0.0],
+                max: [outer / 2.0, outer / 2.0, length],
+                dimensions: [outer, outer, length],
+            }),
+            faces: diameters.iter().enumerate().map(|(i, d)| ParsedFace {
+                id: i as i64,
+                face_type: "cylindrical".to_string(),
+                normal: [1.0, 0.0, 0.0],
+                center: [0.0; 3],
+                area: 0.0,
+                radius: Some(d / 2.0),
+                axis: Some([0.0, 0.0, 1.0]),
+                step_entity_id: None,
+                same_sense: None,
+                semi_angle: None,
+                apex: None,
+                minor_radius: None,
+            }).collect(),
+            product_definition_id: None,
+        }
+    }
+
+    #[test]
+    fn test_matches_screw_bearing_and_rejects_custom_part() {
+        let library = hardware_library();
+
+        let screw = match_part(&round_part("screw", &[5.0, 8.5], 21.0), &library).unwrap();
+        assert_eq!(screw.label, "M5x16 SHCS");
+        assert_eq!(screw.category, HardwareCategory::Fastener);
+
+        let bearing = match_part(&round_part("bearing", &[15.0, 35.0], 11.0), &library).unwrap();
+        assert_eq!(bearing.label, "6202 bearing");
+
+        assert!(match_part(&round_part("shaft", &[14.3, 22.7], 93.0), &library).is_none());
+    }
+}
@@ -0,0 +1,368 @@
+// Hole pattern recognition: bolt circles, grids and rows of like holes, treated as one logical feature
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::ParsedPart;
+use crate::features::{recognize_part_holes, Hole};
+use crate::linalg::{add, any_perpendicular, cross, dot, normalize, scale, solve3, sub, Vec3};
+use crate::model_store::{LoadedModel, ModelStore};
+
+/// Largest offset of a hole from the ideal pattern (mm) for it to still count as a member
+const PATTERN_TOLERANCE: f64 = 1e-3;
+
+/// Holes of one part sharing size and direction, laid out on a circle, grid or row
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolePattern {
+    pub id: String,
+    pub part_id: String,
+    pub kind: String,                          // "bolt_circle", "grid" or "linear"
+    pub hole_ids: Vec<String>,                 // Bolt circles in angular order, grids and rows in lattice order
+    pub count: usize,
+    pub hole_diameter: f64,
+    pub axis: Vec3,
+    pub center: Vec3,                          // Circle center, or the centroid of a grid or row (world)
+    pub bolt_circle_diameter: Option<f64>,
+    pub angular_spacing_deg: Option<f64>,
+    pub full_circle: Option<bool>,             // Equally spaced all the way round, not just an arc
+    pub pitch: Option<[f64; 2]>,               // Spacing along the two lattice directions; the second is 0 for a row
+    pub counts: Option<[usize; 2]>,            // Holes along each pitch direction
+    pub max_deviation: f64,                    // Largest hole offset from the ideal pattern
+}
+
+/// Position tolerances a clearance-hole pattern allows for a given fastener size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FastenerFloat {
+    pub pattern_id: String,
+    pub hole_mmc_diameter: f64,
+    pub fastener_mmc_diameter: f64,
+    pub floating: f64,  // Each part's position tolerance when the fastener passes through both (T = H - F)
+    pub fixed: f64,     // Each part's position tolerance when one part holds the fastener (T = (H - F) / 2)
+}
+
+/// Floating and fixed fastener position tolerances at maximum material condition
+pub fn fastener_float(pattern_id: &str, hole_mmc_diameter: f64, fastener_mmc_diameter: f64) -> Result<FastenerFloat, String> {
+    if !(hole_mmc_diameter.is_finite() && fastener_mmc_diameter.is_finite() && fastener_mmc_diameter > 0.0) {
+        return Err("Hole and fastener diameters must be positive numbers".to_string());
+    }
+    if fastener_mmc_diameter >= hole_mmc_diameter {
+        return Err(format!(
+            "A {:.3} mm fastener leaves no clearance in a {:.3} mm hole",
+            fastener_mmc_diameter, hole_mmc_diameter
+        ));
+    }
+    let clearance = hole_mmc_diameter - fastener_mmc_diameter;
+    Ok(FastenerFloat {
+        pattern_id: pattern_id.to_string(),
+        hole_mmc_diameter,
+        fastener_mmc_diameter,
+        floating: clearance,
+        fixed: clearance / 2.0,
+    })
+}
+
+/// Layout of a hole family in its plane, before ids and world placement
+struct Layout {
+    kind: &'static str,
+    order: Vec<usize>,
+    center: [f64; 2],
+    bolt_circle_diameter: Option<f64>,
+    angular_spacing_deg: Option<f64>,
+    full_circle: Option<bool>,
+    pitch: Option<[f64; 2]>,
+    counts: Option<[usize; 2]>,
+    max_deviation: f64,
+}
+
+fn cross2(a: [f64; 2], b: [f64; 2]) -> f64 {
+    a[0] * b[1] - a[1] * b[0]
+}
+
+fn length2(a: [f64; 2]) -> f64 {
+    a[0].hypot(a[1])
+}
+
+fn minus2(a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+    [a[0] - b[0], a[1] - b[1]]
+}
+
+/// Equally spaced holes on a circle (least-squares circle fit, then the angular spacing)
+fn circle_layout(points: &[[f64; 2]]) -> Option<Layout> {
+    if points.len() < 3 {
+        return None;
+    }
+    // Holes in a row fit a huge circle within tolerance; leave them to the lattice fit
+    let far = points.iter().copied().max_by(|a, b| length2(minus2(*a, points[0])).total_cmp(&length2(minus2(*b, points[0]))))?;
+    let run = minus2(far, points[0]);
+    if points.iter().all(|p| cross2(run, minus2(*p, points[0])).abs() <= PATTERN_TOLERANCE * length2(run)) {
+        return None;
+    }
+
+    // Kasa fit on centered coordinates: x^2 + y^2 + D x + E y + F = 0
+    let n = points.len() as f64;
+    let mean = [points.iter().map(|p| p[0]).sum::<f64>() / n, points.iter().map(|p| p[1]).sum::<f64>() / n];
+    let mut m = [[0.0; 3]; 3];
+    let mut b = [0.0; 3];
+    for p in points {
+        let (x, y) = (p[0] - mean[0], p[1] - mean[1]);
+        let row = [x, y, 1.0];
+        let rhs = -(x * x + y * y);
+        for (i, ri) in row.iter().enumerate() {
+            for (j, rj) in row.iter().enumerate() {
+                m[i][j] += ri * rj;
+            }
+            b[i] += ri * rhs;
+        }
+    }
+    let [d, e, f] = solve3(&m, &b)?;
+    let radius = (d * d / 4.0 + e * e / 4.0 - f).sqrt();
+    if !radius.is_finite() || radius <= PATTERN_TOLERANCE {
+        return None;
+    }
+    let center = [mean[0] - d / 2.0, mean[1] - e / 2.0];
+
+    // Sort by angle and start after the widest gap, so arcs read in order
+    let angle = |p: &[f64; 2]| (p[1] - center[1]).atan2(p[0] - center[0]);
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&a, &b| angle(&points[a]).total_cmp(&angle(&points[b])));
+    let tau = std::f64::consts::TAU;
+    let gaps: Vec<f64> = (0..order.len())
+        .map(|k| (angle(&points[order[(k + 1) % order.len()]]) - angle(&points[order[k]])).rem_euclid(tau))
+        .collect();
+    let widest = (0..gaps.len()).max_by(|&a, &b| gaps[a].total_cmp(&gaps[b]))?;
+    let count = order.len();
+    order.rotate_left((widest + 1) % count);
+
+    let start = angle(&points[order[0]]);
+    let sweep = (angle(&points[order[order.len() - 1]]) - start).rem_euclid(tau);
+    let full_circle = (gaps[widest] - tau / n).abs() * radius <= PATTERN_TOLERANCE;
+    let spacing = if full_circle { tau / n } else { sweep / (n - 1.0) };
+    let max_deviation = order.iter().enumerate()
+        .map(|(k, &i)| {
+            let ideal = start + k as f64 * spacing;
+            let expected = [center[0] + radius * ideal.cos(), center[1] + radius * ideal.sin()];
+            length2(minus2(points[i], expected))
+        })
+        .fold(0.0, f64::max);
+    if max_deviation > PATTERN_TOLERANCE {
+        return None;
+    }
+
+    Some(Layout {
+        kind: "bolt_circle",
+        order,
+        center,
+        bolt_circle_diameter: Some(2.0 * radius),
+        angular_spacing_deg: Some(spacing.to_degrees()),
+        full_circle: Some(full_circle),
+        pitch: None,
+        counts: None,
+        max_deviation,
+    })
+}
+
+fn shortest<'a>(candidates: impl Iterator<Item = &'a [f64; 2]>) -> Option<[f64; 2]> {
+    candidates.copied().min_by(|a, b| length2(*a).total_cmp(&length2(*b)))
+}
+
+/// Holes on a complete rectangular (or skewed) lattice, or evenly along one line
+fn lattice_layout(points: &[[f64; 2]]) -> Option<Layout> {
+    if points.len() < 2 {
+        return None;
+    }
+    let origin = points[0];
+    let offsets: Vec<[f64; 2]> = points[1..].iter().map(|p| minus2(*p, origin)).collect();
+    let u = shortest(offsets.iter())?;
+    let v = shortest(offsets.iter().filter(|o| cross2(u, **o).abs() > 0.1 * length2(u) * length2(**o)));
+
+    // Lattice indices of every hole; all must be whole steps
+    let det = v.map_or(0.0, |v| cross2(u, v));
+    let mut indices = Vec::with_capacity(points.len());
+    let mut max_deviation = 0.0f64;
+    for p in points {
+        let o = minus2(*p, origin);
+        let (i, j) = match v {
+            Some(v) => (cross2(o, v) / det, cross2(u, o) / det),
+            None => ((o[0] * u[0] + o[1] * u[1]) / length2(u).powi(2), 0.0),
+        };
+        let (i, j) = (i.round(), j.round());
+        let ideal = [origin[0] + i * u[0] + j * v.map_or(0.0, |v| v[0]), origin[1] + i * u[1] + j * v.map_or(0.0, |v| v[1])];
+        max_deviation = max_deviation.max(length2(minus2(*p, ideal)));
+        indices.push((i as i64, j as i64));
+    }
+    if max_deviation > PATTERN_TOLERANCE {
+        return None;
+    }
+    let span = |f: fn(&(i64, i64)) -> i64| {
+        let values = indices.iter().map(f);
+        (values.clone().max().unwrap_or(0) - values.min().unwrap_or(0) + 1) as usize
+    };
+    let counts = [span(|&(i, _)| i), span(|&(_, j)| j)];
+    let mut distinct = indices.clone();
+    distinct.sort();
+    distinct.dedup();
+    if distinct.len() != points.len() || counts[0] * counts[1] != points.len() {
+        return None;
+    }
+
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by_key(|&k| (indices[k].1, indices[k].0));
+    let n = points.len() as f64;
+    Some(Layout {
+        kind: if v.is_some() { "grid" } else { "linear" },
+        order,
+        center: [points.iter().map(|p| p[0]).sum::<f64>() / n, points.iter().map(|p| p[1]).sum::<f64>() / n],
+        bolt_circle_diameter: None,
+        angular_spacing_deg: None,
+        full_circle: None,
+        pitch: Some([length2(u), v.map_or(0.0, length2)]),
+        counts: Some(counts),
+        max_deviation,
+    })
+}
+
+/// Recognize hole patterns on a part: like holes are grouped, then fitted as a bolt circle before a grid or row
+pub fn recognize_part_patterns(part: &ParsedPart) -> Vec<HolePattern> {
+    let holes = recognize_part_holes(part);
+    let mut families: Vec<Vec<&Hole>> = Vec::new();
+    for hole in &holes {
+        let family = families.iter_mut().find(|f| {
+            (f[0].diameter - hole.diameter).abs() < 1e-6 * hole.diameter.max(1.0) && dot(&f[0].axis, &hole.axis).abs() > 1.0 - 1e-6
+        });
+        match family {
+            Some(family) => family.push(hole),
+            None => families.push(vec![hole]),
+        }
+    }
+
+    let mut patterns = Vec::new();
+    for family in families.into_iter().filter(|f| f.len() >= 2) {
+        // In-plane coordinates perpendicular to the shared axis
+        let axis = family[0].axis;
+        let e1 = normalize(&any_perpendicular(&axis));
+        let e2 = cross(&axis, &e1);
+        let origin = family[0].center;
+        let points: Vec<[f64; 2]> = family.iter()
+            .map(|h| {
+                let o = sub(&h.center, &origin);
+                [dot(&o, &e1), dot(&o, &e2)]
+            })
+            .collect();
+
+        let Some(layout) = circle_layout(&points).or_else(|| lattice_layout(&points)) else { continue };
+        let center = add(&origin, &add(&scale(&e1, layout.center[0]), &scale(&e2, layout.center[1])));
+        patterns.push(HolePattern {
+            id: format!("{}-pattern-{}", part.id, patterns.len() + 1),
+            part_id: part.id.clone(),
+            kind: layout.kind.to_string(),
+            hole_ids: layout.order.iter().map(|&i| family[i].id.clone()).collect(),
+            count: family.len(),
+            hole_diameter: family[0].diameter,
+            axis,
+            center,
+            bolt_circle_diameter: layout.bolt_circle_diameter,
+            angular_spacing_deg: layout.angular_spacing_deg,
+            full_circle: layout.full_circle,
+            pitch: layout.pitch,
+            counts: layout.counts,
+            max_deviation: layout.max_deviation,
+        });
+    }
+    patterns
+}
+
+/// Hole patterns across every part of a loaded model
+pub fn model_patterns(model: &LoadedModel) -> Vec<HolePattern> {
+    model.assembly.parts.iter().flat_map(recognize_part_patterns).collect()
+}
+
+/// Patterns as single features located at their center, so position controls can be evaluated on the whole pattern
+pub fn model_pattern_features(model: &LoadedModel) -> Vec<Hole> {
+    model_patterns(model).into_iter()
+        .map(|p| Hole {
+            id: p.id,
+            part_id: p.part_id,
+            face_ids: Vec::new(),
+            diameter: p.hole_diameter,
+            center: p.center,
+            axis: p.axis,
+            internal: Some(true),
+        })
+        .collect()
+}
+
+/// Recognize bolt circles, grids and rows of holes on a loaded model
+#[tauri::command]
+pub fn detect_hole_patterns(state: State<'_, ModelStore>, handle: String) -> Result<Vec<HolePattern>, String> {
+    let patterns = state.with_model(&handle, model_patterns)?;
+    tracing::info!(handle = %handle, patterns = patterns.len(), "hole patterns recognized");
+    Ok(patterns)
+}
+
+/// Floating and fixed fastener position tolerances for a pattern; the modeled hole size stands in for MMC
+#[tauri::command]
+pub fn get_pattern_fastener_float(
+    state: State<'_, ModelStore>,
+    handle: String,
+    pattern_id: String,
+    fastener_diameter: f64,
+    hole_mmc_diameter: Option<f64>,
+) -> Result<FastenerFloat, String> {
+    state.with_model(&handle, |model| {
+        let pattern = model_patterns(model).into_iter()
+            .find(|p| p.id == pattern_id)
+            .ok_or_else(|| format!("Unknown hole pattern: {}", pattern_id))?;
+        fastener_float(&pattern.id, hole_mmc_diameter.unwrap_or(pattern.hole_diameter), fastener_diameter)
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly_parser::ParsedFace;
+    use crate::linalg::distance;
+    use crate::test_fixtures::hole;
+
+    #[test]
+    fn test_bolt_circle_and_grid() {
+        // Six M6 clearance holes on a 50 mm circle around (100, 100), and a 2 x 3 grid of 4 mm holes
+        let mut faces: Vec<ParsedFace> = (0..6)
+            .map(|k| {
+                let a = (30.0 + 60.0 * k as f64).to_radians();
+                hole(k, [100.0 + 25.0 * a.cos(), 100.0 + 25.0 * a.sin(), 0.0], 3.2)
+            })
+            .collect();
+        for (k, (x, y)) in [(0.0, 0.0), (15.0, 0.0), (30.0, 0.0), (0.0, 10.0), (15.0, 10.0), (30.0, 10.0)].into_iter().enumerate() {
+            faces.push(hole(10 + k as i64, [200.0 + x, 20.0 + y, 0.0], 2.0));
+        }
+        let part = ParsedPart {
+            id: "flange".to_string(),
+            name: "flange".to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: None,
+            faces,
+            product_definition_id: None,
+        };
+
+        let patterns = recognize_part_patterns(&part);
+        assert_eq!(patterns.len(), 2);
+        let circle = &patterns[0];
+        assert_eq!((circle.kind.as_str(), circle.count, circle.full_circle), ("bolt_circle", 6, Some(true)));
+        assert!((circle.bolt_circle_diameter.unwrap() - 50.0).abs() < 1e-6);
+        assert!((circle.angular_spacing_deg.unwrap() - 60.0).abs() < 1e-6);
+        assert!(distance(&circle.center, &[100.0, 100.0, 0.0]) < 1e-6);
+
+        let grid = &patterns[1];
+        assert_eq!(grid.kind, "grid");
+        assert_eq!(grid.counts, Some([2, 3]));
+        let pitch = grid.pitch.unwrap();
+        assert!((pitch[0] - 10.0).abs() < 1e-9 && (pitch[1] - 15.0).abs() < 1e-9);
+        assert!(distance(&grid.center, &[215.0, 25.0, 0.0]) < 1e-9);
+
+        // 6.4 mm holes with M6 fasteners: 0.4 mm floating, 0.2 mm fixed
+        let float = fastener_float(&circle.id, circle.hole_diameter, 6.0).unwrap();
+        assert!((float.floating - 0.4).abs() < 1e-9 && (float.fixed - 0.2).abs() < 1e-9);
+        assert!(fastener_float(&circle.id, 6.0, 6.4).is_err());
+    }
+}
@@ -0,0 +1,202 @@
+// Hole tables (label, position, size) relative to a chosen datum, with CSV export
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::ParsedPart;
+use crate::features::{part_extent_along, recognize_part_holes};
+use crate::interface_detection::transform_direction;
+use crate::linalg::{cross, dot, normalize, sub, Vec3};
+use crate::model_store::ModelStore;
+
+/// Origin and in-plane axes the table coordinates are measured in (world frame)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HoleTableDatum {
+    pub origin: Vec3,
+    pub x_axis: Vec3,
+    pub y_axis: Vec3,
+}
+
+impl Default for HoleTableDatum {
+    fn default() -> Self {
+        HoleTableDatum {
+            origin: [0.0, 0.0, 0.0],
+            x_axis: [1.0, 0.0, 0.0],
+            y_axis: [0.0, 1.0, 0.0],
+        }
+    }
+}
+
+/// One hole in the table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoleTableRow {
+    pub label: String,           // Letter per size, number per position: A1, A2, B1
+    pub hole_id: String,
+    pub part_id: String,
+    pub x: f64,
+    pub y: f64,
+    pub diameter: f64,
+    pub depth: Option<f64>,      // Part thickness along the axis; holes are assumed through
+    pub hole_type: String,       // "through" along the datum normal, "side" otherwise
+}
+
+/// Hole table for a model or one part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoleTable {
+    pub datum: HoleTableDatum,
+    pub rows: Vec<HoleTableRow>,
+    pub csv: Option<String>,     // CSV text when no output path was given
+    pub output_path: Option<String>,
+}
+
+/// Spreadsheet-style column letters: A..Z, AA, AB, ...
+fn size_letter(mut index: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push((b'A' + (index % 26) as u8) as char);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    letters.iter().rev().collect()
+}
+
+/// Rows for the given parts, sorted by size then position (top row first, left to right)
+pub fn build_hole_table(parts: &[&ParsedPart], datum: &HoleTableDatum) -> Vec<HoleTableRow> {
+    let x_axis = normalize(&datum.x_axis);
+    let normal = normalize(&cross(&x_axis, &datum.y_axis));
+    let y_axis = cross(&normal, &x_axis);
+
+    let mut rows: Vec<HoleTableRow> = parts.iter()
+        .flat_map(|part| {
+            // Hole axes are in world space; the thickness estimate needs the local direction
+            let inverse_rotation = [
+                part.transform[0], part.transform[4], part.transform[8], 0.0,
+                part.transform[1], part.transform[5], part.transform[9], 0.0,
+                part.transform[2], part.transform[6], part.transform[10], 0.0,
+                0.0, 0.0, 0.0, 1.0,
+            ];
+            recognize_part_holes(part).into_iter().map(move |hole| {
+                let offset = sub(&hole.center, &datum.origin);
+                let local_axis = transform_direction(&hole.axis, &inverse_rotation);
+                let along_normal = dot(&hole.axis, &normal).abs() > 1.0 - 1e-6;
+                HoleTableRow {
+                    label: String::new(),
+                    hole_id: hole.id,
+                    part_id: hole.part_id,
+                    x: dot(&offset, &x_axis),
+                    y: dot(&offset, &y_axis),
+                    diameter: hole.diameter,
+                    depth: part_extent_along(part, &local_axis),
+                    hole_type: if along_normal { "through" } else { "side" }.to_string(),
+                }
+            })
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        a.diameter.total_cmp(&b.diameter)
+            .then(b.y.total_cmp(&a.y))
+            .then(a.x.total_cmp(&b.x))
+    });
+
+    let mut size_index = 0;
+    let mut number = 0;
+    for i in 0..rows.len() {
+        if i > 0 && (rows[i].diameter - rows[i - 1].diameter).abs() > 1e-6 {
+            size_index += 1;
+            number = 0;
+        }
+        number += 1;
+        rows[i].label = format!("{}{}", size_letter(size_index), number);
+    }
+    rows
+}
+
+/// CSV with a header row; lengths to three decimals
+pub fn hole_table_csv(rows: &[HoleTableRow]) -> String {
+    let mut csv = String::from("Label,X,Y,Diameter,Depth,Type,Part\n");
+    for row in rows {
+        let depth = row.depth.map(|d| format!("{:.3}", d)).unwrap_or_default();
+        let part = if row.part_id.contains([',', '"']) {
+            format!("\"{}\"", row.part_id.replace('"', "\"\""))
+        } else {
+            row.part_id.clone()
+        };
+        csv.push_str(&format!(
+            "{},{:.3},{:.3},{:.3},{},{},{}\n",
+            row.label, row.x, row.y, row.diameter, depth, row.hole_type, part
+        ));
+    }
+    csv
+}
+
+/// Hole table for a loaded model (or one part), written as CSV to a file or returned as text
+#[tauri::command]
+pub fn generate_hole_table(
+    state: State<'_, ModelStore>,
+    handle: String,
+    part_id: Option<String>,
+    datum: Option<HoleTableDatum>,
+    output_path: Option<String>,
+) -> Result<HoleTable, String> {
+    let datum = datum.unwrap_or_default();
+    let rows = state.with_model(&handle, |model| {
+        let parts: Vec<&ParsedPart> = model.assembly.parts.iter()
+            .filter(|p| part_id.as_ref().map(|id| &p.id == id).unwrap_or(true))
+            .collect();
+        if parts.is_empty() {
+            return Err(format!("Unknown part: {}", part_id.clone().unwrap_or_default()));
+        }
+        Ok(build_hole_table(&parts, &datum))
+    })??;
+
+    let csv = hole_table_csv(&rows);
+    let csv = match &output_path {
+        Some(path) => {
+            std::fs::write(path, &csv).map_err(|e| format!("Failed to write hole table: {}", e))?;
+            tracing::info!(handle = %handle, path = %path, holes = rows.len(), "hole table exported");
+            None
+        }
+        None => Some(csv),
+    };
+
+    Ok(HoleTable { datum, rows, csv, output_path })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly_parser::PartBoundingBox;
+    use crate::test_fixtures::hole;
+
+    #[test]
+    fn test_labels_by_size_and_coordinates_from_datum() {
+        let part = ParsedPart {
+            id: "plate".to_string(),
+            name: "plate".to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: Some(PartBoundingBox { min: [0.0; 3], max: [100.0, 50.0, 8.0], dimensions: [100.0, 50.0, 8.0] }),
+            faces: vec![
+                hole(0, [20.0, 10.0, 0.0], 3.0),
+                hole(1, [80.0, 40.0, 0.0], 2.5),
+                hole(2, [20.0, 40.0, 0.0], 3.0),
+            ],
+            product_definition_id: None,
+        };
+        let datum = HoleTableDatum { origin: [10.0, 10.0, 0.0], ..Default::default() };
+
+        let rows = build_hole_table(&[&part], &datum);
+        let summary: Vec<(&str, f64, f64)> = rows.iter().map(|r| (r.label.as_str(), r.x, r.y)).collect();
+        assert_eq!(summary, vec![("A1", 70.0, 30.0), ("B1", 10.0, 30.0), ("B2", 10.0, 0.0)]);
+        assert_eq!(rows[0].depth, Some(8.0));
+        assert_eq!(rows[0].hole_type, "through");
+
+        let csv = hole_table_csv(&rows);
+        assert_eq!(csv.lines().nth(1), Some("A1,70.000,30.000,5.000,8.000,through,plate"));
+        assert_eq!(size_letter(27), "AB");
+    }
+}
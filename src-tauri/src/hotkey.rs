@@ -0,0 +1,185 @@
+// Global hotkey for screen capture
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{App, AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::storage::{load_json, save_json};
+use crate::{CaptureRegion, CapturedImage, CAPTURED_IMAGE_EVENT};
+
+/// Default capture shortcut
+pub const DEFAULT_CAPTURE_HOTKEY: &str = "CmdOrCtrl+Shift+O";
+
+/// What the capture hotkey grabs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyCaptureMode {
+    Screen,
+    Region,
+}
+
+/// Persisted hotkey configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotkeySettings {
+    pub enabled: bool,
+    pub accelerator: String,
+    pub mode: HotkeyCaptureMode,
+    pub region: Option<CaptureRegion>,   // Required for region mode
+}
+
+impl Default for HotkeySettings {
+    fn default() -> Self {
+        HotkeySettings {
+            enabled: true,
+            accelerator: DEFAULT_CAPTURE_HOTKEY.to_string(),
+            mode: HotkeyCaptureMode::Screen,
+            region: None,
+        }
+    }
+}
+
+/// Managed hotkey state
+pub struct HotkeyState {
+    path: PathBuf,
+    settings: Mutex<HotkeySettings>,
+}
+
+/// Load settings and register the capture shortcut
+pub fn setup_hotkey(app: &App, path: PathBuf) -> Result<(), String> {
+    let settings: HotkeySettings = load_json(&path);
+
+    if settings.enabled {
+        // A shortcut taken by another app should not prevent startup
+        if let Err(e) = register_shortcut(app.handle(), &settings.accelerator) {
+            tracing::warn!(accelerator = %settings.accelerator, error = %e, "capture hotkey unavailable");
+        }
+    }
+
+    app.manage(HotkeyState {
+        path,
+        settings: Mutex::new(settings),
+    });
+    Ok(())
+}
+
+/// Validate an accelerator string such as "CmdOrCtrl+Shift+O"
+fn validate_accelerator(accelerator: &str) -> Result<(), String> {
+    Shortcut::from_str(accelerator)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))
+}
+
+/// Register the capture handler for an accelerator
+fn register_shortcut(app: &AppHandle, accelerator: &str) -> Result<(), String> {
+    validate_accelerator(accelerator)?;
+    app.global_shortcut()
+        .on_shortcut(accelerator, |app, _shortcut, event| {
+            if event.state() == ShortcutState::Pressed {
+                let app = app.clone();
+                tauri::async_runtime::spawn_blocking(move || trigger_capture(&app));
+            }
+        })
+        .map_err(|e| format!("Failed to register shortcut '{}': {}", accelerator, e))?;
+
+    tracing::info!(accelerator, "capture hotkey registered");
+    Ok(())
+}
+
+fn unregister_shortcut(app: &AppHandle, accelerator: &str) {
+    if let Err(e) = app.global_shortcut().unregister(accelerator) {
+        tracing::debug!(accelerator, error = %e, "capture hotkey was not registered");
+    }
+}
+
+/// Capture per the current settings and route the image to the conversation
+fn trigger_capture(app: &AppHandle) {
+    let Some(state) = app.try_state::<HotkeyState>() else { return };
+    let settings = match state.settings.lock() {
+        Ok(settings) => settings.clone(),
+        Err(_) => return,
+    };
+
+    let captured = match (settings.mode, settings.region) {
+        (HotkeyCaptureMode::Region, Some(region)) => crate::capture_region(region).map(|image| ("region", image)),
+        (HotkeyCaptureMode::Region, None) => Err("Region capture selected but no region is configured".to_string()),
+        (HotkeyCaptureMode::Screen, _) => crate::capture_screen().map(|image| ("screen", image)),
+    };
+
+    match captured {
+        Ok((source, image_base64)) => {
+            // The image goes to the frontend without stealing focus from the CAD window
+            let _ = app.emit(CAPTURED_IMAGE_EVENT, CapturedImage {
+                source: source.to_string(),
+                trigger: "hotkey".to_string(),
+                image_base64,
+            });
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "hotkey capture failed");
+            let _ = app.emit("hotkey-error", e);
+        }
+    }
+}
+
+/// Get the capture hotkey settings
+#[tauri::command]
+pub fn get_capture_hotkey(state: State<'_, HotkeyState>) -> Result<HotkeySettings, String> {
+    let settings = state.settings.lock().map_err(|_| "Hotkey state poisoned".to_string())?;
+    Ok(settings.clone())
+}
+
+/// Change the capture hotkey; the previous shortcut is restored if the new one cannot be registered
+#[tauri::command]
+pub fn set_capture_hotkey(
+    app: AppHandle,
+    state: State<'_, HotkeyState>,
+    settings: HotkeySettings,
+) -> Result<HotkeySettings, String> {
+    validate_accelerator(&settings.accelerator)?;
+    if settings.mode == HotkeyCaptureMode::Region && settings.region.is_none() {
+        return Err("Region mode requires a capture region".to_string());
+    }
+
+    let mut current = state.settings.lock().map_err(|_| "Hotkey state poisoned".to_string())?;
+
+    if current.enabled {
+        unregister_shortcut(&app, &current.accelerator);
+    }
+
+    if settings.enabled {
+        if let Err(e) = register_shortcut(&app, &settings.accelerator) {
+            if current.enabled {
+                let _ = register_shortcut(&app, &current.accelerator);
+            }
+            return Err(e);
+        }
+    }
+
+    *current = settings;
+    save_json(&state.path, &*current)?;
+    Ok(current.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_settings() {
+        let settings = HotkeySettings::default();
+        assert!(settings.enabled);
+        assert_eq!(settings.accelerator, DEFAULT_CAPTURE_HOTKEY);
+        assert_eq!(settings.mode, HotkeyCaptureMode::Screen);
+    }
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let json = r#"{"enabled":true,"accelerator":"Alt+F9","mode":"region","region":{"x":10,"y":20,"width":300,"height":200}}"#;
+        let settings: HotkeySettings = serde_json::from_str(json).unwrap();
+        assert_eq!(settings.mode, HotkeyCaptureMode::Region);
+        assert_eq!(settings.region.unwrap().width, 300);
+    }
+}
@@ -1,10 +1,15 @@
 // Interface detection for assembly tolerance analysis
 
 use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
 use crate::assembly_parser::{ParsedPart, ParsedFace};
+use crate::fit_statistics::FitAnalysis;
+use crate::jobs::run_job;
+use crate::joints::joint_type_from_names;
+use crate::validation::{validate, FieldErrors, Validate};
 
 /// Result of interface detection
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InterfaceDetectionResult {
     pub success: bool,
     pub error: Option<String>,
@@ -21,11 +26,22 @@ pub struct DetectedInterface {
     pub part_a_face_id: i64,
     pub part_b_id: String,
     pub part_b_face_id: i64,
-    pub interface_type: String,  // "face_to_face", "pin_in_hole", "shaft_in_bore", "unknown"
+    pub interface_type: String,  // "face_to_face", "pin_in_hole", "shaft_in_bore", "weld", "bond", "unknown"
     pub proximity: f64,          // Distance between faces (mm)
     pub normal_alignment: f64,   // Cosine of angle between normals (0-1)
     pub contact_area: f64,       // Estimated contact area (mm^2)
     pub contact_point: [f64; 3], // Center of contact region
+    #[serde(default)]
+    pub fit: Option<FitAnalysis>, // Statistical fit check, set once pin and hole tolerances are given
+}
+
+/// Two parts whose world-space bounding boxes overlap (interference candidate)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartInterference {
+    pub part_a_id: String,
+    pub part_b_id: String,
+    pub overlap: [f64; 3],      // Overlap extent per axis (mm)
+    pub overlap_volume: f64,    // mm^3
 }
 
 /// Parameters for interface detection
@@ -46,9 +62,32 @@ impl Default for DetectionParams {
     }
 }
 
+impl Validate for DetectionParams {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.at_least("proximity_threshold", self.proximity_threshold, 0.0);
+        errors.within("normal_threshold", self.normal_threshold, 0.0, 1.0);
+        errors.at_least("min_contact_area", self.min_contact_area, 0.0);
+    }
+}
+
 /// Detect mating interfaces between parts
 #[tauri::command]
-pub fn detect_mating_interfaces(
+pub async fn detect_mating_interfaces(
+    app: AppHandle,
+    parts: Vec<ParsedPart>,
+    proximity_threshold: f64,
+    normal_threshold: f64,
+    job_id: Option<String>,
+) -> Result<InterfaceDetectionResult, String> {
+    validate(&DetectionParams { proximity_threshold, normal_threshold, ..DetectionParams::default() })?;
+    run_job(app, "detect_mating_interfaces", job_id, None, move |_| {
+        Ok(find_mating_interfaces(parts, proximity_threshold, normal_threshold))
+    })
+    .await
+}
+
+/// Pairwise interface search shared by the command, loaded models and reports
+pub fn find_mating_interfaces(
     parts: Vec<ParsedPart>,
     proximity_threshold: f64,
     normal_threshold: f64,
@@ -59,6 +98,8 @@ pub fn detect_mating_interfaces(
         min_contact_area: 1.0,
     };
 
+    tracing::info!(parts = parts.len(), proximity_threshold, normal_threshold, "detecting mating interfaces");
+
     let mut interfaces: Vec<DetectedInterface> = Vec::new();
     let mut interface_count_per_part: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
     let mut interface_id = 0;
@@ -93,6 +134,8 @@ pub fn detect_mating_interfaces(
         .map(|(id, _)| id.clone())
         .collect();
 
+    tracing::info!(interfaces = interfaces.len(), junctions = junction_parts.len(), "interface detection finished");
+
     InterfaceDetectionResult {
         success: true,
         error: None,
@@ -102,6 +145,58 @@ pub fn detect_mating_interfaces(
     }
 }
 
+/// Find parts whose bounding boxes overlap in world coordinates
+pub fn detect_bbox_interferences(parts: &[ParsedPart]) -> Vec<PartInterference> {
+    let boxes: Vec<Option<([f64; 3], [f64; 3])>> = parts.iter().map(world_bounding_box).collect();
+    let mut interferences = Vec::new();
+
+    for i in 0..parts.len() {
+        for j in (i + 1)..parts.len() {
+            let (Some((min_a, max_a)), Some((min_b, max_b))) = (boxes[i], boxes[j]) else { continue };
+
+            let overlap = [
+                max_a[0].min(max_b[0]) - min_a[0].max(min_b[0]),
+                max_a[1].min(max_b[1]) - min_a[1].max(min_b[1]),
+                max_a[2].min(max_b[2]) - min_a[2].max(min_b[2]),
+            ];
+
+            // Touching boxes (zero overlap on an axis) are contacts, not interference
+            if overlap.iter().all(|&o| o > 1e-6) {
+                interferences.push(PartInterference {
+                    part_a_id: parts[i].id.clone(),
+                    part_b_id: parts[j].id.clone(),
+                    overlap,
+                    overlap_volume: overlap[0] * overlap[1] * overlap[2],
+                });
+            }
+        }
+    }
+
+    interferences
+}
+
+/// Axis-aligned world bounds of a part's transformed bounding box
+fn world_bounding_box(part: &ParsedPart) -> Option<([f64; 3], [f64; 3])> {
+    let bbox = part.bounding_box.as_ref()?;
+    let mut min = [f64::MAX; 3];
+    let mut max = [f64::MIN; 3];
+
+    for corner in 0..8 {
+        let local = [
+            if corner & 1 == 0 { bbox.min[0] } else { bbox.max[0] },
+            if corner & 2 == 0 { bbox.min[1] } else { bbox.max[1] },
+            if corner & 4 == 0 { bbox.min[2] } else { bbox.max[2] },
+        ];
+        let world = transform_point(&local, &part.transform);
+        for axis in 0..3 {
+            min[axis] = min[axis].min(world[axis]);
+            max[axis] = max[axis].max(world[axis]);
+        }
+    }
+
+    Some((min, max))
+}
+
 /// Find interfaces between two parts
 fn find_interfaces_between_parts(
     part_a: &ParsedPart,
@@ -110,6 +205,8 @@ fn find_interfaces_between_parts(
     interface_id: &mut usize,
 ) -> Vec<DetectedInterface> {
     let mut interfaces = Vec::new();
+    // A weld bead or adhesive body joins whatever it touches
+    let joint_type = joint_type_from_names(&part_a.name, &part_b.name);
 
     // Transform faces to world coordinates
     let faces_a: Vec<TransformedFace> = part_a.faces.iter()
@@ -145,6 +242,10 @@ fn find_interfaces_between_parts(
             if interface_type == "none" {
                 continue;
             }
+            let interface_type = match joint_type {
+                Some(joint) if interface_type != "pin_in_hole" => joint.to_string(),
+                _ => interface_type,
+            };
 
             // Calculate contact point (midpoint between centers)
             let contact_point = [
@@ -173,6 +274,7 @@ fn find_interfaces_between_parts(
                 normal_alignment: alignment.abs(),
                 contact_area,
                 contact_point,
+                fit: None,
             });
         }
     }
@@ -186,6 +288,7 @@ struct TransformedFace {
     normal: [f64; 3],
     face_type: String,
     radius: Option<f64>,
+    area: f64,   // Rigid transforms keep it unchanged
 }
 
 /// Transform face to world coordinates
@@ -195,11 +298,12 @@ fn transform_face(face: &ParsedFace, transform: &[f64; 16]) -> TransformedFace {
         normal: transform_direction(&face.normal, transform),
         face_type: face.face_type.clone(),
         radius: face.radius,
+        area: face.area,
     }
 }
 
 /// Transform a point by 4x4 matrix
-fn transform_point(point: &[f64; 3], matrix: &[f64; 16]) -> [f64; 3] {
+pub(crate) fn transform_point(point: &[f64; 3], matrix: &[f64; 16]) -> [f64; 3] {
     // Matrix is column-major: [x_col, y_col, z_col, translation]
     [
         matrix[0] * point[0] + matrix[4] * point[1] + matrix[8] * point[2] + matrix[12],
@@ -209,7 +313,7 @@ fn transform_point(point: &[f64; 3], matrix: &[f64; 16]) -> [f64; 3] {
 }
 
 /// Transform a direction by 4x4 matrix (no translation)
-fn transform_direction(direction: &[f64; 3], matrix: &[f64; 16]) -> [f64; 3] {
+pub(crate) fn transform_direction(direction: &[f64; 3], matrix: &[f64; 16]) -> [f64; 3] {
     let transformed = [
         matrix[0] * direction[0] + matrix[4] * direction[1] + matrix[8] * direction[2],
         matrix[1] * direction[0] + matrix[5] * direction[1] + matrix[9] * direction[2],
@@ -282,12 +386,19 @@ fn classify_interface(
 fn estimate_contact_area(face_a: &TransformedFace, face_b: &TransformedFace, interface_type: &str) -> f64 {
     match interface_type {
         "face_to_face" => {
-            // Assume square contact proportional to proximity
-            10.0  // Default 10 mm^2 for face contact
+            // Contact can be no larger than the smaller face
+            let area = face_a.area.min(face_b.area);
+            if area > 0.0 { area } else { 10.0 }  // Default 10 mm^2 when a boundary was not resolved
         }
         "pin_in_hole" | "shaft_in_bore" => {
-            // Circular contact based on radius
-            if let Some(r) = face_a.radius.or(face_b.radius) {
+            // Lateral area of the shorter cylinder, else the circular cross-section
+            let lateral = [face_a, face_b].iter()
+                .filter(|f| f.face_type == "cylindrical" && f.area > 0.0)
+                .map(|f| f.area)
+                .fold(f64::INFINITY, f64::min);
+            if lateral.is_finite() {
+                lateral
+            } else if let Some(r) = face_a.radius.or(face_b.radius) {
                 std::f64::consts::PI * r * r
             } else {
                 5.0  // Default
@@ -320,4 +431,30 @@ mod tests {
         let result = classify_interface("planar", "planar", -0.99, None, None);
         assert_eq!(result, "face_to_face");
     }
+
+    #[test]
+    fn test_bbox_interference_ignores_touching_parts() {
+        let part = |id: &str, min: [f64; 3], max: [f64; 3]| ParsedPart {
+            id: id.to_string(),
+            name: id.to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: Some(crate::assembly_parser::PartBoundingBox {
+                min,
+                max,
+                dimensions: [max[0] - min[0], max[1] - min[1], max[2] - min[2]],
+            }),
+            faces: vec![],
+            product_definition_id: None,
+        };
+        let parts = vec![
+            part("a", [0.0, 0.0, 0.0], [10.0, 10.0, 10.0]),
+            part("b", [10.0, 0.0, 0.0], [20.0, 10.0, 10.0]),   // touches a
+            part("c", [8.0, 8.0, 8.0], [12.0, 12.0, 12.0]),    // overlaps a and b
+        ];
+
+        let found = detect_bbox_interferences(&parts);
+        assert_eq!(found.len(), 2);
+        assert!((found[0].overlap_volume - 8.0).abs() < 1e-9);
+    }
 }
@@ -0,0 +1,285 @@
+// ISO 286 limits and fits from embedded tables (nominal sizes up to 500 mm)
+
+use serde::{Deserialize, Serialize};
+
+/// Upper bounds of the ISO 286-1 main size ranges (mm); a range is "over previous, up to and including"
+const SIZE_RANGES: [f64; 13] = [3.0, 6.0, 10.0, 18.0, 30.0, 50.0, 80.0, 120.0, 180.0, 250.0, 315.0, 400.0, 500.0];
+
+/// Standard tolerance grades IT1..IT18 per main size range (µm)
+const IT_TABLE: [[f64; 18]; 13] = [
+    [0.8, 1.2, 2.0, 3.0, 4.0, 6.0, 10.0, 14.0, 25.0, 40.0, 60.0, 100.0, 140.0, 250.0, 400.0, 600.0, 1000.0, 1400.0],
+    [1.0, 1.5, 2.5, 4.0, 5.0, 8.0, 12.0, 18.0, 30.0, 48.0, 75.0, 120.0, 180.0, 300.0, 480.0, 750.0, 1200.0, 1800.0],
+    [1.0, 1.5, 2.5, 4.0, 6.0, 9.0, 15.0, 22.0, 36.0, 58.0, 90.0, 150.0, 220.0, 360.0, 580.0, 900.0, 1500.0, 2200.0],
+    [1.2, 2.0, 3.0, 5.0, 8.0, 11.0, 18.0, 27.0, 43.0, 70.0, 110.0, 180.0, 270.0, 430.0, 700.0, 1100.0, 1800.0, 2700.0],
+    [1.5, 2.5, 4.0, 6.0, 9.0, 13.0, 21.0, 33.0, 52.0, 84.0, 130.0, 210.0, 330.0, 520.0, 840.0, 1300.0, 2100.0, 3300.0],
+    [1.5, 2.5, 4.0, 7.0, 11.0, 16.0, 25.0, 39.0, 62.0, 100.0, 160.0, 250.0, 390.0, 620.0, 1000.0, 1600.0, 2500.0, 3900.0],
+    [2.0, 3.0, 5.0, 8.0, 13.0, 19.0, 30.0, 46.0, 74.0, 120.0, 190.0, 300.0, 460.0, 740.0, 1200.0, 1900.0, 3000.0, 4600.0],
+    [2.5, 4.0, 6.0, 10.0, 15.0, 22.0, 35.0, 54.0, 87.0, 140.0, 220.0, 350.0, 540.0, 870.0, 1400.0, 2200.0, 3500.0, 5400.0],
+    [3.5, 5.0, 8.0, 12.0, 18.0, 25.0, 40.0, 63.0, 100.0, 160.0, 250.0, 400.0, 630.0, 1000.0, 1600.0, 2500.0, 4000.0, 6300.0],
+    [4.5, 7.0, 10.0, 14.0, 20.0, 29.0, 46.0, 72.0, 115.0, 185.0, 290.0, 460.0, 720.0, 1150.0, 1850.0, 2900.0, 4600.0, 7200.0],
+    [6.0, 8.0, 12.0, 16.0, 23.0, 32.0, 52.0, 81.0, 130.0, 210.0, 320.0, 520.0, 810.0, 1300.0, 2100.0, 3200.0, 5200.0, 8100.0],
+    [7.0, 9.0, 13.0, 18.0, 25.0, 36.0, 57.0, 89.0, 140.0, 230.0, 360.0, 570.0, 890.0, 1400.0, 2300.0, 3600.0, 5700.0, 8900.0],
+    [8.0, 10.0, 15.0, 20.0, 27.0, 40.0, 63.0, 97.0, 155.0, 250.0, 400.0, 630.0, 970.0, 1550.0, 2500.0, 4000.0, 6300.0, 9700.0],
+];
+
+// Shaft fundamental deviations (µm) as (size range upper bound, value).
+// c..g give the upper deviation es, k..u the lower deviation ei.
+const SHAFT_C: &[(f64, f64)] = &[
+    (3.0, -60.0), (6.0, -70.0), (10.0, -80.0), (18.0, -95.0), (30.0, -110.0), (40.0, -120.0), (50.0, -130.0),
+    (65.0, -140.0), (80.0, -150.0), (100.0, -170.0), (120.0, -180.0), (140.0, -200.0), (160.0, -210.0),
+    (180.0, -230.0), (200.0, -240.0), (225.0, -260.0), (250.0, -280.0), (280.0, -300.0), (315.0, -330.0),
+    (355.0, -360.0), (400.0, -400.0), (450.0, -440.0), (500.0, -480.0),
+];
+const SHAFT_D: [f64; 13] = [-20.0, -30.0, -40.0, -50.0, -65.0, -80.0, -100.0, -120.0, -145.0, -170.0, -190.0, -210.0, -230.0];
+const SHAFT_E: [f64; 13] = [-14.0, -20.0, -25.0, -32.0, -40.0, -50.0, -60.0, -72.0, -85.0, -100.0, -110.0, -125.0, -135.0];
+const SHAFT_F: [f64; 13] = [-6.0, -10.0, -13.0, -16.0, -20.0, -25.0, -30.0, -36.0, -43.0, -50.0, -56.0, -62.0, -68.0];
+const SHAFT_G: [f64; 13] = [-2.0, -4.0, -5.0, -6.0, -7.0, -9.0, -10.0, -12.0, -14.0, -15.0, -17.0, -18.0, -20.0];
+const SHAFT_K: [f64; 13] = [0.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 3.0, 3.0, 4.0, 4.0, 4.0, 5.0];  // IT4..IT7 only
+const SHAFT_M: [f64; 13] = [2.0, 4.0, 6.0, 7.0, 8.0, 9.0, 11.0, 13.0, 15.0, 17.0, 20.0, 21.0, 23.0];
+const SHAFT_N: [f64; 13] = [4.0, 8.0, 10.0, 12.0, 15.0, 17.0, 20.0, 23.0, 27.0, 31.0, 34.0, 37.0, 40.0];
+const SHAFT_P: [f64; 13] = [6.0, 12.0, 15.0, 18.0, 22.0, 26.0, 32.0, 37.0, 43.0, 50.0, 56.0, 62.0, 68.0];
+const SHAFT_R: &[(f64, f64)] = &[
+    (3.0, 10.0), (6.0, 15.0), (10.0, 19.0), (18.0, 23.0), (30.0, 28.0), (50.0, 34.0), (65.0, 41.0), (80.0, 43.0),
+    (100.0, 51.0), (120.0, 54.0), (140.0, 63.0), (160.0, 65.0), (180.0, 68.0), (200.0, 77.0), (225.0, 80.0),
+    (250.0, 84.0), (280.0, 94.0), (315.0, 98.0), (355.0, 108.0), (400.0, 114.0), (450.0, 126.0), (500.0, 132.0),
+];
+const SHAFT_S: &[(f64, f64)] = &[
+    (3.0, 14.0), (6.0, 19.0), (10.0, 23.0), (18.0, 28.0), (30.0, 35.0), (50.0, 43.0), (65.0, 53.0), (80.0, 59.0),
+    (100.0, 71.0), (120.0, 79.0), (140.0, 92.0), (160.0, 100.0), (180.0, 108.0), (200.0, 122.0), (225.0, 130.0),
+    (250.0, 140.0), (280.0, 158.0), (315.0, 170.0), (355.0, 190.0), (400.0, 208.0), (450.0, 232.0), (500.0, 252.0),
+];
+const SHAFT_U: &[(f64, f64)] = &[
+    (3.0, 18.0), (6.0, 23.0), (10.0, 28.0), (18.0, 33.0), (24.0, 41.0), (30.0, 48.0), (40.0, 60.0), (50.0, 70.0),
+    (65.0, 87.0), (80.0, 102.0), (100.0, 124.0), (120.0, 144.0), (140.0, 170.0), (160.0, 190.0), (180.0, 210.0),
+    (200.0, 236.0), (225.0, 258.0), (250.0, 284.0), (280.0, 315.0), (315.0, 350.0), (355.0, 390.0), (400.0, 435.0),
+    (450.0, 490.0), (500.0, 540.0),
+];
+
+/// Letters with embedded deviations; holes use the upper-case form
+const SUPPORTED_LETTERS: &[&str] = &["c", "d", "e", "f", "g", "h", "js", "k", "m", "n", "p", "r", "s", "u"];
+
+/// One tolerance zone, e.g. H7 or g6, at a nominal size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToleranceZone {
+    pub code: String,
+    pub upper_deviation: f64,  // mm
+    pub lower_deviation: f64,  // mm
+    pub max_size: f64,         // mm
+    pub min_size: f64,         // mm
+    pub tolerance: f64,        // IT grade width (mm)
+}
+
+/// Limits of a hole/shaft fit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FitLimits {
+    pub nominal_diameter: f64,
+    pub fit_code: String,
+    pub hole: ToleranceZone,
+    pub shaft: ToleranceZone,
+    pub max_clearance: f64,  // Largest hole minus smallest shaft; negative is interference (mm)
+    pub min_clearance: f64,  // Smallest hole minus largest shaft; negative is interference (mm)
+    pub fit_type: String,    // "clearance", "transition" or "interference"
+}
+
+fn range_index(size: f64) -> usize {
+    SIZE_RANGES.iter().position(|max| size <= *max).unwrap_or(SIZE_RANGES.len() - 1)
+}
+
+fn lookup(table: &[(f64, f64)], size: f64) -> f64 {
+    table.iter().find(|(max, _)| size <= *max).unwrap_or(&table[table.len() - 1]).1
+}
+
+/// Standard tolerance ITn at a size (µm)
+fn it_grade(size: f64, grade: u8) -> f64 {
+    IT_TABLE[range_index(size)][grade as usize - 1]
+}
+
+/// Shaft (es, ei) in µm
+fn shaft_deviations(letter: &str, size: f64, grade: u8) -> (f64, f64) {
+    let r = range_index(size);
+    let it = it_grade(size, grade);
+    let upper = |es: f64| (es, es - it);
+    let lower = |ei: f64| (ei + it, ei);
+    match letter {
+        "c" => upper(lookup(SHAFT_C, size)),
+        "d" => upper(SHAFT_D[r]),
+        "e" => upper(SHAFT_E[r]),
+        "f" => upper(SHAFT_F[r]),
+        "g" => upper(SHAFT_G[r]),
+        "h" => upper(0.0),
+        "js" => (it / 2.0, -it / 2.0),
+        "k" => lower(if (4..=7).contains(&grade) { SHAFT_K[r] } else { 0.0 }),
+        "m" => lower(SHAFT_M[r]),
+        "n" => lower(SHAFT_N[r]),
+        "p" => lower(SHAFT_P[r]),
+        "r" => lower(lookup(SHAFT_R, size)),
+        "s" => lower(lookup(SHAFT_S, size)),
+        "u" => lower(lookup(SHAFT_U, size)),
+        _ => unreachable!("letter checked by parse_zone"),
+    }
+}
+
+/// Hole (ES, EI) in µm, mirrored from the shaft deviation of the same letter
+fn hole_deviations(letter: &str, size: f64, grade: u8) -> (f64, f64) {
+    let it = it_grade(size, grade);
+    let shaft_ei = || shaft_deviations(letter, size, 7).1;
+    // Special rule: fine grades of K..U shift by delta = ITn - IT(n-1) above 3 mm
+    let delta = if size > 3.0 && grade > 1 { it - it_grade(size, grade - 1) } else { 0.0 };
+    let es = match letter {
+        "c" | "d" | "e" | "f" | "g" | "h" => {
+            let ei = -shaft_deviations(letter, size, grade).0;
+            return (ei + it, ei);
+        }
+        "js" => return (it / 2.0, -it / 2.0),
+        "k" if grade <= 8 => -shaft_deviations(letter, size, 5).1 + delta,
+        "k" => 0.0,
+        "m" if grade <= 8 => -shaft_ei() + delta,
+        "m" => -shaft_ei(),
+        "n" if grade <= 8 => -shaft_ei() + delta,
+        "n" if size > 3.0 => 0.0,
+        "n" => -shaft_ei(),
+        _ if grade <= 7 => -shaft_ei() + delta,
+        _ => -shaft_ei(),
+    };
+    (es, es - it)
+}
+
+/// Split a zone code such as "H7" or "js6" into lower-case letter, grade and whether it is a hole
+fn parse_zone(code: &str) -> Result<(String, u8, bool), String> {
+    let split = code.find(|c: char| c.is_ascii_digit()).ok_or_else(|| format!("Missing IT grade in \"{}\"", code))?;
+    let (letters, digits) = code.split_at(split);
+    let is_hole = !letters.is_empty() && letters.chars().all(|c| c.is_ascii_uppercase());
+    let is_shaft = !letters.is_empty() && letters.chars().all(|c| c.is_ascii_lowercase());
+    if !is_hole && !is_shaft {
+        return Err(format!("Invalid deviation letter in \"{}\"; use upper case for holes, lower case for shafts", code));
+    }
+    let letter = letters.to_ascii_lowercase();
+    if !SUPPORTED_LETTERS.contains(&letter.as_str()) {
+        return Err(format!("Deviation \"{}\" is not in the embedded tables (supported: {})", letters, SUPPORTED_LETTERS.join(", ")));
+    }
+    let grade: u8 = digits.parse().map_err(|_| format!("Invalid IT grade in \"{}\"", code))?;
+    if !(1..=18).contains(&grade) {
+        return Err(format!("IT grade in \"{}\" must be 1 to 18", code));
+    }
+    Ok((letter, grade, is_hole))
+}
+
+fn zone(code: &str, nominal: f64, (upper, lower): (f64, f64)) -> ToleranceZone {
+    // Table values are whole or half microns; round away float noise after converting to mm
+    let mm = |um: f64| (um * 10.0).round() / 10_000.0;
+    ToleranceZone {
+        code: code.to_string(),
+        upper_deviation: mm(upper),
+        lower_deviation: mm(lower),
+        max_size: nominal + mm(upper),
+        min_size: nominal + mm(lower),
+        tolerance: mm(upper - lower),
+    }
+}
+
+fn check_nominal(nominal_diameter: f64) -> Result<(), String> {
+    if !nominal_diameter.is_finite() || nominal_diameter <= 0.0 || nominal_diameter > SIZE_RANGES[SIZE_RANGES.len() - 1] {
+        return Err(format!(
+            "Nominal diameter must be above 0 and at most {} mm, got {}",
+            SIZE_RANGES[SIZE_RANGES.len() - 1],
+            nominal_diameter
+        ));
+    }
+    Ok(())
+}
+
+/// Limits of a single zone such as "k6" or "H7", and whether it is a hole zone
+pub fn tolerance_zone(nominal_diameter: f64, code: &str) -> Result<(ToleranceZone, bool), String> {
+    check_nominal(nominal_diameter)?;
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    let (letter, grade, is_hole) = parse_zone(&code)?;
+    let deviations = if is_hole {
+        hole_deviations(&letter, nominal_diameter, grade)
+    } else {
+        shaft_deviations(&letter, nominal_diameter, grade)
+    };
+    Ok((zone(&code, nominal_diameter, deviations), is_hole))
+}
+
+/// Hole and shaft limits with clearance range for a fit code like "H7/g6"
+pub fn fit_limits(nominal_diameter: f64, fit_code: &str) -> Result<FitLimits, String> {
+    check_nominal(nominal_diameter)?;
+    let code: String = fit_code.chars().filter(|c| !c.is_whitespace()).collect();
+    let (hole_code, shaft_code) = code.split_once('/')
+        .ok_or_else(|| format!("Fit code \"{}\" should look like H7/g6", fit_code))?;
+    let (hole_letter, hole_grade, hole_is_hole) = parse_zone(hole_code)?;
+    let (shaft_letter, shaft_grade, shaft_is_hole) = parse_zone(shaft_code)?;
+    if !hole_is_hole || shaft_is_hole {
+        return Err(format!("Fit code \"{}\" should give the hole (upper case) first, then the shaft", fit_code));
+    }
+
+    let hole = zone(hole_code, nominal_diameter, hole_deviations(&hole_letter, nominal_diameter, hole_grade));
+    let shaft = zone(shaft_code, nominal_diameter, shaft_deviations(&shaft_letter, nominal_diameter, shaft_grade));
+    let max_clearance = hole.upper_deviation - shaft.lower_deviation;
+    let min_clearance = hole.lower_deviation - shaft.upper_deviation;
+    let fit_type = if min_clearance >= 0.0 {
+        "clearance"
+    } else if max_clearance <= 0.0 {
+        "interference"
+    } else {
+        "transition"
+    };
+
+    Ok(FitLimits {
+        nominal_diameter,
+        fit_code: format!("{}/{}", hole_code, shaft_code),
+        hole,
+        shaft,
+        max_clearance,
+        min_clearance,
+        fit_type: fit_type.to_string(),
+    })
+}
+
+/// ISO 286 hole/shaft limits and clearance for a nominal diameter and fit code
+#[tauri::command]
+pub fn calculate_fit(nominal_diameter: f64, fit_code: String) -> Result<FitLimits, String> {
+    fit_limits(nominal_diameter, &fit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_clearance_fit_25_h7_g6() {
+        let fit = fit_limits(25.0, "H7/g6").unwrap();
+        assert!(close(fit.hole.upper_deviation, 0.021) && close(fit.hole.lower_deviation, 0.0));
+        assert!(close(fit.shaft.upper_deviation, -0.007) && close(fit.shaft.lower_deviation, -0.020));
+        assert!(close(fit.max_clearance, 0.041));
+        assert!(close(fit.min_clearance, 0.007));
+        assert_eq!(fit.fit_type, "clearance");
+    }
+
+    #[test]
+    fn test_interference_and_hole_special_rule() {
+        let fit = fit_limits(50.0, "H7/s6").unwrap();
+        assert!(close(fit.shaft.lower_deviation, 0.043) && close(fit.shaft.upper_deviation, 0.059));
+        assert!(close(fit.max_clearance, -0.018));
+        assert!(close(fit.min_clearance, -0.059));
+        assert_eq!(fit.fit_type, "interference");
+
+        // K7 and N7 at 25 mm include the delta shift
+        let k7 = fit_limits(25.0, "K7/h6").unwrap();
+        assert!(close(k7.hole.upper_deviation, 0.006) && close(k7.hole.lower_deviation, -0.015));
+        assert_eq!(k7.fit_type, "transition");
+        let n7 = fit_limits(25.0, "N7/h6").unwrap();
+        assert!(close(n7.hole.upper_deviation, -0.007) && close(n7.hole.lower_deviation, -0.028));
+    }
+
+    #[test]
+    fn test_invalid_codes() {
+        assert!(fit_limits(25.0, "g6/H7").unwrap_err().contains("hole (upper case) first"));
+        assert!(fit_limits(25.0, "H7/zc6").unwrap_err().contains("not in the embedded tables"));
+        assert!(fit_limits(600.0, "H7/g6").is_err());
+    }
+}
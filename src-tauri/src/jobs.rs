@@ -0,0 +1,289 @@
+// Typed job events shared by long-running commands, so the frontend has one progress pattern
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::storage::unix_timestamp;
+
+pub const JOB_STARTED_EVENT: &str = "job-started";
+pub const JOB_PROGRESS_EVENT: &str = "job-progress";
+pub const JOB_LOG_EVENT: &str = "job-log";
+pub const JOB_FINISHED_EVENT: &str = "job-finished";
+pub const JOB_FAILED_EVENT: &str = "job-failed";
+
+/// Progress events closer together than this are dropped (the final one always goes out)
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+static NEXT_JOB_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Jobs that have started and not yet been dropped, by id
+static ACTIVE_JOBS: Mutex<BTreeMap<String, (ActiveJob, Instant)>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobLogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStarted {
+    pub job_id: String,
+    pub kind: String,            // Command name, e.g. "parse_step_mesh"
+    pub label: Option<String>,   // What the job is working on, usually a file name
+    pub started_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobProgress {
+    pub job_id: String,
+    pub kind: String,
+    pub done: usize,
+    pub total: Option<usize>,    // None when the amount of work is unknown
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobLog {
+    pub job_id: String,
+    pub kind: String,
+    pub level: JobLogLevel,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobFinished {
+    pub job_id: String,
+    pub kind: String,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobFailed {
+    pub job_id: String,
+    pub kind: String,
+    pub error: String,
+    pub elapsed_ms: u64,
+}
+
+/// A job that is still running, with its last reported progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveJob {
+    pub job_id: String,
+    pub kind: String,
+    pub label: Option<String>,
+    pub started_at: u64,
+    pub elapsed_ms: u64,         // Filled in when listed
+    pub done: usize,
+    pub total: Option<usize>,
+    pub message: Option<String>,
+}
+
+/// A running job; emits its events to every window
+pub struct Job {
+    app: Option<AppHandle>,
+    id: String,
+    kind: &'static str,
+    started: Instant,
+    last_progress: Mutex<Option<Instant>>,
+}
+
+impl Job {
+    /// Start a job and announce it; the caller's id is kept so it can match events to its request,
+    /// unless a running job already has it
+    pub fn start(app: Option<AppHandle>, kind: &'static str, job_id: Option<String>, label: Option<String>) -> Job {
+        let next_id = || NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed);
+        let mut id = job_id.unwrap_or_else(|| format!("job-{}", next_id()));
+        let started_at = unix_timestamp();
+        let now = Instant::now();
+        if let Ok(mut active) = ACTIVE_JOBS.lock() {
+            // A reused client id gets a suffix, so the two jobs' events and listings stay apart
+            if active.contains_key(&id) {
+                id = format!("{}-{}", id, next_id());
+            }
+            active.insert(id.clone(), (ActiveJob {
+                job_id: id.clone(),
+                kind: kind.to_string(),
+                label: label.clone(),
+                started_at,
+                elapsed_ms: 0,
+                done: 0,
+                total: None,
+                message: None,
+            }, now));
+        }
+        let job = Job { app, id, kind, started: now, last_progress: Mutex::new(None) };
+        tracing::debug!(job_id = %job.id, kind, "job started");
+        let started = JobStarted { job_id: job.id.clone(), kind: kind.to_string(), label, started_at };
+        job.emit(JOB_STARTED_EVENT, started);
+        job
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) {
+        if let Some(app) = &self.app {
+            let _ = app.emit(event, payload);
+        }
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    /// Report progress, throttled so tight loops do not flood the frontend
+    pub fn progress(&self, done: usize, total: Option<usize>, message: Option<String>) {
+        let finished = total.map(|t| done >= t).unwrap_or(false);
+        if let Ok(mut last) = self.last_progress.lock() {
+            if !finished && last.map(|t| t.elapsed() < PROGRESS_INTERVAL).unwrap_or(false) {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+        if let Some((entry, _)) = ACTIVE_JOBS.lock().ok().as_mut().and_then(|active| active.get_mut(&self.id)) {
+            entry.done = done;
+            entry.total = total;
+            entry.message = message.clone();
+        }
+        self.emit(JOB_PROGRESS_EVENT, JobProgress {
+            job_id: self.id.clone(),
+            kind: self.kind.to_string(),
+            done,
+            total,
+            message,
+        });
+    }
+
+    /// Message for the job's log pane; also written to the app log
+    pub fn log(&self, level: JobLogLevel, message: impl Into<String>) {
+        let message = message.into();
+        match level {
+            JobLogLevel::Debug => tracing::debug!(job_id = %self.id, "{}", message),
+            JobLogLevel::Info => tracing::info!(job_id = %self.id, "{}", message),
+            JobLogLevel::Warn => tracing::warn!(job_id = %self.id, "{}", message),
+            JobLogLevel::Error => tracing::error!(job_id = %self.id, "{}", message),
+        }
+        self.emit(JOB_LOG_EVENT, JobLog { job_id: self.id.clone(), kind: self.kind.to_string(), level, message });
+    }
+
+    fn finish(&self) {
+        tracing::debug!(job_id = %self.id, kind = self.kind, elapsed_ms = self.elapsed_ms(), "job finished");
+        self.emit(JOB_FINISHED_EVENT, JobFinished {
+            job_id: self.id.clone(),
+            kind: self.kind.to_string(),
+            elapsed_ms: self.elapsed_ms(),
+        });
+    }
+
+    fn fail(&self, error: &str) {
+        tracing::warn!(job_id = %self.id, kind = self.kind, error = %error, "job failed");
+        self.emit(JOB_FAILED_EVENT, JobFailed {
+            job_id: self.id.clone(),
+            kind: self.kind.to_string(),
+            error: error.to_string(),
+            elapsed_ms: self.elapsed_ms(),
+        });
+    }
+}
+
+impl Drop for Job {
+    fn drop(&mut self) {
+        if let Ok(mut active) = ACTIVE_JOBS.lock() {
+            active.remove(&self.id);
+        }
+    }
+}
+
+/// Jobs still running, oldest first
+pub fn active_jobs() -> Vec<ActiveJob> {
+    let Ok(active) = ACTIVE_JOBS.lock() else { return Vec::new() };
+    let mut jobs: Vec<(ActiveJob, Instant)> = active.values()
+        .map(|(job, started)| (ActiveJob { elapsed_ms: started.elapsed().as_millis() as u64, ..job.clone() }, *started))
+        .collect();
+    jobs.sort_by_key(|(_, started)| *started);
+    jobs.into_iter().map(|(job, _)| job).collect()
+}
+
+/// Run command work on the blocking pool as a job: started, then finished or failed. Jobs are
+/// where deferred commands get timed
+pub(crate) async fn run_job<T, F>(
+    app: AppHandle,
+    kind: &'static str,
+    job_id: Option<String>,
+    label: Option<String>,
+    work: F,
+) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&Job) -> Result<T, String> + Send + 'static,
+{
+    let _metrics = crate::metrics::track(kind);
+    let job = Arc::new(Job::start(Some(app), kind, job_id, label));
+    let worker = Arc::clone(&job);
+    let result = crate::run_blocking(move || work(&worker)).await.and_then(|r| r);
+    match &result {
+        Ok(_) => job.finish(),
+        Err(error) => job.fail(error),
+    }
+    result
+}
+
+/// Async counterpart of run_job for network-bound work that should not hold a blocking thread
+pub(crate) async fn run_async_job<T, F, Fut>(
+    app: AppHandle,
+    kind: &'static str,
+    job_id: Option<String>,
+    label: Option<String>,
+    work: F,
+) -> Result<T, String>
+where
+    F: FnOnce(Arc<Job>) -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let _metrics = crate::metrics::track(kind);
+    let job = Arc::new(Job::start(Some(app), kind, job_id, label));
+    let result = work(Arc::clone(&job)).await;
+    match &result {
+        Ok(_) => job.finish(),
+        Err(error) => job.fail(error),
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_ids_and_progress_throttle() {
+        let a = Job::start(None, "test", None, None);
+        let b = Job::start(None, "test", None, None);
+        assert_ne!(a.id(), b.id());
+        let client = Job::start(None, "test", Some("client-7".to_string()), None);
+        assert_eq!(client.id(), "client-7");
+        assert_ne!(Job::start(None, "test", Some("client-7".to_string()), None).id(), "client-7");
+        drop(client);
+
+        a.progress(1, Some(10), None);
+        let first = a.last_progress.lock().unwrap().unwrap();
+        a.progress(2, Some(10), None);
+        assert_eq!(a.last_progress.lock().unwrap().unwrap(), first);
+        a.progress(10, Some(10), None);
+        assert!(a.last_progress.lock().unwrap().unwrap() > first);
+
+        let listed = active_jobs().into_iter().find(|job| job.job_id == a.id()).unwrap();
+        assert_eq!((listed.done, listed.total), (10, Some(10)));
+        let id = a.id().to_string();
+        drop(a);
+        assert!(active_jobs().iter().all(|job| job.job_id != id));
+    }
+}
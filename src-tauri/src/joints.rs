@@ -0,0 +1,189 @@
+// Welded and bonded joints: fit-up gap and distortion contributors for fabricated assemblies
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::interface_detection::DetectedInterface;
+use crate::journal::{Edit, EditJournal};
+use crate::model_store::ModelStore;
+use crate::tolerance_calc::LinkInput;
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// Part name fragments that mark a weld bead or adhesive layer modelled as its own body
+const WELD_NAMES: [&str; 2] = ["weld", "bead"];
+const BOND_NAMES: [&str; 4] = ["adhesive", "glue", "bond", "epoxy"];
+
+/// Gap and distortion of one joint type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JointParameters {
+    pub gap: f64,            // Nominal fit-up gap or bondline thickness (mm)
+    pub gap_tolerance: f64,  // ± at 3 sigma
+    pub shrinkage: f64,      // Mean pull-in across the joint from weld or cure shrinkage (mm)
+    pub distortion: f64,     // ± scatter of the shrinkage and warping, uniform
+}
+
+impl JointParameters {
+    /// Shop defaults: manual fit-up with transverse weld shrinkage, or a spacer-controlled bondline
+    pub fn default_for(joint_type: &str) -> JointParameters {
+        match joint_type {
+            "weld" => JointParameters { gap: 0.5, gap_tolerance: 0.5, shrinkage: 0.2, distortion: 0.5 },
+            _ => JointParameters { gap: 0.15, gap_tolerance: 0.05, shrinkage: 0.005, distortion: 0.02 },
+        }
+    }
+
+    fn validate_as(&self, field: &str, errors: &mut FieldErrors) {
+        errors.at_least(format!("{}.gap", field), self.gap, 0.0);
+        errors.at_least(format!("{}.gap_tolerance", field), self.gap_tolerance, 0.0);
+        errors.at_least(format!("{}.shrinkage", field), self.shrinkage, 0.0);
+        errors.at_least(format!("{}.distortion", field), self.distortion, 0.0);
+    }
+}
+
+/// Joint parameters overriding the defaults, per joint type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JointContributorInput {
+    pub weld: Option<JointParameters>,
+    pub bond: Option<JointParameters>,
+}
+
+impl Validate for JointContributorInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if let Some(weld) = &self.weld {
+            weld.validate_as("weld", errors);
+        }
+        if let Some(bond) = &self.bond {
+            bond.validate_as("bond", errors);
+        }
+    }
+}
+
+/// Joint type implied by the part names, e.g. a body called "Weld bead 3"
+pub(crate) fn joint_type_from_names(name_a: &str, name_b: &str) -> Option<&'static str> {
+    let names = [name_a.to_lowercase(), name_b.to_lowercase()];
+    let named = |fragments: &[&str]| names.iter().any(|n| fragments.iter().any(|f| n.contains(f)));
+    if named(&WELD_NAMES) {
+        Some("weld")
+    } else if named(&BOND_NAMES) {
+        Some("bond")
+    } else {
+        None
+    }
+}
+
+/// Gap and distortion links of one weld or bond interface
+pub fn joint_contributors(interface: &DetectedInterface, parameters: &JointParameters) -> Vec<LinkInput> {
+    let mut links = vec![LinkInput {
+        name: Some(format!("{} {} gap", interface.id, interface.interface_type)),
+        nominal: parameters.gap,
+        plus_tolerance: parameters.gap_tolerance,
+        minus_tolerance: parameters.gap_tolerance,
+        direction: "positive".to_string(),
+        distribution: "normal".to_string(),
+        sigma: None,
+        inspected: false,
+    }];
+    if parameters.shrinkage > 0.0 || parameters.distortion > 0.0 {
+        links.push(LinkInput {
+            name: Some(format!("{} {} distortion", interface.id, interface.interface_type)),
+            nominal: -parameters.shrinkage,
+            plus_tolerance: parameters.distortion,
+            minus_tolerance: parameters.distortion,
+            direction: "positive".to_string(),
+            distribution: "uniform".to_string(),
+            sigma: None,
+            inspected: false,
+        });
+    }
+    links
+}
+
+/// Contributors for every weld and bond interface, defaults filled in per joint type
+pub fn model_joint_contributors(interfaces: &[DetectedInterface], input: &JointContributorInput) -> Vec<LinkInput> {
+    interfaces.iter()
+        .filter_map(|interface| {
+            let given = match interface.interface_type.as_str() {
+                "weld" => input.weld.clone(),
+                "bond" => input.bond.clone(),
+                _ => return None,
+            };
+            let parameters = given.unwrap_or_else(|| JointParameters::default_for(&interface.interface_type));
+            Some(joint_contributors(interface, &parameters))
+        })
+        .flatten()
+        .collect()
+}
+
+/// Mark a detected interface as a weld or bond (or put it back to face-to-face contact)
+#[tauri::command]
+pub fn mark_interface_joint(
+    state: State<'_, ModelStore>,
+    journal: State<'_, EditJournal>,
+    handle: String,
+    interface_id: String,
+    joint_type: String,
+) -> Result<DetectedInterface, String> {
+    let mut errors = FieldErrors::default();
+    errors.one_of("joint_type", &joint_type, &["weld", "bond", "face_to_face"]);
+    errors.into_result()?;
+
+    let (marked, before, after) = state.with_model_mut(&handle, |model| {
+        let before = model.interfaces.clone();
+        let interface = model.interfaces.as_mut()
+            .and_then(|r| r.interfaces.iter_mut().find(|i| i.id == interface_id))
+            .ok_or_else(|| format!("Unknown interface: {}", interface_id))?;
+        interface.interface_type = joint_type.clone();
+        let marked = interface.clone();
+        Ok::<_, String>((marked, before, model.interfaces.clone()))
+    })??;
+    journal.record(format!("Mark {} as {}", interface_id, joint_type), Edit::Interfaces { handle, before, after });
+    Ok(marked)
+}
+
+/// Gap and distortion contributors of the weld and bond interfaces on a model
+#[tauri::command]
+pub fn list_joint_contributors(
+    state: State<'_, ModelStore>,
+    handle: String,
+    input: Checked<JointContributorInput>,
+) -> Result<Vec<LinkInput>, String> {
+    state.with_model(&handle, |model| {
+        model.interfaces.as_ref()
+            .map(|r| model_joint_contributors(&r.interfaces, &input))
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::interface;
+    use crate::validation::validate;
+
+    #[test]
+    fn test_joint_names_and_contributors() {
+        assert_eq!(joint_type_from_names("Frame", "Weld Bead 3"), Some("weld"));
+        assert_eq!(joint_type_from_names("EPOXY layer", "Cover"), Some("bond"));
+        assert_eq!(joint_type_from_names("Frame", "Cover"), None);
+
+        let joint = |id: &str, interface_type: &str| DetectedInterface {
+            interface_type: interface_type.to_string(),
+            ..interface(id, "frame", "bracket", [0.0; 3])
+        };
+        let interfaces = vec![joint("interface-1", "weld"), joint("interface-2", "face_to_face"), joint("interface-3", "bond")];
+        let input = JointContributorInput {
+            weld: Some(JointParameters { gap: 1.0, gap_tolerance: 0.3, shrinkage: 0.0, distortion: 0.0 }),
+            bond: None,
+        };
+        assert!(validate(&input).is_ok());
+
+        let links = model_joint_contributors(&interfaces, &input);
+        // Weld gap only (no distortion given), bond gap and distortion from the defaults
+        assert_eq!(links.len(), 3);
+        assert_eq!(links[0].name.as_deref(), Some("interface-1 weld gap"));
+        assert_eq!((links[0].nominal, links[0].plus_tolerance), (1.0, 0.3));
+        assert!((links[2].nominal + 0.005).abs() < 1e-12 && links[2].distribution == "uniform");
+
+        let bad = JointContributorInput { bond: Some(JointParameters { gap: -0.1, ..JointParameters::default_for("bond") }), ..input };
+        assert!(validate(&bad).unwrap_err().contains("bond.gap"));
+    }
+}
@@ -0,0 +1,191 @@
+// Undo/redo journal for session edits: interfaces, part names, part placements and stackup drafts
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+use crate::autosave::{AutosaveState, StackupDraft};
+use crate::interface_detection::InterfaceDetectionResult;
+use crate::model_store::ModelStore;
+use crate::part_transforms::PartTransformStore;
+
+/// Oldest entries are dropped beyond this
+const MAX_ENTRIES: usize = 100;
+
+/// State before and after one edit
+#[derive(Debug, Clone)]
+pub enum Edit {
+    Interfaces {
+        handle: String,
+        before: Option<InterfaceDetectionResult>,
+        after: Option<InterfaceDetectionResult>,
+    },
+    PartName {
+        handle: String,
+        part_id: String,
+        before: String,
+        after: String,
+    },
+    PartTransform {
+        handle: String,
+        part_id: String,
+        before: [f64; 16],
+        after: [f64; 16],
+        interfaces_before: Option<InterfaceDetectionResult>,  // Moving a part drops its interfaces
+        interfaces_after: Option<InterfaceDetectionResult>,
+    },
+    StackupDraft {
+        id: String,
+        before: Option<StackupDraft>,
+        after: Option<StackupDraft>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct JournalEntry {
+    label: String,
+    edit: Edit,
+}
+
+/// What undo and redo would do next
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JournalStatus {
+    pub applied: Option<String>,     // Edit just undone or redone
+    pub undo_label: Option<String>,
+    pub redo_label: Option<String>,
+    pub undo_count: usize,
+    pub redo_count: usize,
+}
+
+/// Managed undo and redo stacks
+#[derive(Default)]
+pub struct EditJournal {
+    undo: Mutex<Vec<JournalEntry>>,
+    redo: Mutex<Vec<JournalEntry>>,
+}
+
+impl EditJournal {
+    /// Record a finished edit; anything that could be redone is discarded
+    pub fn record(&self, label: impl Into<String>, edit: Edit) {
+        let label = label.into();
+        tracing::debug!(label = %label, "edit journaled");
+        if let Ok(mut undo) = self.undo.lock() {
+            undo.push(JournalEntry { label, edit });
+            if undo.len() > MAX_ENTRIES {
+                undo.remove(0);
+            }
+        }
+        if let Ok(mut redo) = self.redo.lock() {
+            redo.clear();
+        }
+    }
+
+    fn status(&self, applied: Option<String>) -> JournalStatus {
+        let top = |stack: &Mutex<Vec<JournalEntry>>| {
+            stack.lock().map(|s| (s.last().map(|e| e.label.clone()), s.len())).unwrap_or((None, 0))
+        };
+        let (undo_label, undo_count) = top(&self.undo);
+        let (redo_label, redo_count) = top(&self.redo);
+        JournalStatus { applied, undo_label, redo_label, undo_count, redo_count }
+    }
+
+    /// Pop from one stack, apply the edit in that direction and push it onto the other stack
+    fn step(&self, app: &AppHandle, backward: bool) -> Result<JournalStatus, String> {
+        let (from, to) = if backward { (&self.undo, &self.redo) } else { (&self.redo, &self.undo) };
+        let entry = from.lock().map_err(|_| "Edit journal poisoned".to_string())?.pop();
+        let Some(entry) = entry else { return Ok(self.status(None)) };
+
+        // A failed step (e.g. the model was unloaded) drops the entry rather than blocking the stack
+        apply(app, &entry.edit, backward).map_err(|e| format!("Cannot {} \"{}\": {}", if backward { "undo" } else { "redo" }, entry.label, e))?;
+        tracing::info!(label = %entry.label, backward, "edit journal step");
+        let label = entry.label.clone();
+        to.lock().map_err(|_| "Edit journal poisoned".to_string())?.push(entry);
+        Ok(self.status(Some(label)))
+    }
+}
+
+fn pick<'a, T>(backward: bool, before: &'a T, after: &'a T) -> &'a T {
+    if backward { before } else { after }
+}
+
+/// Put the session back to the state before (`backward`) or after an edit
+fn apply(app: &AppHandle, edit: &Edit, backward: bool) -> Result<(), String> {
+    match edit {
+        Edit::Interfaces { handle, before, after } => {
+            let models = app.state::<ModelStore>();
+            models.with_model_mut(handle, |model| model.interfaces = pick(backward, before, after).clone())
+        }
+        Edit::PartName { handle, part_id, before, after } => {
+            let models = app.state::<ModelStore>();
+            models.with_model_mut(handle, |model| {
+                let part = model.assembly.parts.iter_mut()
+                    .find(|p| p.id == *part_id)
+                    .ok_or_else(|| format!("Unknown part: {}", part_id))?;
+                part.name = pick(backward, before, after).clone();
+                Ok(())
+            })?
+        }
+        Edit::PartTransform { handle, part_id, before, after, interfaces_before, interfaces_after } => {
+            let models = app.state::<ModelStore>();
+            let matrix = *pick(backward, before, after);
+            let key = models.with_model_mut(handle, |model| {
+                let part = model.assembly.parts.iter_mut()
+                    .find(|p| p.id == *part_id)
+                    .ok_or_else(|| format!("Unknown part: {}", part_id))?;
+                part.transform = matrix;
+                model.interfaces = pick(backward, interfaces_before, interfaces_after).clone();
+                Ok::<_, String>(model.content_hash.clone())
+            })??;
+            app.state::<PartTransformStore>().save(key, part_id.clone(), matrix)
+        }
+        Edit::StackupDraft { id, before, after } => {
+            let drafts = app.state::<AutosaveState>();
+            match pick(backward, before, after) {
+                Some(draft) => drafts.put_draft(draft.clone()).map(|_| ()),
+                None => drafts.remove_draft(id).map(|_| ()),
+            }
+        }
+    }
+}
+
+/// Roll back the last journaled edit
+#[tauri::command]
+pub fn undo(app: AppHandle, journal: State<'_, EditJournal>) -> Result<JournalStatus, String> {
+    journal.step(&app, true)
+}
+
+/// Reapply the last undone edit
+#[tauri::command]
+pub fn redo(app: AppHandle, journal: State<'_, EditJournal>) -> Result<JournalStatus, String> {
+    journal.step(&app, false)
+}
+
+/// Labels and depth of the undo and redo stacks
+#[tauri::command]
+pub fn get_journal_status(journal: State<'_, EditJournal>) -> JournalStatus {
+    journal.status(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename(n: usize) -> Edit {
+        Edit::PartName { handle: "model-1".to_string(), part_id: "part-1".to_string(), before: format!("v{}", n), after: format!("v{}", n + 1) }
+    }
+
+    #[test]
+    fn test_record_caps_and_clears_redo() {
+        let journal = EditJournal::default();
+        for n in 0..MAX_ENTRIES + 5 {
+            journal.record(format!("Rename {}", n), rename(n));
+        }
+        journal.redo.lock().unwrap().push(JournalEntry { label: "stale".to_string(), edit: rename(0) });
+
+        journal.record("Rename last", rename(999));
+        let status = journal.status(None);
+        assert_eq!(status.undo_count, MAX_ENTRIES);
+        assert_eq!(status.undo_label.as_deref(), Some("Rename last"));
+        assert_eq!(status.redo_count, 0);
+    }
+}
@@ -0,0 +1,138 @@
+// Size and entity-count guardrails that switch huge STEP files to a partial analysis
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+use crate::part_library::{parse_step_header, StepHeader};
+use crate::storage::{load_json, save_json};
+
+/// Work that is skipped for files over the limits
+pub const PARTIAL_SKIPPED: [&str; 4] = ["mesh", "assembly structure", "interface detection", "library thumbnail"];
+
+/// Thresholds above which only the header and topology counts are computed; 0 disables a check
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LargeFileLimits {
+    pub max_bytes: usize,
+    pub max_entities: usize,
+}
+
+impl Default for LargeFileLimits {
+    fn default() -> Self {
+        LargeFileLimits {
+            max_bytes: 150 * 1024 * 1024,
+            max_entities: 2_000_000,
+        }
+    }
+}
+
+/// Why a file got a partial analysis and what it is missing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialAnalysis {
+    pub reason: String,
+    pub bytes: usize,
+    pub entities: usize,
+    pub limits: LargeFileLimits,
+    pub skipped: Vec<String>,
+    pub header: StepHeader,
+}
+
+struct LimitStore {
+    path: PathBuf,
+    limits: Mutex<LargeFileLimits>,
+}
+
+static LIMITS: OnceLock<LimitStore> = OnceLock::new();
+
+/// Load the configured limits; defaults apply until this runs
+pub fn init_large_file_limits(path: PathBuf) {
+    let limits: LargeFileLimits = load_json(&path);
+    let _ = LIMITS.set(LimitStore { path, limits: Mutex::new(limits) });
+}
+
+/// Current limits
+pub fn large_file_limits() -> LargeFileLimits {
+    LIMITS.get()
+        .and_then(|store| store.limits.lock().ok().map(|l| *l))
+        .unwrap_or_default()
+}
+
+/// Entity count estimate: instances start a line with '#'
+pub fn count_entities(content: &str) -> usize {
+    let bytes = content.as_bytes();
+    let leading = usize::from(bytes.first() == Some(&b'#'));
+    leading + bytes.windows(2).filter(|w| w[0] == b'\n' && w[1] == b'#').count()
+}
+
+/// Partial-analysis report when content is over the limits, None when it can be processed fully
+pub fn assess_with(content: &str, limits: &LargeFileLimits) -> Option<PartialAnalysis> {
+    let bytes = content.len();
+    // The size check is free, so it runs before counting entities
+    let (reason, entities) = if limits.max_bytes > 0 && bytes > limits.max_bytes {
+        (format!("File is {:.0} MB, over the {:.0} MB limit", mb(bytes), mb(limits.max_bytes)), count_entities(content))
+    } else {
+        let entities = count_entities(content);
+        if limits.max_entities == 0 || entities <= limits.max_entities {
+            return None;
+        }
+        (format!("File has {} entities, over the {} entity limit", entities, limits.max_entities), entities)
+    };
+
+    Some(PartialAnalysis {
+        reason,
+        bytes,
+        entities,
+        limits: *limits,
+        skipped: PARTIAL_SKIPPED.iter().map(|s| s.to_string()).collect(),
+        header: parse_step_header(content),
+    })
+}
+
+/// Assess content against the configured limits
+pub fn assess_large_file(content: &str) -> Option<PartialAnalysis> {
+    assess_with(content, &large_file_limits())
+}
+
+fn mb(bytes: usize) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+/// Current large-file limits
+#[tauri::command]
+pub fn get_large_file_limits() -> LargeFileLimits {
+    large_file_limits()
+}
+
+/// Change the large-file limits; 0 disables a check
+#[tauri::command]
+pub fn set_large_file_limits(limits: LargeFileLimits) -> Result<LargeFileLimits, String> {
+    let store = LIMITS.get().ok_or("Large-file limits are not initialized")?;
+    let mut current = store.limits.lock().map_err(|_| "Large-file limit state poisoned".to_string())?;
+    *current = limits;
+    save_json(&store.path, &limits)?;
+    tracing::info!(max_bytes = limits.max_bytes, max_entities = limits.max_entities, "large-file limits updated");
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assess_limits() {
+        let content = "ISO-10303-21;\nHEADER;\nFILE_NAME('big.step','',(''),(''),'','CAD','');\nENDSEC;\nDATA;\n#1=CARTESIAN_POINT('',(0.,0.,0.));\n#2=CARTESIAN_POINT('',(1.,0.,0.));\n#3=VERTEX_POINT('',#1);\nENDSEC;";
+        assert_eq!(count_entities(content), 3);
+        assert!(assess_with(content, &LargeFileLimits::default()).is_none());
+
+        let partial = assess_with(content, &LargeFileLimits { max_bytes: 0, max_entities: 2 }).unwrap();
+        assert_eq!(partial.entities, 3);
+        assert!(partial.reason.contains("3 entities"));
+        assert!(partial.skipped.contains(&"mesh".to_string()));
+        assert_eq!(partial.header.name.as_deref(), Some("big.step"));
+
+        let by_size = assess_with(content, &LargeFileLimits { max_bytes: 10, max_entities: 0 }).unwrap();
+        assert!(by_size.reason.contains("MB limit"));
+        assert!(assess_with(content, &LargeFileLimits { max_bytes: 0, max_entities: 0 }).is_none());
+    }
+}
@@ -0,0 +1,312 @@
+// Small vector helpers shared by the geometry analysis modules
+
+pub type Vec3 = [f64; 3];
+pub type Mat3 = [[f64; 3]; 3];
+
+pub const IDENTITY3: Mat3 = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+pub fn add(a: &Vec3, b: &Vec3) -> Vec3 {
+    [a[0] + b[0], a[1] + b[1], a[2] + b[2]]
+}
+
+pub fn sub(a: &Vec3, b: &Vec3) -> Vec3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn scale(v: &Vec3, s: f64) -> Vec3 {
+    [v[0] * s, v[1] * s, v[2] * s]
+}
+
+pub fn dot(a: &Vec3, b: &Vec3) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: &Vec3, b: &Vec3) -> Vec3 {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn norm(v: &Vec3) -> f64 {
+    dot(v, v).sqrt()
+}
+
+pub fn distance(a: &Vec3, b: &Vec3) -> f64 {
+    norm(&sub(a, b))
+}
+
+/// Unit vector (zero-length vectors are returned unchanged)
+pub fn normalize(v: &Vec3) -> Vec3 {
+    let len = norm(v);
+    if len > 1e-10 {
+        scale(v, 1.0 / len)
+    } else {
+        *v
+    }
+}
+
+/// Component of `v` perpendicular to unit vector `axis`
+pub fn reject(v: &Vec3, axis: &Vec3) -> Vec3 {
+    sub(v, &scale(axis, dot(v, axis)))
+}
+
+/// Some unit vector perpendicular to `v`
+pub fn any_perpendicular(v: &Vec3) -> Vec3 {
+    let helper = if v[0].abs() < 0.9 { [1.0, 0.0, 0.0] } else { [0.0, 1.0, 0.0] };
+    normalize(&cross(v, &helper))
+}
+
+/// Solve a 3x3 linear system by Cramer's rule (None if singular)
+pub fn solve3(m: &[[f64; 3]; 3], b: &Vec3) -> Option<Vec3> {
+    let det = |m: &[[f64; 3]; 3]| dot(&m[0], &cross(&m[1], &m[2]));
+    let d = det(m);
+    if d.abs() < 1e-12 {
+        return None;
+    }
+
+    let mut result = [0.0; 3];
+    for (col, value) in result.iter_mut().enumerate() {
+        let mut replaced = *m;
+        for row in 0..3 {
+            replaced[row][col] = b[row];
+        }
+        *value = det(&replaced) / d;
+    }
+    Some(result)
+}
+
+/// Eigen-decomposition of a symmetric matrix by Jacobi rotations.
+/// Eigenvalues are ascending; `vectors[i]` belongs to `values[i]`.
+pub fn symmetric_eigen<const N: usize>(m: &[[f64; N]; N]) -> ([f64; N], [[f64; N]; N]) {
+    let mut a = *m;
+    let mut v = [[0.0; N]; N];
+    for (i, row) in v.iter_mut().enumerate() {
+        row[i] = 1.0;
+    }
+
+    for _sweep in 0..64 {
+        let off: f64 = (0..N).flat_map(|p| ((p + 1)..N).map(move |q| (p, q))).map(|(p, q)| a[p][q] * a[p][q]).sum();
+        if off < 1e-30 {
+            break;
+        }
+
+        for p in 0..N {
+            for q in (p + 1)..N {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                for (k, (pk, qk)) in row_p.iter().zip(row_q.iter()).enumerate() {
+                    a[p][k] = c * pk - s * qk;
+                    a[q][k] = s * pk + c * qk;
+                }
+                for row in v.iter_mut() {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..N).collect();
+    order.sort_by(|&i, &j| a[i][i].total_cmp(&a[j][j]));
+
+    let mut values = [0.0; N];
+    let mut vectors = [[0.0; N]; N];
+    for (out, &i) in order.iter().enumerate() {
+        values[out] = a[i][i];
+        for k in 0..N {
+            vectors[out][k] = v[k][i];
+        }
+    }
+    (values, vectors)
+}
+
+/// Mean of a point set
+pub fn centroid(points: &[Vec3]) -> Vec3 {
+    let n = points.len().max(1) as f64;
+    let sum = points.iter().fold([0.0; 3], |acc, p| [acc[0] + p[0], acc[1] + p[1], acc[2] + p[2]]);
+    scale(&sum, 1.0 / n)
+}
+
+/// Least-squares plane through points as (centroid, unit normal)
+pub fn fit_plane(points: &[Vec3]) -> Option<(Vec3, Vec3)> {
+    if points.len() < 3 {
+        return None;
+    }
+    let c = centroid(points);
+    let mut cov = [[0.0; 3]; 3];
+    for p in points {
+        let d = sub(p, &c);
+        for i in 0..3 {
+            for j in 0..3 {
+                cov[i][j] += d[i] * d[j];
+            }
+        }
+    }
+
+    // The normal is the direction of least spread; a line of points has no plane
+    let (values, vectors) = symmetric_eigen(&cov);
+    if values[1] < 1e-12 * values[2].max(1e-300) {
+        return None;
+    }
+    Some((c, normalize(&vectors[0])))
+}
+
+pub fn mat3_mul_vec(m: &Mat3, v: &Vec3) -> Vec3 {
+    [dot(&m[0], v), dot(&m[1], v), dot(&m[2], v)]
+}
+
+pub fn mat3_mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+/// Rotation matrix of a unit quaternion (w, x, y, z)
+pub fn quaternion_to_matrix(q: &[f64; 4]) -> Mat3 {
+    let [w, x, y, z] = *q;
+    [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)],
+        [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)],
+        [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)],
+    ]
+}
+
+/// Rotation followed by translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RigidTransform {
+    pub rotation: Mat3,
+    pub translation: Vec3,
+}
+
+impl Default for RigidTransform {
+    fn default() -> Self {
+        RigidTransform { rotation: IDENTITY3, translation: [0.0; 3] }
+    }
+}
+
+impl RigidTransform {
+    pub fn apply(&self, p: &Vec3) -> Vec3 {
+        add(&mat3_mul_vec(&self.rotation, p), &self.translation)
+    }
+
+    /// Transform that applies `self` first, then `next`
+    pub fn then(&self, next: &RigidTransform) -> RigidTransform {
+        RigidTransform {
+            rotation: mat3_mul(&next.rotation, &self.rotation),
+            translation: next.apply(&self.translation),
+        }
+    }
+
+    /// Column-major 4x4 matrix, as used for part transforms
+    pub fn to_matrix(self) -> [f64; 16] {
+        let r = &self.rotation;
+        let t = &self.translation;
+        [
+            r[0][0], r[1][0], r[2][0], 0.0,
+            r[0][1], r[1][1], r[2][1], 0.0,
+            r[0][2], r[1][2], r[2][2], 0.0,
+            t[0], t[1], t[2], 1.0,
+        ]
+    }
+
+    /// Best rigid transform mapping `from` onto `to` (Horn's quaternion method)
+    pub fn fit(from: &[Vec3], to: &[Vec3]) -> Option<RigidTransform> {
+        if from.len() < 3 || from.len() != to.len() {
+            return None;
+        }
+        let cf = centroid(from);
+        let ct = centroid(to);
+
+        let mut s = [[0.0; 3]; 3];
+        for (a, b) in from.iter().zip(to) {
+            let (a, b) = (sub(a, &cf), sub(b, &ct));
+            for i in 0..3 {
+                for j in 0..3 {
+                    s[i][j] += a[i] * b[j];
+                }
+            }
+        }
+
+        let n = [
+            [s[0][0] + s[1][1] + s[2][2], s[1][2] - s[2][1], s[2][0] - s[0][2], s[0][1] - s[1][0]],
+            [s[1][2] - s[2][1], s[0][0] - s[1][1] - s[2][2], s[0][1] + s[1][0], s[2][0] + s[0][2]],
+            [s[2][0] - s[0][2], s[0][1] + s[1][0], -s[0][0] + s[1][1] - s[2][2], s[1][2] + s[2][1]],
+            [s[0][1] - s[1][0], s[2][0] + s[0][2], s[1][2] + s[2][1], -s[0][0] - s[1][1] + s[2][2]],
+        ];
+        let (_, vectors) = symmetric_eigen(&n);
+        let rotation = quaternion_to_matrix(&vectors[3]);
+
+        Some(RigidTransform {
+            rotation,
+            translation: sub(&ct, &mat3_mul_vec(&rotation, &cf)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cross_and_reject() {
+        assert_eq!(cross(&[1.0, 0.0, 0.0], &[0.0, 1.0, 0.0]), [0.0, 0.0, 1.0]);
+        assert_eq!(reject(&[1.0, 2.0, 3.0], &[0.0, 0.0, 1.0]), [1.0, 2.0, 0.0]);
+        assert!((norm(&normalize(&[3.0, 4.0, 0.0])) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_solve3() {
+        let m = [[2.0, 0.0, 0.0], [0.0, 1.0, 1.0], [0.0, 0.0, 4.0]];
+        let x = solve3(&m, &[2.0, 3.0, 8.0]).unwrap();
+        assert!((x[0] - 1.0).abs() < 1e-12 && (x[1] - 1.0).abs() < 1e-12 && (x[2] - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_plane_fit_recovers_tilted_plane() {
+        // z = 0.5x + 2, sampled on a grid
+        let points: Vec<Vec3> = (0..5)
+            .flat_map(|i| (0..5).map(move |j| [i as f64, j as f64, 0.5 * i as f64 + 2.0]))
+            .collect();
+        let (_, n) = fit_plane(&points).unwrap();
+        let expected = normalize(&[-0.5, 0.0, 1.0]);
+        assert!(dot(&n, &expected).abs() > 1.0 - 1e-9);
+
+        let (values, _) = symmetric_eigen(&[[2.0, 1.0], [1.0, 2.0]]);
+        assert!((values[0] - 1.0).abs() < 1e-12 && (values[1] - 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_rigid_fit_recovers_rotation() {
+        let angle: f64 = 0.3;
+        let known = RigidTransform {
+            rotation: [[angle.cos(), -angle.sin(), 0.0], [angle.sin(), angle.cos(), 0.0], [0.0, 0.0, 1.0]],
+            translation: [5.0, -2.0, 1.0],
+        };
+        let from = vec![[0.0, 0.0, 0.0], [10.0, 0.0, 0.0], [0.0, 5.0, 0.0], [0.0, 0.0, 3.0], [2.0, 7.0, 1.0]];
+        let to: Vec<Vec3> = from.iter().map(|p| known.apply(p)).collect();
+
+        let fitted = RigidTransform::fit(&from, &to).unwrap();
+        for (p, q) in from.iter().zip(&to) {
+            assert!(distance(&fitted.apply(p), q) < 1e-9);
+        }
+    }
+}
@@ -0,0 +1,231 @@
+// Structured logging with runtime-adjustable levels and log export
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+use tauri::State;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::{EnvFilter, LevelFilter};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Registry};
+
+/// Log file name prefix inside the app log directory
+const LOG_FILE_PREFIX: &str = "ohmframe";
+
+/// Number of daily log files kept on disk
+const MAX_LOG_FILES: usize = 7;
+
+/// Crate name used as the target prefix for module-level directives
+const CRATE_TARGET: &str = env!("CARGO_CRATE_NAME");
+
+/// Default level when no module override is set
+const DEFAULT_LEVEL: &str = "info";
+
+/// Current log level configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLevels {
+    pub default_level: String,
+    pub modules: BTreeMap<String, String>, // module name -> level
+    pub log_dir: String,
+}
+
+/// Managed logging state (reload handle and file writer guard)
+pub struct LoggingState {
+    filter: reload::Handle<EnvFilter, Registry>,
+    levels: Mutex<(String, BTreeMap<String, String>)>,
+    log_dir: PathBuf,
+    _guard: WorkerGuard,
+}
+
+/// Install the global subscriber: JSON lines to a daily rotating file, plain text to stderr
+pub fn init_logging(log_dir: PathBuf) -> Result<LoggingState, String> {
+    std::fs::create_dir_all(&log_dir)
+        .map_err(|e| format!("Failed to create log directory: {}", e))?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(&log_dir)
+        .map_err(|e| format!("Failed to create log file: {}", e))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let (filter, handle) = reload::Layer::new(build_filter(DEFAULT_LEVEL, &BTreeMap::new())?);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().json().with_writer(writer))
+        .with(fmt::layer().with_writer(std::io::stderr))
+        .try_init()
+        .map_err(|e| format!("Failed to install logger: {}", e))?;
+
+    tracing::info!(log_dir = %log_dir.display(), "logging initialized");
+
+    Ok(LoggingState {
+        filter: handle,
+        levels: Mutex::new((DEFAULT_LEVEL.to_string(), BTreeMap::new())),
+        log_dir,
+        _guard: guard,
+    })
+}
+
+/// Build an EnvFilter from the default level plus per-module overrides
+fn build_filter(default_level: &str, modules: &BTreeMap<String, String>) -> Result<EnvFilter, String> {
+    let mut directives = vec![format!("{}={}", CRATE_TARGET, default_level)];
+    for (module, level) in modules {
+        directives.push(format!("{}={}", module_target(module), level));
+    }
+
+    // Dependencies stay at warn so plugin chatter does not drown app logs
+    EnvFilter::builder()
+        .with_default_directive(LevelFilter::WARN.into())
+        .parse(directives.join(","))
+        .map_err(|e| format!("Invalid log directive: {}", e))
+}
+
+/// Expand a short module name ("assembly_parser") into a full tracing target
+fn module_target(module: &str) -> String {
+    if module.starts_with(CRATE_TARGET) {
+        module.to_string()
+    } else {
+        format!("{}::{}", CRATE_TARGET, module)
+    }
+}
+
+/// Validate a level name
+fn parse_level(level: &str) -> Result<String, String> {
+    level.parse::<LevelFilter>()
+        .map(|l| l.to_string().to_lowercase())
+        .map_err(|_| format!("Unknown log level '{}' (expected trace, debug, info, warn, error, off)", level))
+}
+
+/// Get the active log levels
+#[tauri::command]
+pub fn get_log_levels(state: State<'_, LoggingState>) -> Result<LogLevels, String> {
+    let levels = state.levels.lock().map_err(|_| "Logging state poisoned".to_string())?;
+    Ok(LogLevels {
+        default_level: levels.0.clone(),
+        modules: levels.1.clone(),
+        log_dir: state.log_dir.display().to_string(),
+    })
+}
+
+/// Set the level for one module, or the default level when `module` is omitted
+#[tauri::command]
+pub fn set_log_level(
+    state: State<'_, LoggingState>,
+    module: Option<String>,
+    level: String,
+) -> Result<LogLevels, String> {
+    let level = parse_level(&level)?;
+    {
+        let mut levels = state.levels.lock().map_err(|_| "Logging state poisoned".to_string())?;
+        let mut default_level = levels.0.clone();
+        let mut modules = levels.1.clone();
+        match module.filter(|m| !m.is_empty()) {
+            Some(module) => {
+                modules.insert(module, level.clone());
+            }
+            None => default_level = level.clone(),
+        }
+
+        let filter = build_filter(&default_level, &modules)?;
+        state.filter.reload(filter).map_err(|e| format!("Failed to apply log level: {}", e))?;
+        *levels = (default_level, modules);
+    }
+
+    tracing::info!(level = %level, "log level changed");
+    get_log_levels(state)
+}
+
+/// Zip the recent log files and return the archive path
+#[tauri::command]
+pub fn export_logs(
+    state: State<'_, LoggingState>,
+    destination: Option<String>,
+    days: Option<u64>,
+) -> Result<String, String> {
+    let max_age = Duration::from_secs(days.unwrap_or(MAX_LOG_FILES as u64) * 24 * 3600);
+    let files = recent_log_files(&state.log_dir, max_age)?;
+
+    if files.is_empty() {
+        return Err("No log files found".to_string());
+    }
+
+    let archive_path = match destination {
+        Some(path) => PathBuf::from(path),
+        None => state.log_dir.join(format!("ohmframe-logs-{}.zip", crate::storage::unix_timestamp())),
+    };
+
+    write_zip(&archive_path, &files)?;
+    tracing::info!(archive = %archive_path.display(), files = files.len(), "logs exported");
+
+    Ok(archive_path.display().to_string())
+}
+
+/// Log files modified within `max_age`, oldest first
+fn recent_log_files(dir: &Path, max_age: Duration) -> Result<Vec<PathBuf>, String> {
+    let now = SystemTime::now();
+    let entries = std::fs::read_dir(dir).map_err(|e| format!("Failed to read log directory: {}", e))?;
+
+    let mut files: Vec<(SystemTime, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with(LOG_FILE_PREFIX) && name.ends_with(".log")
+        })
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            let age = now.duration_since(modified).unwrap_or_default();
+            (age <= max_age).then(|| (modified, entry.path()))
+        })
+        .collect();
+
+    files.sort();
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Write files into a deflate-compressed zip archive
+fn write_zip(archive_path: &Path, files: &[PathBuf]) -> Result<(), String> {
+    let file = std::fs::File::create(archive_path)
+        .map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    for path in files {
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("log.log");
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        zip.start_file(name, options).map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        zip.write_all(&bytes).map_err(|e| format!("Failed to write {}: {}", name, e))?;
+    }
+
+    zip.finish().map_err(|e| format!("Failed to finish archive: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_directives() {
+        let mut modules = BTreeMap::new();
+        modules.insert("assembly_parser".to_string(), "debug".to_string());
+        let filter = build_filter("info", &modules).unwrap().to_string();
+        assert!(filter.contains(&format!("{}::assembly_parser=debug", CRATE_TARGET)));
+        assert!(filter.contains(&format!("{}=info", CRATE_TARGET)));
+    }
+
+    #[test]
+    fn test_parse_level() {
+        assert_eq!(parse_level("DEBUG").unwrap(), "debug");
+        assert!(parse_level("verbose").is_err());
+    }
+}
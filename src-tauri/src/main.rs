@@ -6,23 +6,152 @@ use image::{ImageBuffer, Rgba};
 use screenshots::Screen;
 use std::io::Cursor;
 use std::path::Path;
-use tauri::Manager;
+use tauri::{AppHandle, Manager};
 use serde::{Deserialize, Serialize};
 
-// Regex for parsing STEP coordinates
-use regex::Regex;
-
 // Assembly and tolerance stackup modules
 mod assembly_parser;
 mod interface_detection;
 mod tolerance_calc;
+mod fit_statistics;
+mod distribution_fit;
+mod assembly_yield;
+mod assembly_variation;
+mod assembly_directions;
+mod assembly_sequence;
+mod spec_sweep;
+mod thermal_scenarios;
+mod compliant_stack;
+mod stackup_path;
+mod stackup_templates;
+mod tolerance_advisor;
+mod iso_fits;
+mod materials;
+mod press_fit;
+mod bolted_joint;
+mod joints;
+mod step_patterns;
+mod step_tokenizer;
+mod step_entities;
+mod step_format;
+mod face_area;
+mod step_anonymizer;
+mod mesh_query;
+mod mesh_optimize;
+mod mesh_orientation;
+mod mesh_culling;
+mod solid_mesh;
+mod brep_mesh;
+mod linalg;
+mod gdt;
+mod features;
+mod mass_properties;
+mod form_tolerance;
+mod scan;
+mod cmm;
+mod dfm;
+mod cost;
+mod sheet_metal;
+mod hardware;
+mod camera;
+mod snapshot;
+mod dxf;
+mod ortho_views;
+mod hole_table;
+mod hole_patterns;
+mod counterbores;
+mod oring_grooves;
+mod bearing_seats;
+mod coordinate_systems;
+mod revision_compare;
+mod part_library;
+mod annotations;
+mod jobs;
+mod plugins;
+mod scripting;
+mod active_document;
+mod credentials;
+mod onshape;
+mod plm;
+mod validation;
 
 pub use assembly_parser::*;
 pub use interface_detection::*;
 pub use tolerance_calc::*;
+pub use gdt::*;
+pub use features::*;
+pub use form_tolerance::*;
+pub use scan::*;
+pub use cmm::*;
+pub use dfm::*;
+pub use cost::*;
+pub use sheet_metal::*;
+pub use hardware::*;
+pub use camera::*;
+pub use dxf::*;
+pub use ortho_views::*;
+pub use hole_table::*;
+pub use coordinate_systems::*;
+pub use revision_compare::*;
+pub use part_library::*;
+pub use annotations::*;
+pub use jobs::*;
+pub use plugins::*;
+pub use scripting::*;
+pub use active_document::*;
+pub use onshape::*;
+pub use plm::*;
+
+// Backend services
+mod logging;
+mod metrics;
+mod storage;
+mod recent_files;
+mod deep_link;
+mod hotkey;
+mod session;
+mod model_store;
+mod part_transforms;
+mod part_display;
+mod clipping_planes;
+mod clearance_sweep;
+mod report;
+mod model_summary;
+mod report_templates;
+mod tray;
+mod windows;
+mod webhooks;
+mod encryption;
+mod project;
+mod source_integrity;
+mod autosave;
+mod journal;
+mod large_files;
+mod benchmark;
+mod backend_state;
+#[cfg(test)]
+mod test_fixtures;
+
+pub use logging::*;
+pub use metrics::*;
+pub use recent_files::*;
+pub use deep_link::*;
+pub use hotkey::*;
+pub use session::*;
+pub use model_store::*;
+pub use part_transforms::*;
+pub use report::*;
+pub use report_templates::*;
+pub use tray::*;
+pub use windows::*;
+pub use webhooks::*;
+pub use autosave::*;
+pub use large_files::*;
+pub use benchmark::*;
+pub use backend_state::*;
 
 /// Result of STEP file analysis
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StepAnalysisResult {
     pub success: bool,
     pub error: Option<String>,
@@ -32,16 +161,18 @@ pub struct StepAnalysisResult {
     pub surface_area_estimate: Option<f64>,
     pub topology: Option<TopologyInfo>,
     pub features: Option<FeatureInfo>,
+    #[serde(default)]
+    pub partial: Option<large_files::PartialAnalysis>, // Set when the file is over the large-file limits
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BoundingBox {
     pub min: [f64; 3],
     pub max: [f64; 3],
     pub dimensions: [f64; 3], // width, height, depth
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TopologyInfo {
     pub num_solids: usize,
     pub num_shells: usize,
@@ -50,7 +181,7 @@ pub struct TopologyInfo {
     pub num_vertices: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureInfo {
     pub cylindrical_faces: usize, // potential holes
     pub planar_faces: usize,
@@ -60,26 +191,30 @@ pub struct FeatureInfo {
 // ============ 3D Mesh Data Structures ============
 
 /// Mesh data for 3D viewer
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MeshData {
     pub vertices: Vec<f32>,      // [x1,y1,z1,x2,y2,z2,...] flat array
     pub indices: Vec<u32>,       // Triangle indices
     pub normals: Vec<f32>,       // Per-vertex normals
     pub face_groups: Vec<FaceGroup>, // Map triangles to STEP faces
+    #[serde(default)]
+    pub triangle_face_ids: Vec<u32>, // Face id per triangle for selection (u32::MAX if none)
 }
 
 /// Group of triangles belonging to a STEP face
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FaceGroup {
     pub face_id: u32,            // STEP entity ID
     pub face_type: String,       // "planar", "cylindrical", "curved", etc.
     pub start_index: u32,        // First triangle index in indices array
     pub triangle_count: u32,     // Number of triangles
     pub center: [f64; 3],        // Face center for marker placement
+    #[serde(default)]
+    pub cullable: bool,          // Fully enclosed by other parts; the viewer may skip it
 }
 
 /// Result of STEP mesh parsing
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StepMeshResult {
     pub success: bool,
     pub error: Option<String>,
@@ -88,6 +223,30 @@ pub struct StepMeshResult {
     pub bounding_box: Option<BoundingBox>,
     pub topology: Option<TopologyInfo>,
     pub features: Option<FeatureInfo>,
+    #[serde(default)]
+    pub partial: Option<large_files::PartialAnalysis>, // Meshing was skipped for a large file
+    #[serde(default)]
+    pub fallback: Option<String>, // B-rep tessellation failed and the mesh is bounding boxes; the kernel's error
+}
+
+/// Event delivering an image captured outside the main window (tray, hotkey) to the conversation
+pub const CAPTURED_IMAGE_EVENT: &str = "captured-image";
+
+/// Image captured by a background trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedImage {
+    pub source: String,        // "screen", "region", "clipboard"
+    pub trigger: String,       // "tray", "hotkey"
+    pub image_base64: String,  // PNG
+}
+
+/// Screen region in global desktop coordinates
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CaptureRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
 }
 
 /// Capture the primary screen and return as base64 PNG
@@ -102,11 +261,38 @@ fn capture_screen() -> Result<String, String> {
     // Capture the screen
     let capture = screen.capture().map_err(|e| format!("Failed to capture screen: {}", e))?;
 
-    // Convert screenshots::Image to image::ImageBuffer
     let width = capture.width();
     let height = capture.height();
-    let rgba_data = capture.rgba().to_vec();
+    tracing::debug!(width, height, "screen captured");
+
+    encode_rgba_png_base64(width, height, capture.rgba().to_vec())
+}
+
+/// Capture a region of the desktop and return as base64 PNG
+#[tauri::command]
+fn capture_region(region: CaptureRegion) -> Result<String, String> {
+    if region.width == 0 || region.height == 0 {
+        return Err("Capture region must have a non-zero size".to_string());
+    }
+
+    // Pick the screen containing the region origin; capture_area is screen-relative
+    let screen = Screen::from_point(region.x, region.y)
+        .map_err(|e| format!("No screen at ({}, {}): {}", region.x, region.y, e))?;
+    let capture = screen
+        .capture_area(
+            region.x - screen.display_info.x,
+            region.y - screen.display_info.y,
+            region.width,
+            region.height,
+        )
+        .map_err(|e| format!("Failed to capture region: {}", e))?;
+
+    tracing::debug!(width = capture.width(), height = capture.height(), "region captured");
+    encode_rgba_png_base64(capture.width(), capture.height(), capture.rgba().to_vec())
+}
 
+/// Encode raw RGBA pixels as a base64 PNG
+fn encode_rgba_png_base64(width: u32, height: u32, rgba_data: Vec<u8>) -> Result<String, String> {
     let img_buffer: ImageBuffer<Rgba<u8>, Vec<u8>> =
         ImageBuffer::from_raw(width, height, rgba_data)
             .ok_or("Failed to create image buffer")?;
@@ -120,9 +306,7 @@ fn capture_screen() -> Result<String, String> {
         .map_err(|e| format!("Failed to encode image: {}", e))?;
 
     // Encode as base64
-    let base64_string = STANDARD.encode(&png_bytes);
-
-    Ok(base64_string)
+    Ok(STANDARD.encode(&png_bytes))
 }
 
 /// Capture a specific window by title (for CAD software)
@@ -135,72 +319,84 @@ fn capture_window(title: String) -> Result<String, String> {
     let screen = screens.first().ok_or("No screens found")?;
     let capture = screen.capture().map_err(|e| format!("Failed to capture: {}", e))?;
 
-    // Convert screenshots::Image to image::ImageBuffer
-    let width = capture.width();
-    let height = capture.height();
-    let rgba_data = capture.rgba().to_vec();
-
-    let img_buffer: ImageBuffer<Rgba<u8>, Vec<u8>> =
-        ImageBuffer::from_raw(width, height, rgba_data)
-            .ok_or("Failed to create image buffer")?;
-
-    // Encode to PNG
-    let mut png_bytes = Vec::new();
-    let mut cursor = Cursor::new(&mut png_bytes);
-
-    img_buffer
-        .write_to(&mut cursor, image::ImageFormat::Png)
-        .map_err(|e| format!("Failed to encode: {}", e))?;
-
     let _ = title; // Silence unused warning for now
-    Ok(STANDARD.encode(&png_bytes))
+    encode_rgba_png_base64(capture.width(), capture.height(), capture.rgba().to_vec())
+}
+
+/// Run CPU-heavy command work on the blocking pool so IPC and window events keep flowing
+pub(crate) async fn run_blocking<T, F>(work: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| format!("Background task failed: {}", e))
 }
 
 /// Analyze STEP file content directly (passed from frontend)
 #[tauri::command]
-fn analyze_step_content(content: String, filename: String) -> StepAnalysisResult {
+async fn analyze_step_content(
+    app: AppHandle,
+    content: String,
+    filename: String,
+    job_id: Option<String>,
+) -> Result<StepAnalysisResult, String> {
+    let label = Some(filename.clone());
+    jobs::run_job(app, "analyze_step_content", job_id, label, move |_| Ok(analyze_step_text(&content, &filename))).await
+}
+
+/// Text-based STEP analysis shared by the commands and loaded models
+fn analyze_step_text(content: &str, filename: &str) -> StepAnalysisResult {
+    tracing::info!(filename = %filename, bytes = content.len(), "analyzing STEP content");
+
     // Validate it looks like a STEP file
-    if !content.contains("ISO-10303-21") && !content.contains("STEP") {
+    if !step_format::looks_like_step(content) {
+        tracing::warn!(filename = %filename, "rejected content without STEP header");
         return StepAnalysisResult {
             success: false,
             error: Some("Invalid STEP file format".to_string()),
-            filename: Some(filename),
+            filename: Some(filename.to_string()),
             bounding_box: None,
             volume_estimate: None,
             surface_area_estimate: None,
             topology: None,
             features: None,
+            partial: None,
         };
     }
 
-    // Parse STEP content by looking at the raw text
-    // This is a simplified analysis that doesn't require full truck geometry parsing
+    // Count entities by their parsed types, all in one pass
+    let census = step_format::census(content);
+    let count = |entity_type: &str| census.count(entity_type);
 
-    // Count entities by searching for keywords
-    let num_faces = content.matches("ADVANCED_FACE").count()
-        + content.matches("FACE_SURFACE").count();
-    let num_edges = content.matches("EDGE_CURVE").count();
-    let num_vertices = content.matches("VERTEX_POINT").count();
+    let num_faces = census.face_types.faces;
+    let num_edges = count("EDGE_CURVE");
+    let num_vertices = count("VERTEX_POINT");
 
-    // Count face types
-    let cylindrical_faces = content.matches("CYLINDRICAL_SURFACE").count();
-    let planar_faces = content.matches("PLANE(").count();
-    let curved_faces = content.matches("B_SPLINE_SURFACE").count()
-        + content.matches("TOROIDAL_SURFACE").count()
-        + content.matches("SPHERICAL_SURFACE").count()
-        + content.matches("CONICAL_SURFACE").count();
+    // Count face types by the surface each face references
+    let cylindrical_faces = census.face_types.cylindrical;
+    let planar_faces = census.face_types.planar;
+    let curved_faces = census.face_types.curved;
 
     // Count solids and shells
-    let num_solids = content.matches("MANIFOLD_SOLID_BREP").count()
-        .max(content.matches("BREP_WITH_VOIDS").count())
+    let num_solids = count("MANIFOLD_SOLID_BREP")
+        .max(count("BREP_WITH_VOIDS"))
         .max(1);
-    let num_shells = content.matches("CLOSED_SHELL").count()
-        + content.matches("OPEN_SHELL").count();
+    let num_shells = count("CLOSED_SHELL") + count("OPEN_SHELL");
+
+    tracing::debug!(num_solids, num_shells, num_faces, num_edges, num_vertices, "STEP topology counted");
+
+    // Over the limits, callers skip meshing and assembly parsing instead of hanging on the file
+    let partial = large_files::assess_large_file(content);
+    if let Some(partial) = &partial {
+        tracing::warn!(filename = %filename, reason = %partial.reason, "large file, switching to partial analysis");
+    }
 
     StepAnalysisResult {
         success: true,
         error: None,
-        filename: Some(filename),
+        filename: Some(filename.to_string()),
         bounding_box: None, // Would need full geometry processing
         volume_estimate: None,
         surface_area_estimate: None,
@@ -216,6 +412,7 @@ fn analyze_step_content(content: String, filename: String) -> StepAnalysisResult
             planar_faces,
             curved_faces,
         }),
+        partial,
     }
 }
 
@@ -225,6 +422,7 @@ fn analyze_step_file(file_path: String) -> StepAnalysisResult {
     let path = Path::new(&file_path);
 
     if !path.exists() {
+        tracing::warn!(path = %file_path, "STEP file not found");
         return StepAnalysisResult {
             success: false,
             error: Some(format!("File not found: {}", file_path)),
@@ -234,6 +432,7 @@ fn analyze_step_file(file_path: String) -> StepAnalysisResult {
             surface_area_estimate: None,
             topology: None,
             features: None,
+            partial: None,
         };
     }
 
@@ -243,7 +442,7 @@ fn analyze_step_file(file_path: String) -> StepAnalysisResult {
         .unwrap_or_default();
 
     match std::fs::read_to_string(path) {
-        Ok(content) => analyze_step_content(content, filename),
+        Ok(content) => analyze_step_text(&content, &filename),
         Err(e) => StepAnalysisResult {
             success: false,
             error: Some(format!("Failed to read file: {}", e)),
@@ -253,6 +452,7 @@ fn analyze_step_file(file_path: String) -> StepAnalysisResult {
             surface_area_estimate: None,
             topology: None,
             features: None,
+            partial: None,
         },
     }
 }
@@ -260,6 +460,7 @@ fn analyze_step_file(file_path: String) -> StepAnalysisResult {
 /// Open file dialog and return selected STEP file path
 #[tauri::command]
 async fn select_step_file() -> Result<Option<String>, String> {
+    let _metrics = metrics::track("select_step_file");
     // File selection is handled on the frontend with <input type="file">
     // This command is a placeholder for future native dialog support
     Ok(None)
@@ -267,13 +468,42 @@ async fn select_step_file() -> Result<Option<String>, String> {
 
 /// Parse STEP file and generate mesh for 3D viewer
 #[tauri::command]
-fn parse_step_mesh(content: String, filename: String) -> StepMeshResult {
+async fn parse_step_mesh(
+    app: AppHandle,
+    content: String,
+    filename: String,
+    optimize: Option<mesh_optimize::MeshOptimizeOptions>,
+    job_id: Option<String>,
+) -> Result<StepMeshResult, String> {
+    let label = Some(filename.clone());
+    jobs::run_job(app, "parse_step_mesh", job_id, label, move |_| {
+        Ok(mesh_step_text(content, filename, &optimize.unwrap_or_default()))
+    })
+    .await
+}
+
+/// Analysis plus mesh generation, run off the IPC thread by parse_step_mesh
+fn mesh_step_text(content: String, filename: String, optimize: &mesh_optimize::MeshOptimizeOptions) -> StepMeshResult {
     // First, get basic analysis using text-based parsing (always works)
-    let basic_result = analyze_step_content(content.clone(), filename.clone());
+    let basic_result = analyze_step_text(&content, &filename);
+
+    if basic_result.partial.is_some() {
+        return StepMeshResult {
+            success: true,
+            error: None,
+            filename: Some(filename),
+            mesh: None,
+            bounding_box: None,
+            topology: basic_result.topology,
+            features: basic_result.features,
+            partial: basic_result.partial,
+            fallback: None,
+        };
+    }
 
     // Try to parse with truck crates for mesh generation
-    match parse_step_to_mesh(&content) {
-        Ok((mesh, bbox)) => {
+    match parse_step_to_mesh(&content, &basic_result, optimize) {
+        Ok((mesh, bbox, fallback)) => {
             StepMeshResult {
                 success: true,
                 error: None,
@@ -282,10 +512,13 @@ fn parse_step_mesh(content: String, filename: String) -> StepMeshResult {
                 bounding_box: Some(bbox),
                 topology: basic_result.topology,
                 features: basic_result.features,
+                partial: None,
+                fallback,
             }
         }
         Err(e) => {
             // Fallback: return basic analysis without mesh
+            tracing::warn!(filename = %filename, error = %e, "mesh generation failed, returning basic analysis");
             StepMeshResult {
                 success: false,
                 error: Some(format!("Mesh generation failed: {}. Basic analysis available.", e)),
@@ -294,6 +527,8 @@ fn parse_step_mesh(content: String, filename: String) -> StepMeshResult {
                 bounding_box: basic_result.bounding_box,
                 topology: basic_result.topology,
                 features: basic_result.features,
+                partial: None,
+                fallback: None,
             }
         }
     }
@@ -303,15 +538,17 @@ fn parse_step_mesh(content: String, filename: String) -> StepMeshResult {
 fn extract_step_points(content: &str) -> Vec<[f64; 3]> {
     let mut points = Vec::new();
 
-    // Match CARTESIAN_POINT patterns: #123=CARTESIAN_POINT('',(-1.5,2.3,4.5));
-    let point_re = Regex::new(r"CARTESIAN_POINT\s*\(\s*'[^']*'\s*,\s*\(\s*([+-]?\d+\.?\d*(?:[eE][+-]?\d+)?)\s*,\s*([+-]?\d+\.?\d*(?:[eE][+-]?\d+)?)\s*,\s*([+-]?\d+\.?\d*(?:[eE][+-]?\d+)?)\s*\)").unwrap();
-
-    for cap in point_re.captures_iter(content) {
-        if let (Ok(x), Ok(y), Ok(z)) = (
-            cap[1].parse::<f64>(),
-            cap[2].parse::<f64>(),
-            cap[3].parse::<f64>(),
-        ) {
+    // #123=CARTESIAN_POINT('',(-1.5,2.3,4.5)); 2D points are skipped
+    for record in step_tokenizer::records(content) {
+        let Some((_, params)) = record.partials.iter().find(|p| p.0 == "CARTESIAN_POINT") else { continue };
+        let coordinates: Vec<f64> = step_tokenizer::tokenize(params)
+            .filter_map(|token| match token.ok()?.token {
+                step_tokenizer::Token::Real(v) => Some(v),
+                step_tokenizer::Token::Integer(v) => Some(v as f64),
+                _ => None,
+            })
+            .collect();
+        if let [x, y, z] = coordinates[..] {
             points.push([x, y, z]);
         }
     }
@@ -403,27 +640,79 @@ fn create_mesh_from_points(points: &[[f64; 3]]) -> (Vec<f32>, Vec<u32>, Vec<f32>
     (vertices, indices, normals, bbox)
 }
 
-/// Parse STEP file and generate mesh for 3D viewer
-fn parse_step_to_mesh(content: &str) -> std::result::Result<(MeshData, BoundingBox), String> {
-    // Get basic analysis first
-    let basic = analyze_step_content(content.to_string(), "temp.step".to_string());
-
+/// Parse STEP file and generate mesh for 3D viewer, reusing the text analysis. The third value is
+/// the B-rep kernel's error when the mesh fell back to bounding boxes
+fn parse_step_to_mesh(
+    content: &str,
+    basic: &StepAnalysisResult,
+    optimize: &mesh_optimize::MeshOptimizeOptions,
+) -> std::result::Result<(MeshData, BoundingBox, Option<String>), String> {
     if !basic.success {
         return Err("Invalid STEP file".to_string());
     }
+    if let Some(partial) = &basic.partial {
+        return Err(format!("Skipped for a large file: {}", partial.reason));
+    }
 
-    // Extract actual 3D points from the STEP file
-    let points = extract_step_points(content);
+    // Triangulate the real face surfaces; fall back to bounding boxes when the B-rep kernel fails
+    let (mut mesh, bbox, fallback) = match brep_mesh::tessellate_brep(content) {
+        Ok(brep) => {
+            let bbox = brep.bounding_box();
+            let mesh = MeshData {
+                vertices: brep.vertices,
+                indices: brep.indices,
+                normals: brep.normals,
+                face_groups: brep.face_groups,
+                triangle_face_ids: Vec::new(),
+            };
+            (mesh, bbox, None)
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "B-rep tessellation failed, meshing bounding boxes");
+            let (mesh, bbox) = bounding_box_mesh(content, basic)?;
+            (mesh, bbox, Some(e))
+        }
+    };
 
-    if points.is_empty() {
-        return Err("No geometry points found in STEP file".to_string());
+    mesh_orientation::orient_mesh(&mut mesh);
+    mesh_optimize::optimize_mesh(&mut mesh, optimize);
+    if optimize.cull_hidden_faces {
+        mesh_culling::mark_hidden_faces(&mut mesh);
     }
+    mesh.triangle_face_ids = mesh_query::triangle_face_ids(&mesh);
 
-    // Create mesh from extracted points
-    let (vertices, indices, normals, bbox) = create_mesh_from_points(&points);
+    Ok((mesh, bbox, fallback))
+}
+
+/// Box mesh per solid with face groups spread over the STEP face counts, for files the B-rep
+/// kernel cannot read
+fn bounding_box_mesh(content: &str, basic: &StepAnalysisResult) -> std::result::Result<(MeshData, BoundingBox), String> {
+    // Multi-body files get one box per solid, built in parallel; otherwise box all points
+    let multi_body = basic.topology.as_ref().map(|t| t.num_solids > 1).unwrap_or(false);
+    let solids = if multi_body { solid_mesh::tessellate_solids(content) } else { Vec::new() };
+
+    let (vertices, indices, normals, bbox, boxes) = if solids.len() > 1 {
+        tracing::debug!(solids = solids.len(), "merging per-solid meshes");
+        let (vertices, indices, normals, bbox) = solid_mesh::merge_solid_meshes(&solids);
+        let boxes: Vec<BoundingBox> = solids.into_iter().map(|s| s.bounding_box).collect();
+        (vertices, indices, normals, bbox, boxes)
+    } else {
+        // Extract actual 3D points from the STEP file
+        let points = extract_step_points(content);
+
+        if points.is_empty() {
+            return Err("No geometry points found in STEP file".to_string());
+        }
+        tracing::debug!(points = points.len(), "extracted STEP points for meshing");
+
+        // Create mesh from extracted points
+        let (vertices, indices, normals, bbox) = create_mesh_from_points(&points);
+        let boxes = vec![bbox.clone()];
+        (vertices, indices, normals, bbox, boxes)
+    };
 
     // Create face groups based on STEP analysis
-    let topology = basic.topology.unwrap_or(TopologyInfo {
+    let topology = basic.topology.clone().unwrap_or(TopologyInfo {
         num_solids: 1,
         num_shells: 1,
         num_faces: 6,
@@ -431,57 +720,64 @@ fn parse_step_to_mesh(content: &str) -> std::result::Result<(MeshData, BoundingB
         num_vertices: 8,
     });
 
-    let features = basic.features.unwrap_or(FeatureInfo {
+    let features = basic.features.clone().unwrap_or(FeatureInfo {
         cylindrical_faces: 0,
         planar_faces: 6,
         curved_faces: 0,
     });
 
-    // Create face groups for the bounding box mesh
+    // Create face groups for the bounding box meshes, one box per solid
     let mut face_groups = Vec::new();
-    let center = [
-        (bbox.min[0] + bbox.max[0]) / 2.0,
-        (bbox.min[1] + bbox.max[1]) / 2.0,
-        (bbox.min[2] + bbox.max[2]) / 2.0,
-    ];
-
-    // Map the 6 box faces to actual STEP face data
-    let face_offsets = [
-        [0.0, 0.0, bbox.min[2]],  // Back
-        [0.0, 0.0, bbox.max[2]],  // Front
-        [0.0, bbox.min[1], 0.0],  // Bottom
-        [0.0, bbox.max[1], 0.0],  // Top
-        [bbox.min[0], 0.0, 0.0],  // Left
-        [bbox.max[0], 0.0, 0.0],  // Right
+    let box_center = |b: &BoundingBox| [
+        (b.min[0] + b.max[0]) / 2.0,
+        (b.min[1] + b.max[1]) / 2.0,
+        (b.min[2] + b.max[2]) / 2.0,
     ];
-
-    for (i, offset) in face_offsets.iter().enumerate() {
-        let face_center = [
-            center[0] + offset[0] * 0.5,
-            center[1] + offset[1] * 0.5,
-            center[2] + offset[2] * 0.5,
+    let center = box_center(&bbox);
+
+    for (solid, solid_box) in boxes.iter().enumerate() {
+        let solid_center = box_center(solid_box);
+
+        // Map the 6 box faces to actual STEP face data
+        let face_offsets = [
+            [0.0, 0.0, solid_box.min[2]],  // Back
+            [0.0, 0.0, solid_box.max[2]],  // Front
+            [0.0, solid_box.min[1], 0.0],  // Bottom
+            [0.0, solid_box.max[1], 0.0],  // Top
+            [solid_box.min[0], 0.0, 0.0],  // Left
+            [solid_box.max[0], 0.0, 0.0],  // Right
         ];
 
-        // Assign face type based on STEP features
-        let face_type = if i < features.cylindrical_faces {
-            "cylindrical"
-        } else if i < features.cylindrical_faces + features.curved_faces {
-            "curved"
-        } else {
-            "planar"
-        };
-
-        face_groups.push(FaceGroup {
-            face_id: i as u32,
-            face_type: face_type.to_string(),
-            start_index: (i * 6) as u32,  // 6 indices per face (2 triangles)
-            triangle_count: 2,
-            center: face_center,
-        });
+        for (side, offset) in face_offsets.iter().enumerate() {
+            let i = solid * 6 + side;
+            let face_center = [
+                solid_center[0] + offset[0] * 0.5,
+                solid_center[1] + offset[1] * 0.5,
+                solid_center[2] + offset[2] * 0.5,
+            ];
+
+            // Assign face type based on STEP features
+            let face_type = if i < features.cylindrical_faces {
+                "cylindrical"
+            } else if i < features.cylindrical_faces + features.curved_faces {
+                "curved"
+            } else {
+                "planar"
+            };
+
+            face_groups.push(FaceGroup {
+                face_id: i as u32,
+                face_type: face_type.to_string(),
+                start_index: (i * 6) as u32,  // 6 indices per face (2 triangles)
+                triangle_count: 2,
+                center: face_center,
+                cullable: false,
+            });
+        }
     }
 
-    // Add additional face groups for STEP faces beyond 6
-    for extra_id in 6..topology.num_faces {
+    // Add additional face groups for STEP faces beyond the box faces
+    for extra_id in boxes.len() * 6..topology.num_faces {
         // Distribute extra faces markers around the model
         let angle = (extra_id as f64) * 2.0 * std::f64::consts::PI / (topology.num_faces as f64);
         let radius = bbox.dimensions[0].max(bbox.dimensions[1]).max(bbox.dimensions[2]) * 0.3;
@@ -506,46 +802,346 @@ fn parse_step_to_mesh(content: &str) -> std::result::Result<(MeshData, BoundingB
             start_index: 0,  // Visual marker only
             triangle_count: 0,
             center: face_center,
+            cullable: false,
         });
     }
 
-    Ok((
-        MeshData {
-            vertices,
-            indices,
-            normals,
-            face_groups,
-        },
-        bbox,
-    ))
+    let mesh = MeshData {
+        vertices,
+        indices,
+        normals,
+        face_groups,
+        triangle_face_ids: Vec::new(),
+    };
+    Ok((mesh, bbox))
 }
 
+/// Async commands: they return to the IPC layer before their work is done, so metrics time them
+/// where the work runs instead of at dispatch
+const ASYNC_COMMANDS: &[&str] = &[
+    "analyze_active_cad_document",
+    "analyze_step_content",
+    "anonymize_step",
+    "calculate_assembly_yield",
+    "calculate_compliant_stack",
+    "calculate_fit_interference",
+    "calculate_tolerance_stackup",
+    "close_detached_window",
+    "detect_active_cad_document",
+    "detect_mating_interfaces",
+    "fetch_plm_part",
+    "import_onshape_element",
+    "index_part_library",
+    "open_detached_window",
+    "parse_assembly_step",
+    "parse_step_mesh",
+    "push_report_to_webhook",
+    "render_model_snapshot",
+    "run_benchmark",
+    "run_plugin",
+    "run_script",
+    "run_temperature_scenarios",
+    "search_part_library",
+    "select_step_file",
+    "simulate_model_variation",
+    "sweep_stackup_spec",
+];
+
 fn main() {
     tauri::Builder::default()
+        // Must be first: forwards deep links from a second launch to the running instance
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.set_focus();
+            }
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .invoke_handler(metrics::instrument(ASYNC_COMMANDS, tauri::generate_handler![
             capture_screen,
             capture_window,
+            capture_region,
             analyze_step_content,
             analyze_step_file,
             select_step_file,
             parse_step_mesh,
+            step_anonymizer::anonymize_step,
+            step_format::validate_step_format,
             // Assembly and tolerance stackup commands
             assembly_parser::parse_assembly_step,
             interface_detection::detect_mating_interfaces,
-            tolerance_calc::calculate_tolerance_stackup
-        ])
+            tolerance_calc::calculate_tolerance_stackup,
+            fit_statistics::calculate_fit_interference,
+            fit_statistics::analyze_interface_fit,
+            distribution_fit::fit_measured_distribution,
+            assembly_yield::calculate_assembly_yield,
+            assembly_variation::simulate_model_variation,
+            assembly_sequence::get_sequence_chain,
+            assembly_directions::check_assembly_directions,
+            spec_sweep::sweep_stackup_spec,
+            thermal_scenarios::run_temperature_scenarios,
+            compliant_stack::calculate_compliant_stack,
+            stackup_path::get_stackup_path,
+            stackup_templates::list_stackup_templates,
+            stackup_templates::save_stackup_template,
+            stackup_templates::delete_stackup_template,
+            stackup_templates::instantiate_stackup_template,
+            tolerance_advisor::suggest_interface_tolerances,
+            iso_fits::calculate_fit,
+            // Joint calculators
+            materials::list_materials,
+            press_fit::calculate_press_fit,
+            press_fit::analyze_interface_press_fit,
+            bolted_joint::calculate_thread_engagement,
+            bolted_joint::calculate_bolt_preload,
+            bolted_joint::check_fastener_joint,
+            joints::mark_interface_joint,
+            joints::list_joint_contributors,
+            // GD&T datum reference frames and feature control frames
+            gdt::define_datum_frame,
+            gdt::list_datum_frames,
+            gdt::add_feature_control_frame,
+            gdt::list_feature_control_frames,
+            gdt::remove_feature_control_frame,
+            gdt::list_form_contributors,
+            gdt::locate_in_datum_frame,
+            gdt::define_datum_target_frame,
+            gdt::list_datum_target_contributors,
+            gdt::evaluate_true_position,
+            features::detect_holes,
+            features::get_feature_frames,
+            form_tolerance::evaluate_form_tolerance,
+            // Scan import, alignment and deviation
+            scan::import_scan,
+            scan::align_scan,
+            scan::compute_scan_deviation,
+            // CMM results
+            cmm::import_cmm_results,
+            cmm::compare_cmm_results,
+            // Manufacturability and cost
+            dfm::run_dfm_check,
+            cost::estimate_machining_cost,
+            sheet_metal::detect_sheet_metal,
+            // Standard hardware
+            hardware::recognize_hardware,
+            hardware::list_hardware_library,
+            // Recent files
+            recent_files::list_recent_files,
+            recent_files::record_recent_file,
+            recent_files::pin_recent_file,
+            recent_files::remove_recent_file,
+            recent_files::clear_recent_files,
+            recent_files::reopen_recent_file,
+            // Deep links
+            deep_link::take_pending_deep_link,
+            // Logging
+            logging::get_log_levels,
+            logging::set_log_level,
+            logging::export_logs,
+            // Performance metrics
+            metrics::set_metrics_enabled,
+            metrics::get_performance_report,
+            metrics::clear_performance_metrics,
+            // Shared session state and detached windows
+            session::set_shared_model,
+            session::get_shared_model,
+            session::clear_shared_model,
+            session::set_shared_selection,
+            session::get_shared_selection,
+            windows::open_detached_window,
+            windows::close_detached_window,
+            windows::list_open_windows,
+            // Loaded models queried by handle
+            model_store::load_model,
+            model_store::unload_model,
+            model_store::list_models,
+            model_store::get_model_mesh,
+            model_store::get_model_parts,
+            model_store::get_part_faces,
+            model_store::rename_part,
+            model_store::detect_model_interfaces,
+            model_store::pick_face,
+            model_store::get_face_info,
+            camera::get_view_fit,
+            snapshot::render_model_snapshot,
+            model_store::slice_model,
+            model_store::compute_section_properties,
+            clipping_planes::create_clipping_plane,
+            clipping_planes::update_clipping_plane,
+            clipping_planes::delete_clipping_plane,
+            clipping_planes::list_clipping_planes,
+            dxf::export_section_dxf,
+            ortho_views::generate_ortho_views,
+            hole_table::generate_hole_table,
+            hole_patterns::detect_hole_patterns,
+            hole_patterns::get_pattern_fastener_float,
+            counterbores::pair_counterbore_seats,
+            oring_grooves::check_oring_grooves,
+            bearing_seats::check_bearing_seats,
+            coordinate_systems::define_coordinate_system,
+            coordinate_systems::list_coordinate_systems,
+            coordinate_systems::delete_coordinate_system,
+            coordinate_systems::locate_in_coordinate_system,
+            revision_compare::compare_part_revisions,
+            part_library::index_part_library,
+            part_library::search_part_library,
+            annotations::add_annotation,
+            annotations::update_annotation,
+            annotations::delete_annotation,
+            annotations::list_annotations,
+            plugins::list_plugins,
+            plugins::reload_plugins,
+            plugins::run_plugin,
+            scripting::run_script,
+            active_document::detect_active_cad_document,
+            active_document::analyze_active_cad_document,
+            onshape::set_onshape_credentials,
+            onshape::clear_onshape_credentials,
+            onshape::onshape_credentials_configured,
+            onshape::import_onshape_element,
+            plm::list_plm_connectors,
+            plm::save_plm_connector,
+            plm::delete_plm_connector,
+            plm::fetch_plm_part,
+            webhooks::list_webhooks,
+            webhooks::save_webhook,
+            webhooks::delete_webhook,
+            webhooks::push_report_to_webhook,
+            autosave::save_stackup_draft,
+            autosave::discard_stackup_draft,
+            autosave::check_session_recovery,
+            autosave::recover_session,
+            autosave::discard_session_recovery,
+            journal::undo,
+            journal::redo,
+            journal::get_journal_status,
+            encryption::get_encryption_settings,
+            encryption::set_encryption_settings,
+            encryption::unlock_encryption,
+            encryption::lock_encryption,
+            project::save_project,
+            project::open_project,
+            source_integrity::verify_model_source,
+            source_integrity::verify_model_sources,
+            source_integrity::reanalyze_model_from_source,
+            large_files::get_large_file_limits,
+            large_files::set_large_file_limits,
+            benchmark::run_benchmark,
+            backend_state::get_backend_state,
+            model_store::measure_faces,
+            model_store::measure_part_clearance,
+            clearance_sweep::sweep_part_clearance,
+            model_store::compute_projected_area,
+            model_summary::summarize_model,
+            mass_properties::compute_mass_markers,
+            part_transforms::set_part_transform,
+            part_display::set_part_visibility,
+            part_display::set_part_transparency,
+            part_display::isolate_parts,
+            part_display::get_part_display,
+            part_display::reset_part_display,
+            // Reports
+            report::generate_assembly_report,
+            report_templates::list_report_templates,
+            report_templates::save_report_template,
+            report_templates::delete_report_template,
+            // Global capture hotkey
+            hotkey::get_capture_hotkey,
+            hotkey::set_capture_hotkey
+        ]))
         .setup(|app| {
+            // Start logging first so the rest of setup is captured
+            let log_dir = app.path().app_log_dir()?;
+            app.manage(logging::init_logging(log_dir)?);
+
+            // Metrics are opt-in; this only loads the persisted setting
+            metrics::init_metrics(storage::app_data_file(app.handle(), "metrics.json")?);
+
+            // Thresholds that switch huge files to a partial analysis
+            large_files::init_large_file_limits(storage::app_data_file(app.handle(), "large_file_limits.json")?);
+
             // Get the main window - handle potential errors gracefully
             if let Some(window) = app.get_webview_window("main") {
                 // Set window title
                 let _ = window.set_title("Ohmframe Copilot");
             }
+
+            // Load persisted recent files list
+            let recent_path = storage::app_data_file(app.handle(), "recent_files.json")?;
+            app.manage(recent_files::RecentFilesState::load(recent_path));
+
+            // Model state shared by the main and detached windows
+            app.manage(session::SessionState::default());
+
+            // Models parsed once and queried by handle
+            app.manage(model_store::ModelStore::default());
+
+            // Undo/redo history of edits made during this session
+            app.manage(journal::EditJournal::default());
+
+            // Manual part placements, reapplied when the same file is loaded again
+            let transforms_path = storage::app_data_file(app.handle(), "part_transforms.json")?;
+            app.manage(part_transforms::PartTransformStore::load(transforms_path));
+
+            // User report templates
+            let template_dir = storage::app_data_file(app.handle(), "report_templates")?;
+            app.manage(report_templates::ReportTemplateStore::new(template_dir));
+
+            // Built-in and user stackup templates
+            let stackup_template_dir = storage::app_data_file(app.handle(), "stackup_templates")?;
+            app.manage(stackup_templates::StackupTemplateStore::new(stackup_template_dir));
+
+            // Indexed STEP files for library search
+            let library_db = storage::app_data_file(app.handle(), "part_library.sqlite")?;
+            app.manage(part_library::PartLibrary::new(library_db));
+
+            // Review notes for models without a file of their own to sit next to
+            let annotation_dir = storage::app_data_file(app.handle(), "annotations")?;
+            app.manage(annotations::AnnotationStore::new(annotation_dir));
+
+            // Company analyses dropped into the plugins folder as WASM modules
+            let plugin_dir = storage::app_data_file(app.handle(), "plugins")?;
+            app.manage(plugins::PluginHost::new(plugin_dir));
+
+            // REST endpoints for pulling parts from PLM systems; auth values stay in the keychain
+            let plm_path = storage::app_data_file(app.handle(), "plm_connectors.json")?;
+            app.manage(plm::PlmConnectorStore::load(plm_path));
+
+            // Team channels and endpoints that reports can be pushed to
+            let webhook_path = storage::app_data_file(app.handle(), "webhooks.json")?;
+            app.manage(webhooks::WebhookStore::load(webhook_path));
+
+            // At-rest encryption of project bundles and recovery copies of models
+            let encryption_path = storage::app_data_file(app.handle(), "encryption.json")?;
+            app.manage(encryption::EncryptionState::load(encryption_path));
+
+            // Session snapshots; one left over from a crash is offered for recovery
+            let recovery_dir = storage::app_data_file(app.handle(), "recovery")?;
+            app.manage(autosave::AutosaveState::new(recovery_dir));
+            autosave::start_autosave(app.handle().clone());
+
+            // Tray icon for quick capture while CAD is full-screen
+            tray::setup_tray(app)?;
+
+            // Global capture hotkey works even when the app is not focused
+            hotkey::setup_hotkey(app, storage::app_data_file(app.handle(), "hotkey.json")?)?;
+
+            // Handle ohmframe:// links
+            deep_link::setup_deep_links(app)?;
+
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                autosave::mark_clean_exit(app);
+                metrics::flush_metrics();
+            }
+        });
 }
@@ -0,0 +1,192 @@
+// Centre of mass and principal inertia axes as viewer markers, per part and for the assembly
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::ParsedPart;
+use crate::interface_detection::transform_point;
+use crate::linalg::{dot, mat3_mul, scale, sub, symmetric_eigen, Mat3, Vec3, IDENTITY3};
+use crate::model_store::{LoadedModel, ModelStore};
+
+const DEFAULT_DENSITY: f64 = 7.85;  // g/cm³, steel
+
+/// Mass properties of one body, ready to draw: a point plus three axis arrows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MassMarker {
+    pub id: String,
+    pub label: String,
+    pub part_id: Option<String>,           // None for the assembly marker
+    pub mass: f64,                         // kg
+    pub center_of_mass: [f64; 3],          // World space (mm)
+    pub principal_moments: [f64; 3],       // About the centre of mass, ascending (kg·mm²)
+    pub principal_axes: [[f64; 3]; 3],     // Unit vectors, `principal_axes[i]` belongs to `principal_moments[i]`
+    pub radii_of_gyration: [f64; 3],       // Suggested arrow lengths (mm)
+}
+
+/// Markers for every part with a bounding box and for the whole assembly
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MassMarkers {
+    pub density: f64,
+    pub parts: Vec<MassMarker>,
+    pub assembly: Option<MassMarker>,
+    pub approximation: String,
+}
+
+/// Mass, centre and inertia tensor about that centre
+struct Body {
+    mass: f64,
+    center: Vec3,
+    inertia: Mat3,
+}
+
+fn transpose(m: &Mat3) -> Mat3 {
+    [[m[0][0], m[1][0], m[2][0]], [m[0][1], m[1][1], m[2][1]], [m[0][2], m[1][2], m[2][2]]]
+}
+
+/// Solid box between local `min` and `max`, placed by a column-major transform
+fn box_body(min: &Vec3, max: &Vec3, transform: &[f64; 16], density: f64) -> Body {
+    let size = sub(max, min);
+    let mass = density * 1e-6 * size[0] * size[1] * size[2];
+    let local_center = scale(&[min[0] + max[0], min[1] + max[1], min[2] + max[2]], 0.5);
+    let [a, b, c] = size.map(|s| s * s);
+    let local: Mat3 = [
+        [mass * (b + c) / 12.0, 0.0, 0.0],
+        [0.0, mass * (a + c) / 12.0, 0.0],
+        [0.0, 0.0, mass * (a + b) / 12.0],
+    ];
+    let rotation: Mat3 = [
+        [transform[0], transform[4], transform[8]],
+        [transform[1], transform[5], transform[9]],
+        [transform[2], transform[6], transform[10]],
+    ];
+    Body {
+        mass,
+        center: transform_point(&local_center, transform),
+        inertia: mat3_mul(&mat3_mul(&rotation, &local), &transpose(&rotation)),
+    }
+}
+
+fn part_body(part: &ParsedPart, density: f64) -> Option<Body> {
+    let bbox = part.bounding_box.as_ref()?;
+    Some(box_body(&bbox.min, &bbox.max, &part.transform, density))
+}
+
+/// Combine bodies with the parallel axis theorem
+fn combine(bodies: &[Body]) -> Option<Body> {
+    let mass: f64 = bodies.iter().map(|b| b.mass).sum();
+    if mass <= 0.0 {
+        return None;
+    }
+    let weighted = bodies.iter().fold([0.0; 3], |acc, b| [
+        acc[0] + b.mass * b.center[0],
+        acc[1] + b.mass * b.center[1],
+        acc[2] + b.mass * b.center[2],
+    ]);
+    let center = scale(&weighted, 1.0 / mass);
+
+    let mut inertia = [[0.0; 3]; 3];
+    for body in bodies {
+        let d = sub(&body.center, &center);
+        let d2 = dot(&d, &d);
+        for (i, row) in inertia.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value += body.inertia[i][j] + body.mass * (IDENTITY3[i][j] * d2 - d[i] * d[j]);
+            }
+        }
+    }
+    Some(Body { mass, center, inertia })
+}
+
+fn marker(id: String, label: String, part_id: Option<String>, body: &Body) -> MassMarker {
+    let (principal_moments, principal_axes) = symmetric_eigen(&body.inertia);
+    MassMarker {
+        id,
+        label,
+        part_id,
+        mass: body.mass,
+        center_of_mass: body.center,
+        principal_moments,
+        principal_axes,
+        radii_of_gyration: principal_moments.map(|i| (i.max(0.0) / body.mass).sqrt()),
+    }
+}
+
+/// Mass markers for a loaded model; each part is taken as a solid filling its bounding box
+pub fn mass_markers(model: &LoadedModel, density: f64) -> MassMarkers {
+    let mut parts = Vec::new();
+    let mut bodies = Vec::new();
+    for part in &model.assembly.parts {
+        let Some(body) = part_body(part, density) else { continue };
+        parts.push(marker(format!("com-{}", part.id), part.name.clone(), Some(part.id.clone()), &body));
+        bodies.push(body);
+    }
+
+    // Single-part files without assembly structure fall back to the model bounds
+    if bodies.is_empty() {
+        if let Some(bbox) = &model.bounding_box {
+            let identity = [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+            bodies.push(box_body(&bbox.min, &bbox.max, &identity, density));
+        }
+    }
+
+    MassMarkers {
+        density,
+        parts,
+        assembly: combine(&bodies).map(|body| marker("com-assembly".to_string(), model.filename.clone(), None, &body)),
+        approximation: "Uniform density; each part is a solid filling its bounding box".to_string(),
+    }
+}
+
+/// Centre of mass and principal axes markers for a loaded model
+#[tauri::command]
+pub fn compute_mass_markers(
+    state: State<'_, ModelStore>,
+    handle: String,
+    density: Option<f64>,
+) -> Result<MassMarkers, String> {
+    let density = density.unwrap_or(DEFAULT_DENSITY);
+    if !density.is_finite() || density <= 0.0 {
+        return Err(format!("Density must be positive, got {}", density));
+    }
+    state.with_model(&handle, |model| {
+        mass_markers(model, density)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translation(offset: Vec3) -> [f64; 16] {
+        let mut m = [0.0; 16];
+        m[0] = 1.0;
+        m[5] = 1.0;
+        m[10] = 1.0;
+        m[15] = 1.0;
+        m[12..15].copy_from_slice(&offset);
+        m
+    }
+
+    #[test]
+    fn test_box_principal_axes() {
+        // 100 x 20 x 10 mm steel bar: the long axis has the smallest moment
+        let body = box_body(&[0.0, 0.0, 0.0], &[100.0, 20.0, 10.0], &translation([0.0; 3]), 7.85);
+        assert!((body.mass - 0.157).abs() < 1e-9);
+        let m = marker("com".to_string(), "bar".to_string(), None, &body);
+        assert_eq!(m.center_of_mass, [50.0, 10.0, 5.0]);
+        assert!((m.principal_axes[0][0].abs() - 1.0).abs() < 1e-9);
+        assert!((m.principal_moments[0] - 0.157 * 500.0 / 12.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_two_cubes_combine() {
+        let cube = |x: f64| box_body(&[0.0; 3], &[10.0; 3], &translation([x, 0.0, 0.0]), 1.0);
+        let body = combine(&[cube(0.0), cube(90.0)]).unwrap();
+        assert!((body.center[0] - 50.0).abs() < 1e-9);
+        let m = marker("com".to_string(), "pair".to_string(), None, &body);
+        // Spread along x leaves x as the axis of least inertia
+        assert!((m.principal_axes[0][0].abs() - 1.0).abs() < 1e-9);
+        let single = 0.001 * 200.0 / 12.0;
+        assert!((m.principal_moments[2] - (2.0 * single + 2.0 * 0.001 * 45.0 * 45.0)).abs() < 1e-9);
+    }
+}
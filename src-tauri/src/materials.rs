@@ -0,0 +1,53 @@
+// Engineering material properties for joint calculators
+
+use serde::{Deserialize, Serialize};
+
+use crate::validation::FieldErrors;
+
+/// Isotropic elastic properties and strength (MPa)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    pub name: String,
+    pub elastic_modulus: f64,
+    pub poisson_ratio: f64,
+    pub yield_strength: f64,
+    pub tensile_strength: f64,
+}
+
+// Typical room-temperature values: name, E, Poisson, yield, ultimate
+const MATERIALS: [(&str, f64, f64, f64, f64); 7] = [
+    ("Steel, carbon (C45)", 210_000.0, 0.30, 370.0, 630.0),
+    ("Steel, alloy (42CrMo4 QT)", 210_000.0, 0.30, 750.0, 1000.0),
+    ("Stainless steel 304", 193_000.0, 0.29, 215.0, 505.0),
+    ("Aluminium 6061-T6", 68_900.0, 0.33, 276.0, 310.0),
+    ("Aluminium 7075-T6", 71_700.0, 0.33, 503.0, 572.0),
+    ("Brass CuZn39Pb3", 97_000.0, 0.34, 250.0, 430.0),
+    ("Titanium Ti-6Al-4V", 113_800.0, 0.34, 880.0, 950.0),
+];
+
+/// Built-in material catalogue
+pub fn material_library() -> Vec<Material> {
+    MATERIALS.iter()
+        .map(|&(name, elastic_modulus, poisson_ratio, yield_strength, tensile_strength)| Material {
+            name: name.to_string(),
+            elastic_modulus,
+            poisson_ratio,
+            yield_strength,
+            tensile_strength,
+        })
+        .collect()
+}
+
+/// Field checks shared by every input that carries a material
+pub fn validate_material(material: &Material, prefix: &str, errors: &mut FieldErrors) {
+    errors.above(format!("{}.elastic_modulus", prefix), material.elastic_modulus, 0.0);
+    errors.within(format!("{}.poisson_ratio", prefix), material.poisson_ratio, 0.0, 0.5);
+    errors.above(format!("{}.yield_strength", prefix), material.yield_strength, 0.0);
+    errors.above(format!("{}.tensile_strength", prefix), material.tensile_strength, 0.0);
+}
+
+/// The built-in material catalogue
+#[tauri::command]
+pub fn list_materials() -> Vec<Material> {
+    material_library()
+}
@@ -0,0 +1,237 @@
+// Hidden face detection for dense assemblies: faces boxed in by other geometry are marked cullable
+
+use crate::linalg::{add, cross, dot, norm, scale, sub, Vec3};
+use crate::MeshData;
+
+const MAX_GRID_CELLS: usize = 64;   // Per axis
+const NUDGE: f64 = 1e-4;            // Sample offset off the face, relative to the mesh diagonal
+
+/// Barycentric sample positions per triangle: centroid plus one near each corner
+const SAMPLE_WEIGHTS: [[f64; 3]; 4] = [
+    [1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0],
+    [0.8, 0.1, 0.1],
+    [0.1, 0.8, 0.1],
+    [0.1, 0.1, 0.8],
+];
+
+fn corners(mesh: &MeshData, t: usize) -> [Vec3; 3] {
+    [0, 1, 2].map(|k| {
+        let v = mesh.indices[t * 3 + k] as usize * 3;
+        [mesh.vertices[v] as f64, mesh.vertices[v + 1] as f64, mesh.vertices[v + 2] as f64]
+    })
+}
+
+/// Triangles bucketed into a uniform grid over the mesh bounds
+struct TriangleGrid {
+    min: Vec3,
+    cell: Vec3,
+    dims: [i64; 3],
+    cells: Vec<Vec<u32>>,
+}
+
+impl TriangleGrid {
+    fn build(mesh: &MeshData, pad: f64) -> Self {
+        let triangle_count = mesh.indices.len() / 3;
+        let mut min = [f64::MAX; 3];
+        let mut max = [f64::MIN; 3];
+        for t in 0..triangle_count {
+            for p in corners(mesh, t) {
+                for axis in 0..3 {
+                    min[axis] = min[axis].min(p[axis] - pad);
+                    max[axis] = max[axis].max(p[axis] + pad);
+                }
+            }
+        }
+
+        let n = ((triangle_count as f64).cbrt().ceil() as usize * 2).clamp(1, MAX_GRID_CELLS) as i64;
+        let cell = [0, 1, 2].map(|axis| ((max[axis] - min[axis]) / n as f64).max(f64::MIN_POSITIVE));
+        let mut grid = TriangleGrid { min, cell, dims: [n; 3], cells: vec![Vec::new(); (n * n * n) as usize] };
+
+        for t in 0..triangle_count {
+            let [a, b, c] = corners(mesh, t);
+            let lo = grid.cell_of(&[a[0].min(b[0]).min(c[0]), a[1].min(b[1]).min(c[1]), a[2].min(b[2]).min(c[2])]);
+            let hi = grid.cell_of(&[a[0].max(b[0]).max(c[0]), a[1].max(b[1]).max(c[1]), a[2].max(b[2]).max(c[2])]);
+            let [lo, hi] = [lo, hi].map(|c| [0, 1, 2].map(|axis| c[axis].clamp(0, n - 1)));
+            for x in lo[0]..=hi[0] {
+                for y in lo[1]..=hi[1] {
+                    for z in lo[2]..=hi[2] {
+                        let i = grid.index([x, y, z]);
+                        grid.cells[i].push(t as u32);
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    fn cell_of(&self, p: &Vec3) -> [i64; 3] {
+        [0, 1, 2].map(|axis| ((p[axis] - self.min[axis]) / self.cell[axis]).floor() as i64)
+    }
+
+    fn index(&self, c: [i64; 3]) -> usize {
+        ((c[0] * self.dims[1] + c[1]) * self.dims[2] + c[2]) as usize
+    }
+
+    /// Whether a ray from origin along +axis (or -axis) hits a triangle outside the skipped range
+    fn blocked(&self, mesh: &MeshData, origin: &Vec3, axis: usize, positive: bool, skip: &std::ops::Range<usize>) -> bool {
+        let start = self.cell_of(origin);
+        let inside = |c: i64, axis: usize| (0..self.dims[axis]).contains(&c);
+        if (0..3).any(|other| other != axis && !inside(start[other], other)) {
+            return false;
+        }
+        let steps: Box<dyn Iterator<Item = i64>> = if positive {
+            Box::new(start[axis].max(0)..self.dims[axis])
+        } else {
+            Box::new((0..=start[axis].min(self.dims[axis] - 1)).rev())
+        };
+
+        let mut dir = [0.0; 3];
+        dir[axis] = if positive { 1.0 } else { -1.0 };
+        for step in steps {
+            let mut c = start;
+            c[axis] = step;
+            let hit = self.cells[self.index(c)].iter()
+                .map(|t| *t as usize)
+                .any(|t| !skip.contains(&t) && ray_hits(origin, &dir, &corners(mesh, t)));
+            if hit {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Moller-Trumbore intersection, counting hits strictly in front of the origin
+fn ray_hits(origin: &Vec3, dir: &Vec3, [a, b, c]: &[Vec3; 3]) -> bool {
+    let e1 = sub(b, a);
+    let e2 = sub(c, a);
+    let p = cross(dir, &e2);
+    let det = dot(&e1, &p);
+    if det.abs() < 1e-12 {
+        return false;
+    }
+    let s = sub(origin, a);
+    let u = dot(&s, &p) / det;
+    if !(0.0..=1.0).contains(&u) {
+        return false;
+    }
+    let q = cross(&s, &e1);
+    let v = dot(dir, &q) / det;
+    if v < 0.0 || u + v > 1.0 {
+        return false;
+    }
+    dot(&e2, &q) / det > 0.0
+}
+
+/// Mark face groups whose every sample, nudged off the face, is blocked along each axis direction
+/// not pointing back into it; expects outward counter-clockwise winding. Returns the cullable
+/// triangle count
+pub fn mark_hidden_faces(mesh: &mut MeshData) -> usize {
+    let triangle_count = mesh.indices.len() / 3;
+    if triangle_count == 0 {
+        return 0;
+    }
+    let diagonal = {
+        let (min, max) = mesh.vertices.chunks_exact(3).fold(([f64::MAX; 3], [f64::MIN; 3]), |(min, max), p| {
+            ([0, 1, 2].map(|i| min[i].min(p[i] as f64)), [0, 1, 2].map(|i| max[i].max(p[i] as f64)))
+        });
+        norm(&sub(&max, &min))
+    };
+    let nudge = diagonal.max(1.0) * NUDGE;
+    let grid = TriangleGrid::build(mesh, nudge * 2.0);
+
+    let mut hidden_triangles = 0;
+    let mut hidden = vec![false; mesh.face_groups.len()];
+    for (g, group) in mesh.face_groups.iter().enumerate() {
+        let start = (group.start_index as usize / 3).min(triangle_count);
+        let own = start..(start + group.triangle_count as usize).min(triangle_count);
+        if own.is_empty() {
+            continue;
+        }
+
+        hidden[g] = own.clone().all(|t| {
+            let [a, b, c] = corners(mesh, t);
+            let n = cross(&sub(&b, &a), &sub(&c, &a));
+            let length = norm(&n);
+            if length == 0.0 {
+                return true;
+            }
+            let n = scale(&n, 1.0 / length);
+            SAMPLE_WEIGHTS.iter().all(|w| {
+                let p = add(&add(&scale(&a, w[0]), &scale(&b, w[1])), &scale(&c, w[2]));
+                let origin = add(&p, &scale(&n, nudge));
+                (0..3).all(|axis| {
+                    [true, false].into_iter()
+                        .filter(|&positive| (if positive { n[axis] } else { -n[axis] }) > -1e-9)
+                        .all(|positive| grid.blocked(mesh, &origin, axis, positive, &own))
+                })
+            })
+        });
+        if hidden[g] {
+            hidden_triangles += own.len();
+        }
+    }
+
+    for (group, hidden) in mesh.face_groups.iter_mut().zip(hidden) {
+        group.cullable = hidden;
+    }
+    tracing::debug!(hidden_triangles, total = triangle_count, "marked hidden faces");
+    hidden_triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FaceGroup;
+
+    /// Boxes as one mesh with a face group per side, wound outward
+    fn boxes(corners: &[[[f64; 3]; 2]]) -> MeshData {
+        let mut mesh = MeshData {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            normals: Vec::new(),
+            face_groups: Vec::new(),
+            triangle_face_ids: Vec::new(),
+        };
+        for (b, points) in corners.iter().enumerate() {
+            let (vertices, indices, normals, _) = crate::create_mesh_from_points(points);
+            let offset = (mesh.vertices.len() / 3) as u32;
+            mesh.vertices.extend(vertices);
+            mesh.normals.extend(normals);
+            mesh.indices.extend(indices.iter().map(|i| i + offset));
+            mesh.face_groups.extend((0..6).map(|side| FaceGroup {
+                face_id: (b * 6 + side) as u32,
+                face_type: "planar".to_string(),
+                start_index: ((b * 6 + side) * 6) as u32,
+                triangle_count: 2,
+                center: [0.0; 3],
+                cullable: false,
+            }));
+        }
+        crate::mesh_orientation::orient_mesh(&mut mesh);
+        mesh
+    }
+
+    fn cullable(mesh: &MeshData) -> Vec<u32> {
+        mesh.face_groups.iter().filter(|g| g.cullable).map(|g| g.face_id).collect()
+    }
+
+    #[test]
+    fn test_enclosed_part_is_cullable() {
+        let mut mesh = boxes(&[[[0.0; 3], [10.0; 3]], [[4.0; 3], [6.0; 3]]]);
+        assert_eq!(mark_hidden_faces(&mut mesh), 12);
+        assert_eq!(cullable(&mesh), (6..12).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_contact_faces_are_cullable() {
+        // Equal blocks stacked in z: only the touching top and bottom faces are hidden
+        let mut mesh = boxes(&[[[0.0; 3], [1.0; 3]], [[0.0, 0.0, 1.0], [1.0, 1.0, 2.0]]]);
+        assert_eq!(mark_hidden_faces(&mut mesh), 4);
+        assert_eq!(cullable(&mesh), vec![1, 6]);
+
+        // Apart, nothing is hidden
+        let mut mesh = boxes(&[[[0.0; 3], [1.0; 3]], [[3.0; 3], [4.0; 3]]]);
+        assert_eq!(mark_hidden_faces(&mut mesh), 0);
+    }
+}
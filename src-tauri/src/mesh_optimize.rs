@@ -0,0 +1,293 @@
+// Post-tessellation vertex welding and index-buffer optimization
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::MeshData;
+
+const CACHE_SIZE: usize = 32;
+
+/// Mesh optimization settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeshOptimizeOptions {
+    pub weld: bool,
+    pub weld_epsilon: f32,             // Max distance between welded positions
+    pub normal_tolerance_deg: f32,     // Normals further apart stay split, keeping hard edges
+    pub reorder_for_cache: bool,
+    pub cull_hidden_faces: bool,       // Mark faces enclosed by other parts (assemblies) as cullable
+}
+
+impl Default for MeshOptimizeOptions {
+    fn default() -> Self {
+        MeshOptimizeOptions {
+            weld: true,
+            weld_epsilon: 1e-5,
+            normal_tolerance_deg: 1.0,
+            reorder_for_cache: true,
+            cull_hidden_faces: false,
+        }
+    }
+}
+
+/// What an optimization pass changed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeshOptimizeStats {
+    pub vertices_before: usize,
+    pub vertices_after: usize,
+    pub degenerate_triangles_removed: usize,
+}
+
+fn position(mesh: &MeshData, v: usize) -> [f32; 3] {
+    [mesh.vertices[v * 3], mesh.vertices[v * 3 + 1], mesh.vertices[v * 3 + 2]]
+}
+
+fn normal(mesh: &MeshData, v: usize) -> Option<[f32; 3]> {
+    mesh.normals.get(v * 3..v * 3 + 3).map(|n| [n[0], n[1], n[2]])
+}
+
+/// Map each vertex to the first earlier vertex within epsilon and with a matching normal
+fn weld_map(mesh: &MeshData, options: &MeshOptimizeOptions) -> Vec<u32> {
+    let count = mesh.vertices.len() / 3;
+    let eps = options.weld_epsilon.max(f32::MIN_POSITIVE);
+    let min_dot = options.normal_tolerance_deg.to_radians().cos();
+    let cell = |p: [f32; 3]| p.map(|c| (c / eps).floor() as i64);
+
+    let mut grid: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+    let mut remap = Vec::with_capacity(count);
+
+    for v in 0..count {
+        let p = position(mesh, v);
+        let n = normal(mesh, v);
+        let [cx, cy, cz] = cell(p);
+
+        // Positions within epsilon sit in this cell or a neighbour
+        let mut found = None;
+        'search: for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(candidates) = grid.get(&[cx + dx, cy + dy, cz + dz]) else { continue };
+                    for &c in candidates {
+                        let q = position(mesh, c as usize);
+                        let d2 = (0..3).map(|i| (p[i] - q[i]).powi(2)).sum::<f32>();
+                        let normals_match = match (n, normal(mesh, c as usize)) {
+                            (Some(a), Some(b)) => a[0] * b[0] + a[1] * b[1] + a[2] * b[2] >= min_dot,
+                            _ => true,
+                        };
+                        if d2 <= eps * eps && normals_match {
+                            found = Some(c);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+
+        match found {
+            Some(c) => remap.push(c),
+            None => {
+                grid.entry([cx, cy, cz]).or_default().push(v as u32);
+                remap.push(v as u32);
+            }
+        }
+    }
+
+    remap
+}
+
+/// Forsyth vertex score from cache position and remaining triangle count
+fn vertex_score(cache_position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+    let cache = match cache_position {
+        Some(p) if p < 3 => 0.75,
+        Some(p) => (1.0 - (p - 3) as f32 / (CACHE_SIZE - 3) as f32).powf(1.5),
+        None => 0.0,
+    };
+    cache + 2.0 * (remaining as f32).powf(-0.5)
+}
+
+/// Reorder triangles (given as triples) for post-transform vertex cache hits
+fn reorder_triangles(triangles: &[[u32; 3]]) -> Vec<[u32; 3]> {
+    let mut vertex_tris: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (t, tri) in triangles.iter().enumerate() {
+        for &v in tri {
+            vertex_tris.entry(v).or_default().push(t);
+        }
+    }
+    let mut remaining: HashMap<u32, usize> = vertex_tris.iter().map(|(v, t)| (*v, t.len())).collect();
+    let mut emitted = vec![false; triangles.len()];
+    let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut order = Vec::with_capacity(triangles.len());
+    let mut cursor = 0;
+
+    let tri_score = |tri: &[u32; 3], cache: &[u32], remaining: &HashMap<u32, usize>| -> f32 {
+        tri.iter()
+            .map(|v| vertex_score(cache.iter().position(|c| c == v), remaining[v]))
+            .sum()
+    };
+
+    while order.len() < triangles.len() {
+        // Best triangle touching the cache, else the next unemitted one
+        let best = cache.iter()
+            .flat_map(|v| vertex_tris[v].iter().copied())
+            .filter(|&t| !emitted[t])
+            .map(|t| (t, tri_score(&triangles[t], &cache, &remaining)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(t, _)| t);
+        let t = match best {
+            Some(t) => t,
+            None => {
+                while emitted[cursor] {
+                    cursor += 1;
+                }
+                cursor
+            }
+        };
+
+        emitted[t] = true;
+        order.push(triangles[t]);
+        for v in triangles[t] {
+            *remaining.get_mut(&v).unwrap() -= 1;
+            cache.retain(|c| *c != v);
+        }
+        for v in triangles[t].iter().rev() {
+            cache.insert(0, *v);
+        }
+        cache.truncate(CACHE_SIZE);
+    }
+
+    order
+}
+
+/// Weld coincident vertices, drop collapsed triangles, reorder for the vertex cache and
+/// compact the vertex buffer; face group ranges are kept in step
+pub fn optimize_mesh(mesh: &mut MeshData, options: &MeshOptimizeOptions) -> MeshOptimizeStats {
+    let vertices_before = mesh.vertices.len() / 3;
+    let remap: Vec<u32> = if options.weld {
+        weld_map(mesh, options)
+    } else {
+        (0..vertices_before as u32).collect()
+    };
+
+    // Triangle offsets where a face group starts or ends; triangles never cross them
+    let triangle_count = mesh.indices.len() / 3;
+    let mut boundaries = vec![0, triangle_count];
+    for group in &mesh.face_groups {
+        let start = group.start_index as usize / 3;
+        boundaries.push(start.min(triangle_count));
+        boundaries.push((start + group.triangle_count as usize).min(triangle_count));
+    }
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    // Welded triangles per segment, with the new position of every surviving old triangle
+    let mut kept_before = vec![0usize; triangle_count + 1];
+    let mut triangles: Vec<[u32; 3]> = Vec::with_capacity(triangle_count);
+    for segment in boundaries.windows(2) {
+        let mut welded = Vec::new();
+        for t in segment[0]..segment[1] {
+            kept_before[t] = triangles.len() + welded.len();
+            let tri = [0, 1, 2].map(|k| remap[mesh.indices[t * 3 + k] as usize]);
+            if tri[0] != tri[1] && tri[1] != tri[2] && tri[0] != tri[2] {
+                welded.push(tri);
+            }
+        }
+        if options.reorder_for_cache {
+            welded = reorder_triangles(&welded);
+        }
+        triangles.extend(welded);
+    }
+    kept_before[triangle_count] = triangles.len();
+
+    for group in &mut mesh.face_groups {
+        let start = (group.start_index as usize / 3).min(triangle_count);
+        let end = (start + group.triangle_count as usize).min(triangle_count);
+        group.start_index = (kept_before[start] * 3) as u32;
+        group.triangle_count = (kept_before[end] - kept_before[start]) as u32;
+    }
+
+    // Number vertices in order of first use, dropping unreferenced ones
+    let mut new_index: Vec<Option<u32>> = vec![None; vertices_before];
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut indices = Vec::with_capacity(triangles.len() * 3);
+    for v in triangles.iter().flatten().map(|v| *v as usize) {
+        let index = *new_index[v].get_or_insert_with(|| {
+            vertices.extend_from_slice(&mesh.vertices[v * 3..v * 3 + 3]);
+            if let Some(n) = mesh.normals.get(v * 3..v * 3 + 3) {
+                normals.extend_from_slice(n);
+            }
+            (vertices.len() / 3 - 1) as u32
+        });
+        indices.push(index);
+    }
+
+    let stats = MeshOptimizeStats {
+        vertices_before,
+        vertices_after: vertices.len() / 3,
+        degenerate_triangles_removed: triangle_count - triangles.len(),
+    };
+    mesh.vertices = vertices;
+    mesh.normals = normals;
+    mesh.indices = indices;
+
+    tracing::debug!(before = stats.vertices_before, after = stats.vertices_after, "mesh optimized");
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FaceGroup;
+
+    fn group(face_id: u32, start_index: u32, triangle_count: u32) -> FaceGroup {
+        FaceGroup { face_id, face_type: "planar".to_string(), start_index, triangle_count, center: [0.0; 3], cullable: false }
+    }
+
+    #[test]
+    fn test_weld_shared_edges_but_keep_creases() {
+        // Quad in z=0 as two unshared triangles, plus a triangle folded up along x=1
+        let vertices = vec![
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.000001,
+            1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0,
+        ];
+        let mut normals = [0.0, 0.0, 1.0].repeat(6);
+        normals.extend([1.0, 0.0, 0.0].repeat(3));
+        let mut mesh = MeshData {
+            vertices,
+            indices: (0..9).collect(),
+            normals,
+            face_groups: vec![group(0, 0, 2), group(1, 6, 1)],
+            triangle_face_ids: Vec::new(),
+        };
+
+        let stats = optimize_mesh(&mut mesh, &MeshOptimizeOptions::default());
+
+        assert_eq!(stats.vertices_after, 7);
+        assert_eq!(stats.degenerate_triangles_removed, 0);
+        assert_eq!(mesh.indices.len(), 9);
+        assert_eq!(mesh.normals.len(), mesh.vertices.len());
+        assert_eq!((mesh.face_groups[1].start_index, mesh.face_groups[1].triangle_count), (6, 1));
+    }
+
+    #[test]
+    fn test_collapsed_triangles_shift_face_groups() {
+        let mut mesh = MeshData {
+            vertices: vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0],
+            indices: vec![0, 1, 2, 0, 1, 3, 1, 4, 3],
+            normals: Vec::new(),
+            face_groups: vec![group(0, 0, 1), group(1, 3, 2)],
+            triangle_face_ids: Vec::new(),
+        };
+
+        let stats = optimize_mesh(&mut mesh, &MeshOptimizeOptions::default());
+
+        assert_eq!(stats.degenerate_triangles_removed, 1);
+        assert_eq!(stats.vertices_after, 4);
+        assert_eq!((mesh.face_groups[0].start_index, mesh.face_groups[0].triangle_count), (0, 0));
+        assert_eq!((mesh.face_groups[1].start_index, mesh.face_groups[1].triangle_count), (0, 2));
+    }
+}
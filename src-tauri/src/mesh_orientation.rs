@@ -0,0 +1,124 @@
+// Post-tessellation winding fix: counter-clockwise triangles and outward normals throughout
+
+use serde::{Deserialize, Serialize};
+
+use crate::linalg::{add, cross, dot, sub, Vec3};
+use crate::MeshData;
+
+/// What an orientation pass changed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MeshOrientationStats {
+    pub flipped_triangles: usize,  // Wound against their vertex normals
+    pub inverted: bool,            // Whole mesh was inside-out and was turned around
+}
+
+fn position(mesh: &MeshData, v: u32) -> Vec3 {
+    let i = v as usize * 3;
+    [mesh.vertices[i] as f64, mesh.vertices[i + 1] as f64, mesh.vertices[i + 2] as f64]
+}
+
+fn normal(mesh: &MeshData, v: u32) -> Vec3 {
+    let i = v as usize * 3;
+    match mesh.normals.get(i..i + 3) {
+        Some(n) => [n[0] as f64, n[1] as f64, n[2] as f64],
+        None => [0.0; 3],
+    }
+}
+
+fn mesh_center(mesh: &MeshData) -> Vec3 {
+    let count = (mesh.vertices.len() / 3).max(1) as f64;
+    let sum = (0..(mesh.vertices.len() / 3) as u32).fold([0.0; 3], |acc, v| add(&acc, &position(mesh, v)));
+    [sum[0] / count, sum[1] / count, sum[2] / count]
+}
+
+/// Six times the signed volume enclosed by the triangles; negative when they face inward
+fn signed_volume6(mesh: &MeshData) -> f64 {
+    mesh.indices.chunks_exact(3)
+        .map(|t| {
+            let [a, b, c] = [t[0], t[1], t[2]].map(|v| position(mesh, v));
+            dot(&a, &cross(&b, &c))
+        })
+        .sum()
+}
+
+/// Wind every triangle counter-clockwise around its vertex normals (or away from the mesh center
+/// where it has none), then turn the whole mesh around if it still encloses a negative volume
+pub fn orient_mesh(mesh: &mut MeshData) -> MeshOrientationStats {
+    let center = mesh_center(mesh);
+    let mut stats = MeshOrientationStats::default();
+
+    for t in 0..mesh.indices.len() / 3 {
+        let tri = [mesh.indices[t * 3], mesh.indices[t * 3 + 1], mesh.indices[t * 3 + 2]];
+        let [a, b, c] = tri.map(|v| position(mesh, v));
+        let winding = cross(&sub(&b, &a), &sub(&c, &a));
+
+        let normals = tri.map(|v| normal(mesh, v));
+        let mut reference = add(&add(&normals[0], &normals[1]), &normals[2]);
+        if dot(&reference, &reference) == 0.0 {
+            let centroid = [(a[0] + b[0] + c[0]) / 3.0, (a[1] + b[1] + c[1]) / 3.0, (a[2] + b[2] + c[2]) / 3.0];
+            reference = sub(&centroid, &center);
+        }
+
+        if dot(&winding, &reference) < 0.0 {
+            mesh.indices.swap(t * 3 + 1, t * 3 + 2);
+            stats.flipped_triangles += 1;
+        }
+    }
+
+    if signed_volume6(mesh) < 0.0 {
+        for tri in mesh.indices.chunks_exact_mut(3) {
+            tri.swap(1, 2);
+        }
+        for n in &mut mesh.normals {
+            *n = -*n;
+        }
+        stats.inverted = true;
+    }
+
+    if stats.flipped_triangles > 0 || stats.inverted {
+        tracing::debug!(flipped = stats.flipped_triangles, inverted = stats.inverted, "reoriented mesh");
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn box_mesh() -> MeshData {
+        let (vertices, indices, normals, _) = crate::create_mesh_from_points(&[[0.0, 0.0, 0.0], [2.0, 3.0, 4.0]]);
+        MeshData { vertices, indices, normals, face_groups: Vec::new(), triangle_face_ids: Vec::new() }
+    }
+
+    fn windings_agree(mesh: &MeshData) -> bool {
+        mesh.indices.chunks_exact(3).all(|t| {
+            let [a, b, c] = [t[0], t[1], t[2]].map(|v| position(mesh, v));
+            dot(&cross(&sub(&b, &a), &sub(&c, &a)), &normal(mesh, t[0])) > 0.0
+        })
+    }
+
+    #[test]
+    fn test_orient_mesh() {
+        let mut mesh = box_mesh();
+        orient_mesh(&mut mesh);
+        assert!(windings_agree(&mesh));
+        assert!((signed_volume6(&mesh) - 6.0 * 24.0).abs() < 1e-6);
+        assert_eq!(orient_mesh(&mut mesh), MeshOrientationStats::default());
+
+        // Inward normals: windings follow them first, then the whole mesh is turned outward
+        for n in &mut mesh.normals {
+            *n = -*n;
+        }
+        let stats = orient_mesh(&mut mesh);
+        assert_eq!(stats, MeshOrientationStats { flipped_triangles: 12, inverted: true });
+        assert!(windings_agree(&mesh));
+        assert!(signed_volume6(&mesh) > 0.0);
+        assert_eq!(normal(&mesh, 0), [0.0, 0.0, -1.0]);
+
+        // Without normals the mesh center decides
+        let mut bare = box_mesh();
+        bare.normals.clear();
+        orient_mesh(&mut bare);
+        assert!(signed_volume6(&bare) > 0.0);
+    }
+}
@@ -0,0 +1,967 @@
+// Geometric queries against tessellated meshes (picking, slicing, closest point)
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::linalg::{any_perpendicular, cross, distance, dot, norm, normalize, sub, symmetric_eigen};
+use crate::MeshData;
+
+/// Result of a ray pick against the mesh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PickResult {
+    pub face_id: u32,
+    pub face_type: String,
+    pub triangle_index: u32,
+    pub point: [f64; 3],
+    pub distance: f64,
+}
+
+/// Plane given by a point and a normal
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SlicePlane {
+    pub origin: [f64; 3],
+    pub normal: [f64; 3],
+}
+
+/// Connected chain of slice segments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlicePolyline {
+    pub points: Vec<[f64; 3]>,
+    pub closed: bool,
+}
+
+/// Result of slicing a mesh with a plane
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SliceResult {
+    pub plane: SlicePlane,
+    pub polylines: Vec<SlicePolyline>,
+    pub segment_count: usize,
+}
+
+/// Area properties of a planar section, moments taken about the centroid in the (u, v) axes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SectionProperties {
+    pub plane: SlicePlane,
+    pub u_axis: [f64; 3],
+    pub v_axis: [f64; 3],
+    pub area: f64,
+    pub centroid: [f64; 3],
+    pub iuu: f64,                 // Integral of v^2
+    pub ivv: f64,                 // Integral of u^2
+    pub iuv: f64,                 // Product of area
+    pub principal: [f64; 2],      // Major and minor principal moments
+    pub principal_angle_deg: f64, // Major axis angle from u towards v
+    pub loop_count: usize,
+    pub open_polylines: usize,    // Not closed, so left out of the properties
+}
+
+/// Silhouette area of a mesh projected along a direction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectedArea {
+    pub normal: [f64; 3],       // Projection direction (the plane's normal)
+    pub area: f64,
+    pub cell_size: f64,         // Raster cell edge; the area is accurate to about one cell along the outline
+    pub extent: [f64; 2],       // Silhouette width and height in the plane
+}
+
+/// Face details for the viewer's hover/selection panel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaceInfo {
+    pub face_id: u32,
+    pub face_type: String,
+    pub triangle_count: u32,
+    pub area: f64,
+    pub center: [f64; 3],
+    pub normal: Option<[f64; 3]>,    // Area-weighted mean, for planar faces
+    pub axis: Option<[f64; 3]>,      // Plane normal, or the direction all normals are perpendicular to
+    pub adjacent_faces: Vec<u32>,    // Faces sharing an edge
+}
+
+/// Vertex position as f64
+pub fn vertex(mesh: &MeshData, index: u32) -> [f64; 3] {
+    let i = index as usize * 3;
+    [
+        mesh.vertices[i] as f64,
+        mesh.vertices[i + 1] as f64,
+        mesh.vertices[i + 2] as f64,
+    ]
+}
+
+/// Triangle corners by triangle index
+pub fn triangle(mesh: &MeshData, tri: usize) -> [[f64; 3]; 3] {
+    [
+        vertex(mesh, mesh.indices[tri * 3]),
+        vertex(mesh, mesh.indices[tri * 3 + 1]),
+        vertex(mesh, mesh.indices[tri * 3 + 2]),
+    ]
+}
+
+/// Face group index owning a triangle (start_index is an offset into the index buffer)
+pub fn face_group_of_triangle(mesh: &MeshData, tri: usize) -> Option<usize> {
+    let offset = (tri * 3) as u32;
+    mesh.face_groups.iter().position(|g| {
+        g.triangle_count > 0 && offset >= g.start_index && offset < g.start_index + g.triangle_count * 3
+    })
+}
+
+/// Owning face id per triangle, u32::MAX for triangles outside every group
+pub fn triangle_face_ids(mesh: &MeshData) -> Vec<u32> {
+    let mut ids = vec![u32::MAX; mesh.indices.len() / 3];
+    for group in &mesh.face_groups {
+        let start = group.start_index as usize / 3;
+        let end = (start + group.triangle_count as usize).min(ids.len());
+        for id in ids.iter_mut().take(end).skip(start) {
+            *id = group.face_id;
+        }
+    }
+    ids
+}
+
+/// Triangles of a face group
+fn group_triangles(mesh: &MeshData, group: &crate::FaceGroup) -> std::ops::Range<usize> {
+    let start = (group.start_index as usize / 3).min(mesh.indices.len() / 3);
+    start..(start + group.triangle_count as usize).min(mesh.indices.len() / 3)
+}
+
+/// Edges of a face as quantized position pairs, so faces with split vertices still share them
+fn face_edges(mesh: &MeshData, group: &crate::FaceGroup) -> HashSet<([i64; 3], [i64; 3])> {
+    let key = |p: [f64; 3]| p.map(|c| (c * 1e5).round() as i64);
+    let mut edges = HashSet::new();
+    for tri in group_triangles(mesh, group) {
+        let corners = triangle(mesh, tri).map(key);
+        for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+            let (a, b) = (corners[a], corners[b]);
+            edges.insert(if a <= b { (a, b) } else { (b, a) });
+        }
+    }
+    edges
+}
+
+/// Type, area, orientation and neighbours of a face
+pub fn face_info(mesh: &MeshData, face_id: u32) -> Option<FaceInfo> {
+    let group = mesh.face_groups.iter().find(|g| g.face_id == face_id)?;
+
+    let mut area = 0.0;
+    let mut weighted_normal = [0.0; 3];
+    let mut scatter = [[0.0; 3]; 3];
+    for tri in group_triangles(mesh, group) {
+        let [a, b, c] = triangle(mesh, tri);
+        let tri_area = 0.5 * norm(&cross(&sub(&b, &a), &sub(&c, &a)));
+        let n = outward_normal(mesh, tri);
+        area += tri_area;
+        for i in 0..3 {
+            weighted_normal[i] += n[i] * tri_area;
+            for j in 0..3 {
+                scatter[i][j] += n[i] * n[j] * tri_area;
+            }
+        }
+    }
+
+    let normal = (group.face_type == "planar" && dot(&weighted_normal, &weighted_normal) > 1e-24)
+        .then(|| normalize(&weighted_normal));
+    let axis = match group.face_type.as_str() {
+        "planar" => normal,
+        // Normals of a surface of revolution are all perpendicular to its axis
+        "cylindrical" | "conical" if area > 0.0 => Some(normalize(&symmetric_eigen(&scatter).1[0])),
+        _ => None,
+    };
+
+    let edges = face_edges(mesh, group);
+    let mut adjacent_faces: Vec<u32> = mesh.face_groups.iter()
+        .filter(|g| g.face_id != face_id && g.triangle_count > 0)
+        .filter(|g| !face_edges(mesh, g).is_disjoint(&edges))
+        .map(|g| g.face_id)
+        .collect();
+    adjacent_faces.sort_unstable();
+    adjacent_faces.dedup();
+
+    Some(FaceInfo {
+        face_id,
+        face_type: group.face_type.clone(),
+        triangle_count: group.triangle_count,
+        area,
+        center: group.center,
+        normal,
+        axis,
+        adjacent_faces,
+    })
+}
+
+/// Cast a ray and return the nearest face hit
+pub fn pick_face(mesh: &MeshData, origin: [f64; 3], direction: [f64; 3]) -> Option<PickResult> {
+    let dir = normalize(&direction);
+    let mut best: Option<(usize, f64)> = None;
+
+    for tri in 0..mesh.indices.len() / 3 {
+        if let Some(t) = ray_triangle(&origin, &dir, &triangle(mesh, tri)) {
+            if best.map(|(_, bt)| t < bt).unwrap_or(true) {
+                best = Some((tri, t));
+            }
+        }
+    }
+
+    let (tri, t) = best?;
+    let group = face_group_of_triangle(mesh, tri).map(|g| &mesh.face_groups[g]);
+
+    Some(PickResult {
+        face_id: group.map(|g| g.face_id).unwrap_or(u32::MAX),
+        face_type: group.map(|g| g.face_type.clone()).unwrap_or_else(|| "unknown".to_string()),
+        triangle_index: tri as u32,
+        point: [origin[0] + dir[0] * t, origin[1] + dir[1] * t, origin[2] + dir[2] * t],
+        distance: t,
+    })
+}
+
+/// Moller-Trumbore ray/triangle intersection, returning the ray parameter
+fn ray_triangle(origin: &[f64; 3], dir: &[f64; 3], tri: &[[f64; 3]; 3]) -> Option<f64> {
+    let edge1 = sub(&tri[1], &tri[0]);
+    let edge2 = sub(&tri[2], &tri[0]);
+    let p = cross(dir, &edge2);
+    let det = dot(&edge1, &p);
+
+    if det.abs() < 1e-12 {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let s = sub(origin, &tri[0]);
+    let u = dot(&s, &p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = cross(&s, &edge1);
+    let v = dot(dir, &q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = dot(&edge2, &q) * inv_det;
+    (t > 1e-9).then_some(t)
+}
+
+/// Intersect the mesh with a plane and chain the segments into polylines
+pub fn slice_mesh(mesh: &MeshData, plane: SlicePlane) -> SliceResult {
+    let normal = normalize(&plane.normal);
+    let mut segments: Vec<([f64; 3], [f64; 3])> = Vec::new();
+
+    for tri in 0..mesh.indices.len() / 3 {
+        let corners = triangle(mesh, tri);
+        let d: Vec<f64> = corners.iter().map(|c| dot(&sub(c, &plane.origin), &normal)).collect();
+
+        let mut points: Vec<[f64; 3]> = Vec::with_capacity(2);
+        for (a, b) in [(0, 1), (1, 2), (2, 0)] {
+            if (d[a] > 0.0) != (d[b] > 0.0) {
+                let t = d[a] / (d[a] - d[b]);
+                points.push(lerp(&corners[a], &corners[b], t));
+            }
+        }
+
+        if points.len() == 2 && distance(&points[0], &points[1]) > 1e-12 {
+            segments.push((points[0], points[1]));
+        }
+    }
+
+    SliceResult {
+        plane: SlicePlane { origin: plane.origin, normal },
+        segment_count: segments.len(),
+        polylines: chain_segments(&segments),
+    }
+}
+
+/// Silhouette area on a plane, by rasterizing the projected triangles so holes and concavities count correctly
+pub fn projected_area(mesh: &MeshData, normal: [f64; 3], resolution: usize) -> Option<ProjectedArea> {
+    let normal = normalize(&normal);
+    if dot(&normal, &normal) < 0.5 || mesh.indices.len() < 3 {
+        return None;
+    }
+    let u = any_perpendicular(&normal);
+    let v = cross(&normal, &u);
+
+    let projected: Vec<[f64; 2]> = mesh.vertices.chunks_exact(3)
+        .map(|p| {
+            let p = [p[0] as f64, p[1] as f64, p[2] as f64];
+            [dot(&p, &u), dot(&p, &v)]
+        })
+        .collect();
+    let mut min = [f64::MAX; 2];
+    let mut max = [f64::MIN; 2];
+    for p in mesh.indices.iter().map(|&i| projected[i as usize]) {
+        for k in 0..2 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+    let extent = [max[0] - min[0], max[1] - min[1]];
+
+    let resolution = resolution.clamp(16, 4096);
+    let cell = extent[0].max(extent[1]).max(1e-9) / resolution as f64;
+    let (cols, rows) = ((extent[0] / cell).ceil() as usize + 1, (extent[1] / cell).ceil() as usize + 1);
+    let mut covered = vec![false; cols * rows];
+
+    for tri in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| projected[tri[k] as usize]);
+        let twice_area = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+        if twice_area.abs() < 1e-18 {
+            continue;
+        }
+        let edge = |p: [f64; 2], q: [f64; 2], x: f64, y: f64| ((q[0] - p[0]) * (y - p[1]) - (q[1] - p[1]) * (x - p[0])) * twice_area.signum();
+
+        let col_range = |lo: f64, hi: f64| ((lo - min[0]) / cell).floor().max(0.0) as usize..=(((hi - min[0]) / cell).ceil() as usize).min(cols - 1);
+        let row_range = |lo: f64, hi: f64| ((lo - min[1]) / cell).floor().max(0.0) as usize..=(((hi - min[1]) / cell).ceil() as usize).min(rows - 1);
+        for row in row_range(a[1].min(b[1]).min(c[1]), a[1].max(b[1]).max(c[1])) {
+            let y = min[1] + (row as f64 + 0.5) * cell;
+            for col in col_range(a[0].min(b[0]).min(c[0]), a[0].max(b[0]).max(c[0])) {
+                let x = min[0] + (col as f64 + 0.5) * cell;
+                if edge(a, b, x, y) >= 0.0 && edge(b, c, x, y) >= 0.0 && edge(c, a, x, y) >= 0.0 {
+                    covered[row * cols + col] = true;
+                }
+            }
+        }
+    }
+
+    let count = covered.iter().filter(|&&c| c).count();
+    Some(ProjectedArea { normal, area: count as f64 * cell * cell, cell_size: cell, extent })
+}
+
+/// Unit normal plus in-plane (u, v) axes; sections and their exports share this orientation
+pub fn plane_basis(normal: &[f64; 3]) -> ([f64; 3], [f64; 3], [f64; 3]) {
+    let normal = normalize(normal);
+    let u = any_perpendicular(&normal);
+    (normal, u, cross(&normal, &u))
+}
+
+/// Area, first and second moments of a closed loop about the origin; positive when counterclockwise
+fn loop_moments(points: &[[f64; 2]]) -> [f64; 6] {
+    let mut m = [0.0; 6];
+    for (i, p) in points.iter().enumerate() {
+        let q = points[(i + 1) % points.len()];
+        let c = p[0] * q[1] - q[0] * p[1];
+        m[0] += c / 2.0;
+        m[1] += (p[0] + q[0]) * c / 6.0;
+        m[2] += (p[1] + q[1]) * c / 6.0;
+        m[3] += (p[1] * p[1] + p[1] * q[1] + q[1] * q[1]) * c / 12.0;
+        m[4] += (p[0] * p[0] + p[0] * q[0] + q[0] * q[0]) * c / 12.0;
+        m[5] += (p[0] * q[1] + 2.0 * p[0] * p[1] + 2.0 * q[0] * q[1] + q[0] * p[1]) * c / 24.0;
+    }
+    m
+}
+
+fn point_in_loop(p: &[f64; 2], points: &[[f64; 2]]) -> bool {
+    let mut inside = false;
+    for (i, a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        if (a[1] > p[1]) != (b[1] > p[1]) && p[0] < a[0] + (p[1] - a[1]) / (b[1] - a[1]) * (b[0] - a[0]) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Combined moments of nested loops: loops inside an odd number of others are holes
+fn section_moments(loops: &[Vec<[f64; 2]>]) -> [f64; 6] {
+    let mut total = [0.0; 6];
+    for (i, points) in loops.iter().enumerate() {
+        let depth = loops.iter().enumerate()
+            .filter(|(j, other)| *j != i && point_in_loop(&points[0], other))
+            .count();
+        let m = loop_moments(points);
+        // Normalize each loop's orientation, then subtract holes
+        let sign = m[0].signum() * if depth % 2 == 0 { 1.0 } else { -1.0 };
+        for k in 0..6 {
+            total[k] += sign * m[k];
+        }
+    }
+    total
+}
+
+/// Section area, centroid and second moments from the closed loops of a slice
+pub fn section_properties(slice: &SliceResult) -> SectionProperties {
+    let (normal, u, v) = plane_basis(&slice.plane.normal);
+    let origin = slice.plane.origin;
+
+    // Work relative to the plane origin to keep the sums well conditioned
+    let loops: Vec<Vec<[f64; 2]>> = slice.polylines.iter()
+        .filter(|p| p.closed && p.points.len() >= 3)
+        .map(|p| p.points.iter().map(|q| {
+            let d = sub(q, &origin);
+            [dot(&d, &u), dot(&d, &v)]
+        }).collect())
+        .collect();
+    let [area, su, sv, iuu, ivv, iuv] = section_moments(&loops);
+
+    let (cu, cv) = if area.abs() > 1e-18 { (su / area, sv / area) } else { (0.0, 0.0) };
+    let iuu = iuu - area * cv * cv;
+    let ivv = ivv - area * cu * cu;
+    let iuv = iuv - area * cu * cv;
+
+    let mean = (iuu + ivv) / 2.0;
+    let radius = (((iuu - ivv) / 2.0).powi(2) + iuv * iuv).sqrt();
+
+    SectionProperties {
+        plane: SlicePlane { origin, normal },
+        u_axis: u,
+        v_axis: v,
+        area,
+        centroid: [0, 1, 2].map(|k| origin[k] + u[k] * cu + v[k] * cv),
+        iuu,
+        ivv,
+        iuv,
+        principal: [mean + radius, mean - radius],
+        principal_angle_deg: (0.5 * (-2.0 * iuv).atan2(iuu - ivv)).to_degrees(),
+        loop_count: loops.len(),
+        open_polylines: slice.polylines.iter().filter(|p| !p.closed).count(),
+    }
+}
+
+/// Join segments sharing endpoints into polylines
+fn chain_segments(segments: &[([f64; 3], [f64; 3])]) -> Vec<SlicePolyline> {
+    // Quantize endpoints relative to the slice extent so float noise still connects
+    let extent = segments.iter()
+        .flat_map(|(a, b)| [a, b])
+        .flat_map(|p| p.iter())
+        .fold(0.0f64, |m, v| m.max(v.abs()))
+        .max(1.0);
+    let quantum = extent * 1e-9;
+    let key = |p: &[f64; 3]| {
+        (
+            (p[0] / quantum).round() as i64,
+            (p[1] / quantum).round() as i64,
+            (p[2] / quantum).round() as i64,
+        )
+    };
+
+    let mut by_point: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+    for (i, (a, b)) in segments.iter().enumerate() {
+        by_point.entry(key(a)).or_default().push(i);
+        by_point.entry(key(b)).or_default().push(i);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut polylines = Vec::new();
+
+    for start in 0..segments.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+        let mut points = vec![segments[start].0, segments[start].1];
+
+        // Extend forward from the tail, then backward from the head
+        for forward in [true, false] {
+            loop {
+                let end = if forward { *points.last().unwrap() } else { points[0] };
+                let next = by_point.get(&key(&end))
+                    .and_then(|candidates| candidates.iter().copied().find(|&c| !used[c]));
+                let Some(next) = next else { break };
+                used[next] = true;
+
+                let (a, b) = segments[next];
+                let other = if key(&a) == key(&end) { b } else { a };
+                if forward {
+                    points.push(other);
+                } else {
+                    points.insert(0, other);
+                }
+            }
+        }
+
+        let closed = points.len() > 2 && key(&points[0]) == key(points.last().unwrap());
+        if closed {
+            points.pop();
+        }
+        polylines.push(SlicePolyline { points, closed });
+    }
+
+    polylines
+}
+
+/// Closest point on the mesh surface to a query point
+#[derive(Debug, Clone, Copy)]
+pub struct ClosestPoint {
+    pub point: [f64; 3],
+    pub distance: f64,
+    pub triangle_index: usize,
+}
+
+/// Closest pair of points between two meshes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MeshDistance {
+    pub distance: f64,
+    pub point_a: [f64; 3],
+    pub point_b: [f64; 3],
+    pub triangle_a: usize,
+    pub triangle_b: usize,
+}
+
+/// Bounding volume hierarchy over mesh triangles for nearest-surface queries
+pub struct TriangleBvh {
+    triangles: Vec<[[f64; 3]; 3]>,
+    normals: Vec<[f64; 3]>,
+    order: Vec<usize>,
+    nodes: Vec<BvhNode>,
+}
+
+struct BvhNode {
+    min: [f64; 3],
+    max: [f64; 3],
+    // Leaf: range into `order`; inner: children at `left` and `left + 1`
+    left: usize,
+    count: usize,
+}
+
+const BVH_LEAF_SIZE: usize = 4;
+
+impl TriangleBvh {
+    pub fn build(mesh: &MeshData) -> TriangleBvh {
+        let triangles: Vec<[[f64; 3]; 3]> = (0..mesh.indices.len() / 3).map(|t| triangle(mesh, t)).collect();
+        let normals = (0..triangles.len()).map(|t| outward_normal(mesh, t)).collect();
+        let mut bvh = TriangleBvh {
+            order: (0..triangles.len()).collect(),
+            triangles,
+            normals,
+            nodes: Vec::new(),
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.nodes.push(BvhNode { min: [0.0; 3], max: [0.0; 3], left: 0, count: 0 });
+            bvh.split(0, 0, bvh.order.len());
+        }
+        bvh
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// Copy moved by `offset`; the hierarchy stays valid under translation
+    pub fn translated(&self, offset: &[f64; 3]) -> TriangleBvh {
+        let shift = |p: &[f64; 3]| [p[0] + offset[0], p[1] + offset[1], p[2] + offset[2]];
+        TriangleBvh {
+            triangles: self.triangles.iter().map(|t| t.map(|p| shift(&p))).collect(),
+            normals: self.normals.clone(),
+            order: self.order.clone(),
+            nodes: self.nodes.iter()
+                .map(|n| BvhNode { min: shift(&n.min), max: shift(&n.max), left: n.left, count: n.count })
+                .collect(),
+        }
+    }
+
+    /// Distance to the surface, positive outside the material and negative inside
+    pub fn signed_distance(&self, p: &[f64; 3]) -> Option<f64> {
+        let hit = self.closest_point(p)?;
+        let side = dot(&sub(p, &hit.point), &self.normals[hit.triangle_index]);
+        Some(if side < 0.0 { -hit.distance } else { hit.distance })
+    }
+
+    fn split(&mut self, node: usize, start: usize, end: usize) {
+        let (min, max) = self.bounds(&self.order[start..end]);
+        self.nodes[node].min = min;
+        self.nodes[node].max = max;
+
+        if end - start <= BVH_LEAF_SIZE {
+            self.nodes[node].left = start;
+            self.nodes[node].count = end - start;
+            return;
+        }
+
+        // Median split on the longest axis of the triangle centroids
+        let extent = sub(&max, &min);
+        let axis = if extent[0] >= extent[1] && extent[0] >= extent[2] { 0 } else if extent[1] >= extent[2] { 1 } else { 2 };
+        let triangles = &self.triangles;
+        let centroid = |t: usize| triangles[t].iter().map(|p| p[axis]).sum::<f64>();
+        let mid = start + (end - start) / 2;
+        self.order[start..end].select_nth_unstable_by(mid - start, |&a, &b| centroid(a).total_cmp(&centroid(b)));
+
+        let left = self.nodes.len();
+        for _ in 0..2 {
+            self.nodes.push(BvhNode { min: [0.0; 3], max: [0.0; 3], left: 0, count: 0 });
+        }
+        self.nodes[node].left = left;
+        self.split(left, start, mid);
+        self.split(left + 1, mid, end);
+    }
+
+    fn bounds(&self, tris: &[usize]) -> ([f64; 3], [f64; 3]) {
+        let mut min = [f64::MAX; 3];
+        let mut max = [f64::MIN; 3];
+        for p in tris.iter().flat_map(|&t| self.triangles[t].iter()) {
+            for i in 0..3 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+            }
+        }
+        (min, max)
+    }
+
+    /// Nearest point on any triangle
+    pub fn closest_point(&self, p: &[f64; 3]) -> Option<ClosestPoint> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut best: Option<ClosestPoint> = None;
+        let mut best_sq = f64::MAX;
+        let mut stack = vec![0usize];
+
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if box_distance_sq(p, &node.min, &node.max) >= best_sq {
+                continue;
+            }
+            if node.count > 0 {
+                for &t in &self.order[node.left..node.left + node.count] {
+                    let q = closest_on_triangle(p, &self.triangles[t]);
+                    let d = sub(p, &q);
+                    let d_sq = dot(&d, &d);
+                    if d_sq < best_sq {
+                        best_sq = d_sq;
+                        best = Some(ClosestPoint { point: q, distance: d_sq.sqrt(), triangle_index: t });
+                    }
+                }
+            } else {
+                // Visit the nearer child first
+                let (a, b) = (node.left, node.left + 1);
+                let da = box_distance_sq(p, &self.nodes[a].min, &self.nodes[a].max);
+                let db = box_distance_sq(p, &self.nodes[b].min, &self.nodes[b].max);
+                if da < db {
+                    stack.extend([b, a]);
+                } else {
+                    stack.extend([a, b]);
+                }
+            }
+        }
+
+        best
+    }
+
+    /// Exact minimum distance to another mesh by simultaneous descent of both hierarchies
+    pub fn min_distance(&self, other: &TriangleBvh) -> Option<MeshDistance> {
+        if self.nodes.is_empty() || other.nodes.is_empty() {
+            return None;
+        }
+
+        let mut best: Option<MeshDistance> = None;
+        let mut best_sq = f64::MAX;
+        let mut stack = vec![(0usize, 0usize)];
+
+        while let Some((ia, ib)) = stack.pop() {
+            let (na, nb) = (&self.nodes[ia], &other.nodes[ib]);
+            if box_box_distance_sq(na, nb) >= best_sq {
+                continue;
+            }
+
+            if na.count > 0 && nb.count > 0 {
+                for &ta in &self.order[na.left..na.left + na.count] {
+                    for &tb in &other.order[nb.left..nb.left + nb.count] {
+                        let (pa, pb) = closest_between_triangles(&self.triangles[ta], &other.triangles[tb]);
+                        let d = sub(&pa, &pb);
+                        let d_sq = dot(&d, &d);
+                        if d_sq < best_sq {
+                            best_sq = d_sq;
+                            best = Some(MeshDistance { distance: d_sq.sqrt(), point_a: pa, point_b: pb, triangle_a: ta, triangle_b: tb });
+                        }
+                    }
+                }
+                if best_sq == 0.0 {
+                    break;
+                }
+                continue;
+            }
+
+            // Split the larger inner node so both sides shrink evenly
+            let volume = |n: &BvhNode| (0..3).map(|i| n.max[i] - n.min[i]).product::<f64>();
+            let pairs = if nb.count > 0 || (na.count == 0 && volume(na) >= volume(nb)) {
+                [(na.left, ib), (na.left + 1, ib)]
+            } else {
+                [(ia, nb.left), (ia, nb.left + 1)]
+            };
+            let dist = |(a, b): (usize, usize)| box_box_distance_sq(&self.nodes[a], &other.nodes[b]);
+            if dist(pairs[0]) < dist(pairs[1]) {
+                stack.extend([pairs[1], pairs[0]]);
+            } else {
+                stack.extend(pairs);
+            }
+        }
+
+        best
+    }
+}
+
+fn box_box_distance_sq(a: &BvhNode, b: &BvhNode) -> f64 {
+    (0..3)
+        .map(|i| {
+            let d = (a.min[i] - b.max[i]).max(0.0).max(b.min[i] - a.max[i]);
+            d * d
+        })
+        .sum()
+}
+
+/// Closest points between segments p1-q1 and p2-q2 (Ericson 5.1.9)
+fn closest_between_segments(p1: &[f64; 3], q1: &[f64; 3], p2: &[f64; 3], q2: &[f64; 3]) -> ([f64; 3], [f64; 3]) {
+    let d1 = sub(q1, p1);
+    let d2 = sub(q2, p2);
+    let r = sub(p1, p2);
+    let (a, e, f) = (dot(&d1, &d1), dot(&d2, &d2), dot(&d2, &r));
+
+    let (s, t) = if a <= 1e-18 && e <= 1e-18 {
+        (0.0, 0.0)
+    } else if a <= 1e-18 {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = dot(&d1, &r);
+        if e <= 1e-18 {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = dot(&d1, &d2);
+            let denom = a * e - b * b;
+            let mut s = if denom > 1e-18 { ((b * f - c * e) / denom).clamp(0.0, 1.0) } else { 0.0 };
+            let mut t = (b * s + f) / e;
+            if t < 0.0 {
+                t = 0.0;
+                s = (-c / a).clamp(0.0, 1.0);
+            } else if t > 1.0 {
+                t = 1.0;
+                s = ((b - c) / a).clamp(0.0, 1.0);
+            }
+            (s, t)
+        }
+    };
+
+    (lerp(p1, q1, s), lerp(p2, q2, t))
+}
+
+/// Point where a segment crosses a triangle, if it does
+fn segment_triangle_hit(p: &[f64; 3], q: &[f64; 3], tri: &[[f64; 3]; 3]) -> Option<[f64; 3]> {
+    let t = ray_triangle(p, &sub(q, p), tri)?;
+    (t <= 1.0).then(|| lerp(p, q, t))
+}
+
+/// Closest points between two triangles: zero when they cross, else a vertex-face or edge-edge pair
+fn closest_between_triangles(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> ([f64; 3], [f64; 3]) {
+    const EDGES: [(usize, usize); 3] = [(0, 1), (1, 2), (2, 0)];
+
+    for (i, j) in EDGES {
+        if let Some(hit) = segment_triangle_hit(&a[i], &a[j], b) {
+            return (hit, hit);
+        }
+        if let Some(hit) = segment_triangle_hit(&b[i], &b[j], a) {
+            return (hit, hit);
+        }
+    }
+
+    let mut best = (a[0], b[0]);
+    let mut best_sq = f64::MAX;
+    let mut consider = |pa: [f64; 3], pb: [f64; 3]| {
+        let d = sub(&pa, &pb);
+        if dot(&d, &d) < best_sq {
+            best_sq = dot(&d, &d);
+            best = (pa, pb);
+        }
+    };
+
+    for p in a {
+        consider(*p, closest_on_triangle(p, b));
+    }
+    for p in b {
+        consider(closest_on_triangle(p, a), *p);
+    }
+    for (i, j) in EDGES {
+        for (k, l) in EDGES {
+            let (pa, pb) = closest_between_segments(&a[i], &a[j], &b[k], &b[l]);
+            consider(pa, pb);
+        }
+    }
+
+    best
+}
+
+/// Triangle normal from the mesh vertex normals, or from the winding when they are missing
+pub(crate) fn outward_normal(mesh: &MeshData, tri: usize) -> [f64; 3] {
+    let corners = [mesh.indices[tri * 3], mesh.indices[tri * 3 + 1], mesh.indices[tri * 3 + 2]];
+    if corners.iter().all(|&i| (i as usize + 1) * 3 <= mesh.normals.len()) {
+        let mut sum = [0.0; 3];
+        for &i in &corners {
+            for (axis, value) in sum.iter_mut().enumerate() {
+                *value += mesh.normals[i as usize * 3 + axis] as f64;
+            }
+        }
+        if dot(&sum, &sum) > 1e-12 {
+            return normalize(&sum);
+        }
+    }
+    let [a, b, c] = triangle(mesh, tri);
+    normalize(&cross(&sub(&b, &a), &sub(&c, &a)))
+}
+
+fn box_distance_sq(p: &[f64; 3], min: &[f64; 3], max: &[f64; 3]) -> f64 {
+    (0..3)
+        .map(|i| {
+            let d = (min[i] - p[i]).max(0.0).max(p[i] - max[i]);
+            d * d
+        })
+        .sum()
+}
+
+/// Closest point on a triangle (Ericson, Real-Time Collision Detection 5.1.5)
+fn closest_on_triangle(p: &[f64; 3], tri: &[[f64; 3]; 3]) -> [f64; 3] {
+    let [a, b, c] = tri;
+    let ab = sub(b, a);
+    let ac = sub(c, a);
+    let ap = sub(p, a);
+
+    let d1 = dot(&ab, &ap);
+    let d2 = dot(&ac, &ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return *a;
+    }
+
+    let bp = sub(p, b);
+    let d3 = dot(&ab, &bp);
+    let d4 = dot(&ac, &bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return *b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return lerp(a, b, d1 / (d1 - d3));
+    }
+
+    let cp = sub(p, c);
+    let d5 = dot(&ab, &cp);
+    let d6 = dot(&ac, &cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return *c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return lerp(a, c, d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return lerp(b, c, (d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    let v = vb * denom;
+    let w = vc * denom;
+    [
+        a[0] + ab[0] * v + ac[0] * w,
+        a[1] + ab[1] * v + ac[1] * w,
+        a[2] + ab[2] * v + ac[2] * w,
+    ]
+}
+
+fn lerp(a: &[f64; 3], b: &[f64; 3], t: f64) -> [f64; 3] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, a[2] + (b[2] - a[2]) * t]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_cube() -> MeshData {
+        let (vertices, indices, normals, _) = crate::create_mesh_from_points(&[[0.0, 0.0, 0.0], [1.0, 1.0, 1.0]]);
+        let face_groups = (0..6)
+            .map(|i| crate::FaceGroup {
+                face_id: i,
+                face_type: "planar".to_string(),
+                start_index: i * 6,
+                triangle_count: 2,
+                center: [0.5, 0.5, 0.5],
+                cullable: false,
+            })
+            .collect();
+        MeshData { vertices, indices, normals, face_groups, triangle_face_ids: Vec::new() }
+    }
+
+    #[test]
+    fn test_pick_hits_front_face() {
+        let mesh = unit_cube();
+        let hit = pick_face(&mesh, [0.5, 0.5, 5.0], [0.0, 0.0, -1.0]).unwrap();
+        assert!((hit.distance - 4.0).abs() < 1e-6);
+        assert_eq!(hit.face_id, 1); // +Z face
+    }
+
+    #[test]
+    fn test_face_info_reports_area_normal_and_neighbours() {
+        let mesh = unit_cube();
+        assert_eq!(triangle_face_ids(&mesh), vec![0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5]);
+
+        let info = face_info(&mesh, 1).unwrap();
+        assert!((info.area - 1.0).abs() < 1e-9);
+        assert_eq!(info.normal, Some([0.0, 0.0, 1.0]));
+        assert_eq!(info.adjacent_faces, vec![2, 3, 4, 5]);
+        assert!(face_info(&mesh, 9).is_none());
+    }
+
+    #[test]
+    fn test_section_properties_of_cube_and_hollow_square() {
+        let mesh = unit_cube();
+        let slice = slice_mesh(&mesh, SlicePlane { origin: [0.0, 0.0, 0.5], normal: [0.0, 0.0, 1.0] });
+        let section = section_properties(&slice);
+        assert!((section.area - 1.0).abs() < 1e-9);
+        assert!(distance(&section.centroid, &[0.5, 0.5, 0.5]) < 1e-9);
+        assert!((section.iuu - 1.0 / 12.0).abs() < 1e-9 && (section.ivv - 1.0 / 12.0).abs() < 1e-9);
+
+        // 4x4 square with a clockwise 2x2 hole: area 12, I = (4^4 - 2^4) / 12 = 20
+        let square = |h: f64| vec![[-h, -h], [h, -h], [h, h], [-h, h]];
+        let mut hole = square(1.0);
+        hole.reverse();
+        let [area, _, _, iuu, ivv, iuv] = section_moments(&[square(2.0), hole]);
+        assert!((area - 12.0).abs() < 1e-9);
+        assert!((iuu - 20.0).abs() < 1e-9 && (ivv - 20.0).abs() < 1e-9 && iuv.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_projected_area_of_cube() {
+        let mesh = unit_cube();
+        let face_on = projected_area(&mesh, [0.0, 0.0, 1.0], 256).unwrap();
+        assert!((face_on.area - 1.0).abs() < 0.02);
+
+        // Looking down a body diagonal the silhouette is a hexagon of area sqrt(3)
+        let diagonal = projected_area(&mesh, [1.0, 1.0, 1.0], 512).unwrap();
+        assert!((diagonal.area - 3f64.sqrt()).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_slice_cube_gives_closed_square() {
+        let mesh = unit_cube();
+        let result = slice_mesh(&mesh, SlicePlane { origin: [0.0, 0.0, 0.5], normal: [0.0, 0.0, 1.0] });
+        assert_eq!(result.polylines.len(), 1);
+        assert!(result.polylines[0].closed);
+        assert_eq!(result.segment_count, 8);
+    }
+
+    #[test]
+    fn test_min_distance_between_offset_cubes() {
+        let a = TriangleBvh::build(&unit_cube());
+        let (vertices, indices, normals, _) = crate::create_mesh_from_points(&[[3.0, 0.5, 2.0], [4.0, 1.5, 3.0]]);
+        let b = TriangleBvh::build(&MeshData { vertices, indices, normals, face_groups: Vec::new(), triangle_face_ids: Vec::new() });
+
+        // Nearest features are the edge x=1,z=1 of A and the edge x=3,z=2 of B
+        let result = a.min_distance(&b).unwrap();
+        assert!((result.distance - 5f64.sqrt()).abs() < 1e-9);
+        assert!((result.point_a[0] - 1.0).abs() < 1e-9 && (result.point_a[2] - 1.0).abs() < 1e-9);
+        assert!((result.point_b[0] - 3.0).abs() < 1e-9 && (result.point_b[2] - 2.0).abs() < 1e-9);
+
+        let overlapping = a.min_distance(&TriangleBvh::build(&unit_cube())).unwrap();
+        assert_eq!(overlapping.distance, 0.0);
+    }
+
+    #[test]
+    fn test_closest_point_on_cube() {
+        let bvh = TriangleBvh::build(&unit_cube());
+        let hit = bvh.closest_point(&[0.5, 0.5, 3.0]).unwrap();
+        assert!((hit.distance - 2.0).abs() < 1e-6);
+        assert!((hit.point[2] - 1.0).abs() < 1e-6);
+
+        // Inside points find the nearest wall
+        let inside = bvh.closest_point(&[0.5, 0.9, 0.5]).unwrap();
+        assert!((inside.distance - 0.1).abs() < 1e-6);
+        assert!((bvh.signed_distance(&[0.5, 0.9, 0.5]).unwrap() + 0.1).abs() < 1e-6);
+        assert!((bvh.signed_distance(&[0.5, 0.5, 3.0]).unwrap() - 2.0).abs() < 1e-6);
+    }
+}
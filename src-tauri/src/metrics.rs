@@ -0,0 +1,338 @@
+// Opt-in performance metrics for backend commands
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::Runtime;
+
+use crate::storage::{load_json, save_json, unix_timestamp};
+
+/// Samples kept on disk; older samples are dropped first
+const MAX_SAMPLES: usize = 500;
+
+/// Number of slowest samples listed in the report
+const SLOWEST_SAMPLES: usize = 10;
+
+/// New samples are written to disk at most this often, not once per command
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// One timed command invocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandSample {
+    pub command: String,
+    pub timestamp: u64,
+    pub duration_ms: f64,
+    pub input_bytes: usize,
+    pub memory_before: u64,        // Resident memory in bytes
+    pub memory_after: u64,
+}
+
+/// Aggregated statistics for a command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandStats {
+    pub command: String,
+    pub count: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+    pub max_memory_growth: i64,    // Largest resident memory increase (bytes)
+    pub max_input_bytes: usize,
+}
+
+/// Performance report returned to the frontend
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PerformanceReport {
+    pub enabled: bool,
+    pub sample_count: usize,
+    pub current_memory: u64,
+    pub commands: Vec<CommandStats>,     // Sorted by total time, hotspots first
+    pub slowest: Vec<CommandSample>,
+}
+
+/// Persisted metrics file contents
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MetricsFile {
+    enabled: bool,
+    samples: Vec<CommandSample>,
+}
+
+/// Process-wide metrics recorder
+struct MetricsRecorder {
+    enabled: AtomicBool,
+    samples: Mutex<Vec<CommandSample>>,
+    dirty: AtomicBool,             // Samples recorded since the last write
+    pending_input: Mutex<HashMap<String, VecDeque<usize>>>,  // Argument bytes of deferred commands, oldest first
+    path: PathBuf,
+}
+
+static RECORDER: OnceLock<MetricsRecorder> = OnceLock::new();
+
+/// Load persisted settings and samples and start the periodic flush; metrics stay off until the
+/// user opts in
+pub fn init_metrics(path: PathBuf) {
+    let file: MetricsFile = load_json(&path);
+    let _ = RECORDER.set(MetricsRecorder {
+        enabled: AtomicBool::new(file.enabled),
+        samples: Mutex::new(file.samples),
+        dirty: AtomicBool::new(false),
+        pending_input: Mutex::new(HashMap::new()),
+        path,
+    });
+    std::thread::spawn(|| loop {
+        std::thread::sleep(FLUSH_INTERVAL);
+        flush_metrics();
+    });
+}
+
+/// Write samples recorded since the last flush; also called on exit
+pub fn flush_metrics() {
+    let Some(recorder) = RECORDER.get() else { return };
+    if !recorder.dirty.swap(false, Ordering::Relaxed) {
+        return;
+    }
+    if let Ok(samples) = recorder.samples.lock() {
+        persist(recorder, &samples);
+    }
+}
+
+fn recorder() -> Option<&'static MetricsRecorder> {
+    RECORDER.get().filter(|r| r.enabled.load(Ordering::Relaxed))
+}
+
+/// Guard that records a command sample when dropped
+pub struct CommandTimer {
+    command: String,
+    input_bytes: usize,
+    memory_before: u64,
+    start: Instant,
+}
+
+fn start_timer(command: &str, input_bytes: usize) -> CommandTimer {
+    CommandTimer {
+        command: command.to_string(),
+        input_bytes,
+        memory_before: process_memory(),
+        start: Instant::now(),
+    }
+}
+
+/// Start timing a deferred command where its work runs; a no-op unless metrics are enabled.
+/// The argument size is the oldest one `instrument` noted for the command, so overlapping calls
+/// each take their own
+pub fn track(command: &str) -> Option<CommandTimer> {
+    let recorder = recorder()?;
+    let input_bytes = recorder.pending_input.lock().ok()
+        .and_then(|mut pending| pending.get_mut(command).and_then(VecDeque::pop_front))
+        .unwrap_or(0);
+    Some(start_timer(command, input_bytes))
+}
+
+/// Bytes of string and binary data in a command's arguments
+fn payload_bytes(body: &InvokeBody) -> usize {
+    fn json_bytes(value: &Value) -> usize {
+        match value {
+            Value::String(s) => s.len(),
+            Value::Array(items) => items.iter().map(json_bytes).sum(),
+            Value::Object(fields) => fields.values().map(json_bytes).sum(),
+            _ => 0,
+        }
+    }
+    match body {
+        InvokeBody::Json(value) => json_bytes(value),
+        InvokeBody::Raw(bytes) => bytes.len(),
+    }
+}
+
+/// Time every IPC command in one place. Synchronous commands finish inside the handler; commands
+/// in `deferred` return before their work is done and are timed by `track` where it runs
+/// (`run_job` for jobs)
+pub fn instrument<R: Runtime>(
+    deferred: &'static [&'static str],
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let Some(recorder) = recorder() else { return handler(invoke) };
+        let command = invoke.message.command().to_string();
+        let input_bytes = payload_bytes(invoke.message.payload());
+        if deferred.contains(&command.as_str()) {
+            if let Ok(mut pending) = recorder.pending_input.lock() {
+                pending.entry(command).or_default().push_back(input_bytes);
+            }
+            return handler(invoke);
+        }
+        let _timer = start_timer(&command, input_bytes);
+        handler(invoke)
+    }
+}
+
+impl Drop for CommandTimer {
+    fn drop(&mut self) {
+        let Some(recorder) = recorder() else { return };
+
+        let sample = CommandSample {
+            command: std::mem::take(&mut self.command),
+            timestamp: unix_timestamp(),
+            duration_ms: self.start.elapsed().as_secs_f64() * 1000.0,
+            input_bytes: self.input_bytes,
+            memory_before: self.memory_before,
+            memory_after: process_memory(),
+        };
+        tracing::debug!(command = %sample.command, duration_ms = sample.duration_ms, "command timed");
+
+        if let Ok(mut samples) = recorder.samples.lock() {
+            samples.push(sample);
+            if samples.len() > MAX_SAMPLES {
+                let excess = samples.len() - MAX_SAMPLES;
+                samples.drain(..excess);
+            }
+            recorder.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn persist(recorder: &MetricsRecorder, samples: &[CommandSample]) {
+    let file = MetricsFile {
+        enabled: recorder.enabled.load(Ordering::Relaxed),
+        samples: samples.to_vec(),
+    };
+    if let Err(e) = save_json(&recorder.path, &file) {
+        tracing::warn!(error = %e, "failed to persist metrics");
+    }
+}
+
+/// Resident memory of this process in bytes (0 if unavailable)
+pub fn process_memory() -> u64 {
+    let Ok(pid) = sysinfo::get_current_pid() else { return 0 };
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        false,
+        ProcessRefreshKind::nothing().with_memory(),
+    );
+    system.process(pid).map(|p| p.memory()).unwrap_or(0)
+}
+
+/// Aggregate samples per command
+fn build_report(enabled: bool, samples: &[CommandSample]) -> PerformanceReport {
+    let mut by_command: BTreeMap<&str, Vec<&CommandSample>> = BTreeMap::new();
+    for sample in samples {
+        by_command.entry(sample.command.as_str()).or_default().push(sample);
+    }
+
+    let mut commands: Vec<CommandStats> = by_command.into_iter()
+        .map(|(command, samples)| {
+            let mut durations: Vec<f64> = samples.iter().map(|s| s.duration_ms).collect();
+            durations.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let total_ms: f64 = durations.iter().sum();
+            let percentile = |p: f64| durations[((durations.len() - 1) as f64 * p).round() as usize];
+
+            CommandStats {
+                command: command.to_string(),
+                count: durations.len(),
+                mean_ms: total_ms / durations.len() as f64,
+                p50_ms: percentile(0.5),
+                p95_ms: percentile(0.95),
+                max_ms: durations[durations.len() - 1],
+                total_ms,
+                max_memory_growth: samples.iter()
+                    .map(|s| s.memory_after as i64 - s.memory_before as i64)
+                    .max()
+                    .unwrap_or(0),
+                max_input_bytes: samples.iter().map(|s| s.input_bytes).max().unwrap_or(0),
+            }
+        })
+        .collect();
+    commands.sort_by(|a, b| b.total_ms.partial_cmp(&a.total_ms).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut slowest = samples.to_vec();
+    slowest.sort_by(|a, b| b.duration_ms.partial_cmp(&a.duration_ms).unwrap_or(std::cmp::Ordering::Equal));
+    slowest.truncate(SLOWEST_SAMPLES);
+
+    PerformanceReport {
+        enabled,
+        sample_count: samples.len(),
+        current_memory: 0,
+        commands,
+        slowest,
+    }
+}
+
+/// Opt in or out of metrics collection
+#[tauri::command]
+pub fn set_metrics_enabled(enabled: bool) -> Result<(), String> {
+    let recorder = RECORDER.get().ok_or("Metrics not initialized")?;
+    recorder.enabled.store(enabled, Ordering::Relaxed);
+    let samples = recorder.samples.lock().map_err(|_| "Metrics state poisoned".to_string())?;
+    persist(recorder, &samples);
+    tracing::info!(enabled, "performance metrics toggled");
+    Ok(())
+}
+
+/// Summarize collected command timings and memory growth
+#[tauri::command]
+pub fn get_performance_report() -> Result<PerformanceReport, String> {
+    let recorder = RECORDER.get().ok_or("Metrics not initialized")?;
+    let samples = recorder.samples.lock().map_err(|_| "Metrics state poisoned".to_string())?;
+    let mut report = build_report(recorder.enabled.load(Ordering::Relaxed), &samples);
+    report.current_memory = process_memory();
+    Ok(report)
+}
+
+/// Delete all collected samples
+#[tauri::command]
+pub fn clear_performance_metrics() -> Result<(), String> {
+    let recorder = RECORDER.get().ok_or("Metrics not initialized")?;
+    let mut samples = recorder.samples.lock().map_err(|_| "Metrics state poisoned".to_string())?;
+    samples.clear();
+    recorder.dirty.store(false, Ordering::Relaxed);
+    persist(recorder, &samples);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(command: &str, duration_ms: f64) -> CommandSample {
+        CommandSample {
+            command: command.to_string(),
+            timestamp: 0,
+            duration_ms,
+            input_bytes: 100,
+            memory_before: 1000,
+            memory_after: 1500,
+        }
+    }
+
+    #[test]
+    fn test_report_orders_hotspots() {
+        let samples = vec![
+            sample("parse_step_mesh", 90.0),
+            sample("parse_step_mesh", 10.0),
+            sample("capture_screen", 5.0),
+        ];
+        let report = build_report(true, &samples);
+
+        assert_eq!(report.commands[0].command, "parse_step_mesh");
+        assert_eq!(report.commands[0].count, 2);
+        assert!((report.commands[0].mean_ms - 50.0).abs() < 1e-9);
+        assert_eq!(report.commands[0].max_memory_growth, 500);
+        assert!((report.slowest[0].duration_ms - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_track_is_noop_when_uninitialized() {
+        assert!(track("parse_step_mesh").is_none());
+
+        let body = InvokeBody::Json(serde_json::json!({ "content": "ISO-10303-21;", "options": { "name": "ab", "n": 3 } }));
+        assert_eq!(payload_bytes(&body), 15);
+    }
+}
@@ -0,0 +1,594 @@
+// Backend-held model sessions: parse once, query by handle
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::assembly_parser::{parse_assembly_text, AssemblyParseResult, AssemblySummary, FacePage, ParsedFace, ParsedPart};
+use crate::clipping_planes::ClippingPlane;
+use crate::coordinate_systems::CoordinateSystem;
+use crate::gdt::GdtModel;
+use crate::interface_detection::{
+    find_mating_interfaces, transform_direction, transform_point, DetectionParams, InterfaceDetectionResult,
+};
+use crate::journal::{Edit, EditJournal};
+use crate::large_files::PartialAnalysis;
+use crate::linalg::{distance, dot, sub};
+use crate::mesh_query::{
+    face_info, pick_face as pick_mesh_face, projected_area, section_properties, slice_mesh, FaceInfo, PickResult,
+    ProjectedArea, SectionProperties, SlicePlane, SliceResult, TriangleBvh,
+};
+use crate::part_display::PartDisplayState;
+use crate::part_transforms::{apply_part_transforms, PartTransformStore};
+use crate::recent_files::{hash_content, RecentFilesState};
+use crate::snapshot::{render_thumbnail, THUMBNAIL_SIZE};
+use crate::scan::ScanData;
+use crate::storage::unix_timestamp;
+use crate::validation::validate;
+use crate::{BoundingBox, FeatureInfo, MeshData, StepAnalysisResult, StepMeshResult, TopologyInfo};
+
+/// A STEP model parsed once and kept in memory
+pub struct LoadedModel {
+    pub handle: String,
+    pub filename: String,
+    pub path: Option<String>,
+    pub content: String,
+    pub content_hash: String,  // SHA-256 of the content at import
+    pub analysis: StepAnalysisResult,
+    pub mesh: Option<MeshData>,
+    pub bounding_box: Option<BoundingBox>,
+    pub mesh_error: Option<String>,
+    pub mesh_fallback: Option<String>,  // B-rep kernel error when the mesh is bounding boxes
+    pub assembly: AssemblyParseResult,
+    pub interfaces: Option<InterfaceDetectionResult>,
+    pub gdt: GdtModel,
+    pub scan: Option<ScanData>,
+    pub coordinate_systems: Vec<CoordinateSystem>,
+    pub display: PartDisplayState,
+    pub clipping_planes: Vec<ClippingPlane>,
+    pub loaded_at: u64,
+}
+
+/// Summary returned when a model is loaded or listed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub handle: String,
+    pub filename: String,
+    pub path: Option<String>,
+    pub bytes: usize,
+    pub content_hash: String,
+    pub topology: Option<TopologyInfo>,
+    pub features: Option<FeatureInfo>,
+    pub bounding_box: Option<BoundingBox>,
+    pub has_mesh: bool,
+    pub mesh_error: Option<String>,
+    pub triangle_count: usize,
+    pub part_count: usize,
+    pub partial: Option<PartialAnalysis>,  // Set when meshing and assembly parsing were skipped
+    pub loaded_at: u64,
+}
+
+/// Face of a part within a loaded model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaceRef {
+    pub part_id: String,
+    pub face_id: i64,
+}
+
+/// Parsed face resolved to world coordinates
+#[derive(Debug, Clone)]
+pub struct WorldFace {
+    pub face: ParsedFace,
+    pub center: [f64; 3],
+    pub normal: [f64; 3],
+    pub axis: Option<[f64; 3]>,
+}
+
+/// Minimum distance between two parts in world coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartClearance {
+    pub part_a_id: String,
+    pub part_b_id: String,
+    pub distance: f64,
+    pub point_a: [f64; 3],
+    pub point_b: [f64; 3],
+    pub intersecting: bool,
+}
+
+/// Measurement between two faces in world coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FaceMeasurement {
+    pub center_distance: f64,
+    pub angle_degrees: f64,           // Between face normals
+    pub parallel: bool,
+    pub plane_separation: Option<f64>, // Normal distance, parallel planar faces only
+    pub radius_a: Option<f64>,
+    pub radius_b: Option<f64>,
+}
+
+impl LoadedModel {
+    /// Parse content once into analysis, mesh and assembly data
+    pub fn parse(handle: String, content: String, filename: String, path: Option<String>) -> LoadedModel {
+        let analysis = crate::analyze_step_text(&content, &filename);
+        let (mesh, bounding_box, mesh_error, mesh_fallback) = match crate::parse_step_to_mesh(&content, &analysis, &Default::default()) {
+            Ok((mesh, bbox, fallback)) => (Some(mesh), Some(bbox), None, fallback),
+            Err(e) => (None, None, Some(e), None),
+        };
+        let assembly = parse_assembly_text(&content, &filename);
+
+        LoadedModel {
+            handle,
+            filename,
+            path,
+            content_hash: hash_content(content.as_bytes()),
+            content,
+            analysis,
+            mesh,
+            bounding_box,
+            mesh_error,
+            mesh_fallback,
+            assembly,
+            interfaces: None,
+            gdt: GdtModel::default(),
+            scan: None,
+            coordinate_systems: Vec::new(),
+            display: PartDisplayState::default(),
+            clipping_planes: Vec::new(),
+            loaded_at: unix_timestamp(),
+        }
+    }
+
+    /// Summary for the frontend
+    pub fn info(&self) -> ModelInfo {
+        ModelInfo {
+            handle: self.handle.clone(),
+            filename: self.filename.clone(),
+            path: self.path.clone(),
+            bytes: self.content.len(),
+            content_hash: self.content_hash.clone(),
+            topology: self.analysis.topology.clone(),
+            features: self.analysis.features.clone(),
+            bounding_box: self.bounding_box.clone(),
+            has_mesh: self.mesh.is_some(),
+            mesh_error: self.mesh_error.clone(),
+            triangle_count: self.mesh.as_ref().map(|m| m.indices.len() / 3).unwrap_or(0),
+            part_count: self.assembly.parts.len(),
+            partial: self.analysis.partial.clone(),
+            loaded_at: self.loaded_at,
+        }
+    }
+
+    /// Mesh result in the shape returned by parse_step_mesh
+    pub fn mesh_result(&self) -> StepMeshResult {
+        StepMeshResult {
+            success: self.mesh.is_some(),
+            error: self.mesh_error.as_ref()
+                .map(|e| format!("Mesh generation failed: {}. Basic analysis available.", e)),
+            filename: Some(self.filename.clone()),
+            mesh: self.mesh.clone(),
+            bounding_box: self.bounding_box.clone(),
+            topology: self.analysis.topology.clone(),
+            features: self.analysis.features.clone(),
+            partial: self.analysis.partial.clone(),
+            fallback: self.mesh_fallback.clone(),
+        }
+    }
+
+    /// Look up a face and resolve it to world coordinates
+    pub fn world_face(&self, face: &FaceRef) -> Result<WorldFace, String> {
+        let part = self.assembly.parts.iter()
+            .find(|p| p.id == face.part_id)
+            .ok_or_else(|| format!("Unknown part: {}", face.part_id))?;
+        let parsed = part.faces.iter()
+            .find(|f| f.id == face.face_id)
+            .ok_or_else(|| format!("Unknown face {} on part {}", face.face_id, face.part_id))?;
+
+        Ok(WorldFace {
+            face: parsed.clone(),
+            center: transform_point(&parsed.center, &part.transform),
+            normal: transform_direction(&parsed.normal, &part.transform),
+            axis: parsed.axis.map(|axis| transform_direction(&axis, &part.transform)),
+        })
+    }
+}
+
+/// Managed store of loaded models
+#[derive(Default)]
+pub struct ModelStore {
+    models: Mutex<HashMap<String, LoadedModel>>,
+    next_id: Mutex<u64>,
+}
+
+impl ModelStore {
+    fn next_handle(&self) -> Result<String, String> {
+        let mut next_id = self.next_id.lock().map_err(|_| "Model store poisoned".to_string())?;
+        *next_id += 1;
+        Ok(format!("model-{}", *next_id))
+    }
+
+    /// Add a parsed model and return its summary
+    pub fn insert(&self, model: LoadedModel) -> Result<ModelInfo, String> {
+        let info = model.info();
+        let mut models = self.models.lock().map_err(|_| "Model store poisoned".to_string())?;
+        models.insert(model.handle.clone(), model);
+        Ok(info)
+    }
+
+    /// Run a query against a loaded model
+    pub fn with_model<R>(&self, handle: &str, f: impl FnOnce(&LoadedModel) -> R) -> Result<R, String> {
+        let models = self.models.lock().map_err(|_| "Model store poisoned".to_string())?;
+        let model = models.get(handle).ok_or_else(|| format!("Unknown model handle: {}", handle))?;
+        Ok(f(model))
+    }
+
+    /// Run an update against a loaded model
+    pub fn with_model_mut<R>(&self, handle: &str, f: impl FnOnce(&mut LoadedModel) -> R) -> Result<R, String> {
+        let mut models = self.models.lock().map_err(|_| "Model store poisoned".to_string())?;
+        let model = models.get_mut(handle).ok_or_else(|| format!("Unknown model handle: {}", handle))?;
+        Ok(f(model))
+    }
+
+    /// Run a query against every loaded model, oldest first
+    pub fn map_models<R>(&self, mut f: impl FnMut(&LoadedModel) -> R) -> Result<Vec<R>, String> {
+        let models = self.models.lock().map_err(|_| "Model store poisoned".to_string())?;
+        let mut loaded: Vec<&LoadedModel> = models.values().collect();
+        loaded.sort_by_key(|m| m.loaded_at);
+        Ok(loaded.into_iter().map(&mut f).collect())
+    }
+
+    fn remove(&self, handle: &str) -> Result<bool, String> {
+        let mut models = self.models.lock().map_err(|_| "Model store poisoned".to_string())?;
+        Ok(models.remove(handle).is_some())
+    }
+
+    fn list(&self) -> Result<Vec<ModelInfo>, String> {
+        let models = self.models.lock().map_err(|_| "Model store poisoned".to_string())?;
+        let mut infos: Vec<ModelInfo> = models.values().map(|m| m.info()).collect();
+        infos.sort_by_key(|m| m.loaded_at);
+        Ok(infos)
+    }
+}
+
+/// Parse a STEP model once and keep it in the backend; later queries pass the handle
+#[tauri::command]
+pub fn load_model(
+    state: State<'_, ModelStore>,
+    transforms: State<'_, PartTransformStore>,
+    recent: State<'_, RecentFilesState>,
+    content: Option<String>,
+    path: Option<String>,
+    filename: Option<String>,
+) -> Result<ModelInfo, String> {
+    let (content, filename) = match (content, &path) {
+        (Some(content), _) => (content, filename.unwrap_or_else(|| "model.step".to_string())),
+        (None, Some(path)) => {
+            let content = std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            let name = filename.unwrap_or_else(|| {
+                Path::new(path).file_name()
+                    .and_then(|n| n.to_str())
+                    .map(|s| s.to_string())
+                    .unwrap_or_default()
+            });
+            (content, name)
+        }
+        (None, None) => return Err("Either content or path is required".to_string()),
+    };
+
+    let handle = state.next_handle()?;
+    let mut model = LoadedModel::parse(handle.clone(), content, filename, path);
+    let overrides = transforms.for_model(&model);
+    apply_part_transforms(&mut model, &overrides);
+
+    if !model.analysis.success {
+        return Err(model.analysis.error.unwrap_or_else(|| "Invalid STEP file".to_string()));
+    }
+
+    // Refresh the history thumbnail on import so the recent list shows the current shape
+    if let (Some(path), Some(mesh)) = (&model.path, &model.mesh) {
+        if let Some(thumbnail) = render_thumbnail(mesh, THUMBNAIL_SIZE) {
+            if let Err(e) = recent.set_thumbnail(path, thumbnail) {
+                tracing::warn!(error = %e, "failed to store recent file thumbnail");
+            }
+        }
+    }
+
+    tracing::info!(handle = %handle, filename = %model.filename, "model loaded");
+    state.insert(model)
+}
+
+/// Release a loaded model
+#[tauri::command]
+pub fn unload_model(state: State<'_, ModelStore>, handle: String) -> Result<bool, String> {
+    let removed = state.remove(&handle)?;
+    tracing::info!(handle = %handle, removed, "model unloaded");
+    Ok(removed)
+}
+
+/// List loaded models, oldest first
+#[tauri::command]
+pub fn list_models(state: State<'_, ModelStore>) -> Result<Vec<ModelInfo>, String> {
+    state.list()
+}
+
+/// Mesh of a loaded model for the viewer
+#[tauri::command]
+pub fn get_model_mesh(state: State<'_, ModelStore>, handle: String) -> Result<StepMeshResult, String> {
+    state.with_model(&handle, |model| model.mesh_result())
+}
+
+/// Parts returned per page when no limit is given
+const DEFAULT_PART_LIMIT: usize = 500;
+
+/// Faces returned per page when no limit is given
+const DEFAULT_FACE_LIMIT: usize = 1000;
+
+/// Parsed assembly parts of a loaded model, without faces; fetch those with get_part_faces
+#[tauri::command]
+pub fn get_model_parts(
+    state: State<'_, ModelStore>,
+    handle: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<AssemblySummary, String> {
+    state.with_model(&handle, |model| {
+        model.assembly.summary(offset.unwrap_or(0), limit.unwrap_or(DEFAULT_PART_LIMIT))
+    })
+}
+
+/// Faces of one part of a loaded model, a page at a time
+#[tauri::command]
+pub fn get_part_faces(
+    state: State<'_, ModelStore>,
+    handle: String,
+    part_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<FacePage, String> {
+    state.with_model(&handle, |model| {
+        model.assembly.face_page(&part_id, offset.unwrap_or(0), limit.unwrap_or(DEFAULT_FACE_LIMIT))
+    })?
+}
+
+/// Detect mating interfaces on a loaded model; the result is kept with the model
+#[tauri::command]
+pub fn detect_model_interfaces(
+    state: State<'_, ModelStore>,
+    journal: State<'_, EditJournal>,
+    handle: String,
+    proximity_threshold: f64,
+    normal_threshold: f64,
+) -> Result<InterfaceDetectionResult, String> {
+    validate(&DetectionParams { proximity_threshold, normal_threshold, ..DetectionParams::default() })?;
+    let (result, before) = state.with_model_mut(&handle, |model| {
+        let result = find_mating_interfaces(model.assembly.parts.clone(), proximity_threshold, normal_threshold);
+        (result.clone(), model.interfaces.replace(result))
+    })?;
+    journal.record("Detect interfaces", Edit::Interfaces { handle, before, after: Some(result.clone()) });
+    Ok(result)
+}
+
+/// Rename a part of a loaded model
+#[tauri::command]
+pub fn rename_part(
+    state: State<'_, ModelStore>,
+    journal: State<'_, EditJournal>,
+    handle: String,
+    part_id: String,
+    name: String,
+) -> Result<ParsedPart, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Part name must not be empty".to_string());
+    }
+    let (part, before) = state.with_model_mut(&handle, |model| {
+        let part = model.assembly.parts.iter_mut()
+            .find(|p| p.id == part_id)
+            .ok_or_else(|| format!("Unknown part: {}", part_id))?;
+        let before = std::mem::replace(&mut part.name, name.clone());
+        Ok::<_, String>((part.clone(), before))
+    })??;
+    journal.record(format!("Rename {}", before), Edit::PartName { handle, part_id, before, after: name });
+    Ok(part)
+}
+
+/// Pick the face under a ray (viewer click)
+#[tauri::command]
+pub fn pick_face(
+    state: State<'_, ModelStore>,
+    handle: String,
+    origin: [f64; 3],
+    direction: [f64; 3],
+) -> Result<Option<PickResult>, String> {
+    state.with_model(&handle, |model| {
+        model.mesh.as_ref().and_then(|mesh| pick_mesh_face(mesh, origin, direction))
+    })
+}
+
+/// Details of a mesh face for the viewer's selection panel
+#[tauri::command]
+pub fn get_face_info(state: State<'_, ModelStore>, handle: String, face_id: u32) -> Result<FaceInfo, String> {
+    state.with_model(&handle, |model| {
+        let mesh = model.mesh.as_ref().ok_or_else(|| "Model has no mesh".to_string())?;
+        face_info(mesh, face_id).ok_or_else(|| format!("Unknown face: {}", face_id))
+    })?
+}
+
+/// Section a loaded model with a plane, given inline or as a saved clipping plane name
+#[tauri::command]
+pub fn slice_model(
+    state: State<'_, ModelStore>,
+    handle: String,
+    plane: Option<SlicePlane>,
+    plane_name: Option<String>,
+) -> Result<SliceResult, String> {
+    state.with_model(&handle, |model| {
+        let plane = model.resolve_plane(plane, plane_name.as_deref())?;
+        model.mesh.as_ref()
+            .map(|mesh| slice_mesh(mesh, plane))
+            .ok_or_else(|| "Model has no mesh".to_string())
+    })?
+}
+
+/// Area, centroid and second moments of a model section
+#[tauri::command]
+pub fn compute_section_properties(
+    state: State<'_, ModelStore>,
+    handle: String,
+    plane: Option<SlicePlane>,
+    plane_name: Option<String>,
+) -> Result<SectionProperties, String> {
+    state.with_model(&handle, |model| {
+        let plane = model.resolve_plane(plane, plane_name.as_deref())?;
+        model.mesh.as_ref()
+            .map(|mesh| section_properties(&slice_mesh(mesh, plane)))
+            .ok_or_else(|| "Model has no mesh".to_string())
+    })?
+}
+
+/// Measure distance and angle between two faces
+#[tauri::command]
+pub fn measure_faces(
+    state: State<'_, ModelStore>,
+    handle: String,
+    face_a: FaceRef,
+    face_b: FaceRef,
+) -> Result<FaceMeasurement, String> {
+    state.with_model(&handle, |model| {
+        let a = model.world_face(&face_a)?;
+        let b = model.world_face(&face_b)?;
+        Ok(measure_between(&a, &b))
+    })?
+}
+
+/// Part geometry in world coordinates; parts are tessellated as their bounding boxes for now
+pub(crate) fn part_world_mesh(part: &ParsedPart) -> Option<MeshData> {
+    let bbox = part.bounding_box.as_ref()?;
+    let (mut vertices, indices, mut normals, _) = crate::create_mesh_from_points(&[bbox.min, bbox.max]);
+    for v in vertices.chunks_exact_mut(3) {
+        let p = transform_point(&[v[0] as f64, v[1] as f64, v[2] as f64], &part.transform);
+        v.copy_from_slice(&p.map(|c| c as f32));
+    }
+    for n in normals.chunks_exact_mut(3) {
+        let d = transform_direction(&[n[0] as f64, n[1] as f64, n[2] as f64], &part.transform);
+        n.copy_from_slice(&d.map(|c| c as f32));
+    }
+    Some(MeshData { vertices, indices, normals, face_groups: Vec::new(), triangle_face_ids: Vec::new() })
+}
+
+/// Exact triangle-to-triangle clearance between two parts
+#[tauri::command]
+pub fn measure_part_clearance(
+    state: State<'_, ModelStore>,
+    handle: String,
+    part_a: String,
+    part_b: String,
+) -> Result<PartClearance, String> {
+    state.with_model(&handle, |model| {
+        let bvh = |id: &str| {
+            let part = model.assembly.parts.iter()
+                .find(|p| p.id == id)
+                .ok_or_else(|| format!("Unknown part: {}", id))?;
+            part_world_mesh(part)
+                .map(|mesh| TriangleBvh::build(&mesh))
+                .ok_or_else(|| format!("Part {} has no geometry", id))
+        };
+        let result = bvh(&part_a)?.min_distance(&bvh(&part_b)?)
+            .ok_or_else(|| "Parts have no triangles".to_string())?;
+
+        Ok(PartClearance {
+            part_a_id: part_a.clone(),
+            part_b_id: part_b.clone(),
+            distance: result.distance,
+            point_a: result.point_a,
+            point_b: result.point_b,
+            intersecting: result.distance == 0.0,
+        })
+    })?
+}
+
+/// Silhouette area of a part, or the whole model when no part is given, projected along `normal`
+#[tauri::command]
+pub fn compute_projected_area(
+    state: State<'_, ModelStore>,
+    handle: String,
+    part_id: Option<String>,
+    normal: [f64; 3],
+    resolution: Option<usize>,
+) -> Result<ProjectedArea, String> {
+    let resolution = resolution.unwrap_or(512);
+    state.with_model(&handle, |model| {
+        let part_mesh;
+        let mesh = match &part_id {
+            Some(id) => {
+                let part = model.assembly.parts.iter()
+                    .find(|p| &p.id == id)
+                    .ok_or_else(|| format!("Unknown part: {}", id))?;
+                part_mesh = part_world_mesh(part).ok_or_else(|| format!("Part {} has no geometry", id))?;
+                &part_mesh
+            }
+            None => model.mesh.as_ref().ok_or_else(|| "Model has no mesh".to_string())?,
+        };
+        projected_area(mesh, normal, resolution).ok_or_else(|| "Projection direction or mesh is degenerate".to_string())
+    })?
+}
+
+pub(crate) fn measure_between(a: &WorldFace, b: &WorldFace) -> FaceMeasurement {
+    let cos = dot(&a.normal, &b.normal).clamp(-1.0, 1.0);
+    let parallel = cos.abs() > 0.9999;
+
+    let plane_separation = (parallel && a.face.face_type == "planar" && b.face.face_type == "planar")
+        .then(|| dot(&sub(&b.center, &a.center), &a.normal).abs());
+
+    FaceMeasurement {
+        center_distance: distance(&a.center, &b.center),
+        angle_degrees: cos.acos().to_degrees(),
+        parallel,
+        plane_separation,
+        radius_a: a.face.radius,
+        radius_b: b.face.radius,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planar(center: [f64; 3], normal: [f64; 3]) -> WorldFace {
+        let face = ParsedFace {
+            id: 0,
+            face_type: "planar".to_string(),
+            normal,
+            center,
+            area: 1.0,
+            radius: None,
+            axis: None,
+            step_entity_id: None,
+            same_sense: None,
+            semi_angle: None,
+            apex: None,
+            minor_radius: None,
+        };
+        WorldFace { face, center, normal, axis: None }
+    }
+
+    #[test]
+    fn test_handles_are_unique() {
+        let store = ModelStore::default();
+        assert_eq!(store.next_handle().unwrap(), "model-1");
+        assert_eq!(store.next_handle().unwrap(), "model-2");
+        assert!(store.with_model("model-1", |_| ()).is_err());
+    }
+
+    #[test]
+    fn test_parallel_plane_separation() {
+        let a = planar([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]);
+        let b = planar([3.0, 0.0, 5.0], [0.0, 0.0, -1.0]);
+        let m = measure_between(&a, &b);
+        assert!(m.parallel);
+        assert!((m.plane_separation.unwrap() - 5.0).abs() < 1e-9);
+        assert!((m.angle_degrees - 180.0).abs() < 1e-6);
+    }
+}
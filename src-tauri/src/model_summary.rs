@@ -0,0 +1,197 @@
+// Dense text description of a loaded model for LLM prompts, sized to a token budget
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::State;
+
+use crate::features::model_holes;
+use crate::interface_detection::{find_mating_interfaces, DetectionParams};
+use crate::model_store::{LoadedModel, ModelStore};
+
+const DEFAULT_TOKEN_BUDGET: usize = 600;
+const MIN_TOKEN_BUDGET: usize = 40;
+const CHARS_PER_TOKEN: usize = 4;  // Rough average for English text with numbers
+
+/// Summary text and how much of the model it covers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelSummary {
+    pub text: String,
+    pub estimated_tokens: usize,
+    pub token_budget: usize,
+    pub truncated: bool,  // Some list items were left out to fit the budget
+}
+
+/// Heading line plus items, added in order while they fit
+struct Section {
+    heading: String,
+    items: Vec<String>,
+}
+
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Number with at most two decimals and no trailing zeros
+fn num(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
+
+fn dims(d: &[f64; 3]) -> String {
+    format!("{}x{}x{}", num(d[0]), num(d[1]), num(d[2]))
+}
+
+fn point(p: &[f64; 3]) -> String {
+    format!("({},{},{})", num(p[0]), num(p[1]), num(p[2]))
+}
+
+/// Header lines always go in; every section heading is reserved next, then items fill what is left
+fn pack(header: Vec<String>, sections: Vec<Section>, budget: usize) -> (String, bool) {
+    let mut lines = header;
+    let reserved: usize = sections.iter().map(|s| estimate_tokens(&s.heading) + 4).sum();
+    let used = estimate_tokens(&lines.join("\n"));
+    let mut remaining = budget.saturating_sub(used + reserved);
+    let mut truncated = false;
+
+    for section in sections {
+        lines.push(section.heading);
+        let total = section.items.len();
+        for (i, item) in section.items.into_iter().enumerate() {
+            let cost = estimate_tokens(&item) + 1;
+            if cost > remaining {
+                lines.push(format!("  +{} more", total - i));
+                truncated = true;
+                break;
+            }
+            remaining -= cost;
+            lines.push(format!("  {}", item));
+        }
+    }
+    (lines.join("\n"), truncated)
+}
+
+/// Overall size, solids, parts, holes and interfaces of a model as compact text
+pub fn summarize(model: &LoadedModel, token_budget: usize) -> ModelSummary {
+    let budget = token_budget.max(MIN_TOKEN_BUDGET);
+    let analysis = &model.analysis;
+
+    let mut header = vec![format!("Model {}", model.filename)];
+    if let Some(bbox) = model.bounding_box.as_ref().or(analysis.bounding_box.as_ref()) {
+        header.push(format!("Size {} mm, min {} max {}", dims(&bbox.dimensions), point(&bbox.min), point(&bbox.max)));
+    }
+    if let Some(t) = &analysis.topology {
+        header.push(format!(
+            "Topology {} solids, {} shells, {} faces, {} edges, {} vertices",
+            t.num_solids, t.num_shells, t.num_faces, t.num_edges, t.num_vertices
+        ));
+    }
+    if let Some(f) = &analysis.features {
+        header.push(format!("Faces {} planar, {} cylindrical, {} curved", f.planar_faces, f.cylindrical_faces, f.curved_faces));
+    }
+    if let (Some(volume), Some(area)) = (analysis.volume_estimate, analysis.surface_area_estimate) {
+        header.push(format!("Volume ~{} mm3, area ~{} mm2", num(volume), num(area)));
+    }
+    if let Some(partial) = &analysis.partial {
+        header.push(format!("Partial analysis: {}", partial.reason));
+    }
+
+    let parts = &model.assembly.parts;
+    let mut sections = Vec::new();
+    if !parts.is_empty() {
+        sections.push(Section {
+            heading: format!("Parts ({}):", parts.len()),
+            items: parts.iter()
+                .map(|p| match &p.bounding_box {
+                    Some(b) => format!("{} {} {} faces, {} mm at {}", p.id, p.name, p.faces.len(), dims(&b.dimensions), point(&b.min)),
+                    None => format!("{} {} {} faces", p.id, p.name, p.faces.len()),
+                })
+                .collect(),
+        });
+    }
+
+    // Holes of equal diameter on a part are listed together, largest groups first
+    let holes = model_holes(model);
+    if !holes.is_empty() {
+        let mut groups: BTreeMap<(String, String), Vec<String>> = BTreeMap::new();
+        for hole in &holes {
+            groups.entry((hole.part_id.clone(), num(hole.diameter))).or_default().push(point(&hole.center));
+        }
+        let mut groups: Vec<_> = groups.into_iter().collect();
+        groups.sort_by_key(|group| std::cmp::Reverse(group.1.len()));
+        sections.push(Section {
+            heading: format!("Holes ({}):", holes.len()),
+            items: groups.into_iter()
+                .map(|((part, diameter), centers)| format!("{}x D{} on {} at {}", centers.len(), diameter, part, centers.join(" ")))
+                .collect(),
+        });
+    }
+
+    let interfaces = match &model.interfaces {
+        Some(result) => result.interfaces.clone(),
+        None if parts.len() > 1 => {
+            let params = DetectionParams::default();
+            find_mating_interfaces(parts.clone(), params.proximity_threshold, params.normal_threshold).interfaces
+        }
+        None => Vec::new(),
+    };
+    if !interfaces.is_empty() {
+        sections.push(Section {
+            heading: format!("Interfaces ({}):", interfaces.len()),
+            items: interfaces.iter()
+                .map(|i| {
+                    let mut line = format!("{} {}-{} gap {} at {}", i.interface_type, i.part_a_id, i.part_b_id, num(i.proximity), point(&i.contact_point));
+                    if let Some(fit) = &i.fit {
+                        line.push_str(&format!(", {} fit, P(interference) {}%", fit.fit_type, num(100.0 * fit.interference_probability)));
+                    }
+                    line
+                })
+                .collect(),
+        });
+    }
+
+    let (text, truncated) = pack(header, sections, budget);
+    let estimated_tokens = estimate_tokens(&text);
+    tracing::debug!(handle = %model.handle, estimated_tokens, budget, truncated, "model summarized");
+    ModelSummary { text, estimated_tokens, token_budget: budget, truncated }
+}
+
+/// Compact geometry summary of a loaded model for prompts
+#[tauri::command]
+pub fn summarize_model(
+    state: State<'_, ModelStore>,
+    model_handle: String,
+    token_budget: Option<usize>,
+) -> Result<ModelSummary, String> {
+    state.with_model(&model_handle, |model| summarize(model, token_budget.unwrap_or(DEFAULT_TOKEN_BUDGET)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_fits_budget() {
+        let header = vec!["Model gearbox.step".to_string()];
+        let sections = vec![
+            Section { heading: "Parts (30):".to_string(), items: (0..30).map(|i| format!("part-{} housing 12 faces", i)).collect() },
+            Section { heading: "Holes (2):".to_string(), items: vec!["2x D6.6 on part-1 at (0,0,0) (10,0,0)".to_string()] },
+        ];
+        let (text, truncated) = pack(header, sections, 60);
+        assert!(truncated);
+        assert!(estimate_tokens(&text) <= 60, "{}", text);
+        assert!(text.starts_with("Model gearbox.step\nParts (30):\n  part-0"));
+        assert!(text.contains("more\nHoles (2):"));
+    }
+
+    #[test]
+    fn test_summarize_block() {
+        let content = "ISO-10303-21;\nHEADER;\nFILE_NAME('block.step','',(''),(''),'','CAD','');\nENDSEC;\nDATA;\n#1=CARTESIAN_POINT('',(0.,0.,0.));\n#2=CARTESIAN_POINT('',(10.,20.,5.5));\nENDSEC;\nEND-ISO-10303-21;";
+        let model = LoadedModel::parse("model-1".to_string(), content.to_string(), "block.step".to_string(), None);
+        let summary = summarize(&model, 200);
+        assert!(summary.text.starts_with("Model block.step\nSize 10x20x5.5 mm"), "{}", summary.text);
+        assert!(!summary.truncated);
+        assert_eq!(num(-0.001), "0");
+        assert_eq!(num(6.6), "6.6");
+    }
+}
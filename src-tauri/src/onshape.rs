@@ -0,0 +1,259 @@
+// Onshape connector: export a Part Studio or assembly as STEP and load it without manual export
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+use tauri_plugin_http::reqwest;
+use url::Url;
+
+use crate::credentials::{delete_secret, load_secret, store_secret};
+use crate::jobs::{run_async_job, JobLogLevel};
+use crate::model_store::{load_model, ModelInfo, ModelStore};
+use crate::part_transforms::PartTransformStore;
+use crate::recent_files::RecentFilesState;
+
+/// Keychain entry holding the user's API keys
+const ONSHAPE_SECRET: &str = "onshape";
+
+const TRANSLATION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Large assemblies can take minutes to translate
+const TRANSLATION_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// API key pair created in the Onshape developer portal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OnshapeKeys {
+    access_key: String,
+    secret_key: String,
+}
+
+/// Element addressed by an Onshape document URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OnshapeElement {
+    pub base_url: String,        // e.g. https://cad.onshape.com or an enterprise domain
+    pub document_id: String,
+    pub workspace_kind: String,  // "w" workspace, "v" version or "m" microversion
+    pub workspace_id: String,
+    pub element_id: String,
+}
+
+impl OnshapeElement {
+    fn wvm_path(&self) -> String {
+        format!("d/{}/{}/{}", self.document_id, self.workspace_kind, self.workspace_id)
+    }
+}
+
+/// Result of an Onshape import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnshapeImport {
+    pub element: OnshapeElement,
+    pub name: String,
+    pub element_type: String,    // "PARTSTUDIO" or "ASSEMBLY"
+    pub model: ModelInfo,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ElementInfo {
+    name: String,
+    element_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Translation {
+    id: String,
+    request_state: String,       // "ACTIVE", "DONE" or "FAILED"
+    #[serde(default)]
+    result_external_data_ids: Vec<String>,
+    failure_reason: Option<String>,
+}
+
+/// Parse the URL of an open tab: https://cad.onshape.com/documents/<did>/w/<wid>/e/<eid>
+pub fn parse_onshape_url(url: &str) -> Result<OnshapeElement, String> {
+    let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid Onshape URL: {}", e))?;
+    let segments: Vec<&str> = parsed.path_segments().map(|s| s.filter(|p| !p.is_empty()).collect()).unwrap_or_default();
+    match segments.as_slice() {
+        ["documents", document_id, kind @ ("w" | "v" | "m"), workspace_id, "e", element_id, ..] => Ok(OnshapeElement {
+            base_url: parsed.origin().ascii_serialization(),
+            document_id: document_id.to_string(),
+            workspace_kind: kind.to_string(),
+            workspace_id: workspace_id.to_string(),
+            element_id: element_id.to_string(),
+        }),
+        _ => Err("Expected an Onshape tab URL like https://cad.onshape.com/documents/<id>/w/<id>/e/<id>".to_string()),
+    }
+}
+
+/// Authenticated calls against the element's Onshape server
+struct OnshapeClient {
+    http: reqwest::Client,
+    base_url: String,
+    keys: OnshapeKeys,
+}
+
+impl OnshapeClient {
+    fn new(base_url: &str, keys: OnshapeKeys) -> OnshapeClient {
+        OnshapeClient { http: reqwest::Client::new(), base_url: base_url.to_string(), keys }
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder, path: &str) -> Result<reqwest::Response, String> {
+        let response = request
+            .basic_auth(&self.keys.access_key, Some(&self.keys.secret_key))
+            .send()
+            .await
+            .map_err(|e| format!("Onshape request failed: {}", e))?;
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err("Onshape rejected the API keys; check them in the connector settings".to_string());
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Onshape returned {} for {}: {}", status, path, body.chars().take(200).collect::<String>()));
+        }
+        Ok(response)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/v6/{}", self.base_url, path)
+    }
+
+    /// Response body parsed as JSON
+    async fn read_json<T: DeserializeOwned>(response: reqwest::Response, path: &str) -> Result<T, String> {
+        let bytes = response.bytes().await.map_err(|e| format!("Onshape response for {} was cut off: {}", path, e))?;
+        serde_json::from_slice(&bytes).map_err(|e| format!("Unexpected Onshape response for {}: {}", path, e))
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, String> {
+        let response = self.send(self.http.get(self.url(path)), path).await?;
+        Self::read_json(response, path).await
+    }
+
+    async fn post_json<T: DeserializeOwned>(&self, path: &str, body: &serde_json::Value) -> Result<T, String> {
+        let request = self.http.post(self.url(path))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string());
+        let response = self.send(request, path).await?;
+        Self::read_json(response, path).await
+    }
+
+    async fn download(&self, path: &str) -> Result<Vec<u8>, String> {
+        let response = self.send(self.http.get(self.url(path)), path).await?;
+        let bytes = response.bytes().await.map_err(|e| format!("Onshape download failed: {}", e))?;
+        Ok(bytes.to_vec())
+    }
+}
+
+fn load_keys() -> Result<OnshapeKeys, String> {
+    let stored = load_secret(ONSHAPE_SECRET)?.ok_or("Onshape API keys are not configured")?;
+    serde_json::from_str(&stored).map_err(|e| format!("Stored Onshape keys are unreadable: {}", e))
+}
+
+/// Save the user's Onshape API keys in the OS keychain
+#[tauri::command]
+pub fn set_onshape_credentials(access_key: String, secret_key: String) -> Result<(), String> {
+    let keys = OnshapeKeys { access_key: access_key.trim().to_string(), secret_key: secret_key.trim().to_string() };
+    if keys.access_key.is_empty() || keys.secret_key.is_empty() {
+        return Err("Both the access key and the secret key are required".to_string());
+    }
+    let json = serde_json::to_string(&keys).map_err(|e| format!("Failed to serialize keys: {}", e))?;
+    store_secret(ONSHAPE_SECRET, &json)
+}
+
+/// Forget the stored Onshape API keys
+#[tauri::command]
+pub fn clear_onshape_credentials() -> Result<bool, String> {
+    delete_secret(ONSHAPE_SECRET)
+}
+
+/// Whether API keys are stored, so the frontend can prompt for them first
+#[tauri::command]
+pub fn onshape_credentials_configured() -> Result<bool, String> {
+    Ok(load_secret(ONSHAPE_SECRET)?.is_some())
+}
+
+/// Export an Onshape element as STEP and load it as a model
+#[tauri::command]
+pub async fn import_onshape_element(
+    app: AppHandle,
+    state: State<'_, ModelStore>,
+    transforms: State<'_, PartTransformStore>,
+    recent: State<'_, RecentFilesState>,
+    url: String,
+    job_id: Option<String>,
+) -> Result<OnshapeImport, String> {
+    let element = parse_onshape_url(&url)?;
+    let keys = load_keys()?;
+
+    let target = element.clone();
+    let (info, content) = run_async_job(app, "import_onshape_element", job_id, None, |job| async move {
+        let element = target;
+        let client = OnshapeClient::new(&element.base_url, keys);
+        let wvm = element.wvm_path();
+
+        let infos: Vec<ElementInfo> = client
+            .get_json(&format!("documents/{}/elements?elementId={}", wvm, element.element_id))
+            .await?;
+        let info = infos.into_iter().next().ok_or("Element not found in the Onshape document")?;
+        let endpoint = match info.element_type.as_str() {
+            "PARTSTUDIO" => "partstudios",
+            "ASSEMBLY" => "assemblies",
+            other => return Err(format!("Onshape {} elements cannot be exported as STEP", other.to_lowercase())),
+        };
+        job.log(JobLogLevel::Info, format!("Exporting {} from Onshape", info.name));
+
+        let request = serde_json::json!({ "formatName": "STEP", "storeInDocument": false });
+        let mut translation: Translation = client
+            .post_json(&format!("{}/{}/e/{}/translations", endpoint, wvm, element.element_id), &request)
+            .await?;
+
+        // Onshape translates asynchronously; poll until the STEP file is ready
+        let started = Instant::now();
+        let mut polls = 0;
+        while translation.request_state == "ACTIVE" {
+            if started.elapsed() > TRANSLATION_TIMEOUT {
+                return Err("Onshape export timed out".to_string());
+            }
+            tokio::time::sleep(TRANSLATION_POLL_INTERVAL).await;
+            polls += 1;
+            job.progress(polls, None, Some("Waiting for Onshape export".to_string()));
+            translation = client.get_json(&format!("translations/{}", translation.id)).await?;
+        }
+        if translation.request_state != "DONE" {
+            return Err(format!(
+                "Onshape export failed: {}",
+                translation.failure_reason.unwrap_or(translation.request_state)
+            ));
+        }
+
+        let data_id = translation.result_external_data_ids.first().ok_or("Onshape export produced no file")?;
+        let bytes = client.download(&format!("documents/d/{}/externaldata/{}", element.document_id, data_id)).await?;
+        job.log(JobLogLevel::Info, format!("Downloaded {} bytes of STEP", bytes.len()));
+        Ok((info, String::from_utf8_lossy(&bytes).into_owned()))
+    })
+    .await?;
+
+    tracing::info!(element = %element.element_id, name = %info.name, "imported Onshape element");
+    let model = load_model(state, transforms, recent, Some(content), None, Some(format!("{}.step", info.name)))?;
+    Ok(OnshapeImport { element, name: info.name, element_type: info.element_type, model })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_onshape_url() {
+        let element = parse_onshape_url("https://cad.onshape.com/documents/d1/w/w2/e/e3").unwrap();
+        assert_eq!(element.base_url, "https://cad.onshape.com");
+        assert_eq!(element.wvm_path(), "d/d1/w/w2");
+        assert_eq!(element.element_id, "e3");
+
+        let version = parse_onshape_url("https://acme.onshape.com/documents/d1/v/v9/e/e3?renderMode=0").unwrap();
+        assert_eq!((version.base_url.as_str(), version.workspace_kind.as_str()), ("https://acme.onshape.com", "v"));
+
+        assert!(parse_onshape_url("https://cad.onshape.com/documents/d1").is_err());
+        assert!(parse_onshape_url("not a url").is_err());
+    }
+}
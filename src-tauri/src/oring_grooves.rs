@@ -0,0 +1,433 @@
+// O-ring glands: rectangular grooves on shafts, in bores and on faces, checked against AS568 / ISO 3601 sizes
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::ParsedPart;
+use crate::features::{axial_span, is_coaxial, outward_normal, recognize_part_bosses, recognize_part_holes, Hole};
+use crate::interface_detection::{transform_direction, transform_point};
+use crate::linalg::{dot, sub, Vec3};
+use crate::model_store::ModelStore;
+use crate::step_entities::StepEntities;
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// Widest groove (mm) still taken for an O-ring gland; the largest common cross-section is 6.99
+const MAX_GROOVE_WIDTH: f64 = 12.0;
+
+// AS568 sizes (ISO 3601-1 class A uses the same dimensions): dash number, inside diameter, cross-section (mm)
+const AS568: [(u16, f64, f64); 88] = [
+    (10, 6.07, 1.78), (11, 7.65, 1.78), (12, 9.25, 1.78), (13, 10.82, 1.78), (14, 12.42, 1.78), (15, 14.00, 1.78),
+    (16, 15.60, 1.78), (17, 17.17, 1.78), (18, 18.77, 1.78), (19, 20.35, 1.78), (20, 21.95, 1.78), (21, 23.52, 1.78),
+    (22, 25.12, 1.78), (23, 26.70, 1.78), (24, 28.30, 1.78), (25, 29.87, 1.78), (26, 31.47, 1.78), (27, 33.05, 1.78),
+    (28, 34.65, 1.78), (29, 37.82, 1.78), (30, 41.00, 1.78),
+    (110, 9.19, 2.62), (111, 10.77, 2.62), (112, 12.37, 2.62), (113, 13.94, 2.62), (114, 15.54, 2.62), (115, 17.12, 2.62),
+    (116, 18.72, 2.62), (117, 20.29, 2.62), (118, 21.89, 2.62), (119, 23.47, 2.62), (120, 25.07, 2.62), (121, 26.64, 2.62),
+    (122, 28.24, 2.62), (123, 29.82, 2.62), (124, 31.42, 2.62), (125, 32.99, 2.62), (126, 34.59, 2.62), (127, 36.17, 2.62),
+    (128, 37.77, 2.62), (129, 39.34, 2.62), (130, 40.94, 2.62),
+    (210, 18.64, 3.53), (211, 20.22, 3.53), (212, 21.82, 3.53), (213, 23.39, 3.53), (214, 24.99, 3.53), (215, 26.57, 3.53),
+    (216, 28.17, 3.53), (217, 29.74, 3.53), (218, 31.34, 3.53), (219, 32.92, 3.53), (220, 34.52, 3.53), (221, 36.09, 3.53),
+    (222, 37.69, 3.53), (223, 40.87, 3.53), (224, 44.04, 3.53), (225, 47.22, 3.53), (226, 50.39, 3.53), (227, 53.57, 3.53),
+    (228, 56.74, 3.53), (229, 59.92, 3.53), (230, 63.09, 3.53),
+    (325, 37.47, 5.33), (326, 40.64, 5.33), (327, 43.82, 5.33), (328, 46.99, 5.33), (329, 50.17, 5.33), (330, 53.34, 5.33),
+    (331, 56.52, 5.33), (332, 59.69, 5.33), (333, 62.87, 5.33), (334, 66.04, 5.33), (335, 69.22, 5.33), (336, 72.39, 5.33),
+    (337, 75.57, 5.33), (338, 78.74, 5.33), (339, 81.92, 5.33), (340, 85.09, 5.33),
+    (425, 113.67, 6.99), (426, 116.84, 6.99), (427, 120.02, 6.99), (428, 123.19, 6.99), (429, 126.37, 6.99),
+    (430, 129.54, 6.99), (431, 132.72, 6.99), (432, 135.89, 6.99), (433, 139.07, 6.99),
+];
+
+/// Cross-section tolerance (±) per nominal cross-section
+fn cross_section_tolerance(cross_section: f64) -> f64 {
+    match cross_section {
+        cs if cs < 3.0 => 0.08,
+        cs if cs < 4.0 => 0.10,
+        cs if cs < 6.0 => 0.13,
+        _ => 0.15,
+    }
+}
+
+/// Where the groove is cut
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrooveKind {
+    External,  // On a shaft or piston, sealing against a bore
+    Internal,  // In a bore, sealing against a rod
+    Face,      // In a flat face, squeezed axially
+}
+
+/// Outcome of one check, ordered so the worst wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// A rectangular O-ring gland, described by the annulus the ring sits in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OringGroove {
+    pub id: String,
+    pub part_id: String,
+    pub kind: GrooveKind,
+    pub face_ids: Vec<i64>,    // Groove bottom, then the walls
+    pub axis: Vec3,
+    pub inner_diameter: f64,   // Groove bottom (external), rod (internal) or inner wall (face)
+    pub outer_diameter: f64,   // Bore (external), groove bottom (internal) or outer wall (face)
+    pub gland_depth: f64,      // Radial for shaft and bore grooves, axial for face grooves
+    pub width: f64,
+}
+
+/// A standard O-ring size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OringSize {
+    pub dash: String,          // e.g. "AS568-214"
+    pub inner_diameter: f64,
+    pub cross_section: f64,
+    pub cross_section_tolerance: f64,
+}
+
+fn default_service() -> String {
+    "static".to_string()
+}
+
+fn default_diameter_tolerance() -> f64 {
+    0.05
+}
+
+fn default_width_tolerance() -> f64 {
+    0.1
+}
+
+/// Mating diameter or ring size given for one groove instead of the defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrooveOverride {
+    pub groove_id: String,
+    #[serde(default)]
+    pub mating_diameter: Option<f64>,  // Bore for a shaft groove, rod for a bore groove
+    #[serde(default)]
+    pub dash: Option<String>,          // "214", "-214" or "AS568-214"
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OringCheckInput {
+    #[serde(default = "default_service")]
+    pub service: String,              // "static" or "dynamic"
+    #[serde(default = "default_diameter_tolerance")]
+    pub diameter_tolerance: f64,      // ± on groove and mating diameters, and on face groove depth
+    #[serde(default = "default_width_tolerance")]
+    pub width_tolerance: f64,
+    #[serde(default)]
+    pub overrides: Vec<GrooveOverride>,
+}
+
+impl Default for OringCheckInput {
+    fn default() -> Self {
+        OringCheckInput {
+            service: default_service(),
+            diameter_tolerance: default_diameter_tolerance(),
+            width_tolerance: default_width_tolerance(),
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl Validate for OringCheckInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.one_of("service", &self.service, &["static", "dynamic"]);
+        errors.at_least("diameter_tolerance", self.diameter_tolerance, 0.0);
+        errors.at_least("width_tolerance", self.width_tolerance, 0.0);
+        for (i, o) in self.overrides.iter().enumerate() {
+            if let Some(d) = o.mating_diameter {
+                errors.above(format!("overrides[{}].mating_diameter", i), d, 0.0);
+            }
+            if let Some(dash) = &o.dash {
+                if standard_size(dash).is_none() {
+                    errors.add(format!("overrides[{}].dash", i), format!("unknown AS568 size \"{}\"", dash));
+                }
+            }
+        }
+    }
+}
+
+/// Fill and squeeze of a groove with its ring, with the verdict
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrooveCheck {
+    pub groove: OringGroove,
+    pub size: OringSize,
+    pub squeeze_percent: [f64; 3],      // Least, nominal and most, over the ring and groove tolerances
+    pub squeeze_range: [f64; 2],        // Recommended for the service
+    pub fill_percent: [f64; 2],         // Nominal and most
+    pub stretch_percent: f64,           // Ring inside diameter onto the inner diameter; negative when loose
+    pub status: CheckStatus,
+    pub messages: Vec<String>,
+}
+
+fn size_of(dash: u16, inner_diameter: f64, cross_section: f64) -> OringSize {
+    OringSize {
+        dash: format!("AS568-{:03}", dash),
+        inner_diameter,
+        cross_section,
+        cross_section_tolerance: cross_section_tolerance(cross_section),
+    }
+}
+
+/// Look up "214", "-214" or "AS568-214"
+pub fn standard_size(dash: &str) -> Option<OringSize> {
+    let number: u16 = dash.trim().trim_start_matches("AS568").trim_start_matches('-').parse().ok()?;
+    AS568.iter().find(|s| s.0 == number).map(|&(d, id, cs)| size_of(d, id, cs))
+}
+
+/// Squeeze range (%) recommended for a gland
+pub fn squeeze_range(kind: GrooveKind, service: &str) -> [f64; 2] {
+    match (kind, service) {
+        (GrooveKind::Face, _) => [20.0, 35.0],
+        (_, "dynamic") => [10.0, 20.0],
+        _ => [15.0, 30.0],
+    }
+}
+
+/// Cross-section closest to the middle of the squeeze range, then the largest ring that still stretches onto the inner diameter
+pub fn select_size(groove: &OringGroove, range: [f64; 2]) -> OringSize {
+    let middle = (range[0] + range[1]) / 200.0;
+    let off_middle = |cs: f64| ((cs - groove.gland_depth) / cs - middle).abs();
+    let cross_section = AS568.iter().map(|s| s.2).min_by(|a, b| off_middle(*a).total_cmp(&off_middle(*b))).unwrap_or(AS568[0].2);
+    let sizes = AS568.iter().filter(|s| s.2 == cross_section);
+    let &(dash, id, cs) = sizes.clone()
+        .filter(|s| s.1 <= groove.inner_diameter)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .or_else(|| sizes.min_by(|a, b| a.1.total_cmp(&b.1)))
+        .unwrap_or(&AS568[0]);
+    size_of(dash, id, cs)
+}
+
+/// Squeeze, fill and stretch of a ring in a groove at the tolerance extremes
+pub fn check_groove(groove: &OringGroove, size: OringSize, input: &OringCheckInput) -> GrooveCheck {
+    let (cs, dcs) = (size.cross_section, size.cross_section_tolerance);
+    let (h, dh) = (groove.gland_depth, input.diameter_tolerance);
+    let (w, dw) = (groove.width, input.width_tolerance);
+    let section = |d: f64| std::f64::consts::PI * d * d / 4.0;
+
+    let squeeze = |cs: f64, h: f64| 100.0 * (cs - h) / cs;
+    let squeeze_percent = [squeeze(cs - dcs, h + dh), squeeze(cs, h), squeeze(cs + dcs, h - dh)];
+    let fill_percent = [100.0 * section(cs) / (h * w), 100.0 * section(cs + dcs) / ((h - dh) * (w - dw)).max(1e-12)];
+    let stretch_percent = 100.0 * (groove.inner_diameter - size.inner_diameter) / size.inner_diameter;
+    let squeeze_range = squeeze_range(groove.kind, &input.service);
+
+    let mut status = CheckStatus::Pass;
+    let mut messages = Vec::new();
+    let mut flag = |level: CheckStatus, message: String| {
+        status = status.max(level);
+        messages.push(message);
+    };
+    let [least, nominal, most] = squeeze_percent;
+    if least <= 0.0 || nominal < squeeze_range[0] || nominal > squeeze_range[1] {
+        flag(CheckStatus::Fail, format!(
+            "Squeeze {:.1}% (least {:.1}%) is outside {:.0}-{:.0}%", nominal, least, squeeze_range[0], squeeze_range[1]
+        ));
+    } else if least < squeeze_range[0] || most > squeeze_range[1] {
+        flag(CheckStatus::Warn, format!(
+            "Squeeze ranges {:.1}-{:.1}% over the tolerances, beyond {:.0}-{:.0}%", least, most, squeeze_range[0], squeeze_range[1]
+        ));
+    }
+    match fill_percent[1] {
+        fill if fill > 90.0 => flag(CheckStatus::Fail, format!("Groove fill reaches {:.0}%; the ring can extrude or crack the gland (max 90%)", fill)),
+        fill if fill > 85.0 => flag(CheckStatus::Warn, format!("Groove fill reaches {:.0}% (recommended 85% or less)", fill)),
+        _ => {}
+    }
+    match stretch_percent {
+        s if s > 8.0 => flag(CheckStatus::Fail, format!("{} is stretched {:.1}% (max 8%)", size.dash, s)),
+        s if s > 5.0 => flag(CheckStatus::Warn, format!("{} is stretched {:.1}% (recommended 5% or less)", size.dash, s)),
+        s if s < -3.0 => flag(CheckStatus::Warn, format!("{} is {:.1}% loose on the {:.2} mm diameter", size.dash, -s, groove.inner_diameter)),
+        _ => {}
+    }
+
+    GrooveCheck {
+        groove: groove.clone(),
+        size,
+        squeeze_percent,
+        squeeze_range,
+        fill_percent,
+        stretch_percent,
+        status,
+        messages,
+    }
+}
+
+/// Nearest plane facing `facing` (+1 or -1 along the axis) at or beyond `from` when looking `look` along it
+fn nearest_plane(planes: &[(i64, f64, f64)], facing: f64, from: f64, look: f64, tolerance: f64) -> Option<(i64, f64)> {
+    planes.iter()
+        .filter(|(_, s, along)| along * facing > 1.0 - 1e-6 && (s - from) * look >= -tolerance)
+        .min_by(|a, b| ((a.1 - from) * look).total_cmp(&((b.1 - from) * look)))
+        .map(|&(id, s, _)| (id, s))
+}
+
+/// Shaft, bore and face grooves of a part, found from coaxial cylinder pairs and the planes bounding them
+pub fn recognize_part_grooves(entities: &StepEntities, part: &ParsedPart) -> Vec<OringGroove> {
+    let holes = recognize_part_holes(part);
+    let bosses = recognize_part_bosses(part);
+    let coaxial = |a: &Hole, b: &Hole| is_coaxial(&a.center, &a.axis, &b.center, &b.axis);
+    let mut grooves: Vec<OringGroove> = Vec::new();
+
+    for bottom in holes.iter().chain(&bosses) {
+        let internal = bottom.internal == Some(true);
+        let (low, high, bounded) = axial_span(entities, part, bottom);
+        let (low, high) = if bounded { (low, high) } else { (0.0, 0.0) };
+        let tolerance = 1e-3 * bottom.diameter.max(1.0);
+        // Planar faces perpendicular to the axis: id, axial position, outward normal along the axis
+        let planes: Vec<(i64, f64, f64)> = part.faces.iter()
+            .filter(|f| f.face_type == "planar")
+            .map(|f| {
+                let center = transform_point(&f.center, &part.transform);
+                let normal = transform_direction(&outward_normal(f), &part.transform);
+                (f.id, dot(&sub(&center, &bottom.center), &bottom.axis), dot(&normal, &bottom.axis))
+            })
+            .filter(|(_, _, along)| along.abs() > 1.0 - 1e-6)
+            .collect();
+        let mut push = |kind: GrooveKind, face_ids: Vec<i64>, inner_diameter: f64, outer_diameter: f64, gland_depth: f64, width: f64| {
+            grooves.push(OringGroove {
+                id: format!("{}-groove-{}", part.id, grooves.len() + 1),
+                part_id: part.id.clone(),
+                kind,
+                face_ids,
+                axis: bottom.axis,
+                inner_diameter,
+                outer_diameter,
+                gland_depth,
+                width,
+            });
+        };
+
+        // Shaft or bore groove: a land of the same kind on the open side, walls facing each other at both ends
+        let land = if internal {
+            holes.iter().filter(|h| h.diameter < bottom.diameter && coaxial(h, bottom)).max_by(|a, b| a.diameter.total_cmp(&b.diameter))
+        } else {
+            bosses.iter().filter(|b| b.diameter > bottom.diameter && coaxial(b, bottom)).min_by(|a, b| a.diameter.total_cmp(&b.diameter))
+        };
+        if let Some(land) = land {
+            let lower = nearest_plane(&planes, 1.0, low, -1.0, tolerance);
+            let upper = nearest_plane(&planes, -1.0, high, 1.0, tolerance);
+            if let (Some(lower), Some(upper)) = (lower, upper) {
+                let width = upper.1 - lower.1;
+                if width > 0.0 && width <= MAX_GROOVE_WIDTH {
+                    let mut face_ids = bottom.face_ids.clone();
+                    face_ids.extend([lower.0, upper.0]);
+                    let (kind, inner, outer) = if internal {
+                        (GrooveKind::Internal, land.diameter, bottom.diameter)
+                    } else {
+                        (GrooveKind::External, bottom.diameter, land.diameter)
+                    };
+                    push(kind, face_ids, inner, outer, (outer - inner) / 2.0, width);
+                }
+            }
+        }
+
+        // Face groove: this hole is the outer wall around a coaxial boss, with a floor and a top face looking the same way
+        if internal {
+            let inner_wall = bosses.iter()
+                .filter(|b| b.diameter < bottom.diameter && coaxial(b, bottom))
+                .max_by(|a, b| a.diameter.total_cmp(&b.diameter));
+            let Some(inner_wall) = inner_wall else { continue };
+            let width = (bottom.diameter - inner_wall.diameter) / 2.0;
+            if width > MAX_GROOVE_WIDTH {
+                continue;
+            }
+            for (facing, floor_end, top_end) in [(1.0, low, high), (-1.0, high, low)] {
+                let Some(floor) = nearest_plane(&planes, facing, floor_end, -facing, tolerance) else { continue };
+                let Some(top) = nearest_plane(&planes, facing, top_end, facing, tolerance)
+                    .filter(|top| (top.1 - floor.1) * facing > tolerance)
+                else {
+                    continue;
+                };
+                let mut face_ids = bottom.face_ids.clone();
+                face_ids.extend(inner_wall.face_ids.iter().copied());
+                face_ids.push(floor.0);
+                push(GrooveKind::Face, face_ids, inner_wall.diameter, bottom.diameter, (top.1 - floor.1).abs(), width);
+                break;
+            }
+        }
+    }
+    grooves
+}
+
+/// Find O-ring grooves on a loaded model and check each against its standard ring
+#[tauri::command]
+pub fn check_oring_grooves(
+    state: State<'_, ModelStore>,
+    handle: String,
+    input: Checked<OringCheckInput>,
+) -> Result<Vec<GrooveCheck>, String> {
+    state.with_model(&handle, |model| {
+        let entities = StepEntities::parse(&model.content);
+        let checks: Vec<GrooveCheck> = model.assembly.parts.iter()
+            .flat_map(|part| recognize_part_grooves(&entities, part))
+            .map(|mut groove| {
+                let given = input.overrides.iter().find(|o| o.groove_id == groove.id);
+                match (groove.kind, given.and_then(|o| o.mating_diameter)) {
+                    (GrooveKind::External, Some(bore)) => groove.outer_diameter = bore,
+                    (GrooveKind::Internal, Some(rod)) => groove.inner_diameter = rod,
+                    _ => {}
+                }
+                if groove.kind != GrooveKind::Face {
+                    groove.gland_depth = (groove.outer_diameter - groove.inner_diameter) / 2.0;
+                }
+                let size = given.and_then(|o| o.dash.as_deref()).and_then(standard_size)
+                    .unwrap_or_else(|| select_size(&groove, squeeze_range(groove.kind, &input.service)));
+                check_groove(&groove, size, &input)
+            })
+            .collect();
+        tracing::info!(
+            handle = %handle,
+            grooves = checks.len(),
+            failed = checks.iter().filter(|c| c.status == CheckStatus::Fail).count(),
+            "o-ring grooves checked"
+        );
+        checks
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{boss, face};
+
+    #[test]
+    fn test_shaft_groove_fits_as568_120() {
+        // 30 mm shaft with a 3.6 mm wide groove down to 25.9 mm, running in a 30 mm bore
+        let shaft = ParsedPart {
+            id: "shaft".to_string(),
+            name: "shaft".to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: None,
+            faces: vec![
+                boss(1, [0.0, 0.0, 10.0], 15.0),
+                boss(2, [0.0, 0.0, 20.0], 12.95),
+                face(3, "planar", [0.0, 0.0, 18.2], [0.0, 0.0, 1.0], None),
+                face(4, "planar", [0.0, 0.0, 21.8], [0.0, 0.0, -1.0], None),
+                face(5, "planar", [0.0, 0.0, 0.0], [0.0, 0.0, -1.0], None),
+                face(6, "planar", [0.0, 0.0, 40.0], [0.0, 0.0, 1.0], None),
+            ],
+            product_definition_id: None,
+        };
+
+        let grooves = recognize_part_grooves(&StepEntities::parse(""), &shaft);
+        assert_eq!(grooves.len(), 1);
+        let groove = &grooves[0];
+        assert_eq!(groove.kind, GrooveKind::External);
+        assert_eq!(groove.face_ids, vec![2, 3, 4]);
+        assert!((groove.width - 3.6).abs() < 1e-9 && (groove.gland_depth - 2.05).abs() < 1e-9);
+
+        let input = OringCheckInput::default();
+        let size = select_size(groove, squeeze_range(groove.kind, &input.service));
+        assert_eq!(size.dash, "AS568-120");
+        let check = check_groove(groove, size, &input);
+        assert_eq!(check.status, CheckStatus::Pass, "{:?}", check.messages);
+        assert!((check.squeeze_percent[1] - 21.76).abs() < 0.01);
+        assert!(check.fill_percent[1] < 85.0 && check.stretch_percent > 0.0);
+
+        // In a 31 mm bore the same ring barely touches
+        let loose = OringGroove { outer_diameter: 31.0, gland_depth: 2.55, ..groove.clone() };
+        let check = check_groove(&loose, standard_size("-120").unwrap(), &input);
+        assert_eq!(check.status, CheckStatus::Fail);
+    }
+}
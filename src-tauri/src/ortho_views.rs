@@ -0,0 +1,269 @@
+// Orthographic front/top/side views of the tessellated model as SVG line drawings
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use tauri::State;
+
+use crate::linalg::{dot, Vec3};
+use crate::mesh_query::{outward_normal, triangle};
+use crate::model_store::ModelStore;
+use crate::MeshData;
+
+/// Drawing settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrthoViewOptions {
+    pub resolution: usize,       // Depth buffer cells along the longer side of a view
+    pub crease_angle_deg: f64,   // Edges between faces meeting at a sharper angle are drawn
+    pub show_hidden: bool,       // Draw hidden edges dashed
+    pub size_px: f64,            // Rendered width of the longer side
+}
+
+impl Default for OrthoViewOptions {
+    fn default() -> Self {
+        OrthoViewOptions {
+            resolution: 512,
+            crease_angle_deg: 30.0,
+            show_hidden: true,
+            size_px: 400.0,
+        }
+    }
+}
+
+/// One rendered view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrthoView {
+    pub name: String,
+    pub svg: String,
+    pub width: f64,              // Model units
+    pub height: f64,
+    pub visible_segments: usize,
+    pub hidden_segments: usize,
+}
+
+/// Third-angle views for a Z-up model: (name, towards viewer, right, up)
+const VIEWS: [(&str, Vec3, Vec3, Vec3); 3] = [
+    ("front", [0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]),
+    ("top", [0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]),
+    ("right", [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+];
+
+type Segment = ([f64; 2], [f64; 2]);
+
+/// Edge endpoints quantized so triangles with split vertices still share it
+type EdgeKey = ([i64; 3], [i64; 3]);
+
+/// Edge endpoints and the normals of the triangles using it
+struct EdgeUse {
+    a: Vec3,
+    b: Vec3,
+    normals: Vec<Vec3>,
+}
+
+/// Mesh edges worth drawing from this direction: creases, open boundaries and silhouettes
+fn drawable_edges(mesh: &MeshData, toward_viewer: &Vec3, crease_cos: f64) -> Vec<(Vec3, Vec3)> {
+    let key = |p: &Vec3| p.map(|c| (c * 1e5).round() as i64);
+    let mut edges: HashMap<EdgeKey, EdgeUse> = HashMap::new();
+
+    for tri in 0..mesh.indices.len() / 3 {
+        let corners = triangle(mesh, tri);
+        let normal = outward_normal(mesh, tri);
+        for (i, j) in [(0, 1), (1, 2), (2, 0)] {
+            let (a, b) = (corners[i], corners[j]);
+            let k = if key(&a) <= key(&b) { (key(&a), key(&b)) } else { (key(&b), key(&a)) };
+            edges.entry(k).or_insert(EdgeUse { a, b, normals: Vec::new() }).normals.push(normal);
+        }
+    }
+
+    edges.into_values()
+        .filter(|edge| match edge.normals.as_slice() {
+            [n1, n2] => {
+                let silhouette = (dot(n1, toward_viewer) > 0.0) != (dot(n2, toward_viewer) > 0.0);
+                silhouette || dot(n1, n2) < crease_cos
+            }
+            _ => true,
+        })
+        .map(|edge| (edge.a, edge.b))
+        .collect()
+}
+
+/// Project the mesh, rasterize a depth buffer and split each drawable edge into visible and hidden runs
+fn render_view(mesh: &MeshData, view: (&str, Vec3, Vec3, Vec3), options: &OrthoViewOptions) -> OrthoView {
+    let (name, toward, right, up) = view;
+    let project = |p: &Vec3| [dot(p, &right), dot(p, &up), dot(p, &toward)];
+
+    let points: Vec<[f64; 3]> = mesh.vertices.chunks_exact(3)
+        .map(|v| project(&[v[0] as f64, v[1] as f64, v[2] as f64]))
+        .collect();
+    let mut min = [f64::MAX; 2];
+    let mut max = [f64::MIN; 2];
+    for p in &points {
+        for k in 0..2 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+    if points.is_empty() {
+        min = [0.0; 2];
+        max = [0.0; 2];
+    }
+    let (width, height) = (max[0] - min[0], max[1] - min[1]);
+
+    let resolution = options.resolution.clamp(32, 4096);
+    let cell = width.max(height).max(1e-9) / resolution as f64;
+    let cols = (width / cell).ceil() as usize + 1;
+    let rows = (height / cell).ceil() as usize + 1;
+    let mut depth = vec![f64::MIN; cols * rows];
+    let to_cell = |x: f64, lo: f64, n: usize| (((x - lo) / cell).floor().max(0.0) as usize).min(n - 1);
+
+    // Depth buffer holding the nearest surface (largest depth along `toward`)
+    for tri in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| points[tri[k] as usize]);
+        let det = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+        if det.abs() < 1e-18 {
+            continue;
+        }
+        for row in to_cell(a[1].min(b[1]).min(c[1]), min[1], rows)..=to_cell(a[1].max(b[1]).max(c[1]), min[1], rows) {
+            let y = min[1] + (row as f64 + 0.5) * cell;
+            for col in to_cell(a[0].min(b[0]).min(c[0]), min[0], cols)..=to_cell(a[0].max(b[0]).max(c[0]), min[0], cols) {
+                let x = min[0] + (col as f64 + 0.5) * cell;
+                let w1 = ((x - a[0]) * (c[1] - a[1]) - (y - a[1]) * (c[0] - a[0])) / det;
+                let w2 = ((b[0] - a[0]) * (y - a[1]) - (b[1] - a[1]) * (x - a[0])) / det;
+                if w1 < 0.0 || w2 < 0.0 || w1 + w2 > 1.0 {
+                    continue;
+                }
+                let d = a[2] + w1 * (b[2] - a[2]) + w2 * (c[2] - a[2]);
+                let slot = &mut depth[row * cols + col];
+                *slot = slot.max(d);
+            }
+        }
+    }
+
+    // Compare edge samples against the nearest depth in a small neighbourhood, since edges lie on pixel borders
+    let tolerance = cell * 2.0;
+    let nearest = |x: f64, y: f64| {
+        let (col, row) = (to_cell(x, min[0], cols), to_cell(y, min[1], rows));
+        let mut best = f64::MIN;
+        for r in row.saturating_sub(1)..=(row + 1).min(rows - 1) {
+            for c in col.saturating_sub(1)..=(col + 1).min(cols - 1) {
+                best = best.max(depth[r * cols + c]);
+            }
+        }
+        best
+    };
+
+    let mut visible: Vec<Segment> = Vec::new();
+    let mut hidden: Vec<Segment> = Vec::new();
+    for (a, b) in drawable_edges(mesh, &toward, options.crease_angle_deg.to_radians().cos()) {
+        let (pa, pb) = (project(&a), project(&b));
+        let length = ((pb[0] - pa[0]).powi(2) + (pb[1] - pa[1]).powi(2)).sqrt();
+        if length < cell * 0.5 {
+            continue; // Edge seen end-on
+        }
+
+        let samples = (length / cell).ceil() as usize + 1;
+        let at = |t: f64| [0, 1, 2].map(|k| pa[k] + (pb[k] - pa[k]) * t);
+        let mut run_start = 0.0;
+        let mut run_visible = None;
+        for s in 0..=samples {
+            let t = s as f64 / samples as f64;
+            let p = at(t);
+            let is_visible = p[2] >= nearest(p[0], p[1]) - tolerance;
+            match run_visible {
+                None => run_visible = Some(is_visible),
+                Some(v) if v != is_visible => {
+                    let split = (t - 0.5 / samples as f64).max(run_start);
+                    let (from, to) = (at(run_start), at(split));
+                    if v { &mut visible } else { &mut hidden }.push(([from[0], from[1]], [to[0], to[1]]));
+                    run_start = split;
+                    run_visible = Some(is_visible);
+                }
+                _ => {}
+            }
+        }
+        if let Some(v) = run_visible {
+            let (from, to) = (at(run_start), at(1.0));
+            if v { &mut visible } else { &mut hidden }.push(([from[0], from[1]], [to[0], to[1]]));
+        }
+    }
+
+    let svg = to_svg(&visible, if options.show_hidden { &hidden } else { &[] }, min, [width, height], options.size_px);
+    OrthoView {
+        name: name.to_string(),
+        svg,
+        width,
+        height,
+        visible_segments: visible.len(),
+        hidden_segments: hidden.len(),
+    }
+}
+
+fn to_svg(visible: &[Segment], hidden: &[Segment], min: [f64; 2], size: [f64; 2], size_px: f64) -> String {
+    let margin = size[0].max(size[1]).max(1e-9) * 0.05;
+    let (w, h) = (size[0] + 2.0 * margin, size[1] + 2.0 * margin);
+    let scale = size_px / w.max(h);
+    let stroke = w.max(h) / 400.0;
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="{} {} {} {}">"#,
+        w * scale, h * scale, min[0] - margin, -(min[1] + size[1] + margin), w, h,
+    );
+    // SVG y runs down, so flip the view's up axis
+    for (segments, style) in [
+        (hidden, format!(r#"stroke="gray" stroke-dasharray="{} {}""#, stroke * 6.0, stroke * 4.0)),
+        (visible, r#"stroke="black""#.to_string()),
+    ] {
+        if segments.is_empty() {
+            continue;
+        }
+        let _ = write!(svg, r#"<g fill="none" stroke-width="{}" {}>"#, stroke, style);
+        for (a, b) in segments {
+            let _ = write!(svg, r#"<line x1="{:.4}" y1="{:.4}" x2="{:.4}" y2="{:.4}"/>"#, a[0], -a[1], b[0], -b[1]);
+        }
+        svg.push_str("</g>");
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Orthographic views of a mesh: front, top and right side
+pub fn ortho_views(mesh: &MeshData, options: &OrthoViewOptions) -> Vec<OrthoView> {
+    VIEWS.iter().map(|view| render_view(mesh, *view, options)).collect()
+}
+
+/// Front/top/right SVG views of a loaded model for reports
+#[tauri::command]
+pub fn generate_ortho_views(
+    state: State<'_, ModelStore>,
+    handle: String,
+    options: Option<OrthoViewOptions>,
+) -> Result<Vec<OrthoView>, String> {
+    let options = options.unwrap_or_default();
+    state.with_model(&handle, |model| {
+        let mesh = model.mesh.as_ref().ok_or_else(|| "Model has no mesh".to_string())?;
+        Ok(ortho_views(mesh, &options))
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_views_show_outline_and_hide_back_edges() {
+        let (vertices, indices, normals, _) = crate::create_mesh_from_points(&[[0.0, 0.0, 0.0], [4.0, 2.0, 1.0]]);
+        let mesh = MeshData { vertices, indices, normals, face_groups: Vec::new(), triangle_face_ids: Vec::new() };
+
+        let views = ortho_views(&mesh, &OrthoViewOptions::default());
+        let front = views.iter().find(|v| v.name == "front").unwrap();
+
+        // Front outline is four edges; the back face's four edges lie behind it
+        assert!((front.width - 4.0).abs() < 1e-6 && (front.height - 1.0).abs() < 1e-6);
+        assert_eq!(front.visible_segments, 4);
+        assert_eq!(front.hidden_segments, 4);
+        assert!(front.svg.starts_with("<svg") && front.svg.contains("stroke-dasharray"));
+    }
+}
@@ -0,0 +1,135 @@
+// Per-part visibility, transparency and isolation kept with the loaded model
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::State;
+
+use crate::model_store::ModelStore;
+
+/// How one part is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PartDisplay {
+    pub visible: bool,
+    pub opacity: f64,  // 1 is opaque
+}
+
+impl Default for PartDisplay {
+    fn default() -> Self {
+        PartDisplay { visible: true, opacity: 1.0 }
+    }
+}
+
+/// Viewer configuration of a model; parts not listed use the default display
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PartDisplayState {
+    pub parts: BTreeMap<String, PartDisplay>,
+    pub isolated: Vec<String>,  // When non-empty, only these parts are shown
+}
+
+impl PartDisplayState {
+    pub fn display(&self, part_id: &str) -> PartDisplay {
+        self.parts.get(part_id).copied().unwrap_or_default()
+    }
+
+    /// Whether the part is drawn, taking isolation into account
+    pub fn is_shown(&self, part_id: &str) -> bool {
+        self.display(part_id).visible && (self.isolated.is_empty() || self.isolated.iter().any(|p| p == part_id))
+    }
+
+    fn update(&mut self, part_ids: &[String], change: impl Fn(&mut PartDisplay)) {
+        for id in part_ids {
+            let entry = self.parts.entry(id.clone()).or_default();
+            change(entry);
+            if *entry == PartDisplay::default() {
+                self.parts.remove(id);
+            }
+        }
+    }
+}
+
+/// Apply a change to parts of a loaded model, rejecting unknown part ids
+fn update_display(
+    state: &ModelStore,
+    handle: &str,
+    part_ids: &[String],
+    change: impl FnOnce(&mut PartDisplayState),
+) -> Result<PartDisplayState, String> {
+    state.with_model_mut(handle, |model| {
+        if let Some(unknown) = part_ids.iter().find(|id| !model.assembly.parts.iter().any(|p| p.id == **id)) {
+            return Err(format!("Unknown part: {}", unknown));
+        }
+        change(&mut model.display);
+        Ok(model.display.clone())
+    })?
+}
+
+/// Show or hide parts
+#[tauri::command]
+pub fn set_part_visibility(
+    state: State<'_, ModelStore>,
+    handle: String,
+    part_ids: Vec<String>,
+    visible: bool,
+) -> Result<PartDisplayState, String> {
+    update_display(&state, &handle, &part_ids, |display| display.update(&part_ids, |d| d.visible = visible))
+}
+
+/// Set the opacity of parts, from 0 (invisible) to 1 (opaque)
+#[tauri::command]
+pub fn set_part_transparency(
+    state: State<'_, ModelStore>,
+    handle: String,
+    part_ids: Vec<String>,
+    opacity: f64,
+) -> Result<PartDisplayState, String> {
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(format!("Opacity must be between 0 and 1, got {}", opacity));
+    }
+    update_display(&state, &handle, &part_ids, |display| display.update(&part_ids, |d| d.opacity = opacity))
+}
+
+/// Show only the given parts; an empty list ends isolation
+#[tauri::command]
+pub fn isolate_parts(
+    state: State<'_, ModelStore>,
+    handle: String,
+    part_ids: Vec<String>,
+) -> Result<PartDisplayState, String> {
+    update_display(&state, &handle, &part_ids, |display| display.isolated = part_ids.clone())
+}
+
+/// Current viewer configuration of a model
+#[tauri::command]
+pub fn get_part_display(state: State<'_, ModelStore>, handle: String) -> Result<PartDisplayState, String> {
+    state.with_model(&handle, |model| model.display.clone())
+}
+
+/// Show every part opaque again
+#[tauri::command]
+pub fn reset_part_display(state: State<'_, ModelStore>, handle: String) -> Result<PartDisplayState, String> {
+    update_display(&state, &handle, &[], |display| *display = PartDisplayState::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_isolation_and_defaults() {
+        let mut state = PartDisplayState::default();
+        let ids = |ids: &[&str]| ids.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        state.update(&ids(&["part-1"]), |d| d.opacity = 0.3);
+        state.update(&ids(&["part-2"]), |d| d.visible = false);
+        assert!(state.is_shown("part-1"));
+        assert!(!state.is_shown("part-2"));
+
+        state.isolated = ids(&["part-1"]);
+        assert!(!state.is_shown("part-3"));
+
+        // Restoring the default display drops the entry
+        state.update(&ids(&["part-1"]), |d| d.opacity = 1.0);
+        assert!(!state.parts.contains_key("part-1"));
+        assert_eq!(state.display("part-2"), PartDisplay { visible: false, opacity: 1.0 });
+    }
+}
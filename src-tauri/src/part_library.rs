@@ -0,0 +1,501 @@
+// Part library: index STEP files under a folder into SQLite and search them
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
+
+use crate::jobs::{run_job, JobLogLevel};
+use crate::recent_files::hash_content;
+use crate::snapshot::{render_thumbnail, THUMBNAIL_SIZE};
+use crate::storage::unix_timestamp;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS parts (
+    path TEXT PRIMARY KEY,
+    root TEXT NOT NULL,
+    filename TEXT NOT NULL,
+    name TEXT,
+    description TEXT,
+    author TEXT,
+    organization TEXT,
+    originating_system TEXT,
+    schema_name TEXT,
+    timestamp TEXT,
+    hash TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    num_faces INTEGER NOT NULL,
+    size_x REAL,
+    size_y REAL,
+    size_z REAL,
+    thumbnail TEXT,
+    modified INTEGER NOT NULL,
+    indexed_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS parts_root ON parts(root);
+CREATE INDEX IF NOT EXISTS parts_fingerprint ON parts(fingerprint);
+";
+
+/// Managed state pointing at the library database
+pub struct PartLibrary {
+    db: PathBuf,
+}
+
+impl PartLibrary {
+    pub fn new(db: PathBuf) -> Self {
+        PartLibrary { db }
+    }
+}
+
+/// Metadata from a STEP file's HEADER section
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StepHeader {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    pub organization: Option<String>,
+    pub originating_system: Option<String>,
+    pub schema: Option<String>,
+    pub timestamp: Option<String>,
+}
+
+/// One indexed file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    pub path: String,
+    pub filename: String,
+    pub header: StepHeader,
+    pub hash: String,             // SHA-256 of the file contents
+    pub fingerprint: String,      // Face counts and sorted extents; equal for the same shape saved twice
+    pub num_faces: usize,
+    pub dimensions: Option<[f64; 3]>,
+    pub thumbnail: Option<String>, // Base64 PNG
+    pub modified: u64,
+    pub indexed_at: u64,
+}
+
+/// Outcome of indexing a folder
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryIndexResult {
+    pub root: String,
+    pub scanned: usize,
+    pub indexed: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+    pub failed: Vec<LibraryIndexError>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryIndexError {
+    pub path: String,
+    pub error: String,
+}
+
+/// Per-file progress from index_folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryIndexProgress {
+    pub root: String,
+    pub done: usize,
+    pub total: usize,
+    pub path: String,
+}
+
+/// Search filters; all given filters must match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LibrarySearch {
+    pub text: Option<String>,        // Matched against file name and header fields
+    pub dimensions: Option<[f64; 3]>, // Compared as sorted extents, so orientation does not matter
+    pub size_tolerance: f64,          // Relative, per extent
+    pub fingerprint: Option<String>,
+    pub limit: usize,
+}
+
+impl Default for LibrarySearch {
+    fn default() -> Self {
+        LibrarySearch { text: None, dimensions: None, size_tolerance: 0.05, fingerprint: None, limit: 50 }
+    }
+}
+
+fn open_library(db: &Path) -> Result<Connection, String> {
+    if let Some(dir) = db.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Failed to create library directory: {}", e))?;
+    }
+    let conn = Connection::open(db).map_err(|e| format!("Failed to open part library: {}", e))?;
+    conn.execute_batch(SCHEMA).map_err(|e| format!("Failed to initialize part library: {}", e))?;
+    Ok(conn)
+}
+
+/// Top-level arguments of a header record such as FILE_NAME(...)
+fn header_args(content: &str, keyword: &str) -> Option<Vec<String>> {
+    let header = &content[..content.find("DATA;").unwrap_or(content.len())];
+    let start = header.find(keyword)? + keyword.len();
+    let body = header[start..].trim_start().strip_prefix('(')?;
+
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let (mut depth, mut quoted) = (0, false);
+    for c in body.chars() {
+        match c {
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted && depth == 0 => {
+                args.push(current);
+                return Some(args);
+            }
+            ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                args.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    None
+}
+
+/// Quoted strings in an argument, joined; None when all are empty
+fn quoted_text(arg: Option<&String>) -> Option<String> {
+    let arg = arg?;
+    let mut parts: Vec<String> = Vec::new();
+    let mut rest = arg.as_str();
+    while let Some(open) = rest.find('\'') {
+        // '' is an escaped quote inside a STEP string
+        let mut value = String::new();
+        let mut chars = rest[open + 1..].char_indices().peekable();
+        let mut end = rest.len();
+        while let Some((i, c)) = chars.next() {
+            if c == '\'' {
+                if matches!(chars.peek(), Some((_, '\''))) {
+                    value.push('\'');
+                    chars.next();
+                    continue;
+                }
+                end = open + 1 + i + 1;
+                break;
+            }
+            value.push(c);
+        }
+        if !value.trim().is_empty() {
+            parts.push(value.trim().to_string());
+        }
+        rest = &rest[end.min(rest.len())..];
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// Parse FILE_NAME, FILE_DESCRIPTION and FILE_SCHEMA
+pub fn parse_step_header(content: &str) -> StepHeader {
+    let name = header_args(content, "FILE_NAME").unwrap_or_default();
+    let description = header_args(content, "FILE_DESCRIPTION").unwrap_or_default();
+    let schema = header_args(content, "FILE_SCHEMA").unwrap_or_default();
+
+    StepHeader {
+        name: quoted_text(name.first()),
+        timestamp: quoted_text(name.get(1)),
+        author: quoted_text(name.get(2)),
+        organization: quoted_text(name.get(3)),
+        originating_system: quoted_text(name.get(5)).or_else(|| quoted_text(name.get(4))),
+        description: quoted_text(description.first()),
+        schema: quoted_text(schema.first()),
+    }
+}
+
+/// Shape signature that ignores file names, header text and orientation
+fn shape_fingerprint(analysis: &crate::StepAnalysisResult, dimensions: Option<[f64; 3]>) -> String {
+    let features = analysis.features.as_ref();
+    let mut dims = dimensions.unwrap_or([0.0; 3]);
+    dims.sort_by(f64::total_cmp);
+    format!(
+        "p{}-c{}-s{}-{:.1}x{:.1}x{:.1}",
+        features.map(|f| f.planar_faces).unwrap_or(0),
+        features.map(|f| f.cylindrical_faces).unwrap_or(0),
+        features.map(|f| f.curved_faces).unwrap_or(0),
+        dims[0], dims[1], dims[2],
+    )
+}
+
+/// Build the index entry for one STEP file
+fn index_entry(path: &Path, modified: u64) -> Result<LibraryEntry, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let content = String::from_utf8_lossy(&bytes);
+    let filename = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let analysis = crate::analyze_step_text(&content, &filename);
+    if !analysis.success {
+        return Err(analysis.error.unwrap_or_else(|| "Not a STEP file".to_string()));
+    }
+    let mesh = crate::parse_step_to_mesh(&content, &analysis, &Default::default()).ok();
+    let dimensions = mesh.as_ref().map(|(_, bbox, _)| bbox.dimensions);
+
+    Ok(LibraryEntry {
+        path: path.to_string_lossy().into_owned(),
+        filename,
+        header: parse_step_header(&content),
+        hash: hash_content(&bytes),
+        fingerprint: shape_fingerprint(&analysis, dimensions),
+        num_faces: analysis.topology.as_ref().map(|t| t.num_faces).unwrap_or(0),
+        dimensions,
+        thumbnail: mesh.as_ref().and_then(|(mesh, _, _)| render_thumbnail(mesh, THUMBNAIL_SIZE)),
+        modified,
+        indexed_at: unix_timestamp(),
+    })
+}
+
+/// STEP files under a folder, recursively
+fn find_step_files(root: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(root) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            find_step_files(&path, files);
+        } else if path.extension()
+            .map(|e| e.eq_ignore_ascii_case("step") || e.eq_ignore_ascii_case("stp"))
+            .unwrap_or(false)
+        {
+            files.push(path);
+        }
+    }
+}
+
+fn file_modified(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn insert_entry(conn: &Connection, root: &str, entry: &LibraryEntry) -> rusqlite::Result<()> {
+    let [size_x, size_y, size_z] = entry.dimensions.map(|d| d.map(Some)).unwrap_or([None; 3]);
+    conn.execute(
+        "INSERT OR REPLACE INTO parts (path, root, filename, name, description, author, organization,
+            originating_system, schema_name, timestamp, hash, fingerprint, num_faces, size_x, size_y, size_z,
+            thumbnail, modified, indexed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+        params![
+            entry.path, root, entry.filename, entry.header.name, entry.header.description, entry.header.author,
+            entry.header.organization, entry.header.originating_system, entry.header.schema, entry.header.timestamp,
+            entry.hash, entry.fingerprint, entry.num_faces as i64, size_x, size_y, size_z,
+            entry.thumbnail, entry.modified as i64, entry.indexed_at as i64,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Index (or re-index) every STEP file under `root`; unchanged files are skipped and vanished ones dropped
+pub fn index_folder(
+    db: &Path,
+    root: &Path,
+    mut on_progress: impl FnMut(&LibraryIndexProgress),
+) -> Result<LibraryIndexResult, String> {
+    if !root.is_dir() {
+        return Err(format!("Not a folder: {}", root.display()));
+    }
+    let conn = open_library(db)?;
+    let root_key = root.to_string_lossy().into_owned();
+    let sql_error = |e: rusqlite::Error| format!("Part library error: {}", e);
+
+    let mut files = Vec::new();
+    find_step_files(root, &mut files);
+    files.sort();
+    let mut result = LibraryIndexResult { root: root_key.clone(), scanned: files.len(), ..Default::default() };
+
+    for (i, path) in files.iter().enumerate() {
+        let path_key = path.to_string_lossy().into_owned();
+        on_progress(&LibraryIndexProgress { root: root_key.clone(), done: i, total: files.len(), path: path_key.clone() });
+
+        let modified = file_modified(path);
+        let known: Option<i64> = conn
+            .query_row("SELECT modified FROM parts WHERE path = ?1", params![path_key], |row| row.get(0))
+            .optional()
+            .map_err(sql_error)?;
+        if known == Some(modified as i64) {
+            result.unchanged += 1;
+            continue;
+        }
+
+        match index_entry(path, modified) {
+            Ok(entry) => {
+                insert_entry(&conn, &root_key, &entry).map_err(sql_error)?;
+                result.indexed += 1;
+            }
+            Err(error) => {
+                tracing::warn!(path = %path_key, error = %error, "part library skipped file");
+                result.failed.push(LibraryIndexError { path: path_key, error });
+            }
+        }
+    }
+
+    let known: Vec<String> = conn
+        .prepare("SELECT path FROM parts WHERE root = ?1")
+        .and_then(|mut stmt| stmt.query_map(params![root_key], |row| row.get(0))?.collect())
+        .map_err(sql_error)?;
+    for path in known.iter().filter(|p| !Path::new(p).exists()) {
+        conn.execute("DELETE FROM parts WHERE path = ?1", params![path]).map_err(sql_error)?;
+        result.removed += 1;
+    }
+
+    on_progress(&LibraryIndexProgress { root: root_key, done: files.len(), total: files.len(), path: String::new() });
+    Ok(result)
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<LibraryEntry> {
+    let size: [Option<f64>; 3] = [row.get("size_x")?, row.get("size_y")?, row.get("size_z")?];
+    Ok(LibraryEntry {
+        path: row.get("path")?,
+        filename: row.get("filename")?,
+        header: StepHeader {
+            name: row.get("name")?,
+            description: row.get("description")?,
+            author: row.get("author")?,
+            organization: row.get("organization")?,
+            originating_system: row.get("originating_system")?,
+            schema: row.get("schema_name")?,
+            timestamp: row.get("timestamp")?,
+        },
+        hash: row.get("hash")?,
+        fingerprint: row.get("fingerprint")?,
+        num_faces: row.get::<_, i64>("num_faces")? as usize,
+        dimensions: match size {
+            [Some(x), Some(y), Some(z)] => Some([x, y, z]),
+            _ => None,
+        },
+        thumbnail: row.get("thumbnail")?,
+        modified: row.get::<_, i64>("modified")? as u64,
+        indexed_at: row.get::<_, i64>("indexed_at")? as u64,
+    })
+}
+
+/// Entries matching every given filter, most recently modified first
+pub fn search_library(db: &Path, query: &LibrarySearch) -> Result<Vec<LibraryEntry>, String> {
+    let conn = open_library(db)?;
+    let pattern = query.text.as_ref()
+        .map(|t| format!("%{}%", t.trim().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")));
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT * FROM parts
+             WHERE (?1 IS NULL OR filename LIKE ?1 ESCAPE '\\' OR name LIKE ?1 ESCAPE '\\'
+                    OR description LIKE ?1 ESCAPE '\\' OR author LIKE ?1 ESCAPE '\\'
+                    OR organization LIKE ?1 ESCAPE '\\' OR originating_system LIKE ?1 ESCAPE '\\')
+               AND (?2 IS NULL OR fingerprint = ?2)
+             ORDER BY modified DESC",
+        )
+        .map_err(|e| format!("Part library error: {}", e))?;
+    let entries = stmt
+        .query_map(params![pattern, query.fingerprint], entry_from_row)
+        .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+        .map_err(|e| format!("Part library error: {}", e))?;
+
+    let sorted = |mut d: [f64; 3]| {
+        d.sort_by(f64::total_cmp);
+        d
+    };
+    Ok(entries.into_iter()
+        .filter(|entry| match (query.dimensions, entry.dimensions) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(wanted), Some(actual)) => sorted(wanted).iter().zip(sorted(actual))
+                .all(|(w, a)| (w - a).abs() <= query.size_tolerance * w.abs().max(1e-9)),
+        })
+        .take(query.limit.max(1))
+        .collect())
+}
+
+/// Index STEP files under a folder as a job, reporting progress per file
+#[tauri::command]
+pub async fn index_part_library(
+    app: AppHandle,
+    library: State<'_, PartLibrary>,
+    folder: String,
+    job_id: Option<String>,
+) -> Result<LibraryIndexResult, String> {
+    let db = library.db.clone();
+    let label = Some(folder.clone());
+    let result = run_job(app, "index_part_library", job_id, label, move |job| {
+        let result = index_folder(&db, Path::new(&folder), |progress| {
+            job.progress(progress.done, Some(progress.total), Some(progress.path.clone()));
+        })?;
+        for failure in &result.failed {
+            job.log(JobLogLevel::Warn, format!("Skipped {}: {}", failure.path, failure.error));
+        }
+        Ok(result)
+    })
+    .await?;
+
+    tracing::info!(
+        root = %result.root,
+        scanned = result.scanned,
+        indexed = result.indexed,
+        failed = result.failed.len(),
+        "part library indexed"
+    );
+    Ok(result)
+}
+
+/// Search the part library by text, size or shape fingerprint
+#[tauri::command]
+pub async fn search_part_library(
+    library: State<'_, PartLibrary>,
+    query: Option<LibrarySearch>,
+) -> Result<Vec<LibraryEntry>, String> {
+    let _metrics = crate::metrics::track("search_part_library");
+    let db = library.db.clone();
+    let query = query.unwrap_or_default();
+    crate::run_blocking(move || search_library(&db, &query)).await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BRACKET: &str = "ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('Mounting bracket'),'2;1');
+FILE_NAME('bracket.step','2024-05-01T10:00:00',('J. O''Neil'),('Ohmframe'),'ST-DEVELOPER','SolidWorks 2023','');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN { 1 0 10303 214 1 1 1 1 }'));
+ENDSEC;
+DATA;
+#1=CARTESIAN_POINT('',(0.,0.,0.));
+#2=CARTESIAN_POINT('',(40.,20.,5.));
+#3=PLANE('',#4);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+    #[test]
+    fn test_header_index_and_search() {
+        let header = parse_step_header(BRACKET);
+        assert_eq!(header.name.as_deref(), Some("bracket.step"));
+        assert_eq!(header.author.as_deref(), Some("J. O'Neil"));
+        assert_eq!(header.originating_system.as_deref(), Some("SolidWorks 2023"));
+        assert_eq!(header.description.as_deref(), Some("Mounting bracket"));
+        assert_eq!(header.schema.as_deref(), Some("AUTOMOTIVE_DESIGN { 1 0 10303 214 1 1 1 1 }"));
+
+        let dir = std::env::temp_dir().join(format!("ohmframe-library-{}", std::process::id()));
+        let parts = dir.join("parts");
+        std::fs::create_dir_all(parts.join("sub")).unwrap();
+        std::fs::write(parts.join("sub").join("bracket.STEP"), BRACKET).unwrap();
+        std::fs::write(parts.join("notes.txt"), "not a part").unwrap();
+        let db = dir.join("library.sqlite");
+
+        let result = index_folder(&db, &parts, |_| {}).unwrap();
+        assert_eq!((result.scanned, result.indexed, result.removed), (1, 1, 0));
+        assert_eq!(index_folder(&db, &parts, |_| {}).unwrap().unchanged, 1);
+
+        let by_text = search_library(&db, &LibrarySearch { text: Some("mounting".to_string()), ..Default::default() }).unwrap();
+        assert_eq!(by_text.len(), 1);
+        assert!(by_text[0].thumbnail.is_some());
+        let by_size = LibrarySearch { dimensions: Some([5.0, 40.0, 20.0]), ..Default::default() };
+        assert_eq!(search_library(&db, &by_size).unwrap().len(), 1);
+        let too_big = LibrarySearch { dimensions: Some([50.0, 40.0, 20.0]), ..Default::default() };
+        assert!(search_library(&db, &too_big).unwrap().is_empty());
+
+        std::fs::remove_file(parts.join("sub").join("bracket.STEP")).unwrap();
+        assert_eq!(index_folder(&db, &parts, |_| {}).unwrap().removed, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
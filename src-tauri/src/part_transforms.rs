@@ -0,0 +1,155 @@
+// Manual part placement overrides, persisted per model file
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::assembly_parser::ParsedPart;
+use crate::interface_detection::InterfaceDetectionResult;
+use crate::journal::{Edit, EditJournal};
+use crate::model_store::{LoadedModel, ModelStore};
+use crate::storage::{load_json, save_json};
+
+/// Part id to column-major 4x4 transform
+pub type PartTransforms = HashMap<String, [f64; 16]>;
+
+/// Managed state holding overrides keyed by the SHA-256 of the model content
+pub struct PartTransformStore {
+    path: PathBuf,
+    overrides: Mutex<HashMap<String, PartTransforms>>,
+}
+
+impl PartTransformStore {
+    /// Load overrides from disk (empty if missing)
+    pub fn load(path: PathBuf) -> Self {
+        let overrides: HashMap<String, PartTransforms> = load_json(&path);
+        PartTransformStore { path, overrides: Mutex::new(overrides) }
+    }
+
+    /// Overrides saved for a model's content
+    pub fn for_model(&self, model: &LoadedModel) -> PartTransforms {
+        self.overrides.lock().ok()
+            .and_then(|overrides| overrides.get(&model.content_hash).cloned())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save(&self, key: String, part_id: String, matrix: [f64; 16]) -> Result<(), String> {
+        let mut overrides = self.overrides.lock().map_err(|_| "Part transform state poisoned".to_string())?;
+        overrides.entry(key).or_default().insert(part_id, matrix);
+        save_json(&self.path, &*overrides)
+    }
+}
+
+/// Reject matrices that are not finite affine transforms
+fn validate_matrix(matrix: &[f64; 16]) -> Result<(), String> {
+    if matrix.iter().any(|v| !v.is_finite()) {
+        return Err("Transform contains non-finite values".to_string());
+    }
+    // Column-major: the bottom row is elements 3, 7, 11 and 15
+    if matrix[3] != 0.0 || matrix[7] != 0.0 || matrix[11] != 0.0 || matrix[15] != 1.0 {
+        return Err("Transform must be affine (bottom row 0, 0, 0, 1)".to_string());
+    }
+    Ok(())
+}
+
+/// Drop interfaces that involve a moved part; returns how many were removed
+fn invalidate_part_interfaces(result: &mut InterfaceDetectionResult, part_id: &str) -> usize {
+    let before = result.interfaces.len();
+    result.interfaces.retain(|i| i.part_a_id != part_id && i.part_b_id != part_id);
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for interface in &result.interfaces {
+        *counts.entry(&interface.part_a_id).or_insert(0) += 1;
+        *counts.entry(&interface.part_b_id).or_insert(0) += 1;
+    }
+    result.junction_parts.retain(|p| counts.get(p.as_str()).copied().unwrap_or(0) > 1);
+    result.total_interfaces = result.interfaces.len();
+
+    before - result.interfaces.len()
+}
+
+/// Apply saved overrides to a freshly parsed model
+pub fn apply_part_transforms(model: &mut LoadedModel, transforms: &PartTransforms) {
+    for part in &mut model.assembly.parts {
+        if let Some(matrix) = transforms.get(&part.id) {
+            part.transform = *matrix;
+        }
+    }
+}
+
+/// Replace a part's placement, drop its stale interfaces and remember the override for this file
+#[tauri::command]
+pub fn set_part_transform(
+    state: State<'_, ModelStore>,
+    transforms: State<'_, PartTransformStore>,
+    journal: State<'_, EditJournal>,
+    handle: String,
+    part_id: String,
+    matrix: [f64; 16],
+) -> Result<ParsedPart, String> {
+    validate_matrix(&matrix)?;
+
+    let (part, key, edit) = state.with_model_mut(&handle, |model| {
+        let interfaces_before = model.interfaces.clone();
+        let part = model.assembly.parts.iter_mut()
+            .find(|p| p.id == part_id)
+            .ok_or_else(|| format!("Unknown part: {}", part_id))?;
+        let before = std::mem::replace(&mut part.transform, matrix);
+        let part = part.clone();
+
+        if let Some(interfaces) = model.interfaces.as_mut() {
+            let removed = invalidate_part_interfaces(interfaces, &part_id);
+            tracing::debug!(part_id = %part_id, removed, "interfaces invalidated by part move");
+        }
+        let edit = Edit::PartTransform {
+            handle: handle.clone(),
+            part_id: part_id.clone(),
+            before,
+            after: matrix,
+            interfaces_before,
+            interfaces_after: model.interfaces.clone(),
+        };
+        Ok::<_, String>((part, model.content_hash.clone(), edit))
+    })??;
+
+    transforms.save(key, part_id.clone(), matrix)?;
+    journal.record(format!("Move {}", part.name), edit);
+    tracing::info!(handle = %handle, part_id = %part_id, "part transform overridden");
+    Ok(part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::interface;
+
+    #[test]
+    fn test_moving_part_drops_its_interfaces() {
+        let mut result = InterfaceDetectionResult {
+            success: true,
+            error: None,
+            interfaces: vec![
+                interface("i0", "a", "b", [0.0; 3]),
+                interface("i1", "b", "c", [0.0; 3]),
+                interface("i2", "c", "d", [0.0; 3]),
+            ],
+            junction_parts: vec!["b".to_string(), "c".to_string()],
+            total_interfaces: 3,
+        };
+
+        assert_eq!(invalidate_part_interfaces(&mut result, "a"), 1);
+        assert_eq!(result.total_interfaces, 2);
+        assert_eq!(result.junction_parts, vec!["c".to_string()]);
+
+        let mut matrix = [0.0; 16];
+        matrix[0] = 1.0;
+        matrix[5] = 1.0;
+        matrix[10] = 1.0;
+        matrix[12] = 25.0;
+        matrix[15] = 1.0;
+        assert!(validate_matrix(&matrix).is_ok());
+        matrix[3] = 1.0;
+        assert!(validate_matrix(&matrix).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+// Generic PLM connector: fetch a part's STEP file by part number from a configurable REST endpoint
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+use tauri_plugin_http::reqwest;
+
+use crate::credentials::{delete_secret, load_secret, store_secret};
+use crate::jobs::{run_async_job, JobLogLevel};
+use crate::model_store::{load_model, ModelInfo, ModelStore};
+use crate::part_transforms::PartTransformStore;
+use crate::recent_files::RecentFilesState;
+use crate::storage::{load_json, save_json};
+
+/// PLM servers can be slow to render STEP for large assemblies
+const FETCH_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A configured PLM endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlmConnector {
+    pub id: String,
+    pub name: String,
+    pub url_template: String,          // e.g. https://plm.example.com/api/parts/{part_number}/step?rev={revision}
+    pub auth_header: Option<String>,   // Header name, e.g. "Authorization"; the value lives in the keychain
+}
+
+/// Connector as listed to the frontend; secrets are never sent back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlmConnectorInfo {
+    #[serde(flatten)]
+    pub connector: PlmConnector,
+    pub has_secret: bool,
+}
+
+/// Result of fetching a part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlmFetch {
+    pub connector_id: String,
+    pub part_number: String,
+    pub revision: Option<String>,
+    pub url: String,
+    pub model: ModelInfo,
+}
+
+/// Managed state holding the configured connectors
+pub struct PlmConnectorStore {
+    path: PathBuf,
+    connectors: Mutex<Vec<PlmConnector>>,
+}
+
+impl PlmConnectorStore {
+    /// Load connectors from disk (empty if missing)
+    pub fn load(path: PathBuf) -> Self {
+        let connectors: Vec<PlmConnector> = load_json(&path);
+        PlmConnectorStore { path, connectors: Mutex::new(connectors) }
+    }
+
+    fn find(&self, id: &str) -> Result<PlmConnector, String> {
+        let connectors = self.connectors.lock().map_err(|_| "PLM connector state poisoned".to_string())?;
+        connectors.iter().find(|c| c.id == id).cloned().ok_or_else(|| format!("Unknown PLM connector '{}'", id))
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut Vec<PlmConnector>) -> T) -> Result<T, String> {
+        let mut connectors = self.connectors.lock().map_err(|_| "PLM connector state poisoned".to_string())?;
+        let result = f(&mut connectors);
+        save_json(&self.path, &*connectors)?;
+        Ok(result)
+    }
+}
+
+fn secret_name(connector_id: &str) -> String {
+    format!("plm-{}", connector_id)
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters, so part numbers are safe in paths and queries
+fn encode_component(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Fill `{part_number}` and `{revision}` into a URL template
+pub fn expand_url_template(template: &str, part_number: &str, revision: Option<&str>) -> Result<String, String> {
+    let mut url = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        url.push_str(&rest[..start]);
+        let end = rest[start..].find('}').ok_or("Unclosed '{' in URL template")? + start;
+        let value = match &rest[start + 1..end] {
+            "part_number" => part_number,
+            "revision" => revision.ok_or("This connector needs a revision")?,
+            other => return Err(format!("Unknown URL template placeholder '{{{}}}'", other)),
+        };
+        url.push_str(&encode_component(value));
+        rest = &rest[end + 1..];
+    }
+    url.push_str(rest);
+
+    let parsed = url::Url::parse(&url).map_err(|e| format!("URL template does not produce a valid URL: {}", e))?;
+    if parsed.scheme() != "https" && parsed.scheme() != "http" {
+        return Err("PLM connectors must use http or https".to_string());
+    }
+    Ok(url)
+}
+
+/// File name from a Content-Disposition header, if the server sent one
+fn disposition_filename(header: &str) -> Option<String> {
+    let start = header.find("filename=")? + "filename=".len();
+    let name = header[start..].split(';').next()?.trim().trim_matches('"');
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Configured connectors
+#[tauri::command]
+pub fn list_plm_connectors(store: State<'_, PlmConnectorStore>) -> Result<Vec<PlmConnectorInfo>, String> {
+    let connectors = store.connectors.lock().map_err(|_| "PLM connector state poisoned".to_string())?.clone();
+    connectors.into_iter()
+        .map(|connector| {
+            let has_secret = connector.auth_header.is_some() && load_secret(&secret_name(&connector.id))?.is_some();
+            Ok(PlmConnectorInfo { connector, has_secret })
+        })
+        .collect()
+}
+
+/// Add or replace a connector; `secret` replaces the stored auth value, an empty one removes it
+#[tauri::command]
+pub fn save_plm_connector(
+    store: State<'_, PlmConnectorStore>,
+    connector: PlmConnector,
+    secret: Option<String>,
+) -> Result<PlmConnector, String> {
+    if connector.id.trim().is_empty() || connector.name.trim().is_empty() {
+        return Err("Connector id and name are required".to_string());
+    }
+    if !connector.url_template.contains("{part_number}") {
+        return Err("URL template must contain {part_number}".to_string());
+    }
+    expand_url_template(&connector.url_template, "PN-0001", Some("A"))?;
+
+    match secret.as_deref().map(str::trim) {
+        Some("") => {
+            delete_secret(&secret_name(&connector.id))?;
+        }
+        Some(value) => store_secret(&secret_name(&connector.id), value)?,
+        None => {}
+    }
+
+    tracing::info!(connector = %connector.id, "saving PLM connector");
+    let saved = connector.clone();
+    store.update(move |connectors| match connectors.iter_mut().find(|c| c.id == connector.id) {
+        Some(existing) => *existing = connector,
+        None => connectors.push(connector),
+    })?;
+    Ok(saved)
+}
+
+/// Remove a connector and its stored secret
+#[tauri::command]
+pub fn delete_plm_connector(store: State<'_, PlmConnectorStore>, id: String) -> Result<bool, String> {
+    delete_secret(&secret_name(&id))?;
+    store.update(|connectors| {
+        let before = connectors.len();
+        connectors.retain(|c| c.id != id);
+        connectors.len() != before
+    })
+}
+
+/// Download a part's STEP file from a PLM connector and load it as a model
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn fetch_plm_part(
+    app: AppHandle,
+    state: State<'_, ModelStore>,
+    transforms: State<'_, PartTransformStore>,
+    recent: State<'_, RecentFilesState>,
+    store: State<'_, PlmConnectorStore>,
+    connector_id: String,
+    part_number: String,
+    revision: Option<String>,
+    job_id: Option<String>,
+) -> Result<PlmFetch, String> {
+    let connector = store.find(&connector_id)?;
+    let part_number = part_number.trim().to_string();
+    if part_number.is_empty() {
+        return Err("Part number is required".to_string());
+    }
+    let url = expand_url_template(&connector.url_template, &part_number, revision.as_deref())?;
+    let auth = match &connector.auth_header {
+        Some(header) => {
+            let value = load_secret(&secret_name(&connector.id))?
+                .ok_or_else(|| format!("No credentials stored for {}", connector.name))?;
+            Some((header.clone(), value))
+        }
+        None => None,
+    };
+
+    let request_url = url.clone();
+    let requested = part_number.clone();
+    let label = Some(part_number.clone());
+    let (filename, content) = run_async_job(app, "fetch_plm_part", job_id, label, |job| async move {
+        let client = reqwest::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let mut request = client.get(&request_url);
+        if let Some((header, value)) = &auth {
+            request = request.header(header.as_str(), value.as_str());
+        }
+        job.log(JobLogLevel::Info, format!("Requesting {} from {}", part_number, connector.name));
+
+        let response = request.send().await.map_err(|e| format!("{} request failed: {}", connector.name, e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("{} returned {} for {}", connector.name, status, part_number));
+        }
+        let filename = response.headers()
+            .get(reqwest::header::CONTENT_DISPOSITION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(disposition_filename)
+            .unwrap_or_else(|| format!("{}.step", part_number));
+        let bytes = response.bytes().await.map_err(|e| format!("Download from {} failed: {}", connector.name, e))?;
+
+        // Login pages and JSON errors come back as 200 from some PLM gateways
+        let content = String::from_utf8_lossy(&bytes).into_owned();
+        if !content.trim_start().starts_with("ISO-10303-21") {
+            return Err(format!("{} did not return a STEP file for {}", connector.name, part_number));
+        }
+        job.log(JobLogLevel::Info, format!("Downloaded {} bytes", bytes.len()));
+        Ok((filename, content))
+    })
+    .await?;
+
+    tracing::info!(connector = %connector_id, filename = %filename, "fetched part from PLM");
+    let model = load_model(state, transforms, recent, Some(content), None, Some(filename))?;
+    Ok(PlmFetch { connector_id, part_number: requested, revision, url, model })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_template_expansion() {
+        let template = "https://plm.example.com/api/parts/{part_number}/step?rev={revision}";
+        assert_eq!(
+            expand_url_template(template, "BRK 100/2", Some("B")).unwrap(),
+            "https://plm.example.com/api/parts/BRK%20100%2F2/step?rev=B"
+        );
+        assert!(expand_url_template(template, "BRK-100", None).unwrap_err().contains("revision"));
+        assert!(expand_url_template("https://plm/{part}", "X", None).is_err());
+        assert!(expand_url_template("file:///etc/{part_number}", "X", None).is_err());
+
+        assert_eq!(disposition_filename("attachment; filename=\"BRK-100_B.stp\""), Some("BRK-100_B.stp".to_string()));
+        assert_eq!(disposition_filename("inline"), None);
+    }
+}
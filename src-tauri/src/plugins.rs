@@ -0,0 +1,303 @@
+// Sandboxed WASM plugins that run custom analyses over a loaded model
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State};
+use wasmi::{Config, Engine, Instance, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::assembly_parser::ParsedPart;
+use crate::interface_detection::InterfaceDetectionResult;
+use crate::jobs::{run_job, JobLogLevel};
+use crate::model_store::ModelStore;
+
+/// Version of the JSON passed to `analyze`; bumped on incompatible changes
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+/// Instructions a single run may execute before it is stopped
+const PLUGIN_FUEL: u64 = 2_000_000_000;
+
+/// Budget for `describe`, which runs while the plugins folder is scanned
+const DESCRIBE_FUEL: u64 = 10_000_000;
+
+/// Linear memory a plugin may grow to
+const PLUGIN_MEMORY_LIMIT: usize = 256 * 1024 * 1024;
+
+/// A plugin found in the plugins folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub id: String,              // File stem; what run_plugin is called with
+    pub name: String,
+    pub description: Option<String>,
+    pub version: Option<String>,
+    pub path: String,
+}
+
+/// Optional manifest a plugin returns from `describe`
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PluginManifest {
+    name: Option<String>,
+    description: Option<String>,
+    version: Option<String>,
+}
+
+/// What a plugin receives
+#[derive(Debug, Serialize)]
+struct PluginInput<'a> {
+    api_version: u32,
+    filename: &'a str,
+    parts: &'a [ParsedPart],
+    interfaces: Option<&'a InterfaceDetectionResult>,
+    options: serde_json::Value,
+}
+
+/// Output of one plugin run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginRunResult {
+    pub plugin_id: String,
+    pub output: serde_json::Value,
+    pub fuel_used: u64,
+}
+
+struct LoadedPlugin {
+    info: PluginInfo,
+    module: Arc<Module>,
+}
+
+/// Managed state holding compiled plugins.
+/// A plugin exports `memory`, `alloc(len) -> ptr` and `analyze(ptr, len) -> (ptr << 32) | len`, reading
+/// and returning JSON; an optional `describe()` returns its manifest the same way
+pub struct PluginHost {
+    dir: PathBuf,
+    engine: Engine,
+    plugins: Mutex<Vec<LoadedPlugin>>,
+}
+
+/// Per-run store data
+struct PluginState {
+    limits: StoreLimits,
+}
+
+/// Output pointer and length packed by the plugin as (ptr << 32) | len
+fn unpack(packed: i64) -> (usize, usize) {
+    (((packed as u64) >> 32) as usize, (packed as u64 & 0xffff_ffff) as usize)
+}
+
+fn plugin_error(id: &str, e: impl std::fmt::Display) -> String {
+    format!("Plugin {} failed: {}", id, e)
+}
+
+impl PluginHost {
+    pub fn new(dir: PathBuf) -> Self {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let host = PluginHost { dir, engine: Engine::new(&config), plugins: Mutex::new(Vec::new()) };
+        if let Err(e) = host.reload() {
+            tracing::warn!(error = %e, "plugins not loaded");
+        }
+        host
+    }
+
+    /// Compile every `.wasm` file in the plugins folder; broken plugins are logged and skipped
+    pub fn reload(&self) -> Result<Vec<PluginInfo>, String> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create plugins folder: {}", e))?;
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.dir)
+            .map_err(|e| format!("Failed to read plugins folder: {}", e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map(|e| e.eq_ignore_ascii_case("wasm")).unwrap_or(false))
+            .collect();
+        paths.sort();
+
+        let loaded: Vec<LoadedPlugin> = paths.iter()
+            .filter_map(|path| match self.load(path) {
+                Ok(plugin) => Some(plugin),
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "plugin skipped");
+                    None
+                }
+            })
+            .collect();
+        let infos = loaded.iter().map(|p| p.info.clone()).collect();
+
+        *self.plugins.lock().map_err(|_| "Plugin host poisoned".to_string())? = loaded;
+        tracing::info!(dir = %self.dir.display(), "plugins loaded");
+        Ok(infos)
+    }
+
+    fn load(&self, path: &Path) -> Result<LoadedPlugin, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read plugin: {}", e))?;
+        let id = path.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+        let module = Module::new(&self.engine, &bytes).map_err(|e| plugin_error(&id, e))?;
+
+        let manifest = describe(&self.engine, &module).unwrap_or_default();
+        let info = PluginInfo {
+            name: manifest.name.unwrap_or_else(|| id.clone()),
+            description: manifest.description,
+            version: manifest.version,
+            path: path.to_string_lossy().into_owned(),
+            id,
+        };
+        Ok(LoadedPlugin { info, module: Arc::new(module) })
+    }
+
+    fn find(&self, id: &str) -> Result<(PluginInfo, Arc<Module>), String> {
+        let plugins = self.plugins.lock().map_err(|_| "Plugin host poisoned".to_string())?;
+        plugins.iter()
+            .find(|p| p.info.id == id)
+            .map(|p| (p.info.clone(), Arc::clone(&p.module)))
+            .ok_or_else(|| format!("Unknown plugin: {}", id))
+    }
+}
+
+fn instantiate(engine: &Engine, module: &Module, fuel: u64) -> Result<(Store<PluginState>, Instance), wasmi::Error> {
+    let limits = StoreLimitsBuilder::new().memory_size(PLUGIN_MEMORY_LIMIT).build();
+    let mut store = Store::new(engine, PluginState { limits });
+    store.limiter(|state| &mut state.limits);
+    store.set_fuel(fuel)?;
+    // No host imports: plugins see only the JSON they are given
+    let linker = Linker::<PluginState>::new(engine);
+    let instance = linker.instantiate(&mut store, module)?.start(&mut store)?;
+    Ok((store, instance))
+}
+
+fn describe(engine: &Engine, module: &Module) -> Option<PluginManifest> {
+    let (mut store, instance) = instantiate(engine, module, DESCRIBE_FUEL).ok()?;
+    let describe = instance.get_typed_func::<(), i64>(&store, "describe").ok()?;
+    let (ptr, len) = unpack(describe.call(&mut store, ()).ok()?);
+    let memory = instance.get_memory(&store, "memory")?;
+    let bytes = memory.data(&store).get(ptr..ptr.checked_add(len)?)?;
+    serde_json::from_slice(bytes).ok()
+}
+
+/// Call the plugin's `analyze` with the input JSON and parse the JSON it returns
+pub fn run_plugin_module(
+    engine: &Engine,
+    id: &str,
+    module: &Module,
+    input: &[u8],
+    fuel: u64,
+) -> Result<PluginRunResult, String> {
+    let (mut store, instance) = instantiate(engine, module, fuel).map_err(|e| plugin_error(id, e))?;
+    let memory = instance.get_memory(&store, "memory")
+        .ok_or_else(|| plugin_error(id, "no exported memory"))?;
+    let alloc = instance.get_typed_func::<i32, i32>(&store, "alloc").map_err(|e| plugin_error(id, e))?;
+    let analyze = instance.get_typed_func::<(i32, i32), i64>(&store, "analyze").map_err(|e| plugin_error(id, e))?;
+
+    let len = i32::try_from(input.len()).map_err(|_| plugin_error(id, "input too large"))?;
+    let ptr = alloc.call(&mut store, len).map_err(|e| plugin_error(id, e))?;
+    memory.write(&mut store, ptr as u32 as usize, input).map_err(|e| plugin_error(id, e))?;
+
+    let (out_ptr, out_len) = unpack(analyze.call(&mut store, (ptr, len)).map_err(|e| plugin_error(id, e))?);
+    let output = memory.data(&store)
+        .get(out_ptr..out_ptr.saturating_add(out_len))
+        .ok_or_else(|| plugin_error(id, "output is outside plugin memory"))?;
+    let output = serde_json::from_slice(output).map_err(|e| plugin_error(id, format!("output is not JSON: {}", e)))?;
+
+    let fuel_used = fuel - store.get_fuel().unwrap_or(0);
+    Ok(PluginRunResult { plugin_id: id.to_string(), output, fuel_used })
+}
+
+/// Plugins currently loaded
+#[tauri::command]
+pub fn list_plugins(host: State<'_, PluginHost>) -> Result<Vec<PluginInfo>, String> {
+    let plugins = host.plugins.lock().map_err(|_| "Plugin host poisoned".to_string())?;
+    Ok(plugins.iter().map(|p| p.info.clone()).collect())
+}
+
+/// Rescan the plugins folder
+#[tauri::command]
+pub fn reload_plugins(host: State<'_, PluginHost>) -> Result<Vec<PluginInfo>, String> {
+    host.reload()
+}
+
+/// Run a plugin over a loaded model's parts and interfaces
+#[tauri::command]
+pub async fn run_plugin(
+    app: AppHandle,
+    state: State<'_, ModelStore>,
+    host: State<'_, PluginHost>,
+    handle: String,
+    plugin_id: String,
+    options: Option<serde_json::Value>,
+    job_id: Option<String>,
+) -> Result<PluginRunResult, String> {
+    let (info, module) = host.find(&plugin_id)?;
+    let input = state.with_model(&handle, |model| {
+        serde_json::to_vec(&PluginInput {
+            api_version: PLUGIN_API_VERSION,
+            filename: &model.filename,
+            parts: &model.assembly.parts,
+            interfaces: model.interfaces.as_ref(),
+            options: options.unwrap_or(serde_json::Value::Null),
+        })
+        .map_err(|e| format!("Failed to serialize plugin input: {}", e))
+    })??;
+
+    // Engine and module are cheap handles, so the run does not hold the managed state
+    let engine = host.engine.clone();
+    let label = Some(info.name.clone());
+    run_job(app, "run_plugin", job_id, label, move |job| {
+        let result = run_plugin_module(&engine, &info.id, &module, &input, PLUGIN_FUEL)?;
+        job.log(JobLogLevel::Info, format!("{} used {} fuel", info.name, result.fuel_used));
+        Ok(result)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ECHO: &str = r#"
+        (module
+          (memory (export "memory") 2)
+          (global $next (mut i32) (i32.const 1024))
+          (data (i32.const 16) "{\"name\":\"Echo\",\"version\":\"1.0\"}")
+          (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $len)))
+            (local.get $ptr))
+          (func (export "describe") (result i64)
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 31)))
+          (func (export "analyze") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+              (i64.extend_i32_u (local.get $len)))))
+    "#;
+
+    const SPIN: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "analyze") (param i32 i32) (result i64)
+            (loop $forever (br $forever))
+            (i64.const 0)))
+    "#;
+
+    #[test]
+    fn test_echo_plugin_and_fuel_limit() {
+        let dir = std::env::temp_dir().join(format!("ohmframe-plugins-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("echo.wasm"), wat::parse_str(ECHO).unwrap()).unwrap();
+        std::fs::write(dir.join("spin.wasm"), wat::parse_str(SPIN).unwrap()).unwrap();
+        std::fs::write(dir.join("broken.wasm"), b"not wasm").unwrap();
+
+        let host = PluginHost::new(dir.clone());
+        let infos = host.reload().unwrap();
+        let names: Vec<&str> = infos.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Echo", "spin"]);
+
+        let (info, module) = host.find("echo").unwrap();
+        let input = br#"{"api_version":1,"parts":[{"id":"part-1"}]}"#;
+        let result = run_plugin_module(&host.engine, &info.id, &module, input, 1_000_000).unwrap();
+        assert_eq!(result.output["parts"][0]["id"], "part-1");
+        assert!(result.fuel_used > 0);
+
+        let (info, module) = host.find("spin").unwrap();
+        assert!(run_plugin_module(&host.engine, &info.id, &module, b"{}", 1_000_000).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
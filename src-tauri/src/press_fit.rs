@@ -0,0 +1,228 @@
+// Press-fit contact pressure, stresses and assembly force from Lamé thick-cylinder equations
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::materials::{validate_material, Material};
+use crate::model_store::ModelStore;
+use crate::validation::{Checked, FieldErrors, Validate};
+
+fn default_friction() -> f64 {
+    0.15  // Dry steel on steel, pressed in
+}
+
+/// Hollow shaft pressed into a hub; all lengths in mm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PressFitGeometry {
+    pub diameter: f64,            // Nominal interface diameter
+    #[serde(default)]
+    pub shaft_bore: f64,          // 0 for a solid shaft
+    pub hub_outer_diameter: f64,
+    pub length: f64,              // Engaged length
+    pub shaft_material: Material,
+    pub hub_material: Material,
+    #[serde(default = "default_friction")]
+    pub friction_coefficient: f64,
+}
+
+impl PressFitGeometry {
+    fn validate_fields(&self, errors: &mut FieldErrors) {
+        errors.above("diameter", self.diameter, 0.0);
+        errors.at_least("shaft_bore", self.shaft_bore, 0.0);
+        errors.above("length", self.length, 0.0);
+        errors.within("friction_coefficient", self.friction_coefficient, 0.0, 1.0);
+        if self.shaft_bore >= self.diameter {
+            errors.add("shaft_bore", format!("expected less than the diameter {}, got {}", self.diameter, self.shaft_bore));
+        }
+        if self.hub_outer_diameter <= self.diameter {
+            errors.add("hub_outer_diameter", format!("expected more than the diameter {}, got {}", self.diameter, self.hub_outer_diameter));
+        }
+        validate_material(&self.shaft_material, "shaft_material", errors);
+        validate_material(&self.hub_material, "hub_material", errors);
+    }
+}
+
+impl Validate for PressFitGeometry {
+    fn validate(&self, errors: &mut FieldErrors) {
+        self.validate_fields(errors);
+    }
+}
+
+/// Geometry plus the diametral interference to evaluate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PressFitInput {
+    pub interference: f64,  // Diametral (mm)
+    #[serde(flatten)]
+    pub geometry: PressFitGeometry,
+}
+
+impl Validate for PressFitInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.above("interference", self.interference, 0.0);
+        self.geometry.validate_fields(errors);
+    }
+}
+
+/// Stresses in MPa, force in N, torque in N·m
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PressFitResult {
+    pub interference: f64,
+    pub contact_pressure: f64,
+    pub hub_hoop_stress: f64,          // Tensile, at the hub bore
+    pub shaft_hoop_stress: f64,        // Compressive, at the shaft surface
+    pub hub_von_mises: f64,
+    pub shaft_von_mises: f64,          // Peak; at the bore of a hollow shaft
+    pub hub_safety_factor: f64,        // Yield over von Mises
+    pub shaft_safety_factor: f64,
+    pub press_force: f64,
+    pub torque_capacity: f64,
+    pub warnings: Vec<String>,
+}
+
+fn von_mises(hoop: f64, radial: f64) -> f64 {
+    (hoop * hoop - hoop * radial + radial * radial).sqrt()
+}
+
+/// Lamé solution for one diametral interference
+pub fn compute_press_fit(interference: f64, geometry: &PressFitGeometry) -> PressFitResult {
+    let d2 = geometry.diameter * geometry.diameter;
+    let do2 = geometry.hub_outer_diameter * geometry.hub_outer_diameter;
+    let di2 = geometry.shaft_bore * geometry.shaft_bore;
+    let hub = &geometry.hub_material;
+    let shaft = &geometry.shaft_material;
+
+    // Radial compliance of hub and shaft at the interface
+    let hub_ratio = (do2 + d2) / (do2 - d2);
+    let shaft_ratio = (d2 + di2) / (d2 - di2);
+    let compliance = (hub_ratio + hub.poisson_ratio) / hub.elastic_modulus
+        + (shaft_ratio - shaft.poisson_ratio) / shaft.elastic_modulus;
+    let pressure = interference / (geometry.diameter * compliance);
+
+    let hub_hoop_stress = pressure * hub_ratio;
+    let shaft_hoop_stress = -pressure * shaft_ratio;
+    let hub_von_mises = von_mises(hub_hoop_stress, -pressure);
+    let shaft_von_mises = if geometry.shaft_bore > 0.0 {
+        // Bore surface has no radial stress and the largest hoop stress
+        let bore_hoop = -2.0 * pressure * d2 / (d2 - di2);
+        von_mises(bore_hoop, 0.0).max(von_mises(shaft_hoop_stress, -pressure))
+    } else {
+        von_mises(shaft_hoop_stress, -pressure)
+    };
+    let hub_safety_factor = hub.yield_strength / hub_von_mises;
+    let shaft_safety_factor = shaft.yield_strength / shaft_von_mises;
+
+    let area = std::f64::consts::PI * geometry.diameter * geometry.length;
+    let press_force = geometry.friction_coefficient * pressure * area;
+    let torque_capacity = press_force * geometry.diameter / 2.0 / 1000.0;
+
+    let mut warnings = Vec::new();
+    if hub_safety_factor < 1.0 {
+        warnings.push(format!("Hub yields at the bore ({:.0} MPa von Mises vs {:.0} MPa yield)", hub_von_mises, hub.yield_strength));
+    }
+    if shaft_safety_factor < 1.0 {
+        warnings.push(format!("Shaft yields ({:.0} MPa von Mises vs {:.0} MPa yield)", shaft_von_mises, shaft.yield_strength));
+    }
+    if geometry.length > 2.0 * geometry.diameter {
+        warnings.push("Engaged length above twice the diameter; pressure is uneven and force is overestimated".to_string());
+    }
+    tracing::debug!(interference, pressure, press_force, "press fit computed");
+
+    PressFitResult {
+        interference,
+        contact_pressure: pressure,
+        hub_hoop_stress,
+        shaft_hoop_stress,
+        hub_von_mises,
+        shaft_von_mises,
+        hub_safety_factor,
+        shaft_safety_factor,
+        press_force,
+        torque_capacity,
+        warnings,
+    }
+}
+
+/// Press fit at both ends of a detected interface's interference range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterfacePressFit {
+    pub interface_id: String,
+    pub max_interference: PressFitResult,
+    pub min_interference: Option<PressFitResult>,  // None when the fit can also have clearance
+}
+
+/// Contact pressure, stresses and press force for an interference fit
+#[tauri::command]
+pub fn calculate_press_fit(input: Checked<PressFitInput>) -> PressFitResult {
+    compute_press_fit(input.interference, &input.geometry)
+}
+
+/// Press-fit check for an interface whose fit was analyzed; interference comes from the stored fit
+#[tauri::command]
+pub fn analyze_interface_press_fit(
+    state: State<'_, ModelStore>,
+    handle: String,
+    interface_id: String,
+    geometry: Checked<PressFitGeometry>,
+) -> Result<InterfacePressFit, String> {
+    let fit = state.with_model(&handle, |model| {
+        let interface = model.interfaces.as_ref()
+            .and_then(|r| r.interfaces.iter().find(|i| i.id == interface_id))
+            .ok_or_else(|| format!("Unknown interface: {}", interface_id))?;
+        interface.fit.clone()
+            .ok_or_else(|| format!("Interface {} has no fit analysis; analyze its fit first", interface_id))
+    })??;
+
+    if fit.min_clearance >= 0.0 {
+        return Err(format!("Interface {} is a clearance fit; there is no interference to press", interface_id));
+    }
+    Ok(InterfacePressFit {
+        interface_id,
+        max_interference: compute_press_fit(-fit.min_clearance, &geometry),
+        min_interference: (fit.max_clearance < 0.0).then(|| compute_press_fit(-fit.max_clearance, &geometry)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::materials::material_library;
+    use crate::validation::validate;
+
+    fn steel_geometry(shaft_bore: f64) -> PressFitGeometry {
+        let steel = material_library().remove(0);
+        PressFitGeometry {
+            diameter: 50.0,
+            shaft_bore,
+            hub_outer_diameter: 100.0,
+            length: 40.0,
+            shaft_material: steel.clone(),
+            hub_material: steel,
+            friction_coefficient: 0.15,
+        }
+    }
+
+    #[test]
+    fn test_solid_steel_shaft() {
+        // Same material, solid shaft: p = E δ (D² - d²) / (2 d D²)
+        let geometry = steel_geometry(0.0);
+        let result = compute_press_fit(0.05, &geometry);
+        let expected = 210_000.0 * 0.05 * (100.0f64.powi(2) - 50.0f64.powi(2)) / (2.0 * 50.0 * 100.0f64.powi(2));
+        assert!((result.contact_pressure - expected).abs() < 1e-9, "{}", result.contact_pressure);
+        assert!((result.hub_hoop_stress - expected * 5.0 / 3.0).abs() < 1e-9);
+        assert!((result.shaft_hoop_stress + expected).abs() < 1e-9);
+        let force = 0.15 * expected * std::f64::consts::PI * 50.0 * 40.0;
+        assert!((result.press_force - force).abs() < 1e-6);
+        assert!(result.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_hollow_shaft_is_softer() {
+        let solid = compute_press_fit(0.05, &steel_geometry(0.0));
+        let hollow = compute_press_fit(0.05, &steel_geometry(40.0));
+        assert!(hollow.contact_pressure < solid.contact_pressure);
+        assert!(hollow.shaft_von_mises > hollow.contact_pressure);
+
+        let input = PressFitInput { interference: 0.05, geometry: steel_geometry(60.0) };
+        assert!(validate(&input).unwrap_err().contains("shaft_bore"));
+    }
+}
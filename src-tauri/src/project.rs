@@ -0,0 +1,166 @@
+// .ohmproj project bundles: loaded models with their edits and stackup drafts in one file
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+
+use crate::autosave::{
+    model_snapshot, restore_model, AutosaveState, ModelSnapshot, RecoveredSession, RecoveryFailure, StackupDraft,
+};
+use crate::encryption::{is_encrypted, EncryptionState};
+use crate::model_store::ModelStore;
+use crate::part_transforms::PartTransformStore;
+use crate::recent_files::RecentFilesState;
+use crate::session::SessionState;
+use crate::source_integrity::{check_source, SourceCheck};
+use crate::storage::unix_timestamp;
+
+pub const PROJECT_EXTENSION: &str = "ohmproj";
+const FORMAT_VERSION: u32 = 1;
+
+/// A model with its STEP text, so the bundle opens without the original file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectModel {
+    #[serde(flatten)]
+    pub snapshot: ModelSnapshot,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectFile {
+    pub format_version: u32,
+    pub saved_at: u64,
+    pub models: Vec<ProjectModel>,
+    pub active_handle: Option<String>,
+    pub stackups: Vec<StackupDraft>,
+}
+
+/// Result of open_project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenedProject {
+    #[serde(flatten)]
+    pub session: RecoveredSession,
+    pub stale_sources: Vec<SourceCheck>,   // Source files edited or gone since the project was saved
+}
+
+/// Result of save_project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectSaved {
+    pub path: String,
+    pub models: usize,
+    pub stackups: usize,
+    pub encrypted: bool,
+}
+
+fn check_extension(path: &Path) -> Result<(), String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case(PROJECT_EXTENSION) => Ok(()),
+        _ => Err(format!("Project files must end in .{}", PROJECT_EXTENSION)),
+    }
+}
+
+fn parse_project(bytes: &[u8]) -> Result<ProjectFile, String> {
+    let project: ProjectFile = serde_json::from_slice(bytes).map_err(|e| format!("Invalid project file: {}", e))?;
+    if project.format_version > FORMAT_VERSION {
+        return Err(format!("Project was saved by a newer version (format {})", project.format_version));
+    }
+    Ok(project)
+}
+
+/// Save every loaded model and stackup draft to a project bundle, encrypted when enabled
+#[tauri::command]
+pub fn save_project(
+    models: State<'_, ModelStore>,
+    autosave: State<'_, AutosaveState>,
+    session: State<'_, SessionState>,
+    encryption: State<'_, EncryptionState>,
+    path: String,
+) -> Result<ProjectSaved, String> {
+    let target = Path::new(&path);
+    check_extension(target)?;
+    let project = ProjectFile {
+        format_version: FORMAT_VERSION,
+        saved_at: unix_timestamp(),
+        models: models.map_models(|model| ProjectModel {
+            snapshot: model_snapshot(model),
+            content: model.content.clone(),
+        })?,
+        active_handle: session.shared_handle(),
+        stackups: autosave.drafts(),
+    };
+
+    let body = serde_json::to_vec(&project).map_err(|e| format!("Failed to serialize project: {}", e))?;
+    let sealed = encryption.seal(&body)?;
+    let encrypted = is_encrypted(&sealed);
+    std::fs::write(target, sealed).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+
+    tracing::info!(path = %path, models = project.models.len(), encrypted, "project saved");
+    Ok(ProjectSaved { path, models: project.models.len(), stackups: project.stackups.len(), encrypted })
+}
+
+/// Load the models and stackup drafts of a project bundle into the session
+#[tauri::command]
+pub fn open_project(
+    models: State<'_, ModelStore>,
+    transforms: State<'_, PartTransformStore>,
+    recent: State<'_, RecentFilesState>,
+    autosave: State<'_, AutosaveState>,
+    encryption: State<'_, EncryptionState>,
+    path: String,
+) -> Result<OpenedProject, String> {
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let project = parse_project(&encryption.open(bytes)?)?;
+    let mut opened = RecoveredSession {
+        saved_at: project.saved_at,
+        models: Vec::new(),
+        failed: Vec::new(),
+        active_handle: None,
+        stackups: project.stackups.clone(),
+    };
+
+    for ProjectModel { snapshot, content } in project.models {
+        let filename = snapshot.filename.clone();
+        match restore_model(&models, &transforms, &recent, snapshot, content) {
+            Ok(model) => {
+                if project.active_handle.as_deref() == Some(model.previous_handle.as_str()) {
+                    opened.active_handle = Some(model.model.handle.clone());
+                }
+                opened.models.push(model);
+            }
+            Err(error) => opened.failed.push(RecoveryFailure { filename, error }),
+        }
+    }
+    for draft in &project.stackups {
+        autosave.put_draft(draft.clone())?;
+    }
+
+    // Bundled content is what the results were computed from; compare it with the source files
+    let mut stale_sources = Vec::new();
+    for model in &opened.models {
+        let check = models.with_model(&model.model.handle, check_source)?;
+        if check.is_stale() {
+            stale_sources.push(check);
+        }
+    }
+
+    tracing::info!(path = %path, models = opened.models.len(), failed = opened.failed.len(), stale = stale_sources.len(), "project opened");
+    Ok(OpenedProject { session: opened, stale_sources })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_format_checks() {
+        assert!(check_extension(Path::new("gearbox.OHMPROJ")).is_ok());
+        assert!(check_extension(Path::new("gearbox.json")).is_err());
+
+        let project = ProjectFile { format_version: FORMAT_VERSION, saved_at: 1, models: Vec::new(), active_handle: None, stackups: Vec::new() };
+        let body = serde_json::to_vec(&project).unwrap();
+        assert_eq!(parse_project(&body).unwrap().saved_at, 1);
+
+        let newer = ProjectFile { format_version: FORMAT_VERSION + 1, ..project };
+        assert!(parse_project(&serde_json::to_vec(&newer).unwrap()).unwrap_err().contains("newer version"));
+    }
+}
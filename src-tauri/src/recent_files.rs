@@ -0,0 +1,286 @@
+// Recent files service for the start page
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::model_store::ModelStore;
+use crate::snapshot::{render_thumbnail, THUMBNAIL_SIZE};
+use crate::storage::{load_json, save_json, unix_timestamp};
+
+/// Maximum number of unpinned entries kept in the list
+const MAX_RECENT_FILES: usize = 20;
+
+/// A previously opened file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentFile {
+    pub path: String,
+    pub filename: String,
+    pub hash: String,                           // SHA-256 of the file contents
+    pub last_opened: u64,                       // Unix timestamp (seconds)
+    pub pinned: bool,
+    pub thumbnail: Option<String>,              // Base64 PNG
+    pub cached_result: Option<serde_json::Value>, // Last analysis result for instant reopen
+}
+
+/// Result of reopening a recent file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecentFileReopen {
+    pub path: String,
+    pub filename: String,
+    pub content: String,
+    pub hash: String,
+    pub changed_on_disk: bool,
+    pub cached_result: Option<serde_json::Value>, // Only set when the file is unchanged
+}
+
+/// Managed state holding the recent files list
+pub struct RecentFilesState {
+    path: PathBuf,
+    entries: Mutex<Vec<RecentFile>>,
+}
+
+impl RecentFilesState {
+    /// Load the list from disk (empty if missing)
+    pub fn load(path: PathBuf) -> Self {
+        let entries: Vec<RecentFile> = load_json(&path);
+        RecentFilesState {
+            path,
+            entries: Mutex::new(entries),
+        }
+    }
+
+    /// Most recently opened entry, if any
+    pub fn most_recent(&self) -> Option<RecentFile> {
+        let entries = self.entries.lock().ok()?;
+        entries.iter().max_by_key(|e| e.last_opened).cloned()
+    }
+
+    /// Most recent entry with this file name (case-insensitive), for names read from window titles
+    pub fn find_by_filename(&self, filename: &str) -> Option<RecentFile> {
+        let entries = self.entries.lock().ok()?;
+        entries.iter()
+            .filter(|e| e.filename.eq_ignore_ascii_case(filename))
+            .max_by_key(|e| e.last_opened)
+            .cloned()
+    }
+
+    /// Record that a file was opened, hashing its current contents
+    pub fn record(
+        &self,
+        path: String,
+        thumbnail: Option<String>,
+        cached_result: Option<serde_json::Value>,
+    ) -> Result<RecentFile, String> {
+        let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let entry = RecentFile {
+            filename: file_name(&path),
+            hash: hash_content(&bytes),
+            last_opened: unix_timestamp(),
+            pinned: false,
+            thumbnail,
+            cached_result,
+            path,
+        };
+
+        tracing::debug!(path = %entry.path, "recording recent file");
+        self.update(|entries| upsert_entry(entries, entry))
+    }
+
+    /// Attach a thumbnail to an existing entry; false when the path is not in the list
+    pub fn set_thumbnail(&self, path: &str, thumbnail: String) -> Result<bool, String> {
+        self.update(|entries| match entries.iter_mut().find(|e| e.path == path) {
+            Some(entry) => {
+                entry.thumbnail = Some(thumbnail);
+                true
+            }
+            None => false,
+        })
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut Vec<RecentFile>) -> T) -> Result<T, String> {
+        let mut entries = self.entries.lock().map_err(|_| "Recent files state poisoned".to_string())?;
+        let result = f(&mut entries);
+        sort_entries(&mut entries);
+        save_json(&self.path, &*entries)?;
+        Ok(result)
+    }
+}
+
+/// Hash file contents for change detection
+pub fn hash_content(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// List recent files, pinned entries first
+#[tauri::command]
+pub fn list_recent_files(state: State<'_, RecentFilesState>) -> Result<Vec<RecentFile>, String> {
+    let entries = state.entries.lock().map_err(|_| "Recent files state poisoned".to_string())?;
+    Ok(entries.clone())
+}
+
+/// Record that a file was opened, optionally storing a thumbnail and analysis result;
+/// without one, the thumbnail is rendered from the file's loaded model if there is one
+#[tauri::command]
+pub fn record_recent_file(
+    state: State<'_, RecentFilesState>,
+    models: State<'_, ModelStore>,
+    path: String,
+    thumbnail: Option<String>,
+    cached_result: Option<serde_json::Value>,
+) -> Result<RecentFile, String> {
+    let thumbnail = match thumbnail {
+        Some(thumbnail) => Some(thumbnail),
+        None => models.map_models(|model| match (&model.path, &model.mesh) {
+            (Some(loaded), Some(mesh)) if *loaded == path => render_thumbnail(mesh, THUMBNAIL_SIZE),
+            _ => None,
+        })?.into_iter().flatten().next(),
+    };
+    state.record(path, thumbnail, cached_result)
+}
+
+/// Pin or unpin an entry so it survives trimming and clearing
+#[tauri::command]
+pub fn pin_recent_file(state: State<'_, RecentFilesState>, path: String, pinned: bool) -> Result<(), String> {
+    state.update(|entries| {
+        match entries.iter_mut().find(|e| e.path == path) {
+            Some(entry) => {
+                entry.pinned = pinned;
+                Ok(())
+            }
+            None => Err(format!("Not in recent files: {}", path)),
+        }
+    })?
+}
+
+/// Remove a single entry from the list
+#[tauri::command]
+pub fn remove_recent_file(state: State<'_, RecentFilesState>, path: String) -> Result<(), String> {
+    state.update(|entries| entries.retain(|e| e.path != path))
+}
+
+/// Clear the list, keeping pinned entries unless `include_pinned` is set
+#[tauri::command]
+pub fn clear_recent_files(state: State<'_, RecentFilesState>, include_pinned: bool) -> Result<(), String> {
+    state.update(|entries| entries.retain(|e| e.pinned && !include_pinned))
+}
+
+/// Reopen a recent file, returning the cached result when the file is unchanged
+#[tauri::command]
+pub fn reopen_recent_file(state: State<'_, RecentFilesState>, path: String) -> Result<RecentFileReopen, String> {
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let hash = hash_content(content.as_bytes());
+
+    state.update(|entries| {
+        let entry = entries.iter_mut().find(|e| e.path == path);
+        let changed_on_disk = entry.as_ref().map(|e| e.hash != hash).unwrap_or(true);
+        let cached_result = entry.as_ref()
+            .filter(|_| !changed_on_disk)
+            .and_then(|e| e.cached_result.clone());
+
+        if let Some(entry) = entry {
+            entry.last_opened = unix_timestamp();
+            if changed_on_disk {
+                entry.hash = hash.clone();
+                entry.cached_result = None;
+            }
+        }
+
+        RecentFileReopen {
+            filename: file_name(&path),
+            path: path.clone(),
+            content,
+            hash,
+            changed_on_disk,
+            cached_result,
+        }
+    })
+}
+
+/// Insert or refresh an entry, keeping its pin and trimming old unpinned entries
+fn upsert_entry(entries: &mut Vec<RecentFile>, mut entry: RecentFile) -> RecentFile {
+    if let Some(pos) = entries.iter().position(|e| e.path == entry.path) {
+        let existing = entries.remove(pos);
+        entry.pinned = existing.pinned;
+        if entry.thumbnail.is_none() {
+            entry.thumbnail = existing.thumbnail;
+        }
+        if entry.cached_result.is_none() && existing.hash == entry.hash {
+            entry.cached_result = existing.cached_result;
+        }
+    }
+
+    entries.push(entry.clone());
+    sort_entries(entries);
+
+    // Trim the oldest unpinned entries beyond the limit
+    let mut unpinned = 0;
+    entries.retain(|e| {
+        if e.pinned {
+            return true;
+        }
+        unpinned += 1;
+        unpinned <= MAX_RECENT_FILES
+    });
+
+    entry
+}
+
+/// Pinned first, then most recently opened
+fn sort_entries(entries: &mut [RecentFile]) {
+    entries.sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.last_opened.cmp(&a.last_opened)));
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .map(|s| s.to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, last_opened: u64) -> RecentFile {
+        RecentFile {
+            path: path.to_string(),
+            filename: file_name(path),
+            hash: "abc".to_string(),
+            last_opened,
+            pinned: false,
+            thumbnail: None,
+            cached_result: None,
+        }
+    }
+
+    #[test]
+    fn test_upsert_keeps_pin_and_orders() {
+        let mut entries = vec![entry("/a.step", 1), entry("/b.step", 2)];
+        entries[0].pinned = true;
+
+        upsert_entry(&mut entries, entry("/a.step", 5));
+        upsert_entry(&mut entries, entry("/c.step", 3));
+
+        assert_eq!(entries[0].path, "/a.step");
+        assert!(entries[0].pinned);
+        assert_eq!(entries[1].path, "/c.step");
+        assert_eq!(entries[2].path, "/b.step");
+    }
+
+    #[test]
+    fn test_trim_keeps_pinned() {
+        let mut entries = vec![entry("/pinned.step", 0)];
+        entries[0].pinned = true;
+
+        for i in 0..(MAX_RECENT_FILES + 5) {
+            upsert_entry(&mut entries, entry(&format!("/{}.step", i), i as u64 + 1));
+        }
+
+        assert_eq!(entries.len(), MAX_RECENT_FILES + 1);
+        assert!(entries.iter().any(|e| e.path == "/pinned.step"));
+    }
+}
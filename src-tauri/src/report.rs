@@ -0,0 +1,592 @@
+// Shareable assembly reports (HTML and PDF)
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use printpdf::{BuiltinFont, Image, ImageTransform, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::ParsedPart;
+use crate::interface_detection::{
+    detect_bbox_interferences, find_mating_interfaces, DetectedInterface, DetectionParams, PartInterference,
+};
+use crate::mesh_query::{section_properties, slice_mesh};
+use crate::model_store::{LoadedModel, ModelStore};
+use crate::report_templates::{validate_logo, ReportTemplate, ReportTemplateStore};
+use crate::{BoundingBox, FeatureInfo, TopologyInfo};
+
+/// Output format of a report
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportFormat {
+    Html,
+    Pdf,
+}
+
+/// Viewer screenshot embedded in the report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSnapshot {
+    pub caption: String,
+    pub image_base64: String,  // PNG
+}
+
+/// Report request from the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportRequest {
+    pub handle: String,
+    pub format: ReportFormat,
+    pub title: Option<String>,
+    pub template: Option<String>,     // Saved template name; built-in layout when None
+    pub output_path: Option<String>,  // Written to disk when set, otherwise returned inline
+    #[serde(default)]
+    pub snapshots: Vec<ReportSnapshot>,
+    #[serde(default)]
+    pub section_planes: Vec<String>,  // Saved clipping planes to report section properties for
+}
+
+/// Part row in the report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportPart {
+    pub id: String,
+    pub name: String,
+    pub face_count: usize,
+    pub dimensions: Option<[f64; 3]>,
+}
+
+/// Section properties at a saved clipping plane
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportCrossSection {
+    pub plane_name: String,
+    pub area: f64,
+    pub centroid: [f64; 3],
+    pub principal: [f64; 2],
+    pub loop_count: usize,
+}
+
+/// Everything the report shows, collected from a loaded model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssemblyReport {
+    pub title: String,
+    pub filename: String,
+    pub generated_at: String,
+    pub topology: Option<TopologyInfo>,
+    pub features: Option<FeatureInfo>,
+    pub bounding_box: Option<BoundingBox>,
+    pub parts: Vec<ReportPart>,
+    pub interfaces: Vec<DetectedInterface>,
+    pub interferences: Vec<PartInterference>,
+    pub snapshots: Vec<ReportSnapshot>,
+    #[serde(default)]
+    pub cross_sections: Vec<ReportCrossSection>,
+}
+
+/// Result of report generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub format: ReportFormat,
+    pub output_path: Option<String>,
+    pub content_base64: Option<String>,  // Report bytes when no output path was given
+}
+
+/// Collect report data; interfaces detected earlier on the model are reused.
+/// Parts hidden or isolated away in the viewer are left out, along with their interfaces.
+pub fn build_report(model: &LoadedModel, title: Option<String>, snapshots: Vec<ReportSnapshot>) -> AssemblyReport {
+    let shown = |id: &str| model.display.is_shown(id);
+    let parts: Vec<ParsedPart> = model.assembly.parts.iter().filter(|p| shown(&p.id)).cloned().collect();
+    let interfaces = match &model.interfaces {
+        Some(result) => result.interfaces.iter()
+            .filter(|i| shown(&i.part_a_id) && shown(&i.part_b_id))
+            .cloned()
+            .collect(),
+        None => {
+            let params = DetectionParams::default();
+            find_mating_interfaces(parts.clone(), params.proximity_threshold, params.normal_threshold).interfaces
+        }
+    };
+
+    AssemblyReport {
+        title: title.unwrap_or_else(|| format!("Assembly Report - {}", model.filename)),
+        filename: model.filename.clone(),
+        generated_at: chrono::Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        topology: model.analysis.topology.clone(),
+        features: model.analysis.features.clone(),
+        bounding_box: model.bounding_box.clone(),
+        parts: parts.iter()
+            .map(|p| ReportPart {
+                id: p.id.clone(),
+                name: p.name.clone(),
+                face_count: p.faces.len(),
+                dimensions: p.bounding_box.as_ref().map(|b| b.dimensions),
+            })
+            .collect(),
+        interfaces,
+        interferences: detect_bbox_interferences(&parts),
+        snapshots,
+        cross_sections: Vec::new(),
+    }
+}
+
+/// Section properties at each named clipping plane of the model
+pub fn report_cross_sections(model: &LoadedModel, plane_names: &[String]) -> Result<Vec<ReportCrossSection>, String> {
+    if plane_names.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mesh = model.mesh.as_ref().ok_or("Model has no mesh to section")?;
+    plane_names.iter()
+        .map(|name| {
+            let plane = model.resolve_plane(None, Some(name))?;
+            let properties = section_properties(&slice_mesh(mesh, plane));
+            Ok(ReportCrossSection {
+                plane_name: name.clone(),
+                area: properties.area,
+                centroid: properties.centroid,
+                principal: properties.principal,
+                loop_count: properties.loop_count,
+            })
+        })
+        .collect()
+}
+
+/// Escape text for HTML output
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn format_dims(dims: &[f64; 3]) -> String {
+    format!("{:.2} x {:.2} x {:.2} mm", dims[0], dims[1], dims[2])
+}
+
+/// Render a self-contained HTML report (snapshots are inlined as data URLs)
+pub fn render_html(report: &AssemblyReport, template: &ReportTemplate) -> String {
+    let sections = &template.sections;
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\">");
+    html.push_str(&format!("<title>{}</title>", escape_html(&report.title)));
+    html.push_str("<style>body{font-family:Helvetica,Arial,sans-serif;margin:32px;color:#222}\
+        table{border-collapse:collapse;width:100%;margin-bottom:24px}\
+        th,td{border:1px solid #ccc;padding:4px 8px;text-align:left;font-size:13px}\
+        th{background:#f0f0f0}h2{border-bottom:2px solid #333;padding-bottom:4px}\
+        figure{margin:0 0 24px}img{max-width:100%;border:1px solid #ccc}\
+        .warn{color:#b00020}.brand{display:flex;align-items:center;gap:16px}\
+        .brand img{max-height:60px;border:none}footer{margin-top:32px;font-size:12px;color:#666}\
+        </style></head><body>\n");
+
+    // Branding
+    if template.logo_base64.is_some() || template.company_name.is_some() {
+        html.push_str("<div class=\"brand\">");
+        if let Some(logo) = &template.logo_base64 {
+            // Templates saved before logos were checked may hold anything; never embed that
+            match validate_logo(logo) {
+                Ok(()) => html.push_str(&format!("<img src=\"data:image/png;base64,{}\" alt=\"logo\">", logo.trim())),
+                Err(e) => tracing::warn!(template = %template.name, error = %e, "report logo skipped"),
+            }
+        }
+        if let Some(company) = &template.company_name {
+            html.push_str(&format!("<strong>{}</strong>", escape_html(company)));
+        }
+        html.push_str("</div>\n");
+    }
+    if let Some(header) = &template.header {
+        html.push_str(&format!("<p>{}</p>\n", escape_html(header)));
+    }
+
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(&report.title)));
+    html.push_str(&format!(
+        "<p>File: {}<br>Generated: {}</p>\n",
+        escape_html(&report.filename),
+        escape_html(&report.generated_at)
+    ));
+
+    if sections.topology {
+        html.push_str("<h2>Topology</h2>\n<table>");
+        if let Some(t) = &report.topology {
+            for (label, value) in [
+                ("Solids", t.num_solids),
+                ("Shells", t.num_shells),
+                ("Faces", t.num_faces),
+                ("Edges", t.num_edges),
+                ("Vertices", t.num_vertices),
+            ] {
+                html.push_str(&format!("<tr><th>{}</th><td>{}</td></tr>", label, value));
+            }
+        }
+        if let Some(f) = &report.features {
+            html.push_str(&format!(
+                "<tr><th>Planar / cylindrical / curved faces</th><td>{} / {} / {}</td></tr>",
+                f.planar_faces, f.cylindrical_faces, f.curved_faces
+            ));
+        }
+        if let Some(b) = &report.bounding_box {
+            html.push_str(&format!("<tr><th>Overall size</th><td>{}</td></tr>", format_dims(&b.dimensions)));
+        }
+        html.push_str("</table>\n");
+    }
+
+    if sections.parts {
+        html.push_str(&format!("<h2>Parts ({})</h2>\n<table><tr><th>ID</th><th>Name</th><th>Faces</th><th>Size</th></tr>", report.parts.len()));
+        for part in &report.parts {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_html(&part.id),
+                escape_html(&part.name),
+                part.face_count,
+                part.dimensions.as_ref().map(format_dims).unwrap_or_default()
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    if sections.interfaces {
+        html.push_str(&format!("<h2>Mating Interfaces ({})</h2>\n", report.interfaces.len()));
+        if !report.interfaces.is_empty() {
+            html.push_str("<table><tr><th>ID</th><th>Part A</th><th>Part B</th><th>Type</th><th>Gap (mm)</th><th>Contact area (mm&sup2;)</th></tr>");
+            for i in &report.interfaces {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.3}</td><td>{:.2}</td></tr>",
+                    escape_html(&i.id),
+                    escape_html(&i.part_a_id),
+                    escape_html(&i.part_b_id),
+                    escape_html(&i.interface_type),
+                    i.proximity,
+                    i.contact_area
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+    }
+
+    if sections.interferences {
+        html.push_str(&format!("<h2>Interference Check ({})</h2>\n", report.interferences.len()));
+        if report.interferences.is_empty() {
+            html.push_str("<p>No overlapping part envelopes found.</p>\n");
+        } else {
+            html.push_str("<table><tr><th>Part A</th><th>Part B</th><th>Overlap</th><th>Volume (mm&sup3;)</th></tr>");
+            for i in &report.interferences {
+                html.push_str(&format!(
+                    "<tr class=\"warn\"><td>{}</td><td>{}</td><td>{}</td><td>{:.2}</td></tr>",
+                    escape_html(&i.part_a_id),
+                    escape_html(&i.part_b_id),
+                    format_dims(&i.overlap),
+                    i.overlap_volume
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+    }
+
+    if !report.cross_sections.is_empty() {
+        html.push_str(&format!("<h2>Cross Sections ({})</h2>\n", report.cross_sections.len()));
+        html.push_str("<table><tr><th>Plane</th><th>Area (mm&sup2;)</th><th>Centroid</th><th>Principal moments (mm&#8308;)</th><th>Loops</th></tr>");
+        for section in &report.cross_sections {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{:.2}</td><td>({:.2}, {:.2}, {:.2})</td><td>{:.1} / {:.1}</td><td>{}</td></tr>",
+                escape_html(&section.plane_name),
+                section.area,
+                section.centroid[0], section.centroid[1], section.centroid[2],
+                section.principal[0], section.principal[1],
+                section.loop_count
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+
+    if sections.snapshots && !report.snapshots.is_empty() {
+        html.push_str("<h2>Views</h2>\n");
+        for snapshot in &report.snapshots {
+            html.push_str(&format!(
+                "<figure><img src=\"data:image/png;base64,{}\" alt=\"{}\"><figcaption>{}</figcaption></figure>\n",
+                snapshot.image_base64,
+                escape_html(&snapshot.caption),
+                escape_html(&snapshot.caption)
+            ));
+        }
+    }
+
+    if let Some(footer) = &template.footer {
+        html.push_str(&format!("<footer>{}</footer>\n", escape_html(footer)));
+    }
+
+    html.push_str("</body></html>\n");
+    html
+}
+
+// A4 page layout in millimetres
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+
+/// Top-down text cursor that adds pages as needed
+struct PdfCursor {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f32,
+}
+
+impl PdfCursor {
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    fn heading(&mut self, text: &str, size: f32) {
+        self.ensure_space(size * 0.6 + 4.0);
+        self.y -= size * 0.45 + 4.0;
+        self.layer.use_text(text, size, Mm(MARGIN), Mm(self.y), &self.bold);
+        self.y -= 2.0;
+    }
+
+    fn line(&mut self, text: &str) {
+        self.ensure_space(5.0);
+        self.y -= 5.0;
+        self.layer.use_text(text, 10.0, Mm(MARGIN), Mm(self.y), &self.font);
+    }
+
+    /// Place a base64 PNG scaled to at most `max_width` millimetres
+    fn image(&mut self, image_base64: &str, max_width: f32) -> Result<(), String> {
+        let bytes = STANDARD.decode(image_base64)
+            .map_err(|e| format!("Invalid image data: {}", e))?;
+        let decoded = image::load_from_memory(&bytes)
+            .map_err(|e| format!("Invalid image: {}", e))?;
+        let rgb = image::DynamicImage::ImageRgb8(decoded.to_rgb8());
+
+        let dpi = (rgb.width() as f32 * 25.4 / max_width).max(72.0);
+        let height = rgb.height() as f32 * 25.4 / dpi;
+
+        self.ensure_space(height + 8.0);
+        self.y -= height;
+        Image::from_dynamic_image(&rgb).add_to_layer(self.layer.clone(), ImageTransform {
+            translate_x: Some(Mm(MARGIN)),
+            translate_y: Some(Mm(self.y)),
+            dpi: Some(dpi),
+            ..Default::default()
+        });
+        Ok(())
+    }
+}
+
+/// Render the report as a PDF document (custom HTML layouts do not apply)
+pub fn render_pdf(report: &AssemblyReport, template: &ReportTemplate) -> Result<Vec<u8>, String> {
+    let sections = &template.sections;
+    let (doc, page, layer) = PdfDocument::new(&report.title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Layer 1");
+    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| format!("PDF font error: {}", e))?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| format!("PDF font error: {}", e))?;
+    let layer = doc.get_page(page).get_layer(layer);
+
+    let mut pdf = PdfCursor { doc, layer, font, bold, y: PAGE_HEIGHT - MARGIN };
+
+    // Branding
+    if let Some(logo) = &template.logo_base64 {
+        validate_logo(logo)?;
+        pdf.image(logo, 40.0)?;
+    }
+    if let Some(company) = &template.company_name {
+        pdf.heading(company, 12.0);
+    }
+    if let Some(header) = &template.header {
+        pdf.line(header);
+    }
+
+    pdf.heading(&report.title, 18.0);
+    pdf.line(&format!("File: {}", report.filename));
+    pdf.line(&format!("Generated: {}", report.generated_at));
+
+    if sections.topology {
+        pdf.heading("Topology", 14.0);
+        if let Some(t) = &report.topology {
+            pdf.line(&format!(
+                "Solids {}   Shells {}   Faces {}   Edges {}   Vertices {}",
+                t.num_solids, t.num_shells, t.num_faces, t.num_edges, t.num_vertices
+            ));
+        }
+        if let Some(f) = &report.features {
+            pdf.line(&format!(
+                "Planar {}   Cylindrical {}   Curved {}",
+                f.planar_faces, f.cylindrical_faces, f.curved_faces
+            ));
+        }
+        if let Some(b) = &report.bounding_box {
+            pdf.line(&format!("Overall size: {}", format_dims(&b.dimensions)));
+        }
+    }
+
+    if sections.parts {
+        pdf.heading(&format!("Parts ({})", report.parts.len()), 14.0);
+        for part in &report.parts {
+            let size = part.dimensions.as_ref().map(format_dims).unwrap_or_default();
+            pdf.line(&format!("{}  {}  ({} faces)  {}", part.id, part.name, part.face_count, size));
+        }
+    }
+
+    if sections.interfaces {
+        pdf.heading(&format!("Mating Interfaces ({})", report.interfaces.len()), 14.0);
+        for i in &report.interfaces {
+            pdf.line(&format!(
+                "{}: {} / {}  {}  gap {:.3} mm  area {:.2} mm2",
+                i.id, i.part_a_id, i.part_b_id, i.interface_type, i.proximity, i.contact_area
+            ));
+        }
+    }
+
+    if sections.interferences {
+        pdf.heading(&format!("Interference Check ({})", report.interferences.len()), 14.0);
+        if report.interferences.is_empty() {
+            pdf.line("No overlapping part envelopes found.");
+        }
+        for i in &report.interferences {
+            pdf.line(&format!(
+                "{} / {}  overlap {}  volume {:.2} mm3",
+                i.part_a_id, i.part_b_id, format_dims(&i.overlap), i.overlap_volume
+            ));
+        }
+    }
+
+    if !report.cross_sections.is_empty() {
+        pdf.heading(&format!("Cross Sections ({})", report.cross_sections.len()), 14.0);
+        for section in &report.cross_sections {
+            pdf.line(&format!(
+                "{}  area {:.2} mm2  centroid ({:.2}, {:.2}, {:.2})  principal {:.1} / {:.1} mm4  {} loops",
+                section.plane_name,
+                section.area,
+                section.centroid[0], section.centroid[1], section.centroid[2],
+                section.principal[0], section.principal[1],
+                section.loop_count
+            ));
+        }
+    }
+
+    if sections.snapshots && !report.snapshots.is_empty() {
+        pdf.heading("Views", 14.0);
+        for snapshot in &report.snapshots {
+            pdf.image(&snapshot.image_base64, PAGE_WIDTH - 2.0 * MARGIN)?;
+            pdf.line(&snapshot.caption);
+        }
+    }
+
+    if let Some(footer) = &template.footer {
+        pdf.line("");
+        pdf.line(footer);
+    }
+
+    pdf.doc.save_to_bytes().map_err(|e| format!("Failed to write PDF: {}", e))
+}
+
+/// Render to bytes in the requested format; a custom HTML layout replaces the built-in HTML one and
+/// cannot be rendered as PDF
+pub fn render_report(report: &AssemblyReport, format: ReportFormat, template: &ReportTemplate) -> Result<Vec<u8>, String> {
+    match format {
+        ReportFormat::Html => match template.render_custom_html(report) {
+            Some(rendered) => rendered.map(String::into_bytes),
+            None => Ok(render_html(report, template).into_bytes()),
+        },
+        ReportFormat::Pdf if template.html_template.is_some() => Err(format!(
+            "Template '{}' has a custom HTML layout, which only applies to HTML reports",
+            template.name
+        )),
+        ReportFormat::Pdf => render_pdf(report, template),
+    }
+}
+
+/// Generate a shareable report for a loaded model
+#[tauri::command]
+pub fn generate_assembly_report(
+    models: State<'_, ModelStore>,
+    templates: State<'_, ReportTemplateStore>,
+    request: ReportRequest,
+) -> ReportResult {
+    let format = request.format;
+
+    let template = match &request.template {
+        Some(name) => templates.load(name),
+        None => Ok(ReportTemplate::default()),
+    };
+
+    let outcome = template
+        .and_then(|template| {
+            models
+                .with_model(&request.handle, |model| {
+                    let cross_sections = report_cross_sections(model, &request.section_planes)?;
+                    Ok::<_, String>(AssemblyReport { cross_sections, ..build_report(model, request.title, request.snapshots) })
+                })
+                .and_then(|report| report)
+                .and_then(|report| render_report(&report, format, &template))
+        })
+        .and_then(|bytes| match &request.output_path {
+            Some(path) => std::fs::write(path, &bytes)
+                .map(|_| None)
+                .map_err(|e| format!("Failed to write report: {}", e)),
+            None => Ok(Some(STANDARD.encode(&bytes))),
+        });
+
+    match outcome {
+        Ok(content_base64) => {
+            tracing::info!(handle = %request.handle, format = ?format, "assembly report generated");
+            ReportResult {
+                success: true,
+                error: None,
+                format,
+                output_path: request.output_path,
+                content_base64,
+            }
+        }
+        Err(e) => {
+            tracing::warn!(handle = %request.handle, error = %e, "assembly report failed");
+            ReportResult {
+                success: false,
+                error: Some(e),
+                format,
+                output_path: None,
+                content_base64: None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_report() -> AssemblyReport {
+        AssemblyReport {
+            title: "Bracket <rev B>".to_string(),
+            filename: "bracket.step".to_string(),
+            generated_at: "2024-01-01 00:00".to_string(),
+            topology: None,
+            features: None,
+            bounding_box: None,
+            parts: vec![ReportPart {
+                id: "part-1".to_string(),
+                name: "Base & Plate".to_string(),
+                face_count: 6,
+                dimensions: Some([10.0, 20.0, 5.0]),
+            }],
+            interfaces: vec![],
+            interferences: vec![],
+            snapshots: vec![],
+            cross_sections: vec![],
+        }
+    }
+
+    #[test]
+    fn test_html_escapes_user_text() {
+        let html = render_html(&sample_report(), &ReportTemplate::default());
+        assert!(html.contains("Bracket &lt;rev B&gt;"));
+        assert!(html.contains("Base &amp; Plate"));
+        assert!(html.contains("10.00 x 20.00 x 5.00 mm"));
+    }
+
+    #[test]
+    fn test_pdf_has_header() {
+        let bytes = render_pdf(&sample_report(), &ReportTemplate::default()).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+}
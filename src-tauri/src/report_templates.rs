@@ -0,0 +1,281 @@
+// User report templates: branding, section selection and Handlebars layouts
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::State;
+
+use crate::report::AssemblyReport;
+use crate::storage::{load_json, save_json};
+
+/// Report sections a template can include or drop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportSections {
+    pub topology: bool,
+    pub parts: bool,
+    pub interfaces: bool,
+    pub interferences: bool,
+    pub snapshots: bool,
+}
+
+impl Default for ReportSections {
+    fn default() -> Self {
+        ReportSections {
+            topology: true,
+            parts: true,
+            interfaces: true,
+            interferences: true,
+            snapshots: true,
+        }
+    }
+}
+
+/// A saved report template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportTemplate {
+    pub name: String,
+    pub company_name: Option<String>,
+    pub logo_base64: Option<String>,   // PNG
+    pub header: Option<String>,
+    pub footer: Option<String>,
+    pub sections: ReportSections,
+    pub html_template: Option<String>, // Handlebars layout for HTML reports only; the built-in layout is used when None
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Check that a logo is base64-encoded PNG data before it is embedded in a report
+pub fn validate_logo(logo_base64: &str) -> Result<(), String> {
+    let bytes = STANDARD.decode(logo_base64.trim())
+        .map_err(|e| format!("Logo is not valid base64: {}", e))?;
+    if !bytes.starts_with(PNG_SIGNATURE) {
+        return Err("Logo must be a PNG image".to_string());
+    }
+    Ok(())
+}
+
+impl Default for ReportTemplate {
+    fn default() -> Self {
+        ReportTemplate {
+            name: "Default".to_string(),
+            company_name: None,
+            logo_base64: None,
+            header: None,
+            footer: None,
+            sections: ReportSections::default(),
+            html_template: None,
+        }
+    }
+}
+
+/// Data exposed to Handlebars layouts
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    report: &'a AssemblyReport,
+    template: &'a ReportTemplate,
+}
+
+impl ReportTemplate {
+    /// Check that the name is usable, the logo is a PNG and the layout compiles
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Template name is required".to_string());
+        }
+        if let Some(logo) = &self.logo_base64 {
+            validate_logo(logo)?;
+        }
+        if let Some(source) = &self.html_template {
+            let mut registry = Handlebars::new();
+            registry.register_template_string(&self.name, source)
+                .map_err(|e| format!("Template error: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Copy of the report with deselected sections emptied
+    pub fn apply_sections(&self, report: &AssemblyReport) -> AssemblyReport {
+        let mut report = report.clone();
+        if !self.sections.topology {
+            report.topology = None;
+            report.features = None;
+            report.bounding_box = None;
+        }
+        if !self.sections.parts {
+            report.parts.clear();
+        }
+        if !self.sections.interfaces {
+            report.interfaces.clear();
+        }
+        if !self.sections.interferences {
+            report.interferences.clear();
+        }
+        if !self.sections.snapshots {
+            report.snapshots.clear();
+        }
+        report
+    }
+
+    /// Render the custom layout, if the template has one
+    pub fn render_custom_html(&self, report: &AssemblyReport) -> Option<Result<String, String>> {
+        let source = self.html_template.as_ref()?;
+        let report = self.apply_sections(report);
+
+        let mut registry = Handlebars::new();
+        registry.set_strict_mode(false);
+        let rendered = registry
+            .render_template(source, &TemplateContext { report: &report, template: self })
+            .map_err(|e| format!("Failed to render template '{}': {}", self.name, e));
+        Some(rendered)
+    }
+}
+
+/// Managed template directory
+pub struct ReportTemplateStore {
+    dir: PathBuf,
+}
+
+impl ReportTemplateStore {
+    pub fn new(dir: PathBuf) -> Self {
+        ReportTemplateStore { dir }
+    }
+
+    fn path_for(&self, name: &str) -> PathBuf {
+        let file: String = name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", file))
+    }
+
+    /// Names that only differ in characters the file name drops share a file; refuse to let one
+    /// template overwrite another that way
+    fn check_name_free(&self, name: &str) -> Result<(), String> {
+        let path = self.path_for(name);
+        if !path.exists() {
+            return Ok(());
+        }
+        let existing: ReportTemplate = load_json(&path);
+        if existing.name != name {
+            return Err(format!(
+                "Template name '{}' is too similar to the saved template '{}'; choose another name",
+                name, existing.name
+            ));
+        }
+        Ok(())
+    }
+
+    /// Load a template by name
+    pub fn load(&self, name: &str) -> Result<ReportTemplate, String> {
+        let path = self.path_for(name);
+        if !path.exists() {
+            return Err(format!("Unknown report template: {}", name));
+        }
+        Ok(load_json(&path))
+    }
+
+    fn list(&self) -> Vec<ReportTemplate> {
+        let mut templates: Vec<ReportTemplate> = std::fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries.filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                    .map(|p| load_json::<ReportTemplate>(&p))
+                    .collect()
+            })
+            .unwrap_or_default();
+        templates.sort_by(|a, b| a.name.cmp(&b.name));
+        templates
+    }
+}
+
+/// List saved report templates
+#[tauri::command]
+pub fn list_report_templates(store: State<'_, ReportTemplateStore>) -> Vec<ReportTemplate> {
+    store.list()
+}
+
+/// Save (or replace) a report template after checking that it compiles
+#[tauri::command]
+pub fn save_report_template(store: State<'_, ReportTemplateStore>, template: ReportTemplate) -> Result<(), String> {
+    template.validate()?;
+    store.check_name_free(&template.name)?;
+    let path = store.path_for(&template.name);
+    std::fs::create_dir_all(&store.dir)
+        .map_err(|e| format!("Failed to create template directory: {}", e))?;
+    save_json(&path, &template)?;
+    tracing::info!(template = %template.name, "report template saved");
+    Ok(())
+}
+
+/// Delete a report template
+#[tauri::command]
+pub fn delete_report_template(store: State<'_, ReportTemplateStore>, name: String) -> Result<bool, String> {
+    let path = store.path_for(&name);
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete template: {}", e))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> AssemblyReport {
+        AssemblyReport {
+            title: "Pump <A>".to_string(),
+            filename: "pump.step".to_string(),
+            generated_at: "2024-01-01 00:00".to_string(),
+            topology: None,
+            features: None,
+            bounding_box: None,
+            parts: vec![],
+            interfaces: vec![],
+            interferences: vec![],
+            snapshots: vec![],
+            cross_sections: vec![],
+        }
+    }
+
+    #[test]
+    fn test_custom_layout_escapes_fields() {
+        let template = ReportTemplate {
+            company_name: Some("Acme".to_string()),
+            html_template: Some("<h1>{{template.company_name}}: {{report.title}}</h1>".to_string()),
+            ..Default::default()
+        };
+        let html = template.render_custom_html(&report()).unwrap().unwrap();
+        assert_eq!(html, "<h1>Acme: Pump &lt;A&gt;</h1>");
+    }
+
+    #[test]
+    fn test_invalid_layout_and_logo_rejected() {
+        let template = ReportTemplate {
+            html_template: Some("{{#each report.parts}}".to_string()),
+            ..Default::default()
+        };
+        assert!(template.validate().is_err());
+
+        let png = STANDARD.encode(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR");
+        assert!(validate_logo(&png).is_ok());
+        assert!(validate_logo(&STANDARD.encode(b"GIF89a")).is_err());
+        assert!(validate_logo("\"><script>").is_err());
+    }
+
+    #[test]
+    fn test_names_sharing_a_file_are_refused() {
+        let dir = std::env::temp_dir().join(format!("ohmframe-templates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = ReportTemplateStore::new(dir.clone());
+        let saved = ReportTemplate { name: "Acme A/B".to_string(), ..Default::default() };
+        save_json(&store.path_for(&saved.name), &saved).unwrap();
+
+        assert!(store.check_name_free("Acme A/B").is_ok());
+        assert!(store.check_name_free("Acme A B").is_err());
+        assert!(store.check_name_free("Other").is_ok());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
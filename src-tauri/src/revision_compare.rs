@@ -0,0 +1,475 @@
+// Geometry-level comparison of two revisions of the same part
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::{ParsedFace, ParsedPart};
+use crate::features::{outward_normal, recognize_part_holes, Hole};
+use crate::linalg::{add, centroid, cross, distance, dot, mat3_mul_vec, norm, scale, sub, RigidTransform, Vec3};
+use crate::model_store::{LoadedModel, ModelStore};
+use crate::scan::median;
+
+const IDENTITY: [f64; 16] = [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+
+/// How the new revision is brought into the old one's frame
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionAlignmentMode {
+    /// Both revisions share the part's modeling frame (the usual case)
+    None,
+    /// ICP over face centers, pairing only faces with the same type and radius
+    Icp,
+}
+
+/// Comparison settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RevisionCompareOptions {
+    pub alignment: RevisionAlignmentMode,
+    pub max_iterations: usize,
+    pub outlier_factor: f64,          // ICP rejects pairs farther than this multiple of the median
+    pub tolerance: f64,               // Smaller position/size differences are ignored
+    pub match_distance: Option<f64>,  // Farthest a feature may move and still be matched; default 10% of the part diagonal
+}
+
+impl Default for RevisionCompareOptions {
+    fn default() -> Self {
+        RevisionCompareOptions {
+            alignment: RevisionAlignmentMode::None,
+            max_iterations: 50,
+            outlier_factor: 3.0,
+            tolerance: 1e-3,
+            match_distance: None,
+        }
+    }
+}
+
+/// What happened to a matched or unmatched feature
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeometryChange {
+    Added,
+    Removed,
+    Moved,
+    Resized,
+    MovedAndResized,
+}
+
+/// New-to-old transform and fit quality
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionAlignment {
+    pub mode: RevisionAlignmentMode,
+    pub transform: [f64; 16],    // Column-major, new revision to old
+    pub iterations: usize,
+    pub rms: f64,
+    pub matched_faces: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoleChange {
+    pub change: GeometryChange,
+    pub old_hole_id: Option<String>,
+    pub new_hole_id: Option<String>,
+    pub old_diameter: Option<f64>,
+    pub new_diameter: Option<f64>,
+    pub offset: Option<f64>,     // Distance between the hole axes
+    pub position: Vec3,          // Old frame
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WallChange {
+    pub change: GeometryChange,
+    pub old_face_ids: Option<[i64; 2]>,
+    pub new_face_ids: Option<[i64; 2]>,
+    pub old_thickness: Option<f64>,
+    pub new_thickness: Option<f64>,
+    pub normal: Vec3,            // Old frame
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DimensionChange {
+    pub axis: String,
+    pub old: f64,
+    pub new: f64,
+    pub delta: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevisionComparison {
+    pub old_part_id: String,
+    pub new_part_id: String,
+    pub alignment: RevisionAlignment,
+    pub holes: Vec<HoleChange>,
+    pub walls: Vec<WallChange>,
+    pub dimensions: Vec<DimensionChange>,
+    pub unchanged: bool,
+}
+
+/// Material between two opposing planar faces
+struct Wall {
+    faces: [i64; 2],
+    normal: Vec3,
+    mid: Vec3,
+    thickness: f64,
+}
+
+/// Part with its placement removed, so revisions are compared as modeled
+fn local_part(part: &ParsedPart) -> ParsedPart {
+    ParsedPart { transform: IDENTITY, ..part.clone() }
+}
+
+/// Each planar face paired with the nearest opposing plane behind it
+fn part_walls(part: &ParsedPart) -> Vec<Wall> {
+    let planes: Vec<&ParsedFace> = part.faces.iter().filter(|f| f.face_type == "planar").collect();
+    let mut walls: Vec<Wall> = Vec::new();
+
+    for a in &planes {
+        let na = outward_normal(a);
+        let nearest = planes.iter()
+            .filter(|b| dot(&na, &outward_normal(b)) <= -0.9999)
+            .map(|b| (b, -dot(&sub(&b.center, &a.center), &na)))
+            .filter(|(_, thickness)| *thickness > 1e-9)
+            .min_by(|x, y| x.1.total_cmp(&y.1));
+        let Some((b, thickness)) = nearest else { continue };
+        let faces = [a.id.min(b.id), a.id.max(b.id)];
+        if walls.iter().any(|w| w.faces == faces) {
+            continue;
+        }
+        // Report walls with the normal of the lower-id face so both revisions agree on sign
+        let normal = if a.id < b.id { na } else { scale(&na, -1.0) };
+        walls.push(Wall { faces, normal, mid: scale(&add(&a.center, &b.center), 0.5), thickness });
+    }
+    walls
+}
+
+/// Greedy one-to-one matching by ascending cost; returns pairs and the unmatched indices on each side
+fn match_features(
+    old: usize,
+    new: usize,
+    cost: impl Fn(usize, usize) -> Option<f64>,
+) -> (Vec<(usize, usize)>, Vec<usize>, Vec<usize>) {
+    let mut candidates: Vec<(f64, usize, usize)> = (0..old)
+        .flat_map(|i| (0..new).map(move |j| (i, j)))
+        .filter_map(|(i, j)| cost(i, j).map(|c| (c, i, j)))
+        .collect();
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let (mut used_old, mut used_new) = (vec![false; old], vec![false; new]);
+    let mut pairs = Vec::new();
+    for (_, i, j) in candidates {
+        if !used_old[i] && !used_new[j] {
+            used_old[i] = true;
+            used_new[j] = true;
+            pairs.push((i, j));
+        }
+    }
+    pairs.sort_unstable();
+    let unmatched = |used: &[bool]| used.iter().enumerate().filter(|(_, u)| !**u).map(|(i, _)| i).collect();
+    (pairs, unmatched(&used_old), unmatched(&used_new))
+}
+
+/// Faces of the same type and radius are candidates for the same feature
+fn same_fingerprint(a: &ParsedFace, b: &ParsedFace) -> bool {
+    a.face_type == b.face_type && match (a.radius, b.radius) {
+        (Some(ra), Some(rb)) => (ra - rb).abs() < 1e-6 * ra.max(1.0),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Rigidly align the new revision's face centers onto the old revision's
+fn align_revisions(old: &ParsedPart, new: &ParsedPart, options: &RevisionCompareOptions) -> (RigidTransform, RevisionAlignment) {
+    let mut current = RigidTransform::default();
+    let mut alignment = RevisionAlignment {
+        mode: options.alignment,
+        transform: IDENTITY,
+        iterations: 0,
+        rms: 0.0,
+        matched_faces: 0,
+    };
+    if options.alignment == RevisionAlignmentMode::None {
+        return (current, alignment);
+    }
+
+    // Re-origined revisions can be far apart; start with the face centroids on top of each other
+    let centers = |part: &ParsedPart| part.faces.iter().map(|f| f.center).collect::<Vec<Vec3>>();
+    if !old.faces.is_empty() && !new.faces.is_empty() {
+        current.translation = sub(&centroid(&centers(old)), &centroid(&centers(new)));
+    }
+
+    let mut previous_rms = f64::MAX;
+    while alignment.iterations < options.max_iterations {
+        alignment.iterations += 1;
+        let pairs: Vec<(Vec3, Vec3, f64)> = new.faces.iter()
+            .filter_map(|b| {
+                let moved = current.apply(&b.center);
+                old.faces.iter()
+                    .filter(|a| same_fingerprint(a, b))
+                    .map(|a| (moved, a.center, distance(&moved, &a.center)))
+                    .min_by(|x, y| x.2.total_cmp(&y.2))
+            })
+            .collect();
+
+        let distances: Vec<f64> = pairs.iter().map(|p| p.2).collect();
+        let limit = (median(&distances) * options.outlier_factor).max(1e-9);
+        let (from, to): (Vec<Vec3>, Vec<Vec3>) = pairs.iter()
+            .filter(|p| p.2 <= limit)
+            .map(|p| (p.0, p.1))
+            .unzip();
+
+        alignment.matched_faces = from.len();
+        alignment.rms = (from.iter().zip(&to).map(|(a, b)| distance(a, b).powi(2)).sum::<f64>()
+            / from.len().max(1) as f64).sqrt();
+        if (previous_rms - alignment.rms).abs() < 1e-9 {
+            break;
+        }
+        previous_rms = alignment.rms;
+
+        let Some(step) = RigidTransform::fit(&from, &to) else { break };
+        current = current.then(&step);
+    }
+
+    alignment.transform = current.to_matrix();
+    (current, alignment)
+}
+
+fn compare_holes(old: &[Hole], new: &[Hole], align: &RigidTransform, tolerance: f64, reach: f64) -> Vec<HoleChange> {
+    let moved: Vec<(Vec3, Vec3)> = new.iter()
+        .map(|h| (align.apply(&h.center), mat3_mul_vec(&align.rotation, &h.axis)))
+        .collect();
+    // Distance between parallel axes; sliding along the axis is not a move
+    let axis_offset = |i: usize, j: usize| {
+        let (center, axis) = &moved[j];
+        (dot(&old[i].axis, axis).abs() > 1.0 - 1e-6).then(|| norm(&cross(&sub(center, &old[i].center), &old[i].axis)))
+    };
+    let (pairs, removed, added) = match_features(old.len(), new.len(), |i, j| axis_offset(i, j).filter(|d| *d <= reach));
+
+    let mut changes: Vec<HoleChange> = pairs.into_iter()
+        .filter_map(|(i, j)| {
+            let offset = axis_offset(i, j).unwrap_or(0.0);
+            let change = match (offset > tolerance, (new[j].diameter - old[i].diameter).abs() > tolerance) {
+                (true, true) => GeometryChange::MovedAndResized,
+                (true, false) => GeometryChange::Moved,
+                (false, true) => GeometryChange::Resized,
+                (false, false) => return None,
+            };
+            Some(HoleChange {
+                change,
+                old_hole_id: Some(old[i].id.clone()),
+                new_hole_id: Some(new[j].id.clone()),
+                old_diameter: Some(old[i].diameter),
+                new_diameter: Some(new[j].diameter),
+                offset: Some(offset),
+                position: old[i].center,
+            })
+        })
+        .collect();
+    changes.extend(removed.into_iter().map(|i| HoleChange {
+        change: GeometryChange::Removed,
+        old_hole_id: Some(old[i].id.clone()),
+        new_hole_id: None,
+        old_diameter: Some(old[i].diameter),
+        new_diameter: None,
+        offset: None,
+        position: old[i].center,
+    }));
+    changes.extend(added.into_iter().map(|j| HoleChange {
+        change: GeometryChange::Added,
+        old_hole_id: None,
+        new_hole_id: Some(new[j].id.clone()),
+        old_diameter: None,
+        new_diameter: Some(new[j].diameter),
+        offset: None,
+        position: moved[j].0,
+    }));
+    changes
+}
+
+fn compare_walls(old: &[Wall], new: &[Wall], align: &RigidTransform, tolerance: f64, reach: f64) -> Vec<WallChange> {
+    let moved: Vec<(Vec3, Vec3)> = new.iter()
+        .map(|w| (align.apply(&w.mid), mat3_mul_vec(&align.rotation, &w.normal)))
+        .collect();
+    let cost = |i: usize, j: usize| {
+        let (mid, normal) = &moved[j];
+        (dot(&old[i].normal, normal).abs() > 0.9999)
+            .then(|| distance(mid, &old[i].mid))
+            .filter(|d| *d <= reach)
+    };
+    let (pairs, removed, added) = match_features(old.len(), new.len(), cost);
+
+    let mut changes: Vec<WallChange> = pairs.into_iter()
+        .filter(|&(i, j)| (new[j].thickness - old[i].thickness).abs() > tolerance)
+        .map(|(i, j)| WallChange {
+            change: GeometryChange::Resized,
+            old_face_ids: Some(old[i].faces),
+            new_face_ids: Some(new[j].faces),
+            old_thickness: Some(old[i].thickness),
+            new_thickness: Some(new[j].thickness),
+            normal: old[i].normal,
+        })
+        .collect();
+    changes.extend(removed.into_iter().map(|i| WallChange {
+        change: GeometryChange::Removed,
+        old_face_ids: Some(old[i].faces),
+        new_face_ids: None,
+        old_thickness: Some(old[i].thickness),
+        new_thickness: None,
+        normal: old[i].normal,
+    }));
+    changes.extend(added.into_iter().map(|j| WallChange {
+        change: GeometryChange::Added,
+        old_face_ids: None,
+        new_face_ids: Some(new[j].faces),
+        old_thickness: None,
+        new_thickness: Some(new[j].thickness),
+        normal: moved[j].1,
+    }));
+    changes
+}
+
+/// Overall extents per axis, with the new revision's box taken through the alignment
+fn compare_dimensions(old: &ParsedPart, new: &ParsedPart, align: &RigidTransform, tolerance: f64) -> Vec<DimensionChange> {
+    let (Some(old_box), Some(new_box)) = (&old.bounding_box, &new.bounding_box) else { return Vec::new() };
+    let mut min = [f64::MAX; 3];
+    let mut max = [f64::MIN; 3];
+    for i in 0..8 {
+        let corner = [0, 1, 2].map(|axis| if i >> axis & 1 == 0 { new_box.min[axis] } else { new_box.max[axis] });
+        let p = align.apply(&corner);
+        for k in 0..3 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+
+    ["x", "y", "z"].iter().enumerate()
+        .map(|(k, axis)| DimensionChange {
+            axis: axis.to_string(),
+            old: old_box.max[k] - old_box.min[k],
+            new: max[k] - min[k],
+            delta: (max[k] - min[k]) - (old_box.max[k] - old_box.min[k]),
+        })
+        .filter(|d| d.delta.abs() > tolerance)
+        .collect()
+}
+
+/// Align two revisions of a part and report moved/resized holes, changed walls and overall size
+pub fn compare_revisions(old: &ParsedPart, new: &ParsedPart, options: &RevisionCompareOptions) -> RevisionComparison {
+    let (old_local, new_local) = (local_part(old), local_part(new));
+    let (align, alignment) = align_revisions(&old_local, &new_local, options);
+
+    let reach = options.match_distance.unwrap_or_else(|| {
+        old.bounding_box.as_ref().map(|b| distance(&b.min, &b.max) * 0.1).unwrap_or(f64::MAX)
+    });
+    let holes = compare_holes(
+        &recognize_part_holes(&old_local),
+        &recognize_part_holes(&new_local),
+        &align,
+        options.tolerance,
+        reach,
+    );
+    let walls = compare_walls(&part_walls(&old_local), &part_walls(&new_local), &align, options.tolerance, reach);
+    let dimensions = compare_dimensions(&old_local, &new_local, &align, options.tolerance);
+
+    RevisionComparison {
+        old_part_id: old.id.clone(),
+        new_part_id: new.id.clone(),
+        alignment,
+        unchanged: holes.is_empty() && walls.is_empty() && dimensions.is_empty(),
+        holes,
+        walls,
+        dimensions,
+    }
+}
+
+/// The named part, or the only part of a single-part model
+fn select_part(model: &LoadedModel, part_id: Option<&String>) -> Result<ParsedPart, String> {
+    let parts = &model.assembly.parts;
+    match part_id {
+        Some(id) => parts.iter().find(|p| &p.id == id).cloned().ok_or_else(|| format!("Unknown part: {}", id)),
+        None if parts.len() == 1 => Ok(parts[0].clone()),
+        None => Err(format!("Model {} has {} parts; choose one", model.filename, parts.len())),
+    }
+}
+
+/// Compare a part in one loaded model (old revision) against a part in another (new revision)
+#[tauri::command]
+pub fn compare_part_revisions(
+    state: State<'_, ModelStore>,
+    old_handle: String,
+    new_handle: String,
+    old_part_id: Option<String>,
+    new_part_id: Option<String>,
+    options: Option<RevisionCompareOptions>,
+) -> Result<RevisionComparison, String> {
+    let options = options.unwrap_or_default();
+    let old = state.with_model(&old_handle, |model| select_part(model, old_part_id.as_ref()))??;
+    let new = state.with_model(&new_handle, |model| select_part(model, new_part_id.as_ref()))??;
+
+    let comparison = compare_revisions(&old, &new, &options);
+    tracing::info!(
+        old = %old_handle,
+        new = %new_handle,
+        holes = comparison.holes.len(),
+        walls = comparison.walls.len(),
+        "part revisions compared"
+    );
+    Ok(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly_parser::PartBoundingBox;
+    use crate::test_fixtures::{face, hole};
+
+    /// 100 x 50 plate of the given thickness, shifted along X, with two holes
+    fn plate(shift: f64, thickness: f64, holes: [(f64, f64, f64); 2]) -> ParsedPart {
+        let z = thickness / 2.0;
+        let mut faces = vec![
+            face(0, "planar", [shift + 50.0, 25.0, 0.0], [0.0, 0.0, -1.0], None),
+            face(1, "planar", [shift + 50.0, 25.0, thickness], [0.0, 0.0, 1.0], None),
+            face(2, "planar", [shift, 25.0, z], [-1.0, 0.0, 0.0], None),
+            face(3, "planar", [shift + 100.0, 25.0, z], [1.0, 0.0, 0.0], None),
+            face(4, "planar", [shift + 50.0, 0.0, z], [0.0, -1.0, 0.0], None),
+            face(5, "planar", [shift + 50.0, 50.0, z], [0.0, 1.0, 0.0], None),
+        ];
+        for (i, (x, y, r)) in holes.iter().enumerate() {
+            faces.push(hole(6 + i as i64, [shift + x, *y, z], *r));
+        }
+        ParsedPart {
+            id: "bracket".to_string(),
+            name: "bracket".to_string(),
+            step_entity_id: 0,
+            transform: IDENTITY,
+            bounding_box: Some(PartBoundingBox {
+                min: [shift, 0.0, 0.0],
+                max: [shift + 100.0, 50.0, thickness],
+                dimensions: [100.0, 50.0, thickness],
+            }),
+            faces,
+            product_definition_id: None,
+        }
+    }
+
+    #[test]
+    fn test_reports_hole_wall_and_size_changes() {
+        let old = plate(0.0, 8.0, [(20.0, 10.0, 3.0), (80.0, 40.0, 2.5)]);
+        let new = plate(0.0, 10.0, [(22.0, 10.0, 3.0), (80.0, 40.0, 3.0)]);
+
+        let result = compare_revisions(&old, &new, &RevisionCompareOptions::default());
+        let holes: Vec<(GeometryChange, f64)> = result.holes.iter().map(|h| (h.change, h.offset.unwrap())).collect();
+        assert_eq!(holes, vec![(GeometryChange::Moved, 2.0), (GeometryChange::Resized, 0.0)]);
+        assert_eq!(result.walls.len(), 1);
+        assert_eq!((result.walls[0].old_thickness, result.walls[0].new_thickness), (Some(8.0), Some(10.0)));
+        assert_eq!(result.dimensions.len(), 1);
+        assert_eq!((result.dimensions[0].axis.as_str(), result.dimensions[0].delta), ("z", 2.0));
+        assert!(!result.unchanged);
+
+        // A re-origined copy of the same part aligns back and shows no differences
+        let moved = plate(30.0, 8.0, [(20.0, 10.0, 3.0), (80.0, 40.0, 2.5)]);
+        let options = RevisionCompareOptions { alignment: RevisionAlignmentMode::Icp, ..Default::default() };
+        let result = compare_revisions(&old, &moved, &options);
+        assert!((result.alignment.transform[12] + 30.0).abs() < 1e-6, "{:?}", result.alignment);
+        assert!(result.unchanged, "{:?}", result);
+    }
+}
@@ -0,0 +1,681 @@
+// Scan point-cloud import (PLY/XYZ/CSV), best-fit alignment and deviation to the nominal mesh
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::State;
+
+use crate::linalg::{centroid, distance, RigidTransform, Vec3};
+use crate::mesh_query::TriangleBvh;
+use crate::model_store::ModelStore;
+use crate::{BoundingBox, MeshData};
+
+/// Supported scan file formats
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanFormat {
+    Ply,
+    Xyz,
+    Csv,
+}
+
+impl ScanFormat {
+    pub fn from_path(path: &Path) -> Option<ScanFormat> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "ply" => Some(ScanFormat::Ply),
+            "xyz" | "txt" | "pts" => Some(ScanFormat::Xyz),
+            "csv" => Some(ScanFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Scan points attached to a loaded model
+#[derive(Debug, Clone)]
+pub struct ScanData {
+    pub filename: String,
+    pub format: ScanFormat,
+    pub points: Vec<Vec3>,
+    pub alignment: Option<RigidTransform>, // Scan to model coordinates
+}
+
+/// Summary of an imported scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanInfo {
+    pub filename: String,
+    pub format: ScanFormat,
+    pub point_count: usize,
+    pub bounding_box: Option<BoundingBox>,
+    pub aligned: bool,
+}
+
+/// ICP tuning
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlignmentOptions {
+    pub max_iterations: usize,
+    pub sample_size: usize,     // Scan points used per iteration
+    pub tolerance: f64,         // Stop when the RMS changes less than this
+    pub outlier_factor: f64,    // Reject pairs farther than this multiple of the median distance
+}
+
+impl Default for AlignmentOptions {
+    fn default() -> Self {
+        AlignmentOptions { max_iterations: 50, sample_size: 5000, tolerance: 1e-6, outlier_factor: 3.0 }
+    }
+}
+
+/// Result of aligning a scan to the nominal mesh
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlignmentResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub transform: Option<[f64; 16]>, // Column-major, scan to model
+    pub iterations: usize,
+    pub converged: bool,
+    pub rms: f64,
+    pub mean_residual: f64,
+    pub max_residual: f64,
+    pub sample_count: usize,
+    pub inlier_count: usize,
+    pub residuals: Vec<f64>,          // Distance to the mesh per sampled point
+}
+
+/// Tolerance band for the deviation map
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviationOptions {
+    pub upper_tolerance: f64,
+    pub lower_tolerance: f64,  // Negative: material missing
+}
+
+impl Default for DeviationOptions {
+    fn default() -> Self {
+        DeviationOptions { upper_tolerance: 0.1, lower_tolerance: -0.1 }
+    }
+}
+
+/// Summary statistics of signed deviations
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviationStats {
+    pub min: f64,
+    pub max: f64,
+    pub max_abs: f64,
+    pub mean: f64,
+    pub rms: f64,
+    pub std_dev: f64,
+    pub in_tolerance_percent: f64,
+    pub above_count: usize,
+    pub below_count: usize,
+}
+
+/// Per-point signed deviations for the viewer heatmap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviationResult {
+    pub success: bool,
+    pub error: Option<String>,
+    pub points: Vec<f32>,      // Aligned scan points, flattened xyz
+    pub deviations: Vec<f32>,  // Signed distance per point, positive outside the nominal
+    pub upper_tolerance: f64,
+    pub lower_tolerance: f64,
+    pub stats: Option<DeviationStats>,
+}
+
+impl ScanData {
+    pub fn info(&self) -> ScanInfo {
+        ScanInfo {
+            filename: self.filename.clone(),
+            format: self.format,
+            point_count: self.points.len(),
+            bounding_box: points_bounding_box(&self.points),
+            aligned: self.alignment.is_some(),
+        }
+    }
+
+    /// Points in model coordinates, using the alignment when there is one
+    pub fn aligned_points(&self) -> Vec<Vec3> {
+        match &self.alignment {
+            Some(t) => self.points.iter().map(|p| t.apply(p)).collect(),
+            None => self.points.clone(),
+        }
+    }
+}
+
+fn points_bounding_box(points: &[Vec3]) -> Option<BoundingBox> {
+    if points.is_empty() {
+        return None;
+    }
+    let mut min = [f64::MAX; 3];
+    let mut max = [f64::MIN; 3];
+    for p in points {
+        for i in 0..3 {
+            min[i] = min[i].min(p[i]);
+            max[i] = max[i].max(p[i]);
+        }
+    }
+    Some(BoundingBox { min, max, dimensions: [max[0] - min[0], max[1] - min[1], max[2] - min[2]] })
+}
+
+/// Parse scan points from file bytes
+pub fn parse_point_cloud(bytes: &[u8], format: ScanFormat) -> Result<Vec<Vec3>, String> {
+    let points = match format {
+        ScanFormat::Ply => parse_ply(bytes)?,
+        ScanFormat::Xyz | ScanFormat::Csv => parse_delimited(&String::from_utf8_lossy(bytes)),
+    };
+    if points.is_empty() {
+        return Err("No points found in scan".to_string());
+    }
+    Ok(points)
+}
+
+/// XYZ and CSV: one point per line; a header naming x, y and z columns is honoured
+fn parse_delimited(text: &str) -> Vec<Vec3> {
+    let split = |line: &str| -> Vec<String> {
+        line.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .map(|s| s.trim_matches('"').to_string())
+            .collect()
+    };
+
+    let mut columns = [0usize, 1, 2];
+    let mut points = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+        let fields = split(line);
+        let values: Vec<Option<f64>> = columns.iter().map(|&c| fields.get(c).and_then(|f| f.parse().ok())).collect();
+
+        match values[..] {
+            [Some(x), Some(y), Some(z)] => points.push([x, y, z]),
+            _ if n == 0 || points.is_empty() => {
+                // Header row: locate the coordinate columns by name
+                let find = |name: &str| fields.iter().position(|f| f.eq_ignore_ascii_case(name));
+                if let (Some(x), Some(y), Some(z)) = (find("x"), find("y"), find("z")) {
+                    columns = [x, y, z];
+                }
+            }
+            _ => {}
+        }
+    }
+
+    points
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlyEncoding {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone)]
+enum PlyProperty {
+    Scalar { name: String, kind: String },
+    List { count_kind: String, item_kind: String },
+}
+
+#[derive(Debug, Clone)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+/// PLY (ascii or binary): reads x, y, z of the vertex element
+fn parse_ply(bytes: &[u8]) -> Result<Vec<Vec3>, String> {
+    let header_end = bytes.windows(10)
+        .position(|w| w == b"end_header")
+        .ok_or("PLY header has no end_header")?;
+    let body_start = bytes[header_end..].iter()
+        .position(|&b| b == b'\n')
+        .map(|i| header_end + i + 1)
+        .unwrap_or(bytes.len());
+    let header = String::from_utf8_lossy(&bytes[..header_end]);
+
+    let mut lines = header.lines();
+    if lines.next().map(str::trim) != Some("ply") {
+        return Err("Not a PLY file".to_string());
+    }
+
+    let mut encoding = None;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            ["format", "ascii", ..] => encoding = Some(PlyEncoding::Ascii),
+            ["format", "binary_little_endian", ..] => encoding = Some(PlyEncoding::BinaryLittleEndian),
+            ["format", "binary_big_endian", ..] => encoding = Some(PlyEncoding::BinaryBigEndian),
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| format!("Invalid PLY element count: {}", count))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_kind, item_kind, _] => {
+                let element = elements.last_mut().ok_or("PLY property before any element")?;
+                element.properties.push(PlyProperty::List {
+                    count_kind: count_kind.to_string(),
+                    item_kind: item_kind.to_string(),
+                });
+            }
+            ["property", kind, name] => {
+                let element = elements.last_mut().ok_or("PLY property before any element")?;
+                element.properties.push(PlyProperty::Scalar { name: name.to_string(), kind: kind.to_string() });
+            }
+            _ => {}
+        }
+    }
+    let encoding = encoding.ok_or("PLY header has no format line")?;
+
+    let body = &bytes[body_start..];
+    match encoding {
+        PlyEncoding::Ascii => parse_ply_ascii(&String::from_utf8_lossy(body), &elements),
+        binary => parse_ply_binary(body, &elements, binary == PlyEncoding::BinaryLittleEndian),
+    }
+}
+
+fn coordinate_slots(element: &PlyElement) -> Result<[usize; 3], String> {
+    let find = |axis: &str| {
+        element.properties.iter()
+            .position(|p| matches!(p, PlyProperty::Scalar { name, .. } if name == axis))
+            .ok_or_else(|| format!("PLY vertex element has no {} property", axis))
+    };
+    Ok([find("x")?, find("y")?, find("z")?])
+}
+
+fn parse_ply_ascii(body: &str, elements: &[PlyElement]) -> Result<Vec<Vec3>, String> {
+    let mut lines = body.lines().filter(|l| !l.trim().is_empty());
+    let mut points = Vec::new();
+
+    for element in elements {
+        let slots = if element.name == "vertex" { Some(coordinate_slots(element)?) } else { None };
+        for _ in 0..element.count {
+            let line = lines.next().ok_or("PLY body ended early")?;
+            let Some(slots) = slots else { continue };
+
+            // Walk tokens so list properties are skipped by their length
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let mut values = Vec::with_capacity(element.properties.len());
+            let mut cursor = 0;
+            for property in &element.properties {
+                match property {
+                    PlyProperty::Scalar { .. } => {
+                        values.push(tokens.get(cursor).and_then(|t| t.parse::<f64>().ok()));
+                        cursor += 1;
+                    }
+                    PlyProperty::List { .. } => {
+                        let n: usize = tokens.get(cursor).and_then(|t| t.parse().ok()).unwrap_or(0);
+                        values.push(None);
+                        cursor += 1 + n;
+                    }
+                }
+            }
+            match (values[slots[0]], values[slots[1]], values[slots[2]]) {
+                (Some(x), Some(y), Some(z)) => points.push([x, y, z]),
+                _ => return Err(format!("Invalid PLY vertex line: {}", line)),
+            }
+        }
+        if slots.is_some() {
+            break;
+        }
+    }
+
+    Ok(points)
+}
+
+fn ply_kind_size(kind: &str) -> Result<usize, String> {
+    match kind {
+        "char" | "uchar" | "int8" | "uint8" => Ok(1),
+        "short" | "ushort" | "int16" | "uint16" => Ok(2),
+        "int" | "uint" | "int32" | "uint32" | "float" | "float32" => Ok(4),
+        "double" | "float64" => Ok(8),
+        other => Err(format!("Unsupported PLY property type: {}", other)),
+    }
+}
+
+fn read_ply_value(bytes: &[u8], kind: &str, little: bool) -> f64 {
+    macro_rules! read {
+        ($t:ty) => {{
+            let raw = bytes.try_into().unwrap_or_default();
+            (if little { <$t>::from_le_bytes(raw) } else { <$t>::from_be_bytes(raw) }) as f64
+        }};
+    }
+    match kind {
+        "char" | "int8" => read!(i8),
+        "uchar" | "uint8" => read!(u8),
+        "short" | "int16" => read!(i16),
+        "ushort" | "uint16" => read!(u16),
+        "int" | "int32" => read!(i32),
+        "uint" | "uint32" => read!(u32),
+        "float" | "float32" => read!(f32),
+        _ => read!(f64),
+    }
+}
+
+fn parse_ply_binary(body: &[u8], elements: &[PlyElement], little: bool) -> Result<Vec<Vec3>, String> {
+    let mut offset = 0;
+    let mut take = |size: usize| -> Result<&[u8], String> {
+        let slice = body.get(offset..offset + size).ok_or("PLY body ended early")?;
+        offset += size;
+        Ok(slice)
+    };
+    let mut points = Vec::new();
+
+    for element in elements {
+        let slots = if element.name == "vertex" { Some(coordinate_slots(element)?) } else { None };
+        for _ in 0..element.count {
+            let mut point = [0.0; 3];
+            for (i, property) in element.properties.iter().enumerate() {
+                match property {
+                    PlyProperty::Scalar { kind, .. } => {
+                        let value = read_ply_value(take(ply_kind_size(kind)?)?, kind, little);
+                        if let Some(axis) = slots.and_then(|s| s.iter().position(|&slot| slot == i)) {
+                            point[axis] = value;
+                        }
+                    }
+                    PlyProperty::List { count_kind, item_kind } => {
+                        let n = read_ply_value(take(ply_kind_size(count_kind)?)?, count_kind, little) as usize;
+                        take(n * ply_kind_size(item_kind)?)?;
+                    }
+                }
+            }
+            if slots.is_some() {
+                points.push(point);
+            }
+        }
+        if slots.is_some() {
+            break;
+        }
+    }
+
+    Ok(points)
+}
+
+/// Evenly spaced subset of at most `max` points
+fn sample_points(points: &[Vec3], max: usize) -> Vec<Vec3> {
+    let stride = points.len().div_ceil(max.max(1)).max(1);
+    points.iter().step_by(stride).copied().collect()
+}
+
+pub(crate) fn median(values: &[f64]) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    sorted.get(sorted.len() / 2).copied().unwrap_or(0.0)
+}
+
+/// Iterative closest point: rigidly align scan points to the mesh surface
+pub fn align_to_mesh(
+    points: &[Vec3],
+    mesh: &MeshData,
+    options: &AlignmentOptions,
+) -> Result<(AlignmentResult, RigidTransform), String> {
+    let bvh = TriangleBvh::build(mesh);
+    if bvh.is_empty() {
+        return Err("Model mesh has no triangles".to_string());
+    }
+    let sample = sample_points(points, options.sample_size);
+    if sample.len() < 3 {
+        return Err("At least 3 scan points are required".to_string());
+    }
+
+    // Scans usually arrive roughly in model coordinates; only a scan that misses the model entirely
+    // starts with its centroid moved onto the mesh centroid
+    let mesh_vertices: Vec<Vec3> = mesh.vertices.chunks_exact(3).map(|v| [v[0] as f64, v[1] as f64, v[2] as f64]).collect();
+    let mut current = RigidTransform::default();
+    if let (Some(scan_box), Some(mesh_box)) = (points_bounding_box(&sample), points_bounding_box(&mesh_vertices)) {
+        let overlaps = (0..3).all(|i| scan_box.min[i] <= mesh_box.max[i] && scan_box.max[i] >= mesh_box.min[i]);
+        if !overlaps {
+            let (cs, cm) = (centroid(&sample), centroid(&mesh_vertices));
+            current.translation = [cm[0] - cs[0], cm[1] - cs[1], cm[2] - cs[2]];
+        }
+    }
+
+    let mut previous_rms = f64::MAX;
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < options.max_iterations {
+        iterations += 1;
+        let moved: Vec<Vec3> = sample.iter().map(|p| current.apply(p)).collect();
+        let nearest: Vec<_> = moved.iter().filter_map(|p| bvh.closest_point(p)).collect();
+        let distances: Vec<f64> = nearest.iter().map(|c| c.distance).collect();
+
+        let limit = (median(&distances) * options.outlier_factor).max(1e-9);
+        let (from, to): (Vec<Vec3>, Vec<Vec3>) = moved.iter().zip(&nearest)
+            .filter(|(_, c)| c.distance <= limit)
+            .map(|(p, c)| (*p, c.point))
+            .unzip();
+
+        let rms = (from.iter().zip(&to)
+            .map(|(a, b)| distance(a, b).powi(2))
+            .sum::<f64>() / from.len().max(1) as f64)
+            .sqrt();
+        if (previous_rms - rms).abs() < options.tolerance {
+            converged = true;
+            break;
+        }
+        previous_rms = rms;
+
+        let Some(step) = RigidTransform::fit(&from, &to) else { break };
+        current = current.then(&step);
+    }
+
+    let residuals: Vec<f64> = sample.iter()
+        .filter_map(|p| bvh.closest_point(&current.apply(p)))
+        .map(|c| c.distance)
+        .collect();
+    let limit = (median(&residuals) * options.outlier_factor).max(1e-9);
+    let inliers: Vec<f64> = residuals.iter().copied().filter(|&d| d <= limit).collect();
+    if inliers.len() < 3 {
+        return Err("Too few scan points near the model".to_string());
+    }
+
+    let result = AlignmentResult {
+        success: true,
+        error: None,
+        transform: Some(current.to_matrix()),
+        iterations,
+        converged,
+        rms: (inliers.iter().map(|d| d * d).sum::<f64>() / inliers.len() as f64).sqrt(),
+        mean_residual: residuals.iter().sum::<f64>() / residuals.len() as f64,
+        max_residual: residuals.iter().copied().fold(0.0, f64::max),
+        sample_count: sample.len(),
+        inlier_count: inliers.len(),
+        residuals,
+    };
+    Ok((result, current))
+}
+
+/// Signed point-to-surface deviation of every point
+pub fn compute_deviations(points: &[Vec3], mesh: &MeshData, options: &DeviationOptions) -> Result<(Vec<f64>, DeviationStats), String> {
+    let bvh = TriangleBvh::build(mesh);
+    if bvh.is_empty() {
+        return Err("Model mesh has no triangles".to_string());
+    }
+    let deviations: Vec<f64> = points.iter().filter_map(|p| bvh.signed_distance(p)).collect();
+    let stats = deviation_stats(&deviations, options);
+    Ok((deviations, stats))
+}
+
+fn deviation_stats(deviations: &[f64], options: &DeviationOptions) -> DeviationStats {
+    if deviations.is_empty() {
+        return DeviationStats::default();
+    }
+    let n = deviations.len() as f64;
+    let mean = deviations.iter().sum::<f64>() / n;
+    let above_count = deviations.iter().filter(|&&d| d > options.upper_tolerance).count();
+    let below_count = deviations.iter().filter(|&&d| d < options.lower_tolerance).count();
+
+    DeviationStats {
+        min: deviations.iter().copied().fold(f64::MAX, f64::min),
+        max: deviations.iter().copied().fold(f64::MIN, f64::max),
+        max_abs: deviations.iter().fold(0.0, |m, d| m.max(d.abs())),
+        mean,
+        rms: (deviations.iter().map(|d| d * d).sum::<f64>() / n).sqrt(),
+        std_dev: (deviations.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / n).sqrt(),
+        in_tolerance_percent: 100.0 * (deviations.len() - above_count - below_count) as f64 / n,
+        above_count,
+        below_count,
+    }
+}
+
+/// Import a scan point cloud and attach it to a loaded model
+#[tauri::command]
+pub fn import_scan(state: State<'_, ModelStore>, handle: String, path: String) -> Result<ScanInfo, String> {
+    let file = Path::new(&path);
+    let format = ScanFormat::from_path(file).ok_or("Unsupported scan format (expected .ply, .xyz or .csv)")?;
+    let bytes = std::fs::read(file).map_err(|e| format!("Failed to read scan: {}", e))?;
+
+    let scan = ScanData {
+        filename: file.file_name().and_then(|n| n.to_str()).unwrap_or_default().to_string(),
+        format,
+        points: parse_point_cloud(&bytes, format)?,
+        alignment: None,
+    };
+    let info = scan.info();
+
+    state.with_model_mut(&handle, |model| model.scan = Some(scan))?;
+    tracing::info!(handle = %handle, points = info.point_count, "scan imported");
+    Ok(info)
+}
+
+/// Best-fit the model's scan to its nominal mesh; the transform is kept with the scan
+#[tauri::command]
+pub fn align_scan(state: State<'_, ModelStore>, handle: String, options: Option<AlignmentOptions>) -> AlignmentResult {
+    let options = options.unwrap_or_default();
+    let outcome = state.with_model_mut(&handle, |model| {
+        let mesh = model.mesh.as_ref().ok_or("Model has no mesh")?;
+        let scan = model.scan.as_mut().ok_or("No scan imported for this model")?;
+
+        let (result, transform) = align_to_mesh(&scan.points, mesh, &options)?;
+        tracing::info!(handle = %model.handle, rms = result.rms, iterations = result.iterations, "scan aligned");
+        scan.alignment = Some(transform);
+        Ok(result)
+    }).and_then(|r| r);
+
+    outcome.unwrap_or_else(|e| AlignmentResult {
+        success: false,
+        error: Some(e),
+        transform: None,
+        iterations: 0,
+        converged: false,
+        rms: 0.0,
+        mean_residual: 0.0,
+        max_residual: 0.0,
+        sample_count: 0,
+        inlier_count: 0,
+        residuals: Vec::new(),
+    })
+}
+
+/// Signed deviations of the aligned scan against the nominal mesh
+#[tauri::command]
+pub fn compute_scan_deviation(
+    state: State<'_, ModelStore>,
+    handle: String,
+    options: Option<DeviationOptions>,
+) -> DeviationResult {
+    let options = options.unwrap_or_default();
+    let outcome = state.with_model(&handle, |model| {
+        let mesh = model.mesh.as_ref().ok_or("Model has no mesh")?;
+        let scan = model.scan.as_ref().ok_or("No scan imported for this model")?;
+        if scan.alignment.is_none() {
+            return Err("Align the scan to the model before computing deviations".to_string());
+        }
+
+        let points = scan.aligned_points();
+        let (deviations, stats) = compute_deviations(&points, mesh, &options)?;
+        Ok((points, deviations, stats))
+    }).and_then(|r| r);
+
+    match outcome {
+        Ok((points, deviations, stats)) => DeviationResult {
+            success: true,
+            error: None,
+            points: points.iter().flatten().map(|&v| v as f32).collect(),
+            deviations: deviations.iter().map(|&d| d as f32).collect(),
+            upper_tolerance: options.upper_tolerance,
+            lower_tolerance: options.lower_tolerance,
+            stats: Some(stats),
+        },
+        Err(e) => DeviationResult {
+            success: false,
+            error: Some(e),
+            points: Vec::new(),
+            deviations: Vec::new(),
+            upper_tolerance: options.upper_tolerance,
+            lower_tolerance: options.lower_tolerance,
+            stats: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xyz_csv_and_ply() {
+        let xyz = parse_point_cloud(b"# scan\n1 2 3\n4 5 6\n", ScanFormat::Xyz).unwrap();
+        assert_eq!(xyz, vec![[1.0, 2.0, 3.0], [4.0, 5.0, 6.0]]);
+
+        let csv = parse_point_cloud(b"id,z,y,x\n1,3,2,1\n", ScanFormat::Csv).unwrap();
+        assert_eq!(csv, vec![[1.0, 2.0, 3.0]]);
+
+        let mut ply = b"ply\nformat binary_little_endian 1.0\nelement vertex 2\nproperty float x\nproperty float y\nproperty float z\nproperty uchar red\nend_header\n".to_vec();
+        for (p, red) in [([1.0f32, 2.0, 3.0], 255u8), ([-1.0, 0.5, 8.0], 0)] {
+            for v in p {
+                ply.extend_from_slice(&v.to_le_bytes());
+            }
+            ply.push(red);
+        }
+        let points = parse_point_cloud(&ply, ScanFormat::Ply).unwrap();
+        assert_eq!(points, vec![[1.0, 2.0, 3.0], [-1.0, 0.5, 8.0]]);
+    }
+
+    #[test]
+    fn test_icp_recovers_offset_scan() {
+        let (vertices, indices, normals, _) = crate::create_mesh_from_points(&[[0.0, 0.0, 0.0], [10.0, 6.0, 4.0]]);
+        let mesh = MeshData { vertices, indices, normals, face_groups: Vec::new(), triangle_face_ids: Vec::new() };
+
+        // Points sampled on three faces of the box, then displaced
+        let mut points = Vec::new();
+        for i in 0..=10 {
+            for j in 0..=6 {
+                points.push([i as f64, j as f64, 0.0]);
+                points.push([i as f64, 0.0, j as f64 * 4.0 / 6.0]);
+                points.push([0.0, j as f64, i as f64 * 0.4]);
+            }
+        }
+        let angle: f64 = 0.02;
+        let offset = RigidTransform {
+            rotation: [[angle.cos(), -angle.sin(), 0.0], [angle.sin(), angle.cos(), 0.0], [0.0, 0.0, 1.0]],
+            translation: [0.3, -0.2, 0.1],
+        };
+        let scan: Vec<Vec3> = points.iter().map(|p| offset.apply(p)).collect();
+
+        let (result, transform) = align_to_mesh(&scan, &mesh, &AlignmentOptions::default()).unwrap();
+        assert!(result.rms < 1e-3, "rms {}", result.rms);
+        for (original, scanned) in points.iter().zip(&scan).take(20) {
+            assert!(distance(&transform.apply(scanned), original) < 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_signed_deviation_stats() {
+        let (vertices, indices, normals, _) = crate::create_mesh_from_points(&[[0.0, 0.0, 0.0], [10.0, 10.0, 10.0]]);
+        let mesh = MeshData { vertices, indices, normals, face_groups: Vec::new(), triangle_face_ids: Vec::new() };
+
+        // Two points proud of the top face, one sunk below it, one on it
+        let points = vec![[5.0, 5.0, 10.05], [2.0, 2.0, 10.3], [5.0, 5.0, 9.8], [3.0, 3.0, 10.0]];
+        let (deviations, stats) = compute_deviations(&points, &mesh, &DeviationOptions::default()).unwrap();
+
+        assert!((deviations[0] - 0.05).abs() < 1e-6);
+        assert!((deviations[2] + 0.2).abs() < 1e-6);
+        assert_eq!((stats.above_count, stats.below_count), (1, 1));
+        assert!((stats.in_tolerance_percent - 50.0).abs() < 1e-9);
+        assert!((stats.max_abs - 0.3).abs() < 1e-6);
+    }
+}
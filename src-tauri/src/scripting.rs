@@ -0,0 +1,183 @@
+// Rhai scripts that chain model, interface and stackup calls for repetitive analyses
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::serde::{from_dynamic, to_dynamic};
+use rhai::{Dynamic, Engine, EvalAltResult};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State};
+
+use crate::assembly_parser::AssemblyParseResult;
+use crate::interface_detection::{find_mating_interfaces, DetectionParams, InterfaceDetectionResult};
+use crate::jobs::{run_job, JobLogLevel};
+use crate::model_store::ModelStore;
+use crate::tolerance_calc::{compute_tolerance_stackup, ToleranceInput};
+use crate::validation::from_value;
+use crate::StepAnalysisResult;
+
+/// Operations a script may execute before it is stopped
+const SCRIPT_MAX_OPERATIONS: u64 = 50_000_000;
+
+/// Lines of print/debug output kept per run
+const SCRIPT_MAX_OUTPUT_LINES: usize = 10_000;
+
+/// Output of one script run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptResult {
+    pub value: serde_json::Value,   // Value of the last expression
+    pub output: Vec<String>,        // print() and debug() lines
+    pub operations: u64,
+}
+
+/// Copy of the loaded model a script reads, so the store is not locked while it runs
+struct ScriptModel {
+    filename: String,
+    analysis: StepAnalysisResult,
+    assembly: AssemblyParseResult,
+    interfaces: Option<InterfaceDetectionResult>,
+}
+
+type ScriptError = Box<EvalAltResult>;
+
+fn to_script<T: Serialize>(value: &T) -> Result<Dynamic, ScriptError> {
+    to_dynamic(value)
+}
+
+fn loaded(model: &Option<Arc<ScriptModel>>) -> Result<&ScriptModel, ScriptError> {
+    model.as_deref().ok_or_else(|| "No model loaded for this script".into())
+}
+
+/// Engine with resource limits, no module imports and only the bindings below
+fn script_engine(model: Option<Arc<ScriptModel>>, output: Arc<Mutex<Vec<String>>>, operations: Arc<AtomicU64>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(SCRIPT_MAX_OPERATIONS);
+    engine.set_max_call_levels(64);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(1 << 20);
+    engine.set_max_array_size(1_000_000);
+    engine.set_max_map_size(100_000);
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+
+    engine.on_progress(move |ops| {
+        operations.store(ops, Ordering::Relaxed);
+        None
+    });
+    let printed = Arc::clone(&output);
+    engine.on_print(move |text| push_output(&printed, text.to_string()));
+    engine.on_debug(move |text, _, pos| push_output(&output, format!("[{}] {}", pos, text)));
+
+    let m = model.clone();
+    engine.register_fn("filename", move || -> Result<String, ScriptError> { Ok(loaded(&m)?.filename.clone()) });
+    let m = model.clone();
+    engine.register_fn("analysis", move || -> Result<Dynamic, ScriptError> { to_script(&loaded(&m)?.analysis) });
+    let m = model.clone();
+    engine.register_fn("parts", move || -> Result<Dynamic, ScriptError> { to_script(&loaded(&m)?.assembly.parts) });
+    let m = model.clone();
+    engine.register_fn("part", move |id: &str| -> Result<Dynamic, ScriptError> {
+        let model = loaded(&m)?;
+        match model.assembly.parts.iter().find(|p| p.id == id) {
+            Some(part) => to_script(part),
+            None => Err(format!("Unknown part '{}'", id).into()),
+        }
+    });
+
+    // Stored interfaces when the model has them, otherwise a detection with default parameters
+    let m = model.clone();
+    engine.register_fn("interfaces", move || -> Result<Dynamic, ScriptError> {
+        let model = loaded(&m)?;
+        match &model.interfaces {
+            Some(result) => to_script(result),
+            None => {
+                let params = DetectionParams::default();
+                to_script(&find_mating_interfaces(model.assembly.parts.clone(), params.proximity_threshold, params.normal_threshold))
+            }
+        }
+    });
+    let m = model;
+    engine.register_fn("detect_interfaces", move |proximity: f64, normal: f64| -> Result<Dynamic, ScriptError> {
+        to_script(&find_mating_interfaces(loaded(&m)?.assembly.parts.clone(), proximity, normal))
+    });
+
+    engine.register_fn("stackup", |input: Dynamic| -> Result<Dynamic, ScriptError> {
+        let input: ToleranceInput = from_value(from_dynamic(&input)?)?;
+        to_script(&compute_tolerance_stackup(input))
+    });
+
+    engine
+}
+
+fn push_output(output: &Mutex<Vec<String>>, line: String) {
+    if let Ok(mut lines) = output.lock() {
+        if lines.len() < SCRIPT_MAX_OUTPUT_LINES {
+            lines.push(line);
+        }
+    }
+}
+
+/// Evaluate a script; the model is optional so stackup-only scripts run without one
+fn execute_script(model: Option<ScriptModel>, source: &str) -> Result<ScriptResult, String> {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let operations = Arc::new(AtomicU64::new(0));
+    let engine = script_engine(model.map(Arc::new), Arc::clone(&output), Arc::clone(&operations));
+
+    let value = engine.eval::<Dynamic>(source).map_err(|e| format!("Script failed: {}", e))?;
+    let value = from_dynamic::<serde_json::Value>(&value).map_err(|e| format!("Script result is not serializable: {}", e))?;
+    let output = output.lock().map(|lines| lines.clone()).unwrap_or_default();
+    Ok(ScriptResult { value, output, operations: operations.load(Ordering::Relaxed) })
+}
+
+/// Run a user script, optionally against a loaded model
+#[tauri::command]
+pub async fn run_script(
+    app: AppHandle,
+    state: State<'_, ModelStore>,
+    handle: Option<String>,
+    source: String,
+    job_id: Option<String>,
+) -> Result<ScriptResult, String> {
+    let model = match &handle {
+        Some(handle) => Some(state.with_model(handle, |model| ScriptModel {
+            filename: model.filename.clone(),
+            analysis: model.analysis.clone(),
+            assembly: model.assembly.clone(),
+            interfaces: model.interfaces.clone(),
+        })?),
+        None => None,
+    };
+    let label = model.as_ref().map(|m| m.filename.clone());
+
+    run_job(app, "run_script", job_id, label, move |job| {
+        let result = execute_script(model, &source)?;
+        job.log(JobLogLevel::Info, format!("Script finished after {} operations", result.operations));
+        Ok(result)
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_stackup_output_and_limits() {
+        let result = execute_script(None, r#"
+            let links = [];
+            for i in 0..3 {
+                links.push(#{ nominal: 10.0, plus_tolerance: 0.1, minus_tolerance: 0.1,
+                              direction: "positive", distribution: "normal" });
+            }
+            let r = stackup(#{ links: links });
+            print(`nominal ${r.total_nominal}`);
+            r.worst_case.tolerance
+        "#)
+        .unwrap();
+        assert!((result.value.as_f64().unwrap() - 0.3).abs() < 1e-9);
+        assert_eq!(result.output, vec!["nominal 30.0".to_string()]);
+
+        assert!(execute_script(None, "parts()").unwrap_err().contains("No model loaded"));
+        assert!(execute_script(None, "loop {}").is_err());
+        assert!(execute_script(None, r#"import "secrets" as s; 1"#).is_err());
+    }
+}
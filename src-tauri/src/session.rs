@@ -0,0 +1,147 @@
+// Backend-held model state shared across windows
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::assembly_parser::AssemblyParseResult;
+use crate::interface_detection::InterfaceDetectionResult;
+use crate::StepMeshResult;
+
+/// Event broadcast to every window when the shared model changes
+pub const SHARED_MODEL_EVENT: &str = "shared-model-changed";
+
+/// Event broadcast to every window when the selection changes
+pub const SELECTION_EVENT: &str = "selection-changed";
+
+/// Model currently shown by the main window and any detached windows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedModel {
+    pub revision: u64,
+    pub filename: String,
+    pub handle: Option<String>,  // Loaded model handle, for queries from detached windows
+    pub mesh_result: Option<StepMeshResult>,
+    pub assembly: Option<AssemblyParseResult>,
+    pub interfaces: Option<InterfaceDetectionResult>,
+}
+
+/// Lightweight notification payload (windows fetch the full model on demand)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedModelChanged {
+    pub revision: u64,
+    pub filename: Option<String>,
+}
+
+/// Selection shared between the stackup table and the viewer
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SharedSelection {
+    pub part_ids: Vec<String>,
+    pub face_ids: Vec<i64>,
+    pub interface_ids: Vec<String>,
+    pub source_window: Option<String>,
+}
+
+/// Managed session state
+#[derive(Default)]
+pub struct SessionState {
+    shared: Mutex<Option<SharedModel>>,
+    selection: Mutex<SharedSelection>,
+    revision: Mutex<u64>,
+}
+
+impl SessionState {
+    /// Handle of the model the main window is showing
+    pub fn shared_handle(&self) -> Option<String> {
+        self.shared.lock().ok()?.as_ref()?.handle.clone()
+    }
+
+    fn next_revision(&self) -> Result<u64, String> {
+        let mut revision = self.revision.lock().map_err(|_| "Session state poisoned".to_string())?;
+        *revision += 1;
+        Ok(*revision)
+    }
+}
+
+/// Publish the model the main window loaded so detached windows can show it
+#[tauri::command]
+pub fn set_shared_model(
+    app: AppHandle,
+    state: State<'_, SessionState>,
+    filename: String,
+    handle: Option<String>,
+    mesh_result: Option<StepMeshResult>,
+    assembly: Option<AssemblyParseResult>,
+    interfaces: Option<InterfaceDetectionResult>,
+) -> Result<u64, String> {
+    let revision = state.next_revision()?;
+    {
+        let mut shared = state.shared.lock().map_err(|_| "Session state poisoned".to_string())?;
+        *shared = Some(SharedModel {
+            revision,
+            filename: filename.clone(),
+            handle,
+            mesh_result,
+            assembly,
+            interfaces,
+        });
+    }
+
+    tracing::info!(filename = %filename, revision, "shared model updated");
+    let _ = app.emit(SHARED_MODEL_EVENT, SharedModelChanged { revision, filename: Some(filename) });
+    Ok(revision)
+}
+
+/// Get the shared model (None if nothing is loaded)
+#[tauri::command]
+pub fn get_shared_model(state: State<'_, SessionState>) -> Result<Option<SharedModel>, String> {
+    let shared = state.shared.lock().map_err(|_| "Session state poisoned".to_string())?;
+    Ok(shared.clone())
+}
+
+/// Unload the shared model from all windows
+#[tauri::command]
+pub fn clear_shared_model(app: AppHandle, state: State<'_, SessionState>) -> Result<(), String> {
+    let revision = state.next_revision()?;
+    {
+        let mut shared = state.shared.lock().map_err(|_| "Session state poisoned".to_string())?;
+        *shared = None;
+    }
+
+    let _ = app.emit(SHARED_MODEL_EVENT, SharedModelChanged { revision, filename: None });
+    Ok(())
+}
+
+/// Update the shared selection and broadcast it to every window
+#[tauri::command]
+pub fn set_shared_selection(
+    app: AppHandle,
+    state: State<'_, SessionState>,
+    selection: SharedSelection,
+) -> Result<(), String> {
+    {
+        let mut current = state.selection.lock().map_err(|_| "Session state poisoned".to_string())?;
+        *current = selection.clone();
+    }
+
+    let _ = app.emit(SELECTION_EVENT, selection);
+    Ok(())
+}
+
+/// Get the current shared selection
+#[tauri::command]
+pub fn get_shared_selection(state: State<'_, SessionState>) -> Result<SharedSelection, String> {
+    let selection = state.selection.lock().map_err(|_| "Session state poisoned".to_string())?;
+    Ok(selection.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revisions_increase() {
+        let state = SessionState::default();
+        assert_eq!(state.next_revision().unwrap(), 1);
+        assert_eq!(state.next_revision().unwrap(), 2);
+    }
+}
@@ -0,0 +1,287 @@
+// Sheet-metal part detection, bend extraction and flat-pattern estimate
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::assembly_parser::{ParsedFace, ParsedPart};
+use crate::features::{is_coaxial, outward_normal, part_extent_along};
+use crate::interface_detection::transform_direction;
+use crate::linalg::{cross, dot, norm, normalize, sub, Vec3};
+use crate::model_store::ModelStore;
+
+/// Default K-factor (neutral axis position as a fraction of thickness)
+pub const DEFAULT_K_FACTOR: f64 = 0.44;
+
+/// Flat region bounded by two parallel faces one thickness apart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Flange {
+    pub id: String,
+    pub face_ids: [i64; 2],
+    pub normal: Vec3,              // World
+    pub length: Option<f64>,       // Flat length, estimated from the part envelope
+}
+
+/// Bend between flanges: a coaxial inner/outer cylinder pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bend {
+    pub id: String,
+    pub face_ids: [i64; 2],        // Inner, outer
+    pub axis: Vec3,                // World
+    pub inner_radius: f64,
+    pub angle_degrees: Option<f64>,
+    pub flange_ids: Vec<String>,
+    pub bend_allowance: Option<f64>,
+}
+
+/// Sheet-metal analysis of one part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SheetMetalPart {
+    pub part_id: String,
+    pub name: String,
+    pub is_sheet_metal: bool,
+    pub thickness: Option<f64>,
+    pub k_factor: f64,
+    pub flanges: Vec<Flange>,
+    pub bends: Vec<Bend>,
+    pub flat_pattern: Option<[f64; 2]>, // Length x width; parallel bends only
+}
+
+/// Separation of two planar faces when the material lies between them
+fn plane_separation(a: &ParsedFace, b: &ParsedFace) -> Option<f64> {
+    let na = outward_normal(a);
+    if dot(&na, &outward_normal(b)) > -0.9999 {
+        return None;
+    }
+    let gap = -dot(&sub(&b.center, &a.center), &na);
+    (gap > 1e-9).then_some(gap)
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-3 * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Distance from the plane of a face to a point
+fn plane_distance(face: &ParsedFace, point: &Vec3) -> f64 {
+    dot(&sub(point, &face.center), &normalize(&face.normal)).abs()
+}
+
+/// Detect whether a part is sheet metal and extract its flanges and bends
+pub fn analyze_part(part: &ParsedPart, k_factor: f64) -> SheetMetalPart {
+    let mut result = SheetMetalPart {
+        part_id: part.id.clone(),
+        name: part.name.clone(),
+        is_sheet_metal: false,
+        thickness: None,
+        k_factor,
+        flanges: Vec::new(),
+        bends: Vec::new(),
+        flat_pattern: None,
+    };
+
+    let planes: Vec<&ParsedFace> = part.faces.iter().filter(|f| f.face_type == "planar").collect();
+
+    // Thickness: the smallest separation between opposing planes
+    let Some(thickness) = planes.iter().enumerate()
+        .flat_map(|(i, a)| planes[i + 1..].iter().filter_map(move |b| plane_separation(a, b)))
+        .reduce(f64::min)
+    else {
+        return result;
+    };
+
+    // Pair planes one thickness apart into flanges
+    let mut paired = vec![false; planes.len()];
+    for i in 0..planes.len() {
+        if paired[i] {
+            continue;
+        }
+        let partner = (i + 1..planes.len())
+            .find(|&j| !paired[j] && plane_separation(planes[i], planes[j]).is_some_and(|s| close(s, thickness)));
+        if let Some(j) = partner {
+            paired[i] = true;
+            paired[j] = true;
+            result.flanges.push(Flange {
+                id: format!("{}-flange-{}", part.id, result.flanges.len() + 1),
+                face_ids: [planes[i].id, planes[j].id],
+                normal: transform_direction(&outward_normal(planes[i]), &part.transform),
+                length: None,
+            });
+        }
+    }
+    let flange_normals: Vec<Vec3> = planes.iter().zip(&paired)
+        .filter(|(_, &p)| p)
+        .map(|(f, _)| outward_normal(f))
+        .collect();
+
+    // Every other plane must be an edge face of some flange
+    let all_planes_explained = planes.iter().zip(&paired)
+        .all(|(f, &p)| p || flange_normals.iter().any(|n| dot(n, &outward_normal(f)).abs() < 1e-3));
+
+    let mut envelope = part.bounding_box.as_ref().map(|b| b.dimensions).unwrap_or([0.0; 3]);
+    envelope.sort_by(f64::total_cmp);
+    let thin = thickness <= 0.25 * envelope[1];
+
+    result.is_sheet_metal = !result.flanges.is_empty() && all_planes_explained && thin;
+    if !result.is_sheet_metal {
+        result.flanges.clear();
+        return result;
+    }
+    result.thickness = Some(thickness);
+
+    // Bends: coaxial cylinders whose radii differ by the thickness
+    let cylinders: Vec<&ParsedFace> = part.faces.iter()
+        .filter(|f| f.face_type == "cylindrical" && f.radius.is_some() && f.axis.is_some())
+        .collect();
+    let mut local_bends: Vec<(Vec3, Vec3, f64, [i64; 2])> = Vec::new();
+    let mut used = vec![false; cylinders.len()];
+    for i in 0..cylinders.len() {
+        for j in 0..cylinders.len() {
+            if i == j || used[i] || used[j] {
+                continue;
+            }
+            let (inner, outer) = (cylinders[i], cylinders[j]);
+            let (ri, ro) = (inner.radius.unwrap_or(0.0), outer.radius.unwrap_or(0.0));
+            let (ai, ao) = (inner.axis.unwrap_or_default(), outer.axis.unwrap_or_default());
+            if close(ro - ri, thickness) && is_coaxial(&inner.center, &ai, &outer.center, &ao) {
+                used[i] = true;
+                used[j] = true;
+                local_bends.push((inner.center, normalize(&ai), ri, [inner.id, outer.id]));
+            }
+        }
+    }
+
+    // A flange belongs to a bend when its planes are tangent to the bend cylinders
+    let flange_planes: Vec<(&ParsedFace, &ParsedFace)> = result.flanges.iter()
+        .filter_map(|fl| {
+            let a = planes.iter().find(|p| p.id == fl.face_ids[0])?;
+            let b = planes.iter().find(|p| p.id == fl.face_ids[1])?;
+            Some((*a, *b))
+        })
+        .collect();
+    let mut ossb_per_flange = vec![0.0; result.flanges.len()];
+
+    for (n, (point, axis, radius, face_ids)) in local_bends.iter().enumerate() {
+        let adjacent: Vec<usize> = flange_planes.iter().enumerate()
+            .filter(|(_, (a, b))| {
+                dot(&outward_normal(a), axis).abs() < 1e-3
+                    && [plane_distance(a, point), plane_distance(b, point)].iter()
+                        .any(|d| close(*d, *radius) || close(*d, radius + thickness))
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        // Angle between the outer faces of the two flanges
+        let outer_normal = |i: usize| {
+            let (a, b) = flange_planes[i];
+            let outer = if close(plane_distance(a, point), radius + thickness) { a } else { b };
+            outward_normal(outer)
+        };
+        let angle = match adjacent[..] {
+            [a, b] => Some(dot(&outer_normal(a), &outer_normal(b)).clamp(-1.0, 1.0).acos().to_degrees()),
+            _ => None,
+        };
+        let bend_allowance = angle.map(|deg| deg.to_radians() * (radius + k_factor * thickness));
+        if let Some(deg) = angle {
+            // Outside setback relative to the envelope, which measures to the outer mold lines
+            let ossb = (radius + thickness) * (deg.to_radians() / 2.0).tan();
+            for &i in &adjacent {
+                ossb_per_flange[i] += ossb;
+            }
+        }
+
+        result.bends.push(Bend {
+            id: format!("{}-bend-{}", part.id, n + 1),
+            face_ids: *face_ids,
+            axis: transform_direction(axis, &part.transform),
+            inner_radius: *radius,
+            angle_degrees: angle,
+            flange_ids: adjacent.iter().map(|&i| result.flanges[i].id.clone()).collect(),
+            bend_allowance,
+        });
+    }
+
+    // Flange lengths run across the bend axis within the flange plane
+    let bend_axis = local_bends.first().map(|b| b.1);
+    if let Some(axis) = bend_axis {
+        for (i, (face, _)) in flange_planes.iter().enumerate() {
+            let along = normalize(&cross(&axis, &outward_normal(face)));
+            if norm(&along) < 0.5 {
+                continue;
+            }
+            result.flanges[i].length = part_extent_along(part, &along).map(|e| (e - ossb_per_flange[i]).max(0.0));
+        }
+
+        let parallel = local_bends.iter().all(|b| dot(&b.1, &axis).abs() > 1.0 - 1e-6);
+        let complete = result.bends.iter().all(|b| b.bend_allowance.is_some())
+            && result.flanges.iter().all(|f| f.length.is_some());
+        if parallel && complete {
+            let length = result.flanges.iter().filter_map(|f| f.length).sum::<f64>()
+                + result.bends.iter().filter_map(|b| b.bend_allowance).sum::<f64>();
+            result.flat_pattern = part_extent_along(part, &axis).map(|width| [length, width]);
+        }
+    }
+
+    result
+}
+
+/// Find sheet-metal parts on a loaded model
+#[tauri::command]
+pub fn detect_sheet_metal(
+    state: State<'_, ModelStore>,
+    handle: String,
+    k_factor: Option<f64>,
+) -> Result<Vec<SheetMetalPart>, String> {
+    let k_factor = k_factor.unwrap_or(DEFAULT_K_FACTOR);
+    state.with_model(&handle, |model| {
+        model.assembly.parts.iter()
+            .map(|part| analyze_part(part, k_factor))
+            .filter(|part| part.is_sheet_metal)
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembly_parser::PartBoundingBox;
+    use crate::test_fixtures::{boss, face};
+
+    #[test]
+    fn test_l_bracket_bend_and_flat_pattern() {
+        // 2 mm sheet, 3 mm inner radius, horizontal flange 50 long, vertical flange 30 tall, 20 wide
+        let (t, r) = (2.0, 3.0);
+        let y = Some([0.0, 1.0, 0.0]);
+        let part = ParsedPart {
+            id: "bracket".to_string(),
+            name: "bracket".to_string(),
+            step_entity_id: 0,
+            transform: [1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0],
+            bounding_box: Some(PartBoundingBox { min: [0.0; 3], max: [50.0, 20.0, 30.0], dimensions: [50.0, 20.0, 30.0] }),
+            faces: vec![
+                face(0, "planar", [10.0, 0.0, 0.0], [0.0, 0.0, -1.0], None),
+                face(1, "planar", [10.0, 0.0, t], [0.0, 0.0, 1.0], None),
+                face(2, "planar", [0.0, 0.0, 10.0], [-1.0, 0.0, 0.0], None),
+                face(3, "planar", [t, 0.0, 10.0], [1.0, 0.0, 0.0], None),
+                face(4, "planar", [0.0, 0.0, 0.0], [0.0, -1.0, 0.0], None),   // Edge faces
+                face(5, "planar", [0.0, 20.0, 0.0], [0.0, 1.0, 0.0], None),
+                face(6, "planar", [50.0, 0.0, 0.0], [1.0, 0.0, 0.0], None),
+                ParsedFace { axis: y, ..boss(7, [r + t, 0.0, r + t], r) },
+                ParsedFace { axis: y, ..boss(8, [r + t, 0.0, r + t], r + t) },
+            ],
+            product_definition_id: None,
+        };
+
+        let sm = analyze_part(&part, DEFAULT_K_FACTOR);
+        assert!(sm.is_sheet_metal);
+        assert_eq!(sm.thickness, Some(t));
+        assert_eq!(sm.flanges.len(), 2);
+        assert_eq!(sm.bends.len(), 1);
+        assert!((sm.bends[0].angle_degrees.unwrap() - 90.0).abs() < 1e-6);
+        assert_eq!(sm.bends[0].flange_ids.len(), 2);
+
+        // 50 + 30 outer dims minus two setbacks of 5, plus the bend allowance
+        let allowance = std::f64::consts::FRAC_PI_2 * (r + DEFAULT_K_FACTOR * t);
+        let [length, width] = sm.flat_pattern.unwrap();
+        assert!((length - (80.0 - 10.0 + allowance)).abs() < 1e-6);
+        assert!((width - 20.0).abs() < 1e-9);
+    }
+}
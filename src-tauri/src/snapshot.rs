@@ -0,0 +1,608 @@
+// Offscreen viewer snapshots: the tessellated model with markers and stackup polylines rendered to PNG on the GPU
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+use wgpu::util::DeviceExt;
+
+use crate::camera::{bounding_sphere, fit_camera, BoundingSphere, CameraFit, CameraFitOptions};
+use crate::jobs::run_job;
+use crate::linalg::{any_perpendicular, cross, dot, norm, normalize, sub, Vec3};
+use crate::mesh_query::outward_normal;
+use crate::model_store::ModelStore;
+use crate::validation::{Checked, FieldErrors, Validate};
+use crate::MeshData;
+
+const MAX_SIZE: u32 = 4096;
+/// Edge length of list thumbnails (recent files, part library) in pixels
+pub const THUMBNAIL_SIZE: u32 = 128;
+const COLOR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+const MODEL_COLOR: [f32; 4] = [0.55, 0.65, 0.75, 1.0];
+const MARKER_COLOR: [f32; 4] = [0.9, 0.2, 0.15, 1.0];
+const POLYLINE_COLOR: [f32; 4] = [1.0, 0.75, 0.0, 1.0];
+const MARKER_SIZE: f64 = 0.015;  // Fraction of the model radius
+
+/// Position, normal (zero for unlit overlays) and color
+const VERTEX_ATTRIBUTES: [wgpu::VertexAttribute; 3] = wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3, 2 => Float32x4];
+const VERTEX_FLOATS: usize = 10;
+
+const SHADER: &str = r#"
+struct Uniforms {
+    view_proj: mat4x4<f32>,
+    light: vec4<f32>,
+};
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+
+struct VertexOut {
+    @builtin(position) clip: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@location(0) position: vec3<f32>, @location(1) normal: vec3<f32>, @location(2) color: vec4<f32>) -> VertexOut {
+    var out: VertexOut;
+    out.clip = uniforms.view_proj * vec4<f32>(position, 1.0);
+    out.normal = normal;
+    out.color = color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+    if (length(in.normal) < 0.1) {
+        return in.color;
+    }
+    let shade = 0.3 + 0.7 * abs(dot(normalize(in.normal), uniforms.light.xyz));
+    return vec4<f32>(in.color.rgb * shade, in.color.a);
+}
+"#;
+
+/// Point drawn as a small diamond on top of the model, e.g. an interface contact point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotMarker {
+    pub position: Vec3,
+    #[serde(default)]
+    pub color: Option<[f64; 4]>,  // RGBA 0-1
+}
+
+/// Line strip drawn on top of the model, e.g. the points of a stackup path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotPolyline {
+    pub points: Vec<Vec3>,
+    #[serde(default)]
+    pub color: Option<[f64; 4]>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotRequest {
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub camera: Option<CameraFit>,  // Fitted to the model from an isometric direction when absent
+    #[serde(default)]
+    pub markers: Vec<SnapshotMarker>,
+    #[serde(default)]
+    pub polylines: Vec<SnapshotPolyline>,
+    #[serde(default)]
+    pub background: Option<[f64; 4]>,  // Transparent when absent
+}
+
+fn validate_color(field: String, color: Option<[f64; 4]>, errors: &mut FieldErrors) {
+    if let Some(color) = color {
+        for (channel, value) in ["r", "g", "b", "a"].iter().zip(color) {
+            errors.within(format!("{}.{}", field, channel), value, 0.0, 1.0);
+        }
+    }
+}
+
+impl Validate for SnapshotRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        for (name, value) in [("width", self.width), ("height", self.height)] {
+            if value == 0 || value > MAX_SIZE {
+                errors.add(name, format!("expected 1 to {}, got {}", MAX_SIZE, value));
+            }
+        }
+        if let Some(camera) = &self.camera {
+            errors.within("camera.fov_deg", camera.fov_deg, 1.0, 179.0);
+            errors.above("camera.near", camera.near, 0.0);
+            errors.above("camera.far", camera.far, camera.near);
+            if sub(&camera.position, &camera.target).iter().all(|c| c.abs() < 1e-12) {
+                errors.add("camera.target", "expected a target away from the camera position");
+            }
+        }
+        for (i, marker) in self.markers.iter().enumerate() {
+            validate_color(format!("markers[{}].color", i), marker.color, errors);
+        }
+        for (i, polyline) in self.polylines.iter().enumerate() {
+            if polyline.points.len() < 2 {
+                errors.add(format!("polylines[{}].points", i), "expected at least two points");
+            }
+            validate_color(format!("polylines[{}].color", i), polyline.color, errors);
+        }
+        validate_color("background".to_string(), self.background, errors);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotResult {
+    pub width: u32,
+    pub height: u32,
+    pub camera: CameraFit,
+    pub image_base64: String,  // PNG
+}
+
+/// Column-major product a * b of 4x4 matrices
+fn mat4_mul(a: &[f64; 16], b: &[f64; 16]) -> [f64; 16] {
+    let mut m = [0.0; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            m[column * 4 + row] = (0..4).map(|k| a[k * 4 + row] * b[column * 4 + k]).sum();
+        }
+    }
+    m
+}
+
+/// Right-handed view and perspective projection with wgpu's 0..1 depth range
+pub fn view_projection(camera: &CameraFit, aspect: f64) -> [f64; 16] {
+    let z = normalize(&sub(&camera.position, &camera.target));
+    let side = cross(&camera.up, &z);
+    let x = if norm(&side) < 1e-9 { any_perpendicular(&z) } else { normalize(&side) };
+    let y = cross(&z, &x);
+    let eye = camera.position;
+    let view = [
+        x[0], y[0], z[0], 0.0,
+        x[1], y[1], z[1], 0.0,
+        x[2], y[2], z[2], 0.0,
+        -dot(&x, &eye), -dot(&y, &eye), -dot(&z, &eye), 1.0,
+    ];
+
+    let f = 1.0 / (camera.fov_deg.to_radians() / 2.0).tan();
+    let (near, far) = (camera.near, camera.far);
+    let projection = [
+        f / aspect, 0.0, 0.0, 0.0,
+        0.0, f, 0.0, 0.0,
+        0.0, 0.0, far / (near - far), -1.0,
+        0.0, 0.0, near * far / (near - far), 0.0,
+    ];
+    mat4_mul(&projection, &view)
+}
+
+fn rgba(color: Option<[f64; 4]>, default: [f32; 4]) -> [f32; 4] {
+    color.map(|c| c.map(|v| v as f32)).unwrap_or(default)
+}
+
+fn push_vertex(vertices: &mut Vec<f32>, position: &Vec3, normal: [f32; 3], color: [f32; 4]) {
+    vertices.extend(position.map(|v| v as f32));
+    vertices.extend(normal);
+    vertices.extend(color);
+}
+
+/// Interleaved vertices of the shaded model
+fn model_vertices(mesh: &MeshData) -> Vec<f32> {
+    let mut vertices = Vec::with_capacity(mesh.vertices.len() / 3 * VERTEX_FLOATS);
+    for (k, p) in mesh.vertices.chunks_exact(3).enumerate() {
+        vertices.extend_from_slice(p);
+        vertices.extend_from_slice(mesh.normals.get(k * 3..k * 3 + 3).unwrap_or(&[0.0, 0.0, 1.0]));
+        vertices.extend(MODEL_COLOR);
+    }
+    vertices
+}
+
+/// Unlit octahedra at the marker positions, as a triangle list
+fn marker_vertices(markers: &[SnapshotMarker], size: f64) -> Vec<f32> {
+    let mut vertices = Vec::new();
+    for marker in markers {
+        let color = rgba(marker.color, MARKER_COLOR);
+        let c = marker.position;
+        let tip = |axis: usize, sign: f64| {
+            let mut p = c;
+            p[axis] += sign * size;
+            p
+        };
+        for (sx, sy, sz) in [(1.0, 1.0, 1.0), (-1.0, 1.0, 1.0), (1.0, -1.0, 1.0), (-1.0, -1.0, 1.0),
+                             (1.0, 1.0, -1.0), (-1.0, 1.0, -1.0), (1.0, -1.0, -1.0), (-1.0, -1.0, -1.0)] {
+            for p in [tip(0, sx), tip(1, sy), tip(2, sz)] {
+                push_vertex(&mut vertices, &p, [0.0; 3], color);
+            }
+        }
+    }
+    vertices
+}
+
+/// Polyline segments as an unlit line list
+fn polyline_vertices(polylines: &[SnapshotPolyline]) -> Vec<f32> {
+    let mut vertices = Vec::new();
+    for polyline in polylines {
+        let color = rgba(polyline.color, POLYLINE_COLOR);
+        for segment in polyline.points.windows(2) {
+            push_vertex(&mut vertices, &segment[0], [0.0; 3], color);
+            push_vertex(&mut vertices, &segment[1], [0.0; 3], color);
+        }
+    }
+    vertices
+}
+
+fn bytes<T: Copy, const N: usize>(values: &[T], to_bytes: impl Fn(T) -> [u8; N]) -> Vec<u8> {
+    values.iter().flat_map(|v| to_bytes(*v)).collect()
+}
+
+/// GPU state for one snapshot
+struct Renderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+}
+
+impl Renderer {
+    fn new() -> Result<Renderer, String> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            compatible_surface: None,
+            force_fallback_adapter: false,
+        }))
+        .ok_or("No graphics adapter available for offscreen rendering")?;
+        tracing::debug!(adapter = ?adapter.get_info(), "snapshot adapter");
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("snapshot"),
+                required_features: wgpu::Features::empty(),
+                required_limits: wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+                memory_hints: wgpu::MemoryHints::default(),
+            },
+            None,
+        ))
+        .map_err(|e| format!("Failed to open the graphics device: {}", e))?;
+        Ok(Renderer { device, queue })
+    }
+
+    fn pipeline(&self, shader: &wgpu::ShaderModule, layout: &wgpu::PipelineLayout, topology: wgpu::PrimitiveTopology, overlay: bool) -> wgpu::RenderPipeline {
+        self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("snapshot"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: (VERTEX_FLOATS * 4) as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &VERTEX_ATTRIBUTES,
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: COLOR_FORMAT,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState { topology, ..Default::default() },
+            // Overlays stay visible through the parts: contact points and stack loops run inside the assembly
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: !overlay,
+                depth_compare: if overlay { wgpu::CompareFunction::Always } else { wgpu::CompareFunction::Less },
+                stencil: Default::default(),
+                bias: Default::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+
+    fn vertex_buffer(&self, vertices: &[f32]) -> Option<(wgpu::Buffer, u32)> {
+        if vertices.is_empty() {
+            return None;
+        }
+        let buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("snapshot vertices"),
+            contents: &bytes(vertices, f32::to_le_bytes),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        Some((buffer, (vertices.len() / VERTEX_FLOATS) as u32))
+    }
+
+    /// Draw and read back tightly packed RGBA rows
+    fn render(&self, mesh: &MeshData, request: &SnapshotRequest, camera: &CameraFit, marker_size: f64) -> Result<Vec<u8>, String> {
+        let device = &self.device;
+        let (width, height) = (request.width, request.height);
+
+        let view_proj = view_projection(camera, width as f64 / height as f64);
+        let light = normalize(&sub(&camera.position, &camera.target));
+        let uniform_values: Vec<f32> = view_proj.iter().chain(light.iter()).map(|v| *v as f32).chain([0.0]).collect();
+        let uniforms = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("snapshot uniforms"),
+            contents: &bytes(&uniform_values, f32::to_le_bytes),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("snapshot"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("snapshot"),
+            layout: &bind_layout,
+            entries: &[wgpu::BindGroupEntry { binding: 0, resource: uniforms.as_entire_binding() }],
+        });
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("snapshot"),
+            bind_group_layouts: &[&bind_layout],
+            push_constant_ranges: &[],
+        });
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("snapshot"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let surfaces = self.pipeline(&shader, &layout, wgpu::PrimitiveTopology::TriangleList, false);
+        let markers = self.pipeline(&shader, &layout, wgpu::PrimitiveTopology::TriangleList, true);
+        let lines = self.pipeline(&shader, &layout, wgpu::PrimitiveTopology::LineList, true);
+
+        let model = self.vertex_buffer(&model_vertices(mesh));
+        let indices = (!mesh.indices.is_empty()).then(|| device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("snapshot indices"),
+            contents: &bytes(&mesh.indices, u32::to_le_bytes),
+            usage: wgpu::BufferUsages::INDEX,
+        }));
+        let marker_buffer = self.vertex_buffer(&marker_vertices(&request.markers, marker_size));
+        let line_buffer = self.vertex_buffer(&polyline_vertices(&request.polylines));
+
+        let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+        let target = |format: wgpu::TextureFormat, usage: wgpu::TextureUsages| device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("snapshot target"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        });
+        let color = target(COLOR_FORMAT, wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC);
+        let depth = target(DEPTH_FORMAT, wgpu::TextureUsages::RENDER_ATTACHMENT);
+        let color_view = color.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+        let [r, g, b, a] = request.background.unwrap_or([0.0; 4]);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("snapshot") });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("snapshot"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a }), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations { load: wgpu::LoadOp::Clear(1.0), store: wgpu::StoreOp::Discard }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            pass.set_bind_group(0, &bind_group, &[]);
+            if let (Some((vertices, _)), Some(indices)) = (&model, &indices) {
+                pass.set_pipeline(&surfaces);
+                pass.set_vertex_buffer(0, vertices.slice(..));
+                pass.set_index_buffer(indices.slice(..), wgpu::IndexFormat::Uint32);
+                pass.draw_indexed(0..mesh.indices.len() as u32, 0, 0..1);
+            }
+            for (pipeline, buffer) in [(&lines, &line_buffer), (&markers, &marker_buffer)] {
+                if let Some((vertices, count)) = buffer {
+                    pass.set_pipeline(pipeline);
+                    pass.set_vertex_buffer(0, vertices.slice(..));
+                    pass.draw(0..*count, 0..1);
+                }
+            }
+        }
+
+        // Rows of a texture copy are padded to 256 bytes
+        let row = width * 4;
+        let padded_row = row.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT) * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("snapshot readback"),
+            size: (padded_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture { texture: &color, mip_level: 0, origin: wgpu::Origin3d::ZERO, aspect: wgpu::TextureAspect::All },
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout { offset: 0, bytes_per_row: Some(padded_row), rows_per_image: Some(height) },
+            },
+            size,
+        );
+        self.queue.submit([encoder.finish()]);
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        let _ = device.poll(wgpu::Maintain::Wait);
+        receiver.recv()
+            .map_err(|_| "Snapshot readback was dropped".to_string())?
+            .map_err(|e| format!("Failed to read the snapshot back: {}", e))?;
+
+        let pixels = slice.get_mapped_range()
+            .chunks(padded_row as usize)
+            .flat_map(|line| line[..row as usize].to_vec())
+            .collect();
+        readback.unmap();
+        Ok(pixels)
+    }
+}
+
+/// Shaded isometric view of a mesh as a base64 PNG with a transparent background; a CPU raster, fast enough
+/// for import and indexing and independent of a graphics adapter
+pub fn render_thumbnail(mesh: &MeshData, size: u32) -> Option<String> {
+    let toward = normalize(&[1.0, -1.0, 1.0]);
+    let right = normalize(&[1.0, 1.0, 0.0]);
+    let up = cross(&toward, &right);
+    let light = normalize(&[0.4, -0.3, 1.0]);
+
+    let projected: Vec<Vec3> = mesh.vertices.chunks_exact(3)
+        .map(|v| {
+            let p = [v[0] as f64, v[1] as f64, v[2] as f64];
+            [dot(&p, &right), dot(&p, &up), dot(&p, &toward)]
+        })
+        .collect();
+    if projected.is_empty() {
+        return None;
+    }
+    let (mut min, mut max) = ([f64::MAX; 2], [f64::MIN; 2]);
+    for p in &projected {
+        for k in 0..2 {
+            min[k] = min[k].min(p[k]);
+            max[k] = max[k].max(p[k]);
+        }
+    }
+
+    let edge = size;
+    let size = size as usize;
+    let scale = (size as f64 * 0.9) / (max[0] - min[0]).max(max[1] - min[1]).max(1e-9);
+    let offset = [
+        (size as f64 - (max[0] - min[0]) * scale) / 2.0,
+        (size as f64 - (max[1] - min[1]) * scale) / 2.0,
+    ];
+    let to_pixel = |p: &Vec3| [(p[0] - min[0]) * scale + offset[0], size as f64 - ((p[1] - min[1]) * scale + offset[1]), p[2]];
+
+    let mut depth = vec![f64::MIN; size * size];
+    let mut rgba = vec![0u8; size * size * 4];
+    for (tri, corners) in mesh.indices.chunks_exact(3).enumerate() {
+        let [a, b, c] = [0, 1, 2].map(|k| to_pixel(&projected[corners[k] as usize]));
+        let det = (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+        if det.abs() < 1e-12 {
+            continue;
+        }
+        let shade = 0.3 + 0.7 * dot(&outward_normal(mesh, tri), &light).max(0.0);
+        let color = [90.0, 120.0, 150.0].map(|c: f64| (c * shade + 60.0).min(255.0) as u8);
+
+        let lo = |v: f64| (v.floor().max(0.0) as usize).min(size - 1);
+        for y in lo(a[1].min(b[1]).min(c[1]))..=lo(a[1].max(b[1]).max(c[1])) {
+            for x in lo(a[0].min(b[0]).min(c[0]))..=lo(a[0].max(b[0]).max(c[0])) {
+                let (px, py) = (x as f64 + 0.5, y as f64 + 0.5);
+                let w1 = ((px - a[0]) * (c[1] - a[1]) - (py - a[1]) * (c[0] - a[0])) / det;
+                let w2 = ((b[0] - a[0]) * (py - a[1]) - (b[1] - a[1]) * (px - a[0])) / det;
+                if w1 < 0.0 || w2 < 0.0 || w1 + w2 > 1.0 {
+                    continue;
+                }
+                let d = a[2] + w1 * (b[2] - a[2]) + w2 * (c[2] - a[2]);
+                let i = y * size + x;
+                if d > depth[i] {
+                    depth[i] = d;
+                    rgba[i * 4..i * 4 + 4].copy_from_slice(&[color[0], color[1], color[2], 255]);
+                }
+            }
+        }
+    }
+
+    crate::encode_rgba_png_base64(edge, edge, rgba).ok()
+}
+
+/// Render a mesh with overlays to a base64 PNG; the camera is fitted to the mesh when none is given
+pub fn render_snapshot(mesh: &MeshData, request: &SnapshotRequest) -> Result<SnapshotResult, String> {
+    let points: Vec<Vec3> = mesh.vertices.chunks_exact(3).map(|v| [v[0] as f64, v[1] as f64, v[2] as f64]).collect();
+    let sphere = bounding_sphere(&points).unwrap_or(BoundingSphere { center: [0.0; 3], radius: 1.0 });
+    let camera = request.camera.clone().unwrap_or_else(|| {
+        fit_camera(&sphere, &CameraFitOptions { aspect: request.width as f64 / request.height as f64, ..CameraFitOptions::default() })
+    });
+    tracing::info!(width = request.width, height = request.height, triangles = mesh.indices.len() / 3, "rendering snapshot");
+
+    let pixels = Renderer::new()?.render(mesh, request, &camera, sphere.radius * MARKER_SIZE)?;
+    Ok(SnapshotResult {
+        width: request.width,
+        height: request.height,
+        image_base64: crate::encode_rgba_png_base64(request.width, request.height, pixels)?,
+        camera,
+    })
+}
+
+/// Render a loaded model offscreen for reports and prompts, without the webview
+#[tauri::command]
+pub async fn render_model_snapshot(
+    app: AppHandle,
+    state: State<'_, ModelStore>,
+    handle: String,
+    request: Checked<SnapshotRequest>,
+    job_id: Option<String>,
+) -> Result<SnapshotResult, String> {
+    let request = request.into_inner();
+    let mesh = state.with_model(&handle, |model| model.mesh.clone())?
+        .ok_or("The model has no mesh to render")?;
+    run_job(app, "render_model_snapshot", job_id, None, move |_| render_snapshot(&mesh, &request)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::validate;
+
+    fn project(m: &[f64; 16], p: &Vec3) -> Vec3 {
+        let clip: Vec<f64> = (0..4).map(|row| m[row] * p[0] + m[4 + row] * p[1] + m[8 + row] * p[2] + m[12 + row]).collect();
+        [clip[0] / clip[3], clip[1] / clip[3], clip[2] / clip[3]]
+    }
+
+    #[test]
+    fn test_projection_centres_target_and_orients_up() {
+        let camera = CameraFit {
+            position: [100.0, 0.0, 0.0],
+            target: [0.0; 3],
+            up: [0.0, 0.0, 1.0],
+            fov_deg: 45.0,
+            near: 10.0,
+            far: 200.0,
+        };
+        let m = view_projection(&camera, 2.0);
+        let centre = project(&m, &[0.0; 3]);
+        assert!(centre[0].abs() < 1e-12 && centre[1].abs() < 1e-12);
+        assert!(centre[2] > 0.0 && centre[2] < 1.0);
+        // Up is up on screen, and the near and far planes map to depth 0 and 1
+        assert!(project(&m, &[0.0, 0.0, 10.0])[1] > 0.0);
+        assert!(project(&m, &[90.0, 0.0, 0.0])[2].abs() < 1e-9);
+        assert!((project(&m, &[-100.0, 0.0, 0.0])[2] - 1.0).abs() < 1e-9);
+
+        let marker = SnapshotMarker { position: [1.0, 2.0, 3.0], color: None };
+        assert_eq!(marker_vertices(&[marker], 0.5).len(), 24 * VERTEX_FLOATS);
+
+        let request = SnapshotRequest {
+            width: 0,
+            height: 600,
+            camera: None,
+            markers: vec![],
+            polylines: vec![SnapshotPolyline { points: vec![[0.0; 3]], color: Some([1.5, 0.0, 0.0, 1.0]) }],
+            background: None,
+        };
+        let error = validate(&request).unwrap_err();
+        assert!(error.contains("width") && error.contains("polylines[0].points") && error.contains("polylines[0].color.r"));
+    }
+
+    #[test]
+    fn test_thumbnail_of_single_triangle() {
+        let mesh = MeshData {
+            vertices: vec![0.0, 0.0, 0.0, 10.0, 0.0, 0.0, 0.0, 10.0, 0.0],
+            indices: vec![0, 1, 2],
+            normals: vec![0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0],
+            face_groups: vec![],
+            triangle_face_ids: vec![],
+        };
+        let png = render_thumbnail(&mesh, 32).unwrap();
+        assert!(png.starts_with("iVBORw0KGgo"));  // Base64 of the PNG signature
+        assert!(render_thumbnail(&MeshData { vertices: vec![], indices: vec![], ..mesh }, 32).is_none());
+    }
+}
@@ -0,0 +1,141 @@
+// Per-solid bounding boxes for multi-body files the B-rep kernel cannot read: each MANIFOLD_SOLID_BREP
+// gets a box around its CARTESIAN_POINTs, not a tessellation of its faces (that is brep_mesh)
+
+use std::collections::HashSet;
+
+use crate::step_entities::{StepEntities, StepEntity};
+use crate::BoundingBox;
+
+/// Bounding-box mesh of one solid, before merging
+#[derive(Debug, Clone)]
+pub struct SolidMesh {
+    pub vertices: Vec<f32>,
+    pub indices: Vec<u32>,
+    pub normals: Vec<f32>,
+    pub bounding_box: BoundingBox,
+}
+
+/// Cartesian points reachable from a solid through its shells, faces, edges and vertices
+fn solid_points(entities: &StepEntities, solid: &StepEntity) -> Vec<[f64; 3]> {
+    let mut points = Vec::new();
+    let mut visited: HashSet<i64> = HashSet::new();
+    let mut stack: Vec<i64> = solid.references().collect();
+
+    while let Some(id) = stack.pop() {
+        if !visited.insert(id) {
+            continue;
+        }
+        let Some(entity) = entities.get(id) else { continue };
+        if entity.entity_type == "CARTESIAN_POINT" {
+            points.extend(entity.triple());
+        } else {
+            // Every partial of a complex instance, e.g. the control points of a rational spline
+            stack.extend(entities.instance_references(id).filter(|r| !visited.contains(r)));
+        }
+    }
+    points
+}
+
+/// Box around the solid's points; None when it reaches no points
+fn tessellate_solid(entities: &StepEntities, solid: &StepEntity) -> Option<SolidMesh> {
+    let points = solid_points(entities, solid);
+    if points.is_empty() {
+        return None;
+    }
+    let (vertices, indices, normals, bounding_box) = crate::create_mesh_from_points(&points);
+    Some(SolidMesh { vertices, indices, normals, bounding_box })
+}
+
+/// Box every solid in the file for the bounding-box fallback, spreading the solids over the
+/// available cores; solids without geometry are left out and the result keeps file order
+pub fn tessellate_solids(content: &str) -> Vec<SolidMesh> {
+    let entities = StepEntities::parse(content);
+    let solids: Vec<&StepEntity> = entities.of_type("MANIFOLD_SOLID_BREP").collect();
+    if solids.is_empty() {
+        return Vec::new();
+    }
+
+    let threads = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1).min(solids.len());
+    let chunk_size = solids.len().div_ceil(threads);
+    tracing::debug!(solids = solids.len(), threads, "tessellating solids");
+
+    let entities = &entities;
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = solids.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || {
+                chunk.iter().filter_map(|solid| tessellate_solid(entities, solid)).collect::<Vec<_>>()
+            }))
+            .collect();
+        workers.into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect()
+    })
+}
+
+/// Concatenate solid meshes into one buffer set, with indices shifted past earlier solids
+pub fn merge_solid_meshes(meshes: &[SolidMesh]) -> (Vec<f32>, Vec<u32>, Vec<f32>, BoundingBox) {
+    let mut vertices: Vec<f32> = Vec::with_capacity(meshes.iter().map(|m| m.vertices.len()).sum());
+    let mut indices: Vec<u32> = Vec::with_capacity(meshes.iter().map(|m| m.indices.len()).sum());
+    let mut normals: Vec<f32> = Vec::with_capacity(vertices.capacity());
+    let mut min = [f64::MAX; 3];
+    let mut max = [f64::MIN; 3];
+
+    for mesh in meshes {
+        let offset = (vertices.len() / 3) as u32;
+        vertices.extend_from_slice(&mesh.vertices);
+        normals.extend_from_slice(&mesh.normals);
+        indices.extend(mesh.indices.iter().map(|i| i + offset));
+        for axis in 0..3 {
+            min[axis] = min[axis].min(mesh.bounding_box.min[axis]);
+            max[axis] = max[axis].max(mesh.bounding_box.max[axis]);
+        }
+    }
+
+    if meshes.is_empty() {
+        min = [0.0; 3];
+        max = [0.0; 3];
+    }
+    let bbox = BoundingBox {
+        min,
+        max,
+        dimensions: [max[0] - min[0], max[1] - min[1], max[2] - min[2]],
+    };
+    (vertices, indices, normals, bbox)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solids_meshed_separately() {
+        let content = "\
+#1=CARTESIAN_POINT('',(0.,0.,0.));
+#2=CARTESIAN_POINT('',(1.,1.,1.));
+#3=VERTEX_POINT('',#1);
+#4=VERTEX_POINT('',#2);
+#5=CLOSED_SHELL('',(#3,#4));
+#6=MANIFOLD_SOLID_BREP('A',#5);
+#11=CARTESIAN_POINT('',(10.,10.,10.));
+#12=CARTESIAN_POINT('',(12.,14.,16.));
+#13=VERTEX_POINT('',#11);
+#14=VERTEX_POINT('',#12);
+#15=CLOSED_SHELL('',(#13,#14,#3));
+#16=MANIFOLD_SOLID_BREP('B',#15);
+#20=MANIFOLD_SOLID_BREP('empty',#99);";
+
+        let solids = tessellate_solids(content);
+        assert_eq!(solids.len(), 2);
+        assert_eq!(solids[0].bounding_box.max, [1.0, 1.0, 1.0]);
+        // B shares a vertex with A, so its box reaches back to the origin
+        assert_eq!(solids[1].bounding_box.min, [0.0, 0.0, 0.0]);
+        assert_eq!(solids[1].bounding_box.max, [12.0, 14.0, 16.0]);
+
+        let (vertices, indices, normals, bbox) = merge_solid_meshes(&solids);
+        assert_eq!(vertices.len(), 2 * 24 * 3);
+        assert_eq!(normals.len(), vertices.len());
+        assert_eq!(indices.len(), 2 * 36);
+        assert_eq!(indices[36], 24);
+        assert_eq!(bbox.dimensions, [12.0, 14.0, 16.0]);
+    }
+}
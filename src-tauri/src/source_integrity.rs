@@ -0,0 +1,126 @@
+// SHA-256 checks of a loaded model against its source file, so stale results are not used silently
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::model_store::{LoadedModel, ModelInfo, ModelStore};
+use crate::part_transforms::{apply_part_transforms, PartTransformStore};
+use crate::recent_files::hash_content;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceStatus {
+    Unchanged,
+    Changed,    // The file was edited since import; results describe the old geometry
+    Missing,    // The file was moved or deleted
+    NoSource,   // Loaded from content, nothing to compare with
+}
+
+/// Checksum recorded at import compared with the file on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceCheck {
+    pub handle: String,
+    pub filename: String,
+    pub path: Option<String>,
+    pub recorded_hash: String,
+    pub current_hash: Option<String>,
+    pub status: SourceStatus,
+}
+
+impl SourceCheck {
+    /// Whether the user should be offered a re-analysis
+    pub fn is_stale(&self) -> bool {
+        matches!(self.status, SourceStatus::Changed | SourceStatus::Missing)
+    }
+}
+
+/// Compare a model's recorded checksum with its source file
+pub fn check_source(model: &LoadedModel) -> SourceCheck {
+    let current_hash = model.path.as_deref()
+        .and_then(|p| std::fs::read(p).ok())
+        .map(|bytes| hash_content(&bytes));
+    let status = match (&model.path, &current_hash) {
+        (None, _) => SourceStatus::NoSource,
+        (Some(_), None) => SourceStatus::Missing,
+        (Some(_), Some(hash)) if *hash == model.content_hash => SourceStatus::Unchanged,
+        (Some(_), Some(_)) => SourceStatus::Changed,
+    };
+    if matches!(status, SourceStatus::Changed | SourceStatus::Missing) {
+        tracing::warn!(handle = %model.handle, filename = %model.filename, ?status, "model source no longer matches import");
+    }
+    SourceCheck {
+        handle: model.handle.clone(),
+        filename: model.filename.clone(),
+        path: model.path.clone(),
+        recorded_hash: model.content_hash.clone(),
+        current_hash,
+        status,
+    }
+}
+
+/// Check one loaded model against its source file
+#[tauri::command]
+pub fn verify_model_source(state: State<'_, ModelStore>, handle: String) -> Result<SourceCheck, String> {
+    state.with_model(&handle, check_source)
+}
+
+/// Check every loaded model against its source file
+#[tauri::command]
+pub fn verify_model_sources(state: State<'_, ModelStore>) -> Result<Vec<SourceCheck>, String> {
+    state.map_models(check_source)
+}
+
+/// Parse the current source file again under the same handle; interfaces found on the old geometry are dropped
+#[tauri::command]
+pub fn reanalyze_model_from_source(
+    state: State<'_, ModelStore>,
+    transforms: State<'_, PartTransformStore>,
+    handle: String,
+) -> Result<ModelInfo, String> {
+    let (path, filename) = state.with_model(&handle, |model| (model.path.clone(), model.filename.clone()))?;
+    let path = path.ok_or_else(|| format!("{} was not loaded from a file", filename))?;
+    let content = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let mut model = LoadedModel::parse(handle.clone(), content, filename, Some(path));
+    if !model.analysis.success {
+        return Err(model.analysis.error.unwrap_or_else(|| "Invalid STEP file".to_string()));
+    }
+    let overrides = transforms.for_model(&model);
+    apply_part_transforms(&mut model, &overrides);
+
+    // Viewer setup survives; anything computed from the old geometry does not
+    state.with_model(&handle, |old| {
+        model.coordinate_systems = old.coordinate_systems.clone();
+        model.display = old.display.clone();
+        model.clipping_planes = old.clipping_planes.clone();
+    })?;
+    tracing::info!(handle = %handle, filename = %model.filename, "model re-analyzed from source");
+    state.insert(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_status() {
+        let dir = std::env::temp_dir().join(format!("ohmframe-source-{}", std::process::id()));
+        let _ = std::fs::create_dir_all(&dir);
+        let path = dir.join("block.step");
+        std::fs::write(&path, "ISO-10303-21;").unwrap();
+
+        let model = LoadedModel::parse(
+            "model-1".to_string(), "ISO-10303-21;".to_string(), "block.step".to_string(), Some(path.to_string_lossy().into_owned()),
+        );
+        assert_eq!(check_source(&model).status, SourceStatus::Unchanged);
+
+        std::fs::write(&path, "ISO-10303-21; edited").unwrap();
+        let check = check_source(&model);
+        assert_eq!(check.status, SourceStatus::Changed);
+        assert!(check.is_stale());
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(check_source(&model).status, SourceStatus::Missing);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,185 @@
+// What-if sweeps of the target spec or one link's tolerance, reporting Cpk and yield at each step
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::jobs::run_job;
+use crate::tolerance_calc::{cpk, sample_stackup, validate_link, LinkInput, TargetSpec, MAX_MONTE_CARLO_SAMPLES};
+use crate::validation::{Checked, FieldErrors, Validate};
+
+const DEFAULT_SAMPLES: usize = 10_000;
+const MAX_STEPS: usize = 200;
+const DEFAULT_MIN_CPK: f64 = 1.33;
+
+/// Tolerance being swept; the value is applied as ± to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SweepParameter {
+    SpecTolerance,                   // Target spec plus and minus tolerance
+    LinkTolerance { index: usize },  // One link's plus and minus tolerance
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecSweepInput {
+    pub links: Vec<LinkInput>,
+    pub target_spec: TargetSpec,
+    pub parameter: SweepParameter,
+    pub from: f64,
+    pub to: f64,
+    pub steps: usize,                   // Values from `from` to `to` inclusive
+    pub min_cpk: Option<f64>,           // Capability the spec has to reach; default 1.33
+    pub monte_carlo_samples: Option<usize>,
+}
+
+impl Validate for SpecSweepInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.links.is_empty() {
+            errors.add("links", "expected at least one link");
+        }
+        for (i, link) in self.links.iter().enumerate() {
+            validate_link(link, &format!("links[{}]", i), errors);
+        }
+        errors.finite("target_spec.nominal", self.target_spec.nominal);
+        errors.at_least("target_spec.plus_tolerance", self.target_spec.plus_tolerance, 0.0);
+        errors.at_least("target_spec.minus_tolerance", self.target_spec.minus_tolerance, 0.0);
+        if let SweepParameter::LinkTolerance { index } = self.parameter {
+            if index >= self.links.len() {
+                errors.add("parameter.index", format!("expected a link index below {}, got {}", self.links.len(), index));
+            }
+        }
+        errors.at_least("from", self.from, 0.0);
+        errors.at_least("to", self.to, self.from);
+        if self.steps < 2 || self.steps > MAX_STEPS {
+            errors.add("steps", format!("expected 2 to {}, got {}", MAX_STEPS, self.steps));
+        }
+        if let Some(min_cpk) = self.min_cpk {
+            errors.above("min_cpk", min_cpk, 0.0);
+        }
+        if let Some(samples) = self.monte_carlo_samples {
+            if samples == 0 || samples > MAX_MONTE_CARLO_SAMPLES {
+                errors.add("monte_carlo_samples", format!("expected 1 to {}, got {}", MAX_MONTE_CARLO_SAMPLES, samples));
+            }
+        }
+    }
+}
+
+/// Stack capability at one swept value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepPoint {
+    pub value: f64,
+    pub cpk: f64,
+    pub yield_fraction: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpecSweepResult {
+    pub parameter: SweepParameter,
+    pub points: Vec<SweepPoint>,
+    pub min_cpk: f64,
+    pub first_meeting_cpk: Option<f64>,  // Smallest swept value that reaches `min_cpk` (largest for a link)
+}
+
+fn evaluate(totals: &[f64], spec: &TargetSpec, value: f64) -> SweepPoint {
+    let n = totals.len() as f64;
+    let mean = totals.iter().sum::<f64>() / n;
+    let std_dev = (totals.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt();
+    let (low, high) = (spec.nominal - spec.minus_tolerance, spec.nominal + spec.plus_tolerance);
+    let within = totals.iter().filter(|t| **t >= low && **t <= high).count();
+    SweepPoint { value, cpk: cpk(mean, std_dev, spec), yield_fraction: within as f64 / n, mean, std_dev }
+}
+
+/// Cpk and yield at evenly spaced values of the swept tolerance
+pub fn sweep_spec(input: &SpecSweepInput) -> SpecSweepResult {
+    let samples = input.monte_carlo_samples.unwrap_or(DEFAULT_SAMPLES);
+    let values: Vec<f64> = (0..input.steps)
+        .map(|i| input.from + (input.to - input.from) * i as f64 / (input.steps - 1) as f64)
+        .collect();
+    tracing::info!(links = input.links.len(), steps = input.steps, samples, parameter = ?input.parameter, "sweeping stackup spec");
+
+    let points: Vec<SweepPoint> = match input.parameter {
+        SweepParameter::SpecTolerance => {
+            // The stack does not change, only the limits it is judged against
+            let totals = sample_stackup(&input.links, samples);
+            values.iter()
+                .map(|&value| {
+                    let spec = TargetSpec { plus_tolerance: value, minus_tolerance: value, ..input.target_spec.clone() };
+                    evaluate(&totals, &spec, value)
+                })
+                .collect()
+        }
+        SweepParameter::LinkTolerance { index } => values.iter()
+            .map(|&value| {
+                let mut links = input.links.clone();
+                links[index].plus_tolerance = value;
+                links[index].minus_tolerance = value;
+                evaluate(&sample_stackup(&links, samples), &input.target_spec, value)
+            })
+            .collect(),
+    };
+
+    // Opening the spec helps as it grows; a link tolerance helps as it shrinks
+    let min_cpk = input.min_cpk.unwrap_or(DEFAULT_MIN_CPK);
+    let meeting = points.iter().filter(|p| p.cpk >= min_cpk).map(|p| p.value);
+    let first_meeting_cpk = match input.parameter {
+        SweepParameter::SpecTolerance => meeting.reduce(f64::min),
+        SweepParameter::LinkTolerance { .. } => meeting.reduce(f64::max),
+    };
+
+    SpecSweepResult { parameter: input.parameter.clone(), points, min_cpk, first_meeting_cpk }
+}
+
+/// Cpk and yield versus the target spec or one link's tolerance
+#[tauri::command]
+pub async fn sweep_stackup_spec(
+    app: AppHandle,
+    input: Checked<SpecSweepInput>,
+    job_id: Option<String>,
+) -> Result<SpecSweepResult, String> {
+    let input = input.into_inner();
+    run_job(app, "sweep_stackup_spec", job_id, None, move |_| Ok(sweep_spec(&input))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::validate;
+
+    fn link(nominal: f64, tolerance: f64, direction: &str) -> LinkInput {
+        LinkInput {
+            name: None,
+            nominal,
+            plus_tolerance: tolerance,
+            minus_tolerance: tolerance,
+            direction: direction.to_string(),
+            distribution: "normal".to_string(),
+            sigma: Some(3.0),
+            inspected: false,
+        }
+    }
+
+    #[test]
+    fn test_opening_spec_raises_cpk() {
+        // Two links of ±0.3 at 3 sigma: stack sigma ≈ 0.141, so Cpk 1.33 needs about ±0.566
+        let input = SpecSweepInput {
+            links: vec![link(20.0, 0.3, "positive"), link(19.0, 0.3, "negative")],
+            target_spec: TargetSpec { nominal: 1.0, plus_tolerance: 0.1, minus_tolerance: 0.1 },
+            parameter: SweepParameter::SpecTolerance,
+            from: 0.1,
+            to: 1.0,
+            steps: 10,
+            min_cpk: None,
+            monte_carlo_samples: Some(20_000),
+        };
+        assert!(validate(&input).is_ok());
+
+        let result = sweep_spec(&input);
+        assert_eq!(result.points.len(), 10);
+        assert!(result.points.windows(2).all(|w| w[1].cpk > w[0].cpk && w[1].yield_fraction >= w[0].yield_fraction));
+        assert!((result.first_meeting_cpk.unwrap() - 0.6).abs() < 1e-9);
+
+        let bad = SpecSweepInput { parameter: SweepParameter::LinkTolerance { index: 5 }, ..input };
+        assert!(validate(&bad).unwrap_err().contains("parameter.index"));
+    }
+}
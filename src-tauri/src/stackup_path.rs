@@ -0,0 +1,146 @@
+// World-space dimension loop through the interfaces of a stackup chain, for drawing in the viewer
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::interface_detection::DetectedInterface;
+use crate::linalg::{distance, dot, norm, normalize, sub, Vec3};
+use crate::model_store::ModelStore;
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// Interfaces in chain order; neighbours must share a part
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackupPathInput {
+    pub interface_ids: Vec<String>,
+    pub axis: Option<[f64; 3]>,  // Stack direction; the chain's longest extent when absent
+}
+
+impl Validate for StackupPathInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.interface_ids.len() < 2 {
+            errors.add("interface_ids", "expected at least two interfaces");
+        }
+        if let Some(axis) = self.axis {
+            if !axis.iter().all(|c| c.is_finite()) || norm(&axis) < 1e-9 {
+                errors.add("axis", "expected a finite, non-zero vector");
+            }
+        }
+    }
+}
+
+/// One leg of the loop: across a part between two interfaces, or the closing gap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathSegment {
+    pub part_id: Option<String>,  // None for a closing gap that runs through no shared part
+    pub from_interface: String,
+    pub to_interface: String,
+    pub start: [f64; 3],
+    pub end: [f64; 3],
+    pub length: f64,
+    pub axial_length: f64,        // Signed distance along the stack axis
+    pub direction: String,        // "positive" or "negative", as a stackup link
+}
+
+/// Closed polyline through the contact points of the chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StackupPath {
+    pub points: Vec<[f64; 3]>,    // First point repeated at the end
+    pub segments: Vec<PathSegment>,
+    pub axis: [f64; 3],
+}
+
+fn shared_part(a: &DetectedInterface, b: &DetectedInterface) -> Option<String> {
+    [&a.part_a_id, &a.part_b_id].into_iter()
+        .find(|p| **p == b.part_a_id || **p == b.part_b_id)
+        .cloned()
+}
+
+/// Unit axis along which the points spread the most
+fn dominant_axis(points: &[Vec3]) -> Vec3 {
+    let extent = |axis: usize| {
+        let values = points.iter().map(|p| p[axis]);
+        values.clone().fold(f64::NEG_INFINITY, f64::max) - values.fold(f64::INFINITY, f64::min)
+    };
+    let best = (0..3).max_by(|a, b| extent(*a).total_cmp(&extent(*b))).unwrap_or(2);
+    let mut axis = [0.0; 3];
+    axis[best] = 1.0;
+    axis
+}
+
+/// Polyline through the interfaces in order, closed back to the first
+pub fn build_stackup_path(interfaces: &[&DetectedInterface], axis: Option<Vec3>) -> Result<StackupPath, String> {
+    for pair in interfaces.windows(2) {
+        if shared_part(pair[0], pair[1]).is_none() {
+            return Err(format!("Interfaces {} and {} share no part", pair[0].id, pair[1].id));
+        }
+    }
+
+    let mut points: Vec<Vec3> = interfaces.iter().map(|i| i.contact_point).collect();
+    let axis = normalize(&axis.unwrap_or_else(|| dominant_axis(&points)));
+    let n = interfaces.len();
+    let segments = (0..n)
+        .map(|k| {
+            let (from, to) = (interfaces[k], interfaces[(k + 1) % n]);
+            let axial_length = dot(&sub(&to.contact_point, &from.contact_point), &axis);
+            PathSegment {
+                part_id: shared_part(from, to),
+                from_interface: from.id.clone(),
+                to_interface: to.id.clone(),
+                start: from.contact_point,
+                end: to.contact_point,
+                length: distance(&from.contact_point, &to.contact_point),
+                axial_length,
+                direction: if axial_length < 0.0 { "negative" } else { "positive" }.to_string(),
+            }
+        })
+        .collect();
+    points.push(points[0]);
+
+    Ok(StackupPath { points, segments, axis })
+}
+
+/// Dimension loop for a chain of interfaces of a loaded model
+#[tauri::command]
+pub fn get_stackup_path(
+    state: State<'_, ModelStore>,
+    handle: String,
+    input: Checked<StackupPathInput>,
+) -> Result<StackupPath, String> {
+    state.with_model(&handle, |model| {
+        let stored = model.interfaces.as_ref().map(|r| r.interfaces.as_slice()).unwrap_or_default();
+        let chain = input.interface_ids.iter()
+            .map(|id| stored.iter().find(|i| i.id == *id).ok_or_else(|| format!("Unknown interface: {}", id)))
+            .collect::<Result<Vec<_>, _>>()?;
+        build_stackup_path(&chain, input.axis)
+    })?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::interface;
+
+    #[test]
+    fn test_loop_through_housing_and_shaft() {
+        // Housing seats a bearing at z=0 and a cover at z=30; the shaft spans the bearing to the cover gap
+        let chain = [
+            interface("interface-1", "housing", "bearing", [0.0, 0.0, 0.0]),
+            interface("interface-2", "bearing", "shaft", [0.0, 0.0, 10.0]),
+            interface("interface-3", "shaft", "cover", [0.0, 2.0, 29.5]),
+            interface("interface-4", "cover", "housing", [0.0, 5.0, 30.0]),
+        ];
+        let refs: Vec<&DetectedInterface> = chain.iter().collect();
+        let path = build_stackup_path(&refs, None).unwrap();
+        assert_eq!(path.axis, [0.0, 0.0, 1.0]);
+        assert_eq!(path.points.len(), 5);
+        assert_eq!(path.points[4], path.points[0]);
+        assert_eq!(path.segments[1].part_id.as_deref(), Some("shaft"));
+        assert_eq!(path.segments[3].part_id.as_deref(), Some("housing"));
+        assert_eq!(path.segments[3].direction, "negative");
+        let closure: f64 = path.segments.iter().map(|s| s.axial_length).sum();
+        assert!(closure.abs() < 1e-12);
+
+        let broken = [&chain[0], &chain[2]];
+        assert!(build_stackup_path(&broken, None).unwrap_err().contains("share no part"));
+    }
+}
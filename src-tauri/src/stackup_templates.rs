@@ -0,0 +1,347 @@
+// Stackup templates for common chains, with placeholder links bound to model features on use
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::State;
+
+use crate::features::model_holes;
+use crate::model_store::{measure_between, FaceRef, LoadedModel, ModelStore};
+use crate::storage::{load_json, save_json};
+use crate::tolerance_calc::{LinkInput, TargetSpec, ToleranceInput};
+use crate::validation::{validate, FieldErrors, Validate};
+
+/// What a placeholder link should be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlaceholderKind {
+    FaceDistance,  // Separation of two parallel planar faces
+    HoleDiameter,
+    InterfaceGap,
+    Value,         // Entered by hand, e.g. a bought-in part's width
+}
+
+/// Link of a template, nominal left open until bound
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateLink {
+    pub placeholder: String,
+    pub description: String,
+    pub kind: PlaceholderKind,
+    pub direction: String,
+    pub distribution: String,
+    pub plus_tolerance: f64,
+    pub minus_tolerance: f64,
+    #[serde(default)]
+    pub default_nominal: Option<f64>,  // Used when the placeholder is not bound
+}
+
+/// Reusable stackup chain
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StackupTemplate {
+    pub name: String,
+    pub description: String,
+    pub links: Vec<TemplateLink>,
+    pub target_spec: Option<TargetSpec>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+impl Validate for StackupTemplate {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.name.trim().is_empty() {
+            errors.add("name", "expected a template name");
+        }
+        if self.links.is_empty() {
+            errors.add("links", "expected at least one link");
+        }
+        for (i, link) in self.links.iter().enumerate() {
+            let prefix = format!("links[{}]", i);
+            if link.placeholder.trim().is_empty() {
+                errors.add(format!("{}.placeholder", prefix), "expected a placeholder name");
+            } else if self.links[..i].iter().any(|l| l.placeholder == link.placeholder) {
+                errors.add(format!("{}.placeholder", prefix), format!("duplicate placeholder \"{}\"", link.placeholder));
+            }
+            errors.one_of(format!("{}.direction", prefix), &link.direction, &["positive", "negative"]);
+            errors.one_of(format!("{}.distribution", prefix), &link.distribution, &["normal", "uniform"]);
+            errors.at_least(format!("{}.plus_tolerance", prefix), link.plus_tolerance, 0.0);
+            errors.at_least(format!("{}.minus_tolerance", prefix), link.minus_tolerance, 0.0);
+        }
+    }
+}
+
+/// Feature a placeholder is bound to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FeatureBinding {
+    FaceDistance { face_a: FaceRef, face_b: FaceRef },
+    HoleDiameter { hole_id: String },
+    InterfaceGap { interface_id: String },
+    Value { nominal: f64 },
+}
+
+/// Template turned into a stackup input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateInstance {
+    pub template: String,
+    pub input: ToleranceInput,
+    pub defaulted: Vec<String>,  // Placeholders that took the template's default nominal
+}
+
+fn link(placeholder: &str, description: &str, kind: PlaceholderKind, direction: &str, tolerance: f64) -> TemplateLink {
+    TemplateLink {
+        placeholder: placeholder.to_string(),
+        description: description.to_string(),
+        kind,
+        direction: direction.to_string(),
+        distribution: "normal".to_string(),
+        plus_tolerance: tolerance,
+        minus_tolerance: tolerance,
+        default_nominal: None,
+    }
+}
+
+/// Templates shipped with the app
+pub fn builtin_templates() -> Vec<StackupTemplate> {
+    use PlaceholderKind::*;
+    vec![
+        StackupTemplate {
+            name: "Bearing axial stack".to_string(),
+            description: "End play of a bearing clamped between a housing shoulder and a cover spigot".to_string(),
+            links: vec![
+                link("housing_depth", "Housing shoulder to cover seating face", FaceDistance, "positive", 0.05),
+                link("cover_spigot", "Cover spigot length into the housing", FaceDistance, "negative", 0.05),
+                TemplateLink {
+                    default_nominal: Some(15.0),
+                    ..link("bearing_width", "Bearing width (catalogue)", Value, "negative", 0.12)
+                },
+                link("spacer_width", "Spacer or shim width", Value, "negative", 0.02),
+            ],
+            target_spec: Some(TargetSpec { nominal: 0.15, plus_tolerance: 0.15, minus_tolerance: 0.1 }),
+            builtin: true,
+        },
+        StackupTemplate {
+            name: "Bolted flange gap".to_string(),
+            description: "Gap between bolted flanges when the gasket seats before the flanges touch".to_string(),
+            links: vec![
+                link("housing_pilot", "Housing face to pilot shoulder", FaceDistance, "positive", 0.05),
+                link("cover_pilot", "Cover pilot depth", FaceDistance, "negative", 0.05),
+                TemplateLink {
+                    default_nominal: Some(1.0),
+                    ..link("gasket_thickness", "Compressed gasket thickness", Value, "negative", 0.1)
+                },
+            ],
+            target_spec: Some(TargetSpec { nominal: 0.0, plus_tolerance: 0.3, minus_tolerance: 0.0 }),
+            builtin: true,
+        },
+        StackupTemplate {
+            name: "Pin in hole clearance".to_string(),
+            description: "Diametral clearance of a pin in a hole".to_string(),
+            links: vec![
+                link("hole_diameter", "Hole diameter", HoleDiameter, "positive", 0.01),
+                link("pin_diameter", "Pin diameter", Value, "negative", 0.005),
+            ],
+            target_spec: Some(TargetSpec { nominal: 0.02, plus_tolerance: 0.03, minus_tolerance: 0.02 }),
+            builtin: true,
+        },
+    ]
+}
+
+/// Nominal of a bound feature in the model
+fn resolve_binding(model: Option<&LoadedModel>, binding: &FeatureBinding) -> Result<f64, String> {
+    let model = || model.ok_or_else(|| "Binding to model features needs a model handle".to_string());
+    match binding {
+        FeatureBinding::Value { nominal } => Ok(*nominal),
+        FeatureBinding::FaceDistance { face_a, face_b } => {
+            let model = model()?;
+            let measurement = measure_between(&model.world_face(face_a)?, &model.world_face(face_b)?);
+            measurement.plane_separation
+                .ok_or_else(|| format!("Faces {} and {} are not parallel planes", face_a.face_id, face_b.face_id))
+        }
+        FeatureBinding::HoleDiameter { hole_id } => model_holes(model()?).into_iter()
+            .find(|h| h.id == *hole_id)
+            .map(|h| h.diameter)
+            .ok_or_else(|| format!("Unknown hole: {}", hole_id)),
+        FeatureBinding::InterfaceGap { interface_id } => model()?.interfaces.as_ref()
+            .and_then(|r| r.interfaces.iter().find(|i| i.id == *interface_id))
+            .map(|i| i.proximity)
+            .ok_or_else(|| format!("Unknown interface: {}", interface_id)),
+    }
+}
+
+/// Stackup input from a template; every placeholder needs a binding or a default nominal
+pub fn instantiate(
+    template: &StackupTemplate,
+    model: Option<&LoadedModel>,
+    bindings: &HashMap<String, FeatureBinding>,
+) -> Result<TemplateInstance, String> {
+    if let Some(unknown) = bindings.keys().find(|k| !template.links.iter().any(|l| l.placeholder == **k)) {
+        return Err(format!("Template {} has no placeholder {}", template.name, unknown));
+    }
+
+    let mut links = Vec::new();
+    let mut defaulted = Vec::new();
+    let mut missing = Vec::new();
+    for link in &template.links {
+        let nominal = match (bindings.get(&link.placeholder), link.default_nominal) {
+            (Some(binding), _) => resolve_binding(model, binding)
+                .map_err(|e| format!("{}: {}", link.placeholder, e))?,
+            (None, Some(nominal)) => {
+                defaulted.push(link.placeholder.clone());
+                nominal
+            }
+            (None, None) => {
+                missing.push(link.placeholder.clone());
+                continue;
+            }
+        };
+        links.push(LinkInput {
+            name: Some(link.placeholder.clone()),
+            nominal,
+            plus_tolerance: link.plus_tolerance,
+            minus_tolerance: link.minus_tolerance,
+            direction: link.direction.clone(),
+            distribution: link.distribution.clone(),
+            sigma: None,
+            inspected: false,
+        });
+    }
+    if !missing.is_empty() {
+        return Err(format!("Unbound placeholders: {}", missing.join(", ")));
+    }
+
+    Ok(TemplateInstance {
+        template: template.name.clone(),
+        input: ToleranceInput {
+            links,
+            monte_carlo_samples: None,
+            target_spec: template.target_spec.clone(),
+            gauge_sigma: None,
+            adjuster: None,
+        },
+        defaulted,
+    })
+}
+
+/// Managed directory of user templates
+pub struct StackupTemplateStore {
+    dir: PathBuf,
+}
+
+impl StackupTemplateStore {
+    pub fn new(dir: PathBuf) -> Self {
+        StackupTemplateStore { dir }
+    }
+
+    fn path_for(&self, name: &str) -> PathBuf {
+        let file: String = name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", file))
+    }
+
+    fn user_templates(&self) -> Vec<StackupTemplate> {
+        std::fs::read_dir(&self.dir)
+            .map(|entries| {
+                entries.filter_map(|e| e.ok())
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                    .map(|p| load_json::<StackupTemplate>(&p))
+                    .filter(|t| !t.name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Built-in templates followed by user templates; a user template replaces a built-in of the same name
+    fn list(&self) -> Vec<StackupTemplate> {
+        let mut user = self.user_templates();
+        user.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut templates: Vec<StackupTemplate> = builtin_templates().into_iter()
+            .filter(|b| !user.iter().any(|u| u.name == b.name))
+            .collect();
+        templates.extend(user);
+        templates
+    }
+
+    fn find(&self, name: &str) -> Result<StackupTemplate, String> {
+        self.list().into_iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| format!("Unknown stackup template: {}", name))
+    }
+}
+
+/// Built-in and saved stackup templates
+#[tauri::command]
+pub fn list_stackup_templates(store: State<'_, StackupTemplateStore>) -> Vec<StackupTemplate> {
+    store.list()
+}
+
+/// Save (or replace) a user stackup template
+#[tauri::command]
+pub fn save_stackup_template(store: State<'_, StackupTemplateStore>, template: StackupTemplate) -> Result<(), String> {
+    validate(&template)?;
+    std::fs::create_dir_all(&store.dir)
+        .map_err(|e| format!("Failed to create template directory: {}", e))?;
+    save_json(&store.path_for(&template.name), &StackupTemplate { builtin: false, ..template.clone() })?;
+    tracing::info!(template = %template.name, "stackup template saved");
+    Ok(())
+}
+
+/// Delete a user stackup template; built-in templates cannot be deleted
+#[tauri::command]
+pub fn delete_stackup_template(store: State<'_, StackupTemplateStore>, name: String) -> Result<bool, String> {
+    let path = store.path_for(&name);
+    if !path.exists() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to delete template: {}", e))?;
+    Ok(true)
+}
+
+/// Build a stackup from a template, binding placeholders to features of a loaded model
+#[tauri::command]
+pub fn instantiate_stackup_template(
+    store: State<'_, StackupTemplateStore>,
+    models: State<'_, ModelStore>,
+    name: String,
+    handle: Option<String>,
+    bindings: HashMap<String, FeatureBinding>,
+) -> Result<TemplateInstance, String> {
+    let template = store.find(&name)?;
+    match handle {
+        Some(handle) => models.with_model(&handle, |model| instantiate(&template, Some(model), &bindings))?,
+        None => instantiate(&template, None, &bindings),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtins_are_valid() {
+        for template in builtin_templates() {
+            assert!(validate(&template).is_ok(), "{}", template.name);
+        }
+    }
+
+    #[test]
+    fn test_instantiate_with_values_and_defaults() {
+        let template = builtin_templates().remove(0);
+        let mut bindings = HashMap::new();
+        bindings.insert("housing_depth".to_string(), FeatureBinding::Value { nominal: 40.0 });
+        bindings.insert("cover_spigot".to_string(), FeatureBinding::Value { nominal: 5.0 });
+        let err = instantiate(&template, None, &bindings).unwrap_err();
+        assert_eq!(err, "Unbound placeholders: spacer_width");
+
+        bindings.insert("spacer_width".to_string(), FeatureBinding::Value { nominal: 19.85 });
+        let instance = instantiate(&template, None, &bindings).unwrap();
+        assert_eq!(instance.defaulted, vec!["bearing_width".to_string()]);
+        assert_eq!(instance.input.links.len(), 4);
+        assert_eq!(instance.input.links[2].nominal, 15.0);
+
+        let hole = FeatureBinding::HoleDiameter { hole_id: "part-1-hole-1".to_string() };
+        bindings.insert("spacer_width".to_string(), hole);
+        assert!(instantiate(&template, None, &bindings).unwrap_err().contains("needs a model handle"));
+    }
+}
@@ -0,0 +1,270 @@
+// Sanitized STEP copies for support requests and AI prompts: identifying text is replaced and
+// freeform geometry can be jittered
+
+use once_cell::sync::Lazy;
+use rand::Rng;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::AppHandle;
+
+use crate::jobs::run_job;
+use crate::step_entities::StepEntities;
+use crate::step_patterns::STEP_STRING;
+use crate::step_tokenizer::records;
+
+/// Entities whose strings describe people, companies, documents or approvals
+const CLEARED_TYPES: &[&str] = &[
+    "PERSON",
+    "ORGANIZATION",
+    "ADDRESS",
+    "PERSONAL_ADDRESS",
+    "ORGANIZATIONAL_ADDRESS",
+    "DOCUMENT",
+    "DOCUMENT_FILE",
+    "SECURITY_CLASSIFICATION",
+    "APPROVAL",
+    "PRODUCT_DEFINITION",
+    "PRODUCT_DEFINITION_FORMATION",
+    "PRODUCT_DEFINITION_FORMATION_WITH_SPECIFIED_SOURCE",
+];
+
+/// Freeform curves and surfaces; their interior control points are safe to jitter
+const BSPLINE_TYPES: &[&str] = &[
+    "B_SPLINE_CURVE",
+    "B_SPLINE_CURVE_WITH_KNOTS",
+    "B_SPLINE_SURFACE",
+    "B_SPLINE_SURFACE_WITH_KNOTS",
+];
+
+/// Parenthesized list of references only: a control point row
+static REFERENCE_LIST: Lazy<Regex> = Lazy::new(|| Regex::new(r"\(\s*(#\d+(?:\s*,\s*#\d+)*)\s*\)").unwrap());
+
+static FILE_NAME: Lazy<Regex> = Lazy::new(|| Regex::new(r"FILE_NAME\s*\(([^;]*)\)\s*;").unwrap());
+
+static FILE_DESCRIPTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"FILE_DESCRIPTION\s*\(\s*\(([^)]*)\)").unwrap());
+
+/// Anonymization settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnonymizeOptions {
+    pub jitter_mm: Option<f64>,  // Move interior B-spline control points by up to this much per axis
+}
+
+/// Sanitized STEP text and what was changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnonymizeResult {
+    pub content: String,
+    pub products_renamed: usize,  // PRODUCT entities now named PART-001, PART-002, ...
+    pub strings_cleared: usize,
+    pub points_jittered: usize,
+}
+
+/// STEP real: always with a decimal point, trailing zeros dropped
+fn step_real(value: f64) -> String {
+    let text = format!("{:.9}", value);
+    let text = text.trim_end_matches('0');
+    if text == "-0." { "0.".to_string() } else { text.to_string() }
+}
+
+/// Control points strictly inside each B-spline, skipping points any other entity also uses
+fn jitter_candidates(entities: &StepEntities) -> Vec<i64> {
+    let pinned: HashSet<i64> = entities.iter()
+        .filter(|e| !BSPLINE_TYPES.contains(&e.entity_type))
+        .flat_map(|e| e.references())
+        .collect();
+
+    let mut candidates = Vec::new();
+    for spline in entities.iter().filter(|e| BSPLINE_TYPES.contains(&e.entity_type)) {
+        let rows: Vec<Vec<i64>> = REFERENCE_LIST.captures_iter(spline.data)
+            .map(|c| c[1].split(',').filter_map(|r| r.trim().trim_start_matches('#').parse().ok()).collect())
+            .collect();
+        let interior = |len: usize| 1..len.saturating_sub(1).max(1);
+        let ids: Vec<i64> = match rows.len() {
+            0 => Vec::new(),
+            1 => rows[0][interior(rows[0].len())].to_vec(),
+            n => rows[interior(n)].iter().flat_map(|row| row[interior(row.len())].iter().copied()).collect(),
+        };
+        candidates.extend(ids.into_iter().filter(|id| {
+            !pinned.contains(id) && entities.get_typed(*id, "CARTESIAN_POINT").is_some()
+        }));
+    }
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+/// Replace identifying strings and optionally jitter freeform geometry
+pub fn anonymize_step_text(content: &str, options: &AnonymizeOptions) -> AnonymizeResult {
+    let entities = StepEntities::parse(content);
+    let mut strings_cleared = 0;
+
+    // Product ids and names become generic ids, also where other entities repeat them
+    let mut generic_names: Vec<(String, String)> = Vec::new();
+    let mut product_ids: HashMap<i64, String> = HashMap::new();
+    for (n, product) in entities.of_type("PRODUCT").enumerate() {
+        let generic = format!("PART-{:03}", n + 1);
+        for original in STEP_STRING.find_iter(product.data).take(2) {
+            let original = original.as_str().trim_matches('\'');
+            if !original.is_empty() {
+                generic_names.push((original.to_string(), generic.clone()));
+            }
+        }
+        product_ids.insert(product.id, generic);
+    }
+    generic_names.sort_by_key(|name| std::cmp::Reverse(name.0.len()));
+
+    let mut rng = rand::thread_rng();
+    let jittered: HashMap<i64, [f64; 3]> = match options.jitter_mm.filter(|a| *a > 0.0) {
+        Some(amplitude) => jitter_candidates(&entities).into_iter()
+            .filter_map(|id| {
+                let point = entities.get(id)?.triple()?;
+                Some((id, point.map(|c| c + rng.gen_range(-amplitude..=amplitude))))
+            })
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    let (header, data) = content.split_at(content.find("DATA;").unwrap_or(0));
+    let header = FILE_NAME.replace_all(header, |cap: &Captures| {
+        strings_cleared += STEP_STRING.find_iter(&cap[1]).count();
+        format!("FILE_NAME({});", STEP_STRING.replace_all(&cap[1], "''"))
+    });
+    let header = FILE_DESCRIPTION.replace_all(&header, |cap: &Captures| {
+        strings_cleared += STEP_STRING.find_iter(&cap[1]).count();
+        format!("FILE_DESCRIPTION(({})", STEP_STRING.replace_all(&cap[1], "''"))
+    });
+
+    let mut occurrences = 0;
+    let mut rewritten_data = String::with_capacity(data.len());
+    let mut copied = 0;
+    for record in records(data) {
+        // Complex instances (units, contexts, rational splines) carry no identifying text
+        let [(entity_type, params)] = record.partials[..] else { continue };
+        let id = record.id;
+        let rewritten = if let Some(generic) = product_ids.get(&id) {
+            let mut index = 0;
+            STEP_STRING.replace_all(params, |_: &Captures| {
+                index += 1;
+                if index <= 2 { format!("'{}'", generic) } else { "''".to_string() }
+            }).into_owned()
+        } else if entity_type == "NEXT_ASSEMBLY_USAGE_OCCURRENCE" {
+            occurrences += 1;
+            let mut index = 0;
+            STEP_STRING.replace_all(params, |_: &Captures| {
+                index += 1;
+                if index == 1 { format!("'OCC-{}'", occurrences) } else { "''".to_string() }
+            }).into_owned()
+        } else if CLEARED_TYPES.contains(&entity_type) {
+            strings_cleared += STEP_STRING.find_iter(params).filter(|s| s.as_str() != "''").count();
+            STEP_STRING.replace_all(params, "''").into_owned()
+        } else if let Some(point) = jittered.get(&id) {
+            let name = STEP_STRING.find(params).map(|s| s.as_str()).unwrap_or("''");
+            format!("{},({},{},{})", name, step_real(point[0]), step_real(point[1]), step_real(point[2]))
+        } else {
+            STEP_STRING.replace_all(params, |s: &Captures| {
+                let mut text = s[0].to_string();
+                for (original, generic) in &generic_names {
+                    text = text.replace(original.as_str(), generic);
+                }
+                text
+            }).into_owned()
+        };
+        rewritten_data.push_str(&data[copied..record.start]);
+        rewritten_data.push_str(&format!("#{}={}({});", id, entity_type, rewritten));
+        copied = record.end;
+    }
+    rewritten_data.push_str(&data[copied..]);
+
+    tracing::info!(products = product_ids.len(), strings_cleared, jittered = jittered.len(), "STEP anonymized");
+    AnonymizeResult {
+        content: format!("{}{}", header, rewritten_data),
+        products_renamed: product_ids.len(),
+        strings_cleared,
+        points_jittered: jittered.len(),
+    }
+}
+
+/// Sanitized copy of STEP content that is safe to share outside the company
+#[tauri::command]
+pub async fn anonymize_step(
+    app: AppHandle,
+    content: String,
+    options: Option<AnonymizeOptions>,
+    job_id: Option<String>,
+) -> Result<AnonymizeResult, String> {
+    let options = options.unwrap_or_default();
+    if let Some(jitter) = options.jitter_mm {
+        if !jitter.is_finite() || jitter < 0.0 {
+            return Err(format!("jitter_mm: expected a value >= 0, got {}", jitter));
+        }
+    }
+    run_job(app, "anonymize_step", job_id, None, move |_| Ok(anonymize_step_text(&content, &options))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('Acme bracket for Project X'),'2;1');
+FILE_NAME('acme_bracket.step','2024-05-01T10:00:00',('Jane O''Neil'),('Acme Corp'),'','CAD 2024','');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+#1=PRODUCT('ACME-4711','Acme Bracket','Rev B for Project X',(#2));
+#2=PRODUCT_CONTEXT('',#3,'mechanical');
+#4=PERSON('jdoe','Doe','Jane',$,$,$);
+#5=ORGANIZATION('','Acme Corp','');
+#6=MANIFOLD_SOLID_BREP('Acme Bracket solid',#7);
+#7=CLOSED_SHELL('',(#8));
+#10=CARTESIAN_POINT('',(0.,0.,0.));
+#11=CARTESIAN_POINT('',(1.,1.,0.));
+#12=CARTESIAN_POINT('',(2.,1.,0.));
+#13=CARTESIAN_POINT('',(3.,0.,0.));
+#14=B_SPLINE_CURVE_WITH_KNOTS('',3,(#10,#11,#12,#13),.UNSPECIFIED.,.F.,.F.,(4,4),(0.,1.),.UNSPECIFIED.);
+#15=VERTEX_POINT('',#10);
+#16=NEXT_ASSEMBLY_USAGE_OCCURRENCE('Acme Bracket:1','Acme Bracket','',#20,#21,$);
+ENDSEC;
+END-ISO-10303-21;";
+
+    #[test]
+    fn test_identifying_text_removed() {
+        let result = anonymize_step_text(CONTENT, &AnonymizeOptions::default());
+        for secret in ["Acme", "ACME", "Jane", "Neil", "Project X", "Doe"] {
+            assert!(!result.content.contains(secret), "{} leaked:\n{}", secret, result.content);
+        }
+        assert!(result.content.contains("#1=PRODUCT('PART-001','PART-001','',(#2));"));
+        assert!(result.content.contains("#6=MANIFOLD_SOLID_BREP('PART-001 solid',#7);"));
+        assert!(result.content.contains("#16=NEXT_ASSEMBLY_USAGE_OCCURRENCE('OCC-1','',''"));
+        assert!(result.content.contains("#2=PRODUCT_CONTEXT('',#3,'mechanical');"));
+        assert!(result.content.contains("FILE_DESCRIPTION((''),'2;1');"));
+        assert_eq!(result.products_renamed, 1);
+        assert_eq!(result.points_jittered, 0);
+        assert_eq!(StepEntities::parse(&result.content).len(), StepEntities::parse(CONTENT).len());
+    }
+
+    #[test]
+    fn test_jitter_moves_interior_control_points_only() {
+        let result = anonymize_step_text(CONTENT, &AnonymizeOptions { jitter_mm: Some(0.05) });
+        assert_eq!(result.points_jittered, 2);
+
+        let entities = StepEntities::parse(&result.content);
+        let point = |id: i64| entities.get(id).unwrap().triple().unwrap();
+        assert_eq!(point(10), [0.0, 0.0, 0.0]);
+        assert_eq!(point(13), [3.0, 0.0, 0.0]);
+        for (id, original) in [(11, [1.0, 1.0, 0.0]), (12, [2.0, 1.0, 0.0])] {
+            let moved = point(id);
+            assert!((0..3).all(|i| (moved[i] - original[i]).abs() <= 0.05 + 1e-9));
+        }
+    }
+
+    #[test]
+    fn test_step_real() {
+        assert_eq!(step_real(2.0), "2.");
+        assert_eq!(step_real(-0.125), "-0.125");
+        assert_eq!(step_real(0.0), "0.");
+        assert_eq!(step_real(-0.0000000001), "0.");
+    }
+}
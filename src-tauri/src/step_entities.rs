@@ -0,0 +1,237 @@
+// Arena of STEP entity records borrowed from the file content, with typed field access
+
+use std::borrow::Cow;
+
+use crate::step_patterns::COORDINATE_TRIPLE;
+use crate::step_tokenizer::{records, references, tokenize, unescape, Spanned, Token};
+
+/// One `#id=TYPE(...);` record, or one partial type of a complex instance; type and parameters
+/// point into the source text
+#[derive(Debug, Clone, Copy)]
+pub struct StepEntity<'a> {
+    pub id: i64,
+    pub entity_type: &'a str,
+    pub data: &'a str,
+}
+
+impl<'a> StepEntity<'a> {
+    /// Ids referenced by the parameters, in order
+    pub fn references(&self) -> impl Iterator<Item = i64> + 'a {
+        references(self.data)
+    }
+
+    /// First string parameter, unescaped
+    pub fn quoted(&self) -> Option<Cow<'a, str>> {
+        tokenize(self.data).find_map(|token| match token {
+            Ok(Spanned { token: Token::String(text), .. }) => Some(unescape(text)),
+            _ => None,
+        })
+    }
+
+    /// First parenthesized (x, y, z) triple, as in CARTESIAN_POINT and DIRECTION
+    pub fn triple(&self) -> Option<[f64; 3]> {
+        let cap = COORDINATE_TRIPLE.captures(self.data)?;
+        Some([cap[1].parse().ok()?, cap[2].parse().ok()?, cap[3].parse().ok()?])
+    }
+
+    /// Top-level parameters in schema order, split outside nested lists and strings
+    pub fn params(&self) -> impl Iterator<Item = &'a str> + 'a {
+        let data = self.data;
+        let bytes = data.as_bytes();
+        let (mut start, mut depth, mut in_string, mut i) = (0, 0usize, false, 0);
+        std::iter::from_fn(move || {
+            if start > bytes.len() {
+                return None;
+            }
+            while i < bytes.len() {
+                match bytes[i] {
+                    b'\'' => in_string = !in_string,
+                    b'(' if !in_string => depth += 1,
+                    b')' if !in_string => depth = depth.saturating_sub(1),
+                    b',' if !in_string && depth == 0 => {
+                        let param = data[start..i].trim();
+                        i += 1;
+                        start = i;
+                        return Some(param);
+                    }
+                    _ => {}
+                }
+                i += 1;
+            }
+            let param = data[start..].trim();
+            start = bytes.len() + 1;
+            Some(param)
+        })
+    }
+
+    /// Parameter at a schema position; None when out of range or unset (`$`)
+    pub fn param(&self, index: usize) -> Option<&'a str> {
+        self.params().nth(index).filter(|p| *p != "$" && !p.is_empty())
+    }
+
+    /// Entity reference at a schema position
+    pub fn param_ref(&self, index: usize) -> Option<i64> {
+        self.param(index)?.strip_prefix('#')?.trim().parse().ok()
+    }
+
+    /// Real or integer at a schema position, sign and exponent included
+    pub fn param_real(&self, index: usize) -> Option<f64> {
+        self.param(index)?.parse().ok().filter(|v: &f64| v.is_finite())
+    }
+
+    /// .T./.F. at a schema position
+    pub fn param_flag(&self, index: usize) -> Option<bool> {
+        match self.param(index)? {
+            ".T." => Some(true),
+            ".F." => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// All entities of a file, sorted by id for lookup without a hash map. A complex instance is
+/// stored as one entity per partial type, all under the instance id and in file order
+#[derive(Debug, Default)]
+pub struct StepEntities<'a> {
+    entities: Vec<StepEntity<'a>>,
+    instances: usize,
+}
+
+impl<'a> StepEntities<'a> {
+    pub fn parse(content: &'a str) -> Self {
+        let mut instances: Vec<(i64, Vec<(&'a str, &'a str)>)> = records(content)
+            .map(|record| (record.id, record.partials))
+            .collect();
+
+        // Exporters almost always write ids in ascending order, so this is usually a no-op
+        if !instances.windows(2).all(|w| w[0].0 <= w[1].0) {
+            instances.sort_by_key(|i| i.0);
+        }
+        instances.dedup_by_key(|i| i.0);
+
+        let entities = instances.iter()
+            .flat_map(|(id, partials)| {
+                partials.iter().map(move |&(entity_type, data)| StepEntity { id: *id, entity_type, data })
+            })
+            .collect();
+        StepEntities { entities, instances: instances.len() }
+    }
+
+    /// Number of instances, counting a complex instance once
+    pub fn len(&self) -> usize {
+        self.instances
+    }
+
+    /// Entity with an id; the first partial type of a complex instance
+    pub fn get(&self, id: i64) -> Option<&StepEntity<'a>> {
+        self.instance(id).first()
+    }
+
+    /// Every partial type of an instance, a single entity for simple instances
+    pub fn instance(&self, id: i64) -> &[StepEntity<'a>] {
+        let start = self.entities.partition_point(|e| e.id < id);
+        let end = start + self.entities[start..].partition_point(|e| e.id == id);
+        &self.entities[start..end]
+    }
+
+    /// Referenced entity, only when it has the given type (or a complex instance has it as a partial)
+    pub fn get_typed(&self, id: i64, entity_type: &str) -> Option<&StepEntity<'a>> {
+        self.instance(id).iter().find(|e| e.entity_type == entity_type)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StepEntity<'a>> {
+        self.entities.iter()
+    }
+
+    pub fn of_type<'s>(&'s self, entity_type: &'s str) -> impl Iterator<Item = &'s StepEntity<'a>> {
+        self.entities.iter().filter(move |e| e.entity_type == entity_type)
+    }
+
+    /// Entities referenced by `entity`, skipping dangling ids
+    pub fn referenced<'s>(&'s self, entity: &StepEntity<'a>) -> impl Iterator<Item = &'s StepEntity<'a>> {
+        entity.references().filter_map(move |id| self.get(id))
+    }
+
+    /// Ids referenced by any partial type of an instance
+    pub fn instance_references(&self, id: i64) -> impl Iterator<Item = i64> + '_ {
+        self.instance(id).iter().flat_map(|e| e.references())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_and_typed_fields() {
+        let content = "\
+#12=DIRECTION('',(0.,0.,1.));
+#10=CARTESIAN_POINT('origin',(1.5,-2.,3.E1));
+#11=AXIS2_PLACEMENT_3D('',#10,#12,$);
+#20=CYLINDRICAL_SURFACE('',#11,4.25);
+#30=ADVANCED_FACE('',(#40),#20,.F.);";
+        let entities = StepEntities::parse(content);
+
+        assert_eq!(entities.len(), 5);
+        assert_eq!(entities.iter().map(|e| e.id).collect::<Vec<_>>(), vec![10, 11, 12, 20, 30]);
+
+        let point = entities.get(10).unwrap();
+        assert_eq!(point.quoted().as_deref(), Some("origin"));
+        assert_eq!(point.triple(), Some([1.5, -2.0, 30.0]));
+
+        let placement = entities.get_typed(11, "AXIS2_PLACEMENT_3D").unwrap();
+        let types: Vec<&str> = entities.referenced(placement).map(|e| e.entity_type).collect();
+        assert_eq!(types, vec!["CARTESIAN_POINT", "DIRECTION"]);
+        assert!(entities.get_typed(11, "PLANE").is_none());
+
+        assert_eq!(entities.get(20).unwrap().param_real(2), Some(4.25));
+        assert_eq!(entities.get(30).unwrap().param_flag(3), Some(false));
+        assert!(entities.get(99).is_none());
+    }
+
+    #[test]
+    fn test_params_by_position() {
+        let content = "\
+#1=AXIS2_PLACEMENT_3D('a,(b)',#2,$,#4);
+#5=CYLINDRICAL_SURFACE('',#1,-2.5E-1);
+#6=ADVANCED_FACE('',(#7,#8),#5,.F.);";
+        let entities = StepEntities::parse(content);
+
+        let placement = entities.get(1).unwrap();
+        assert_eq!(placement.params().collect::<Vec<_>>(), vec!["'a,(b)'", "#2", "$", "#4"]);
+        assert_eq!(placement.param_ref(1), Some(2));
+        assert_eq!(placement.param_ref(2), None);
+        assert_eq!(placement.param_ref(3), Some(4));
+
+        assert_eq!(entities.get(5).unwrap().param_real(2), Some(-0.25));
+        let face = entities.get(6).unwrap();
+        assert_eq!(face.param_ref(2), Some(5));
+        assert_eq!(face.param_flag(3), Some(false));
+        assert_eq!(face.param(4), None);
+    }
+
+    #[test]
+    fn test_complex_instances_and_split_records() {
+        let content = "\
+#1=PRODUCT('Arm; left','Arm',
+  '',(#2));
+#4=(BOUNDED_CURVE()B_SPLINE_CURVE(1,(#5,#6),.UNSPECIFIED.,.F.,.F.)CURVE()RATIONAL_B_SPLINE_CURVE((1.,1.)));
+#5=CARTESIAN_POINT('',(0.,0.,0.));
+#6=CARTESIAN_POINT('',(1.,0.,0.));";
+        let entities = StepEntities::parse(content);
+
+        assert_eq!(entities.len(), 4);
+        assert_eq!(entities.get(1).unwrap().params().nth(1), Some("'Arm'"));
+        assert_eq!(entities.get(1).unwrap().quoted().as_deref(), Some("Arm; left"));
+        let escaped = StepEntities::parse("#9=PRODUCT('O''Brien bracket','',$);");
+        assert_eq!(escaped.get(9).unwrap().quoted().as_deref(), Some("O'Brien bracket"));
+
+        assert_eq!(entities.instance(4).len(), 4);
+        assert_eq!(entities.get(4).unwrap().entity_type, "BOUNDED_CURVE");
+        let spline = entities.get_typed(4, "B_SPLINE_CURVE").unwrap();
+        assert_eq!(spline.param_real(0), Some(1.0));
+        assert_eq!(entities.of_type("RATIONAL_B_SPLINE_CURVE").count(), 1);
+        assert_eq!(entities.instance_references(4).collect::<Vec<_>>(), vec![5, 6]);
+        assert!(entities.instance(3).is_empty());
+    }
+}
@@ -0,0 +1,289 @@
+// Exchange-structure census of STEP files: statements and instances come from the tokenizer,
+// so entity types are parsed rather than matched as substrings
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::step_tokenizer::{records, references, statements, tokenize, unescape, Spanned, Statement, Token};
+
+/// Geometry behind a face
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SurfaceClass {
+    Planar,
+    Cylindrical,
+    Curved,
+}
+
+/// Class of a surface from the types of its instance (every partial of a complex one)
+pub(crate) fn surface_class<'t>(mut types: impl Iterator<Item = &'t str>) -> Option<SurfaceClass> {
+    types.find_map(|t| match t {
+        "PLANE" => Some(SurfaceClass::Planar),
+        "CYLINDRICAL_SURFACE" => Some(SurfaceClass::Cylindrical),
+        "CONICAL_SURFACE" | "SPHERICAL_SURFACE" | "TOROIDAL_SURFACE" | "DEGENERATE_TOROIDAL_SURFACE"
+        | "SURFACE_OF_REVOLUTION" | "SURFACE_OF_LINEAR_EXTRUSION" | "OFFSET_SURFACE" => Some(SurfaceClass::Curved),
+        t if t.starts_with("B_SPLINE_SURFACE") || t.starts_with("RATIONAL_B_SPLINE_SURFACE") => Some(SurfaceClass::Curved),
+        _ => None,
+    })
+}
+
+/// Faces by the type of their underlying surface
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FaceTypeCounts {
+    pub faces: usize,
+    pub planar: usize,
+    pub cylindrical: usize,
+    pub curved: usize,
+    pub unresolved: usize,   // Surface reference missing or of an unknown type
+}
+
+/// One pass over a file: structure, type counts and face geometry
+#[derive(Debug, Default)]
+pub struct StepCensus<'a> {
+    pub starts_with_magic: bool,
+    pub has_end: bool,
+    pub header_entities: Vec<&'a str>,
+    pub schema: Option<String>,
+    pub data_sections: usize,
+    pub instances: usize,
+    pub malformed: Vec<String>,            // Statements in DATA that are not instances (first few)
+    pub malformed_count: usize,
+    pub type_counts: HashMap<&'a str, usize>,
+    pub face_types: FaceTypeCounts,
+}
+
+const MAX_REPORTED: usize = 10;
+
+fn excerpt(statement: &str) -> String {
+    statement.chars().take(60).collect()
+}
+
+impl<'a> StepCensus<'a> {
+    pub fn count(&self, entity_type: &str) -> usize {
+        self.type_counts.get(entity_type).copied().unwrap_or(0)
+    }
+}
+
+#[derive(PartialEq)]
+enum Section {
+    None,
+    Header,
+    Data,
+}
+
+pub fn census(content: &str) -> StepCensus<'_> {
+    let mut census = StepCensus::default();
+    let mut section = Section::None;
+    let mut surfaces: HashMap<i64, SurfaceClass> = HashMap::new();
+    let mut face_surfaces: Vec<Option<i64>> = Vec::new();
+
+    for (index, statement) in statements(content).enumerate() {
+        if index == 0 {
+            census.starts_with_magic = statement == Statement::Other("ISO-10303-21");
+            if census.starts_with_magic {
+                continue;
+            }
+        }
+        match statement {
+            Statement::Other("HEADER") => section = Section::Header,
+            Statement::Other("ENDSEC") => section = Section::None,
+            Statement::Other("END-ISO-10303-21") => census.has_end = true,
+            Statement::Other(s) if section == Section::None && (s == "DATA" || s.starts_with("DATA(") || s.starts_with("DATA (")) => {
+                section = Section::Data;
+                census.data_sections += 1;
+            }
+            Statement::Other(s) if section == Section::Header => {
+                let name = s.split('(').next().unwrap_or("").trim();
+                if name == "FILE_SCHEMA" {
+                    census.schema = tokenize(s).find_map(|token| match token {
+                        Ok(Spanned { token: Token::String(schema), .. }) => Some(unescape(schema).into_owned()),
+                        _ => None,
+                    });
+                }
+                census.header_entities.push(name);
+            }
+            Statement::Instance(record) if section == Section::Data => {
+                census.instances += 1;
+                for &(t, _) in &record.partials {
+                    *census.type_counts.entry(t).or_insert(0) += 1;
+                }
+                if let Some(class) = surface_class(record.partials.iter().map(|p| p.0)) {
+                    surfaces.insert(record.id, class);
+                }
+                if let Some((_, params)) = record.partials.iter().find(|p| p.0 == "ADVANCED_FACE" || p.0 == "FACE_SURFACE") {
+                    // (name, bounds, face_geometry, same_sense): the geometry is the last reference
+                    face_surfaces.push(references(params).last());
+                }
+            }
+            Statement::Other(s) | Statement::Malformed(s) if section == Section::Data => {
+                census.malformed_count += 1;
+                if census.malformed.len() < MAX_REPORTED {
+                    census.malformed.push(excerpt(s));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let faces = &mut census.face_types;
+    faces.faces = face_surfaces.len();
+    for surface in face_surfaces {
+        match surface.and_then(|id| surfaces.get(&id)) {
+            Some(SurfaceClass::Planar) => faces.planar += 1,
+            Some(SurfaceClass::Cylindrical) => faces.cylindrical += 1,
+            Some(SurfaceClass::Curved) => faces.curved += 1,
+            None => faces.unresolved += 1,
+        }
+    }
+    census
+}
+
+/// Quick format check: the exchange structure opens with ISO-10303-21
+pub fn looks_like_step(content: &str) -> bool {
+    statements(content).next() == Some(Statement::Other("ISO-10303-21"))
+}
+
+/// Outcome of validate_step_format
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FormatReport {
+    pub valid: bool,
+    pub strict: bool,
+    pub schema: Option<String>,
+    pub instances: usize,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    pub face_types: FaceTypeCounts,
+}
+
+/// References to instances that do not exist, as (from, to) pairs
+fn dangling_references(content: &str) -> (usize, Vec<(i64, i64)>) {
+    let ids: HashSet<i64> = records(content).map(|r| r.id).collect();
+    let mut count = 0;
+    let mut first = Vec::new();
+    for record in records(content) {
+        let targets = record.partials.iter().flat_map(|(_, params)| references(params));
+        for target in targets.filter(|r| !ids.contains(r)) {
+            count += 1;
+            if first.len() < MAX_REPORTED {
+                first.push((record.id, target));
+            }
+        }
+    }
+    (count, first)
+}
+
+/// Check a file against the exchange structure; strict mode also requires the full header,
+/// well-formed instances and resolvable references
+pub fn check_format(content: &str, strict: bool) -> FormatReport {
+    let census = census(content);
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    if !census.starts_with_magic {
+        errors.push("File does not start with ISO-10303-21;".to_string());
+    }
+    if census.data_sections == 0 {
+        errors.push("No DATA section".to_string());
+    } else if census.instances == 0 {
+        errors.push("DATA section holds no entity instances".to_string());
+    }
+
+    let mut strict_issues = Vec::new();
+    for required in ["FILE_DESCRIPTION", "FILE_NAME", "FILE_SCHEMA"] {
+        if !census.header_entities.contains(&required) {
+            strict_issues.push(format!("Header is missing {}", required));
+        }
+    }
+    if !census.has_end {
+        strict_issues.push("File does not end with END-ISO-10303-21;".to_string());
+    }
+    if census.malformed_count > 0 {
+        strict_issues.push(format!(
+            "{} statement(s) in DATA are not entity instances, e.g. {}",
+            census.malformed_count,
+            census.malformed.join(" | ")
+        ));
+    }
+    if strict && errors.is_empty() {
+        let (count, first) = dangling_references(content);
+        if count > 0 {
+            let list: Vec<String> = first.iter().map(|(from, to)| format!("#{} -> #{}", from, to)).collect();
+            strict_issues.push(format!("{} reference(s) to missing instances, e.g. {}", count, list.join(", ")));
+        }
+    }
+    if strict {
+        errors.extend(strict_issues);
+    } else {
+        warnings.extend(strict_issues);
+    }
+    if census.face_types.unresolved > 0 {
+        warnings.push(format!("{} face(s) have no recognizable surface", census.face_types.unresolved));
+    }
+
+    FormatReport {
+        valid: errors.is_empty(),
+        strict,
+        schema: census.schema.clone(),
+        instances: census.instances,
+        errors,
+        warnings,
+        face_types: census.face_types,
+    }
+}
+
+/// Validate STEP content; strict mode rejects files the lenient import would accept with warnings
+#[tauri::command]
+pub fn validate_step_format(content: String, strict: Option<bool>) -> FormatReport {
+    check_format(&content, strict.unwrap_or(false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "ISO-10303-21;
+HEADER;
+FILE_DESCRIPTION(('plate; with a hole'),'2;1');
+FILE_NAME('plate.step','',(''),(''),'','','');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+/* placement, not a face */
+#1=AXIS2_PLACEMENT_3D('',#2,#3,#4);
+#2=CARTESIAN_POINT('',(0.,0.,0.));
+#3=DIRECTION('',(0.,0.,1.));
+#4=DIRECTION('',(1.,0.,0.));
+#5=PLANE('',#1);
+#6=CYLINDRICAL_SURFACE('PLANE(',#1,
+  5.);
+#7=ADVANCED_FACE('',(),#5,.T.);
+#8=ADVANCED_FACE('',(),#6,.F.);
+#9=(BOUNDED_SURFACE()B_SPLINE_SURFACE(1,1,((#2,#2),(#2,#2)),.UNSPECIFIED.,.F.,.F.,.F.)SURFACE()GEOMETRIC_REPRESENTATION_ITEM()REPRESENTATION_ITEM(''));
+#10=FACE_SURFACE('',(),#9,.T.);
+ENDSEC;
+END-ISO-10303-21;";
+
+    #[test]
+    fn test_census_counts_parsed_types() {
+        let census = census(FILE);
+        assert!(census.starts_with_magic && census.has_end);
+        assert_eq!(census.schema.as_deref(), Some("AUTOMOTIVE_DESIGN"));
+        assert_eq!(census.instances, 10);
+        // Neither the placement nor the string inside #6 counts as a plane
+        assert_eq!(census.count("PLANE"), 1);
+        assert_eq!(census.count("B_SPLINE_SURFACE"), 1);
+        assert_eq!(census.face_types, FaceTypeCounts { faces: 3, planar: 1, cylindrical: 1, curved: 1, unresolved: 0 });
+    }
+
+    #[test]
+    fn test_format_checks() {
+        assert!(check_format(FILE, true).valid);
+        assert!(!looks_like_step("Notes on the STEP process"));
+        assert!(!check_format("Notes on the STEP process", false).valid);
+
+        let dangling = FILE.replace("#7=ADVANCED_FACE('',(),#5,.T.);", "#7=ADVANCED_FACE('',(),#50,.T.);");
+        assert!(check_format(&dangling, false).valid);
+        let strict = check_format(&dangling, true);
+        assert!(!strict.valid);
+        assert!(strict.errors[0].contains("#7 -> #50"));
+    }
+}
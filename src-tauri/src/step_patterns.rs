@@ -0,0 +1,12 @@
+// Compiled regex patterns shared by the STEP parsers
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Parenthesized coordinate triple: (x, y, z)
+pub static COORDINATE_TRIPLE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\(\s*([+-]?\d+\.?\d*(?:[eE][+-]?\d+)?)\s*,\s*([+-]?\d+\.?\d*(?:[eE][+-]?\d+)?)\s*,\s*([+-]?\d+\.?\d*(?:[eE][+-]?\d+)?)\s*\)").unwrap()
+});
+
+/// STEP string literal, with doubled quotes as escapes: 'O''Brien'
+pub static STEP_STRING: Lazy<Regex> = Lazy::new(|| Regex::new(r"'(?:[^']|'')*'").unwrap());
@@ -0,0 +1,387 @@
+// ISO 10303-21 tokenizer: lexes the exchange structure and groups tokens into instance records,
+// so multi-line parameter lists, `;` inside strings and complex instances survive
+
+use std::borrow::Cow;
+
+/// One lexical token; text slices borrow from the source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token<'a> {
+    Keyword(&'a str),       // Entity type, user-defined !NAME, or a section keyword such as DATA
+    InstanceName(i64),      // #123
+    Integer(i64),
+    Real(f64),
+    String(&'a str),        // Between the quotes, doubled quotes left as written
+    Enumeration(&'a str),   // Between the dots: T, F, UNSPECIFIED, ...
+    Binary(&'a str),        // Between the double quotes
+    Omitted,                // $
+    Derived,                // *
+    Open,
+    Close,
+    Comma,
+    Equals,
+    Semicolon,
+}
+
+/// Token with its byte range in the source
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spanned<'a> {
+    pub token: Token<'a>,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A byte that starts no token, or a string left open at the end of the input
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub start: usize,
+    pub end: usize,     // Where lexing resumes
+    pub message: String,
+}
+
+/// Tokens of a file, skipping whitespace and `/* */` comments; a byte that starts no token is an
+/// error and lexing resumes after it
+pub struct Lexer<'a> {
+    content: &'a str,
+    pos: usize,
+}
+
+pub fn tokenize(content: &str) -> Lexer<'_> {
+    let pos = if content.starts_with('\u{feff}') { '\u{feff}'.len_utf8() } else { 0 };
+    Lexer { content, pos }
+}
+
+fn is_keyword_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'-'
+}
+
+impl<'a> Lexer<'a> {
+    /// Offset of the first byte at or after `from` that is not in `pred`
+    fn scan_while(&self, from: usize, pred: impl Fn(u8) -> bool) -> usize {
+        let bytes = self.content.as_bytes();
+        let mut i = from;
+        while i < bytes.len() && pred(bytes[i]) {
+            i += 1;
+        }
+        i
+    }
+
+    /// Sign, digits, optional fraction and exponent; reals always have the decimal point
+    fn number(&self, start: usize) -> Result<(Token<'a>, usize), String> {
+        let bytes = self.content.as_bytes();
+        let mut i = start;
+        if matches!(bytes.get(i), Some(b'+') | Some(b'-')) {
+            i += 1;
+        }
+        let digits = i;
+        i = self.scan_while(i, |b| b.is_ascii_digit());
+        if i == digits {
+            return Err(format!("Sign without digits at byte {}", start));
+        }
+        let mut real = false;
+        if bytes.get(i) == Some(&b'.') {
+            real = true;
+            i = self.scan_while(i + 1, |b| b.is_ascii_digit());
+            if matches!(bytes.get(i), Some(b'E') | Some(b'e')) {
+                let mut e = i + 1;
+                if matches!(bytes.get(e), Some(b'+') | Some(b'-')) {
+                    e += 1;
+                }
+                let end = self.scan_while(e, |b| b.is_ascii_digit());
+                if end > e {
+                    i = end;
+                }
+            }
+        }
+        let text = &self.content[start..i];
+        let token = if real {
+            Token::Real(text.parse().map_err(|_| format!("Invalid real \"{}\" at byte {}", text, start))?)
+        } else {
+            Token::Integer(text.parse().map_err(|_| format!("Invalid integer \"{}\" at byte {}", text, start))?)
+        };
+        Ok((token, i))
+    }
+
+    /// Text up to the closing delimiter, with the offset after it
+    fn delimited(&self, start: usize, close: u8, what: &str) -> Result<(&'a str, usize), String> {
+        let bytes = self.content.as_bytes();
+        let mut i = start + 1;
+        while i < bytes.len() {
+            if bytes[i] == close {
+                // In strings a doubled quote is an escaped quote
+                if close == b'\'' && bytes.get(i + 1) == Some(&b'\'') {
+                    i += 2;
+                    continue;
+                }
+                return Ok((&self.content[start + 1..i], i + 1));
+            }
+            i += 1;
+        }
+        Err(format!("Unterminated {} starting at byte {}", what, start))
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Spanned<'a>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.content.as_bytes();
+        loop {
+            self.pos = self.scan_while(self.pos, |b| b.is_ascii_whitespace());
+            if bytes[self.pos..].starts_with(b"/*") {
+                self.pos = self.content[self.pos + 2..].find("*/").map_or(bytes.len(), |i| self.pos + 2 + i + 2);
+                continue;
+            }
+            break;
+        }
+        let start = self.pos;
+        let b = *bytes.get(start)?;
+
+        let single = |token| Ok((token, start + 1));
+        let lexed = match b {
+            b'(' => single(Token::Open),
+            b')' => single(Token::Close),
+            b',' => single(Token::Comma),
+            b'=' => single(Token::Equals),
+            b';' => single(Token::Semicolon),
+            b'$' => single(Token::Omitted),
+            b'*' => single(Token::Derived),
+            b'#' => {
+                let end = self.scan_while(start + 1, |b| b.is_ascii_digit());
+                self.content[start + 1..end].parse()
+                    .map(|id| (Token::InstanceName(id), end))
+                    .map_err(|_| format!("Instance name without a number at byte {}", start))
+            }
+            b'\'' => self.delimited(start, b'\'', "string").map(|(s, end)| (Token::String(s), end)),
+            b'"' => self.delimited(start, b'"', "binary").map(|(s, end)| (Token::Binary(s), end)),
+            b'.' if bytes.get(start + 1).is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_') => {
+                self.delimited(start, b'.', "enumeration").map(|(s, end)| (Token::Enumeration(s), end))
+            }
+            b'+' | b'-' | b'0'..=b'9' => self.number(start),
+            b'!' | b'A'..=b'Z' | b'a'..=b'z' | b'_' => {
+                let end = self.scan_while(start + 1, is_keyword_byte);
+                Ok((Token::Keyword(&self.content[start..end]), end))
+            }
+            _ => Err(format!("Unexpected character at byte {}", start)),
+        };
+
+        Some(match lexed {
+            Ok((token, end)) => {
+                self.pos = end;
+                Ok(Spanned { token, start, end })
+            }
+            Err(message) => {
+                // Resume after the offending character (whole UTF-8 sequence)
+                self.pos = start + self.content[start..].chars().next().map_or(1, char::len_utf8);
+                Err(LexError { start, end: self.pos, message })
+            }
+        })
+    }
+}
+
+/// One `#id = ...;` instance. A simple instance has one partial; a complex instance lists each of
+/// its partial types with that type's own parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record<'a> {
+    pub id: i64,
+    pub partials: Vec<(&'a str, &'a str)>,  // (type, parameters between the outer parentheses)
+    pub start: usize,                       // Byte range of the whole statement, `;` included
+    pub end: usize,
+}
+
+/// Instance names referenced in a parameter list, in order; a `#` inside a string is not one
+pub fn references(params: &str) -> impl Iterator<Item = i64> + '_ {
+    tokenize(params).filter_map(|token| match token {
+        Ok(Spanned { token: Token::InstanceName(id), .. }) => Some(id),
+        _ => None,
+    })
+}
+
+/// Text of a string token with its doubled quotes collapsed: `O''Brien` reads as `O'Brien`
+pub fn unescape(text: &str) -> Cow<'_, str> {
+    if text.contains("''") { Cow::Owned(text.replace("''", "'")) } else { Cow::Borrowed(text) }
+}
+
+/// One `;`-terminated statement of a file. Text is trimmed and excludes the `;`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement<'a> {
+    Instance(Record<'a>),
+    Malformed(&'a str),  // Starts with an instance name but is not a well-formed instance
+    Other(&'a str),      // Section keywords and header entities
+}
+
+/// Statements of a file in source order
+pub struct Statements<'a> {
+    content: &'a str,
+    tokens: Lexer<'a>,
+    statement_ended: bool,  // The last token taken was a `;`
+    text_end: usize,        // End of the last token taken before a `;`
+}
+
+pub fn statements(content: &str) -> Statements<'_> {
+    Statements { content, tokens: tokenize(content), statement_ended: false, text_end: 0 }
+}
+
+impl<'a> Statements<'a> {
+    fn next_token(&mut self) -> Option<Result<Spanned<'a>, LexError>> {
+        let token = self.tokens.next();
+        self.statement_ended = matches!(token, Some(Ok(Spanned { token: Token::Semicolon, .. })));
+        match &token {
+            Some(Ok(t)) if !self.statement_ended => self.text_end = t.end,
+            Some(Err(e)) => self.text_end = e.end,
+            _ => {}
+        }
+        token
+    }
+
+    /// Parameters of one `TYPE(...)` after its keyword: the text between the parentheses
+    fn partial(&mut self) -> Option<&'a str> {
+        let open = match self.next_token()? {
+            Ok(Spanned { token: Token::Open, end, .. }) => end,
+            _ => return None,
+        };
+        let mut depth = 1;
+        loop {
+            let token = self.next_token()?.ok()?;
+            match token.token {
+                Token::Open => depth += 1,
+                Token::Close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(self.content[open..token.start].trim());
+                    }
+                }
+                // A record never ends inside its parameter list
+                Token::Semicolon => return None,
+                _ => {}
+            }
+        }
+    }
+
+    /// Everything after `#id =`, through the closing `;`
+    fn body(&mut self) -> Option<(Vec<(&'a str, &'a str)>, usize)> {
+        let mut partials = Vec::new();
+        match self.next_token()?.ok()?.token {
+            Token::Keyword(entity_type) => {
+                partials.push((entity_type, self.partial()?));
+            }
+            Token::Open => loop {
+                match self.next_token()?.ok()?.token {
+                    Token::Keyword(entity_type) => partials.push((entity_type, self.partial()?)),
+                    Token::Close if !partials.is_empty() => break,
+                    _ => return None,
+                }
+            },
+            _ => return None,
+        }
+        match self.next_token()?.ok()? {
+            Spanned { token: Token::Semicolon, end, .. } => Some((partials, end)),
+            _ => None,
+        }
+    }
+
+    /// Drop tokens through the next `;` and return the statement's text
+    fn rest_of_statement(&mut self, start: usize) -> &'a str {
+        while !self.statement_ended && self.next_token().is_some() {}
+        self.content[start..self.text_end.max(start)].trim()
+    }
+}
+
+impl<'a> Iterator for Statements<'a> {
+    type Item = Statement<'a>;
+
+    fn next(&mut self) -> Option<Statement<'a>> {
+        loop {
+            let (start, id) = match self.next_token()? {
+                Ok(Spanned { token: Token::Semicolon, .. }) => continue,
+                Ok(Spanned { token: Token::InstanceName(id), start, .. }) => (start, id),
+                Ok(Spanned { start, .. }) | Err(LexError { start, .. }) => {
+                    return Some(Statement::Other(self.rest_of_statement(start)));
+                }
+            };
+            if !matches!(self.next_token(), Some(Ok(Spanned { token: Token::Equals, .. }))) {
+                return Some(Statement::Malformed(self.rest_of_statement(start)));
+            }
+            // A damaged body may already have taken its `;`
+            return Some(match self.body() {
+                Some((partials, end)) => Statement::Instance(Record { id, partials, start, end }),
+                None => Statement::Malformed(self.rest_of_statement(start)),
+            });
+        }
+    }
+}
+
+/// Instance records of a file in source order. Statements that are not well-formed instances
+/// (header entities, section keywords, damaged records) are skipped
+pub struct Records<'a> {
+    statements: Statements<'a>,
+    pub malformed: usize,
+}
+
+pub fn records(content: &str) -> Records<'_> {
+    Records { statements: statements(content), malformed: 0 }
+}
+
+impl<'a> Iterator for Records<'a> {
+    type Item = Record<'a>;
+
+    fn next(&mut self) -> Option<Record<'a>> {
+        loop {
+            match self.statements.next()? {
+                Statement::Instance(record) => return Some(record),
+                Statement::Malformed(_) => self.malformed += 1,
+                Statement::Other(_) => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens() {
+        let tokens: Vec<Token> = tokenize("#12 = X('it''s;',(1.,-2.5E-1,3),.T.,$,*, \"0F\") /* c */ ;")
+            .map(|t| t.unwrap().token)
+            .collect();
+        assert_eq!(tokens, vec![
+            Token::InstanceName(12), Token::Equals, Token::Keyword("X"), Token::Open,
+            Token::String("it''s;"), Token::Comma, Token::Open, Token::Real(1.0), Token::Comma,
+            Token::Real(-0.25), Token::Comma, Token::Integer(3), Token::Close, Token::Comma,
+            Token::Enumeration("T"), Token::Comma, Token::Omitted, Token::Comma, Token::Derived, Token::Comma,
+            Token::Binary("0F"), Token::Close, Token::Semicolon,
+        ]);
+        assert!(tokenize("'open").next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_records_across_lines_strings_and_complex_instances() {
+        let content = "ISO-10303-21;
+HEADER;
+FILE_NAME('a;b.stp','',(''),(''),'','','');
+ENDSEC;
+DATA;
+#1=PRODUCT('Bracket; rev A','Bracket',
+  '',(#2));
+#2 = ( LENGTH_UNIT ( ) NAMED_UNIT ( * ) SI_UNIT ( .MILLI. , .METRE. ) ) ;
+#3=BROKEN('',#1;
+#4=CARTESIAN_POINT('',(0.,0.,1.));
+ENDSEC;
+END-ISO-10303-21;";
+        let mut records = records(content);
+        let all: Vec<Record> = records.by_ref().collect();
+        assert_eq!(all.iter().map(|r| r.id).collect::<Vec<_>>(), vec![1, 2, 4]);
+        assert_eq!(records.malformed, 1);
+
+        assert_eq!(all[0].partials, vec![("PRODUCT", "'Bracket; rev A','Bracket',\n  '',(#2)")]);
+        assert!(content[all[0].start..all[0].end].ends_with("(#2));"));
+        let types: Vec<&str> = all[1].partials.iter().map(|p| p.0).collect();
+        assert_eq!(types, vec!["LENGTH_UNIT", "NAMED_UNIT", "SI_UNIT"]);
+        assert_eq!(all[1].partials[2].1, ".MILLI. , .METRE.");
+        let kinds: Vec<Statement> = statements(content).skip(4).take(2).collect();
+        assert_eq!(kinds[0], Statement::Other("DATA"));
+        assert!(matches!(kinds[1], Statement::Instance(Record { id: 1, .. })));
+        assert_eq!(statements(content).nth(7), Some(Statement::Malformed("#3=BROKEN('',#1")));
+        assert_eq!(references("'#9',(#2,#30),$").collect::<Vec<_>>(), vec![2, 30]);
+    }
+}
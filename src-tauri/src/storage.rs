@@ -0,0 +1,67 @@
+// App data persistence helpers shared by backend services
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// Resolve a file inside the app data directory, creating the directory if needed
+pub fn app_data_file(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let dir = app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app data directory: {}", e))?;
+
+    Ok(dir.join(name))
+}
+
+/// Load a JSON file, falling back to the default value if missing or unreadable
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> T {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+/// Write a value as pretty JSON, replacing the file atomically
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+
+    // Write to a sibling temp file first so a crash never leaves a truncated file
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, text)
+        .map_err(|e| format!("Failed to write {}: {}", tmp_path.display(), e))?;
+    std::fs::rename(&tmp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+/// Current time as seconds since the Unix epoch
+pub fn unix_timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_roundtrip() {
+        let path = std::env::temp_dir().join(format!("ohmframe-storage-{}.json", std::process::id()));
+        save_json(&path, &vec![1, 2, 3]).unwrap();
+        let loaded: Vec<i32> = load_json(&path);
+        assert_eq!(loaded, vec![1, 2, 3]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_load_missing_returns_default() {
+        let loaded: Vec<i32> = load_json(Path::new("/nonexistent/ohmframe.json"));
+        assert!(loaded.is_empty());
+    }
+}
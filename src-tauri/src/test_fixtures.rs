@@ -0,0 +1,51 @@
+// Builders for the parsed faces and interfaces that unit tests assemble by hand
+
+use crate::assembly_parser::ParsedFace;
+use crate::interface_detection::DetectedInterface;
+use crate::linalg::Vec3;
+
+/// Face with no area or STEP id. A face with a radius is a cylinder along +Z that faces its axis,
+/// as a hole does
+pub fn face(id: i64, face_type: &str, center: Vec3, normal: Vec3, radius: Option<f64>) -> ParsedFace {
+    ParsedFace {
+        id,
+        face_type: face_type.to_string(),
+        normal,
+        center,
+        area: 0.0,
+        radius,
+        axis: radius.map(|_| [0.0, 0.0, 1.0]),
+        step_entity_id: None,
+        same_sense: Some(radius.is_none()),
+        semi_angle: None,
+        apex: None,
+        minor_radius: None,
+    }
+}
+
+/// Cylindrical hole along +Z
+pub fn hole(id: i64, center: Vec3, radius: f64) -> ParsedFace {
+    face(id, "cylindrical", center, [1.0, 0.0, 0.0], Some(radius))
+}
+
+/// Cylindrical shaft along +Z, facing away from its axis
+pub fn boss(id: i64, center: Vec3, radius: f64) -> ParsedFace {
+    ParsedFace { same_sense: Some(true), ..hole(id, center, radius) }
+}
+
+/// Face-to-face contact between face 1 of part `a` and face 2 of part `b`
+pub fn interface(id: &str, a: &str, b: &str, contact_point: Vec3) -> DetectedInterface {
+    DetectedInterface {
+        id: id.to_string(),
+        part_a_id: a.to_string(),
+        part_a_face_id: 1,
+        part_b_id: b.to_string(),
+        part_b_face_id: 2,
+        interface_type: "face_to_face".to_string(),
+        proximity: 0.0,
+        normal_alignment: 1.0,
+        contact_area: 100.0,
+        contact_point,
+        fit: None,
+    }
+}
@@ -0,0 +1,199 @@
+// One stackup evaluated at several temperatures, links growing with their thermal expansion coefficients
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::jobs::run_job;
+use crate::tolerance_calc::{
+    compute_tolerance_stackup, validate_link, LinkInput, TargetSpec, ToleranceCalcResult, ToleranceInput, MAX_MONTE_CARLO_SAMPLES,
+};
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// ISO 1 reference temperature for dimensional specifications (°C)
+const REFERENCE_TEMPERATURE: f64 = 20.0;
+
+/// Stackup link with its coefficient of thermal expansion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThermalLink {
+    #[serde(flatten)]
+    pub link: LinkInput,
+    #[serde(default)]
+    pub cte: Option<f64>,  // 1/K, e.g. 11.5e-6 for carbon steel; dimensionally stable when absent
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemperatureScenario {
+    pub name: String,      // "cold", "ambient", "hot", ...
+    pub temperature: f64,  // °C
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThermalScenarioInput {
+    pub links: Vec<ThermalLink>,
+    pub scenarios: Vec<TemperatureScenario>,
+    pub reference_temperature: Option<f64>,  // Temperature the drawing values hold at; default 20 °C
+    pub target_spec: Option<TargetSpec>,
+    pub monte_carlo_samples: Option<usize>,
+}
+
+impl Validate for ThermalScenarioInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        if self.links.is_empty() {
+            errors.add("links", "expected at least one link");
+        }
+        for (i, link) in self.links.iter().enumerate() {
+            validate_link(&link.link, &format!("links[{}]", i), errors);
+            if let Some(cte) = link.cte {
+                errors.within(format!("links[{}].cte", i), cte, -1e-4, 1e-3);
+            }
+        }
+        if self.scenarios.is_empty() {
+            errors.add("scenarios", "expected at least one temperature scenario");
+        }
+        for (i, scenario) in self.scenarios.iter().enumerate() {
+            errors.above(format!("scenarios[{}].temperature", i), scenario.temperature, -273.15);
+        }
+        if let Some(reference) = self.reference_temperature {
+            errors.above("reference_temperature", reference, -273.15);
+        }
+        if let Some(spec) = &self.target_spec {
+            errors.finite("target_spec.nominal", spec.nominal);
+            errors.at_least("target_spec.plus_tolerance", spec.plus_tolerance, 0.0);
+            errors.at_least("target_spec.minus_tolerance", spec.minus_tolerance, 0.0);
+        }
+        if let Some(samples) = self.monte_carlo_samples {
+            if samples == 0 || samples > MAX_MONTE_CARLO_SAMPLES {
+                errors.add("monte_carlo_samples", format!("expected 1 to {}, got {}", MAX_MONTE_CARLO_SAMPLES, samples));
+            }
+        }
+    }
+}
+
+/// Stackup at one temperature
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScenarioResult {
+    pub name: String,
+    pub temperature: f64,
+    pub nominal_shift: f64,  // Change of the nominal total from the reference temperature
+    pub result: ToleranceCalcResult,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThermalScenarioResult {
+    pub reference_temperature: f64,
+    pub scenarios: Vec<ScenarioResult>,
+    pub worst_scenario: Option<String>,  // Lowest Cpk with a target spec, else the largest nominal shift
+}
+
+/// Links as they measure at `temperature`; tolerances grow with the part
+fn links_at(links: &[ThermalLink], temperature: f64, reference: f64) -> Vec<LinkInput> {
+    links.iter()
+        .map(|thermal| {
+            let factor = 1.0 + thermal.cte.unwrap_or(0.0) * (temperature - reference);
+            LinkInput {
+                nominal: thermal.link.nominal * factor,
+                plus_tolerance: thermal.link.plus_tolerance * factor,
+                minus_tolerance: thermal.link.minus_tolerance * factor,
+                ..thermal.link.clone()
+            }
+        })
+        .collect()
+}
+
+/// Full stackup analysis per temperature scenario
+pub fn run_thermal_scenarios(input: &ThermalScenarioInput) -> ThermalScenarioResult {
+    let reference = input.reference_temperature.unwrap_or(REFERENCE_TEMPERATURE);
+    let reference_nominal: f64 = links_at(&input.links, reference, reference).iter()
+        .map(|l| if l.direction == "negative" { -l.nominal } else { l.nominal })
+        .sum();
+    tracing::info!(links = input.links.len(), scenarios = input.scenarios.len(), reference, "running temperature scenarios");
+
+    let scenarios: Vec<ScenarioResult> = input.scenarios.iter()
+        .map(|scenario| {
+            let result = compute_tolerance_stackup(ToleranceInput {
+                links: links_at(&input.links, scenario.temperature, reference),
+                monte_carlo_samples: input.monte_carlo_samples,
+                target_spec: input.target_spec.clone(),
+                gauge_sigma: None,
+                adjuster: None,
+            });
+            ScenarioResult {
+                name: scenario.name.clone(),
+                temperature: scenario.temperature,
+                nominal_shift: result.total_nominal - reference_nominal,
+                result,
+            }
+        })
+        .collect();
+
+    let worst = if input.target_spec.is_some() {
+        let cpk = |s: &ScenarioResult| s.result.monte_carlo.as_ref().map(|mc| mc.cpk).unwrap_or(f64::INFINITY);
+        scenarios.iter().min_by(|a, b| cpk(a).total_cmp(&cpk(b)))
+    } else {
+        scenarios.iter().max_by(|a, b| a.nominal_shift.abs().total_cmp(&b.nominal_shift.abs()))
+    };
+
+    ThermalScenarioResult {
+        reference_temperature: reference,
+        worst_scenario: worst.map(|s| s.name.clone()),
+        scenarios,
+    }
+}
+
+/// Evaluate one stackup at hot, cold and other temperature cases in one call
+#[tauri::command]
+pub async fn run_temperature_scenarios(
+    app: AppHandle,
+    input: Checked<ThermalScenarioInput>,
+    job_id: Option<String>,
+) -> Result<ThermalScenarioResult, String> {
+    let input = input.into_inner();
+    run_job(app, "run_temperature_scenarios", job_id, None, move |_| Ok(run_thermal_scenarios(&input))).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validation::validate;
+
+    fn link(nominal: f64, direction: &str, cte: f64) -> ThermalLink {
+        ThermalLink {
+            link: LinkInput {
+                name: None,
+                nominal,
+                plus_tolerance: 0.02,
+                minus_tolerance: 0.02,
+                direction: direction.to_string(),
+                distribution: "normal".to_string(),
+                sigma: Some(3.0),
+                inspected: false,
+            },
+            cte: Some(cte),
+        }
+    }
+
+    #[test]
+    fn test_aluminium_housing_opens_when_hot() {
+        // 100 mm aluminium housing around a 99.9 mm steel shaft: the gap grows with temperature
+        let input = ThermalScenarioInput {
+            links: vec![link(100.0, "positive", 23.6e-6), link(99.9, "negative", 11.5e-6)],
+            scenarios: vec![
+                TemperatureScenario { name: "cold".to_string(), temperature: -40.0 },
+                TemperatureScenario { name: "ambient".to_string(), temperature: 20.0 },
+                TemperatureScenario { name: "hot".to_string(), temperature: 85.0 },
+            ],
+            reference_temperature: None,
+            target_spec: Some(TargetSpec { nominal: 0.1, plus_tolerance: 0.1, minus_tolerance: 0.1 }),
+            monte_carlo_samples: Some(5_000),
+        };
+        assert!(validate(&input).is_ok());
+
+        let result = run_thermal_scenarios(&input);
+        let shift = |name: &str| result.scenarios.iter().find(|s| s.name == name).unwrap().nominal_shift;
+        assert!(shift("ambient").abs() < 1e-12);
+        let expected_hot = 65.0 * (100.0 * 23.6e-6 - 99.9 * 11.5e-6);
+        assert!((shift("hot") - expected_hot).abs() < 1e-9);
+        assert!(shift("cold") < 0.0);
+        assert_eq!(result.worst_scenario.as_deref(), Some("hot"));
+    }
+}
@@ -0,0 +1,190 @@
+// Rules-based tolerance advice: ISO 286 fits for cylindrical interfaces, ISO 2768 classes for faces
+
+use serde::{Deserialize, Serialize};
+
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// ISO 286 covers nominal sizes up to this diameter (mm)
+const ISO_286_MAX_SIZE: f64 = 3150.0;
+
+/// What the interface has to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterfaceFunction {
+    Locating,  // Accurate, repeatable position
+    Sliding,   // Moves or rotates in service
+    Press,     // Held by interference
+}
+
+/// Interface to advise on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToleranceAdviceRequest {
+    pub interface_type: String,  // As detected: "pin_in_hole", "shaft_in_bore", "face_to_face", "unknown"
+    pub size: f64,               // Diameter for cylindrical interfaces, longest face length otherwise (mm)
+    pub function: InterfaceFunction,
+}
+
+impl Validate for ToleranceAdviceRequest {
+    fn validate(&self, errors: &mut FieldErrors) {
+        errors.one_of("interface_type", &self.interface_type, &["pin_in_hole", "shaft_in_bore", "face_to_face", "unknown"]);
+        errors.above("size", self.size, 0.0);
+    }
+}
+
+/// One suggested fit or tolerance class
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToleranceSuggestion {
+    pub fit_code: Option<String>,         // ISO 286 hole-basis fit, e.g. "H7/g6"
+    pub tolerance_class: Option<String>,  // General tolerance class, e.g. "ISO 2768-f"
+    pub flatness: Option<f64>,            // Suggested flatness callout for planar contact (mm)
+    pub description: String,
+    pub rationale: String,
+    pub preferred: bool,                  // First choice for the function
+}
+
+/// Suggestions for an interface, preferred first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToleranceAdvice {
+    pub interface_type: String,
+    pub size: f64,
+    pub function: InterfaceFunction,
+    pub suggestions: Vec<ToleranceSuggestion>,
+    pub notes: Vec<String>,
+}
+
+fn fit(code: &str, description: &str, rationale: &str, preferred: bool) -> ToleranceSuggestion {
+    ToleranceSuggestion {
+        fit_code: Some(code.to_string()),
+        tolerance_class: None,
+        flatness: None,
+        description: description.to_string(),
+        rationale: rationale.to_string(),
+        preferred,
+    }
+}
+
+/// ISO 2768-2 general flatness for a face length: class H (fine) or K (medium)
+fn general_flatness(length: f64, fine: bool) -> f64 {
+    let table: [(f64, f64, f64); 6] = [
+        (10.0, 0.02, 0.05),
+        (30.0, 0.05, 0.1),
+        (100.0, 0.1, 0.2),
+        (300.0, 0.2, 0.4),
+        (1000.0, 0.3, 0.6),
+        (f64::INFINITY, 0.4, 0.8),
+    ];
+    let (_, h, k) = table.iter().find(|(max, _, _)| length <= *max).copied().unwrap_or(table[5]);
+    if fine { h } else { k }
+}
+
+fn cylindrical_suggestions(function: InterfaceFunction) -> Vec<ToleranceSuggestion> {
+    match function {
+        InterfaceFunction::Locating => vec![
+            fit("H7/h6", "Sliding location", "Zero minimum clearance: parts assemble by hand and locate accurately", true),
+            fit("H7/k6", "Transition location", "Near-zero clearance for more accuracy where a light tap to assemble is acceptable", false),
+            fit("H7/g6", "Close location with clearance", "Small guaranteed clearance when parts must assemble freely or be removed often", false),
+        ],
+        InterfaceFunction::Sliding => vec![
+            fit("H7/g6", "Sliding", "Small clearance for accurate sliding or slow rotation without play", true),
+            fit("H8/f7", "Close running", "Clearance for lubricated running at moderate speed with good location", false),
+            fit("H9/d9", "Free running", "Generous clearance for high speed, heavy journal pressure or large temperature swings", false),
+        ],
+        InterfaceFunction::Press => vec![
+            fit("H7/p6", "Locational interference", "Light interference that locates rigidly without transmitting much load by friction", true),
+            fit("H7/s6", "Medium drive", "Permanent assembly transmitting moderate torque; press or shrink fit", false),
+            fit("H7/u6", "Force fit", "Heavy interference for high load transfer; check hub stresses, usually shrink fitted", false),
+        ],
+    }
+}
+
+fn face_suggestions(size: f64, function: InterfaceFunction) -> Vec<ToleranceSuggestion> {
+    let class = |class: &str, fine: bool, description: &str, rationale: &str, preferred: bool| ToleranceSuggestion {
+        fit_code: None,
+        tolerance_class: Some(class.to_string()),
+        flatness: Some(general_flatness(size, fine)),
+        description: description.to_string(),
+        rationale: rationale.to_string(),
+        preferred,
+    };
+    match function {
+        InterfaceFunction::Locating => vec![
+            class("ISO 2768-fH", true, "Fine general tolerances with class H flatness", "Seating faces set position, so their flatness feeds straight into the stackup", true),
+            class("ISO 2768-mK", false, "Medium general tolerances with class K flatness", "Enough when a datum feature elsewhere controls the location", false),
+        ],
+        InterfaceFunction::Sliding => vec![
+            class("ISO 2768-fH", true, "Fine general tolerances with class H flatness", "Sliding faces need flatness for even contact and wear; specify roughness too", true),
+        ],
+        InterfaceFunction::Press => vec![
+            class("ISO 2768-mK", false, "Medium general tolerances with class K flatness", "Clamped faces pull flat under load; tolerance the clamping features instead", true),
+        ],
+    }
+}
+
+/// Suggested fits or tolerance classes for an interface
+pub fn advise(request: &ToleranceAdviceRequest) -> ToleranceAdvice {
+    let mut notes = Vec::new();
+    let cylindrical = matches!(request.interface_type.as_str(), "pin_in_hole" | "shaft_in_bore");
+    let suggestions = if cylindrical {
+        if request.size > ISO_286_MAX_SIZE {
+            notes.push(format!("ISO 286 covers diameters up to {} mm; agree limits with the supplier", ISO_286_MAX_SIZE));
+        }
+        if request.function == InterfaceFunction::Press && request.size < 3.0 {
+            notes.push("Below 3 mm interference fits give tiny grip; consider knurling or adhesive".to_string());
+        }
+        if request.interface_type == "shaft_in_bore" {
+            notes.push("Shaft-in-bore detected from a cylinder against a face; confirm the mating diameters".to_string());
+        }
+        cylindrical_suggestions(request.function)
+    } else {
+        if request.interface_type == "unknown" {
+            notes.push("Interface type unknown; general tolerances suggested until the contact is classified".to_string());
+        }
+        face_suggestions(request.size, request.function)
+    };
+
+    ToleranceAdvice {
+        interface_type: request.interface_type.clone(),
+        size: request.size,
+        function: request.function,
+        suggestions,
+        notes,
+    }
+}
+
+/// Suggest ISO fits or tolerance classes for an interface's type, size and function
+#[tauri::command]
+pub fn suggest_interface_tolerances(request: Checked<ToleranceAdviceRequest>) -> ToleranceAdvice {
+    advise(&request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(interface_type: &str, size: f64, function: InterfaceFunction) -> ToleranceAdviceRequest {
+        ToleranceAdviceRequest { interface_type: interface_type.to_string(), size, function }
+    }
+
+    #[test]
+    fn test_pin_fits_by_function() {
+        let preferred = |function| {
+            let advice = advise(&request("pin_in_hole", 10.0, function));
+            assert_eq!(advice.suggestions.iter().filter(|s| s.preferred).count(), 1);
+            advice.suggestions[0].fit_code.clone().unwrap()
+        };
+        assert_eq!(preferred(InterfaceFunction::Locating), "H7/h6");
+        assert_eq!(preferred(InterfaceFunction::Sliding), "H7/g6");
+        assert_eq!(preferred(InterfaceFunction::Press), "H7/p6");
+        assert!(advise(&request("pin_in_hole", 2.0, InterfaceFunction::Press)).notes[0].contains("3 mm"));
+    }
+
+    #[test]
+    fn test_face_flatness_scales_with_size() {
+        let small = advise(&request("face_to_face", 25.0, InterfaceFunction::Locating));
+        assert_eq!(small.suggestions[0].tolerance_class.as_deref(), Some("ISO 2768-fH"));
+        assert_eq!(small.suggestions[0].flatness, Some(0.05));
+        let large = advise(&request("face_to_face", 500.0, InterfaceFunction::Press));
+        assert_eq!(large.suggestions[0].flatness, Some(0.6));
+        assert!(large.suggestions.iter().all(|s| s.fit_code.is_none()));
+    }
+}
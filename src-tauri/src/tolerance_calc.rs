@@ -4,6 +4,16 @@ use serde::{Deserialize, Serialize};
 use rand::Rng;
 use rand::distributions::{Distribution, Uniform};
 use rand_distr::Normal;
+use tauri::AppHandle;
+
+use crate::jobs::run_job;
+use crate::validation::{Checked, FieldErrors, Validate};
+
+/// Upper bound on Monte Carlo samples; every sample is kept in memory for percentiles
+pub const MAX_MONTE_CARLO_SAMPLES: usize = 10_000_000;
+
+/// Redraws of an inspected link before falling back to its mean (only reached with a tiny sigma multiplier)
+const MAX_SCREENING_DRAWS: usize = 1000;
 
 /// Input for tolerance calculation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,17 +21,25 @@ pub struct ToleranceInput {
     pub links: Vec<LinkInput>,
     pub monte_carlo_samples: Option<usize>,
     pub target_spec: Option<TargetSpec>,
+    #[serde(default)]
+    pub gauge_sigma: Option<f64>,  // Measurement system (gauge R&R) standard deviation
+    #[serde(default)]
+    pub adjuster: Option<AdjusterInput>,  // Set at assembly to bring the stack to the target nominal
 }
 
 /// Individual link input
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LinkInput {
+    #[serde(default)]
+    pub name: Option<String>,    // Used to match CMM measurements
     pub nominal: f64,
     pub plus_tolerance: f64,
     pub minus_tolerance: f64,
     pub direction: String,       // "positive" or "negative"
-    pub distribution: String,    // "normal" or "uniform"
+    pub distribution: String,    // "normal", "uniform" or "form" (form error band, see `form_link`)
     pub sigma: Option<f64>,      // Default 3.0 for normal distribution
+    #[serde(default)]
+    pub inspected: bool,         // 100% inspected: parts outside the drawing limits are screened out
 }
 
 /// Target specification for comparison
@@ -32,6 +50,65 @@ pub struct TargetSpec {
     pub minus_tolerance: f64,
 }
 
+/// Link whose value is chosen at assembly to compensate the others: shim, slotted hole, set screw
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdjusterInput {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub direction: String,       // "positive" or "negative"
+    pub min: Option<f64>,        // Smallest value the adjuster can take; unlimited when absent
+    pub max: Option<f64>,
+    pub step: Option<f64>,       // Shim increment from `min` (or zero); continuous when absent
+}
+
+impl Validate for ToleranceInput {
+    fn validate(&self, errors: &mut FieldErrors) {
+        for (i, link) in self.links.iter().enumerate() {
+            validate_link(link, &format!("links[{}]", i), errors);
+        }
+        if let Some(samples) = self.monte_carlo_samples {
+            if samples == 0 || samples > MAX_MONTE_CARLO_SAMPLES {
+                errors.add("monte_carlo_samples", format!("expected 1 to {}, got {}", MAX_MONTE_CARLO_SAMPLES, samples));
+            }
+        }
+        if let Some(spec) = &self.target_spec {
+            errors.finite("target_spec.nominal", spec.nominal);
+            errors.at_least("target_spec.plus_tolerance", spec.plus_tolerance, 0.0);
+            errors.at_least("target_spec.minus_tolerance", spec.minus_tolerance, 0.0);
+        }
+        if let Some(sigma) = self.gauge_sigma {
+            errors.at_least("gauge_sigma", sigma, 0.0);
+        }
+        if let Some(adjuster) = &self.adjuster {
+            if self.target_spec.is_none() {
+                errors.add("target_spec", "required when an adjuster is given");
+            }
+            errors.one_of("adjuster.direction", &adjuster.direction, &["positive", "negative"]);
+            let min = adjuster.min.filter(|v| errors.finite("adjuster.min", *v));
+            let max = adjuster.max.filter(|v| errors.finite("adjuster.max", *v));
+            if let (Some(min), Some(max)) = (min, max) {
+                errors.at_least("adjuster.max", max, min);
+            }
+            if let Some(step) = adjuster.step {
+                errors.above("adjuster.step", step, 0.0);
+            }
+        }
+    }
+}
+
+/// Checks for one link, with fields reported under `prefix`
+pub fn validate_link(link: &LinkInput, prefix: &str, errors: &mut FieldErrors) {
+    let field = |name: &str| format!("{}.{}", prefix, name);
+    errors.finite(field("nominal"), link.nominal);
+    errors.at_least(field("plus_tolerance"), link.plus_tolerance, 0.0);
+    errors.at_least(field("minus_tolerance"), link.minus_tolerance, 0.0);
+    errors.one_of(field("direction"), &link.direction, &["positive", "negative"]);
+    errors.one_of(field("distribution"), &link.distribution, &["normal", "uniform", "form"]);
+    if let Some(sigma) = link.sigma {
+        errors.above(field("sigma"), sigma, 0.0);
+    }
+}
+
 /// Result of tolerance calculation
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ToleranceCalcResult {
@@ -42,6 +119,14 @@ pub struct ToleranceCalcResult {
     pub rss: RssResult,
     pub monte_carlo: Option<MonteCarloResult>,
     pub contributions: Vec<ContributionResult>,
+    #[serde(default)]
+    pub measurement: Option<MeasurementSystemResult>,  // Set when a gauge sigma is given
+    #[serde(default)]
+    pub adjustment: Option<AdjustmentResult>,          // Set when an adjuster is given
+    #[serde(default)]
+    pub pareto: Vec<ParetoEntry>,                      // Contributions, largest variance first
+    #[serde(default)]
+    pub waterfall: Vec<WaterfallStep>,                 // Worst-case totals after each link, in link order
 }
 
 /// Worst-case analysis result
@@ -94,6 +179,51 @@ pub struct HistogramBin {
     pub percentage: f64,
 }
 
+/// True (process) versus observed (process plus gauge) variation of the stackup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeasurementSystemResult {
+    pub gauge_sigma: f64,
+    pub true_sigma: f64,
+    pub observed_sigma: f64,
+    pub gauge_variance_percent: f64,           // Share of observed variance that is the gauge
+    pub true_cpk: Option<f64>,                 // Capabilities need a target spec
+    pub observed_cpk: Option<f64>,
+    pub precision_to_tolerance: Option<f64>,   // 6 gauge sigma over the spec width
+}
+
+/// One bar of the contribution Pareto chart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParetoEntry {
+    pub index: usize,
+    pub name: Option<String>,
+    pub percent: f64,
+    pub cumulative_percent: f64,
+}
+
+/// One link of the worst-case waterfall chart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaterfallStep {
+    pub index: usize,
+    pub name: Option<String>,
+    pub min: f64,              // This link's signed range
+    pub max: f64,
+    pub cumulative_nominal: f64,
+    pub cumulative_min: f64,
+    pub cumulative_max: f64,
+}
+
+/// Adjuster setting needed per build and the variation left once it is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdjustmentResult {
+    pub required_min: f64,           // Worst-case adjuster range that brings every build to nominal
+    pub required_max: f64,
+    pub required_p0_1: f64,          // Simulated 99.8% range of the ideal setting
+    pub required_p99_9: f64,
+    pub out_of_range_fraction: f64,  // Builds needing a setting outside min..max
+    pub residual_worst_case: WorstCaseResult,
+    pub residual: MonteCarloResult,  // Stack total after adjustment
+}
+
 /// Contribution of each link
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ContributionResult {
@@ -105,8 +235,19 @@ pub struct ContributionResult {
 
 /// Calculate tolerance stackup
 #[tauri::command]
-pub fn calculate_tolerance_stackup(input: ToleranceInput) -> ToleranceCalcResult {
+pub async fn calculate_tolerance_stackup(
+    app: AppHandle,
+    input: Checked<ToleranceInput>,
+    job_id: Option<String>,
+) -> Result<ToleranceCalcResult, String> {
+    let input = input.into_inner();
+    run_job(app, "calculate_tolerance_stackup", job_id, None, move |_| Ok(compute_tolerance_stackup(input))).await
+}
+
+/// Stackup calculation; Monte Carlo runs make this worth keeping off the IPC thread
+pub fn compute_tolerance_stackup(input: ToleranceInput) -> ToleranceCalcResult {
     if input.links.is_empty() {
+        tracing::warn!("tolerance stackup requested without links");
         return ToleranceCalcResult {
             success: false,
             error: Some("No links provided".to_string()),
@@ -115,9 +256,15 @@ pub fn calculate_tolerance_stackup(input: ToleranceInput) -> ToleranceCalcResult
             rss: RssResult { min: 0.0, max: 0.0, tolerance: 0.0, sigma: 0.0 },
             monte_carlo: None,
             contributions: vec![],
+            measurement: None,
+            adjustment: None,
+            pareto: vec![],
+            waterfall: vec![],
         };
     }
 
+    tracing::info!(links = input.links.len(), samples = ?input.monte_carlo_samples, "calculating tolerance stackup");
+
     // Calculate total nominal
     let total_nominal: f64 = input.links.iter()
         .map(|link| {
@@ -158,6 +305,15 @@ pub fn calculate_tolerance_stackup(input: ToleranceInput) -> ToleranceCalcResult
         Some(run_monte_carlo(&input.links, 10000, input.target_spec.as_ref()))
     };
 
+    let measurement = input.gauge_sigma.zip(monte_carlo.as_ref()).map(|(gauge_sigma, mc)| {
+        measurement_system(mc.mean, mc.std_dev, gauge_sigma, input.target_spec.as_ref())
+    });
+
+    let adjustment = input.adjuster.as_ref().zip(input.target_spec.as_ref()).map(|(adjuster, spec)| {
+        let samples = input.monte_carlo_samples.unwrap_or(10000);
+        compute_adjustment(&input.links, adjuster, spec, samples)
+    });
+
     ToleranceCalcResult {
         success: true,
         error: None,
@@ -165,28 +321,173 @@ pub fn calculate_tolerance_stackup(input: ToleranceInput) -> ToleranceCalcResult
         worst_case,
         rss,
         monte_carlo,
+        measurement,
+        adjustment,
+        pareto: pareto(&contributions, &input.links),
+        waterfall: worst_case_waterfall(&input.links),
         contributions,
     }
 }
 
-/// Calculate worst-case stackup
-fn calculate_worst_case(links: &[LinkInput]) -> WorstCaseResult {
-    let mut total_min = 0.0;
-    let mut total_max = 0.0;
+/// Contributions sorted by variance share, with the running total
+pub fn pareto(contributions: &[ContributionResult], links: &[LinkInput]) -> Vec<ParetoEntry> {
+    let mut sorted: Vec<&ContributionResult> = contributions.iter().collect();
+    sorted.sort_by(|a, b| b.percent.total_cmp(&a.percent).then(a.index.cmp(&b.index)));
+    let mut cumulative = 0.0;
+    sorted.into_iter()
+        .map(|c| {
+            cumulative += c.percent;
+            ParetoEntry {
+                index: c.index,
+                name: links.get(c.index).and_then(|l| l.name.clone()),
+                percent: c.percent,
+                cumulative_percent: cumulative,
+            }
+        })
+        .collect()
+}
 
-    for link in links {
-        let sign = if link.direction == "negative" { -1.0 } else { 1.0 };
+/// Running worst-case min and max as each link is added
+pub fn worst_case_waterfall(links: &[LinkInput]) -> Vec<WaterfallStep> {
+    let (mut nominal, mut total_min, mut total_max) = (0.0, 0.0, 0.0);
+    links.iter().enumerate()
+        .map(|(index, link)| {
+            let (min, max) = link_bounds(link);
+            nominal += if link.direction == "negative" { -link.nominal } else { link.nominal };
+            total_min += min;
+            total_max += max;
+            WaterfallStep {
+                index,
+                name: link.name.clone(),
+                min,
+                max,
+                cumulative_nominal: nominal,
+                cumulative_min: total_min,
+                cumulative_max: total_max,
+            }
+        })
+        .collect()
+}
 
-        if sign > 0.0 {
-            // Positive direction: nominal - minus to nominal + plus
-            total_min += link.nominal - link.minus_tolerance;
-            total_max += link.nominal + link.plus_tolerance;
+/// Adjuster setting for a stack of the other links: ideal value, then snapped to a shim step and clamped to its range
+fn adjuster_setting(adjuster: &AdjusterInput, fixed_total: f64, target: f64) -> (f64, f64) {
+    let sign = if adjuster.direction == "negative" { -1.0 } else { 1.0 };
+    let ideal = sign * (target - fixed_total);
+    let mut setting = ideal;
+    if let Some(step) = adjuster.step {
+        let origin = adjuster.min.unwrap_or(0.0);
+        setting = origin + ((setting - origin) / step).round() * step;
+    }
+    if let Some(min) = adjuster.min {
+        setting = setting.max(min);
+    }
+    if let Some(max) = adjuster.max {
+        setting = setting.min(max);
+    }
+    (ideal, setting)
+}
+
+/// Required adjuster range and residual stack variation once the adjuster is set on each build
+pub fn compute_adjustment(links: &[LinkInput], adjuster: &AdjusterInput, spec: &TargetSpec, samples: usize) -> AdjustmentResult {
+    let sign = if adjuster.direction == "negative" { -1.0 } else { 1.0 };
+    let target = spec.nominal;
+    let fixed = calculate_worst_case(links);
+    let (low, high) = (adjuster_setting(adjuster, fixed.min, target).0, adjuster_setting(adjuster, fixed.max, target).0);
+
+    // Residual at the worst-case stacks, and half a shim step between them
+    let residual_at = |total: f64| total + sign * adjuster_setting(adjuster, total, target).1;
+    let half_step = adjuster.step.map(|s| s / 2.0).unwrap_or(0.0);
+    let ends = [residual_at(fixed.min), residual_at(fixed.max)];
+    let residual_min = ends[0].min(ends[1]).min(target - half_step);
+    let residual_max = ends[0].max(ends[1]).max(target + half_step);
+
+    let mut ideal: Vec<f64> = Vec::with_capacity(samples);
+    let mut residual: Vec<f64> = Vec::with_capacity(samples);
+    let mut out_of_range = 0usize;
+    for total in sample_stackup(links, samples) {
+        let (needed, setting) = adjuster_setting(adjuster, total, target);
+        let limited = adjuster.min.is_some_and(|m| needed < m) || adjuster.max.is_some_and(|m| needed > m);
+        out_of_range += limited as usize;
+        ideal.push(needed);
+        residual.push(total + sign * setting);
+    }
+    ideal.sort_by(|a, b| a.total_cmp(b));
+    residual.sort_by(|a, b| a.total_cmp(b));
+
+    AdjustmentResult {
+        required_min: low.min(high),
+        required_max: low.max(high),
+        required_p0_1: ideal[(samples as f64 * 0.001) as usize],
+        required_p99_9: ideal[(samples as f64 * 0.999).min((samples - 1) as f64) as usize],
+        out_of_range_fraction: out_of_range as f64 / samples as f64,
+        residual_worst_case: WorstCaseResult {
+            min: residual_min,
+            max: residual_max,
+            tolerance: (residual_max - residual_min) / 2.0,
+        },
+        residual: summarize_samples(residual, Some(spec)),
+    }
+}
+
+/// Lowest distance from the mean to a spec limit, in 3-sigma units
+pub(crate) fn cpk(mean: f64, sigma: f64, spec: &TargetSpec) -> f64 {
+    let upper_limit = spec.nominal + spec.plus_tolerance;
+    let lower_limit = spec.nominal - spec.minus_tolerance;
+    ((upper_limit - mean) / (3.0 * sigma)).min((mean - lower_limit) / (3.0 * sigma))
+}
+
+/// Gauge variation adds in quadrature to the process, so measured parts look less capable
+fn measurement_system(mean: f64, true_sigma: f64, gauge_sigma: f64, spec: Option<&TargetSpec>) -> MeasurementSystemResult {
+    let observed_variance = true_sigma.powi(2) + gauge_sigma.powi(2);
+    let observed_sigma = observed_variance.sqrt();
+    MeasurementSystemResult {
+        gauge_sigma,
+        true_sigma,
+        observed_sigma,
+        gauge_variance_percent: if observed_variance > 0.0 {
+            100.0 * gauge_sigma.powi(2) / observed_variance
         } else {
-            // Negative direction: -(nominal + plus) to -(nominal - minus)
-            total_min -= link.nominal + link.plus_tolerance;
-            total_max -= link.nominal - link.minus_tolerance;
-        }
+            0.0
+        },
+        true_cpk: spec.map(|s| cpk(mean, true_sigma, s)),
+        observed_cpk: spec.map(|s| cpk(mean, observed_sigma, s)),
+        precision_to_tolerance: spec
+            .map(|s| s.plus_tolerance + s.minus_tolerance)
+            .filter(|width| *width > 0.0)
+            .map(|width| 6.0 * gauge_sigma / width),
+    }
+}
+
+/// Signed min and max a link adds to the stack
+fn link_bounds(link: &LinkInput) -> (f64, f64) {
+    if link.direction == "negative" {
+        // Negative direction: -(nominal + plus) to -(nominal - minus)
+        (-(link.nominal + link.plus_tolerance), -(link.nominal - link.minus_tolerance))
+    } else {
+        // Positive direction: nominal - minus to nominal + plus
+        (link.nominal - link.minus_tolerance, link.nominal + link.plus_tolerance)
+    }
+}
+
+/// Contributor for the form error of a face (flatness band, roundness zone): 0 to `band`, skewed toward 0
+pub fn form_link(name: Option<String>, band: f64, direction: &str) -> LinkInput {
+    LinkInput {
+        name,
+        nominal: 0.0,
+        plus_tolerance: band,
+        minus_tolerance: 0.0,
+        direction: direction.to_string(),
+        distribution: "form".to_string(),
+        sigma: None,
+        inspected: false,
     }
+}
+
+/// Calculate worst-case stackup
+pub fn calculate_worst_case(links: &[LinkInput]) -> WorstCaseResult {
+    let (total_min, total_max) = links.iter()
+        .map(link_bounds)
+        .fold((0.0, 0.0), |(min, max), (lo, hi)| (min + lo, max + hi));
 
     WorstCaseResult {
         min: total_min,
@@ -196,19 +497,31 @@ fn calculate_worst_case(links: &[LinkInput]) -> WorstCaseResult {
 }
 
 /// Calculate RSS (Root Sum Square) stackup
-fn calculate_rss(links: &[LinkInput]) -> (RssResult, Vec<f64>) {
+pub fn calculate_rss(links: &[LinkInput]) -> (RssResult, Vec<f64>) {
     let mut total_nominal = 0.0;
     let mut variances: Vec<f64> = Vec::new();
 
     for link in links {
         let sign = if link.direction == "negative" { -1.0 } else { 1.0 };
-        total_nominal += sign * link.nominal;
 
         // Calculate variance based on distribution
         let total_tol = link.plus_tolerance + link.minus_tolerance;
         let sigma = link.sigma.unwrap_or(3.0);
 
+        // A form deviation sits above the lower limit: centre on the Rayleigh mean, scale * sqrt(pi/2)
+        let mean = if link.distribution == "form" {
+            link.nominal - link.minus_tolerance + total_tol / sigma * (std::f64::consts::PI / 2.0).sqrt()
+        } else {
+            link.nominal
+        };
+        total_nominal += sign * mean;
+
         let variance = match link.distribution.as_str() {
+            "normal" if link.inspected => {
+                // Normal truncated at ±k sigma: the screened tails no longer contribute
+                let half_tol = total_tol / 2.0;
+                (half_tol / sigma).powi(2) * truncated_variance_factor(sigma)
+            }
             "normal" => {
                 // For normal distribution, tolerance = k*sigma
                 // Variance = (tolerance / k)^2
@@ -219,6 +532,11 @@ fn calculate_rss(links: &[LinkInput]) -> (RssResult, Vec<f64>) {
                 // For uniform distribution, variance = (range)^2 / 12
                 total_tol.powi(2) / 12.0
             }
+            "form" => {
+                // Rayleigh with scale band / k; the truncation at the band is ignored
+                let scale = total_tol / sigma;
+                (4.0 - std::f64::consts::PI) / 2.0 * scale.powi(2)
+            }
             _ => {
                 // Default to normal
                 let half_tol = total_tol / 2.0;
@@ -243,8 +561,20 @@ fn calculate_rss(links: &[LinkInput]) -> (RssResult, Vec<f64>) {
     }, variances)
 }
 
+/// Variance of a standard normal truncated at ±k, relative to the untruncated one
+fn truncated_variance_factor(k: f64) -> f64 {
+    let density = (-0.5 * k * k).exp() / (2.0 * std::f64::consts::PI).sqrt();
+    let mass = 2.0 * crate::fit_statistics::standard_normal_cdf(k) - 1.0;
+    if mass > 0.0 { 1.0 - 2.0 * k * density / mass } else { 1.0 }
+}
+
 /// Run Monte Carlo simulation
 fn run_monte_carlo(links: &[LinkInput], samples: usize, target_spec: Option<&TargetSpec>) -> MonteCarloResult {
+    summarize_samples(sample_stackup(links, samples), target_spec)
+}
+
+/// Simulated stackup totals, sorted ascending
+pub fn sample_stackup(links: &[LinkInput], samples: usize) -> Vec<f64> {
     let mut rng = rand::thread_rng();
     let mut results: Vec<f64> = Vec::with_capacity(samples);
 
@@ -254,26 +584,7 @@ fn run_monte_carlo(links: &[LinkInput], samples: usize, target_spec: Option<&Tar
 
         for link in links {
             let sign = if link.direction == "negative" { -1.0 } else { 1.0 };
-            let nominal = link.nominal;
-            let plus = link.plus_tolerance;
-            let minus = link.minus_tolerance;
-            let sigma = link.sigma.unwrap_or(3.0);
-
-            let sample = match link.distribution.as_str() {
-                "uniform" => {
-                    let uniform = Uniform::new(nominal - minus, nominal + plus);
-                    uniform.sample(&mut rng)
-                }
-                _ => {
-                    // Normal distribution
-                    let mean = nominal + (plus - minus) / 2.0;  // Adjust for asymmetric tolerance
-                    let std = (plus + minus) / (2.0 * sigma);
-                    let normal = Normal::new(mean, std).unwrap_or(Normal::new(mean, 0.001).unwrap());
-                    normal.sample(&mut rng)
-                }
-            };
-
-            total += sign * sample;
+            total += sign * sample_link(link, &mut rng);
         }
 
         results.push(total);
@@ -281,6 +592,57 @@ fn run_monte_carlo(links: &[LinkInput], samples: usize, target_spec: Option<&Tar
 
     // Sort for percentile calculation
     results.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    results
+}
+
+/// One simulated value of a link, before its direction is applied
+pub fn sample_link<R: Rng + ?Sized>(link: &LinkInput, rng: &mut R) -> f64 {
+    let nominal = link.nominal;
+    let plus = link.plus_tolerance;
+    let minus = link.minus_tolerance;
+    let sigma = link.sigma.unwrap_or(3.0);
+
+    match link.distribution.as_str() {
+        "uniform" => {
+            // Uniform::new panics on an empty range; a link without tolerance is just its nominal
+            if plus + minus <= 0.0 {
+                return nominal;
+            }
+            let uniform = Uniform::new(nominal - minus, nominal + plus);
+            uniform.sample(rng)
+        }
+        "form" => {
+            // Form deviation is a non-negative magnitude: Rayleigh, truncated at the band by inverting its CDF
+            let band = plus + minus;
+            let scale = band / sigma;
+            if scale <= 0.0 {
+                return nominal - minus;
+            }
+            let covered = 1.0 - (-0.5 * sigma * sigma).exp();
+            let u: f64 = rng.gen::<f64>() * covered;
+            nominal - minus + scale * (-2.0 * (1.0 - u).ln()).sqrt()
+        }
+        _ => {
+            // Normal distribution
+            let mean = nominal + (plus - minus) / 2.0;  // Adjust for asymmetric tolerance
+            let std = (plus + minus) / (2.0 * sigma);
+            let normal = Normal::new(mean, std).unwrap_or(Normal::new(mean, 0.001).unwrap());
+            if !link.inspected {
+                return normal.sample(rng);
+            }
+            // Screened parts: redraw until within the drawing limits
+            let (low, high) = (nominal - minus, nominal + plus);
+            (0..MAX_SCREENING_DRAWS)
+                .map(|_| normal.sample(rng))
+                .find(|v| *v >= low && *v <= high)
+                .unwrap_or(mean)
+        }
+    }
+}
+
+/// Statistics, percentiles and histogram of sorted samples
+pub fn summarize_samples(results: Vec<f64>, target_spec: Option<&TargetSpec>) -> MonteCarloResult {
+    let samples = results.len();
 
     // Calculate statistics
     let mean: f64 = results.iter().sum::<f64>() / samples as f64;
@@ -292,11 +654,7 @@ fn run_monte_carlo(links: &[LinkInput], samples: usize, target_spec: Option<&Tar
 
     // Calculate Cpk
     let cpk = if let Some(spec) = target_spec {
-        let upper_limit = spec.nominal + spec.plus_tolerance;
-        let lower_limit = spec.nominal - spec.minus_tolerance;
-        let cpu = (upper_limit - mean) / (3.0 * std_dev);
-        let cpl = (mean - lower_limit) / (3.0 * std_dev);
-        cpu.min(cpl)
+        cpk(mean, std_dev, spec)
     } else {
         // Use ±3sigma as spec limits
         1.0
@@ -351,12 +709,14 @@ mod tests {
     #[test]
     fn test_worst_case_single_link() {
         let links = vec![LinkInput {
+            name: None,
             nominal: 10.0,
             plus_tolerance: 0.1,
             minus_tolerance: 0.1,
             direction: "positive".to_string(),
             distribution: "normal".to_string(),
             sigma: Some(3.0),
+            inspected: false,
         }];
 
         let result = calculate_worst_case(&links);
@@ -368,20 +728,24 @@ mod tests {
     fn test_worst_case_stack() {
         let links = vec![
             LinkInput {
+                name: None,
                 nominal: 10.0,
                 plus_tolerance: 0.1,
                 minus_tolerance: 0.1,
                 direction: "positive".to_string(),
                 distribution: "normal".to_string(),
                 sigma: Some(3.0),
+                inspected: false,
             },
             LinkInput {
+                name: None,
                 nominal: 5.0,
                 plus_tolerance: 0.05,
                 minus_tolerance: 0.05,
                 direction: "positive".to_string(),
                 distribution: "normal".to_string(),
                 sigma: Some(3.0),
+                inspected: false,
             },
         ];
 
@@ -393,15 +757,174 @@ mod tests {
     #[test]
     fn test_monte_carlo() {
         let links = vec![LinkInput {
+            name: None,
             nominal: 10.0,
             plus_tolerance: 0.1,
             minus_tolerance: 0.1,
             direction: "positive".to_string(),
             distribution: "normal".to_string(),
             sigma: Some(3.0),
+            inspected: false,
         }];
 
         let result = run_monte_carlo(&links, 1000, None);
         assert!((result.mean - 10.0).abs() < 0.1);  // Mean should be close to nominal
     }
+
+    #[test]
+    fn test_gauge_contribution() {
+        let spec = TargetSpec { nominal: 10.0, plus_tolerance: 0.1, minus_tolerance: 0.1 };
+        let result = measurement_system(10.0, 0.03, 0.04, Some(&spec));
+        assert!((result.observed_sigma - 0.05).abs() < 1e-12);
+        assert!((result.gauge_variance_percent - 64.0).abs() < 1e-9);
+        assert!((result.true_cpk.unwrap() - 0.1 / 0.09).abs() < 1e-9);
+        assert!((result.observed_cpk.unwrap() - 0.1 / 0.15).abs() < 1e-9);
+        assert!((result.precision_to_tolerance.unwrap() - 1.2).abs() < 1e-9);
+
+        let unspecified = measurement_system(10.0, 0.03, 0.0, None);
+        assert!((unspecified.observed_sigma - 0.03).abs() < 1e-12);
+        assert!(unspecified.true_cpk.is_none());
+    }
+
+    #[test]
+    fn test_shim_compensates_stack() {
+        // Housing depth minus two parts leaves a gap closed with 0.1 mm shims from 0.5 to 1.5 mm
+        let link = |nominal: f64, direction: &str| LinkInput {
+            name: None,
+            nominal,
+            plus_tolerance: 0.2,
+            minus_tolerance: 0.2,
+            direction: direction.to_string(),
+            distribution: "uniform".to_string(),
+            sigma: None,
+            inspected: false,
+        };
+        let links = vec![link(50.0, "positive"), link(20.0, "negative"), link(29.0, "negative")];
+        let shim = AdjusterInput { name: None, direction: "negative".to_string(), min: Some(0.5), max: Some(1.5), step: Some(0.1) };
+        let spec = TargetSpec { nominal: 0.0, plus_tolerance: 0.1, minus_tolerance: 0.1 };
+
+        let result = compute_adjustment(&links, &shim, &spec, 20_000);
+        assert!((result.required_min - 0.4).abs() < 1e-9 && (result.required_max - 1.6).abs() < 1e-9);
+        assert!(result.out_of_range_fraction > 0.0 && result.out_of_range_fraction < 0.05);
+        // Without clamping the gap would stay within half a shim step
+        assert!(result.residual.percentiles.p1 >= -0.05 - 1e-9 && result.residual.percentiles.p99 <= 0.05 + 1e-9);
+        assert!((result.residual_worst_case.max - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pareto_and_waterfall() {
+        let link = |name: &str, nominal: f64, tolerance: f64, direction: &str| LinkInput {
+            name: Some(name.to_string()),
+            nominal,
+            plus_tolerance: tolerance,
+            minus_tolerance: tolerance,
+            direction: direction.to_string(),
+            distribution: "normal".to_string(),
+            sigma: Some(3.0),
+            inspected: false,
+        };
+        let links = vec![link("housing", 30.0, 0.1, "positive"), link("shaft", 29.0, 0.3, "negative"), link("clip", 0.5, 0.2, "negative")];
+        let (_, variances) = calculate_rss(&links);
+        let total: f64 = variances.iter().sum();
+        let contributions: Vec<ContributionResult> = variances.iter().enumerate()
+            .map(|(index, v)| ContributionResult { index, nominal_contribution: 0.0, variance_contribution: *v, percent: 100.0 * v / total })
+            .collect();
+
+        let bars = pareto(&contributions, &links);
+        assert_eq!(bars.iter().map(|b| b.index).collect::<Vec<_>>(), vec![1, 2, 0]);
+        assert_eq!(bars[0].name.as_deref(), Some("shaft"));
+        assert!((bars[2].cumulative_percent - 100.0).abs() < 1e-9);
+
+        let steps = worst_case_waterfall(&links);
+        let last = steps.last().unwrap();
+        let wc = calculate_worst_case(&links);
+        assert!((last.cumulative_min - wc.min).abs() < 1e-12 && (last.cumulative_max - wc.max).abs() < 1e-12);
+        assert!((steps[1].cumulative_nominal - 1.0).abs() < 1e-12);
+        assert!((steps[1].min - -29.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_inspected_link_stays_within_limits() {
+        // Sigma 1.5 leaves 13% of parts outside ±0.1; screening removes them
+        let link = LinkInput {
+            name: None,
+            nominal: 10.0,
+            plus_tolerance: 0.1,
+            minus_tolerance: 0.1,
+            direction: "positive".to_string(),
+            distribution: "normal".to_string(),
+            sigma: Some(1.5),
+            inspected: true,
+        };
+        let samples = sample_stackup(std::slice::from_ref(&link), 20_000);
+        assert!(samples[0] >= 9.9 && samples[samples.len() - 1] <= 10.1);
+
+        let (screened, _) = calculate_rss(std::slice::from_ref(&link));
+        let (unscreened, _) = calculate_rss(&[LinkInput { inspected: false, ..link }]);
+        assert!(screened.sigma < unscreened.sigma);
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let simulated = (samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt();
+        assert!((simulated - screened.sigma).abs() < 0.002, "{} vs {}", simulated, screened.sigma);
+    }
+
+    #[test]
+    fn test_form_band_is_bounded_and_skewed() {
+        let link = form_link(Some("flatness".to_string()), 0.05, "positive");
+        let samples = sample_stackup(std::slice::from_ref(&link), 20_000);
+        assert!(samples[0] >= 0.0 && samples[samples.len() - 1] <= 0.05);
+        // Rayleigh with scale band/3: mean ≈ 1.2533 * 0.01667, well below the band centre
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        assert!((mean - 0.05 / 3.0 * 1.2533).abs() < 0.001, "{}", mean);
+
+        let (rss, _) = calculate_rss(std::slice::from_ref(&link));
+        assert!((rss.sigma - 0.05 / 3.0 * 0.6551).abs() < 1e-4);
+        let wc = calculate_worst_case(&[link]);
+        assert_eq!((wc.min, wc.max), (0.0, 0.05));
+    }
+
+    #[test]
+    fn test_rss_centre_matches_monte_carlo_mean_with_form_link() {
+        let links = vec![
+            LinkInput {
+                name: None,
+                nominal: 10.0,
+                plus_tolerance: 0.1,
+                minus_tolerance: 0.1,
+                direction: "positive".to_string(),
+                distribution: "normal".to_string(),
+                sigma: Some(3.0),
+                inspected: false,
+            },
+            form_link(Some("flatness".to_string()), 0.3, "negative"),
+        ];
+        let (rss, _) = calculate_rss(&links);
+        let rss_centre = (rss.min + rss.max) / 2.0;
+        assert!((rss_centre - (10.0 - 0.1 * 1.2533)).abs() < 1e-4, "{}", rss_centre);
+
+        // The sampled band is truncated at 3 scale, which pulls its mean in by about 0.002
+        let mc = run_monte_carlo(&links, 20_000, None);
+        assert!((mc.mean - rss_centre).abs() < 0.006, "{} vs {}", mc.mean, rss_centre);
+    }
+
+    #[test]
+    fn test_zero_width_uniform_link_samples_nominal() {
+        let link = LinkInput {
+            name: None,
+            nominal: 4.0,
+            plus_tolerance: 0.0,
+            minus_tolerance: 0.0,
+            direction: "positive".to_string(),
+            distribution: "uniform".to_string(),
+            sigma: None,
+            inspected: false,
+        };
+        let mut errors = FieldErrors::default();
+        validate_link(&link, "links[0]", &mut errors);
+        assert!(errors.into_result().is_ok());
+
+        let mut rng = rand::thread_rng();
+        assert_eq!(sample_link(&link, &mut rng), 4.0);
+        assert!(run_monte_carlo(&[link], 100, None).std_dev.abs() < 1e-12);
+    }
 }
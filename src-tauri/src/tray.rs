@@ -0,0 +1,148 @@
+// System tray with quick-capture and quick-analyze actions
+
+use serde::{Deserialize, Serialize};
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{App, AppHandle, Emitter, Manager};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::recent_files::RecentFilesState;
+use crate::{CapturedImage, StepAnalysisResult, CAPTURED_IMAGE_EVENT};
+
+/// Event carrying a quick-analysis result
+pub const TRAY_ANALYSIS_EVENT: &str = "tray-analysis";
+
+/// Event carrying a tray action failure
+pub const TRAY_ERROR_EVENT: &str = "tray-error";
+
+const MENU_CAPTURE: &str = "capture_screen";
+const MENU_CLIPBOARD: &str = "analyze_clipboard";
+const MENU_LAST_FILE: &str = "analyze_last_file";
+const MENU_ACTIVE_DOCUMENT: &str = "analyze_active_document";
+const MENU_SHOW: &str = "show_window";
+const MENU_QUIT: &str = "quit";
+
+/// Analysis routed from the tray to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrayAnalysis {
+    pub path: String,
+    pub result: StepAnalysisResult,
+}
+
+/// Build the tray icon and its menu
+pub fn setup_tray(app: &App) -> tauri::Result<()> {
+    let capture = MenuItem::with_id(app, MENU_CAPTURE, "Capture Screen", true, None::<&str>)?;
+    let clipboard = MenuItem::with_id(app, MENU_CLIPBOARD, "Analyze Clipboard Image", true, None::<&str>)?;
+    let last_file = MenuItem::with_id(app, MENU_LAST_FILE, "Analyze Last File", true, None::<&str>)?;
+    let active = MenuItem::with_id(app, MENU_ACTIVE_DOCUMENT, "Analyze Active CAD Part", true, None::<&str>)?;
+    let show = MenuItem::with_id(app, MENU_SHOW, "Show Copilot", true, None::<&str>)?;
+    let quit = MenuItem::with_id(app, MENU_QUIT, "Quit", true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(app)?;
+    let separator_2 = PredefinedMenuItem::separator(app)?;
+
+    let menu = Menu::with_items(
+        app,
+        &[&capture, &clipboard, &last_file, &active, &separator, &show, &separator_2, &quit],
+    )?;
+
+    let mut builder = TrayIconBuilder::with_id("main")
+        .tooltip("Ohmframe Copilot")
+        .menu(&menu)
+        .show_menu_on_left_click(true)
+        .on_menu_event(handle_menu_event);
+
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+
+    builder.build(app)?;
+    Ok(())
+}
+
+/// Dispatch a tray menu action; slow work runs off the event loop
+fn handle_menu_event(app: &AppHandle, event: MenuEvent) {
+    let action = event.id().as_ref().to_string();
+    tracing::info!(action = %action, "tray action");
+
+    match action.as_str() {
+        MENU_SHOW => show_main_window(app),
+        MENU_QUIT => app.exit(0),
+        MENU_CAPTURE | MENU_CLIPBOARD | MENU_LAST_FILE | MENU_ACTIVE_DOCUMENT => {
+            let app = app.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                let outcome = match action.as_str() {
+                    MENU_CAPTURE => quick_capture(&app),
+                    MENU_CLIPBOARD => quick_clipboard(&app),
+                    MENU_ACTIVE_DOCUMENT => quick_analyze_active_document(&app),
+                    _ => quick_analyze_last_file(&app),
+                };
+                if let Err(e) = outcome {
+                    tracing::warn!(action = %action, error = %e, "tray action failed");
+                    let _ = app.emit(TRAY_ERROR_EVENT, e);
+                }
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Bring the main window to the front
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Capture the screen and send it to the conversation
+fn quick_capture(app: &AppHandle) -> Result<(), String> {
+    let image = CapturedImage {
+        source: "screen".to_string(),
+        trigger: "tray".to_string(),
+        image_base64: crate::capture_screen()?,
+    };
+
+    show_main_window(app);
+    app.emit(CAPTURED_IMAGE_EVENT, image)
+        .map_err(|e| format!("Failed to deliver capture: {}", e))
+}
+
+/// Send the clipboard image (e.g. a CAD screenshot) to the conversation
+fn quick_clipboard(app: &AppHandle) -> Result<(), String> {
+    let image = app.clipboard()
+        .read_image()
+        .map_err(|e| format!("Clipboard does not contain an image: {}", e))?;
+    let image = CapturedImage {
+        source: "clipboard".to_string(),
+        trigger: "tray".to_string(),
+        image_base64: crate::encode_rgba_png_base64(image.width(), image.height(), image.rgba().to_vec())?,
+    };
+
+    show_main_window(app);
+    app.emit(CAPTURED_IMAGE_EVENT, image)
+        .map_err(|e| format!("Failed to deliver clipboard image: {}", e))
+}
+
+/// Re-run the STEP analysis on the most recently opened file
+fn quick_analyze_last_file(app: &AppHandle) -> Result<(), String> {
+    let recent = app.try_state::<RecentFilesState>()
+        .and_then(|state| state.most_recent())
+        .ok_or("No recent file to analyze")?;
+
+    let result = crate::analyze_step_file(recent.path.clone());
+    show_main_window(app);
+    app.emit(TRAY_ANALYSIS_EVENT, TrayAnalysis { path: recent.path, result })
+        .map_err(|e| format!("Failed to deliver analysis: {}", e))
+}
+
+/// Analyze the part open in the user's CAD application
+fn quick_analyze_active_document(app: &AppHandle) -> Result<(), String> {
+    let (document, path) = crate::active_document::active_step_file(app)?;
+    tracing::info!(document = %document.name, path = %path, "tray analyzing active CAD document");
+
+    let result = crate::analyze_step_file(path.clone());
+    show_main_window(app);
+    app.emit(TRAY_ANALYSIS_EVENT, TrayAnalysis { path, result })
+        .map_err(|e| format!("Failed to deliver analysis: {}", e))
+}
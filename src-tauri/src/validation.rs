@@ -0,0 +1,155 @@
+// Field-level validation of command payloads, so bad input names the field at fault
+
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer};
+use std::ops::Deref;
+
+/// Range and enum checks run after a payload deserializes
+pub trait Validate {
+    fn validate(&self, errors: &mut FieldErrors);
+}
+
+/// Problems found in a payload, each prefixed with the path of its field
+#[derive(Debug, Default)]
+pub struct FieldErrors {
+    errors: Vec<String>,
+}
+
+impl FieldErrors {
+    /// Record a problem with a field
+    pub fn add(&mut self, field: impl AsRef<str>, message: impl AsRef<str>) {
+        self.errors.push(format!("{}: {}", field.as_ref(), message.as_ref()));
+    }
+
+    /// Value must be a finite number
+    pub fn finite(&mut self, field: impl AsRef<str>, value: f64) -> bool {
+        let ok = value.is_finite();
+        if !ok {
+            self.add(field, format!("expected a finite number, got {}", value));
+        }
+        ok
+    }
+
+    /// Value must be finite and at least `min`
+    pub fn at_least(&mut self, field: impl AsRef<str>, value: f64, min: f64) {
+        if self.finite(field.as_ref(), value) && value < min {
+            self.add(field, format!("expected a value >= {}, got {}", min, value));
+        }
+    }
+
+    /// Value must be finite and strictly above `min`
+    pub fn above(&mut self, field: impl AsRef<str>, value: f64, min: f64) {
+        if self.finite(field.as_ref(), value) && value <= min {
+            self.add(field, format!("expected a value > {}, got {}", min, value));
+        }
+    }
+
+    /// Value must be finite and within `min..=max`
+    pub fn within(&mut self, field: impl AsRef<str>, value: f64, min: f64, max: f64) {
+        if self.finite(field.as_ref(), value) && !(min..=max).contains(&value) {
+            self.add(field, format!("expected a value between {} and {}, got {}", min, max, value));
+        }
+    }
+
+    /// Value must be one of the listed options
+    pub fn one_of(&mut self, field: impl AsRef<str>, value: &str, options: &[&str]) {
+        if !options.contains(&value) {
+            let expected = options.iter().map(|o| format!("\"{}\"", o)).collect::<Vec<_>>().join(" or ");
+            self.add(field, format!("expected {}, got \"{}\"", expected, value));
+        }
+    }
+
+    /// Ok when nothing was recorded, otherwise every problem joined into one message
+    pub fn into_result(self) -> Result<(), String> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors.join("; "))
+        }
+    }
+}
+
+/// Run a value's checks
+pub fn validate<T: Validate>(value: &T) -> Result<(), String> {
+    let mut errors = FieldErrors::default();
+    value.validate(&mut errors);
+    errors.into_result()
+}
+
+/// Deserialize a JSON value, reporting the path of the first field that does not fit
+pub fn from_value<T: DeserializeOwned + Validate>(value: serde_json::Value) -> Result<T, String> {
+    let parsed: T = serde_path_to_error::deserialize(value).map_err(describe_path_error)?;
+    validate(&parsed)?;
+    Ok(parsed)
+}
+
+fn describe_path_error<E: std::fmt::Display>(error: serde_path_to_error::Error<E>) -> String {
+    let path = error.path().to_string();
+    if path == "." {
+        error.inner().to_string()
+    } else {
+        format!("{}: {}", path, error.inner())
+    }
+}
+
+/// Command argument that fails with field-level messages instead of a bare serde error
+#[derive(Debug, Clone)]
+pub struct Checked<T>(pub T);
+
+impl<T> Checked<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for Checked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<'de, T: Deserialize<'de> + Validate> Deserialize<'de> for Checked<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let parsed: T = serde_path_to_error::deserialize(deserializer).map_err(|e| D::Error::custom(describe_path_error(e)))?;
+        validate(&parsed).map_err(D::Error::custom)?;
+        Ok(Checked(parsed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface_detection::DetectionParams;
+    use crate::tolerance_calc::ToleranceInput;
+    use serde_json::json;
+
+    fn link(nominal: serde_json::Value) -> serde_json::Value {
+        json!({ "nominal": nominal, "plus_tolerance": 0.1, "minus_tolerance": 0.1, "direction": "positive", "distribution": "normal", "sigma": null })
+    }
+
+    #[test]
+    fn test_field_level_errors() {
+        let ok = json!({ "links": [link(json!(10.0))], "monte_carlo_samples": 1000, "target_spec": null });
+        assert!(from_value::<ToleranceInput>(ok).is_ok());
+
+        let wrong_type = json!({ "links": [link(json!(10.0)), link(json!("ten"))], "monte_carlo_samples": null, "target_spec": null });
+        let err = from_value::<ToleranceInput>(wrong_type).unwrap_err();
+        assert!(err.starts_with("links[1].nominal: invalid type: string \"ten\", expected f64"), "{}", err);
+
+        let mut bad = link(json!(5.0));
+        bad["minus_tolerance"] = json!(-0.2);
+        bad["direction"] = json!("up");
+        let err = from_value::<ToleranceInput>(json!({ "links": [bad], "monte_carlo_samples": 0, "target_spec": null })).unwrap_err();
+        assert!(err.contains("links[0].minus_tolerance: expected a value >= 0, got -0.2"), "{}", err);
+        assert!(err.contains("links[0].direction: expected \"positive\" or \"negative\", got \"up\""), "{}", err);
+        assert!(err.contains("monte_carlo_samples"), "{}", err);
+
+        let checked: Result<Checked<DetectionParams>, _> =
+            serde_json::from_value(json!({ "proximity_threshold": 2.0, "normal_threshold": 1.5, "min_contact_area": 1.0 }));
+        assert!(checked.unwrap_err().to_string().contains("normal_threshold: expected a value between 0 and 1, got 1.5"));
+        let missing: Result<Checked<DetectionParams>, _> = serde_json::from_value(json!({ "proximity_threshold": 2.0 }));
+        assert!(missing.unwrap_err().to_string().contains("missing field `normal_threshold`"));
+    }
+}
@@ -0,0 +1,280 @@
+// Push finished reports and stackup results to Slack, Teams or in-house webhooks
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, State};
+use tauri_plugin_http::reqwest;
+
+use crate::credentials::{delete_secret, load_secret, store_secret};
+use crate::jobs::{run_async_job, JobLogLevel};
+use crate::model_store::ModelStore;
+use crate::report::{build_report, render_pdf, AssemblyReport};
+use crate::report_templates::{ReportTemplate, ReportTemplateStore};
+use crate::tolerance_calc::ToleranceCalcResult;
+use crate::storage::{load_json, save_json};
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How the payload is shaped for the receiving end
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookKind {
+    Slack,      // Incoming webhook; message text only
+    Teams,      // Incoming webhook; message text only
+    Generic,    // In-house endpoint; receives the full report as JSON or PDF
+}
+
+/// A configured webhook; the URL is kept in the keychain since it usually embeds a token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: String,
+    pub name: String,
+    pub kind: WebhookKind,
+}
+
+/// Webhook as listed to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookInfo {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub has_url: bool,
+}
+
+/// What is sent for generic endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookPayloadFormat {
+    Json,
+    Pdf,
+}
+
+/// Report to push
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookPushRequest {
+    pub handle: String,
+    pub format: WebhookPayloadFormat,
+    pub title: Option<String>,
+    pub template: Option<String>,              // Used for PDF output
+    pub stackup: Option<ToleranceCalcResult>,  // Stackup the report is about, if any
+}
+
+/// Outcome of a push
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPushResult {
+    pub webhook_id: String,
+    pub status: u16,
+    pub bytes: usize,
+}
+
+/// JSON body for generic endpoints
+#[derive(Debug, Serialize)]
+struct ReportPayload<'a> {
+    event: &'static str,
+    report: &'a AssemblyReport,
+    stackup: Option<&'a ToleranceCalcResult>,
+}
+
+/// Managed state holding the configured webhooks
+pub struct WebhookStore {
+    path: PathBuf,
+    webhooks: Mutex<Vec<Webhook>>,
+}
+
+impl WebhookStore {
+    /// Load webhooks from disk (empty if missing)
+    pub fn load(path: PathBuf) -> Self {
+        let webhooks: Vec<Webhook> = load_json(&path);
+        WebhookStore { path, webhooks: Mutex::new(webhooks) }
+    }
+
+    fn find(&self, id: &str) -> Result<Webhook, String> {
+        let webhooks = self.webhooks.lock().map_err(|_| "Webhook state poisoned".to_string())?;
+        webhooks.iter().find(|w| w.id == id).cloned().ok_or_else(|| format!("Unknown webhook '{}'", id))
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut Vec<Webhook>) -> T) -> Result<T, String> {
+        let mut webhooks = self.webhooks.lock().map_err(|_| "Webhook state poisoned".to_string())?;
+        let result = f(&mut webhooks);
+        save_json(&self.path, &*webhooks)?;
+        Ok(result)
+    }
+}
+
+fn secret_name(webhook_id: &str) -> String {
+    format!("webhook-{}", webhook_id)
+}
+
+/// Chat message summarizing a report and its stackup
+pub fn summary_text(report: &AssemblyReport, stackup: Option<&ToleranceCalcResult>) -> String {
+    let mut lines = vec![
+        format!("*{}*", report.title),
+        format!(
+            "{} - {} parts, {} interfaces, {} interferences",
+            report.filename,
+            report.parts.len(),
+            report.interfaces.len(),
+            report.interferences.len()
+        ),
+    ];
+    if let Some(stackup) = stackup.filter(|s| s.success) {
+        lines.push(format!(
+            "Stackup nominal {:.3} mm | worst case {:.3} to {:.3} | RSS {:.3} to {:.3}",
+            stackup.total_nominal, stackup.worst_case.min, stackup.worst_case.max, stackup.rss.min, stackup.rss.max
+        ));
+        if let Some(mc) = &stackup.monte_carlo {
+            lines.push(format!("Monte Carlo mean {:.3}, std dev {:.4}, Cpk {:.2}", mc.mean, mc.std_dev, mc.cpk));
+        }
+    }
+    lines.push(format!("Generated {}", report.generated_at));
+    lines.join("\n")
+}
+
+/// Configured webhooks
+#[tauri::command]
+pub fn list_webhooks(store: State<'_, WebhookStore>) -> Result<Vec<WebhookInfo>, String> {
+    let webhooks = store.webhooks.lock().map_err(|_| "Webhook state poisoned".to_string())?.clone();
+    webhooks.into_iter()
+        .map(|webhook| {
+            let has_url = load_secret(&secret_name(&webhook.id))?.is_some();
+            Ok(WebhookInfo { webhook, has_url })
+        })
+        .collect()
+}
+
+/// Add or replace a webhook; `url` replaces the stored URL when given
+#[tauri::command]
+pub fn save_webhook(store: State<'_, WebhookStore>, webhook: Webhook, url: Option<String>) -> Result<Webhook, String> {
+    if webhook.id.trim().is_empty() || webhook.name.trim().is_empty() {
+        return Err("Webhook id and name are required".to_string());
+    }
+    if let Some(url) = url.as_deref().map(str::trim) {
+        let parsed = url::Url::parse(url).map_err(|e| format!("Invalid webhook URL: {}", e))?;
+        if parsed.scheme() != "https" && parsed.scheme() != "http" {
+            return Err("Webhooks must use http or https".to_string());
+        }
+        store_secret(&secret_name(&webhook.id), url)?;
+    }
+
+    tracing::info!(webhook = %webhook.id, kind = ?webhook.kind, "saving webhook");
+    let saved = webhook.clone();
+    store.update(move |webhooks| match webhooks.iter_mut().find(|w| w.id == webhook.id) {
+        Some(existing) => *existing = webhook,
+        None => webhooks.push(webhook),
+    })?;
+    Ok(saved)
+}
+
+/// Remove a webhook and its stored URL
+#[tauri::command]
+pub fn delete_webhook(store: State<'_, WebhookStore>, id: String) -> Result<bool, String> {
+    delete_secret(&secret_name(&id))?;
+    store.update(|webhooks| {
+        let before = webhooks.len();
+        webhooks.retain(|w| w.id != id);
+        webhooks.len() != before
+    })
+}
+
+/// Build a report for a loaded model and post it to a webhook
+#[tauri::command]
+pub async fn push_report_to_webhook(
+    app: AppHandle,
+    models: State<'_, ModelStore>,
+    templates: State<'_, ReportTemplateStore>,
+    store: State<'_, WebhookStore>,
+    webhook_id: String,
+    request: WebhookPushRequest,
+    job_id: Option<String>,
+) -> Result<WebhookPushResult, String> {
+    let webhook = store.find(&webhook_id)?;
+    let url = load_secret(&secret_name(&webhook.id))?.ok_or_else(|| format!("No URL stored for {}", webhook.name))?;
+    if webhook.kind != WebhookKind::Generic && request.format == WebhookPayloadFormat::Pdf {
+        return Err(format!("{} webhooks only accept messages; send JSON instead of PDF", webhook.name));
+    }
+
+    let report = models.with_model(&request.handle, |model| build_report(model, request.title, Vec::new()))?;
+    let (body, content_type) = match (webhook.kind, request.format) {
+        (WebhookKind::Slack | WebhookKind::Teams, _) => {
+            let text = summary_text(&report, request.stackup.as_ref());
+            (serde_json::json!({ "text": text }).to_string().into_bytes(), "application/json")
+        }
+        (WebhookKind::Generic, WebhookPayloadFormat::Json) => {
+            let payload = ReportPayload { event: "assembly_report", report: &report, stackup: request.stackup.as_ref() };
+            let body = serde_json::to_vec(&payload).map_err(|e| format!("Failed to serialize report: {}", e))?;
+            (body, "application/json")
+        }
+        (WebhookKind::Generic, WebhookPayloadFormat::Pdf) => {
+            let template = match &request.template {
+                Some(name) => templates.load(name)?,
+                None => ReportTemplate::default(),
+            };
+            (render_pdf(&report, &template)?, "application/pdf")
+        }
+    };
+
+    let label = Some(webhook.name.clone());
+    let filename = format!("{}.pdf", report.filename);
+    run_async_job(app, "push_report_to_webhook", job_id, label, |job| async move {
+        let bytes = body.len();
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let mut post = client.post(&url).header(reqwest::header::CONTENT_TYPE, content_type).body(body);
+        if content_type == "application/pdf" {
+            post = post.header(reqwest::header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename));
+        }
+
+        let response = post.send().await.map_err(|e| format!("Posting to {} failed: {}", webhook.name, e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("{} rejected the report with {}", webhook.name, status));
+        }
+        job.log(JobLogLevel::Info, format!("Posted {} bytes to {}", bytes, webhook.name));
+        Ok(WebhookPushResult { webhook_id: webhook.id, status: status.as_u16(), bytes })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tolerance_calc::{RssResult, WorstCaseResult};
+
+    #[test]
+    fn test_summary_text() {
+        let report = AssemblyReport {
+            title: "Gearbox review".to_string(),
+            filename: "gearbox.step".to_string(),
+            generated_at: "2024-05-01 10:00".to_string(),
+            topology: None,
+            features: None,
+            bounding_box: None,
+            parts: Vec::new(),
+            interfaces: Vec::new(),
+            interferences: Vec::new(),
+            snapshots: Vec::new(),
+            cross_sections: Vec::new(),
+        };
+        let stackup = ToleranceCalcResult {
+            success: true,
+            error: None,
+            total_nominal: 30.0,
+            worst_case: WorstCaseResult { min: 29.7, max: 30.3, tolerance: 0.3 },
+            rss: RssResult { min: 29.83, max: 30.17, tolerance: 0.17, sigma: 3.0 },
+            monte_carlo: None,
+            contributions: Vec::new(),
+            measurement: None,
+            adjustment: None,
+            pareto: Vec::new(),
+            waterfall: Vec::new(),
+        };
+
+        let text = summary_text(&report, Some(&stackup));
+        assert!(text.starts_with("*Gearbox review*\ngearbox.step - 0 parts"));
+        assert!(text.contains("worst case 29.700 to 30.300"));
+        assert!(!summary_text(&report, None).contains("Stackup"));
+    }
+}
@@ -0,0 +1,93 @@
+// Detached viewer and report windows
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+/// Kinds of windows that can be detached from the main window
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetachedWindowKind {
+    Viewer,
+    Report,
+}
+
+impl DetachedWindowKind {
+    /// Window label (one window per kind)
+    pub fn label(&self) -> &'static str {
+        match self {
+            DetachedWindowKind::Viewer => "viewer",
+            DetachedWindowKind::Report => "report",
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            DetachedWindowKind::Viewer => "Ohmframe Copilot - 3D Viewer",
+            DetachedWindowKind::Report => "Ohmframe Copilot - Report",
+        }
+    }
+
+    fn size(&self) -> (f64, f64) {
+        match self {
+            DetachedWindowKind::Viewer => (1100.0, 800.0),
+            DetachedWindowKind::Report => (900.0, 1000.0),
+        }
+    }
+}
+
+/// Open (or focus) a detached window; it reads the shared model from the backend
+#[tauri::command]
+pub async fn open_detached_window(app: AppHandle, kind: DetachedWindowKind) -> Result<String, String> {
+    let _metrics = crate::metrics::track("open_detached_window");
+    let label = kind.label();
+
+    if let Some(window) = app.get_webview_window(label) {
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        return Ok(label.to_string());
+    }
+
+    // The frontend routes on the ?window= query parameter
+    let url = WebviewUrl::App(format!("index.html?window={}", label).into());
+    let (width, height) = kind.size();
+
+    WebviewWindowBuilder::new(&app, label, url)
+        .title(kind.title())
+        .inner_size(width, height)
+        .min_inner_size(400.0, 300.0)
+        .build()
+        .map_err(|e| format!("Failed to open {} window: {}", label, e))?;
+
+    tracing::info!(window = label, "opened detached window");
+    Ok(label.to_string())
+}
+
+/// Close a detached window if it is open
+#[tauri::command]
+pub async fn close_detached_window(app: AppHandle, kind: DetachedWindowKind) -> Result<(), String> {
+    let _metrics = crate::metrics::track("close_detached_window");
+    if let Some(window) = app.get_webview_window(kind.label()) {
+        window.close().map_err(|e| format!("Failed to close window: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Labels of the currently open windows
+#[tauri::command]
+pub fn list_open_windows(app: AppHandle) -> Vec<String> {
+    let mut labels: Vec<String> = app.webview_windows().into_keys().collect();
+    labels.sort();
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_deserializes_from_label() {
+        let kind: DetachedWindowKind = serde_json::from_str("\"viewer\"").unwrap();
+        assert_eq!(kind, DetachedWindowKind::Viewer);
+        assert_eq!(DetachedWindowKind::Report.label(), "report");
+    }
+}
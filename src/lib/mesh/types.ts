@@ -39,6 +39,7 @@ export interface StepMeshResult {
     planar_faces: number;
     curved_faces: number;
   };
+  fallback?: string; // B-rep tessellation failed and the mesh is bounding boxes
 }
 
 export interface FailedFace {